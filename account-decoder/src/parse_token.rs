@@ -19,12 +19,29 @@ pub fn spl_token_id() -> Pubkey {
     Pubkey::new_from_array(spl_token::id().to_bytes())
 }
 
+// A helper function to convert spl_token_2022::id() as spl_sdk::pubkey::Pubkey to
+// solana_sdk::pubkey::Pubkey
+pub fn spl_token_2022_id() -> Pubkey {
+    Pubkey::new_from_array(spl_token_2022::id().to_bytes())
+}
+
+// Returns true if the program is spl-token or spl-token-2022
+pub fn is_known_spl_token_id(program_id: &Pubkey) -> bool {
+    *program_id == spl_token_id() || *program_id == spl_token_2022_id()
+}
+
 // A helper function to convert spl_token::native_mint::id() as spl_sdk::pubkey::Pubkey to
 // solana_sdk::pubkey::Pubkey
 pub fn spl_token_native_mint() -> Pubkey {
     Pubkey::new_from_array(spl_token::native_mint::id().to_bytes())
 }
 
+// A helper function to convert spl_token_2022::native_mint::id() as spl_sdk::pubkey::Pubkey to
+// solana_sdk::pubkey::Pubkey
+pub fn spl_token_2022_native_mint() -> Pubkey {
+    Pubkey::new_from_array(spl_token_2022::native_mint::id().to_bytes())
+}
+
 // A helper function to convert a solana_sdk::pubkey::Pubkey to spl_sdk::pubkey::Pubkey
 pub fn spl_token_pubkey(pubkey: &Pubkey) -> SplTokenPubkey {
     SplTokenPubkey::new_from_array(pubkey.to_bytes())
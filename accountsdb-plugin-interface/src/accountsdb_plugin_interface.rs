@@ -81,8 +81,57 @@ pub struct ReplicaBlockInfo<'a> {
     pub block_height: Option<u64>,
 }
 
+/// Extends `ReplicaBlockInfo` with what is needed to place the block in its fork and to check
+/// that all its entries and transactions were received
+#[derive(Clone, Debug)]
+pub struct ReplicaBlockInfoV2<'a> {
+    pub parent_slot: u64,
+    pub parent_blockhash: &'a str,
+    pub slot: u64,
+    pub blockhash: &'a str,
+    pub rewards: &'a [Reward],
+    pub block_time: Option<UnixTimestamp>,
+    pub block_height: Option<u64>,
+
+    /// The number of transactions executed in the block, which is the number of
+    /// `notify_transaction` calls to expect for the slot
+    pub executed_transaction_count: u64,
+
+    /// The number of entries in the block, which is the number of `notify_entry` calls to
+    /// expect for the slot
+    pub entry_count: u64,
+}
+
 pub enum ReplicaBlockInfoVersions<'a> {
     V0_0_1(&'a ReplicaBlockInfo<'a>),
+    V0_0_2(&'a ReplicaBlockInfoV2<'a>),
+}
+
+/// Information about an entry of a block
+#[derive(Clone, Debug)]
+pub struct ReplicaEntryInfo<'a> {
+    /// The slot of the block the entry belongs to
+    pub slot: u64,
+
+    /// The position of the entry in the block
+    pub index: usize,
+
+    /// The number of hashes since the previous entry
+    pub num_hashes: u64,
+
+    /// The entry's hash
+    pub hash: &'a [u8],
+
+    /// The number of transactions in the entry, zero for ticks
+    pub executed_transaction_count: u64,
+}
+
+/// A wrapper to future-proof ReplicaEntryInfo handling.
+/// If there were a change to the structure of ReplicaEntryInfo,
+/// there would be new enum entry for the newer version, forcing
+/// plugin implementations to handle the change.
+pub enum ReplicaEntryInfoVersions<'a> {
+    V0_0_1(&'a ReplicaEntryInfo<'a>),
 }
 
 /// Errors returned by plugin calls
@@ -204,6 +253,13 @@ pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
         Ok(())
     }
 
+    /// Called for each entry of a block when the block is replayed, before the block's metadata
+    /// is notified.
+    #[allow(unused_variables)]
+    fn notify_entry(&mut self, entry: ReplicaEntryInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
     /// Check if the plugin is interested in account data
     /// Default is true -- if the plugin is not interested in
     /// account data, please return false.
@@ -217,4 +273,11 @@ pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
     fn transaction_notifications_enabled(&self) -> bool {
         false
     }
+
+    /// Check if the plugin is interested in entry data
+    /// Default is false -- if the plugin is interested in
+    /// entry data, return true.
+    fn entry_notifications_enabled(&self) -> bool {
+        false
+    }
 }
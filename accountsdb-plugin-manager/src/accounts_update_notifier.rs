@@ -15,11 +15,56 @@ use {
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
     },
-    std::sync::{Arc, RwLock},
+    std::{
+        sync::{Arc, Mutex, RwLock},
+        thread::sleep,
+        time::{Duration, Instant},
+    },
 };
+
+// Only sleep once the notifications are this far ahead of the configured rate, to avoid sleeping
+// after every account
+const MIN_STARTUP_THROTTLE_SLEEP: Duration = Duration::from_millis(10);
+
+/// Limits the rate at which the accounts restored from the snapshot are notified at startup
+#[derive(Debug)]
+struct StartupThrottle {
+    max_accounts_per_second: u64,
+    /// When the first account was notified
+    start: Option<Instant>,
+    notified_accounts: u64,
+}
+
+impl StartupThrottle {
+    fn new(max_accounts_per_second: u64) -> Self {
+        Self {
+            max_accounts_per_second,
+            start: None,
+            notified_accounts: 0,
+        }
+    }
+
+    /// Accounts for one more notified account, sleeping if the notifications are ahead of
+    /// `max_accounts_per_second`. Returns how long it slept.
+    fn throttle(&mut self) -> Duration {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.notified_accounts += 1;
+        let expected_elapsed = Duration::from_secs_f64(
+            self.notified_accounts as f64 / self.max_accounts_per_second as f64,
+        );
+        let ahead = expected_elapsed.saturating_sub(start.elapsed());
+        if ahead < MIN_STARTUP_THROTTLE_SLEEP {
+            return Duration::ZERO;
+        }
+        sleep(ahead);
+        ahead
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct AccountsUpdateNotifierImpl {
     plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+    startup_throttle: Option<Mutex<StartupThrottle>>,
 }
 
 impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
@@ -48,6 +93,16 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
         }
         measure_all.stop();
 
+        if let Some(startup_throttle) = &self.startup_throttle {
+            let throttled = startup_throttle.lock().unwrap().throttle();
+            if !throttled.is_zero() {
+                inc_new_counter_debug!(
+                    "accountsdb-plugin-notify-account-restore-throttled-us",
+                    throttled.as_micros() as usize
+                );
+            }
+        }
+
         inc_new_counter_debug!(
             "accountsdb-plugin-notify-account-restore-all-us",
             measure_all.as_us() as usize,
@@ -57,6 +112,18 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
     }
 
     fn notify_end_of_restore_from_snapshot(&self) {
+        if let Some(startup_throttle) = &self.startup_throttle {
+            let startup_throttle = startup_throttle.lock().unwrap();
+            info!(
+                "Notified {} accounts restored from the snapshot in {:?}",
+                startup_throttle.notified_accounts,
+                startup_throttle
+                    .start
+                    .map(|start| start.elapsed())
+                    .unwrap_or_default()
+            );
+        }
+
         let mut plugin_manager = self.plugin_manager.write().unwrap();
         if plugin_manager.plugins.is_empty() {
             return;
@@ -89,8 +156,18 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
 }
 
 impl AccountsUpdateNotifierImpl {
-    pub fn new(plugin_manager: Arc<RwLock<AccountsDbPluginManager>>) -> Self {
-        AccountsUpdateNotifierImpl { plugin_manager }
+    /// Accounts restored from the snapshot are notified at most `startup_accounts_per_second`
+    /// per second, if set
+    pub fn new(
+        plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
+        startup_accounts_per_second: Option<u64>,
+    ) -> Self {
+        AccountsUpdateNotifierImpl {
+            plugin_manager,
+            startup_throttle: startup_accounts_per_second.map(|max_accounts_per_second| {
+                Mutex::new(StartupThrottle::new(max_accounts_per_second))
+            }),
+        }
     }
 
     fn accountinfo_from_shared_account_data<'a>(
@@ -132,11 +209,19 @@ impl AccountsUpdateNotifierImpl {
     ) {
         let mut measure2 = Measure::start("accountsdb-plugin-notify_plugins_of_account_update");
         let mut plugin_manager = self.plugin_manager.write().unwrap();
+        let plugin_manager = &mut *plugin_manager;
 
         if plugin_manager.plugins.is_empty() {
             return;
         }
-        for plugin in plugin_manager.plugins.iter_mut() {
+        for (plugin, filter) in plugin_manager
+            .plugins
+            .iter_mut()
+            .zip(plugin_manager.filters.iter())
+        {
+            if !filter.wants_account(account.pubkey, account.owner) {
+                continue;
+            }
             let mut measure = Measure::start("accountsdb-plugin-update-account");
             match plugin.update_account(
                 ReplicaAccountInfoVersions::V0_0_1(&account),
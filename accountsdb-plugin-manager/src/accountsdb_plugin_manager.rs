@@ -1,5 +1,6 @@
 /// Managing the AccountsDb plugins
 use {
+    crate::plugin_filter::PluginFilter,
     libloading::{Library, Symbol},
     log::*,
     solana_accountsdb_plugin_interface::accountsdb_plugin_interface::AccountsDbPlugin,
@@ -9,6 +10,9 @@ use {
 #[derive(Default, Debug)]
 pub struct AccountsDbPluginManager {
     pub plugins: Vec<Box<dyn AccountsDbPlugin>>,
+    /// The filter of each plugin, by the plugin's position in `plugins`
+    pub filters: Vec<PluginFilter>,
+    config_files: Vec<String>,
     libs: Vec<Library>,
 }
 
@@ -16,6 +20,8 @@ impl AccountsDbPluginManager {
     pub fn new() -> Self {
         AccountsDbPluginManager {
             plugins: Vec::default(),
+            filters: Vec::default(),
+            config_files: Vec::default(),
             libs: Vec::default(),
         }
     }
@@ -28,6 +34,7 @@ impl AccountsDbPluginManager {
         &mut self,
         libpath: &str,
         config_file: &str,
+        filter: PluginFilter,
     ) -> Result<(), Box<dyn Error>> {
         type PluginConstructor = unsafe fn() -> *mut dyn AccountsDbPlugin;
         let lib = Library::new(libpath)?;
@@ -36,6 +43,8 @@ impl AccountsDbPluginManager {
         let mut plugin = Box::from_raw(plugin_raw);
         plugin.on_load(config_file)?;
         self.plugins.push(plugin);
+        self.filters.push(filter);
+        self.config_files.push(config_file.to_string());
         self.libs.push(lib);
         Ok(())
     }
@@ -47,12 +56,40 @@ impl AccountsDbPluginManager {
             info!("Unloading plugin for {:?}", plugin.name());
             plugin.on_unload();
         }
+        self.filters.clear();
+        self.config_files.clear();
 
         for lib in self.libs.drain(..) {
             drop(lib);
         }
     }
 
+    /// The names of the loaded plugins. The names are copied because a plugin's name lives in
+    /// its library, which is unloaded along with the plugin.
+    pub fn plugin_names(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .map(|plugin| plugin.name().to_string())
+            .collect()
+    }
+
+    /// Unload the plugin named `name` and its library, returning the config file the plugin
+    /// was loaded with, or `None` if no such plugin is loaded.
+    pub fn unload_plugin(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .plugins
+            .iter()
+            .position(|plugin| plugin.name() == name)?;
+        let mut plugin = self.plugins.remove(index);
+        info!("Unloading plugin for {:?}", plugin.name());
+        plugin.on_unload();
+        // The plugin's code lives in the library, so the library must outlive it
+        drop(plugin);
+        drop(self.libs.remove(index));
+        self.filters.remove(index);
+        Some(self.config_files.remove(index))
+    }
+
     /// Check if there is any plugin interested in account data
     pub fn account_data_notifications_enabled(&self) -> bool {
         for plugin in &self.plugins {
@@ -72,4 +109,14 @@ impl AccountsDbPluginManager {
         }
         false
     }
+
+    /// Check if there is any plugin interested in entry data
+    pub fn entry_notifications_enabled(&self) -> bool {
+        for plugin in &self.plugins {
+            if plugin.entry_notifications_enabled() {
+                return true;
+            }
+        }
+        false
+    }
 }
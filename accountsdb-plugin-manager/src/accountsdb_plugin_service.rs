@@ -3,7 +3,7 @@ use {
         accounts_update_notifier::AccountsUpdateNotifierImpl,
         accountsdb_plugin_manager::AccountsDbPluginManager,
         block_metadata_notifier::BlockMetadataNotifierImpl,
-        block_metadata_notifier_interface::BlockMetadataNotifierLock,
+        block_metadata_notifier_interface::BlockMetadataNotifierLock, plugin_filter::PluginFilter,
         slot_status_notifier::SlotStatusNotifierImpl, slot_status_observer::SlotStatusObserver,
         transaction_notifier::TransactionNotifierImpl,
     },
@@ -43,6 +43,12 @@ pub enum AccountsdbPluginServiceError {
 
     #[error("Cannot load plugin shared library")]
     PluginLoadError(String),
+
+    #[error("The plugin filter in the config file is invalid")]
+    InvalidPluginFilter(String),
+
+    #[error("No plugin with this name is loaded")]
+    PluginNotLoaded(String),
 }
 
 /// The service managing the AccountsDb plugin workflow.
@@ -67,10 +73,13 @@ impl AccountsDbPluginService {
     ///    shall create the implementation of `AccountsDbPlugin` and returns to the caller.
     ///    The rest of the JSON fields' definition is up to to the concrete plugin implementation
     ///    It is usually used to configure the connection information for the external data store.
+    /// * `startup_accounts_per_second` - The maximum rate at which the accounts restored from the
+    ///    snapshot are notified at startup, unlimited if `None`
 
     pub fn new(
         confirmed_bank_receiver: Receiver<BankNotification>,
         accountsdb_plugin_config_files: &[PathBuf],
+        startup_accounts_per_second: Option<u64>,
     ) -> Result<Self, AccountsdbPluginServiceError> {
         info!(
             "Starting AccountsDbPluginService from config files: {:?}",
@@ -84,13 +93,16 @@ impl AccountsDbPluginService {
         let account_data_notifications_enabled =
             plugin_manager.account_data_notifications_enabled();
         let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled();
+        let entry_notifications_enabled = plugin_manager.entry_notifications_enabled();
 
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
 
         let accounts_update_notifier: Option<AccountsUpdateNotifier> =
             if account_data_notifications_enabled {
-                let accounts_update_notifier =
-                    AccountsUpdateNotifierImpl::new(plugin_manager.clone());
+                let accounts_update_notifier = AccountsUpdateNotifierImpl::new(
+                    plugin_manager.clone(),
+                    startup_accounts_per_second,
+                );
                 Some(Arc::new(RwLock::new(accounts_update_notifier)))
             } else {
                 None
@@ -107,7 +119,10 @@ impl AccountsDbPluginService {
         let (slot_status_observer, block_metadata_notifier): (
             Option<SlotStatusObserver>,
             Option<BlockMetadataNotifierLock>,
-        ) = if account_data_notifications_enabled || transaction_notifications_enabled {
+        ) = if account_data_notifications_enabled
+            || transaction_notifications_enabled
+            || entry_notifications_enabled
+        {
             let slot_status_notifier = SlotStatusNotifierImpl::new(plugin_manager.clone());
             let slot_status_notifier = Arc::new(RwLock::new(slot_status_notifier));
             (
@@ -133,10 +148,14 @@ impl AccountsDbPluginService {
         })
     }
 
-    fn load_plugin(
+    /// Loads the plugin of `accountsdb_plugin_config_file`, returning the plugin's name.
+    ///
+    /// Plugins loaded once the validator is running only receive the kinds of notifications
+    /// that the plugins the validator started with enabled.
+    pub fn load_plugin(
         plugin_manager: &mut AccountsDbPluginManager,
         accountsdb_plugin_config_file: &Path,
-    ) -> Result<(), AccountsdbPluginServiceError> {
+    ) -> Result<String, AccountsdbPluginServiceError> {
         let mut file = match File::open(accountsdb_plugin_config_file) {
             Ok(file) => file,
             Err(err) => {
@@ -170,13 +189,19 @@ impl AccountsDbPluginService {
         let libpath = result["libpath"]
             .as_str()
             .ok_or(AccountsdbPluginServiceError::LibPathNotSet)?;
+        let filter = PluginFilter::from_config(&result).map_err(|err| {
+            AccountsdbPluginServiceError::InvalidPluginFilter(format!(
+                "Invalid filter in the config file {:?}, error: {}",
+                accountsdb_plugin_config_file, err
+            ))
+        })?;
         let config_file = accountsdb_plugin_config_file
             .as_os_str()
             .to_str()
             .ok_or(AccountsdbPluginServiceError::InvalidPluginPath)?;
 
         unsafe {
-            let result = plugin_manager.load_plugin(libpath, config_file);
+            let result = plugin_manager.load_plugin(libpath, config_file, filter);
             if let Err(err) = result {
                 let msg = format!(
                     "Failed to load the plugin library: {:?}, error: {:?}",
@@ -185,7 +210,39 @@ impl AccountsDbPluginService {
                 return Err(AccountsdbPluginServiceError::PluginLoadError(msg));
             }
         }
-        Ok(())
+        Ok(plugin_manager.plugins.last().unwrap().name().to_string())
+    }
+
+    /// Unloads the plugin named `name`
+    pub fn unload_plugin(
+        plugin_manager: &RwLock<AccountsDbPluginManager>,
+        name: &str,
+    ) -> Result<(), AccountsdbPluginServiceError> {
+        // Notifications are delivered under the write lock, so acquiring it waits for those in
+        // flight to drain
+        plugin_manager
+            .write()
+            .unwrap()
+            .unload_plugin(name)
+            .map(|_| ())
+            .ok_or_else(|| AccountsdbPluginServiceError::PluginNotLoaded(name.to_string()))
+    }
+
+    /// Unloads the plugin named `name` and loads it again from its config file, without
+    /// notifications being delivered in between
+    pub fn reload_plugin(
+        plugin_manager: &RwLock<AccountsDbPluginManager>,
+        name: &str,
+    ) -> Result<String, AccountsdbPluginServiceError> {
+        let mut plugin_manager = plugin_manager.write().unwrap();
+        let config_file = plugin_manager
+            .unload_plugin(name)
+            .ok_or_else(|| AccountsdbPluginServiceError::PluginNotLoaded(name.to_string()))?;
+        Self::load_plugin(&mut plugin_manager, Path::new(&config_file))
+    }
+
+    pub fn get_plugin_manager(&self) -> Arc<RwLock<AccountsDbPluginManager>> {
+        self.plugin_manager.clone()
     }
 
     pub fn get_accounts_update_notifier(&self) -> Option<AccountsUpdateNotifier> {
@@ -5,8 +5,9 @@ use {
     },
     log::*,
     solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
-        ReplicaBlockInfo, ReplicaBlockInfoVersions,
+        ReplicaBlockInfoV2, ReplicaBlockInfoVersions, ReplicaEntryInfo, ReplicaEntryInfoVersions,
     },
+    solana_entry::entry::Entry,
     solana_measure::measure::Measure,
     solana_metrics::*,
     solana_runtime::bank::RewardInfo,
@@ -20,14 +21,18 @@ pub(crate) struct BlockMetadataNotifierImpl {
 }
 
 impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
-    /// Notify the block metadata
+    /// Notify the entries of the block, then the block metadata
     fn notify_block_metadata(
         &self,
+        parent_slot: u64,
+        parent_blockhash: &str,
         slot: u64,
         blockhash: &str,
         rewards: &RwLock<Vec<(Pubkey, RewardInfo)>>,
         block_time: Option<UnixTimestamp>,
         block_height: Option<u64>,
+        executed_transaction_count: u64,
+        entries: &[Entry],
     ) {
         let mut plugin_manager = self.plugin_manager.write().unwrap();
         if plugin_manager.plugins.is_empty() {
@@ -36,10 +41,44 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
         let rewards = Self::build_rewards(rewards);
 
         for plugin in plugin_manager.plugins.iter_mut() {
+            if plugin.entry_notifications_enabled() {
+                let mut measure = Measure::start("accountsdb-plugin-notify-entries");
+                for (index, entry) in entries.iter().enumerate() {
+                    let entry_info = Self::build_replica_entry_info(slot, index, entry);
+                    if let Err(err) =
+                        plugin.notify_entry(ReplicaEntryInfoVersions::V0_0_1(&entry_info))
+                    {
+                        error!(
+                            "Failed to notify entry {} at slot {}, error: {} to plugin {}",
+                            index,
+                            slot,
+                            err,
+                            plugin.name()
+                        );
+                    }
+                }
+                measure.stop();
+                inc_new_counter_debug!(
+                    "accountsdb-plugin-notify-entries-us",
+                    measure.as_us() as usize,
+                    1000,
+                    1000
+                );
+            }
+
             let mut measure = Measure::start("accountsdb-plugin-update-slot");
-            let block_info =
-                Self::build_replica_block_info(slot, blockhash, &rewards, block_time, block_height);
-            let block_info = ReplicaBlockInfoVersions::V0_0_1(&block_info);
+            let block_info = Self::build_replica_block_info(
+                parent_slot,
+                parent_blockhash,
+                slot,
+                blockhash,
+                &rewards,
+                block_time,
+                block_height,
+                executed_transaction_count,
+                entries.len() as u64,
+            );
+            let block_info = ReplicaBlockInfoVersions::V0_0_2(&block_info);
             match plugin.notify_block_metadata(block_info) {
                 Err(err) => {
                     error!(
@@ -83,19 +122,38 @@ impl BlockMetadataNotifierImpl {
             .collect()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_replica_block_info<'a>(
+        parent_slot: u64,
+        parent_blockhash: &'a str,
         slot: u64,
         blockhash: &'a str,
         rewards: &'a [Reward],
         block_time: Option<UnixTimestamp>,
         block_height: Option<u64>,
-    ) -> ReplicaBlockInfo<'a> {
-        ReplicaBlockInfo {
+        executed_transaction_count: u64,
+        entry_count: u64,
+    ) -> ReplicaBlockInfoV2<'a> {
+        ReplicaBlockInfoV2 {
+            parent_slot,
+            parent_blockhash,
             slot,
             blockhash,
             rewards,
             block_time,
             block_height,
+            executed_transaction_count,
+            entry_count,
+        }
+    }
+
+    fn build_replica_entry_info(slot: u64, index: usize, entry: &Entry) -> ReplicaEntryInfo<'_> {
+        ReplicaEntryInfo {
+            slot,
+            index,
+            num_hashes: entry.num_hashes,
+            hash: entry.hash.as_ref(),
+            executed_transaction_count: entry.transactions.len() as u64,
         }
     }
 
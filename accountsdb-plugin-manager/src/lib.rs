@@ -3,6 +3,7 @@ pub mod accountsdb_plugin_manager;
 pub mod accountsdb_plugin_service;
 pub mod block_metadata_notifier;
 pub mod block_metadata_notifier_interface;
+pub mod plugin_filter;
 pub mod slot_status_notifier;
 pub mod slot_status_observer;
 pub mod transaction_notifier;
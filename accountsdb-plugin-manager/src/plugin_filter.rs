@@ -0,0 +1,97 @@
+/// Filters declared in a plugin's config file, which the plugin manager applies before
+/// invoking the plugin
+use {
+    solana_accountsdb_plugin_interface::accountsdb_plugin_interface::SlotStatus,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, str::FromStr},
+};
+
+/// The filters of a plugin, from the optional `filter` field of its config file:
+///
+/// ```text
+/// "filter": {
+///     "owners": ["TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"],
+///     "addresses": ["SysvarC1ock11111111111111111111111111111111"],
+///     "commitment": "confirmed"
+/// }
+/// ```
+///
+/// An account update is delivered if the account's owner is one of `owners` or its address is
+/// one of `addresses`; every account update is delivered if neither is set. Slot status updates
+/// are only delivered from `commitment` up, so a plugin with the `rooted` commitment is only
+/// notified of rooted slots.
+#[derive(Debug, Default)]
+pub struct PluginFilter {
+    owners: Option<HashSet<Pubkey>>,
+    addresses: Option<HashSet<Pubkey>>,
+    commitment: Option<SlotStatus>,
+}
+
+impl PluginFilter {
+    pub fn from_config(config: &serde_json::Value) -> Result<Self, String> {
+        let filter = &config["filter"];
+        if filter.is_null() {
+            return Ok(Self::default());
+        }
+        let commitment = match filter["commitment"].as_str() {
+            None => None,
+            Some("processed") => Some(SlotStatus::Processed),
+            Some("confirmed") => Some(SlotStatus::Confirmed),
+            Some("rooted") => Some(SlotStatus::Rooted),
+            Some(commitment) => return Err(format!("Invalid commitment: {}", commitment)),
+        };
+        Ok(Self {
+            owners: Self::pubkeys_from_config(filter, "owners")?,
+            addresses: Self::pubkeys_from_config(filter, "addresses")?,
+            commitment,
+        })
+    }
+
+    fn pubkeys_from_config(
+        filter: &serde_json::Value,
+        field: &str,
+    ) -> Result<Option<HashSet<Pubkey>>, String> {
+        if filter[field].is_null() {
+            return Ok(None);
+        }
+        let pubkeys = filter[field]
+            .as_array()
+            .ok_or_else(|| format!("{} must be an array of pubkeys", field))?;
+        pubkeys
+            .iter()
+            .map(|pubkey| {
+                pubkey
+                    .as_str()
+                    .and_then(|pubkey| Pubkey::from_str(pubkey).ok())
+                    .ok_or_else(|| format!("Invalid pubkey in {}: {}", field, pubkey))
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Whether the plugin should be notified of the update of the account at `pubkey` owned by
+    /// `owner`
+    pub fn wants_account(&self, pubkey: &[u8], owner: &[u8]) -> bool {
+        if self.owners.is_none() && self.addresses.is_none() {
+            return true;
+        }
+        let is_listed = |pubkeys: &Option<HashSet<Pubkey>>, pubkey: &[u8]| {
+            pubkeys
+                .as_ref()
+                .map_or(false, |pubkeys| pubkeys.contains(&Pubkey::new(pubkey)))
+        };
+        is_listed(&self.owners, owner) || is_listed(&self.addresses, pubkey)
+    }
+
+    /// Whether the plugin should be notified of slots reaching `status`
+    pub fn wants_slot_status(&self, status: &SlotStatus) -> bool {
+        let level = |status: &SlotStatus| match status {
+            SlotStatus::Processed => 0,
+            SlotStatus::Confirmed => 1,
+            SlotStatus::Rooted => 2,
+        };
+        self.commitment
+            .as_ref()
+            .map_or(true, |commitment| level(status) >= level(commitment))
+    }
+}
@@ -46,11 +46,19 @@ impl SlotStatusNotifierImpl {
 
     pub fn notify_slot_status(&self, slot: Slot, parent: Option<Slot>, slot_status: SlotStatus) {
         let mut plugin_manager = self.plugin_manager.write().unwrap();
+        let plugin_manager = &mut *plugin_manager;
         if plugin_manager.plugins.is_empty() {
             return;
         }
 
-        for plugin in plugin_manager.plugins.iter_mut() {
+        for (plugin, filter) in plugin_manager
+            .plugins
+            .iter_mut()
+            .zip(plugin_manager.filters.iter())
+        {
+            if !filter.wants_slot_status(&slot_status) {
+                continue;
+            }
             let mut measure = Measure::start("accountsdb-plugin-update-slot");
             match plugin.update_slot_status(slot, parent, slot_status.clone()) {
                 Err(err) => {
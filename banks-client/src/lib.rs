@@ -6,11 +6,16 @@
 //! cumbersome to use.
 
 pub use crate::error::BanksClientError;
-pub use solana_banks_interface::{BanksClient as TarpcClient, TransactionStatus};
+pub use solana_banks_interface::{
+    BanksClient as TarpcClient, BanksTransactionResultWithMetadata,
+    BanksTransactionResultWithSimulation, TransactionMetadata, TransactionSimulationDetails,
+    TransactionStatus,
+};
 use {
     borsh::BorshDeserialize,
-    futures::{future::join_all, Future, FutureExt, TryFutureExt},
-    solana_banks_interface::{BanksRequest, BanksResponse, BanksTransactionResultWithSimulation},
+    bytes::Bytes,
+    futures::{future::join_all, Future, FutureExt, SinkExt, StreamExt, TryFutureExt},
+    solana_banks_interface::{BanksRequest, BanksResponse},
     solana_program::{
         clock::Slot, fee_calculator::FeeCalculator, hash::Hash, program_pack::Pack, pubkey::Pubkey,
         rent::Rent, sysvar::Sysvar,
@@ -22,14 +27,24 @@ use {
         signature::Signature,
         transaction::{self, Transaction},
     },
+    std::{convert::TryFrom, sync::Arc},
     tarpc::{
         client::{self, NewClient, RequestDispatch},
         context::{self, Context},
-        serde_transport::tcp,
+        serde_transport::{self, tcp},
         ClientMessage, Response, Transport,
     },
-    tokio::{net::ToSocketAddrs, time::Duration},
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::{TcpStream, ToSocketAddrs},
+        time::Duration,
+    },
+    tokio_rustls::{
+        rustls::{ClientConfig, ServerName},
+        TlsConnector,
+    },
     tokio_serde::formats::Bincode,
+    tokio_util::codec::{Framed, LengthDelimitedCodec},
 };
 
 mod error;
@@ -136,6 +151,29 @@ impl BanksClient {
             .map_err(Into::into)
     }
 
+    pub fn simulate_transaction_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.inner
+            .simulate_transaction_with_commitment_and_context(ctx, transaction, commitment)
+            .map_err(Into::into)
+    }
+
+    pub fn process_transaction_with_metadata_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithMetadata, BanksClientError>> + '_
+    {
+        self.inner
+            .process_transaction_with_metadata_and_context(ctx, transaction)
+            .map_err(Into::into)
+    }
+
     pub fn get_account_with_commitment_and_context(
         &mut self,
         ctx: Context,
@@ -253,6 +291,40 @@ impl BanksClient {
         })
     }
 
+    /// Process a transaction against the working bank and return its result along with the logs,
+    /// compute units consumed and return data of its execution
+    pub fn process_transaction_with_metadata(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithMetadata, BanksClientError>> + '_
+    {
+        self.process_transaction_with_metadata_and_context(context::current(), transaction)
+    }
+
+    /// Simulate a transaction at the given commitment level, returning its result along with the
+    /// logs, compute units consumed, return data and inner instructions of its execution
+    pub fn simulate_transaction_with_commitment(
+        &mut self,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.simulate_transaction_with_commitment_and_context(
+            context::current(),
+            transaction,
+            commitment,
+        )
+    }
+
+    /// Simulate a transaction at the default commitment level
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = Result<BanksTransactionResultWithSimulation, BanksClientError>> + '_
+    {
+        self.simulate_transaction_with_commitment(transaction, CommitmentLevel::default())
+    }
+
     /// Send a transaction and return any preflight (sanitization or simulation) errors, or return
     /// after the transaction has been finalized or rejected.
     pub fn process_transaction_with_preflight(
@@ -465,21 +537,107 @@ pub async fn start_tcp_client<T: ToSocketAddrs>(addr: T) -> Result<BanksClient,
     })
 }
 
+/// Configuration of a client connecting to a server started with
+/// `solana_banks_server::banks_server::start_tcp_loopback_server()`
+#[derive(Clone, Default)]
+pub struct TcpClientConfig {
+    /// The token the server expects, if any
+    pub auth_token: Option<String>,
+    /// If set, the connection is made over TLS
+    pub tls: Option<TcpClientTlsConfig>,
+}
+
+#[derive(Clone)]
+pub struct TcpClientTlsConfig {
+    pub config: Arc<ClientConfig>,
+    /// The name the server's certificate is verified against
+    pub server_name: String,
+}
+
+pub async fn start_tcp_client_with_config<T: ToSocketAddrs>(
+    addr: T,
+    config: TcpClientConfig,
+) -> Result<BanksClient, BanksClientError> {
+    let stream = TcpStream::connect(addr).await?;
+    match config.tls {
+        Some(tls) => {
+            let server_name = ServerName::try_from(tls.server_name.as_str())
+                .map_err(|_| BanksClientError::ClientError("invalid TLS server name"))?;
+            let stream = TlsConnector::from(tls.config)
+                .connect(server_name, stream)
+                .await?;
+            start_framed_client(stream, config.auth_token).await
+        }
+        None => start_framed_client(stream, config.auth_token).await,
+    }
+}
+
+async fn start_framed_client<S>(
+    stream: S,
+    auth_token: Option<String>,
+) -> Result<BanksClient, BanksClientError>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    if let Some(auth_token) = auth_token {
+        framed.send(Bytes::from(auth_token)).await?;
+        match framed.next().await.transpose()? {
+            Some(response) if response.as_ref() == [1] => {}
+            _ => return Err(BanksClientError::ClientError("authentication failed")),
+        }
+    }
+    start_client(serde_transport::new(framed, Bincode::default())).await
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        solana_banks_server::banks_server::start_local_server,
+        solana_banks_server::banks_server::{
+            start_local_server, start_tcp_loopback_server, TcpLoopbackServerConfig,
+        },
         solana_runtime::{
-            bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache,
-            genesis_utils::create_genesis_config,
+            bank::Bank,
+            bank_forks::BankForks,
+            commitment::BlockCommitmentCache,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
         },
         solana_sdk::{message::Message, signature::Signer, system_instruction},
         std::sync::{Arc, RwLock},
         tarpc::transport,
         tokio::{runtime::Runtime, time::sleep},
+        tokio_rustls::rustls::{self, RootCertStore},
     };
 
+    fn new_bank_forks() -> (
+        Arc<RwLock<BankForks>>,
+        Arc<RwLock<BlockCommitmentCache>>,
+        GenesisConfigInfo,
+    ) {
+        let genesis = create_genesis_config(10);
+        let bank = Bank::new_for_tests(&genesis.genesis_config);
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        (bank_forks, block_commitment_cache, genesis)
+    }
+
+    async fn transfer(
+        banks_client: &mut BanksClient,
+        genesis: &GenesisConfigInfo,
+        to: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let mint_pubkey = genesis.mint_keypair.pubkey();
+        let instruction = system_instruction::transfer(&mint_pubkey, to, 1);
+        let message = Message::new(&[instruction], Some(&mint_pubkey));
+        let recent_blockhash = banks_client.get_latest_blockhash().await?;
+        let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+        banks_client.process_transaction(transaction).await
+    }
+
     #[test]
     fn test_banks_client_new() {
         let (client_transport, _server_transport) = transport::channel::unbounded();
@@ -566,4 +724,94 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_banks_server_tcp_loopback_auth() -> Result<(), BanksClientError> {
+        let (bank_forks, block_commitment_cache, genesis) = new_bank_forks();
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+
+        Runtime::new()?.block_on(async {
+            let addr = start_tcp_loopback_server(
+                "127.0.0.1:0".parse().unwrap(),
+                bank_forks,
+                block_commitment_cache,
+                Duration::from_millis(1),
+                TcpLoopbackServerConfig {
+                    auth_token: Some("secret".to_string()),
+                    tls_config: None,
+                },
+            )
+            .await?;
+
+            let wrong_token = TcpClientConfig {
+                auth_token: Some("wrong".to_string()),
+                tls: None,
+            };
+            assert!(matches!(
+                start_tcp_client_with_config(addr, wrong_token).await,
+                Err(BanksClientError::ClientError("authentication failed"))
+            ));
+
+            // Clients share the bank of the server
+            let config = TcpClientConfig {
+                auth_token: Some("secret".to_string()),
+                tls: None,
+            };
+            let mut banks_client = start_tcp_client_with_config(addr, config.clone()).await?;
+            transfer(&mut banks_client, &genesis, &bob_pubkey).await?;
+            let mut other_banks_client = start_tcp_client_with_config(addr, config).await?;
+            assert_eq!(other_banks_client.get_balance(bob_pubkey).await?, 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_banks_server_tcp_loopback_tls() -> Result<(), BanksClientError> {
+        let (bank_forks, block_commitment_cache, genesis) = new_bank_forks();
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+
+        let certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let certificate_der = rustls::Certificate(certificate.serialize_der().unwrap());
+        let server_config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![certificate_der.clone()],
+                rustls::PrivateKey(certificate.serialize_private_key_der()),
+            )
+            .unwrap();
+        let mut root_certificates = RootCertStore::empty();
+        root_certificates.add(&certificate_der).unwrap();
+        let client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_certificates)
+            .with_no_client_auth();
+
+        Runtime::new()?.block_on(async {
+            let addr = start_tcp_loopback_server(
+                "127.0.0.1:0".parse().unwrap(),
+                bank_forks,
+                block_commitment_cache,
+                Duration::from_millis(1),
+                TcpLoopbackServerConfig {
+                    auth_token: Some("secret".to_string()),
+                    tls_config: Some(Arc::new(server_config)),
+                },
+            )
+            .await?;
+
+            let config = TcpClientConfig {
+                auth_token: Some("secret".to_string()),
+                tls: Some(TcpClientTlsConfig {
+                    config: Arc::new(client_config),
+                    server_name: "localhost".to_string(),
+                }),
+            };
+            let mut banks_client = start_tcp_client_with_config(addr, config).await?;
+            transfer(&mut banks_client, &genesis, &bob_pubkey).await?;
+            assert_eq!(banks_client.get_balance(bob_pubkey).await?, 1);
+            Ok(())
+        })
+    }
 }
@@ -1,13 +1,15 @@
 use {
     bincode::{deserialize, serialize},
+    bytes::Bytes,
     crossbeam_channel::{unbounded, Receiver, Sender},
-    futures::{future, prelude::stream::StreamExt},
+    futures::{future, prelude::stream::StreamExt, SinkExt},
     solana_banks_interface::{
-        Banks, BanksRequest, BanksResponse, BanksTransactionResultWithSimulation,
-        TransactionConfirmationStatus, TransactionSimulationDetails, TransactionStatus,
+        Banks, BanksRequest, BanksResponse, BanksTransactionResultWithMetadata,
+        BanksTransactionResultWithSimulation, TransactionConfirmationStatus, TransactionMetadata,
+        TransactionSimulationDetails, TransactionStatus,
     },
     solana_runtime::{
-        bank::{Bank, TransactionSimulationResult},
+        bank::{Bank, TransactionExecutionResult, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::BlockCommitmentCache,
     },
@@ -27,6 +29,7 @@ use {
         send_transaction_service::{SendTransactionService, TransactionInfo},
         tpu_info::NullTpuInfo,
     },
+    solana_transaction_status::map_inner_instructions,
     std::{
         convert::TryFrom,
         io,
@@ -35,17 +38,39 @@ use {
         thread::Builder,
         time::Duration,
     },
+    subtle::ConstantTimeEq,
     tarpc::{
         context::Context,
-        serde_transport::tcp,
+        serde_transport::{self, tcp},
         server::{self, incoming::Incoming, Channel},
         transport::{self, channel::UnboundedChannel},
         ClientMessage, Response,
     },
-    tokio::time::sleep,
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::TcpListener,
+        time::{sleep, timeout},
+    },
+    tokio_rustls::{rustls::ServerConfig, TlsAcceptor},
     tokio_serde::formats::Bincode,
+    tokio_util::codec::{Framed, LengthDelimitedCodec},
 };
 
+/// How long a client of a TCP loopback server has for each of the TLS handshake and
+/// authenticating after connecting
+const AUTHENTICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration of a server started with `start_tcp_loopback_server()`
+#[derive(Clone, Default)]
+pub struct TcpLoopbackServerConfig {
+    /// If set, clients must send this token in the first frame after connecting. The server
+    /// answers with a frame holding a single byte, 1 if the token matches and 0 otherwise, and
+    /// then disconnects clients that didn't authenticate.
+    pub auth_token: Option<String>,
+    /// If set, connections are served over TLS
+    pub tls_config: Option<Arc<ServerConfig>>,
+}
+
 #[derive(Clone)]
 struct BanksServer {
     bank_forks: Arc<RwLock<BankForks>>,
@@ -161,6 +186,30 @@ fn verify_transaction(
     }
 }
 
+fn simulate_transaction(
+    bank: &Bank,
+    transaction: SanitizedTransaction,
+) -> (transaction::Result<()>, TransactionSimulationDetails) {
+    let TransactionSimulationResult {
+        result,
+        logs,
+        post_simulation_accounts: _,
+        units_consumed,
+        inner_instructions,
+        return_data,
+        invocations,
+    } = bank.simulate_transaction_unchecked(transaction);
+    let simulation_details = TransactionSimulationDetails {
+        logs,
+        units_consumed,
+        return_data,
+        inner_instructions: inner_instructions
+            .map(|inner_instructions| map_inner_instructions(inner_instructions).collect()),
+        invocations: invocations.unwrap_or_default(),
+    };
+    (result, simulation_details)
+}
+
 #[tarpc::server]
 impl Banks for BanksServer {
     async fn send_transaction_with_context(self, _: Context, transaction: Transaction) {
@@ -261,21 +310,12 @@ impl Banks for BanksServer {
                 }
                 Ok(tx) => tx,
             };
-        if let TransactionSimulationResult {
-            result: Err(err),
-            logs,
-            post_simulation_accounts: _,
-            units_consumed,
-        } = self
-            .bank(commitment)
-            .simulate_transaction_unchecked(sanitized_transaction)
-        {
+        let (result, simulation_details) =
+            simulate_transaction(&self.bank(commitment), sanitized_transaction);
+        if let Err(err) = result {
             return BanksTransactionResultWithSimulation {
                 result: Some(Err(err)),
-                simulation_details: Some(TransactionSimulationDetails {
-                    logs,
-                    units_consumed,
-                }),
+                simulation_details: Some(simulation_details),
             };
         }
         BanksTransactionResultWithSimulation {
@@ -286,6 +326,30 @@ impl Banks for BanksServer {
         }
     }
 
+    async fn simulate_transaction_with_commitment_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation {
+        let sanitized_transaction =
+            match SanitizedTransaction::try_from_legacy_transaction(transaction) {
+                Err(err) => {
+                    return BanksTransactionResultWithSimulation {
+                        result: Some(Err(err)),
+                        simulation_details: None,
+                    };
+                }
+                Ok(tx) => tx,
+            };
+        let (result, simulation_details) =
+            simulate_transaction(&self.bank(commitment), sanitized_transaction);
+        BanksTransactionResultWithSimulation {
+            result: Some(result),
+            simulation_details: Some(simulation_details),
+        }
+    }
+
     async fn process_transaction_with_commitment_and_context(
         self,
         _: Context,
@@ -314,6 +378,36 @@ impl Banks for BanksServer {
             .await
     }
 
+    async fn process_transaction_with_metadata_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+    ) -> BanksTransactionResultWithMetadata {
+        let bank = self.bank_forks.read().unwrap().working_bank();
+        if let Err(err) = verify_transaction(&transaction, &bank.feature_set) {
+            return BanksTransactionResultWithMetadata {
+                result: Err(err),
+                metadata: None,
+            };
+        }
+        match bank.process_transaction_with_metadata(&transaction) {
+            TransactionExecutionResult::NotExecuted(err) => BanksTransactionResultWithMetadata {
+                result: Err(err),
+                metadata: None,
+            },
+            TransactionExecutionResult::Executed(details) => BanksTransactionResultWithMetadata {
+                result: details.status,
+                metadata: Some(TransactionMetadata {
+                    log_messages: details.log_messages.unwrap_or_default(),
+                    compute_units_consumed: details.executed_units,
+                    instructions_compute_units_consumed: details.instructions_executed_units,
+                    return_data: details.return_data,
+                    invocations: details.invocations.unwrap_or_default(),
+                }),
+            },
+        }
+    }
+
     async fn get_account_with_commitment_and_context(
         self,
         _: Context,
@@ -416,3 +510,71 @@ pub async fn start_tcp_server(
     server.await;
     Ok(())
 }
+
+/// Serve `bank_forks` over TCP, processing transactions against the working bank like
+/// `start_local_server()`, so that test environments can share an in-memory bank with other
+/// processes. Returns the address the server listens on.
+pub async fn start_tcp_loopback_server(
+    listen_addr: SocketAddr,
+    bank_forks: Arc<RwLock<BankForks>>,
+    block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+    poll_signature_status_sleep_duration: Duration,
+    config: TcpLoopbackServerConfig,
+) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(listen_addr).await?;
+    let local_addr = listener.local_addr()?;
+    let banks_server = BanksServer::new_loopback(
+        bank_forks,
+        block_commitment_cache,
+        poll_signature_status_sleep_duration,
+    );
+    let tls_acceptor = config.tls_config.map(TlsAcceptor::from);
+    let auth_token = config.auth_token;
+    tokio::spawn(async move {
+        loop {
+            // Ignore accept errors
+            let stream = match listener.accept().await {
+                Ok((stream, _peer_addr)) => stream,
+                Err(_) => continue,
+            };
+            let banks_server = banks_server.clone();
+            let tls_acceptor = tls_acceptor.clone();
+            let auth_token = auth_token.clone();
+            tokio::spawn(async move {
+                match tls_acceptor {
+                    Some(tls_acceptor) => {
+                        // Don't let a client that stalls the handshake hold the connection open
+                        if let Ok(Ok(stream)) =
+                            timeout(AUTHENTICATION_TIMEOUT, tls_acceptor.accept(stream)).await
+                        {
+                            serve_connection(stream, banks_server, auth_token).await;
+                        }
+                    }
+                    None => serve_connection(stream, banks_server, auth_token).await,
+                }
+            });
+        }
+    });
+    Ok(local_addr)
+}
+
+async fn serve_connection<S>(stream: S, banks_server: BanksServer, auth_token: Option<String>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    if let Some(auth_token) = auth_token {
+        let authenticated = matches!(
+            timeout(AUTHENTICATION_TIMEOUT, framed.next()).await,
+            Ok(Some(Ok(token))) if bool::from(token.as_ref().ct_eq(auth_token.as_bytes()))
+        );
+        let response = Bytes::from_static(if authenticated { &[1] } else { &[0] });
+        if framed.send(response).await.is_err() || !authenticated {
+            return;
+        }
+    }
+    let transport = serde_transport::new(framed, Bincode::default());
+    server::BaseChannel::with_defaults(transport)
+        .execute(banks_server.serve())
+        .await;
+}
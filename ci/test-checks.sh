@@ -69,6 +69,16 @@ _ ci/order-crates-for-publishing.py
 # run nightly clippy for `sdk/` as there's a moderate amount of nightly-only code there
 _ "$cargo" nightly clippy -Zunstable-options --workspace --all-targets -- --deny=warnings --deny=clippy::integer_arithmetic
 
+# the zk-token-sdk pod types must keep building without `std`; only the rlib is checked since
+# the cdylib needs an allocator and a panic handler
+_ "$cargo" stable rustc -p solana-zk-token-sdk --lib --no-default-features --crate-type rlib --profile check
+# and the verifiers must keep building without the `prover` feature
+_ "$cargo" stable check -p solana-zk-token-sdk --no-default-features --features std
+
+# borsh only derives its traits for arrays of `Default` elements, so check the proof data with it
+_ "$cargo" stable check -p solana-zk-token-sdk --features borsh-traits
+_ "$cargo" stable check -p solana-zk-token-sdk --features serde-traits,borsh-traits
+
 _ "$cargo" stable fmt --all -- --check
 
 _ ci/do-audit.sh
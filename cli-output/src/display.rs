@@ -4,10 +4,16 @@ use {
     console::style,
     indicatif::{ProgressBar, ProgressStyle},
     solana_sdk::{
-        clock::UnixTimestamp, hash::Hash, message::Message, native_token::lamports_to_sol,
-        program_utils::limited_deserialize, pubkey::Pubkey, stake, transaction::Transaction,
+        clock::UnixTimestamp,
+        hash::Hash,
+        message::{AccountKeys, Message},
+        native_token::lamports_to_sol,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        stake,
+        transaction::Transaction,
     },
-    solana_transaction_status::UiTransactionStatusMeta,
+    solana_transaction_status::{parse_instruction::parse_registered, UiTransactionStatusMeta},
     spl_memo::{id as spl_memo_id, v1::id as spl_memo_v1_id},
     std::{collections::HashMap, fmt, io},
 };
@@ -263,6 +269,17 @@ pub fn write_transaction<W: io::Write>(
                 writeln!(w, "{}  Data: \"{}\"", prefix, s)?;
                 raw = false;
             }
+        } else if let Ok(parsed_instruction) = parse_registered(
+            &program_pubkey,
+            instruction,
+            &AccountKeys::new(&message.account_keys, None),
+        ) {
+            writeln!(
+                w,
+                "{}  {}: {}",
+                prefix, parsed_instruction.program, parsed_instruction.parsed
+            )?;
+            raw = false;
         }
 
         if raw {
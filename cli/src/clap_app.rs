@@ -90,6 +90,15 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
                 .global(true)
                 .help("Do not use address labels in the output"),
         )
+        .arg(
+            Arg::with_name("instruction_parser_config")
+                .long("instruction-parser-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .global(true)
+                .help("JSON file describing the instructions of additional programs, \
+                       which are then decoded when displaying transactions"),
+        )
         .arg(
             Arg::with_name("output_format")
                 .long("output")
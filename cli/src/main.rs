@@ -14,7 +14,14 @@ use {
     solana_cli_output::{display::println_name_value, OutputFormat},
     solana_client::rpc_config::RpcSendTransactionConfig,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
-    std::{collections::HashMap, error, path::PathBuf, sync::Arc, time::Duration},
+    solana_transaction_status::parse_configured::register_configured_instruction_parsers,
+    std::{
+        collections::HashMap,
+        error,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    },
 };
 
 pub fn println_name_value_or(name: &str, value: &str, setting_type: SettingType) {
@@ -198,6 +205,10 @@ pub fn parse_args<'a>(
         }
     }
 
+    if let Some(instruction_parser_config) = matches.value_of("instruction_parser_config") {
+        register_configured_instruction_parsers(Path::new(instruction_parser_config))?;
+    }
+
     let verbose = matches.is_present("verbose");
     let output_format = OutputFormat::from_matches(matches, "output_format", verbose);
 
@@ -226,6 +226,8 @@ impl RpcSender for MockSender {
                             pre_token_balances: None,
                             post_token_balances: None,
                             rewards: None,
+                            return_data: None,
+                            compute_units_consumed: None,
                         }),
                 },
                 block_time: Some(1628633791),
@@ -337,6 +339,8 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    return_data: None,
+                    inner_instructions: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -42,6 +42,8 @@ pub struct RpcSimulateTransactionConfig {
     pub commitment: Option<CommitmentConfig>,
     pub encoding: Option<UiTransactionEncoding>,
     pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
+    #[serde(default)]
+    pub inner_instructions: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -209,6 +211,28 @@ pub struct RpcSignaturesForAddressConfig {
     pub commitment: Option<CommitmentConfig>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcSignatureOrder {
+    Ascending,
+    Descending,
+}
+
+impl Default for RpcSignatureOrder {
+    fn default() -> Self {
+        Self::Descending
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignaturesForAddressInRangeConfig {
+    pub start_slot: Option<Slot>,
+    pub end_slot: Option<Slot>,
+    pub limit: Option<usize>,
+    pub order: Option<RpcSignatureOrder>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcEncodingConfigWrapper<T> {
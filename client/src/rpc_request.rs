@@ -86,6 +86,7 @@ pub enum RpcRequest {
     )]
     GetSnapshotSlot,
     GetSignaturesForAddress,
+    GetSignaturesForAddressInRange,
     GetSignatureStatuses,
     GetSlot,
     GetSlotLeader,
@@ -159,6 +160,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
             RpcRequest::GetSnapshotSlot => "getSnapshotSlot",
             RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
+            RpcRequest::GetSignaturesForAddressInRange => "getSignaturesForAddressInRange",
             RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
             RpcRequest::GetSlot => "getSlot",
             RpcRequest::GetSlotLeader => "getSlotLeader",
@@ -1813,6 +1813,10 @@ mod tests {
             log_messages: None,
             inner_instructions: None,
             durable_nonce_fee: None,
+            return_data: None,
+            executed_units: 0,
+            instructions_executed_units: vec![],
+            invocations: None,
         })
     }
 
@@ -2993,6 +2997,7 @@ mod tests {
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
                 true,
+                false,
                 None,
                 blockstore.clone(),
                 &Arc::new(AtomicBool::new(false)),
@@ -3155,6 +3160,7 @@ mod tests {
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
                 true,
+                false,
                 None,
                 blockstore.clone(),
                 &Arc::new(AtomicBool::new(false)),
@@ -3191,6 +3197,7 @@ mod tests {
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
                     loaded_addresses: sanitized_tx.get_loaded_addresses(),
+                    compute_units_consumed: Some(0),
                     ..TransactionStatusMeta::default()
                 })
             );
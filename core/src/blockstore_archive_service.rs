@@ -0,0 +1,74 @@
+//! The `blockstore_archive_service` moves the shreds of old rooted slots out of the blockstore
+//! and into its archive
+
+use {
+    solana_ledger::{blockstore::Blockstore, blockstore_archive::BlockstoreArchiveConfig},
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(60);
+const SLEEP_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct BlockstoreArchiveService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl BlockstoreArchiveService {
+    pub fn new(
+        blockstore: &Arc<Blockstore>,
+        config: &BlockstoreArchiveConfig,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let blockstore = blockstore.clone();
+        let min_slot_age = config.min_slot_age;
+        let slots_per_pack = config.slots_per_pack;
+        let exit = exit.clone();
+
+        info!(
+            "Starting BlockstoreArchive service: min slot age {}, {} slots per pack",
+            min_slot_age, slots_per_pack
+        );
+        let thread_hdl = Builder::new()
+            .name("blockstore-archive".to_string())
+            .spawn(move || {
+                let mut last_archive: Option<Instant> = None;
+                while !exit.load(Ordering::Relaxed) {
+                    if last_archive.map_or(false, |last_archive| {
+                        last_archive.elapsed() < ARCHIVE_INTERVAL
+                    }) {
+                        sleep(SLEEP_INTERVAL);
+                        continue;
+                    }
+                    last_archive = Some(Instant::now());
+                    let max_slot = blockstore.last_root().saturating_sub(min_slot_age);
+                    // Catch up one pack at a time so that exiting is never held up for long
+                    while !exit.load(Ordering::Relaxed) {
+                        match blockstore.archive_next_pack(max_slot, slots_per_pack) {
+                            Ok(Some((first_slot, last_slot))) => {
+                                info!("Archived slots {} to {}", first_slot, last_slot)
+                            }
+                            Ok(None) => break,
+                            Err(err) => {
+                                error!("Failed to archive slots: {:?}", err);
+                                break;
+                            }
+                        }
+                    }
+                }
+            })
+            .unwrap();
+
+        Self { thread_hdl }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
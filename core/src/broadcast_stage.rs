@@ -11,11 +11,15 @@ use {
     crate::{
         cluster_nodes::{ClusterNodes, ClusterNodesCache},
         result::{Error, Result},
+        serve_repair::LeaderSlotRepairs,
     },
     crossbeam_channel::{unbounded, Receiver, RecvError, RecvTimeoutError, Sender},
     itertools::Itertools,
     solana_gossip::cluster_info::{ClusterInfo, ClusterInfoError, DATA_PLANE_FANOUT},
-    solana_ledger::{blockstore::Blockstore, shred::Shred},
+    solana_ledger::{
+        blockstore::Blockstore,
+        shred::{Shred, MAX_CODING_SHREDS_PER_FEC_BLOCK},
+    },
     solana_measure::measure::Measure,
     solana_metrics::{inc_new_counter_error, inc_new_counter_info},
     solana_poh::poh_recorder::WorkingBankEntry,
@@ -84,6 +88,8 @@ impl BroadcastStageType {
         blockstore: &Arc<Blockstore>,
         bank_forks: &Arc<RwLock<BankForks>>,
         shred_version: u16,
+        broadcast_config: BroadcastConfig,
+        leader_slot_repairs: Arc<LeaderSlotRepairs>,
     ) -> BroadcastStage {
         match self {
             BroadcastStageType::Standard => BroadcastStage::new(
@@ -94,7 +100,11 @@ impl BroadcastStageType {
                 exit_sender,
                 blockstore,
                 bank_forks,
-                StandardBroadcastRun::new(shred_version),
+                StandardBroadcastRun::new_with_config(
+                    shred_version,
+                    broadcast_config,
+                    leader_slot_repairs,
+                ),
             ),
 
             BroadcastStageType::FailEntryVerification => BroadcastStage::new(
@@ -133,6 +143,29 @@ impl BroadcastStageType {
     }
 }
 
+/// Tunables of the standard broadcast run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BroadcastConfig {
+    /// Number of coding shreds generated per MAX_DATA_SHREDS_PER_FEC_BLOCK
+    /// data shreds, at most MAX_CODING_SHREDS_PER_FEC_BLOCK
+    pub coding_shreds_per_fec_block: u32,
+    /// Raise the coding rate towards MAX_CODING_SHREDS_PER_FEC_BLOCK as
+    /// downstream peers request more repairs of this node's leader slots
+    pub adaptive_fec: bool,
+    /// Maximum number of entries shredded together in one batch
+    pub max_entries_per_batch: usize,
+}
+
+impl Default for BroadcastConfig {
+    fn default() -> Self {
+        Self {
+            coding_shreds_per_fec_block: MAX_CODING_SHREDS_PER_FEC_BLOCK,
+            adaptive_fec: false,
+            max_entries_per_batch: broadcast_utils::RECEIVE_ENTRY_COUNT_THRESHOLD,
+        }
+    }
+}
+
 trait BroadcastRun {
     fn run(
         &mut self,
@@ -458,7 +491,10 @@ pub mod test {
             blockstore::{make_slot_entries, Blockstore},
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
             get_tmp_ledger_path,
-            shred::{max_ticks_per_n_shreds, ProcessShredsStats, Shredder},
+            shred::{
+                max_ticks_per_n_shreds, ProcessShredsStats, Shredder,
+                MAX_CODING_SHREDS_PER_FEC_BLOCK,
+            },
         },
         solana_runtime::bank::Bank,
         solana_sdk::{
@@ -492,6 +528,7 @@ pub mod test {
             &data_shreds[0..],
             true, // is_last_in_slot
             0,    // next_code_index
+            MAX_CODING_SHREDS_PER_FEC_BLOCK,
             &mut ProcessShredsStats::default(),
         )
         .unwrap();
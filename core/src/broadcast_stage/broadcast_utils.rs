@@ -34,9 +34,17 @@ pub struct UnfinishedSlotInfo {
 /// This parameter tunes how many entries are received in one iteration of recv loop
 /// This will prevent broadcast stage from consuming more entries, that could have led
 /// to delays in shredding, and broadcasting shreds to peer validators
-const RECEIVE_ENTRY_COUNT_THRESHOLD: usize = 8;
+pub const RECEIVE_ENTRY_COUNT_THRESHOLD: usize = 8;
 
 pub(super) fn recv_slot_entries(receiver: &Receiver<WorkingBankEntry>) -> Result<ReceiveResults> {
+    recv_slot_entries_with_limit(receiver, RECEIVE_ENTRY_COUNT_THRESHOLD)
+}
+
+/// Like `recv_slot_entries`, but receives up to `max_entries` entries per call
+pub(super) fn recv_slot_entries_with_limit(
+    receiver: &Receiver<WorkingBankEntry>,
+    max_entries: usize,
+) -> Result<ReceiveResults> {
     let timer = Duration::new(1, 0);
     let recv_start = Instant::now();
     let (mut bank, (entry, mut last_tick_height)) = receiver.recv_timeout(timer)?;
@@ -61,7 +69,7 @@ pub(super) fn recv_slot_entries(receiver: &Receiver<WorkingBankEntry>) -> Result
             last_tick_height = tick_height;
             entries.push(entry);
 
-            if entries.len() >= RECEIVE_ENTRY_COUNT_THRESHOLD {
+            if entries.len() >= max_entries {
                 break;
             }
 
@@ -10,8 +10,8 @@ use {
     },
     solana_entry::entry::Entry,
     solana_ledger::shred::{
-        ProcessShredsStats, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
-        SHRED_TICK_REFERENCE_MASK,
+        ProcessShredsStats, Shred, Shredder, MAX_CODING_SHREDS_PER_FEC_BLOCK,
+        MAX_DATA_SHREDS_PER_FEC_BLOCK, SHRED_TICK_REFERENCE_MASK,
     },
     solana_sdk::{
         signature::Keypair,
@@ -20,6 +20,76 @@ use {
     std::{sync::RwLock, time::Duration},
 };
 
+// With adaptive FEC, the coding rate reaches MAX_CODING_SHREDS_PER_FEC_BLOCK
+// once peers request repairs for this percentage of the shreds broadcast.
+// Only repairs of this node's own leader slots are counted.
+const REPAIR_PERCENT_FOR_MAX_CODING_RATE: u64 = 5;
+
+// Adjusts the number of coding shreds per FEC block to the repair requests
+// downstream peers send for the shreds of this node's leader slots.
+#[derive(Clone)]
+struct FecRateController {
+    config: BroadcastConfig,
+    leader_slot_repairs: Arc<LeaderSlotRepairs>,
+    last_repair_requests: u64,
+    coding_shreds_per_fec_block: u32,
+}
+
+impl FecRateController {
+    fn new(config: BroadcastConfig, leader_slot_repairs: Arc<LeaderSlotRepairs>) -> Self {
+        let coding_shreds_per_fec_block = config
+            .coding_shreds_per_fec_block
+            .clamp(1, MAX_CODING_SHREDS_PER_FEC_BLOCK);
+        Self {
+            config,
+            last_repair_requests: leader_slot_repairs.num_requests(),
+            leader_slot_repairs,
+            coding_shreds_per_fec_block,
+        }
+    }
+
+    // Called when this node starts broadcasting a leader slot.
+    fn start_slot(&self, slot: Slot) {
+        self.leader_slot_repairs.add_slot(slot);
+    }
+
+    fn coding_shreds_per_fec_block(&self) -> u32 {
+        self.coding_shreds_per_fec_block
+    }
+
+    // Called once a slot of num_data_shreds data shreds is fully broadcast.
+    fn update(&mut self, slot: Slot, num_data_shreds: u32) {
+        let repair_requests = self.leader_slot_repairs.num_requests();
+        let num_repairs = repair_requests.saturating_sub(self.last_repair_requests);
+        self.last_repair_requests = repair_requests;
+        if !self.config.adaptive_fec || num_data_shreds == 0 {
+            return;
+        }
+        let base_rate = self
+            .config
+            .coding_shreds_per_fec_block
+            .clamp(1, MAX_CODING_SHREDS_PER_FEC_BLOCK);
+        let num_data_shreds = u64::from(num_data_shreds);
+        let scaled_repairs = num_repairs
+            .saturating_mul(100 / REPAIR_PERCENT_FOR_MAX_CODING_RATE)
+            .min(num_data_shreds);
+        let headroom = u64::from(MAX_CODING_SHREDS_PER_FEC_BLOCK - base_rate);
+        self.coding_shreds_per_fec_block =
+            base_rate + (headroom * scaled_repairs / num_data_shreds) as u32;
+        datapoint_info!(
+            "broadcast-fec-rate",
+            ("slot", slot, i64),
+            ("num_data_shreds", num_data_shreds, i64),
+            ("num_repairs", num_repairs, i64),
+            (
+                "coding_shreds_per_fec_block",
+                self.coding_shreds_per_fec_block,
+                i64
+            ),
+        );
+    }
+}
+
 #[derive(Clone)]
 pub struct StandardBroadcastRun {
     process_shreds_stats: ProcessShredsStats,
@@ -32,10 +102,20 @@ pub struct StandardBroadcastRun {
     last_datapoint_submit: Arc<AtomicInterval>,
     num_batches: usize,
     cluster_nodes_cache: Arc<ClusterNodesCache<BroadcastStage>>,
+    max_entries_per_batch: usize,
+    fec_rate: FecRateController,
 }
 
 impl StandardBroadcastRun {
     pub(super) fn new(shred_version: u16) -> Self {
+        Self::new_with_config(shred_version, BroadcastConfig::default(), Arc::default())
+    }
+
+    pub(super) fn new_with_config(
+        shred_version: u16,
+        config: BroadcastConfig,
+        leader_slot_repairs: Arc<LeaderSlotRepairs>,
+    ) -> Self {
         let cluster_nodes_cache = Arc::new(ClusterNodesCache::<BroadcastStage>::new(
             CLUSTER_NODES_CACHE_NUM_EPOCH_CAP,
             CLUSTER_NODES_CACHE_TTL,
@@ -51,6 +131,8 @@ impl StandardBroadcastRun {
             last_datapoint_submit: Arc::default(),
             num_batches: 0,
             cluster_nodes_cache,
+            max_entries_per_batch: config.max_entries_per_batch.max(1),
+            fec_rate: FecRateController::new(config, leader_slot_repairs),
         }
     }
 
@@ -89,6 +171,7 @@ impl StandardBroadcastRun {
                     keypair,
                     &mut self.unfinished_slot,
                     true, // is_last_in_slot
+                    self.fec_rate.coding_shreds_per_fec_block(),
                     stats,
                 );
                 shreds.insert(0, shred);
@@ -205,6 +288,7 @@ impl StandardBroadcastRun {
             let parent_slot = bank.parent_slot();
 
             self.current_slot_and_parent = Some((slot, parent_slot));
+            self.fec_rate.start_slot(slot);
             receive_elapsed = Duration::new(0, 0);
         }
 
@@ -288,6 +372,7 @@ impl StandardBroadcastRun {
             keypair,
             &mut self.unfinished_slot,
             is_last_in_slot,
+            self.fec_rate.coding_shreds_per_fec_block(),
             &mut process_stats,
         );
         let coding_shreds = Arc::new(coding_shreds);
@@ -308,6 +393,10 @@ impl StandardBroadcastRun {
 
         if last_tick_height == bank.max_tick_height() {
             self.report_and_reset_stats(false);
+            if let Some(unfinished_slot) = &self.unfinished_slot {
+                self.fec_rate
+                    .update(unfinished_slot.slot, unfinished_slot.next_shred_index);
+            }
             self.unfinished_slot = None;
         }
 
@@ -452,6 +541,7 @@ fn make_coding_shreds(
     keypair: &Keypair,
     unfinished_slot: &mut Option<UnfinishedSlotInfo>,
     is_slot_end: bool,
+    coding_shreds_per_fec_block: u32,
     stats: &mut ProcessShredsStats,
 ) -> Vec<Shred> {
     let unfinished_slot = match unfinished_slot {
@@ -471,11 +561,13 @@ fn make_coding_shreds(
             .drain(0..size - offset)
             .collect()
     };
+    let next_code_index = unfinished_slot.next_code_index;
     let shreds = Shredder::data_shreds_to_coding_shreds(
         keypair,
         &data_shreds,
         is_slot_end,
-        unfinished_slot.next_code_index,
+        next_code_index,
+        coding_shreds_per_fec_block,
         stats,
     )
     .unwrap();
@@ -487,6 +579,11 @@ fn make_coding_shreds(
     {
         unfinished_slot.next_code_index = unfinished_slot.next_code_index.max(index + 1);
     }
+    // Coding shreds are indexed from their FEC set index, so skip the indices
+    // left unused when generating fewer coding shreds than data shreds.
+    unfinished_slot.next_code_index = unfinished_slot
+        .next_code_index
+        .max(next_code_index + data_shreds.len() as u32);
     shreds
 }
 
@@ -499,7 +596,8 @@ impl BroadcastRun for StandardBroadcastRun {
         socket_sender: &Sender<(Arc<Vec<Shred>>, Option<BroadcastShredBatchInfo>)>,
         blockstore_sender: &Sender<(Arc<Vec<Shred>>, Option<BroadcastShredBatchInfo>)>,
     ) -> Result<()> {
-        let receive_results = broadcast_utils::recv_slot_entries(receiver)?;
+        let receive_results =
+            broadcast_utils::recv_slot_entries_with_limit(receiver, self.max_entries_per_batch)?;
         // TODO: Confirm that last chunk of coding shreds
         // will not be lost or delayed for too long.
         self.process_receive_results(
@@ -837,4 +935,61 @@ mod test {
             .unwrap();
         assert!(standard_broadcast_run.unfinished_slot.is_none())
     }
+
+    #[test]
+    fn test_fec_rate_controller() {
+        let leader_slot_repairs = Arc::new(LeaderSlotRepairs::default());
+        let record_repairs = |slot, num_repairs| {
+            for _ in 0..num_repairs {
+                leader_slot_repairs.record_request(slot);
+            }
+        };
+        let config = BroadcastConfig {
+            coding_shreds_per_fec_block: 8,
+            adaptive_fec: true,
+            ..BroadcastConfig::default()
+        };
+        let mut fec_rate = FecRateController::new(config, leader_slot_repairs.clone());
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 8);
+
+        // No repairs keeps the configured rate
+        fec_rate.start_slot(0);
+        fec_rate.update(0, 1000);
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 8);
+
+        // Repairs of slots produced by other leaders are ignored
+        record_repairs(7, 500);
+        fec_rate.start_slot(1);
+        fec_rate.update(1, 1000);
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 8);
+
+        // Repairs for half of the threshold raise the rate half way
+        record_repairs(0, 25);
+        fec_rate.update(1, 1000);
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 20);
+
+        // Repairs beyond the threshold max out the rate
+        record_repairs(1, 500);
+        fec_rate.update(2, 1000);
+        assert_eq!(
+            fec_rate.coding_shreds_per_fec_block(),
+            MAX_CODING_SHREDS_PER_FEC_BLOCK
+        );
+
+        // And the rate falls back once repairs stop
+        fec_rate.update(3, 1000);
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 8);
+
+        // Without adaptive FEC the configured rate is used regardless of repairs
+        let mut fec_rate = FecRateController::new(
+            BroadcastConfig {
+                adaptive_fec: false,
+                ..config
+            },
+            leader_slot_repairs.clone(),
+        );
+        record_repairs(1, 500);
+        fec_rate.update(4, 1000);
+        assert_eq!(fec_rate.coding_shreds_per_fec_block(), 8);
+    }
 }
@@ -8,8 +8,10 @@ use {
         blockstore_db::Result as BlockstoreResult,
     },
     solana_measure::measure::Measure,
-    solana_sdk::clock::{Slot, DEFAULT_TICKS_PER_SLOT, TICKS_PER_DAY},
+    solana_sdk::clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SLOT, TICKS_PER_DAY},
     std::{
+        convert::TryFrom,
+        fmt,
         string::ToString,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -41,6 +43,29 @@ pub const DEFAULT_PURGE_SLOT_INTERVAL: u64 = 512;
 // Once a day should be ample
 const DEFAULT_COMPACTION_SLOT_INTERVAL: u64 = TICKS_PER_DAY / DEFAULT_TICKS_PER_SLOT;
 
+/// How much ledger history the LedgerCleanupService keeps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LedgerRetention {
+    /// Keep roughly this many shreds of the newest slots
+    Shreds(u64),
+    /// Keep the slots whose block time is within this duration of the newest
+    /// cached block time. Requires block times to be cached in the blockstore.
+    Time(Duration),
+}
+
+impl fmt::Display for LedgerRetention {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LedgerRetention::Shreds(max_ledger_shreds) => {
+                write!(f, "max ledger shreds={}", max_ledger_shreds)
+            }
+            LedgerRetention::Time(max_ledger_age) => {
+                write!(f, "max ledger age={}s", max_ledger_age.as_secs())
+            }
+        }
+    }
+}
+
 pub struct LedgerCleanupService {
     t_cleanup: JoinHandle<()>,
     t_compact: JoinHandle<()>,
@@ -50,7 +75,8 @@ impl LedgerCleanupService {
     pub fn new(
         new_root_receiver: Receiver<Slot>,
         blockstore: Arc<Blockstore>,
-        max_ledger_shreds: u64,
+        retention: LedgerRetention,
+        dry_run: bool,
         exit: &Arc<AtomicBool>,
         compaction_interval: Option<u64>,
         max_compaction_jitter: Option<u64>,
@@ -64,8 +90,8 @@ impl LedgerCleanupService {
         let last_compact_slot2 = last_compact_slot.clone();
 
         info!(
-            "LedgerCleanupService active. max ledger shreds={}, compaction interval={}",
-            max_ledger_shreds, compaction_interval,
+            "LedgerCleanupService active. {}, compaction interval={}, dry run={}",
+            retention, compaction_interval, dry_run,
         );
 
         let exit_compact = exit.clone();
@@ -80,7 +106,8 @@ impl LedgerCleanupService {
                 if let Err(e) = Self::cleanup_ledger(
                     &new_root_receiver,
                     &blockstore,
-                    max_ledger_shreds,
+                    retention,
+                    dry_run,
                     &mut last_purge_slot,
                     DEFAULT_PURGE_SLOT_INTERVAL,
                     &last_compact_slot,
@@ -118,6 +145,21 @@ impl LedgerCleanupService {
     }
 
     fn find_slots_to_clean(
+        blockstore: &Arc<Blockstore>,
+        root: Slot,
+        retention: LedgerRetention,
+    ) -> (bool, Slot, Slot, u64) {
+        match retention {
+            LedgerRetention::Shreds(max_ledger_shreds) => {
+                Self::find_slots_to_clean_by_shreds(blockstore, root, max_ledger_shreds)
+            }
+            LedgerRetention::Time(max_ledger_age) => {
+                Self::find_slots_to_clean_by_time(blockstore, root, max_ledger_age)
+            }
+        }
+    }
+
+    fn find_slots_to_clean_by_shreds(
         blockstore: &Arc<Blockstore>,
         root: Slot,
         max_ledger_shreds: u64,
@@ -163,6 +205,60 @@ impl LedgerCleanupService {
         (true, first_slot, lowest_cleanup_slot, total_shreds)
     }
 
+    fn find_slots_to_clean_by_time(
+        blockstore: &Arc<Blockstore>,
+        root: Slot,
+        max_ledger_age: Duration,
+    ) -> (bool, Slot, Slot, u64) {
+        let mut iterate_time = Measure::start("iterate_time");
+        let mut total_shreds = 0;
+        let mut first_slot = None;
+        for (slot, meta) in blockstore.slot_meta_iterator(0).unwrap() {
+            if slot > root {
+                break;
+            }
+            first_slot.get_or_insert(slot);
+            total_shreds += meta.received;
+        }
+        let block_times: Vec<_> = blockstore
+            .block_time_iterator(first_slot.unwrap_or_default())
+            .unwrap()
+            .take_while(|(slot, _)| *slot <= root)
+            .collect();
+        iterate_time.stop();
+        info!(
+            "first_slot={:?} total_shreds={} block_times={} max_ledger_age={}s, {}",
+            first_slot,
+            total_shreds,
+            block_times.len(),
+            max_ledger_age.as_secs(),
+            iterate_time
+        );
+
+        let (first_slot, newest_block_time) = match (first_slot, block_times.last()) {
+            (Some(first_slot), Some((_, block_time))) => (first_slot, *block_time),
+            _ => {
+                warn!("purge: no cached block times up to root {}", root);
+                return (false, 0, 0, total_shreds);
+            }
+        };
+        let max_ledger_age =
+            UnixTimestamp::try_from(max_ledger_age.as_secs()).unwrap_or(UnixTimestamp::MAX);
+        let oldest_block_time_to_keep = newest_block_time.saturating_sub(max_ledger_age);
+        // Block times of rooted slots don't go backwards, so every slot up to the
+        // last one older than the retention window can be cleaned
+        let lowest_cleanup_slot = block_times
+            .iter()
+            .take_while(|(_, block_time)| *block_time < oldest_block_time_to_keep)
+            .last()
+            .map(|(slot, _)| *slot);
+
+        match lowest_cleanup_slot {
+            Some(lowest_cleanup_slot) => (true, first_slot, lowest_cleanup_slot, total_shreds),
+            None => (false, 0, 0, total_shreds),
+        }
+    }
+
     fn receive_new_roots(new_root_receiver: &Receiver<Slot>) -> Result<Slot, RecvTimeoutError> {
         let root = new_root_receiver.recv_timeout(Duration::from_secs(1))?;
         // Get the newest root
@@ -172,7 +268,8 @@ impl LedgerCleanupService {
     pub fn cleanup_ledger(
         new_root_receiver: &Receiver<Slot>,
         blockstore: &Arc<Blockstore>,
-        max_ledger_shreds: u64,
+        retention: LedgerRetention,
+        dry_run: bool,
         last_purge_slot: &mut u64,
         purge_interval: u64,
         last_compact_slot: &Arc<AtomicU64>,
@@ -191,9 +288,20 @@ impl LedgerCleanupService {
         *last_purge_slot = root;
 
         let (slots_to_clean, purge_first_slot, lowest_cleanup_slot, total_shreds) =
-            Self::find_slots_to_clean(blockstore, root, max_ledger_shreds);
+            Self::find_slots_to_clean(blockstore, root, retention);
 
-        if slots_to_clean {
+        if slots_to_clean && dry_run {
+            info!(
+                "purge dry run: would purge data from slots {} to {}",
+                purge_first_slot, lowest_cleanup_slot
+            );
+            datapoint_info!(
+                "ledger_cleanup_dry_run",
+                ("purge_first_slot", purge_first_slot, i64),
+                ("lowest_cleanup_slot", lowest_cleanup_slot, i64),
+                ("total_shreds", total_shreds, i64),
+            );
+        } else if slots_to_clean {
             let purge_complete = Arc::new(AtomicBool::new(false));
             let blockstore = blockstore.clone();
             let purge_complete1 = purge_complete.clone();
@@ -333,7 +441,8 @@ mod tests {
         LedgerCleanupService::cleanup_ledger(
             &receiver,
             &blockstore,
-            5,
+            LedgerRetention::Shreds(5),
+            false,
             &mut last_purge_slot,
             10,
             &highest_compaction_slot,
@@ -364,6 +473,63 @@ mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_cleanup_by_time() {
+        solana_logger::setup();
+        let blockstore_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&blockstore_path).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 50, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        for slot in 0..50 {
+            blockstore
+                .cache_block_time(slot, slot as UnixTimestamp * 10)
+                .unwrap();
+        }
+        let blockstore = Arc::new(blockstore);
+        let (sender, receiver) = unbounded();
+        let retention = LedgerRetention::Time(Duration::from_secs(100));
+
+        // A dry run leaves the ledger untouched
+        let mut last_purge_slot = 0;
+        let highest_compaction_slot = Arc::new(AtomicU64::new(0));
+        sender.send(50).unwrap();
+        LedgerCleanupService::cleanup_ledger(
+            &receiver,
+            &blockstore,
+            retention,
+            true,
+            &mut last_purge_slot,
+            10,
+            &highest_compaction_slot,
+        )
+        .unwrap();
+        assert_eq!(last_purge_slot, 50);
+        assert_eq!(highest_compaction_slot.load(Ordering::Relaxed), 0);
+        assert_eq!(blockstore.slot_meta_iterator(0).unwrap().count(), 50);
+
+        // Slots with a block time more than 100s older than the newest one, 490, are purged
+        let mut last_purge_slot = 0;
+        sender.send(50).unwrap();
+        LedgerCleanupService::cleanup_ledger(
+            &receiver,
+            &blockstore,
+            retention,
+            false,
+            &mut last_purge_slot,
+            10,
+            &highest_compaction_slot,
+        )
+        .unwrap();
+        assert_eq!(highest_compaction_slot.load(Ordering::Relaxed), 38);
+        blockstore
+            .slot_meta_iterator(0)
+            .unwrap()
+            .for_each(|(slot, _)| assert!(slot > 38));
+
+        drop(blockstore);
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_cleanup_speed() {
         solana_logger::setup();
@@ -403,7 +569,8 @@ mod tests {
             LedgerCleanupService::cleanup_ledger(
                 &receiver,
                 &blockstore,
-                initial_slots,
+                LedgerRetention::Shreds(initial_slots),
+                false,
                 &mut last_purge_slot,
                 10,
                 &last_compaction_slot,
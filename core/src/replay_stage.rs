@@ -23,6 +23,7 @@ use {
         rewards_recorder_service::RewardsRecorderSender,
         tower_storage::{SavedTower, TowerStorage},
         unfrozen_gossip_verified_vote_hashes::UnfrozenGossipVerifiedVoteHashes,
+        validator::ValidatorDrain,
         voting_service::VoteOp,
         window_service::DuplicateSlotReceiver,
     },
@@ -139,6 +140,7 @@ pub struct ReplayStageConfig {
     pub wait_for_vote_to_start_leader: bool,
     pub ancestor_hashes_replay_update_sender: AncestorHashesReplayUpdateSender,
     pub tower_storage: Arc<dyn TowerStorage>,
+    pub validator_drain: Arc<ValidatorDrain>,
 }
 
 #[derive(Default)]
@@ -382,6 +384,7 @@ impl ReplayStage {
             wait_for_vote_to_start_leader,
             ancestor_hashes_replay_update_sender,
             tower_storage,
+            validator_drain,
         } = config;
 
         trace!("replay stage");
@@ -412,6 +415,7 @@ impl ReplayStage {
                 let mut last_reset = Hash::default();
                 let mut partition_exists = false;
                 let mut skipped_slots_info = SkippedSlotsInfo::default();
+                let mut drain_root = None;
                 let mut replay_timing = ReplayTiming::default();
                 let mut duplicate_slots_tracker = DuplicateSlotsTracker::default();
                 let mut gossip_duplicate_confirmed_slots: GossipDuplicateConfirmedSlots = GossipDuplicateConfirmedSlots::default();
@@ -811,7 +815,23 @@ impl ReplayStage {
                     // may add a bank that will not included in either of these maps.
                     drop(ancestors);
                     drop(descendants);
-                    if !tpu_has_bank {
+                    if validator_drain.is_requested() {
+                        // Stop producing blocks and wait for the next root before reporting the
+                        // validator as drained
+                        let root = bank_forks.read().unwrap().root();
+                        match drain_root {
+                            None => drain_root = Some(root),
+                            Some(drain_root) if !tpu_has_bank && root > drain_root => {
+                                if !validator_drain.is_drained()
+                                    && !validator_drain.is_timed_out()
+                                {
+                                    info!("validator drained at root {}", root);
+                                    validator_drain.set_drained();
+                                }
+                            }
+                            Some(_) => (),
+                        }
+                    } else if !tpu_has_bank {
                         Self::maybe_start_leader(
                             &my_pubkey,
                             &bank_forks,
@@ -2262,13 +2282,31 @@ impl ReplayStage {
                 }
                 Self::record_rewards(&bank, rewards_recorder_sender);
                 if let Some(ref block_metadata_notifier) = block_metadata_notifier {
+                    let parent = bank.parent();
+                    let parent_blockhash = parent
+                        .as_ref()
+                        .map(|parent| parent.last_blockhash())
+                        .unwrap_or_default();
+                    let parent_transaction_count =
+                        parent.map_or(0, |parent| parent.transaction_count());
+                    let entries = match blockstore.get_slot_entries(bank.slot(), 0) {
+                        Ok(entries) => entries,
+                        Err(err) => {
+                            warn!("Failed to load entries of slot {}: {:?}", bank.slot(), err);
+                            vec![]
+                        }
+                    };
                     let block_metadata_notifier = block_metadata_notifier.read().unwrap();
                     block_metadata_notifier.notify_block_metadata(
+                        bank.parent_slot(),
+                        &parent_blockhash.to_string(),
                         bank.slot(),
                         &bank.last_blockhash().to_string(),
                         &bank.rewards,
                         Some(bank.clock().unix_timestamp),
                         Some(bank.block_height()),
+                        bank.transaction_count() - parent_transaction_count,
+                        &entries,
                     )
                 }
             } else {
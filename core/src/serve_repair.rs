@@ -31,10 +31,10 @@ use {
     },
     solana_streamer::streamer::{PacketBatchReceiver, PacketBatchSender},
     std::{
-        collections::HashSet,
+        collections::{BTreeSet, HashSet},
         net::SocketAddr,
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, RwLock,
         },
         thread::{Builder, JoinHandle},
@@ -167,6 +167,42 @@ pub enum RepairProtocol {
 #[derive(Clone)]
 pub struct ServeRepair {
     cluster_info: Arc<ClusterInfo>,
+    leader_slot_repairs: Arc<LeaderSlotRepairs>,
+}
+
+// Number of this node's most recent leader slots whose repair requests are counted
+const MAX_LEADER_SLOTS_TRACKED_FOR_REPAIRS: usize = 64;
+
+/// Counts the window index requests this node serves for the slots it produced as leader, so
+/// that the broadcast stage can adapt its FEC rate to how many of its own shreds peers miss.
+/// Requests for slots produced by other leaders aren't counted.
+#[derive(Debug, Default)]
+pub struct LeaderSlotRepairs {
+    slots: RwLock<BTreeSet<Slot>>,
+    num_requests: AtomicU64,
+}
+
+impl LeaderSlotRepairs {
+    /// Starts counting the repair requests for `slot`, which this node is broadcasting
+    pub fn add_slot(&self, slot: Slot) {
+        let mut slots = self.slots.write().unwrap();
+        slots.insert(slot);
+        while slots.len() > MAX_LEADER_SLOTS_TRACKED_FOR_REPAIRS {
+            let oldest_slot = *slots.iter().next().unwrap();
+            slots.remove(&oldest_slot);
+        }
+    }
+
+    pub(crate) fn record_request(&self, slot: Slot) {
+        if self.slots.read().unwrap().contains(&slot) {
+            self.num_requests.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Total number of repair requests served for this node's leader slots
+    pub fn num_requests(&self) -> u64 {
+        self.num_requests.load(Ordering::Relaxed)
+    }
 }
 
 // Cache entry for repair peers for a slot.
@@ -204,7 +240,14 @@ impl RepairPeers {
 
 impl ServeRepair {
     pub fn new(cluster_info: Arc<ClusterInfo>) -> Self {
-        Self { cluster_info }
+        Self {
+            cluster_info,
+            leader_slot_repairs: Arc::default(),
+        }
+    }
+
+    pub fn leader_slot_repairs(&self) -> Arc<LeaderSlotRepairs> {
+        self.leader_slot_repairs.clone()
     }
 
     fn my_info(&self) -> ContactInfo {
@@ -249,6 +292,7 @@ impl ServeRepair {
             match &request {
                 RepairProtocol::WindowIndexWithNonce(_, slot, shred_index, nonce) => {
                     stats.window_index += 1;
+                    me.read().unwrap().leader_slot_repairs.record_request(*slot);
                     (
                         Self::run_window_request(
                             recycler,
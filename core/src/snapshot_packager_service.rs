@@ -84,6 +84,7 @@ impl SnapshotPackagerService {
                     // last_full_snapshot_slot that requires this archive call to succeed.
                     snapshot_utils::archive_snapshot_package(
                         &snapshot_package,
+                        snapshot_config.archive_compression,
                         snapshot_config.maximum_full_snapshot_archives_to_retain,
                         snapshot_config.maximum_incremental_snapshot_archives_to_retain,
                     )
@@ -219,7 +220,8 @@ mod tests {
             snapshot_archive_info::SnapshotArchiveInfo,
             snapshot_package::{SnapshotPackage, SnapshotType},
             snapshot_utils::{
-                self, ArchiveFormat, SnapshotVersion, SNAPSHOT_STATUS_CACHE_FILE_NAME,
+                self, ArchiveCompressionOptions, ArchiveFormat, SnapshotVersion,
+                SNAPSHOT_STATUS_CACHE_FILE_NAME,
             },
         },
         solana_sdk::hash::Hash,
@@ -325,6 +327,7 @@ mod tests {
         // Make tarball from packageable snapshot
         snapshot_utils::archive_snapshot_package(
             &snapshot_package,
+            ArchiveCompressionOptions::default(),
             snapshot_utils::DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             snapshot_utils::DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
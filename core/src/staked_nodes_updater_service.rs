@@ -0,0 +1,69 @@
+//! The `staked_nodes_updater_service` periodically refreshes the map of TPU addresses to stake
+//! that the QUIC server consults when ranking connections for eviction.
+
+use {
+    solana_gossip::cluster_info::ClusterInfo,
+    solana_runtime::bank_forks::BankForks,
+    solana_streamer::quic::StakedNodes,
+    std::{
+        collections::HashMap,
+        net::IpAddr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const IP_TO_STAKE_REFRESH_DURATION: Duration = Duration::from_secs(5);
+
+pub struct StakedNodesUpdaterService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl StakedNodesUpdaterService {
+    pub fn new(
+        exit: Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        staked_nodes: StakedNodes,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("sol-sn-updater".to_string())
+            .spawn(move || {
+                let mut last_stakes = Instant::now();
+                while !exit.load(Ordering::Relaxed) {
+                    if last_stakes.elapsed() > IP_TO_STAKE_REFRESH_DURATION {
+                        let ip_to_stake = Self::ip_to_stake(&cluster_info, &bank_forks);
+                        *staked_nodes.write().unwrap() = ip_to_stake;
+                        last_stakes = Instant::now();
+                    } else {
+                        sleep(Duration::from_millis(100));
+                    }
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    fn ip_to_stake(
+        cluster_info: &ClusterInfo,
+        bank_forks: &RwLock<BankForks>,
+    ) -> HashMap<IpAddr, u64> {
+        let staked_nodes = bank_forks.read().unwrap().root_bank().staked_nodes();
+        cluster_info
+            .tpu_peers()
+            .into_iter()
+            .filter_map(|node| {
+                let stake = staked_nodes.get(&node.id)?;
+                Some((node.tpu.ip(), *stake))
+            })
+            .collect()
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
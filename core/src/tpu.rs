@@ -4,14 +4,18 @@
 use {
     crate::{
         banking_stage::BankingStage,
-        broadcast_stage::{BroadcastStage, BroadcastStageType, RetransmitSlotsReceiver},
+        broadcast_stage::{
+            BroadcastConfig, BroadcastStage, BroadcastStageType, RetransmitSlotsReceiver,
+        },
         cluster_info_vote_listener::{
             ClusterInfoVoteListener, GossipDuplicateConfirmedSlotsSender,
             GossipVerifiedVoteHashSender, VerifiedVoteSender, VoteTracker,
         },
         fetch_stage::FetchStage,
+        serve_repair::LeaderSlotRepairs,
         sigverify::TransactionSigVerifier,
         sigverify_stage::SigVerifyStage,
+        staked_nodes_updater_service::StakedNodesUpdaterService,
     },
     crossbeam_channel::{unbounded, Receiver},
     solana_gossip::cluster_info::ClusterInfo,
@@ -27,6 +31,7 @@ use {
         vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
     },
     solana_sdk::signature::Keypair,
+    solana_streamer::quic::{ConnectionTableConfig, StakedNodes},
     std::{
         net::UdpSocket,
         sync::{atomic::AtomicBool, Arc, Mutex, RwLock},
@@ -52,6 +57,7 @@ pub struct Tpu {
     cluster_info_vote_listener: ClusterInfoVoteListener,
     broadcast_stage: BroadcastStage,
     tpu_quic_t: thread::JoinHandle<()>,
+    staked_nodes_updater_service: StakedNodesUpdaterService,
 }
 
 impl Tpu {
@@ -79,6 +85,9 @@ impl Tpu {
         cluster_confirmed_slot_sender: GossipDuplicateConfirmedSlotsSender,
         cost_model: &Arc<RwLock<CostModel>>,
         keypair: &Keypair,
+        tpu_quic_connection_table_config: ConnectionTableConfig,
+        broadcast_config: BroadcastConfig,
+        leader_slot_repairs: Arc<LeaderSlotRepairs>,
     ) -> Self {
         let TpuSockets {
             transactions: transactions_sockets,
@@ -102,12 +111,21 @@ impl Tpu {
         );
         let (verified_sender, verified_receiver) = unbounded();
 
+        let staked_nodes = StakedNodes::default();
+        let staked_nodes_updater_service = StakedNodesUpdaterService::new(
+            exit.clone(),
+            cluster_info.clone(),
+            bank_forks.clone(),
+            staked_nodes.clone(),
+        );
         let tpu_quic_t = solana_streamer::quic::spawn_server(
             transactions_quic_sockets,
             keypair,
             cluster_info.my_contact_info().tpu.ip(),
             packet_sender,
             exit.clone(),
+            staked_nodes,
+            tpu_quic_connection_table_config,
         )
         .unwrap();
 
@@ -165,6 +183,8 @@ impl Tpu {
             blockstore,
             &bank_forks,
             shred_version,
+            broadcast_config,
+            leader_slot_repairs,
         );
 
         Self {
@@ -175,6 +195,7 @@ impl Tpu {
             cluster_info_vote_listener,
             broadcast_stage,
             tpu_quic_t,
+            staked_nodes_updater_service,
         }
     }
 
@@ -187,6 +208,7 @@ impl Tpu {
             self.banking_stage.join(),
         ];
         self.tpu_quic_t.join()?;
+        self.staked_nodes_updater_service.join()?;
         let broadcast_result = self.broadcast_stage.join();
         for result in results {
             result?;
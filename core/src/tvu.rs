@@ -15,7 +15,7 @@ use {
         consensus::Tower,
         cost_update_service::CostUpdateService,
         drop_bank_service::DropBankService,
-        ledger_cleanup_service::LedgerCleanupService,
+        ledger_cleanup_service::{LedgerCleanupService, LedgerRetention},
         replay_stage::{ReplayStage, ReplayStageConfig},
         retransmit_stage::RetransmitStage,
         rewards_recorder_service::RewardsRecorderSender,
@@ -23,6 +23,7 @@ use {
         sigverify_shreds::ShredSigVerifier,
         sigverify_stage::SigVerifyStage,
         tower_storage::TowerStorage,
+        validator::ValidatorDrain,
         voting_service::VotingService,
     },
     crossbeam_channel::{unbounded, Receiver},
@@ -61,6 +62,7 @@ use {
         net::UdpSocket,
         sync::{atomic::AtomicBool, Arc, Mutex, RwLock},
         thread,
+        time::Duration,
     },
 };
 
@@ -89,6 +91,8 @@ pub struct TvuSockets {
 #[derive(Default)]
 pub struct TvuConfig {
     pub max_ledger_shreds: Option<u64>,
+    pub max_ledger_age: Option<Duration>,
+    pub ledger_cleanup_dry_run: bool,
     pub shred_version: u16,
     pub halt_on_known_validators_accounts_hash_mismatch: bool,
     pub known_validators: Option<HashSet<Pubkey>>,
@@ -101,6 +105,7 @@ pub struct TvuConfig {
     pub rocksdb_max_compaction_jitter: Option<u64>,
     pub wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
+    pub validator_drain: Arc<ValidatorDrain>,
 }
 
 impl Tvu {
@@ -293,6 +298,7 @@ impl Tvu {
             wait_for_vote_to_start_leader: tvu_config.wait_for_vote_to_start_leader,
             ancestor_hashes_replay_update_sender,
             tower_storage: tower_storage.clone(),
+            validator_drain: tvu_config.validator_drain.clone(),
         };
 
         let (voting_sender, voting_receiver) = unbounded();
@@ -348,11 +354,17 @@ impl Tvu {
             transaction_cost_metrics_sender,
         );
 
-        let ledger_cleanup_service = tvu_config.max_ledger_shreds.map(|max_ledger_shreds| {
+        let ledger_retention = match (tvu_config.max_ledger_age, tvu_config.max_ledger_shreds) {
+            (Some(max_ledger_age), _) => Some(LedgerRetention::Time(max_ledger_age)),
+            (None, Some(max_ledger_shreds)) => Some(LedgerRetention::Shreds(max_ledger_shreds)),
+            (None, None) => None,
+        };
+        let ledger_cleanup_service = ledger_retention.map(|ledger_retention| {
             LedgerCleanupService::new(
                 ledger_cleanup_slot_receiver,
                 blockstore.clone(),
-                max_ledger_shreds,
+                ledger_retention,
+                tvu_config.ledger_cleanup_dry_run,
                 exit,
                 compaction_interval,
                 max_compaction_jitter,
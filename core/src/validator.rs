@@ -3,7 +3,8 @@
 pub use solana_perf::report_target_features;
 use {
     crate::{
-        broadcast_stage::BroadcastStageType,
+        blockstore_archive_service::BlockstoreArchiveService,
+        broadcast_stage::{BroadcastConfig, BroadcastStageType},
         cache_block_meta_service::{CacheBlockMetaSender, CacheBlockMetaService},
         cluster_info_vote_listener::VoteTracker,
         completed_data_sets_service::CompletedDataSetsService,
@@ -22,7 +23,10 @@ use {
     },
     crossbeam_channel::{bounded, unbounded, Receiver},
     rand::{thread_rng, Rng},
-    solana_accountsdb_plugin_manager::accountsdb_plugin_service::AccountsDbPluginService,
+    solana_accountsdb_plugin_manager::{
+        accountsdb_plugin_manager::AccountsDbPluginManager,
+        accountsdb_plugin_service::AccountsDbPluginService,
+    },
     solana_entry::poh::compute_hash_time_ns,
     solana_gossip::{
         cluster_info::{
@@ -36,7 +40,11 @@ use {
     solana_ledger::{
         bank_forks_utils,
         blockstore::{Blockstore, BlockstoreSignals, CompletedSlotsReceiver, PurgeType},
-        blockstore_db::{BlockstoreOptions, BlockstoreRecoveryMode},
+        blockstore_archive::BlockstoreArchiveConfig,
+        blockstore_db::{
+            BlockstoreCompactionOptions, BlockstoreCompressionType, BlockstoreOptions,
+            BlockstoreRecoveryMode, BlockstoreTuningProfile,
+        },
         blockstore_processor::{self, TransactionStatusSender},
         leader_schedule::FixedSchedule,
         leader_schedule_cache::LeaderScheduleCache,
@@ -91,7 +99,7 @@ use {
         timing::timestamp,
     },
     solana_send_transaction_service::send_transaction_service,
-    solana_streamer::socket::SocketAddrSpace,
+    solana_streamer::{quic::ConnectionTableConfig, socket::SocketAddrSpace},
     solana_vote_program::vote_state::VoteState,
     std::{
         collections::{HashMap, HashSet},
@@ -120,11 +128,17 @@ pub struct ValidatorConfig {
     pub rpc_config: JsonRpcConfig,
     pub accountsdb_repl_service_config: Option<AccountsDbReplServiceConfig>,
     pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
+    pub accountsdb_plugin_startup_accounts_per_second: Option<u64>,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
     pub snapshot_config: Option<SnapshotConfig>,
     pub max_ledger_shreds: Option<u64>,
+    /// Purge ledger data older than this, by block time. Takes precedence over `max_ledger_shreds`
+    pub max_ledger_age: Option<Duration>,
+    /// Only report what the ledger cleanup would purge
+    pub ledger_cleanup_dry_run: bool,
     pub broadcast_stage_type: BroadcastStageType,
+    pub broadcast_config: BroadcastConfig,
     pub enable_partition: Option<Arc<AtomicBool>>,
     pub enforce_ulimit_nofile: bool,
     pub fixed_leader_schedule: Option<FixedSchedule>,
@@ -141,6 +155,10 @@ pub struct ValidatorConfig {
     pub accounts_hash_interval_slots: u64,
     pub max_genesis_archive_unpacked_size: u64,
     pub wal_recovery_mode: Option<BlockstoreRecoveryMode>,
+    pub blockstore_compaction: BlockstoreCompactionOptions,
+    pub blockstore_shred_compression: BlockstoreCompressionType,
+    pub blockstore_tuning: BlockstoreTuningProfile,
+    pub blockstore_archive: Option<BlockstoreArchiveConfig>,
     pub poh_verify: bool, // Perform PoH verification during blockstore processing at boo
     pub require_tower: bool,
     pub tower_storage: Arc<dyn TowerStorage>,
@@ -161,7 +179,9 @@ pub struct ValidatorConfig {
     pub accounts_db_skip_shrink: bool,
     pub accounts_db_use_index_hash_calculation: bool,
     pub tpu_coalesce_ms: u64,
+    pub tpu_quic_connection_table_config: ConnectionTableConfig,
     pub validator_exit: Arc<RwLock<Exit>>,
+    pub validator_drain: Arc<ValidatorDrain>,
     pub no_wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
 }
@@ -175,15 +195,19 @@ impl Default for ValidatorConfig {
             expected_shred_version: None,
             voting_disabled: false,
             max_ledger_shreds: None,
+            max_ledger_age: None,
+            ledger_cleanup_dry_run: false,
             account_paths: Vec::new(),
             account_shrink_paths: None,
             rpc_config: JsonRpcConfig::default(),
             accountsdb_repl_service_config: None,
             accountsdb_plugin_config_files: None,
+            accountsdb_plugin_startup_accounts_per_second: None,
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
             snapshot_config: None,
             broadcast_stage_type: BroadcastStageType::Standard,
+            broadcast_config: BroadcastConfig::default(),
             enable_partition: None,
             enforce_ulimit_nofile: true,
             fixed_leader_schedule: None,
@@ -200,6 +224,10 @@ impl Default for ValidatorConfig {
             accounts_hash_interval_slots: std::u64::MAX,
             max_genesis_archive_unpacked_size: MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
             wal_recovery_mode: None,
+            blockstore_compaction: BlockstoreCompactionOptions::default(),
+            blockstore_shred_compression: BlockstoreCompressionType::default(),
+            blockstore_tuning: BlockstoreTuningProfile::default(),
+            blockstore_archive: None,
             poh_verify: true,
             require_tower: false,
             tower_storage: Arc::new(crate::tower_storage::NullTowerStorage::default()),
@@ -219,7 +247,9 @@ impl Default for ValidatorConfig {
             accounts_db_skip_shrink: false,
             accounts_db_use_index_hash_calculation: true,
             tpu_coalesce_ms: DEFAULT_TPU_COALESCE_MS,
+            tpu_quic_connection_table_config: ConnectionTableConfig::default(),
             validator_exit: Arc::new(RwLock::new(Exit::default())),
+            validator_drain: Arc::default(),
             no_wait_for_vote_to_start_leader: true,
             accounts_shrink_ratio: AccountShrinkThreshold::default(),
             accounts_db_config: None,
@@ -262,6 +292,49 @@ impl Default for ValidatorStartProgress {
     }
 }
 
+// `ValidatorDrain` coordinates a graceful shutdown requested over the admin RPC channel. Once
+// requested, replay stops starting new leader slots and reports the validator as drained after the
+// next root is made. On exit a drained validator produces a final snapshot of its root bank and
+// flushes the blockstore. A drain that doesn't finish before its deadline falls back to a normal
+// exit.
+#[derive(Debug, Default)]
+pub struct ValidatorDrain {
+    requested: AtomicBool,
+    drained: AtomicBool,
+    deadline: Mutex<Option<Instant>>,
+}
+
+impl ValidatorDrain {
+    pub fn request(&self, timeout: Duration) {
+        *self.deadline.lock().unwrap() = Some(Instant::now() + timeout);
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Time left until the drain deadline, `None` if no drain was requested
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .lock()
+            .unwrap()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    pub fn set_drained(&self) {
+        self.drained.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_drained(&self) -> bool {
+        self.drained.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Default)]
 struct TransactionHistoryServices {
     transaction_status_sender: Option<TransactionStatusSender>,
@@ -275,6 +348,7 @@ struct TransactionHistoryServices {
 
 pub struct Validator {
     validator_exit: Arc<RwLock<Exit>>,
+    validator_drain: Arc<ValidatorDrain>,
     json_rpc_service: Option<JsonRpcService>,
     pubsub_service: Option<PubSubService>,
     rpc_completed_slots_service: JoinHandle<()>,
@@ -284,6 +358,7 @@ pub struct Validator {
     cache_block_meta_service: Option<CacheBlockMetaService>,
     system_monitor_service: Option<SystemMonitorService>,
     sample_performance_service: Option<SamplePerformanceService>,
+    blockstore_archive_service: Option<BlockstoreArchiveService>,
     stats_reporter_service: StatsReporterService,
     gossip_service: GossipService,
     serve_repair_service: ServeRepairService,
@@ -295,6 +370,9 @@ pub struct Validator {
     tvu: Tvu,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
+    bank_forks: Arc<RwLock<BankForks>>,
+    pub blockstore: Arc<Blockstore>,
+    snapshot_config: Option<SnapshotConfig>,
     accountsdb_repl_service: Option<AccountsDbReplService>,
     accountsdb_plugin_service: Option<AccountsDbPluginService>,
 }
@@ -342,6 +420,7 @@ impl Validator {
                 let result = AccountsDbPluginService::new(
                     confirmed_bank_receiver,
                     accountsdb_plugin_config_files,
+                    config.accountsdb_plugin_startup_accounts_per_second,
                 );
                 match result {
                     Ok(accountsdb_plugin_service) => Some(accountsdb_plugin_service),
@@ -513,6 +592,10 @@ impl Validator {
                 None
             };
 
+        let blockstore_archive_service = config.blockstore_archive.as_ref().map(|archive_config| {
+            BlockstoreArchiveService::new(&blockstore, archive_config, &exit)
+        });
+
         info!("Starting validator with working bank slot {}", bank.slot());
         {
             let hard_forks: Vec<_> = bank.hard_forks().read().unwrap().iter().copied().collect();
@@ -727,6 +810,7 @@ impl Validator {
             &exit,
         );
         let serve_repair = Arc::new(RwLock::new(ServeRepair::new(cluster_info.clone())));
+        let leader_slot_repairs = serve_repair.read().unwrap().leader_slot_repairs();
         let serve_repair_service = ServeRepairService::new(
             &serve_repair,
             Some(blockstore.clone()),
@@ -854,6 +938,8 @@ impl Validator {
             cluster_confirmed_slot_receiver,
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
+                max_ledger_age: config.max_ledger_age,
+                ledger_cleanup_dry_run: config.ledger_cleanup_dry_run,
                 halt_on_known_validators_accounts_hash_mismatch: config
                     .halt_on_known_validators_accounts_hash_mismatch,
                 shred_version: node.info.shred_version,
@@ -867,6 +953,7 @@ impl Validator {
                 rocksdb_max_compaction_jitter: config.rocksdb_compaction_interval,
                 wait_for_vote_to_start_leader,
                 accounts_shrink_ratio: config.accounts_shrink_ratio,
+                validator_drain: config.validator_drain.clone(),
             },
             &max_slots,
             &cost_model,
@@ -894,7 +981,7 @@ impl Validator {
             &exit,
             node.info.shred_version,
             vote_tracker,
-            bank_forks,
+            bank_forks.clone(),
             verified_vote_sender,
             gossip_verified_vote_hash_sender,
             replay_vote_receiver,
@@ -904,6 +991,9 @@ impl Validator {
             cluster_confirmed_slot_sender,
             &cost_model,
             &identity_keypair,
+            config.tpu_quic_connection_table_config,
+            config.broadcast_config,
+            leader_slot_repairs,
         );
 
         datapoint_info!("validator-new", ("id", id.to_string(), String));
@@ -922,6 +1012,7 @@ impl Validator {
             cache_block_meta_service,
             system_monitor_service,
             sample_performance_service,
+            blockstore_archive_service,
             snapshot_packager_service,
             completed_data_sets_service,
             tpu,
@@ -930,12 +1021,23 @@ impl Validator {
             poh_recorder,
             ip_echo_server,
             validator_exit: config.validator_exit.clone(),
+            validator_drain: config.validator_drain.clone(),
             cluster_info,
+            bank_forks,
+            blockstore,
+            snapshot_config: config.snapshot_config.clone(),
             accountsdb_repl_service,
             accountsdb_plugin_service,
         }
     }
 
+    /// The manager of the AccountsDb plugins, if the validator was started with any
+    pub fn accountsdb_plugin_manager(&self) -> Option<Arc<RwLock<AccountsDbPluginManager>>> {
+        self.accountsdb_plugin_service
+            .as_ref()
+            .map(AccountsDbPluginService::get_plugin_manager)
+    }
+
     // Used for notifying many nodes in parallel to exit
     pub fn exit(&mut self) {
         self.validator_exit.write().unwrap().exit();
@@ -1027,6 +1129,12 @@ impl Validator {
                 .expect("sample_performance_service");
         }
 
+        if let Some(blockstore_archive_service) = self.blockstore_archive_service {
+            blockstore_archive_service
+                .join()
+                .expect("blockstore_archive_service");
+        }
+
         if let Some(s) = self.snapshot_packager_service {
             s.join().expect("snapshot_packager_service");
         }
@@ -1058,6 +1166,58 @@ impl Validator {
                 .join()
                 .expect("accountsdb_plugin_service");
         }
+
+        if self.validator_drain.is_drained() {
+            Self::finish_drain(
+                &self.bank_forks,
+                &self.blockstore,
+                self.snapshot_config.as_ref(),
+                &self.validator_drain,
+            );
+        }
+    }
+
+    // Persists the state of a drained validator so that it restarts from its latest root without
+    // replaying the ledger. The final snapshot is abandoned if it isn't done by the drain
+    // deadline, its partial archive is removed on the next start.
+    fn finish_drain(
+        bank_forks: &RwLock<BankForks>,
+        blockstore: &Blockstore,
+        snapshot_config: Option<&SnapshotConfig>,
+        validator_drain: &ValidatorDrain,
+    ) {
+        let root_bank = bank_forks.read().unwrap().root_bank();
+        if let Some(snapshot_config) = snapshot_config.cloned() {
+            info!("Creating final snapshot for slot {}", root_bank.slot());
+            let (result_sender, result_receiver) = bounded(1);
+            Builder::new()
+                .name("solana-final-snapshot".to_string())
+                .spawn(move || {
+                    let result = snapshot_utils::bank_to_full_snapshot_archive(
+                        &snapshot_config.bank_snapshots_dir,
+                        &root_bank,
+                        Some(snapshot_config.snapshot_version),
+                        &snapshot_config.snapshot_archives_dir,
+                        snapshot_config.archive_format,
+                        snapshot_config.archive_compression,
+                        snapshot_config.maximum_full_snapshot_archives_to_retain,
+                        snapshot_config.maximum_incremental_snapshot_archives_to_retain,
+                    );
+                    let _ = result_sender.send(result);
+                })
+                .unwrap();
+            let timeout = validator_drain.remaining().unwrap_or_default();
+            match result_receiver.recv_timeout(timeout) {
+                Ok(Ok(archive_info)) => {
+                    info!("Created final snapshot: {}", archive_info.path().display())
+                }
+                Ok(Err(err)) => error!("Unable to create final snapshot: {}", err),
+                Err(_) => warn!("Final snapshot not created before the drain timeout, exiting"),
+            }
+        }
+        if let Err(err) = blockstore.flush() {
+            error!("Unable to flush blockstore: {:?}", err);
+        }
     }
 }
 
@@ -1250,6 +1410,13 @@ fn new_banks_from_ledger(
         BlockstoreOptions {
             recovery_mode: config.wal_recovery_mode.clone(),
             enforce_ulimit_nofile,
+            compaction: config.blockstore_compaction.clone(),
+            shred_compression: config.blockstore_shred_compression,
+            tuning: config.blockstore_tuning.clone(),
+            archive: config
+                .blockstore_archive
+                .as_ref()
+                .map(|archive_config| archive_config.storage.clone()),
             ..BlockstoreOptions::default()
         },
     )
@@ -1306,8 +1473,12 @@ fn new_banks_from_ledger(
                 exit,
                 enable_rpc_transaction_history,
                 config.rpc_config.enable_cpi_and_log_storage,
+                config.rpc_config.enable_program_signatures_index,
                 transaction_notifier,
             )
+        } else if config.max_ledger_age.is_some() {
+            // Time based ledger cleanup needs the block times of rooted slots
+            initialize_cache_block_meta_service(blockstore.clone(), exit)
         } else {
             TransactionHistoryServices::default()
         };
@@ -1374,6 +1545,7 @@ fn new_banks_from_ledger(
             None,
             &snapshot_config.snapshot_archives_dir,
             snapshot_config.archive_format,
+            snapshot_config.archive_compression,
             snapshot_config.maximum_full_snapshot_archives_to_retain,
             snapshot_config.maximum_incremental_snapshot_archives_to_retain,
         )
@@ -1499,6 +1671,7 @@ fn initialize_rpc_transaction_history_services(
     exit: &Arc<AtomicBool>,
     enable_rpc_transaction_history: bool,
     enable_cpi_and_log_storage: bool,
+    enable_program_signatures_index: bool,
     transaction_notifier: Option<TransactionNotifierLock>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
@@ -1511,6 +1684,7 @@ fn initialize_rpc_transaction_history_services(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
         enable_rpc_transaction_history,
+        enable_program_signatures_index,
         transaction_notifier.clone(),
         blockstore.clone(),
         exit,
@@ -1542,6 +1716,20 @@ fn initialize_rpc_transaction_history_services(
     }
 }
 
+fn initialize_cache_block_meta_service(
+    blockstore: Arc<Blockstore>,
+    exit: &Arc<AtomicBool>,
+) -> TransactionHistoryServices {
+    let (cache_block_meta_sender, cache_block_meta_receiver) = unbounded();
+    let cache_block_meta_service =
+        CacheBlockMetaService::new(cache_block_meta_receiver, blockstore, exit);
+    TransactionHistoryServices {
+        cache_block_meta_sender: Some(cache_block_meta_sender),
+        cache_block_meta_service: Some(cache_block_meta_service),
+        ..TransactionHistoryServices::default()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 enum ValidatorError {
     BadExpectedBankHash,
@@ -2021,4 +2209,20 @@ mod tests {
         };
         check_poh_speed(&genesis_config, Some(10_000));
     }
+
+    #[test]
+    fn test_validator_drain_timeout() {
+        let validator_drain = ValidatorDrain::default();
+        assert_eq!(validator_drain.remaining(), None);
+        assert!(!validator_drain.is_timed_out());
+
+        validator_drain.request(Duration::from_secs(600));
+        assert!(validator_drain.is_requested());
+        assert!(!validator_drain.is_timed_out());
+
+        let validator_drain = ValidatorDrain::default();
+        validator_drain.request(Duration::ZERO);
+        assert!(validator_drain.is_timed_out());
+        assert_eq!(validator_drain.remaining(), Some(Duration::ZERO));
+    }
 }
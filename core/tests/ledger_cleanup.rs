@@ -6,7 +6,7 @@ mod tests {
     use {
         crossbeam_channel::unbounded,
         log::*,
-        solana_core::ledger_cleanup_service::LedgerCleanupService,
+        solana_core::ledger_cleanup_service::{LedgerCleanupService, LedgerRetention},
         solana_ledger::{
             blockstore::{make_many_slot_shreds, Blockstore},
             blockstore_db::BlockstoreOptions,
@@ -322,7 +322,8 @@ mod tests {
             Some(LedgerCleanupService::new(
                 receiver,
                 blockstore.clone(),
-                max_ledger_shreds,
+                LedgerRetention::Shreds(max_ledger_shreds),
+                false,
                 &exit,
                 compaction_interval,
                 None,
@@ -603,7 +604,8 @@ mod tests {
         LedgerCleanupService::cleanup_ledger(
             &receiver,
             &blockstore,
-            max_ledger_shreds,
+            LedgerRetention::Shreds(max_ledger_shreds),
+            false,
             &mut last_purge_slot,
             10,
             &highest_compact_slot,
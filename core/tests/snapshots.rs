@@ -71,7 +71,7 @@ mod tests {
             snapshot_package::{
                 AccountsPackage, PendingSnapshotPackage, SnapshotPackage, SnapshotType,
             },
-            snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+            snapshot_utils::{self, ArchiveCompressionOptions, ArchiveFormat, SnapshotVersion},
             status_cache::MAX_CACHE_ENTRIES,
         },
         solana_sdk::{
@@ -293,6 +293,7 @@ mod tests {
         let snapshot_package = SnapshotPackage::from(accounts_package);
         snapshot_utils::archive_snapshot_package(
             &snapshot_package,
+            ArchiveCompressionOptions::default(),
             snapshot_config.maximum_full_snapshot_archives_to_retain,
             snapshot_config.maximum_incremental_snapshot_archives_to_retain,
         )
@@ -773,6 +774,7 @@ mod tests {
             &snapshot_config.snapshot_archives_dir,
             bank.get_snapshot_storages(None),
             snapshot_config.archive_format,
+            ArchiveCompressionOptions::default(),
             snapshot_config.snapshot_version,
             snapshot_config.maximum_full_snapshot_archives_to_retain,
             snapshot_config.maximum_incremental_snapshot_archives_to_retain,
@@ -810,6 +812,7 @@ mod tests {
             &snapshot_config.snapshot_archives_dir,
             storages,
             snapshot_config.archive_format,
+            ArchiveCompressionOptions::default(),
             snapshot_config.snapshot_version,
             snapshot_config.maximum_full_snapshot_archives_to_retain,
             snapshot_config.maximum_incremental_snapshot_archives_to_retain,
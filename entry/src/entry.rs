@@ -35,8 +35,9 @@ use {
         cell::RefCell,
         cmp,
         ffi::OsStr,
+        str::FromStr,
         sync::{
-            atomic::{AtomicUsize, Ordering},
+            atomic::{AtomicU8, AtomicUsize, Ordering},
             Arc, Mutex, Once,
         },
         thread::{self, JoinHandle},
@@ -103,6 +104,133 @@ pub struct Api<'a> {
         Symbol<'a, unsafe extern "C" fn(hashes: *mut u8, num_hashes: *const u64)>,
 }
 
+/// Backend used to verify the PoH hashes of entries. There is no OpenCL backend: the perf-libs
+/// only ship CUDA kernels for PoH verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PohVerifyBackend {
+    /// Use the fastest backend available
+    Auto,
+    /// Portable, multi-threaded CPU verification
+    Cpu,
+    /// AVX2/AVX512 CPU verification through `libpoh-simd.so`
+    CpuSimd,
+    /// GPU verification through the CUDA perf-libs
+    Cuda,
+}
+
+impl PohVerifyBackend {
+    pub const STRINGS: [&'static str; 4] = ["auto", "cpu", "cpu-simd", "cuda"];
+
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::Auto => 0,
+            Self::Cpu => 1,
+            Self::CpuSimd => 2,
+            Self::Cuda => 3,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Cpu,
+            2 => Self::CpuSimd,
+            3 => Self::Cuda,
+            _ => Self::Auto,
+        }
+    }
+
+    /// Returns whether this backend can be used on this machine with the libraries loaded so far
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Auto | Self::Cpu => true,
+            Self::CpuSimd => api().is_some() && has_x86_simd().0,
+            Self::Cuda => perf_libs::api().is_some(),
+        }
+    }
+
+    /// Returns every backend that is available on this machine, see `is_available()`
+    pub fn detect() -> Vec<Self> {
+        [Self::Cpu, Self::CpuSimd, Self::Cuda]
+            .into_iter()
+            .filter(|backend| backend.is_available())
+            .collect()
+    }
+}
+
+impl FromStr for PohVerifyBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "cpu" => Ok(Self::Cpu),
+            "cpu-simd" => Ok(Self::CpuSimd),
+            "cuda" => Ok(Self::Cuda),
+            "opencl" => {
+                Err("OpenCL PoH verification is not supported, use cuda instead".to_string())
+            }
+            _ => Err(format!("{} is not a PoH verify backend", s)),
+        }
+    }
+}
+
+static POH_VERIFY_BACKEND: AtomicU8 = AtomicU8::new(PohVerifyBackend::Auto.to_u8());
+
+/// Selects the backend used by `EntrySlice::start_verify()`. Falls back to `Auto` if `backend`
+/// isn't available, and returns the backend that was actually selected.
+pub fn set_poh_verify_backend(backend: PohVerifyBackend) -> PohVerifyBackend {
+    let backend = if backend.is_available() {
+        backend
+    } else {
+        warn!(
+            "PoH verify backend {:?} is not available, available backends: {:?}",
+            backend,
+            PohVerifyBackend::detect()
+        );
+        PohVerifyBackend::Auto
+    };
+    POH_VERIFY_BACKEND.store(backend.to_u8(), Ordering::Relaxed);
+    backend
+}
+
+pub fn poh_verify_backend() -> PohVerifyBackend {
+    PohVerifyBackend::from_u8(POH_VERIFY_BACKEND.load(Ordering::Relaxed))
+}
+
+// Returns whether AVX2 and AVX512 are supported, in that order
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn has_x86_simd() -> (bool, bool) {
+    (
+        is_x86_feature_detected!("avx2"),
+        is_x86_feature_detected!("avx512f"),
+    )
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn has_x86_simd() -> (bool, bool) {
+    (false, false)
+}
+
+fn report_poh_verify_throughput(
+    backend: &'static str,
+    num_entries: usize,
+    num_hashes: u64,
+    elapsed_us: u64,
+) {
+    datapoint_debug!(
+        "entry-poh-verify",
+        ("backend", backend, String),
+        ("num_entries", num_entries, i64),
+        ("num_hashes", num_hashes, i64),
+        ("elapsed_us", elapsed_us, i64),
+        (
+            "hashes_per_ms",
+            num_hashes.saturating_mul(1000) / elapsed_us.max(1),
+            i64
+        ),
+    );
+}
+
 /// Each Entry contains three pieces of data. The `num_hashes` field is the number
 /// of hashes performed since the previous entry.  The `hash` field is the result
 /// of hashing `hash` from the previous entry `num_hashes` times.  The `transactions`
@@ -633,6 +761,12 @@ impl EntrySlice for [Entry] {
         });
 
         let poh_duration_us = timing::duration_as_us(&now.elapsed());
+        report_poh_verify_throughput(
+            "cpu",
+            self.len(),
+            self.iter().map(|entry| entry.num_hashes).sum(),
+            poh_duration_us,
+        );
         EntryVerificationState {
             verification_status: if res {
                 EntryVerificationStatus::Success
@@ -716,6 +850,12 @@ impl EntrySlice for [Entry] {
             })
         });
         let poh_duration_us = timing::duration_as_us(&now.elapsed());
+        report_poh_verify_throughput(
+            "cpu-simd",
+            self.len(),
+            self.iter().map(|entry| entry.num_hashes).sum(),
+            poh_duration_us,
+        );
         EntryVerificationState {
             verification_status: if res {
                 EntryVerificationStatus::Success
@@ -728,15 +868,9 @@ impl EntrySlice for [Entry] {
     }
 
     fn verify_cpu(&self, start_hash: &Hash) -> EntryVerificationState {
-        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-        let (has_avx2, has_avx512) = (
-            is_x86_feature_detected!("avx2"),
-            is_x86_feature_detected!("avx512f"),
-        );
-        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
-        let (has_avx2, has_avx512) = (false, false);
+        let (has_avx2, has_avx512) = has_x86_simd();
 
-        if api().is_some() {
+        if api().is_some() && poh_verify_backend() != PohVerifyBackend::Cpu {
             if has_avx512 && self.len() >= 128 {
                 self.verify_cpu_x86_simd(start_hash, 16)
             } else if has_avx2 && self.len() >= 48 {
@@ -755,11 +889,10 @@ impl EntrySlice for [Entry] {
         recyclers: VerifyRecyclers,
     ) -> EntryVerificationState {
         let start = Instant::now();
-        let api = perf_libs::api();
-        if api.is_none() {
-            return self.verify_cpu(start_hash);
-        }
-        let api = api.unwrap();
+        let api = match (perf_libs::api(), poh_verify_backend()) {
+            (Some(api), PohVerifyBackend::Auto | PohVerifyBackend::Cuda) => api,
+            _ => return self.verify_cpu(start_hash),
+        };
         inc_new_counter_info!("entry_verify-num_entries", self.len() as usize);
 
         let genesis = [Entry {
@@ -805,11 +938,10 @@ impl EntrySlice for [Entry] {
                 );
             }
             assert!(res == 0, "GPU PoH verify many failed");
-            inc_new_counter_info!(
-                "entry_verify-gpu_thread",
-                timing::duration_as_us(&gpu_wait.elapsed()) as usize
-            );
-            timing::duration_as_us(&gpu_wait.elapsed())
+            let gpu_us = timing::duration_as_us(&gpu_wait.elapsed());
+            inc_new_counter_info!("entry_verify-gpu_thread", gpu_us as usize);
+            report_poh_verify_throughput("cuda", length, num_hashes_vec.iter().sum(), gpu_us);
+            gpu_us
         });
 
         let verifications = PAR_THREAD_POOL.with(|thread_pool| {
@@ -1154,6 +1286,42 @@ mod tests {
         assert!(!bad_ticks.verify(&zero)); // inductive step, bad
     }
 
+    #[test]
+    fn test_poh_verify_backend() {
+        assert!(PohVerifyBackend::detect().contains(&PohVerifyBackend::Cpu));
+        for backend in PohVerifyBackend::STRINGS {
+            assert!(backend.parse::<PohVerifyBackend>().is_ok());
+        }
+        assert_eq!(
+            "cpu-simd".parse::<PohVerifyBackend>(),
+            Ok(PohVerifyBackend::CpuSimd)
+        );
+        assert!("opencl".parse::<PohVerifyBackend>().is_err());
+        for backend in [
+            PohVerifyBackend::Auto,
+            PohVerifyBackend::Cpu,
+            PohVerifyBackend::CpuSimd,
+            PohVerifyBackend::Cuda,
+        ] {
+            assert_eq!(PohVerifyBackend::from_u8(backend.to_u8()), backend);
+        }
+        if !PohVerifyBackend::Cuda.is_available() {
+            assert_eq!(
+                set_poh_verify_backend(PohVerifyBackend::Cuda),
+                PohVerifyBackend::Auto
+            );
+        }
+
+        assert_eq!(
+            set_poh_verify_backend(PohVerifyBackend::Cpu),
+            PohVerifyBackend::Cpu
+        );
+        let zero = Hash::default();
+        let ticks = create_ticks(64, 1, zero);
+        assert!(ticks.verify(&zero));
+        set_poh_verify_backend(PohVerifyBackend::Auto);
+    }
+
     #[test]
     fn test_verify_slice_with_hashes1() {
         solana_logger::setup();
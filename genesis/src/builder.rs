@@ -0,0 +1,302 @@
+//! A builder for genesis configs, for private clusters and tests that need a genesis config
+//! without going through the `solana-genesis` command line
+use {
+    crate::load_genesis_accounts,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        clock::{self, UnixTimestamp},
+        epoch_schedule::EpochSchedule,
+        fee_calculator::FeeRateGovernor,
+        genesis_config::{ClusterType, GenesisConfig},
+        inflation::Inflation,
+        poh_config::PohConfig,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_program,
+    },
+    solana_stake_program::stake_state,
+    solana_vote_program::vote_state::{self, VoteState},
+    std::io,
+};
+
+pub struct GenesisConfigBuilder {
+    genesis_config: GenesisConfig,
+    features: Vec<Pubkey>,
+    activate_all_features: bool,
+}
+
+impl GenesisConfigBuilder {
+    /// Starts from the defaults `solana-genesis` uses for `cluster_type`. All features are
+    /// activated for development clusters, like `solana-genesis` does.
+    pub fn new(cluster_type: ClusterType) -> Self {
+        let slots_per_epoch = match cluster_type {
+            ClusterType::Development => clock::DEFAULT_DEV_SLOTS_PER_EPOCH,
+            ClusterType::Devnet | ClusterType::Testnet | ClusterType::MainnetBeta => {
+                clock::DEFAULT_SLOTS_PER_EPOCH
+            }
+        };
+        Self {
+            genesis_config: GenesisConfig {
+                native_instruction_processors: vec![],
+                ticks_per_slot: clock::DEFAULT_TICKS_PER_SLOT,
+                epoch_schedule: EpochSchedule::custom(slots_per_epoch, slots_per_epoch, false),
+                cluster_type,
+                ..GenesisConfig::default()
+            },
+            features: vec![],
+            activate_all_features: cluster_type == ClusterType::Development,
+        }
+    }
+
+    pub fn creation_time(mut self, creation_time: UnixTimestamp) -> Self {
+        self.genesis_config.creation_time = creation_time;
+        self
+    }
+
+    pub fn ticks_per_slot(mut self, ticks_per_slot: u64) -> Self {
+        self.genesis_config.ticks_per_slot = ticks_per_slot;
+        self
+    }
+
+    pub fn poh_config(mut self, poh_config: PohConfig) -> Self {
+        self.genesis_config.poh_config = poh_config;
+        self
+    }
+
+    pub fn epoch_schedule(mut self, epoch_schedule: EpochSchedule) -> Self {
+        self.genesis_config.epoch_schedule = epoch_schedule;
+        self
+    }
+
+    pub fn inflation(mut self, inflation: Inflation) -> Self {
+        self.genesis_config.inflation = inflation;
+        self
+    }
+
+    pub fn fee_rate_governor(mut self, fee_rate_governor: FeeRateGovernor) -> Self {
+        self.genesis_config.fee_rate_governor = fee_rate_governor;
+        self
+    }
+
+    /// Rent exempt balances of programs and bootstrap validators are computed with the rent set
+    /// at the time they are added, so this should be called first
+    pub fn rent(mut self, rent: Rent) -> Self {
+        self.genesis_config.rent = rent;
+        self
+    }
+
+    /// Registers a native program, in addition to the builtins every bank loads
+    pub fn builtin(mut self, name: &str, program_id: Pubkey) -> Self {
+        self.genesis_config
+            .native_instruction_processors
+            .push((name.to_string(), program_id));
+        self
+    }
+
+    pub fn account(mut self, pubkey: Pubkey, account: AccountSharedData) -> Self {
+        self.genesis_config.add_account(pubkey, account);
+        self
+    }
+
+    /// Adds the accounts returned by `accounts_fn`, which sees the genesis config built so far
+    pub fn accounts_from_fn<F>(mut self, accounts_fn: F) -> Self
+    where
+        F: FnOnce(&GenesisConfig) -> Vec<(Pubkey, AccountSharedData)>,
+    {
+        for (pubkey, account) in accounts_fn(&self.genesis_config) {
+            self.genesis_config.add_account(pubkey, account);
+        }
+        self
+    }
+
+    /// Adds the accounts of a file in the `--primordial-accounts-file` format
+    pub fn accounts_from_file(mut self, file: &str) -> io::Result<Self> {
+        load_genesis_accounts(file, &mut self.genesis_config)?;
+        Ok(self)
+    }
+
+    /// Adds a rent exempt, executable program account owned by `loader`
+    pub fn program(mut self, address: Pubkey, loader: Pubkey, program_data: Vec<u8>) -> Self {
+        let lamports = self.genesis_config.rent.minimum_balance(program_data.len());
+        self.genesis_config.add_account(
+            address,
+            AccountSharedData::from(Account {
+                lamports,
+                data: program_data,
+                executable: true,
+                owner: loader,
+                rent_epoch: 0,
+            }),
+        );
+        self
+    }
+
+    /// Adds the identity, vote and stake accounts of a bootstrap validator, like
+    /// `--bootstrap-validator` does. The stake is authorized to `stake_authority`, or to the
+    /// identity if there is none.
+    #[allow(clippy::too_many_arguments)]
+    pub fn bootstrap_validator(
+        mut self,
+        identity_pubkey: Pubkey,
+        vote_pubkey: Pubkey,
+        stake_pubkey: Pubkey,
+        lamports: u64,
+        stake_lamports: u64,
+        commission: u8,
+        stake_authority: Option<Pubkey>,
+    ) -> Self {
+        let rent = &self.genesis_config.rent;
+        let vote_account = vote_state::create_account_with_authorized(
+            &identity_pubkey,
+            &identity_pubkey,
+            &identity_pubkey,
+            commission,
+            VoteState::get_rent_exempt_reserve(rent).max(1),
+        );
+        let stake_account = stake_state::create_account(
+            stake_authority.as_ref().unwrap_or(&identity_pubkey),
+            &vote_pubkey,
+            &vote_account,
+            rent,
+            stake_lamports,
+        );
+
+        self.genesis_config.add_account(
+            identity_pubkey,
+            AccountSharedData::new(lamports, 0, &system_program::id()),
+        );
+        self.genesis_config.add_account(stake_pubkey, stake_account);
+        self.genesis_config.add_account(vote_pubkey, vote_account);
+        self
+    }
+
+    pub fn activate_feature(mut self, feature_id: Pubkey) -> Self {
+        self.features.push(feature_id);
+        self
+    }
+
+    pub fn activate_all_features(mut self) -> Self {
+        self.activate_all_features = true;
+        self
+    }
+
+    /// Adds the stake program's genesis accounts and the activated features
+    pub fn build(mut self) -> GenesisConfig {
+        solana_stake_program::add_genesis_accounts(&mut self.genesis_config);
+        if self.activate_all_features {
+            solana_runtime::genesis_utils::activate_all_features(&mut self.genesis_config);
+        }
+        for feature_id in self.features {
+            solana_runtime::genesis_utils::activate_feature(&mut self.genesis_config, feature_id);
+        }
+        self.genesis_config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{bpf_loader, feature, feature_set, stake},
+        std::io::Write,
+    };
+
+    #[test]
+    fn test_genesis_config_builder() {
+        let identity = Pubkey::new_unique();
+        let vote = Pubkey::new_unique();
+        let stake_pubkey = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let builtin = Pubkey::new_unique();
+        let feature_id = feature_set::gate_large_block::id();
+
+        let genesis_config = GenesisConfigBuilder::new(ClusterType::Testnet)
+            .ticks_per_slot(8)
+            .inflation(Inflation::new_disabled())
+            .builtin("my_builtin", builtin)
+            .bootstrap_validator(identity, vote, stake_pubkey, 42, 1_000_000_000, 10, None)
+            .program(program, bpf_loader::id(), vec![1, 2, 3])
+            .accounts_from_fn(|genesis_config| {
+                vec![(
+                    Pubkey::new_unique(),
+                    AccountSharedData::new(genesis_config.ticks_per_slot, 0, &system_program::id()),
+                )]
+            })
+            .activate_feature(feature_id)
+            .build();
+
+        assert_eq!(genesis_config.ticks_per_slot, 8);
+        assert_eq!(genesis_config.inflation, Inflation::new_disabled());
+        assert_eq!(
+            genesis_config.epoch_schedule.slots_per_epoch,
+            clock::DEFAULT_SLOTS_PER_EPOCH
+        );
+        assert_eq!(
+            genesis_config.native_instruction_processors,
+            vec![("my_builtin".to_string(), builtin)]
+        );
+        assert_eq!(genesis_config.accounts[&identity].lamports, 42);
+        assert_eq!(
+            genesis_config.accounts[&vote].owner,
+            solana_vote_program::id()
+        );
+        assert_eq!(
+            genesis_config.accounts[&stake_pubkey].owner,
+            stake::program::id()
+        );
+        assert!(genesis_config.accounts[&program].executable);
+        assert_eq!(
+            genesis_config.accounts[&program].lamports,
+            genesis_config.rent.minimum_balance(3)
+        );
+        assert_eq!(
+            genesis_config
+                .accounts
+                .values()
+                .filter(|account| account.lamports == 8)
+                .count(),
+            1
+        );
+        assert!(genesis_config.accounts.contains_key(&stake::config::id()));
+        // Only the requested feature is active outside of development clusters
+        assert_eq!(
+            genesis_config
+                .accounts
+                .values()
+                .filter(|account| account.owner == feature::id())
+                .count(),
+            1
+        );
+        assert_eq!(genesis_config.accounts[&feature_id].owner, feature::id());
+
+        let genesis_config = GenesisConfigBuilder::new(ClusterType::Development).build();
+        assert_eq!(
+            genesis_config.epoch_schedule.slots_per_epoch,
+            clock::DEFAULT_DEV_SLOTS_PER_EPOCH
+        );
+        assert!(genesis_config.accounts.contains_key(&feature_id));
+    }
+
+    #[test]
+    fn test_genesis_config_builder_accounts_from_file() {
+        let pubkey = Pubkey::new_unique();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "{}:\n  balance: 7\n  owner: {}\n  data: ~\n  executable: false",
+            pubkey,
+            system_program::id()
+        )
+        .unwrap();
+
+        let genesis_config = GenesisConfigBuilder::new(ClusterType::Development)
+            .accounts_from_file(file.path().to_str().unwrap())
+            .unwrap()
+            .build();
+        assert_eq!(genesis_config.accounts[&pubkey].lamports, 7);
+
+        assert!(GenesisConfigBuilder::new(ClusterType::Development)
+            .accounts_from_file("no-such-file")
+            .is_err());
+    }
+}
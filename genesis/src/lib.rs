@@ -1,10 +1,20 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod address_generator;
+pub mod builder;
 pub mod genesis_accounts;
 pub mod stakes;
 pub mod unlocks;
 
-use serde::{Deserialize, Serialize};
+use {
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        genesis_config::GenesisConfig,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+    },
+    std::{collections::HashMap, error, fs::File, io, str::FromStr},
+};
 
 /// An account where the data is encoded as a Base64 string.
 #[derive(Serialize, Deserialize, Debug)]
@@ -14,3 +24,401 @@ pub struct Base64Account {
     pub data: String,
     pub executable: bool,
 }
+
+pub enum AccountFileFormat {
+    Pubkey,
+    Keypair,
+}
+
+fn pubkey_from_str(key_str: &str) -> Result<Pubkey, Box<dyn error::Error>> {
+    Pubkey::from_str(key_str).or_else(|_| {
+        let bytes: Vec<u8> = serde_json::from_str(key_str)?;
+        let keypair = Keypair::from_bytes(&bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        Ok(keypair.pubkey())
+    })
+}
+
+pub fn load_genesis_accounts(file: &str, genesis_config: &mut GenesisConfig) -> io::Result<u64> {
+    let mut lamports = 0;
+    let accounts_file = File::open(file)?;
+
+    let genesis_accounts: HashMap<String, Base64Account> =
+        serde_yaml::from_reader(accounts_file)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))?;
+
+    for (key, account_details) in genesis_accounts {
+        let pubkey = pubkey_from_str(key.as_str()).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("Invalid pubkey/keypair {}: {:?}", key, err),
+            )
+        })?;
+
+        let owner_program_id = Pubkey::from_str(account_details.owner.as_str()).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("Invalid owner: {}: {:?}", account_details.owner, err),
+            )
+        })?;
+
+        let mut account = AccountSharedData::new(account_details.balance, 0, &owner_program_id);
+        if account_details.data != "~" {
+            account.set_data(
+                base64::decode(account_details.data.as_str()).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("Invalid account data: {}: {:?}", account_details.data, err),
+                    )
+                })?,
+            );
+        }
+        account.set_executable(account_details.executable);
+        lamports += account.lamports();
+        genesis_config.add_account(pubkey, account);
+    }
+
+    Ok(lamports)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{fs::remove_file, io::Write, path::Path},
+    };
+
+    #[test]
+    fn test_append_primordial_accounts_to_genesis() {
+        // Test invalid file returns error
+        assert!(load_genesis_accounts("unknownfile", &mut GenesisConfig::default()).is_err());
+
+        let mut genesis_config = GenesisConfig::default();
+
+        let mut genesis_accounts = HashMap::new();
+        genesis_accounts.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 2,
+                executable: false,
+                data: String::from("aGVsbG8="),
+            },
+        );
+        genesis_accounts.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 1,
+                executable: true,
+                data: String::from("aGVsbG8gd29ybGQ="),
+            },
+        );
+        genesis_accounts.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 3,
+                executable: true,
+                data: String::from("bWUgaGVsbG8gdG8gd29ybGQ="),
+            },
+        );
+
+        let serialized = serde_yaml::to_string(&genesis_accounts).unwrap();
+        let path = Path::new("test_append_primordial_accounts_to_genesis.yml");
+        let mut file = File::create(path).unwrap();
+        file.write_all(&serialized.into_bytes()).unwrap();
+
+        load_genesis_accounts(
+            "test_append_primordial_accounts_to_genesis.yml",
+            &mut genesis_config,
+        )
+        .expect("test_append_primordial_accounts_to_genesis.yml");
+        // Test valid file returns ok
+
+        remove_file(path).unwrap();
+
+        {
+            // Test all accounts were added
+            assert_eq!(genesis_config.accounts.len(), genesis_accounts.len());
+
+            // Test account data matches
+            for (pubkey_str, b64_account) in genesis_accounts.iter() {
+                let pubkey = pubkey_str.parse().unwrap();
+                assert_eq!(
+                    b64_account.owner,
+                    genesis_config.accounts[&pubkey].owner.to_string()
+                );
+
+                assert_eq!(
+                    b64_account.balance,
+                    genesis_config.accounts[&pubkey].lamports
+                );
+
+                assert_eq!(
+                    b64_account.executable,
+                    genesis_config.accounts[&pubkey].executable
+                );
+
+                assert_eq!(
+                    b64_account.data,
+                    base64::encode(&genesis_config.accounts[&pubkey].data)
+                );
+            }
+        }
+
+        // Test more accounts can be appended
+        let mut genesis_accounts1 = HashMap::new();
+        genesis_accounts1.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 6,
+                executable: true,
+                data: String::from("eW91IGFyZQ=="),
+            },
+        );
+        genesis_accounts1.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 5,
+                executable: false,
+                data: String::from("bWV0YSBzdHJpbmc="),
+            },
+        );
+        genesis_accounts1.insert(
+            solana_sdk::pubkey::new_rand().to_string(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 10,
+                executable: false,
+                data: String::from("YmFzZTY0IHN0cmluZw=="),
+            },
+        );
+
+        let serialized = serde_yaml::to_string(&genesis_accounts1).unwrap();
+        let path = Path::new("test_append_primordial_accounts_to_genesis.yml");
+        let mut file = File::create(path).unwrap();
+        file.write_all(&serialized.into_bytes()).unwrap();
+
+        load_genesis_accounts(
+            "test_append_primordial_accounts_to_genesis.yml",
+            &mut genesis_config,
+        )
+        .expect("test_append_primordial_accounts_to_genesis.yml");
+
+        remove_file(path).unwrap();
+
+        // Test total number of accounts is correct
+        assert_eq!(
+            genesis_config.accounts.len(),
+            genesis_accounts.len() + genesis_accounts1.len()
+        );
+
+        // Test old accounts are still there
+        for (pubkey_str, b64_account) in genesis_accounts.iter() {
+            let pubkey = &pubkey_str.parse().unwrap();
+            assert_eq!(
+                b64_account.balance,
+                genesis_config.accounts[pubkey].lamports,
+            );
+        }
+
+        // Test new account data matches
+        for (pubkey_str, b64_account) in genesis_accounts1.iter() {
+            let pubkey = pubkey_str.parse().unwrap();
+            assert_eq!(
+                b64_account.owner,
+                genesis_config.accounts[&pubkey].owner.to_string()
+            );
+
+            assert_eq!(
+                b64_account.balance,
+                genesis_config.accounts[&pubkey].lamports,
+            );
+
+            assert_eq!(
+                b64_account.executable,
+                genesis_config.accounts[&pubkey].executable,
+            );
+
+            assert_eq!(
+                b64_account.data,
+                base64::encode(&genesis_config.accounts[&pubkey].data),
+            );
+        }
+
+        // Test accounts from keypairs can be appended
+        let account_keypairs: Vec<_> = (0..3).map(|_| Keypair::new()).collect();
+        let mut genesis_accounts2 = HashMap::new();
+        genesis_accounts2.insert(
+            serde_json::to_string(&account_keypairs[0].to_bytes().to_vec()).unwrap(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 20,
+                executable: true,
+                data: String::from("Y2F0IGRvZw=="),
+            },
+        );
+        genesis_accounts2.insert(
+            serde_json::to_string(&account_keypairs[1].to_bytes().to_vec()).unwrap(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 15,
+                executable: false,
+                data: String::from("bW9ua2V5IGVsZXBoYW50"),
+            },
+        );
+        genesis_accounts2.insert(
+            serde_json::to_string(&account_keypairs[2].to_bytes().to_vec()).unwrap(),
+            Base64Account {
+                owner: solana_sdk::pubkey::new_rand().to_string(),
+                balance: 30,
+                executable: true,
+                data: String::from("Y29tYSBtb2Nh"),
+            },
+        );
+
+        let serialized = serde_yaml::to_string(&genesis_accounts2).unwrap();
+        let path = Path::new("test_append_primordial_accounts_to_genesis.yml");
+        let mut file = File::create(path).unwrap();
+        file.write_all(&serialized.into_bytes()).unwrap();
+
+        load_genesis_accounts(
+            "test_append_primordial_accounts_to_genesis.yml",
+            &mut genesis_config,
+        )
+        .expect("genesis");
+
+        remove_file(path).unwrap();
+
+        // Test total number of accounts is correct
+        assert_eq!(
+            genesis_config.accounts.len(),
+            genesis_accounts.len() + genesis_accounts1.len() + genesis_accounts2.len()
+        );
+
+        // Test old accounts are still there
+        for (pubkey_str, b64_account) in genesis_accounts {
+            let pubkey = pubkey_str.parse().unwrap();
+            assert_eq!(
+                b64_account.balance,
+                genesis_config.accounts[&pubkey].lamports,
+            );
+        }
+
+        // Test new account data matches
+        for (pubkey_str, b64_account) in genesis_accounts1 {
+            let pubkey = pubkey_str.parse().unwrap();
+            assert_eq!(
+                b64_account.owner,
+                genesis_config.accounts[&pubkey].owner.to_string(),
+            );
+
+            assert_eq!(
+                b64_account.balance,
+                genesis_config.accounts[&pubkey].lamports,
+            );
+
+            assert_eq!(
+                b64_account.executable,
+                genesis_config.accounts[&pubkey].executable,
+            );
+
+            assert_eq!(
+                b64_account.data,
+                base64::encode(&genesis_config.accounts[&pubkey].data),
+            );
+        }
+
+        // Test account data for keypairs matches
+        account_keypairs.iter().for_each(|keypair| {
+            let keypair_str = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+            let pubkey = keypair.pubkey();
+            assert_eq!(
+                genesis_accounts2[&keypair_str].owner,
+                genesis_config.accounts[&pubkey].owner.to_string(),
+            );
+
+            assert_eq!(
+                genesis_accounts2[&keypair_str].balance,
+                genesis_config.accounts[&pubkey].lamports,
+            );
+
+            assert_eq!(
+                genesis_accounts2[&keypair_str].executable,
+                genesis_config.accounts[&pubkey].executable,
+            );
+
+            assert_eq!(
+                genesis_accounts2[&keypair_str].data,
+                base64::encode(&genesis_config.accounts[&pubkey].data),
+            );
+        });
+    }
+
+    #[test]
+    fn test_genesis_account_struct_compatibility() {
+        let yaml_string_pubkey = "---
+98frSc8R8toHoS3tQ1xWSvHCvGEADRM9hAm5qmUKjSDX:
+  balance: 4
+  owner: Gw6S9CPzR8jHku1QQMdiqcmUKjC2dhJ3gzagWduA6PGw
+  data:
+  executable: true
+88frSc8R8toHoS3tQ1xWSvHCvGEADRM9hAm5qmUKjSDX:
+  balance: 3
+  owner: Gw7S9CPzR8jHku1QQMdiqcmUKjC2dhJ3gzagWduA6PGw
+  data: ~
+  executable: true
+6s36rsNPDfRSvzwek7Ly3mQu9jUMwgqBhjePZMV6Acp4:
+  balance: 2
+  owner: DBC5d45LUHTCrq42ZmCdzc8A8ufwTaiYsL9pZY7KU6TR
+  data: aGVsbG8=
+  executable: false
+8Y98svZv5sPHhQiPqZvqA5Z5djQ8hieodscvb61RskMJ:
+  balance: 1
+  owner: DSknYr8cPucRbx2VyssZ7Yx3iiRqNGD38VqVahkUvgV1
+  data: aGVsbG8gd29ybGQ=
+  executable: true";
+
+        let tmpfile = tempfile::NamedTempFile::new().unwrap();
+        let path = tmpfile.path();
+        let mut file = File::create(path).unwrap();
+        file.write_all(yaml_string_pubkey.as_bytes()).unwrap();
+
+        let mut genesis_config = GenesisConfig::default();
+        load_genesis_accounts(path.to_str().unwrap(), &mut genesis_config).expect("genesis");
+        remove_file(path).unwrap();
+
+        assert_eq!(genesis_config.accounts.len(), 4);
+
+        let yaml_string_keypair = "---
+\"[17,12,234,59,35,246,168,6,64,36,169,164,219,96,253,79,238,202,164,160,195,89,9,96,179,117,255,239,32,64,124,66,233,130,19,107,172,54,86,32,119,148,4,39,199,40,122,230,249,47,150,168,163,159,83,233,97,18,25,238,103,25,253,108]\":
+  balance: 20
+  owner: 9ZfsP6Um1KU8d5gNzTsEbSJxanKYp5EPF36qUu4FJqgp
+  data: Y2F0IGRvZw==
+  executable: true
+\"[36,246,244,43,37,214,110,50,134,148,148,8,205,82,233,67,223,245,122,5,149,232,213,125,244,182,26,29,56,224,70,45,42,163,71,62,222,33,229,54,73,136,53,174,128,103,247,235,222,27,219,129,180,77,225,174,220,74,201,123,97,155,159,234]\":
+  balance: 15
+  owner: F9dmtjJPi8vfLu1EJN4KkyoGdXGmVfSAhxz35Qo9RDCJ
+  data: bW9ua2V5IGVsZXBoYW50
+  executable: false
+\"[103,27,132,107,42,149,72,113,24,138,225,109,209,31,158,6,26,11,8,76,24,128,131,215,156,80,251,114,103,220,111,235,56,22,87,5,209,56,53,12,224,170,10,66,82,42,11,138,51,76,120,27,166,200,237,16,200,31,23,5,57,22,131,221]\":
+  balance: 30
+  owner: AwAR5mAbNPbvQ4CvMeBxwWE8caigQoMC2chkWAbh2b9V
+  data: Y29tYSBtb2Nh
+  executable: true";
+
+        let tmpfile = tempfile::NamedTempFile::new().unwrap();
+        let path = tmpfile.path();
+        let mut file = File::create(path).unwrap();
+        file.write_all(yaml_string_keypair.as_bytes()).unwrap();
+
+        let mut genesis_config = GenesisConfig::default();
+        load_genesis_accounts(path.to_str().unwrap(), &mut genesis_config).expect("genesis");
+        remove_file(path).unwrap();
+
+        assert_eq!(genesis_config.accounts.len(), 3);
+    }
+}
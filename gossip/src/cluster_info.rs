@@ -2745,6 +2745,25 @@ pub struct Sockets {
     pub tpu_quic: UdpSocket,
 }
 
+/// Addresses a group of validator sockets is bound to and advertised at in gossip
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeInterface {
+    pub bind_ip: Option<IpAddr>,
+    pub public_ip: Option<IpAddr>,
+}
+
+/// Per-service interfaces for validators that separate public transaction ingress from
+/// private inter-validator links
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeInterfaces {
+    /// TVU, TVU forwards, retransmit and broadcast sockets
+    pub tvu: NodeInterface,
+    /// TPU, TPU forwards, TPU vote and TPU QUIC sockets
+    pub tpu: NodeInterface,
+    /// Repair, serve repair and ancestor hashes sockets
+    pub repair: NodeInterface,
+}
+
 #[derive(Debug)]
 pub struct Node {
     pub info: ContactInfo,
@@ -2903,53 +2922,82 @@ impl Node {
         gossip_addr: &SocketAddr,
         port_range: PortRange,
         bind_ip_addr: IpAddr,
+    ) -> Node {
+        Self::new_multihomed(
+            pubkey,
+            gossip_addr,
+            port_range,
+            bind_ip_addr,
+            &NodeInterfaces::default(),
+        )
+    }
+
+    /// Like `new_with_external_ip`, but binds and advertises the TVU, TPU and repair sockets
+    /// on the interfaces given in `interfaces`, falling back to `bind_ip_addr` and the
+    /// gossip address for any that are unset.
+    pub fn new_multihomed(
+        pubkey: &Pubkey,
+        gossip_addr: &SocketAddr,
+        port_range: PortRange,
+        bind_ip_addr: IpAddr,
+        interfaces: &NodeInterfaces,
     ) -> Node {
         let (gossip_port, (gossip, ip_echo)) =
             Self::get_gossip_port(gossip_addr, port_range, bind_ip_addr);
 
+        let tvu_bind_ip = interfaces.tvu.bind_ip.unwrap_or(bind_ip_addr);
+        let tpu_bind_ip = interfaces.tpu.bind_ip.unwrap_or(bind_ip_addr);
+        let repair_bind_ip = interfaces.repair.bind_ip.unwrap_or(bind_ip_addr);
+        let tvu_public_ip = interfaces.tvu.public_ip.unwrap_or_else(|| gossip_addr.ip());
+        let tpu_public_ip = interfaces.tpu.public_ip.unwrap_or_else(|| gossip_addr.ip());
+        let repair_public_ip = interfaces
+            .repair
+            .public_ip
+            .unwrap_or_else(|| gossip_addr.ip());
+
         let (tvu_port, tvu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu multi_bind");
+            multi_bind_in_range(tvu_bind_ip, port_range, 8).expect("tvu multi_bind");
 
         let (tvu_forwards_port, tvu_forwards_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu_forwards multi_bind");
+            multi_bind_in_range(tvu_bind_ip, port_range, 8).expect("tvu_forwards multi_bind");
 
         let (tpu_port, tpu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
+            multi_bind_in_range(tpu_bind_ip, port_range, 32).expect("tpu multi_bind");
 
         let (_tpu_port_quic, tpu_quic) = Self::bind(
-            bind_ip_addr,
+            tpu_bind_ip,
             (tpu_port + QUIC_PORT_OFFSET, tpu_port + QUIC_PORT_OFFSET + 1),
         );
 
         let (tpu_forwards_port, tpu_forwards_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tpu_forwards multi_bind");
+            multi_bind_in_range(tpu_bind_ip, port_range, 8).expect("tpu_forwards multi_bind");
 
         let (tpu_vote_port, tpu_vote_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 1).expect("tpu_vote multi_bind");
+            multi_bind_in_range(tpu_bind_ip, port_range, 1).expect("tpu_vote multi_bind");
 
         let (_, retransmit_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("retransmit multi_bind");
+            multi_bind_in_range(tvu_bind_ip, port_range, 8).expect("retransmit multi_bind");
 
-        let (repair_port, repair) = Self::bind(bind_ip_addr, port_range);
-        let (serve_repair_port, serve_repair) = Self::bind(bind_ip_addr, port_range);
+        let (repair_port, repair) = Self::bind(repair_bind_ip, port_range);
+        let (serve_repair_port, serve_repair) = Self::bind(repair_bind_ip, port_range);
 
         let (_, broadcast) =
-            multi_bind_in_range(bind_ip_addr, port_range, 4).expect("broadcast multi_bind");
+            multi_bind_in_range(tvu_bind_ip, port_range, 4).expect("broadcast multi_bind");
 
-        let (_, ancestor_hashes_requests) = Self::bind(bind_ip_addr, port_range);
+        let (_, ancestor_hashes_requests) = Self::bind(repair_bind_ip, port_range);
 
         let info = ContactInfo {
             id: *pubkey,
             gossip: SocketAddr::new(gossip_addr.ip(), gossip_port),
-            tvu: SocketAddr::new(gossip_addr.ip(), tvu_port),
-            tvu_forwards: SocketAddr::new(gossip_addr.ip(), tvu_forwards_port),
-            repair: SocketAddr::new(gossip_addr.ip(), repair_port),
-            tpu: SocketAddr::new(gossip_addr.ip(), tpu_port),
-            tpu_forwards: SocketAddr::new(gossip_addr.ip(), tpu_forwards_port),
-            tpu_vote: SocketAddr::new(gossip_addr.ip(), tpu_vote_port),
+            tvu: SocketAddr::new(tvu_public_ip, tvu_port),
+            tvu_forwards: SocketAddr::new(tvu_public_ip, tvu_forwards_port),
+            repair: SocketAddr::new(repair_public_ip, repair_port),
+            tpu: SocketAddr::new(tpu_public_ip, tpu_port),
+            tpu_forwards: SocketAddr::new(tpu_public_ip, tpu_forwards_port),
+            tpu_vote: SocketAddr::new(tpu_public_ip, tpu_vote_port),
             rpc: socketaddr_any!(),
             rpc_pubsub: socketaddr_any!(),
-            serve_repair: SocketAddr::new(gossip_addr.ip(), serve_repair_port),
+            serve_repair: SocketAddr::new(repair_public_ip, serve_repair_port),
             wallclock: 0,
             shred_version: 0,
         };
@@ -3532,6 +3580,41 @@ mod tests {
         assert_eq!(node.sockets.gossip.local_addr().unwrap().port(), port);
     }
 
+    #[test]
+    fn new_multihomed_test() {
+        let bind_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        let tpu_bind_ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let gossip_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let tpu_public_ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let interfaces = NodeInterfaces {
+            tpu: NodeInterface {
+                bind_ip: Some(tpu_bind_ip),
+                public_ip: Some(tpu_public_ip),
+            },
+            ..NodeInterfaces::default()
+        };
+        let node = Node::new_multihomed(
+            &solana_sdk::pubkey::new_rand(),
+            &SocketAddr::new(gossip_ip, 0),
+            VALIDATOR_PORT_RANGE,
+            bind_ip,
+            &interfaces,
+        );
+
+        check_socket(&node.sockets.gossip, bind_ip, VALIDATOR_PORT_RANGE);
+        check_socket(&node.sockets.repair, bind_ip, VALIDATOR_PORT_RANGE);
+        check_sockets(&node.sockets.tvu, bind_ip, VALIDATOR_PORT_RANGE);
+        check_sockets(&node.sockets.tpu, tpu_bind_ip, VALIDATOR_PORT_RANGE);
+        check_sockets(&node.sockets.tpu_vote, tpu_bind_ip, VALIDATOR_PORT_RANGE);
+
+        assert_eq!(node.info.gossip.ip(), gossip_ip);
+        assert_eq!(node.info.tvu.ip(), gossip_ip);
+        assert_eq!(node.info.repair.ip(), gossip_ip);
+        assert_eq!(node.info.tpu.ip(), tpu_public_ip);
+        assert_eq!(node.info.tpu_forwards.ip(), tpu_public_ip);
+        assert_eq!(node.info.tpu_vote.ip(), tpu_public_ip);
+    }
+
     //test that all cluster_info objects only generate signed messages
     //when constructed with keypairs
     #[test]
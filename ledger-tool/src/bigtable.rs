@@ -7,19 +7,23 @@ use {
     log::info,
     serde_json::json,
     solana_clap_utils::{
-        input_parsers::pubkey_of,
-        input_validators::{is_slot, is_valid_pubkey},
+        input_parsers::{pubkey_of, pubkeys_of},
+        input_validators::{is_parsable, is_slot, is_valid_pubkey},
     },
     solana_cli_output::{
         display::println_transaction, CliBlock, CliTransaction, CliTransactionConfirmation,
         OutputFormat,
     },
-    solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType},
+    solana_ledger::{
+        bigtable_upload::ConfirmedBlockUploadConfig, blockstore::Blockstore,
+        blockstore_db::AccessType,
+    },
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_storage_bigtable::{BigTableLedgerStorage, LedgerStorage},
     solana_transaction_status::{ConfirmedBlock, Encodable, UiTransactionEncoding},
     std::{
         collections::HashSet,
-        path::Path,
+        path::{Path, PathBuf},
         process::exit,
         result::Result,
         sync::{atomic::AtomicBool, Arc},
@@ -30,20 +34,19 @@ async fn upload(
     blockstore: Blockstore,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
-    allow_missing_metadata: bool,
-    force_reupload: bool,
+    config: ConfirmedBlockUploadConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None, None)
+    let read_only = config.dry_run;
+    let bigtable = BigTableLedgerStorage::new(read_only, None, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
     solana_ledger::bigtable_upload::upload_confirmed_blocks(
         Arc::new(blockstore),
-        bigtable,
+        Arc::new(bigtable),
         starting_slot,
         ending_slot,
-        allow_missing_metadata,
-        force_reupload,
+        config,
         Arc::new(AtomicBool::new(false)),
     )
     .await
@@ -51,15 +54,16 @@ async fn upload(
 
 async fn delete_slots(slots: Vec<Slot>, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
     let read_only = dry_run;
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(read_only, None, None)
+    let bigtable = BigTableLedgerStorage::new(read_only, None, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
-    solana_ledger::bigtable_delete::delete_confirmed_blocks(bigtable, slots, dry_run).await
+    solana_ledger::bigtable_delete::delete_confirmed_blocks(Arc::new(bigtable), slots, dry_run)
+        .await
 }
 
 async fn first_available_block() -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None, None).await?;
+    let bigtable = BigTableLedgerStorage::new(true, None, None).await?;
     match bigtable.get_first_available_block().await? {
         Some(block) => println!("{}", block),
         None => println!("No blocks available"),
@@ -69,7 +73,7 @@ async fn first_available_block() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None, None)
+    let bigtable = BigTableLedgerStorage::new(false, None, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -87,7 +91,7 @@ async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn st
 }
 
 async fn blocks(starting_slot: Slot, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None, None)
+    let bigtable = BigTableLedgerStorage::new(false, None, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -105,7 +109,7 @@ async fn compare_blocks(
 ) -> Result<(), Box<dyn std::error::Error>> {
     assert!(!credential_path.is_empty());
 
-    let owned_bigtable = solana_storage_bigtable::LedgerStorage::new(false, None, None)
+    let owned_bigtable = BigTableLedgerStorage::new(false, None, None)
         .await
         .map_err(|err| format!("failed to connect to owned bigtable: {:?}", err))?;
     let owned_bigtable_slots = owned_bigtable
@@ -115,10 +119,9 @@ async fn compare_blocks(
         "owned bigtable {} blocks found ",
         owned_bigtable_slots.len()
     );
-    let reference_bigtable =
-        solana_storage_bigtable::LedgerStorage::new(false, None, Some(credential_path))
-            .await
-            .map_err(|err| format!("failed to connect to reference bigtable: {:?}", err))?;
+    let reference_bigtable = BigTableLedgerStorage::new(false, None, Some(credential_path))
+        .await
+        .map_err(|err| format!("failed to connect to reference bigtable: {:?}", err))?;
 
     let reference_bigtable_slots = reference_bigtable
         .get_confirmed_blocks(starting_slot, limit)
@@ -146,7 +149,7 @@ async fn confirm(
     verbose: bool,
     output_format: OutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None, None)
+    let bigtable = BigTableLedgerStorage::new(false, None, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
@@ -199,7 +202,7 @@ pub async fn transaction_history(
     show_transactions: bool,
     query_chunk_size: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let bigtable = solana_storage_bigtable::LedgerStorage::new(true, None, None).await?;
+    let bigtable = BigTableLedgerStorage::new(true, None, None).await?;
 
     let mut loaded_block: Option<(Slot, ConfirmedBlock)> = None;
     while limit > 0 {
@@ -331,6 +334,61 @@ impl BigTableSubCommand for App<'_, '_> {
                                     Note: reupload will *not* delete any data from the tx-by-addr table;\
                                     Use with care.",
                                 ),
+                        )
+                        .arg(
+                            Arg::with_name("addresses")
+                                .long("address")
+                                .validator(is_valid_pubkey)
+                                .value_name("ADDRESS")
+                                .takes_value(true)
+                                .multiple(true)
+                                .help(
+                                    "Only upload blocks with a transaction mentioning this address. \
+                                    May be specified multiple times",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("skip_vote_only_blocks")
+                                .long("skip-vote-only-blocks")
+                                .takes_value(false)
+                                .help("Don't upload blocks without any transaction other than votes"),
+                        )
+                        .arg(
+                            Arg::with_name("dry_run")
+                                .long("dry-run")
+                                .takes_value(false)
+                                .help("List the blocks that would be uploaded without uploading them"),
+                        )
+                        .arg(
+                            Arg::with_name("progress_file")
+                                .long("progress-file")
+                                .value_name("FILE")
+                                .takes_value(true)
+                                .help(
+                                    "Record the last uploaded slot in FILE, and resume \
+                                    after the slot recorded there by a previous upload",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("num_parallel_uploads")
+                                .long("num-parallel-uploads")
+                                .validator(is_parsable::<usize>)
+                                .value_name("NUM")
+                                .takes_value(true)
+                                .default_value("32")
+                                .help("Number of blocks to upload in parallel"),
+                        )
+                        .arg(
+                            Arg::with_name("max_upload_retries")
+                                .long("max-upload-retries")
+                                .validator(is_parsable::<usize>)
+                                .value_name("NUM")
+                                .takes_value(true)
+                                .default_value("5")
+                                .help(
+                                    "Number of times to retry a failed block upload, \
+                                    with exponential backoff",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -516,21 +574,30 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
         ("upload", Some(arg_matches)) => {
             let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
-            let allow_missing_metadata = arg_matches.is_present("allow_missing_metadata");
-            let force_reupload = arg_matches.is_present("force_reupload");
+            let config = ConfirmedBlockUploadConfig {
+                force_reupload: arg_matches.is_present("force_reupload"),
+                allow_missing_metadata: arg_matches.is_present("allow_missing_metadata"),
+                addresses: pubkeys_of(arg_matches, "addresses")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+                skip_vote_only_blocks: arg_matches.is_present("skip_vote_only_blocks"),
+                dry_run: arg_matches.is_present("dry_run"),
+                progress_path: value_t!(arg_matches, "progress_file", PathBuf).ok(),
+                num_blocks_to_upload_in_parallel: value_t_or_exit!(
+                    arg_matches,
+                    "num_parallel_uploads",
+                    usize
+                ),
+                max_upload_retries: value_t_or_exit!(arg_matches, "max_upload_retries", usize),
+            };
             let blockstore = crate::open_blockstore(
                 &canonicalize_ledger_path(ledger_path),
                 AccessType::TryPrimaryThenSecondary,
                 None,
             );
 
-            runtime.block_on(upload(
-                blockstore,
-                starting_slot,
-                ending_slot,
-                allow_missing_metadata,
-                force_reupload,
-            ))
+            runtime.block_on(upload(blockstore, starting_slot, ending_slot, config))
         }
         ("delete-slots", Some(arg_matches)) => {
             let slots = values_t_or_exit!(arg_matches, "slots", Slot);
@@ -0,0 +1,468 @@
+/// The `export` subcommand
+use {
+    clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    itertools::Itertools,
+    log::*,
+    serde::Serialize,
+    solana_ledger::blockstore::Blockstore,
+    solana_sdk::{clock::Slot, instruction::CompiledInstruction},
+    solana_transaction_status::{VersionedConfirmedBlock, VersionedTransactionWithStatusMeta},
+    std::{
+        fs::{self, File},
+        marker::PhantomData,
+        path::{Path, PathBuf},
+        process::exit,
+        result::Result,
+    },
+};
+#[cfg(feature = "parquet-export")]
+use {
+    parquet::{
+        errors::ParquetError,
+        file::{
+            properties::WriterProperties,
+            writer::{FileWriter, RowGroupWriter, SerializedFileWriter},
+        },
+        record::RecordWriter,
+        schema::types::TypePtr,
+    },
+    parquet_derive::ParquetRecordWriter,
+    std::sync::Arc,
+};
+
+// Number of rows buffered before they are written out as a parquet row group
+#[cfg(feature = "parquet-export")]
+const PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+// Parquet output is only available with the `parquet-export` feature
+const EXPORT_FORMATS: &[&str] = &[
+    "csv",
+    #[cfg(feature = "parquet-export")]
+    "parquet",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    #[cfg(feature = "parquet-export")]
+    Parquet,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            #[cfg(feature = "parquet-export")]
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl From<&str> for ExportFormat {
+    fn from(string: &str) -> Self {
+        match string {
+            "csv" => ExportFormat::Csv,
+            #[cfg(feature = "parquet-export")]
+            "parquet" => ExportFormat::Parquet,
+            bad_format => panic!("Invalid export format: {}", bad_format),
+        }
+    }
+}
+
+/// A row of one of the exported files
+trait ExportRecord: Serialize + Sized {
+    #[cfg(feature = "parquet-export")]
+    fn parquet_schema(rows: &[Self]) -> Result<TypePtr, ParquetError>;
+
+    #[cfg(feature = "parquet-export")]
+    fn write_parquet_row_group(
+        rows: &[Self],
+        row_group: &mut Box<dyn RowGroupWriter>,
+    ) -> Result<(), ParquetError>;
+}
+
+#[cfg(feature = "parquet-export")]
+impl<T: Serialize> ExportRecord for T
+where
+    for<'a> &'a [T]: RecordWriter<T>,
+{
+    fn parquet_schema(rows: &[Self]) -> Result<TypePtr, ParquetError> {
+        rows.schema()
+    }
+
+    fn write_parquet_row_group(
+        rows: &[Self],
+        row_group: &mut Box<dyn RowGroupWriter>,
+    ) -> Result<(), ParquetError> {
+        rows.write_to_row_group(row_group)
+    }
+}
+
+#[cfg(not(feature = "parquet-export"))]
+impl<T: Serialize> ExportRecord for T {}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "parquet-export", derive(ParquetRecordWriter))]
+struct BlockRecord {
+    slot: u64,
+    parent_slot: u64,
+    blockhash: String,
+    previous_blockhash: String,
+    block_time: Option<i64>,
+    block_height: Option<u64>,
+    transaction_count: u64,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "parquet-export", derive(ParquetRecordWriter))]
+struct TransactionRecord {
+    slot: u64,
+    transaction_index: u64,
+    signature: String,
+    /// `None` for transactions without status metadata
+    success: Option<bool>,
+    error: Option<String>,
+    fee: Option<u64>,
+    instruction_count: u64,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "parquet-export", derive(ParquetRecordWriter))]
+struct InstructionRecord {
+    slot: u64,
+    transaction_index: u64,
+    signature: String,
+    /// Index of the top level instruction
+    instruction_index: u64,
+    /// Index within the inner instructions of `instruction_index`, `None` for
+    /// top level instructions
+    inner_instruction_index: Option<u64>,
+    program_id: String,
+    /// Space separated account addresses
+    accounts: String,
+    /// Base58 encoded instruction data
+    data: String,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "parquet-export", derive(ParquetRecordWriter))]
+struct BalanceChangeRecord {
+    slot: u64,
+    transaction_index: u64,
+    signature: String,
+    account: String,
+    pre_balance: u64,
+    post_balance: u64,
+}
+
+enum RecordSink<T> {
+    Csv {
+        writer: csv::Writer<File>,
+        _record: PhantomData<T>,
+    },
+    #[cfg(feature = "parquet-export")]
+    Parquet {
+        writer: SerializedFileWriter<File>,
+        rows: Vec<T>,
+    },
+}
+
+impl<T: ExportRecord> RecordSink<T> {
+    fn new(format: ExportFormat, path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::create(path)
+            .map_err(|err| format!("Unable to create {}: {}", path.display(), err))?;
+        Ok(match format {
+            ExportFormat::Csv => RecordSink::Csv {
+                writer: csv::WriterBuilder::new().from_writer(file),
+                _record: PhantomData,
+            },
+            #[cfg(feature = "parquet-export")]
+            ExportFormat::Parquet => {
+                let rows: Vec<T> = Vec::with_capacity(PARQUET_ROW_GROUP_SIZE);
+                let schema = T::parquet_schema(&rows)?;
+                let properties = Arc::new(WriterProperties::builder().build());
+                RecordSink::Parquet {
+                    writer: SerializedFileWriter::new(file, schema, properties)?,
+                    rows,
+                }
+            }
+        })
+    }
+
+    fn write(&mut self, record: T) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            RecordSink::Csv { writer, .. } => writer.serialize(record)?,
+            #[cfg(feature = "parquet-export")]
+            RecordSink::Parquet { rows, .. } => {
+                rows.push(record);
+                if rows.len() >= PARQUET_ROW_GROUP_SIZE {
+                    self.flush_row_group()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "parquet-export")]
+    fn flush_row_group(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let RecordSink::Parquet { writer, rows } = self {
+            if !rows.is_empty() {
+                let mut row_group = writer.next_row_group()?;
+                T::write_parquet_row_group(rows, &mut row_group)?;
+                writer.close_row_group(row_group)?;
+                rows.clear();
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match &mut self {
+            RecordSink::Csv { writer, .. } => writer.flush()?,
+            #[cfg(feature = "parquet-export")]
+            RecordSink::Parquet { .. } => {
+                self.flush_row_group()?;
+                if let RecordSink::Parquet { writer, .. } = &mut self {
+                    writer.close()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct ExportSinks {
+    blocks: RecordSink<BlockRecord>,
+    transactions: RecordSink<TransactionRecord>,
+    instructions: RecordSink<InstructionRecord>,
+    balance_changes: RecordSink<BalanceChangeRecord>,
+}
+
+impl ExportSinks {
+    fn new(format: ExportFormat, output_dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(output_dir)?;
+        let path = |name: &str| output_dir.join(format!("{}.{}", name, format.extension()));
+        Ok(Self {
+            blocks: RecordSink::new(format, &path("blocks"))?,
+            transactions: RecordSink::new(format, &path("transactions"))?,
+            instructions: RecordSink::new(format, &path("instructions"))?,
+            balance_changes: RecordSink::new(format, &path("balance_changes"))?,
+        })
+    }
+
+    fn export_block(
+        &mut self,
+        slot: Slot,
+        block: VersionedConfirmedBlock,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.blocks.write(BlockRecord {
+            slot,
+            parent_slot: block.parent_slot,
+            blockhash: block.blockhash,
+            previous_blockhash: block.previous_blockhash,
+            block_time: block.block_time,
+            block_height: block.block_height,
+            transaction_count: block.transactions.len() as u64,
+        })?;
+        for (transaction_index, transaction) in block.transactions.iter().enumerate() {
+            self.export_transaction(slot, transaction_index as u64, transaction)?;
+        }
+        Ok(())
+    }
+
+    fn export_transaction(
+        &mut self,
+        slot: Slot,
+        transaction_index: u64,
+        transaction_with_meta: &VersionedTransactionWithStatusMeta,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let VersionedTransactionWithStatusMeta { transaction, meta } = transaction_with_meta;
+        let signature = transaction.signatures[0].to_string();
+        let account_keys = transaction_with_meta.account_keys();
+        let instructions = transaction.message.instructions();
+
+        self.transactions.write(TransactionRecord {
+            slot,
+            transaction_index,
+            signature: signature.clone(),
+            success: meta.as_ref().map(|meta| meta.status.is_ok()),
+            error: meta
+                .as_ref()
+                .and_then(|meta| meta.status.as_ref().err())
+                .map(|err| err.to_string()),
+            fee: meta.as_ref().map(|meta| meta.fee),
+            instruction_count: instructions.len() as u64,
+        })?;
+
+        let instruction_record =
+            |instruction_index: usize,
+             inner_instruction_index: Option<usize>,
+             instruction: &CompiledInstruction| {
+                let address = |index: u8| {
+                    account_keys
+                        .get(usize::from(index))
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_default()
+                };
+                InstructionRecord {
+                    slot,
+                    transaction_index,
+                    signature: signature.clone(),
+                    instruction_index: instruction_index as u64,
+                    inner_instruction_index: inner_instruction_index.map(|index| index as u64),
+                    program_id: address(instruction.program_id_index),
+                    accounts: instruction.accounts.iter().map(|i| address(*i)).join(" "),
+                    data: bs58::encode(&instruction.data).into_string(),
+                }
+            };
+        for (instruction_index, instruction) in instructions.iter().enumerate() {
+            self.instructions
+                .write(instruction_record(instruction_index, None, instruction))?;
+        }
+        let inner_instructions = meta
+            .as_ref()
+            .and_then(|meta| meta.inner_instructions.as_ref());
+        for inner_instructions in inner_instructions.into_iter().flatten() {
+            for (inner_instruction_index, instruction) in
+                inner_instructions.instructions.iter().enumerate()
+            {
+                self.instructions.write(instruction_record(
+                    usize::from(inner_instructions.index),
+                    Some(inner_instruction_index),
+                    instruction,
+                ))?;
+            }
+        }
+
+        if let Some(meta) = meta {
+            let balances = meta.pre_balances.iter().zip(meta.post_balances.iter());
+            for (account, (pre_balance, post_balance)) in account_keys.iter().zip(balances) {
+                if pre_balance != post_balance {
+                    self.balance_changes.write(BalanceChangeRecord {
+                        slot,
+                        transaction_index,
+                        signature: signature.clone(),
+                        account: account.to_string(),
+                        pre_balance: *pre_balance,
+                        post_balance: *post_balance,
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.blocks.finish()?;
+        self.transactions.finish()?;
+        self.instructions.finish()?;
+        self.balance_changes.finish()
+    }
+}
+
+/// Exports the rooted blocks in `starting_slot..=ending_slot` into one file
+/// per record type in `output_dir`
+pub fn export_blocks(
+    blockstore: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    format: ExportFormat,
+    output_dir: &Path,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut sinks = ExportSinks::new(format, output_dir)?;
+    let mut exported_blocks = 0;
+    let slots = blockstore
+        .rooted_slot_iterator(starting_slot)?
+        .take_while(|slot| *slot <= ending_slot);
+    for slot in slots {
+        match blockstore.get_rooted_block(slot, false) {
+            Ok(block) => {
+                sinks.export_block(slot, block)?;
+                exported_blocks += 1;
+            }
+            Err(err) => warn!("Skipping slot {}: {:?}", slot, err),
+        }
+    }
+    sinks.finish()?;
+    Ok(exported_blocks)
+}
+
+fn parse_slot_range(range: &str) -> Result<(Slot, Slot), String> {
+    let (starting_slot, ending_slot) = range
+        .split_once('-')
+        .ok_or_else(|| format!("expected STARTING_SLOT-ENDING_SLOT, got {}", range))?;
+    let starting_slot = starting_slot
+        .parse::<Slot>()
+        .map_err(|err| format!("invalid starting slot: {}", err))?;
+    let ending_slot = ending_slot
+        .parse::<Slot>()
+        .map_err(|err| format!("invalid ending slot: {}", err))?;
+    if starting_slot > ending_slot {
+        return Err(format!(
+            "starting slot {} is greater than ending slot {}",
+            starting_slot, ending_slot
+        ));
+    }
+    Ok((starting_slot, ending_slot))
+}
+
+pub trait ExportSubCommand {
+    fn export_subcommand(self) -> Self;
+}
+
+impl ExportSubCommand for App<'_, '_> {
+    fn export_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("export")
+                .about(
+                    "Export the rooted blocks in a slot range as columnar files: \
+                     blocks, transactions, instructions and balance changes",
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(EXPORT_FORMATS)
+                        .default_value("csv")
+                        .help("Output file format"),
+                )
+                .arg(
+                    Arg::with_name("range")
+                        .long("range")
+                        .value_name("STARTING_SLOT-ENDING_SLOT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|range| parse_slot_range(&range).map(|_| ()))
+                        .help("Inclusive range of slots to export"),
+                )
+                .arg(
+                    Arg::with_name("output_dir")
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .default_value("export")
+                        .help("Directory to write the exported files into"),
+                ),
+        )
+    }
+}
+
+pub fn export_process_command(blockstore: &Blockstore, matches: &ArgMatches<'_>) {
+    let format = ExportFormat::from(matches.value_of("format").unwrap());
+    let (starting_slot, ending_slot) =
+        parse_slot_range(matches.value_of("range").unwrap()).unwrap();
+    let output_dir = PathBuf::from(value_t_or_exit!(matches, "output_dir", String));
+
+    match export_blocks(blockstore, starting_slot, ending_slot, format, &output_dir) {
+        Ok(exported_blocks) => println!(
+            "Exported {} blocks to {}",
+            exported_blocks,
+            output_dir.display()
+        ),
+        Err(err) => {
+            eprintln!("Export failed: {}", err);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+/// Comparison of the state of two ledgers at a slot for the `diff` subcommand
+use {
+    solana_ledger::blockstore::Blockstore,
+    solana_runtime::bank::Bank,
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        pubkey::Pubkey,
+    },
+    solana_transaction_status::VersionedTransactionWithStatusMeta,
+    std::collections::HashMap,
+};
+
+/// An account that differs between the two banks, along with the slot each bank last wrote it in
+#[derive(Debug)]
+pub struct AccountDivergence {
+    pub pubkey: Pubkey,
+    pub base: Option<(AccountSharedData, Slot)>,
+    pub other: Option<(AccountSharedData, Slot)>,
+}
+
+impl AccountDivergence {
+    /// Earliest slot in which either bank wrote the account, the latest slot the two banks could
+    /// have started to disagree about it
+    pub fn first_written_slot(&self) -> Slot {
+        self.base
+            .iter()
+            .chain(self.other.iter())
+            .map(|(_, slot)| *slot)
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AccountsDiff {
+    pub num_divergent: usize,
+    /// The divergent account written the earliest, which points at where the banks started to
+    /// disagree
+    pub first_divergent: Option<AccountDivergence>,
+}
+
+/// Compares every account of `base` with the same account of `other`. Zero lamport accounts are
+/// treated as missing.
+pub fn diff_accounts(base: &Bank, other: &Bank) -> Result<AccountsDiff, String> {
+    let mut base_accounts = HashMap::new();
+    base.scan_all_accounts_with_modified_slots(|pubkey, account, slot| {
+        if account.lamports() != 0 {
+            base_accounts.insert(*pubkey, (account, slot));
+        }
+    })
+    .map_err(|err| format!("Unable to scan accounts of the base bank: {:?}", err))?;
+
+    let mut diff = AccountsDiff::default();
+    let mut record = |divergence: AccountDivergence| {
+        diff.num_divergent += 1;
+        let is_earlier = diff.first_divergent.as_ref().map_or(true, |first| {
+            (divergence.first_written_slot(), divergence.pubkey)
+                < (first.first_written_slot(), first.pubkey)
+        });
+        if is_earlier {
+            diff.first_divergent = Some(divergence);
+        }
+    };
+    other
+        .scan_all_accounts_with_modified_slots(|pubkey, account, slot| {
+            if account.lamports() == 0 {
+                return;
+            }
+            let base = base_accounts.remove(pubkey);
+            if base.as_ref().map(|(base_account, _)| base_account) != Some(&account) {
+                record(AccountDivergence {
+                    pubkey: *pubkey,
+                    base,
+                    other: Some((account, slot)),
+                });
+            }
+        })
+        .map_err(|err| format!("Unable to scan accounts of the other bank: {:?}", err))?;
+    for (pubkey, base) in base_accounts {
+        record(AccountDivergence {
+            pubkey,
+            base: Some(base),
+            other: None,
+        });
+    }
+    Ok(diff)
+}
+
+/// A transaction of the block that differs between the two ledgers, by position in the block
+#[derive(Debug)]
+pub struct TransactionDivergence {
+    pub index: usize,
+    pub base: Option<VersionedTransactionWithStatusMeta>,
+    pub other: Option<VersionedTransactionWithStatusMeta>,
+}
+
+#[derive(Debug, Default)]
+pub struct TransactionsDiff {
+    pub num_transactions: usize,
+    pub num_divergent: usize,
+    pub first_divergent: Option<TransactionDivergence>,
+}
+
+/// Compares the transactions of the block of `slot` and their statuses between two ledgers
+pub fn diff_transactions(
+    base: &Blockstore,
+    other: &Blockstore,
+    slot: Slot,
+) -> Result<TransactionsDiff, String> {
+    let get_transactions = |blockstore: &Blockstore| {
+        blockstore
+            .get_complete_block(slot, false)
+            .map(|block| block.transactions)
+            .map_err(|err| {
+                format!(
+                    "Unable to read block {} of {}: {:?}",
+                    slot,
+                    blockstore.ledger_path().display(),
+                    err
+                )
+            })
+    };
+    let mut base_transactions = get_transactions(base)?.into_iter();
+    let mut other_transactions = get_transactions(other)?.into_iter();
+
+    let mut diff = TransactionsDiff::default();
+    for index in 0.. {
+        let (base, other) = match (base_transactions.next(), other_transactions.next()) {
+            (None, None) => break,
+            transactions => transactions,
+        };
+        diff.num_transactions += 1;
+        if base != other {
+            diff.num_divergent += 1;
+            if diff.first_divergent.is_none() {
+                diff.first_divergent = Some(TransactionDivergence { index, base, other });
+            }
+        }
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::{create_ticks, next_entry},
+        solana_ledger::{
+            blockstore::entries_to_test_shreds,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            get_tmp_ledger_path_auto_delete,
+        },
+        solana_sdk::{hash::Hash, signature::Signer, system_transaction},
+        solana_transaction_status::TransactionStatusMeta,
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn test_diff_accounts() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let changed = Pubkey::new_unique();
+        // Each bank gets its own accounts db, as it would when loaded from its own ledger
+        let new_bank = || {
+            let parent = Arc::new(Bank::new_for_tests(&genesis_config));
+            parent.store_account(&changed, &AccountSharedData::new(1, 0, &Pubkey::default()));
+            Bank::new_from_parent(&parent, &Pubkey::default(), 1)
+        };
+        let base = new_bank();
+        let other = new_bank();
+
+        let diff = diff_accounts(&base, &other).unwrap();
+        assert_eq!(diff.num_divergent, 0);
+        assert!(diff.first_divergent.is_none());
+
+        let only_in_base = Pubkey::new_unique();
+        base.store_account(
+            &only_in_base,
+            &AccountSharedData::new(1, 0, &Pubkey::default()),
+        );
+        other.store_account(&changed, &AccountSharedData::new(2, 0, &Pubkey::default()));
+
+        let diff = diff_accounts(&base, &other).unwrap();
+        assert_eq!(diff.num_divergent, 2);
+        let first_divergent = diff.first_divergent.unwrap();
+        assert_eq!(first_divergent.pubkey, changed);
+        assert_eq!(first_divergent.first_written_slot(), 0);
+        assert_eq!(first_divergent.base.unwrap().0.lamports(), 1);
+        assert_eq!(first_divergent.other.unwrap().0.lamports(), 2);
+    }
+
+    #[test]
+    fn test_diff_transactions() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let blockhash = genesis_config.hash();
+        let transfers: Vec<_> = (0..3)
+            .map(|_| {
+                system_transaction::transfer(&mint_keypair, &Pubkey::new_unique(), 1, blockhash)
+            })
+            .collect();
+        let entry = next_entry(&Hash::default(), 1, transfers.clone());
+        let mut entries = vec![entry.clone()];
+        entries.extend(create_ticks(1, 0, entry.hash));
+
+        let ledger_paths = [
+            get_tmp_ledger_path_auto_delete!(),
+            get_tmp_ledger_path_auto_delete!(),
+        ];
+        let blockstores: Vec<_> = ledger_paths
+            .iter()
+            .map(|ledger_path| {
+                let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+                blockstore
+                    .insert_shreds(entries_to_test_shreds(&entries, 1, 0, true, 0), None, false)
+                    .unwrap();
+                for (i, transfer) in transfers.iter().enumerate() {
+                    blockstore
+                        .write_transaction_status(
+                            1,
+                            transfer.signatures[0],
+                            vec![&mint_keypair.pubkey()],
+                            vec![],
+                            TransactionStatusMeta {
+                                fee: i as u64,
+                                ..TransactionStatusMeta::default()
+                            },
+                        )
+                        .unwrap();
+                }
+                blockstore
+            })
+            .collect();
+
+        let diff = diff_transactions(&blockstores[0], &blockstores[1], 1).unwrap();
+        assert_eq!(diff.num_transactions, 3);
+        assert_eq!(diff.num_divergent, 0);
+
+        blockstores[1]
+            .write_transaction_status(
+                1,
+                transfers[1].signatures[0],
+                vec![&mint_keypair.pubkey()],
+                vec![],
+                TransactionStatusMeta {
+                    fee: 42,
+                    ..TransactionStatusMeta::default()
+                },
+            )
+            .unwrap();
+        let diff = diff_transactions(&blockstores[0], &blockstores[1], 1).unwrap();
+        assert_eq!(diff.num_divergent, 1);
+        let first_divergent = diff.first_divergent.unwrap();
+        assert_eq!(first_divergent.index, 1);
+        assert_eq!(first_divergent.base.unwrap().meta.unwrap().fee, 1);
+        assert_eq!(first_divergent.other.unwrap().meta.unwrap().fee, 42);
+
+        assert!(diff_transactions(&blockstores[0], &blockstores[1], 2).is_err());
+    }
+}
@@ -23,7 +23,10 @@ use {
         ancestor_iterator::AncestorIterator,
         bank_forks_utils,
         blockstore::{create_new_ledger, Blockstore, PurgeType},
-        blockstore_db::{self, AccessType, BlockstoreOptions, BlockstoreRecoveryMode, Database},
+        blockstore_db::{
+            self, AccessType, BlockstoreCompressionType, BlockstoreOptions, BlockstoreRecoveryMode,
+            Database, IteratorDirection,
+        },
         blockstore_processor::ProcessOptions,
         shred::Shred,
     },
@@ -33,13 +36,16 @@ use {
         accounts_index::{AccountsIndexConfig, ScanConfig},
         bank::{Bank, RewardCalculationEvent},
         bank_forks::BankForks,
+        block_cost_limits::MAX_BLOCK_UNITS,
+        commitment::VOTE_THRESHOLD_SIZE,
         cost_model::CostModel,
         cost_tracker::CostTracker,
         hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
         snapshot_archive_info::SnapshotArchiveInfoGetter,
         snapshot_config::SnapshotConfig,
         snapshot_utils::{
-            self, ArchiveFormat, SnapshotVersion, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            self, ArchiveCompressionOptions, ArchiveFormat, SnapshotVersion,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         },
     },
@@ -80,8 +86,136 @@ use {
 
 mod bigtable;
 use bigtable::*;
+mod export;
+use export::*;
+mod ledger_diff;
+use ledger_diff::*;
 mod ledger_path;
 use ledger_path::*;
+mod minimize;
+use minimize::*;
+mod shred_bundle;
+use shred_bundle::*;
+mod simulate;
+use simulate::*;
+mod verify;
+use verify::*;
+
+#[derive(Clone, Copy, PartialEq)]
+enum AccountsOutputFormat {
+    Json,
+    Csv,
+}
+
+impl From<&str> for AccountsOutputFormat {
+    fn from(string: &str) -> Self {
+        match string {
+            "json" => AccountsOutputFormat::Json,
+            "csv" => AccountsOutputFormat::Csv,
+            bad_format => panic!("Invalid accounts output format: {}", bad_format),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AccountRecord {
+    pubkey: String,
+    owner: String,
+    lamports: u64,
+    executable: bool,
+    rent_epoch: Epoch,
+    slot: Slot,
+    data_len: usize,
+    data: Option<String>,
+}
+
+enum AccountsWriter<W: Write> {
+    Json { writer: W, num_records: usize },
+    Csv(csv::Writer<W>),
+}
+
+impl<W: Write> AccountsWriter<W> {
+    fn new(format: AccountsOutputFormat, mut writer: W) -> io::Result<Self> {
+        Ok(match format {
+            AccountsOutputFormat::Json => {
+                write!(writer, "[")?;
+                AccountsWriter::Json {
+                    writer,
+                    num_records: 0,
+                }
+            }
+            AccountsOutputFormat::Csv => {
+                AccountsWriter::Csv(csv::WriterBuilder::new().from_writer(writer))
+            }
+        })
+    }
+
+    fn write(&mut self, record: &AccountRecord) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            AccountsWriter::Json {
+                writer,
+                num_records,
+            } => {
+                if *num_records > 0 {
+                    write!(writer, ",")?;
+                }
+                writeln!(writer)?;
+                serde_json::to_writer(&mut *writer, record)?;
+                *num_records += 1;
+            }
+            AccountsWriter::Csv(writer) => writer.serialize(record)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            AccountsWriter::Json { mut writer, .. } => {
+                writeln!(writer, "\n]")?;
+                writer.flush()
+            }
+            AccountsWriter::Csv(mut writer) => writer.flush(),
+        }
+    }
+}
+
+/// Streams the accounts of `bank` that pass `filter` to stdout, without
+/// collecting them in memory first. Json output is a single array.
+fn output_accounts<F>(
+    bank: &Bank,
+    format: AccountsOutputFormat,
+    include_account_data: bool,
+    filter: F,
+) -> Result<usize, Box<dyn std::error::Error>>
+where
+    F: Fn(&Pubkey, &AccountSharedData) -> bool,
+{
+    let stdout = stdout();
+    let mut writer = AccountsWriter::new(format, io::BufWriter::new(stdout.lock()))?;
+    let mut num_accounts = 0;
+    let mut write_result = Ok(());
+
+    bank.scan_all_accounts_with_modified_slots(|pubkey, account, slot| {
+        if write_result.is_err() || !filter(pubkey, &account) {
+            return;
+        }
+        let record = AccountRecord {
+            pubkey: pubkey.to_string(),
+            owner: account.owner().to_string(),
+            lamports: account.lamports(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            slot,
+            data_len: account.data().len(),
+            data: include_account_data.then(|| bs58::encode(account.data()).into_string()),
+        };
+        write_result = writer.write(&record);
+        num_accounts += 1;
+    })?;
+    write_result?;
+    writer.finish()?;
+    Ok(num_accounts)
+}
 
 #[derive(PartialEq)]
 enum LedgerOutputMethod {
@@ -346,8 +480,8 @@ fn render_dot(dot: String, output_file: &str, output_format: &str) -> io::Result
     Ok(())
 }
 
-#[allow(clippy::cognitive_complexity)]
-fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
+/// The tips of the forks of `bank_forks`
+fn get_fork_slots(bank_forks: &BankForks) -> HashSet<Slot> {
     let frozen_banks = bank_forks.frozen_banks();
     let mut fork_slots: HashSet<_> = frozen_banks.keys().cloned().collect();
     for (_, bank) in frozen_banks {
@@ -355,11 +489,18 @@ fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
             fork_slots.remove(&parent.slot());
         }
     }
+    fork_slots
+}
 
-    // Search all forks and collect the last vote made by each validator
+/// The latest vote of each validator found on any of the forks, keyed by validator identity,
+/// along with the validator's stake and the total stake of the fork it was found on
+fn get_last_votes(
+    bank_forks: &BankForks,
+    fork_slots: &HashSet<Slot>,
+) -> HashMap<Pubkey, (Slot, VoteState, u64, u64)> {
     let mut last_votes = HashMap::new();
     let default_vote_state = VoteState::default();
-    for fork_slot in &fork_slots {
+    for fork_slot in fork_slots {
         let bank = &bank_forks[*fork_slot];
 
         let total_stake = bank
@@ -383,6 +524,149 @@ fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
             }
         }
     }
+    last_votes
+}
+
+/// Confirmation and duplicate status of a slot of the fork graph
+#[derive(Serialize)]
+struct SlotOverlay {
+    slot: Slot,
+    parent: Option<Slot>,
+    /// Stake of the validators whose latest vote is for this slot or one of its descendants
+    voted_stake: u64,
+    rooted: bool,
+    optimistically_confirmed: bool,
+    duplicate_confirmed: bool,
+    /// Duplicate shreds have been detected for this slot
+    duplicate: bool,
+}
+
+/// Stake weight of a fork of the fork graph
+#[derive(Serialize)]
+struct ForkOverlay {
+    tip: Slot,
+    /// Stake of the validators whose latest vote is for a slot of this fork
+    stake: u64,
+}
+
+/// Confirmation status, duplicate markers and stake weights laid over the fork graph
+#[derive(Serialize)]
+struct ForkGraphOverlay {
+    total_stake: u64,
+    slots: Vec<SlotOverlay>,
+    forks: Vec<ForkOverlay>,
+    /// Slots that failed to replay and so have no bank in the fork graph
+    dead_slots: Vec<Slot>,
+}
+
+fn get_fork_graph_overlay(
+    bank_forks: &BankForks,
+    blockstore: &Blockstore,
+    fork_slots: &HashSet<Slot>,
+    last_votes: &HashMap<Pubkey, (Slot, VoteState, u64, u64)>,
+) -> ForkGraphOverlay {
+    let frozen_banks = bank_forks.frozen_banks();
+    let total_stake = bank_forks
+        .root_bank()
+        .vote_accounts()
+        .values()
+        .map(|(stake, _)| stake)
+        .sum();
+    let ancestors_of = |bank: &Arc<Bank>| -> Vec<Slot> {
+        std::iter::once(bank.slot())
+            .chain(bank.parents().iter().map(|parent| parent.slot()))
+            .collect()
+    };
+
+    // A vote for a slot is a vote for all of its ancestors as well
+    let mut voted_stakes: HashMap<Slot, u64> = HashMap::new();
+    for (last_vote_slot, _, stake, _) in last_votes.values() {
+        if let Some(bank) = frozen_banks.get(last_vote_slot) {
+            for slot in ancestors_of(bank) {
+                *voted_stakes.entry(slot).or_default() += stake;
+            }
+        }
+    }
+
+    let mut slots: Vec<_> = frozen_banks
+        .values()
+        .map(|bank| {
+            let slot = bank.slot();
+            let voted_stake = voted_stakes.get(&slot).copied().unwrap_or_default();
+            SlotOverlay {
+                slot,
+                parent: bank.parent().map(|parent| parent.slot()),
+                voted_stake,
+                rooted: slot == bank_forks.root() || blockstore.is_root(slot),
+                optimistically_confirmed: voted_stake as f64
+                    > VOTE_THRESHOLD_SIZE * total_stake as f64,
+                duplicate_confirmed: blockstore.is_duplicate_confirmed(slot),
+                duplicate: blockstore.has_duplicate_shreds_in_slot(slot),
+            }
+        })
+        .collect();
+    slots.sort_unstable_by_key(|slot| slot.slot);
+
+    let mut forks: Vec<_> = fork_slots
+        .iter()
+        .map(|fork_slot| {
+            let fork: HashSet<_> = ancestors_of(&frozen_banks[fork_slot]).into_iter().collect();
+            ForkOverlay {
+                tip: *fork_slot,
+                stake: last_votes
+                    .values()
+                    .filter(|(last_vote_slot, ..)| fork.contains(last_vote_slot))
+                    .map(|(_, _, stake, _)| stake)
+                    .sum(),
+            }
+        })
+        .collect();
+    forks.sort_unstable_by_key(|fork| fork.tip);
+
+    let highest_slot = slots.last().map(|slot| slot.slot).unwrap_or_default();
+    let dead_slots = blockstore
+        .dead_slots_iterator(bank_forks.root())
+        .map(|dead_slots| {
+            dead_slots
+                .take_while(|slot| *slot <= highest_slot)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    ForkGraphOverlay {
+        total_stake,
+        slots,
+        forks,
+        dead_slots,
+    }
+}
+
+fn graph_forks_json(bank_forks: &BankForks, blockstore: &Blockstore) -> String {
+    let fork_slots = get_fork_slots(bank_forks);
+    let last_votes = get_last_votes(bank_forks, &fork_slots);
+    let overlay = get_fork_graph_overlay(bank_forks, blockstore, &fork_slots, &last_votes);
+    serde_json::to_string_pretty(&overlay).unwrap()
+}
+
+#[allow(clippy::cognitive_complexity)]
+fn graph_forks(bank_forks: &BankForks, blockstore: &Blockstore, include_all_votes: bool) -> String {
+    let fork_slots = get_fork_slots(bank_forks);
+
+    // Search all forks and collect the last vote made by each validator
+    let last_votes = get_last_votes(bank_forks, &fork_slots);
+    let default_vote_state = VoteState::default();
+
+    let overlay = get_fork_graph_overlay(bank_forks, blockstore, &fork_slots, &last_votes);
+    let slot_overlays: HashMap<_, _> = overlay
+        .slots
+        .iter()
+        .map(|slot_overlay| (slot_overlay.slot, slot_overlay))
+        .collect();
+    let fork_stakes: HashMap<_, _> = overlay
+        .forks
+        .iter()
+        .map(|fork| (fork.tip, fork.stake))
+        .collect();
 
     // Figure the stake distribution at all the nodes containing the last vote from each
     // validator
@@ -420,8 +704,21 @@ fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
             }
 
             if !styled_slots.contains(&bank.slot()) {
+                let slot_overlay = slot_overlays[&bank.slot()];
+                let mut statuses = vec![];
+                if slot_overlay.rooted {
+                    statuses.push("rooted");
+                } else if slot_overlay.optimistically_confirmed {
+                    statuses.push("optimistically confirmed");
+                }
+                if slot_overlay.duplicate_confirmed {
+                    statuses.push("duplicate confirmed");
+                }
+                if slot_overlay.duplicate {
+                    statuses.push("DUPLICATE");
+                }
                 dot.push(format!(
-                    r#"    "{}"[label="{} (epoch {})\nleader: {}{}{}",style="{}{}"];"#,
+                    r#"    "{}"[label="{} (epoch {})\nleader: {}{}{}{}{}",style="{}{}"{}];"#,
                     bank.slot(),
                     bank.slot(),
                     bank.epoch(),
@@ -446,8 +743,28 @@ fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
                     } else {
                         "".to_string()
                     },
+                    match fork_stakes.get(&bank.slot()) {
+                        Some(fork_stake) if first => format!(
+                            "\nfork stake: {:.1} SOL ({:.1}%)",
+                            lamports_to_sol(*fork_stake),
+                            *fork_stake as f64 / overlay.total_stake as f64 * 100.,
+                        ),
+                        _ => "".to_string(),
+                    },
+                    if statuses.is_empty() {
+                        "".to_string()
+                    } else {
+                        format!("\n{}", statuses.join(", "))
+                    },
                     if first { "filled," } else { "" },
-                    ""
+                    if slot_overlay.duplicate { "bold" } else { "" },
+                    if slot_overlay.duplicate {
+                        ",color=red"
+                    } else if slot_overlay.rooted || slot_overlay.optimistically_confirmed {
+                        ",color=green"
+                    } else {
+                        ""
+                    },
                 ));
                 styled_slots.insert(bank.slot());
             }
@@ -530,6 +847,14 @@ fn graph_forks(bank_forks: &BankForks, include_all_votes: bool) -> String {
         ));
     }
 
+    // List the slots that failed to replay
+    for dead_slot in &overlay.dead_slots {
+        dot.push(format!(
+            r#"  "{}"[label="{} (dead)",style=dashed,color=red];"#,
+            dead_slot, dead_slot
+        ));
+    }
+
     // Annotate the final "..." node with absent vote and stake information
     if absent_votes > 0 {
         dot.push(format!(
@@ -658,6 +983,7 @@ fn analyze_storage(database: &Database) {
     analyze_column::<ShredCode>(database, "ShredCode");
     analyze_column::<TransactionStatus>(database, "TransactionStatus");
     analyze_column::<AddressSignatures>(database, "AddressSignatures");
+    analyze_column::<ProgramSignatures>(database, "ProgramSignatures");
     analyze_column::<TransactionMemos>(database, "TransactionMemos");
     analyze_column::<TransactionStatusIndex>(database, "TransactionStatusIndex");
     analyze_column::<Rewards>(database, "Rewards");
@@ -678,6 +1004,7 @@ fn open_blockstore(
             access_type,
             recovery_mode: wal_recovery_mode,
             enforce_ulimit_nofile: true,
+            ..BlockstoreOptions::default()
         },
     ) {
         Ok(blockstore) => blockstore,
@@ -697,6 +1024,45 @@ fn hardforks_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Slot>> {
     }
 }
 
+// Archive format parsing is duplicated in validator/src/main.rs...
+fn archive_compression_of(matches: &ArgMatches<'_>) -> (ArchiveFormat, ArchiveCompressionOptions) {
+    let archive_format = match matches.value_of("snapshot_archive_format").unwrap() {
+        "bz2" => ArchiveFormat::TarBzip2,
+        "gzip" => ArchiveFormat::TarGzip,
+        "zstd" => ArchiveFormat::TarZstd,
+        "lz4" => ArchiveFormat::TarLz4,
+        "tar" | "none" => ArchiveFormat::Tar,
+        archive_format_str => panic!("Archive format not recognized: {}", archive_format_str),
+    };
+    let archive_compression = ArchiveCompressionOptions {
+        level: value_t!(matches, "snapshot_compression_level", i32).ok(),
+        num_threads: value_t_or_exit!(matches, "snapshot_compression_threads", u32),
+    };
+    if let Some(level) = archive_compression.level {
+        match archive_format.compression_levels() {
+            Some(levels) if levels.contains(&level) => {}
+            Some(levels) => {
+                eprintln!(
+                    "Invalid --snapshot-compression-level {}: must be between {} and {} for {:?}",
+                    level,
+                    levels.start(),
+                    levels.end(),
+                    archive_format,
+                );
+                exit(1);
+            }
+            None => {
+                eprintln!(
+                    "--snapshot-compression-level is not supported for {:?}",
+                    archive_format
+                );
+                exit(1);
+            }
+        }
+    }
+    (archive_format, archive_compression)
+}
+
 fn load_bank_forks(
     arg_matches: &ArgMatches,
     genesis_config: &GenesisConfig,
@@ -724,7 +1090,25 @@ fn load_bank_forks(
             ..SnapshotConfig::default()
         })
     };
-    let account_paths = if let Some(account_paths) = arg_matches.value_of("account_paths") {
+    let account_paths = get_account_paths(arg_matches, blockstore);
+
+    let (accounts_package_sender, _) = unbounded();
+    bank_forks_utils::load(
+        genesis_config,
+        blockstore,
+        account_paths,
+        None,
+        snapshot_config.as_ref(),
+        process_options,
+        None,
+        None,
+        accounts_package_sender,
+        None,
+    )
+}
+
+fn get_account_paths(arg_matches: &ArgMatches, blockstore: &Blockstore) -> Vec<PathBuf> {
+    if let Some(account_paths) = arg_matches.value_of("account_paths") {
         if !blockstore.is_primary_access() {
             // Be defensive, when default account dir is explicitly specified, it's still possible
             // to wipe the dir possibly shared by the running validator!
@@ -741,21 +1125,7 @@ fn load_bank_forks(
             non_primary_accounts_path
         );
         vec![non_primary_accounts_path]
-    };
-
-    let (accounts_package_sender, _) = unbounded();
-    bank_forks_utils::load(
-        genesis_config,
-        blockstore,
-        account_paths,
-        None,
-        snapshot_config.as_ref(),
-        process_options,
-        None,
-        None,
-        accounts_package_sender,
-        None,
-    )
+    }
 }
 
 fn compute_slot_cost(blockstore: &Blockstore, slot: Slot) -> Result<(), String> {
@@ -969,6 +1339,31 @@ fn main() {
         .default_value(SnapshotVersion::default().into())
         .help("Output snapshot version");
 
+    let snapshot_archive_format_arg = Arg::with_name("snapshot_archive_format")
+        .long("snapshot-archive-format")
+        .possible_values(&["bz2", "gzip", "zstd", "lz4", "tar", "none"])
+        .default_value("zstd")
+        .value_name("ARCHIVE_TYPE")
+        .takes_value(true)
+        .help("Snapshot archive format to use");
+    let snapshot_compression_level_arg = Arg::with_name("snapshot_compression_level")
+        .long("snapshot-compression-level")
+        .value_name("LEVEL")
+        .takes_value(true)
+        .validator(is_parsable::<i32>)
+        .help(
+            "Compression level to use for the snapshot archive. The valid range depends on \
+             --snapshot-archive-format: bz2 1-9, gzip 0-9, zstd 1-22, lz4 0-16. \
+             [default: the format's own default level]",
+        );
+    let snapshot_compression_threads_arg = Arg::with_name("snapshot_compression_threads")
+        .long("snapshot-compression-threads")
+        .value_name("NUMBER")
+        .takes_value(true)
+        .default_value("0")
+        .validator(is_parsable::<u32>)
+        .help("Number of worker threads used to compress a zstd snapshot archive");
+
     let default_max_full_snapshot_archives_to_retain =
         &DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN.to_string();
     let maximum_full_snapshot_archives_to_retain = Arg::with_name(
@@ -1001,6 +1396,8 @@ fn main() {
     let default_bootstrap_validator_stake_lamports = &sol_to_lamports(0.5)
         .max(StakeState::get_rent_exempt_reserve(&rent))
         .to_string();
+    let default_block_cost_limit = &MAX_BLOCK_UNITS.to_string();
+    let column_names = Database::column_names();
 
     let matches = App::new(crate_name!())
         .about(crate_description!())
@@ -1061,6 +1458,8 @@ fn main() {
                 .help("Show additional information where supported"),
         )
         .bigtable_subcommand()
+        .export_subcommand()
+        .shred_bundle_subcommands()
         .subcommand(
             SubCommand::with_name("print")
             .about("Print the ledger")
@@ -1259,6 +1658,27 @@ fn main() {
                     .takes_value(false)
                     .help("After verifying the ledger, print some information about the account stores"),
             )
+            .arg(
+                Arg::with_name("parallel_ranges")
+                    .long("parallel-ranges")
+                    .value_name("NUM")
+                    .takes_value(true)
+                    .validator(is_parsable::<usize>)
+                    .conflicts_with_all(&["no_snapshot", "print_accounts_stats"])
+                    .help("Split the ledger into the ranges between the full snapshot archives \
+                           and replay up to NUM of them at once, each on its own bank. \
+                           Every range must reproduce the accounts hash of the snapshot \
+                           archive it ends at"),
+            )
+            .arg(
+                Arg::with_name("checkpoint_file")
+                    .long("checkpoint-file")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .requires("parallel_ranges")
+                    .help("Record the verified ranges in FILE, and skip the ranges already \
+                           recorded there, so that an interrupted verification can be resumed"),
+            )
         ).subcommand(
             SubCommand::with_name("graph")
             .about("Create a Graphviz rendering of the ledger")
@@ -1277,7 +1697,9 @@ fn main() {
                     .index(1)
                     .value_name("FILENAME")
                     .takes_value(true)
-                    .help("Output file"),
+                    .help("Output file. Rendered with Graphviz if it ends in .pdf or .png, \
+                           written as JSON without Graphviz formatting if it ends in .json \
+                           and as DOT otherwise"),
             )
         ).subcommand(
             SubCommand::with_name("create-snapshot")
@@ -1287,6 +1709,9 @@ fn main() {
             .arg(&hard_forks_arg)
             .arg(&max_genesis_archive_unpacked_size_arg)
             .arg(&snapshot_version_arg)
+            .arg(&snapshot_archive_format_arg)
+            .arg(&snapshot_compression_level_arg)
+            .arg(&snapshot_compression_threads_arg)
             .arg(&maximum_full_snapshot_archives_to_retain)
             .arg(&maximum_incremental_snapshot_archives_to_retain)
             .arg(
@@ -1427,6 +1852,124 @@ fn main() {
                           base for the incremental snapshot.")
                     .conflicts_with("no_snapshot")
             )
+        ).subcommand(
+            SubCommand::with_name("create-minimized-snapshot")
+            .about("Create a snapshot containing only the accounts of the given programs \
+                    and the system state needed to run a cluster from it")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&snapshot_version_arg)
+            .arg(&snapshot_archive_format_arg)
+            .arg(&snapshot_compression_level_arg)
+            .arg(&snapshot_compression_threads_arg)
+            .arg(&maximum_full_snapshot_archives_to_retain)
+            .arg(&maximum_incremental_snapshot_archives_to_retain)
+            .arg(
+                Arg::with_name("snapshot_slot")
+                    .long("slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot at which to create the snapshot"),
+            )
+            .arg(
+                Arg::with_name("include_programs")
+                    .long("include-program")
+                    .value_name("PROGRAM_ID")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .multiple(true)
+                    .required(true)
+                    .help("Program whose accounts are kept in the snapshot, along with its \
+                           program data account and every account it owns"),
+            )
+            .arg(
+                Arg::with_name("output_directory")
+                    .index(1)
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .help("Output directory for the snapshot [default: --ledger directory]"),
+            )
+        ).subcommand(
+            SubCommand::with_name("diff")
+            .about("Compare the accounts, bank hashes and transaction statuses of two ledgers \
+                    at a slot and report the first divergence")
+            .arg(&no_snapshot_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("base")
+                    .long("base")
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Ledger to compare against"),
+            )
+            .arg(
+                Arg::with_name("other")
+                    .long("other")
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Ledger to compare"),
+            )
+            .arg(
+                Arg::with_name("slot")
+                    .long("slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot at which to compare the ledgers"),
+            )
+        ).subcommand(
+            SubCommand::with_name("simulate-block")
+            .about("Replay a single block on top of its parent bank, optionally with features \
+                    toggled or a different block cost limit, and report the resulting bank hash \
+                    and the outcome of every transaction")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("slot")
+                    .long("slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot of the block to replay"),
+            )
+            .arg(
+                Arg::with_name("activate_features")
+                    .long("activate-feature")
+                    .value_name("FEATURE_ID")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .multiple(true)
+                    .help("Feature to activate for the replay of the block"),
+            )
+            .arg(
+                Arg::with_name("deactivate_features")
+                    .long("deactivate-feature")
+                    .value_name("FEATURE_ID")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .multiple(true)
+                    .help("Feature to deactivate for the replay of the block"),
+            )
+            .arg(
+                Arg::with_name("block_cost_limit")
+                    .long("block-cost-limit")
+                    .value_name("UNITS")
+                    .validator(is_parsable::<u64>)
+                    .takes_value(true)
+                    .default_value(default_block_cost_limit)
+                    .help("Compute units the block may consume"),
+            )
         ).subcommand(
             SubCommand::with_name("accounts")
             .about("Print account stats and contents after processing the ledger")
@@ -1452,6 +1995,31 @@ fn main() {
                     .takes_value(false)
                     .help("Do not print account data when printing account contents."),
             )
+            .arg(
+                Arg::with_name("owner")
+                    .long("owner")
+                    .value_name("PUBKEY")
+                    .takes_value(true)
+                    .validator(is_pubkey)
+                    .help("Only include accounts owned by this program"),
+            )
+            .arg(
+                Arg::with_name("min_lamports")
+                    .long("min-lamports")
+                    .value_name("LAMPORTS")
+                    .takes_value(true)
+                    .validator(is_parsable::<u64>)
+                    .help("Only include accounts with at least this many lamports"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .long("output")
+                    .value_name("FORMAT")
+                    .takes_value(true)
+                    .possible_values(&["json", "csv"])
+                    .help("Stream the matching accounts to stdout in this format \
+                           instead of printing them with the account stats"),
+            )
             .arg(&max_genesis_archive_unpacked_size_arg)
         ).subcommand(
             SubCommand::with_name("capitalization")
@@ -1604,6 +2172,116 @@ fn main() {
                 .about("Output statistics in JSON format about \
                         all column families in the ledger rocksdb")
         )
+        .subcommand(
+            SubCommand::with_name("compact")
+            .about("Manually compact column families of the ledger rocksdb")
+            .arg(
+                Arg::with_name("columns")
+                    .long("column")
+                    .value_name("COLUMN")
+                    .takes_value(true)
+                    .multiple(true)
+                    .possible_values(&column_names)
+                    .help("Column family to compact. May be specified multiple times \
+                           [default: all column families]"),
+            )
+            .arg(
+                Arg::with_name("shred_compression")
+                    .long("shred-compression")
+                    .value_name("COMPRESSION_TYPE")
+                    .takes_value(true)
+                    .possible_values(&["none", "lz4"])
+                    .default_value("none")
+                    .help("Compression to rewrite the data shreds with. Use this to migrate \
+                           an existing ledger to or from --rocksdb-shred-compression"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("program-signatures")
+            .about("Print rooted transaction signatures that invoked a program, newest first. \
+                    Requires a ledger written with --enable-program-signatures-index")
+            .arg(
+                Arg::with_name("program_id")
+                    .index(1)
+                    .value_name("PROGRAM_ID")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Program id to list signatures for"),
+            )
+            .arg(
+                Arg::with_name("start_slot")
+                    .long("start-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("Lowest slot to search"),
+            )
+            .arg(
+                Arg::with_name("end_slot")
+                    .long("end-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .help("Highest slot to search [default: last root]"),
+            )
+            .arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .value_name("NUM")
+                    .validator(is_parsable::<usize>)
+                    .takes_value(true)
+                    .default_value("1000")
+                    .help("Maximum number of signatures to print"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("address-signatures")
+            .about("Print rooted transaction signatures that mention an address within a range \
+                    of slots")
+            .arg(
+                Arg::with_name("address")
+                    .index(1)
+                    .value_name("ADDRESS")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Address to list signatures for"),
+            )
+            .arg(
+                Arg::with_name("start_slot")
+                    .long("start-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("Lowest slot to search"),
+            )
+            .arg(
+                Arg::with_name("end_slot")
+                    .long("end-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .help("Highest slot to search [default: last root]"),
+            )
+            .arg(
+                Arg::with_name("limit")
+                    .long("limit")
+                    .value_name("NUM")
+                    .validator(is_parsable::<usize>)
+                    .takes_value(true)
+                    .default_value("1000")
+                    .help("Maximum number of signatures to print"),
+            )
+            .arg(
+                Arg::with_name("reverse")
+                    .long("reverse")
+                    .takes_value(false)
+                    .help("Print the newest signatures first instead of the oldest"),
+            )
+        )
         .subcommand(
             SubCommand::with_name("compute-slot-cost")
             .about("runs cost_model over the block at the given slots, \
@@ -1635,6 +2313,107 @@ fn main() {
 
     if let ("bigtable", Some(arg_matches)) = matches.subcommand() {
         bigtable_process_command(&ledger_path, arg_matches)
+    } else if let ("diff-shreds", Some(arg_matches)) = matches.subcommand() {
+        diff_shreds_process_command(arg_matches)
+    } else if let ("diff", Some(arg_matches)) = matches.subcommand() {
+        // Both ledgers come from the subcommand, the global --ledger argument is unused
+        let slot = value_t_or_exit!(arg_matches, "slot", Slot);
+        let load_ledger = |name| {
+            let ledger_path = canonicalize_ledger_path(&parse_ledger_path(arg_matches, name));
+            let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+            let blockstore = open_blockstore(
+                &ledger_path,
+                AccessType::TryPrimaryThenSecondary,
+                wal_recovery_mode.clone(),
+            );
+            let bank_forks = load_bank_forks(
+                arg_matches,
+                &genesis_config,
+                &blockstore,
+                ProcessOptions {
+                    dev_halt_at_slot: Some(slot),
+                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                    poh_verify: false,
+                    ..ProcessOptions::default()
+                },
+                None,
+            )
+            .map(|(bank_forks, ..)| bank_forks)
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to load ledger {}: {:?}", ledger_path.display(), err);
+                exit(1);
+            });
+            let bank = bank_forks.get(slot).cloned().unwrap_or_else(|| {
+                eprintln!(
+                    "Error: Slot {} is not available in {}",
+                    slot,
+                    ledger_path.display()
+                );
+                exit(1);
+            });
+            (blockstore, bank)
+        };
+        let (base_blockstore, base_bank) = load_ledger("base");
+        let (other_blockstore, other_bank) = load_ledger("other");
+
+        println!("Slot {}", slot);
+        println!("Base bank hash:  {}", base_bank.hash());
+        println!("Other bank hash: {}", other_bank.hash());
+        if base_bank.hash() == other_bank.hash() {
+            println!("Bank hashes match");
+        }
+
+        let accounts_diff = diff_accounts(&base_bank, &other_bank).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        });
+        println!("Divergent accounts: {}", accounts_diff.num_divergent);
+        if let Some(divergence) = &accounts_diff.first_divergent {
+            println!(
+                "First divergent account: {}, first written in slot {}",
+                divergence.pubkey,
+                divergence.first_written_slot()
+            );
+            for (name, account) in [("base", &divergence.base), ("other", &divergence.other)] {
+                match account {
+                    Some((account, modified_slot)) => println!(
+                        "  {}: {} lamports, owner {}, {} bytes of data, last written in slot {}",
+                        name,
+                        account.lamports(),
+                        account.owner(),
+                        account.data().len(),
+                        modified_slot
+                    ),
+                    None => println!("  {}: missing", name),
+                }
+            }
+        }
+
+        match diff_transactions(&base_blockstore, &other_blockstore, slot) {
+            Ok(transactions_diff) => {
+                println!(
+                    "Divergent transactions: {} of {}",
+                    transactions_diff.num_divergent, transactions_diff.num_transactions
+                );
+                if let Some(divergence) = &transactions_diff.first_divergent {
+                    println!("First divergent transaction: #{}", divergence.index);
+                    for (name, transaction) in
+                        [("base", &divergence.base), ("other", &divergence.other)]
+                    {
+                        match transaction {
+                            Some(transaction) => println!(
+                                "  {}: {}, status {:?}",
+                                name,
+                                transaction.transaction.signatures[0],
+                                transaction.meta.as_ref().map(|meta| &meta.status),
+                            ),
+                            None => println!("  {}: missing", name),
+                        }
+                    }
+                }
+            }
+            Err(err) => println!("Unable to compare transactions: {}", err),
+        }
     } else {
         let ledger_path = canonicalize_ledger_path(&ledger_path);
 
@@ -2049,20 +2828,38 @@ fn main() {
                     AccessType::TryPrimaryThenSecondary,
                     wal_recovery_mode,
                 );
-                let (bank_forks, ..) = load_bank_forks(
-                    arg_matches,
-                    &open_genesis_config_by(&ledger_path, arg_matches),
-                    &blockstore,
-                    process_options,
-                    snapshot_archive_path,
-                )
-                .unwrap_or_else(|err| {
-                    eprintln!("Ledger verification failed: {:?}", err);
-                    exit(1);
-                });
-                if print_accounts_stats {
-                    let working_bank = bank_forks.working_bank();
-                    working_bank.print_accounts_stats();
+                if let Ok(num_threads) = value_t!(arg_matches, "parallel_ranges", usize) {
+                    let snapshot_archives_dir =
+                        snapshot_archive_path.unwrap_or_else(|| ledger_path.clone());
+                    let account_paths = get_account_paths(arg_matches, &blockstore);
+                    if let Err(err) = verify_ledger_ranges(
+                        &open_genesis_config_by(&ledger_path, arg_matches),
+                        Arc::new(blockstore),
+                        &snapshot_archives_dir,
+                        account_paths,
+                        process_options,
+                        num_threads.max(1),
+                        value_t!(arg_matches, "checkpoint_file", PathBuf).ok(),
+                    ) {
+                        eprintln!("Ledger verification failed: {}", err);
+                        exit(1);
+                    }
+                } else {
+                    let (bank_forks, ..) = load_bank_forks(
+                        arg_matches,
+                        &open_genesis_config_by(&ledger_path, arg_matches),
+                        &blockstore,
+                        process_options,
+                        snapshot_archive_path,
+                    )
+                    .unwrap_or_else(|err| {
+                        eprintln!("Ledger verification failed: {:?}", err);
+                        exit(1);
+                    });
+                    if print_accounts_stats {
+                        let working_bank = bank_forks.working_bank();
+                        working_bank.print_accounts_stats();
+                    }
                 }
                 exit_signal.store(true, Ordering::Relaxed);
                 system_monitor_service.join().unwrap();
@@ -2091,10 +2888,17 @@ fn main() {
                     snapshot_archive_path,
                 ) {
                     Ok((bank_forks, ..)) => {
-                        let dot =
-                            graph_forks(&bank_forks, arg_matches.is_present("include_all_votes"));
-
                         let extension = Path::new(&output_file).extension();
+                        let dot = if extension == Some(OsStr::new("json")) {
+                            graph_forks_json(&bank_forks, &blockstore)
+                        } else {
+                            graph_forks(
+                                &bank_forks,
+                                &blockstore,
+                                arg_matches.is_present("include_all_votes"),
+                            )
+                        };
+
                         let result = if extension == Some(OsStr::new("pdf")) {
                             render_dot(dot, &output_file, "pdf")
                         } else if extension == Some(OsStr::new("png")) {
@@ -2168,6 +2972,7 @@ fn main() {
                     "maximum_incremental_snapshots_to_retain",
                     usize
                 );
+                let (archive_format, archive_compression) = archive_compression_of(arg_matches);
                 let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
                 let blockstore = open_blockstore(
                     &ledger_path,
@@ -2437,7 +3242,8 @@ fn main() {
                                     full_snapshot_slot,
                                     Some(snapshot_version),
                                     output_directory,
-                                    ArchiveFormat::TarZstd,
+                                    archive_format,
+                                    archive_compression,
                                     maximum_full_snapshot_archives_to_retain,
                                     maximum_incremental_snapshot_archives_to_retain,
                                 )
@@ -2460,7 +3266,8 @@ fn main() {
                                     &bank,
                                     Some(snapshot_version),
                                     output_directory,
-                                    ArchiveFormat::TarZstd,
+                                    archive_format,
+                                    archive_compression,
                                     maximum_full_snapshot_archives_to_retain,
                                     maximum_incremental_snapshot_archives_to_retain,
                                 )
@@ -2491,6 +3298,213 @@ fn main() {
                     }
                 }
             }
+            ("create-minimized-snapshot", Some(arg_matches)) => {
+                let output_directory = value_t!(arg_matches, "output_directory", PathBuf)
+                    .unwrap_or_else(|_| ledger_path.clone());
+                let snapshot_slot = value_t_or_exit!(arg_matches, "snapshot_slot", Slot);
+                let include_programs = pubkeys_of(arg_matches, "include_programs").unwrap();
+                let snapshot_version = arg_matches.value_of("snapshot_version").map_or(
+                    SnapshotVersion::default(),
+                    |s| {
+                        s.parse::<SnapshotVersion>().unwrap_or_else(|e| {
+                            eprintln!("Error: {}", e);
+                            exit(1)
+                        })
+                    },
+                );
+                let maximum_full_snapshot_archives_to_retain =
+                    value_t_or_exit!(arg_matches, "maximum_full_snapshots_to_retain", usize);
+                let maximum_incremental_snapshot_archives_to_retain = value_t_or_exit!(
+                    arg_matches,
+                    "maximum_incremental_snapshots_to_retain",
+                    usize
+                );
+                let (archive_format, archive_compression) = archive_compression_of(arg_matches);
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+
+                info!(
+                    "Creating minimized snapshot of slot {} in {}",
+                    snapshot_slot,
+                    output_directory.display()
+                );
+
+                match load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    ProcessOptions {
+                        dev_halt_at_slot: Some(snapshot_slot),
+                        new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                        poh_verify: false,
+                        ..ProcessOptions::default()
+                    },
+                    snapshot_archive_path,
+                ) {
+                    Ok((bank_forks, ..)) => {
+                        let bank = bank_forks.get(snapshot_slot).unwrap_or_else(|| {
+                            eprintln!("Error: Slot {} is not available", snapshot_slot);
+                            exit(1);
+                        });
+                        let bank =
+                            Bank::new_from_parent(bank, bank.collector_id(), bank.slot() + 1);
+
+                        let num_removed_accounts = minimize_bank(&bank, &include_programs)
+                            .unwrap_or_else(|err| {
+                                eprintln!("Unable to minimize slot {}: {}", snapshot_slot, err);
+                                exit(1);
+                            });
+                        while !bank.is_complete() {
+                            bank.register_tick(&Hash::new_unique());
+                        }
+                        bank.set_capitalization();
+
+                        println!(
+                            "Creating a version {} minimized snapshot of slot {} without {} accounts",
+                            snapshot_version,
+                            bank.slot(),
+                            num_removed_accounts,
+                        );
+                        let full_snapshot_archive_info =
+                            snapshot_utils::bank_to_full_snapshot_archive(
+                                ledger_path,
+                                &bank,
+                                Some(snapshot_version),
+                                output_directory,
+                                archive_format,
+                                archive_compression,
+                                maximum_full_snapshot_archives_to_retain,
+                                maximum_incremental_snapshot_archives_to_retain,
+                            )
+                            .unwrap_or_else(|err| {
+                                eprintln!("Unable to create snapshot: {}", err);
+                                exit(1);
+                            });
+
+                        println!(
+                            "Successfully created minimized snapshot for slot {}, hash {}: {}",
+                            bank.slot(),
+                            bank.hash(),
+                            full_snapshot_archive_info.path().display(),
+                        );
+                        println!(
+                            "Shred version: {}",
+                            compute_shred_version(
+                                &genesis_config.hash(),
+                                Some(&bank.hard_forks().read().unwrap())
+                            )
+                        );
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to load ledger: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
+            ("simulate-block", Some(arg_matches)) => {
+                let slot = value_t_or_exit!(arg_matches, "slot", Slot);
+                let options = SimulateBlockOptions {
+                    activate_features: pubkeys_of(arg_matches, "activate_features")
+                        .unwrap_or_default(),
+                    deactivate_features: pubkeys_of(arg_matches, "deactivate_features")
+                        .unwrap_or_default(),
+                    block_cost_limit: value_t_or_exit!(arg_matches, "block_cost_limit", u64),
+                };
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                let parent_slot = blockstore
+                    .meta(slot)
+                    .ok()
+                    .flatten()
+                    .and_then(|meta| meta.parent_slot)
+                    .unwrap_or_else(|| {
+                        eprintln!("Error: Parent of slot {} is unknown", slot);
+                        exit(1);
+                    });
+
+                match load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    ProcessOptions {
+                        dev_halt_at_slot: Some(parent_slot),
+                        new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                        poh_verify: false,
+                        ..ProcessOptions::default()
+                    },
+                    snapshot_archive_path,
+                ) {
+                    Ok((bank_forks, leader_schedule_cache, ..)) => {
+                        let parent = bank_forks.get(parent_slot).unwrap_or_else(|| {
+                            eprintln!("Error: Parent slot {} is not available", parent_slot);
+                            exit(1);
+                        });
+                        let simulated_block = simulate_block(
+                            parent,
+                            &leader_schedule_cache,
+                            &blockstore,
+                            slot,
+                            &options,
+                        )
+                        .unwrap_or_else(|err| {
+                            eprintln!("Unable to simulate slot {}: {}", slot, err);
+                            exit(1);
+                        });
+
+                        println!(
+                            "Slot {} (parent {}), {} transaction(s)",
+                            simulated_block.slot,
+                            simulated_block.parent_slot,
+                            simulated_block.transactions.len()
+                        );
+                        for outcome in &simulated_block.transactions {
+                            match (&outcome.result, outcome.executed) {
+                                (Ok(()), _) => println!("  {}: ok", outcome.signature),
+                                (Err(err), true) => {
+                                    println!("  {}: failed: {}", outcome.signature, err)
+                                }
+                                (Err(err), false) => {
+                                    println!("  {}: not executed: {}", outcome.signature, err)
+                                }
+                            }
+                            if verbose_level > 0 {
+                                for message in outcome.log_messages.iter().flatten() {
+                                    println!("    {}", message);
+                                }
+                            }
+                        }
+                        if let Some(replay_error) = &simulated_block.replay_error {
+                            println!("Block is dead: {}", replay_error);
+                        }
+                        if let Some(bank_hash) = simulated_block.bank_hash {
+                            println!("Bank hash: {}", bank_hash);
+                        }
+                        match simulated_block.ledger_bank_hash {
+                            Some(ledger_bank_hash)
+                                if Some(ledger_bank_hash) == simulated_block.bank_hash =>
+                            {
+                                println!("Bank hash matches the ledger")
+                            }
+                            Some(ledger_bank_hash) => {
+                                println!("Bank hash differs from the ledger: {}", ledger_bank_hash)
+                            }
+                            None => println!("Ledger has no bank hash for slot {}", slot),
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to load ledger: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
             ("accounts", Some(arg_matches)) => {
                 let dev_halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
                 let process_options = ProcessOptions {
@@ -2519,14 +3533,38 @@ fn main() {
                 });
 
                 let bank = bank_forks.working_bank();
+                let owner = pubkey_of(arg_matches, "owner");
+                let min_lamports = value_t!(arg_matches, "min_lamports", u64).unwrap_or(0);
+                let include_account = |pubkey: &Pubkey, account: &AccountSharedData| {
+                    (include_sysvars || !solana_sdk::sysvar::is_sysvar_id(pubkey))
+                        && owner.map_or(true, |owner| account.owner() == &owner)
+                        && account.lamports() >= min_lamports
+                };
+
+                if let Some(output) = arg_matches.value_of("output") {
+                    let include_account_data = !arg_matches.is_present("no_account_data");
+                    let mut measure = Measure::start("streaming accounts");
+                    let num_accounts = output_accounts(
+                        &bank,
+                        AccountsOutputFormat::from(output),
+                        include_account_data,
+                        include_account,
+                    )
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to output accounts: {}", err);
+                        exit(1);
+                    });
+                    measure.stop();
+                    info!("{} accounts, {}", num_accounts, measure);
+                    return;
+                }
+
                 let mut measure = Measure::start("getting accounts");
                 let accounts: BTreeMap<_, _> = bank
                     .get_all_accounts_with_modified_slots()
                     .unwrap()
                     .into_iter()
-                    .filter(|(pubkey, _account, _slot)| {
-                        include_sysvars || !solana_sdk::sysvar::is_sysvar_id(pubkey)
-                    })
+                    .filter(|(pubkey, account, _slot)| include_account(pubkey, account))
                     .map(|(pubkey, account, slot)| (pubkey, (account, slot)))
                     .collect();
                 measure.stop();
@@ -3307,6 +4345,100 @@ fn main() {
                 );
                 println!("Ok.");
             }
+            ("export", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                export_process_command(&blockstore, arg_matches);
+            }
+            ("dump-shreds", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                dump_shreds_process_command(&blockstore, arg_matches);
+            }
+            ("compact", Some(arg_matches)) => {
+                let columns = arg_matches
+                    .values_of("columns")
+                    .map(|columns| columns.collect())
+                    .unwrap_or_else(|| column_names.clone());
+                let blockstore = Blockstore::open_with_options(
+                    &ledger_path,
+                    BlockstoreOptions {
+                        recovery_mode: wal_recovery_mode,
+                        shred_compression: BlockstoreCompressionType::from(
+                            arg_matches.value_of("shred_compression").unwrap(),
+                        ),
+                        ..BlockstoreOptions::default()
+                    },
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to open ledger at {:?}: {:?}", ledger_path, err);
+                    exit(1);
+                });
+                if let Err(err) = blockstore.compact_columns(&columns) {
+                    eprintln!("Compaction failed: {:?}", err);
+                    exit(1);
+                }
+                println!("Ok.");
+            }
+            ("program-signatures", Some(arg_matches)) => {
+                let program_id = pubkey_of(arg_matches, "program_id").unwrap();
+                let start_slot = value_t_or_exit!(arg_matches, "start_slot", Slot);
+                let limit = value_t_or_exit!(arg_matches, "limit", usize);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                let end_slot = value_t!(arg_matches, "end_slot", Slot)
+                    .unwrap_or_else(|_| blockstore.last_root());
+                match blockstore.get_program_signatures(program_id, start_slot, end_slot, limit) {
+                    Ok(signatures) => {
+                        for (slot, signature) in signatures {
+                            println!("{} {}", slot, signature);
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to read program signatures: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
+            ("address-signatures", Some(arg_matches)) => {
+                let address = pubkey_of(arg_matches, "address").unwrap();
+                let start_slot = value_t_or_exit!(arg_matches, "start_slot", Slot);
+                let limit = value_t_or_exit!(arg_matches, "limit", usize);
+                let direction = if arg_matches.is_present("reverse") {
+                    IteratorDirection::Reverse
+                } else {
+                    IteratorDirection::Forward
+                };
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::TryPrimaryThenSecondary,
+                    wal_recovery_mode,
+                );
+                let end_slot = value_t!(arg_matches, "end_slot", Slot)
+                    .unwrap_or_else(|_| blockstore.last_root());
+                match blockstore.get_address_signatures_in_range(
+                    address, start_slot, end_slot, limit, direction,
+                ) {
+                    Ok(signatures) => {
+                        for (slot, signature) in signatures {
+                            println!("{} {}", slot, signature);
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to read address signatures: {:?}", err);
+                        exit(1);
+                    }
+                }
+            }
             ("compute-slot-cost", Some(arg_matches)) => {
                 let blockstore = open_blockstore(
                     &ledger_path,
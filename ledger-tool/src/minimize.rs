@@ -0,0 +1,182 @@
+/// Reduction of a bank to the accounts of a few programs for the `create-minimized-snapshot`
+/// subcommand
+use {
+    log::*,
+    solana_runtime::{accounts_index::ScanConfig, bank::Bank},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        account_utils::StateMut,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        config, feature, native_loader,
+        pubkey::Pubkey,
+        stake::{self, state::StakeState},
+        sysvar,
+    },
+    std::collections::HashSet,
+};
+
+/// Owners of the accounts every bank needs regardless of the programs it is minimized to:
+/// sysvars, builtin programs, feature gates and the stake and validator configs
+fn is_required_owner(owner: &Pubkey) -> bool {
+    *owner == sysvar::id()
+        || *owner == native_loader::id()
+        || *owner == feature::id()
+        || *owner == config::program::id()
+}
+
+/// Returns the program accounts of `include_programs`, their program data accounts and all of
+/// the accounts they own
+fn get_program_accounts(
+    bank: &Bank,
+    include_programs: &[Pubkey],
+) -> Result<HashSet<Pubkey>, String> {
+    let mut accounts = HashSet::new();
+    for program_id in include_programs {
+        let program_account = bank
+            .get_account(program_id)
+            .ok_or_else(|| format!("Program account does not exist: {}", program_id))?;
+        if !program_account.executable() {
+            return Err(format!("Account is not a program: {}", program_id));
+        }
+        accounts.insert(*program_id);
+
+        if bpf_loader_upgradeable::check_id(program_account.owner()) {
+            if let Ok(UpgradeableLoaderState::Program {
+                programdata_address,
+            }) = program_account.state()
+            {
+                accounts.insert(programdata_address);
+            }
+        }
+
+        let owned_accounts = bank
+            .get_program_accounts(program_id, &ScanConfig::default())
+            .map_err(|err| format!("Unable to scan accounts of {}: {:?}", program_id, err))?;
+        accounts.extend(owned_accounts.into_iter().map(|(address, _)| address));
+    }
+    Ok(accounts)
+}
+
+/// Removes every account from `bank` that neither belongs to one of `include_programs` nor is
+/// needed by the bank itself, returning the number of removed accounts.
+///
+/// Staked vote accounts, the identities voting with them and the stake delegated to them are kept
+/// so that the leader schedule of the minimized bank stays the same.
+pub fn minimize_bank(bank: &Bank, include_programs: &[Pubkey]) -> Result<usize, String> {
+    let mut accounts_to_keep = get_program_accounts(bank, include_programs)?;
+
+    let staked_vote_accounts: HashSet<_> = bank
+        .vote_accounts()
+        .iter()
+        .filter(|(_, (stake, _))| *stake > 0)
+        .map(|(vote_pubkey, (_, vote_account))| {
+            if let Ok(vote_state) = vote_account.vote_state().as_ref() {
+                accounts_to_keep.insert(vote_state.node_pubkey);
+            }
+            *vote_pubkey
+        })
+        .collect();
+    accounts_to_keep.extend(staked_vote_accounts.iter());
+
+    let mut accounts_to_remove = vec![];
+    bank.scan_all_accounts_with_modified_slots(|address, account, _slot| {
+        if account.lamports() == 0
+            || accounts_to_keep.contains(address)
+            || is_required_owner(account.owner())
+        {
+            return;
+        }
+        if stake::program::check_id(account.owner()) {
+            if let Ok(StakeState::Stake(_, stake)) = account.state() {
+                if staked_vote_accounts.contains(&stake.delegation.voter_pubkey) {
+                    return;
+                }
+            }
+        }
+        accounts_to_remove.push(*address);
+    })
+    .map_err(|err| format!("Unable to scan accounts: {:?}", err))?;
+
+    info!(
+        "Keeping {} accounts of {} programs, removing {} accounts",
+        accounts_to_keep.len(),
+        include_programs.len(),
+        accounts_to_remove.len()
+    );
+    for address in &accounts_to_remove {
+        bank.store_account(address, &AccountSharedData::default());
+    }
+    Ok(accounts_to_remove.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_runtime::genesis_utils::create_genesis_config_with_leader,
+        solana_sdk::{account::WritableAccount, bpf_loader, signature::Signer, sysvar::clock},
+        std::sync::Arc,
+    };
+
+    fn store_program(bank: &Bank) -> Pubkey {
+        let program_id = Pubkey::new_unique();
+        let mut program_account = AccountSharedData::new(1, 0, &bpf_loader::id());
+        program_account.set_executable(true);
+        bank.store_account(&program_id, &program_account);
+        program_id
+    }
+
+    #[test]
+    fn test_minimize_bank() {
+        let genesis_config_info =
+            create_genesis_config_with_leader(1_000_000, &Pubkey::new_unique(), 100);
+        let parent = Arc::new(Bank::new_for_tests(&genesis_config_info.genesis_config));
+        let bank = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+
+        let included_program = store_program(&bank);
+        let excluded_program = store_program(&bank);
+        let included_account = Pubkey::new_unique();
+        bank.store_account(
+            &included_account,
+            &AccountSharedData::new(1, 0, &included_program),
+        );
+        let excluded_account = Pubkey::new_unique();
+        bank.store_account(
+            &excluded_account,
+            &AccountSharedData::new(1, 0, &excluded_program),
+        );
+        let mint = genesis_config_info.mint_keypair.pubkey();
+
+        assert!(minimize_bank(&bank, &[included_program]).unwrap() >= 3);
+
+        assert!(bank.get_account(&included_program).is_some());
+        assert!(bank.get_account(&included_account).is_some());
+        assert!(bank.get_account(&clock::id()).is_some());
+        assert!(bank
+            .get_account(&genesis_config_info.voting_keypair.pubkey())
+            .is_some());
+        assert!(bank
+            .get_account(&genesis_config_info.validator_pubkey)
+            .is_some());
+
+        assert!(bank.get_account(&excluded_program).is_none());
+        assert!(bank.get_account(&excluded_account).is_none());
+        assert!(bank.get_account(&mint).is_none());
+    }
+
+    #[test]
+    fn test_minimize_bank_invalid_program() {
+        let genesis_config_info =
+            create_genesis_config_with_leader(1_000_000, &Pubkey::new_unique(), 100);
+        let bank = Bank::new_for_tests(&genesis_config_info.genesis_config);
+
+        assert!(minimize_bank(&bank, &[Pubkey::new_unique()]).is_err());
+
+        let not_a_program = Pubkey::new_unique();
+        bank.store_account(
+            &not_a_program,
+            &AccountSharedData::new(1, 0, &Pubkey::default()),
+        );
+        assert!(minimize_bank(&bank, &[not_a_program]).is_err());
+    }
+}
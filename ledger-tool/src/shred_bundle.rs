@@ -0,0 +1,436 @@
+/// The `dump-shreds` and `diff-shreds` subcommands, for investigating duplicate blocks
+use {
+    clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    serde::{Deserialize, Serialize},
+    solana_clap_utils::input_validators::is_slot,
+    solana_ledger::{
+        blockstore::Blockstore,
+        shred::{Shred, ShredType},
+    },
+    solana_sdk::clock::Slot,
+    std::{
+        collections::{BTreeMap, HashSet},
+        fs::File,
+        path::{Path, PathBuf},
+        process::exit,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundledShredType {
+    Data,
+    Code,
+}
+
+impl From<ShredType> for BundledShredType {
+    fn from(shred_type: ShredType) -> Self {
+        match shred_type {
+            ShredType::Data => BundledShredType::Data,
+            ShredType::Code => BundledShredType::Code,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledShred {
+    pub shred_type: BundledShredType,
+    pub index: u32,
+    pub fec_set_index: u32,
+    pub version: u16,
+    /// Leader signature of the shred, which tells two versions of the same shred apart
+    pub signature: String,
+    /// Base58 encoded shred payload
+    pub payload: String,
+}
+
+impl BundledShred {
+    fn key(&self) -> (BundledShredType, u32) {
+        (self.shred_type, self.index)
+    }
+}
+
+impl From<&Shred> for BundledShred {
+    fn from(shred: &Shred) -> Self {
+        Self {
+            shred_type: shred.shred_type().into(),
+            index: shred.index(),
+            fec_set_index: shred.common_header.fec_set_index,
+            version: shred.version(),
+            signature: shred.signature().to_string(),
+            payload: bs58::encode(&shred.payload).into_string(),
+        }
+    }
+}
+
+/// Every shred a blockstore holds for a slot, in a form that can be moved between machines and
+/// compared with the bundle of another validator
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShredBundle {
+    pub slot: Slot,
+    pub parent_slot: Option<Slot>,
+    pub is_full: bool,
+    pub is_dead: bool,
+    pub bank_hash: Option<String>,
+    /// The data and coding shreds stored for the slot
+    pub shreds: Vec<BundledShred>,
+    /// The two conflicting shreds recorded when the slot was detected as duplicate
+    pub duplicate_proof: Vec<BundledShred>,
+}
+
+impl ShredBundle {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|err| format!("Unable to open {}: {}", path.display(), err))?;
+        serde_json::from_reader(file)
+            .map_err(|err| format!("Unable to parse {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|err| format!("Unable to create {}: {}", path.display(), err))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|err| format!("Unable to write {}: {}", path.display(), err))
+    }
+}
+
+/// Collects the shreds of `slot` along with its duplicate proof, if any
+pub fn dump_shred_bundle(blockstore: &Blockstore, slot: Slot) -> Result<ShredBundle, String> {
+    let data_shreds = blockstore
+        .get_data_shreds_for_slot(slot, 0)
+        .map_err(|err| format!("Unable to read data shreds of slot {}: {:?}", slot, err))?;
+    let coding_shreds = blockstore
+        .get_coding_shreds_for_slot(slot, 0)
+        .map_err(|err| format!("Unable to read coding shreds of slot {}: {:?}", slot, err))?;
+    let duplicate_proof = match blockstore.get_duplicate_slot(slot) {
+        Some(proof) => [proof.shred1, proof.shred2]
+            .into_iter()
+            .map(|payload| {
+                Shred::new_from_serialized_shred(payload)
+                    .map(|shred| BundledShred::from(&shred))
+                    .map_err(|err| format!("Invalid duplicate proof of slot {}: {:?}", slot, err))
+            })
+            .collect::<Result<_, _>>()?,
+        None => vec![],
+    };
+    if data_shreds.is_empty() && coding_shreds.is_empty() && duplicate_proof.is_empty() {
+        return Err(format!("No shreds stored for slot {}", slot));
+    }
+
+    let meta = blockstore
+        .meta(slot)
+        .map_err(|err| format!("Unable to read meta of slot {}: {:?}", slot, err))?;
+    Ok(ShredBundle {
+        slot,
+        parent_slot: meta.and_then(|meta| meta.parent_slot),
+        is_full: blockstore.is_full(slot),
+        is_dead: blockstore.is_dead(slot),
+        bank_hash: blockstore.get_bank_hash(slot).map(|hash| hash.to_string()),
+        shreds: data_shreds
+            .iter()
+            .chain(coding_shreds.iter())
+            .map(BundledShred::from)
+            .collect(),
+        duplicate_proof,
+    })
+}
+
+/// A shred both bundles store under the same type and index, but with different signatures
+#[derive(Debug)]
+pub struct ConflictingShred {
+    pub first: BundledShred,
+    pub second: BundledShred,
+    /// Whether either version is part of the duplicate proof of either bundle
+    pub in_duplicate_proof: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ShredBundleDiff {
+    pub num_matching: usize,
+    pub only_in_first: Vec<BundledShred>,
+    pub only_in_second: Vec<BundledShred>,
+    pub conflicting: Vec<ConflictingShred>,
+}
+
+/// Compares the stored shreds of two bundles of the same slot
+pub fn diff_shred_bundles(
+    first: &ShredBundle,
+    second: &ShredBundle,
+) -> Result<ShredBundleDiff, String> {
+    if first.slot != second.slot {
+        return Err(format!(
+            "Bundles are of different slots: {} and {}",
+            first.slot, second.slot
+        ));
+    }
+    let proof_signatures: HashSet<_> = first
+        .duplicate_proof
+        .iter()
+        .chain(second.duplicate_proof.iter())
+        .map(|shred| shred.signature.as_str())
+        .collect();
+    let mut second_shreds: BTreeMap<_, _> = second
+        .shreds
+        .iter()
+        .map(|shred| (shred.key(), shred))
+        .collect();
+
+    let mut diff = ShredBundleDiff::default();
+    for shred in &first.shreds {
+        match second_shreds.remove(&shred.key()) {
+            None => diff.only_in_first.push(shred.clone()),
+            Some(other) if other.signature == shred.signature => diff.num_matching += 1,
+            Some(other) => diff.conflicting.push(ConflictingShred {
+                first: shred.clone(),
+                second: other.clone(),
+                in_duplicate_proof: proof_signatures.contains(shred.signature.as_str())
+                    || proof_signatures.contains(other.signature.as_str()),
+            }),
+        }
+    }
+    diff.only_in_second = second_shreds.into_values().cloned().collect();
+    Ok(diff)
+}
+
+fn shred_label(shred: &BundledShred) -> String {
+    format!(
+        "{:?} shred {} (fec set {})",
+        shred.shred_type, shred.index, shred.fec_set_index
+    )
+}
+
+fn print_bundle_summary(name: &str, bundle: &ShredBundle) {
+    println!(
+        "{}: {} shreds, full: {}, dead: {}, bank hash: {}",
+        name,
+        bundle.shreds.len(),
+        bundle.is_full,
+        bundle.is_dead,
+        bundle.bank_hash.as_deref().unwrap_or("none"),
+    );
+    for shred in &bundle.duplicate_proof {
+        println!(
+            "  duplicate proof: {} {}",
+            shred_label(shred),
+            shred.signature
+        );
+    }
+}
+
+pub trait ShredBundleSubCommand {
+    fn shred_bundle_subcommands(self) -> Self;
+}
+
+impl ShredBundleSubCommand for App<'_, '_> {
+    fn shred_bundle_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("dump-shreds")
+                .about(
+                    "Write every shred stored for a slot, including its duplicate proof, \
+                     to a portable JSON bundle",
+                )
+                .arg(
+                    Arg::with_name("slot")
+                        .long("slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .required(true)
+                        .help("Slot to dump"),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .index(1)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("File to write the bundle to"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-shreds")
+                .about("Compare two shred bundles written by dump-shreds")
+                .arg(
+                    Arg::with_name("first")
+                        .index(1)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("First bundle"),
+                )
+                .arg(
+                    Arg::with_name("second")
+                        .index(2)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Second bundle"),
+                ),
+        )
+    }
+}
+
+pub fn dump_shreds_process_command(blockstore: &Blockstore, matches: &ArgMatches<'_>) {
+    let slot = value_t_or_exit!(matches, "slot", Slot);
+    let output_file = PathBuf::from(value_t_or_exit!(matches, "output_file", String));
+
+    match dump_shred_bundle(blockstore, slot).and_then(|bundle| {
+        bundle.save(&output_file)?;
+        Ok(bundle)
+    }) {
+        Ok(bundle) => println!(
+            "Wrote {} shreds and {} duplicate proof shreds of slot {} to {}",
+            bundle.shreds.len(),
+            bundle.duplicate_proof.len(),
+            slot,
+            output_file.display()
+        ),
+        Err(err) => {
+            eprintln!("Dump failed: {}", err);
+            exit(1);
+        }
+    }
+}
+
+pub fn diff_shreds_process_command(matches: &ArgMatches<'_>) {
+    let load = |name| {
+        let path = PathBuf::from(value_t_or_exit!(matches, name, String));
+        ShredBundle::load(&path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        })
+    };
+    let first = load("first");
+    let second = load("second");
+    let diff = diff_shred_bundles(&first, &second).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+
+    println!("Slot {}", first.slot);
+    print_bundle_summary("First", &first);
+    print_bundle_summary("Second", &second);
+    println!("Matching shreds: {}", diff.num_matching);
+    for shred in &diff.only_in_first {
+        println!("Only in first: {} {}", shred_label(shred), shred.signature);
+    }
+    for shred in &diff.only_in_second {
+        println!("Only in second: {} {}", shred_label(shred), shred.signature);
+    }
+    for conflict in &diff.conflicting {
+        println!(
+            "Conflicting {:?} shred {}{}:\n  first:  {} (fec set {})\n  second: {} (fec set {})",
+            conflict.first.shred_type,
+            conflict.first.index,
+            if conflict.in_duplicate_proof {
+                ", part of a duplicate proof"
+            } else {
+                ""
+            },
+            conflict.first.signature,
+            conflict.first.fec_set_index,
+            conflict.second.signature,
+            conflict.second.fec_set_index,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::create_ticks,
+        solana_ledger::{blockstore::entries_to_test_shreds, get_tmp_ledger_path_auto_delete},
+        solana_sdk::hash::Hash,
+    };
+
+    fn insert_slot(blockstore: &Blockstore, slot: Slot) -> Vec<Shred> {
+        let entries = create_ticks(10, 0, Hash::new_unique());
+        let shreds = entries_to_test_shreds(&entries, slot, slot - 1, true, 0);
+        blockstore
+            .insert_shreds(shreds.clone(), None, false)
+            .unwrap();
+        shreds
+    }
+
+    #[test]
+    fn test_dump_shred_bundle() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        assert!(dump_shred_bundle(&blockstore, 5).is_err());
+
+        let shreds = insert_slot(&blockstore, 5);
+        let other_shreds =
+            entries_to_test_shreds(&create_ticks(10, 0, Hash::default()), 5, 4, true, 0);
+        blockstore
+            .store_duplicate_slot(
+                5,
+                shreds[0].payload.clone(),
+                other_shreds[0].payload.clone(),
+            )
+            .unwrap();
+
+        let bundle = dump_shred_bundle(&blockstore, 5).unwrap();
+        assert_eq!(bundle.slot, 5);
+        assert_eq!(bundle.parent_slot, Some(4));
+        assert!(bundle.is_full);
+        assert!(!bundle.is_dead);
+        assert_eq!(bundle.shreds.len(), shreds.len());
+        assert_eq!(
+            bundle.shreds[0].signature,
+            shreds[0].signature().to_string()
+        );
+        assert_eq!(bundle.duplicate_proof.len(), 2);
+        assert_eq!(
+            bundle.duplicate_proof[1].signature,
+            other_shreds[0].signature().to_string()
+        );
+
+        let bundle_path = ledger_path.path().join("bundle.json");
+        bundle.save(&bundle_path).unwrap();
+        assert_eq!(ShredBundle::load(&bundle_path).unwrap(), bundle);
+    }
+
+    #[test]
+    fn test_diff_shred_bundles() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let other_ledger_path = get_tmp_ledger_path_auto_delete!();
+        let other_blockstore = Blockstore::open(other_ledger_path.path()).unwrap();
+
+        let shreds = insert_slot(&blockstore, 5);
+        let other_shreds = insert_slot(&other_blockstore, 5);
+        blockstore
+            .store_duplicate_slot(
+                5,
+                shreds[0].payload.clone(),
+                other_shreds[0].payload.clone(),
+            )
+            .unwrap();
+        let bundle = dump_shred_bundle(&blockstore, 5).unwrap();
+        let other_bundle = dump_shred_bundle(&other_blockstore, 5).unwrap();
+
+        let diff = diff_shred_bundles(&bundle, &bundle).unwrap();
+        assert_eq!(diff.num_matching, shreds.len());
+        assert!(diff.conflicting.is_empty());
+
+        let diff = diff_shred_bundles(&bundle, &other_bundle).unwrap();
+        assert_eq!(diff.num_matching, 0);
+        assert_eq!(diff.conflicting.len(), shreds.len());
+        assert!(diff.conflicting[0].in_duplicate_proof);
+        assert!(!diff.conflicting[1].in_duplicate_proof);
+
+        let mut partial_bundle = dump_shred_bundle(&other_blockstore, 5).unwrap();
+        partial_bundle.shreds = bundle.shreds[1..].to_vec();
+        let diff = diff_shred_bundles(&bundle, &partial_bundle).unwrap();
+        assert_eq!(diff.num_matching, shreds.len() - 1);
+        assert_eq!(diff.only_in_first, vec![bundle.shreds[0].clone()]);
+        assert!(diff.only_in_second.is_empty());
+
+        insert_slot(&blockstore, 6);
+        let bundle_6 = dump_shred_bundle(&blockstore, 6).unwrap();
+        assert!(diff_shred_bundles(&bundle, &bundle_6).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+/// Replay of a single block on top of its parent bank for the `simulate-block` subcommand
+use {
+    crossbeam_channel::unbounded,
+    solana_ledger::{
+        blockstore::Blockstore,
+        blockstore_processor::{self, TransactionStatusMessage, TransactionStatusSender},
+        leader_schedule_cache::LeaderScheduleCache,
+    },
+    solana_runtime::bank::{Bank, TransactionExecutionResult},
+    solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey, signature::Signature, transaction},
+    std::sync::Arc,
+};
+
+/// Changes to the conditions the block was originally replayed under
+pub struct SimulateBlockOptions {
+    pub activate_features: Vec<Pubkey>,
+    pub deactivate_features: Vec<Pubkey>,
+    /// Compute units the block may consume, enforced when `gate_large_block` is active
+    pub block_cost_limit: u64,
+}
+
+pub struct TransactionOutcome {
+    pub signature: Signature,
+    /// `false` if the transaction was dropped without being committed, e.g. for an expired
+    /// blockhash
+    pub executed: bool,
+    pub result: transaction::Result<()>,
+    pub log_messages: Option<Vec<String>>,
+}
+
+pub struct SimulatedBlock {
+    pub slot: Slot,
+    pub parent_slot: Slot,
+    /// Error that would have marked the block dead
+    pub replay_error: Option<String>,
+    /// Hash of the bank after the block, if it replayed successfully
+    pub bank_hash: Option<Hash>,
+    /// Hash of the bank recorded in the ledger when the block was originally replayed
+    pub ledger_bank_hash: Option<Hash>,
+    pub transactions: Vec<TransactionOutcome>,
+}
+
+/// Replays the block of `slot` on top of `parent`, its parent bank, under the conditions of
+/// `options`.
+///
+/// The replayed transactions are recorded in the status cache `parent` shares with its
+/// descendants, so a block can only be simulated once on top of the same parent.
+pub fn simulate_block(
+    parent: &Arc<Bank>,
+    leader_schedule_cache: &LeaderScheduleCache,
+    blockstore: &Blockstore,
+    slot: Slot,
+    options: &SimulateBlockOptions,
+) -> Result<SimulatedBlock, String> {
+    let (entries, _num_shreds, is_full) = blockstore
+        .get_slot_entries_with_shred_info(slot, 0, true)
+        .map_err(|err| format!("Failed to load entries of slot {}: {:?}", slot, err))?;
+    if !is_full {
+        return Err(format!("Slot {} is not full", slot));
+    }
+    let leader = leader_schedule_cache
+        .slot_leader_at(slot, Some(parent))
+        .ok_or_else(|| format!("Unknown leader of slot {}", slot))?;
+
+    let mut bank = Bank::new_from_parent(parent, &leader, slot);
+    for feature_id in &options.activate_features {
+        bank.activate_feature(feature_id);
+    }
+    for feature_id in &options.deactivate_features {
+        bank.deactivate_feature(feature_id);
+    }
+    let bank = Arc::new(bank);
+
+    let (sender, receiver) = unbounded();
+    let transaction_status_sender = TransactionStatusSender {
+        sender,
+        enable_cpi_and_log_storage: true,
+    };
+    let result = blockstore_processor::process_entries_with_block_cost_limit(
+        &bank,
+        entries,
+        Some(&transaction_status_sender),
+        options.block_cost_limit,
+    );
+    drop(transaction_status_sender);
+
+    let mut transactions = vec![];
+    for message in receiver.iter() {
+        if let TransactionStatusMessage::Batch(batch) = message {
+            for (transaction, execution_result) in batch
+                .transactions
+                .iter()
+                .zip(batch.execution_results.into_iter())
+            {
+                let result = execution_result.flattened_result();
+                let (executed, log_messages) = match execution_result {
+                    TransactionExecutionResult::Executed(details) => (true, details.log_messages),
+                    TransactionExecutionResult::NotExecuted(_) => (false, None),
+                };
+                transactions.push(TransactionOutcome {
+                    signature: *transaction.signature(),
+                    executed,
+                    result,
+                    log_messages,
+                });
+            }
+        }
+    }
+
+    let replay_error = match result {
+        Err(err) => Some(format!("{:?}", err)),
+        Ok(()) if !bank.is_complete() => Some("Block is missing ticks".to_string()),
+        Ok(()) => None,
+    };
+    let bank_hash = if replay_error.is_none() {
+        bank.freeze();
+        Some(bank.hash())
+    } else {
+        None
+    };
+
+    Ok(SimulatedBlock {
+        slot,
+        parent_slot: parent.slot(),
+        replay_error,
+        bank_hash,
+        ledger_bank_hash: blockstore.get_bank_hash(slot),
+        transactions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::{create_ticks, next_entry},
+        solana_ledger::{
+            blockstore::entries_to_test_shreds,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            get_tmp_ledger_path_auto_delete,
+        },
+        solana_sdk::{feature_set, system_transaction},
+    };
+
+    #[test]
+    fn test_simulate_block() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let new_parent = || Arc::new(Bank::new_for_tests(&genesis_config));
+        let parent = new_parent();
+        let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&parent);
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let to = Pubkey::new_unique();
+        let transfer = system_transaction::transfer(&mint_keypair, &to, 1, parent.last_blockhash());
+        let failed_transfer = system_transaction::transfer(
+            &mint_keypair,
+            &Pubkey::new_unique(),
+            1_000_000,
+            parent.last_blockhash(),
+        );
+        let entry = next_entry(
+            &parent.last_blockhash(),
+            1,
+            vec![transfer.clone(), failed_transfer.clone()],
+        );
+        let mut entries = vec![entry.clone()];
+        entries.extend(create_ticks(genesis_config.ticks_per_slot, 0, entry.hash));
+        blockstore
+            .insert_shreds(entries_to_test_shreds(&entries, 1, 0, true, 0), None, false)
+            .unwrap();
+
+        let options = SimulateBlockOptions {
+            activate_features: vec![],
+            deactivate_features: vec![feature_set::gate_large_block::id()],
+            block_cost_limit: 0,
+        };
+        let simulated_block =
+            simulate_block(&parent, &leader_schedule_cache, &blockstore, 1, &options).unwrap();
+        assert_eq!(simulated_block.parent_slot, 0);
+        assert_eq!(simulated_block.replay_error, None);
+        assert_eq!(simulated_block.transactions.len(), 2);
+        let outcome = |signature| {
+            simulated_block
+                .transactions
+                .iter()
+                .find(|outcome| outcome.signature == signature)
+                .unwrap()
+        };
+        assert!(outcome(transfer.signatures[0]).result.is_ok());
+        assert!(outcome(transfer.signatures[0]).log_messages.is_some());
+        assert!(outcome(failed_transfer.signatures[0]).executed);
+        assert!(outcome(failed_transfer.signatures[0]).result.is_err());
+
+        // The simulated bank matches a regular replay of the block
+        let leader = leader_schedule_cache
+            .slot_leader_at(1, Some(&parent))
+            .unwrap();
+        let mut bank = Bank::new_from_parent(&new_parent(), &leader, 1);
+        bank.deactivate_feature(&feature_set::gate_large_block::id());
+        let bank = Arc::new(bank);
+        blockstore_processor::process_entries_for_tests(&bank, entries, false, None, None).unwrap();
+        bank.freeze();
+        assert_eq!(simulated_block.bank_hash, Some(bank.hash()));
+
+        // The block no longer fits once the cost limit is enforced
+        let options = SimulateBlockOptions {
+            activate_features: vec![feature_set::gate_large_block::id()],
+            deactivate_features: vec![],
+            ..options
+        };
+        let simulated_block = simulate_block(
+            &new_parent(),
+            &leader_schedule_cache,
+            &blockstore,
+            1,
+            &options,
+        )
+        .unwrap();
+        assert!(simulated_block.replay_error.is_some());
+        assert_eq!(simulated_block.bank_hash, None);
+
+        assert!(simulate_block(
+            &new_parent(),
+            &leader_schedule_cache,
+            &blockstore,
+            2,
+            &options
+        )
+        .is_err());
+    }
+}
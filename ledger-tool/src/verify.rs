@@ -0,0 +1,333 @@
+/// Parallel, checkpointed replay for the `verify` subcommand
+use {
+    crossbeam_channel::unbounded,
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_ledger::{
+        bank_forks_utils,
+        blockstore::Blockstore,
+        blockstore_processor::{self, ProcessOptions},
+    },
+    solana_runtime::{
+        bank_forks::BankForks,
+        snapshot_archive_info::{FullSnapshotArchiveInfo, SnapshotArchiveInfoGetter},
+        snapshot_utils,
+    },
+    solana_sdk::{clock::Slot, genesis_config::GenesisConfig},
+    std::{
+        collections::HashSet,
+        fmt, fs,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::Builder,
+    },
+};
+
+/// A fork-free stretch of the rooted ledger between two full snapshot archives. Replaying it from
+/// the bank of `start` (or genesis) must reproduce the accounts hash recorded in `end`. The last
+/// range of the ledger has no `end` and is replayed as far as the ledger goes.
+struct VerifyRange {
+    start: Option<FullSnapshotArchiveInfo>,
+    end: Option<FullSnapshotArchiveInfo>,
+}
+
+impl VerifyRange {
+    fn start_slot(&self) -> Slot {
+        self.start.as_ref().map(|start| start.slot()).unwrap_or(0)
+    }
+
+    fn end_slot(&self) -> Option<Slot> {
+        self.end.as_ref().map(|end| end.slot())
+    }
+}
+
+impl fmt::Display for VerifyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.start {
+            None => write!(f, "range [genesis, ")?,
+            Some(start) => write!(f, "range [{}, ", start.slot())?,
+        }
+        match self.end_slot() {
+            None => write!(f, "..]"),
+            Some(end_slot) => write!(f, "{}]", end_slot),
+        }
+    }
+}
+
+/// Progress of a verification, persisted after every verified range so that an interrupted
+/// verification can resume where it left off
+#[derive(Default, Serialize, Deserialize)]
+struct VerifyCheckpoint {
+    /// `(start_slot, end_slot)` of every verified range
+    verified_ranges: HashSet<(Slot, Slot)>,
+}
+
+impl VerifyCheckpoint {
+    fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = fs::File::open(path)
+            .map_err(|err| format!("Unable to open {}: {}", path.display(), err))?;
+        serde_json::from_reader(file)
+            .map_err(|err| format!("Unable to parse {}: {}", path.display(), err))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        // Write to a temporary file first so an interruption never leaves a truncated checkpoint
+        let tmp_path = path.with_extension("tmp");
+        let file = fs::File::create(&tmp_path)
+            .map_err(|err| format!("Unable to create {}: {}", tmp_path.display(), err))?;
+        serde_json::to_writer(file, self)
+            .map_err(|err| format!("Unable to write {}: {}", tmp_path.display(), err))?;
+        fs::rename(&tmp_path, path)
+            .map_err(|err| format!("Unable to write {}: {}", path.display(), err))
+    }
+}
+
+/// Splits the ledger into ranges bounded by the full snapshot archives found in
+/// `snapshot_archives_dir`, ignoring archives past `halt_at_slot`
+fn get_verify_ranges(
+    blockstore: &Blockstore,
+    snapshot_archives_dir: &Path,
+    halt_at_slot: Option<Slot>,
+) -> Vec<VerifyRange> {
+    let mut archives: Vec<_> = snapshot_utils::get_full_snapshot_archives(snapshot_archives_dir)
+        .into_iter()
+        .filter(|archive| halt_at_slot.map_or(true, |halt_at_slot| archive.slot() <= halt_at_slot))
+        .collect();
+    archives.sort_unstable_by_key(|archive| archive.slot());
+    archives.dedup_by_key(|archive| archive.slot());
+
+    let mut ranges = vec![];
+    // The ledger can only be replayed from genesis when it hasn't been purged yet
+    if archives.is_empty() || blockstore.is_full(0) {
+        ranges.push(VerifyRange {
+            start: None,
+            end: archives.first().cloned(),
+        });
+    }
+    for (start, end) in archives.iter().zip(archives.iter().skip(1)) {
+        ranges.push(VerifyRange {
+            start: Some(start.clone()),
+            end: Some(end.clone()),
+        });
+    }
+    if let Some(last) = archives.last() {
+        if halt_at_slot != Some(last.slot()) {
+            ranges.push(VerifyRange {
+                start: Some(last.clone()),
+                end: None,
+            });
+        }
+    }
+    ranges
+}
+
+fn replay_range(
+    range: &VerifyRange,
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    account_paths: Vec<PathBuf>,
+    bank_snapshots_dir: &Path,
+    process_options: ProcessOptions,
+) -> Result<BankForks, String> {
+    let (accounts_package_sender, _) = unbounded();
+    match &range.start {
+        None => blockstore_processor::process_blockstore(
+            genesis_config,
+            blockstore,
+            account_paths,
+            process_options,
+            None,
+            None,
+            accounts_package_sender,
+            None,
+        )
+        .map(|(bank_forks, ..)| bank_forks),
+        Some(start) => bank_forks_utils::load_from_full_snapshot_archive(
+            genesis_config,
+            blockstore,
+            account_paths,
+            bank_snapshots_dir,
+            start,
+            process_options,
+            accounts_package_sender,
+        )
+        .map(|(bank_forks, ..)| bank_forks),
+    }
+    .map_err(|err| format!("{}: {:?}", range, err))
+}
+
+fn verify_range(
+    range: &VerifyRange,
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    account_paths: &[PathBuf],
+    process_options: &ProcessOptions,
+) -> Result<(), String> {
+    // Every range gets its own accounts and bank snapshot directories so that ranges can be
+    // replayed side by side
+    let range_dir = format!("verify-range-{}", range.start_slot());
+    let account_paths: Vec<_> = account_paths
+        .iter()
+        .map(|path| path.join(&range_dir))
+        .collect();
+    let bank_snapshots_dir = blockstore
+        .ledger_path()
+        .join(format!("snapshot.{}", range_dir));
+    let remove_range_dirs = || {
+        for path in account_paths.iter().chain(Some(&bank_snapshots_dir)) {
+            let _ = fs::remove_dir_all(path);
+        }
+    };
+    // Clear anything left behind by an interrupted verification
+    remove_range_dirs();
+
+    let process_options = ProcessOptions {
+        dev_halt_at_slot: range.end_slot().or(process_options.dev_halt_at_slot),
+        ..process_options.clone()
+    };
+    let result = replay_range(
+        range,
+        genesis_config,
+        blockstore,
+        account_paths.clone(),
+        &bank_snapshots_dir,
+        process_options,
+    )
+    .and_then(|bank_forks| {
+        let end = match &range.end {
+            Some(end) => end,
+            None => return Ok(()),
+        };
+        let bank = bank_forks
+            .get(end.slot())
+            .ok_or_else(|| format!("{}: slot {} was not reached", range, end.slot()))?;
+        let accounts_hash = bank.update_accounts_hash();
+        if accounts_hash != *end.hash() {
+            return Err(format!(
+                "{}: accounts hash mismatch at slot {}: replayed {}, snapshot archive has {}",
+                range,
+                end.slot(),
+                accounts_hash,
+                end.hash()
+            ));
+        }
+        Ok(())
+    });
+    remove_range_dirs();
+    result
+}
+
+/// Replays the ranges of the ledger between full snapshot archives on up to `num_threads`
+/// banks at once.
+///
+/// When `checkpoint_path` is given, ranges recorded there as verified by a previous run are
+/// skipped, and every newly verified range is added to it.
+pub fn verify_ledger_ranges(
+    genesis_config: &GenesisConfig,
+    blockstore: Arc<Blockstore>,
+    snapshot_archives_dir: &Path,
+    account_paths: Vec<PathBuf>,
+    process_options: ProcessOptions,
+    num_threads: usize,
+    checkpoint_path: Option<PathBuf>,
+) -> Result<(), String> {
+    let checkpoint = match &checkpoint_path {
+        Some(checkpoint_path) => VerifyCheckpoint::load(checkpoint_path)?,
+        None => VerifyCheckpoint::default(),
+    };
+
+    let (range_sender, range_receiver) = unbounded();
+    let mut num_ranges = 0;
+    for range in get_verify_ranges(
+        &blockstore,
+        snapshot_archives_dir,
+        process_options.dev_halt_at_slot,
+    ) {
+        let verified = range.end_slot().map_or(false, |end_slot| {
+            checkpoint
+                .verified_ranges
+                .contains(&(range.start_slot(), end_slot))
+        });
+        if verified {
+            info!("Skipping {}, verified by a previous run", range);
+        } else {
+            range_sender.send(range).unwrap();
+            num_ranges += 1;
+        }
+    }
+    drop(range_sender);
+    println!(
+        "Verifying {} ledger range(s) on {} thread(s)",
+        num_ranges, num_threads
+    );
+
+    let checkpoint = Arc::new(Mutex::new(checkpoint));
+    let errors = Arc::new(Mutex::new(vec![]));
+    let failed = Arc::new(AtomicBool::new(false));
+    let genesis_config = Arc::new(genesis_config.clone());
+    let threads: Vec<_> = (0..num_threads.min(num_ranges))
+        .map(|i| {
+            let range_receiver = range_receiver.clone();
+            let genesis_config = genesis_config.clone();
+            let blockstore = blockstore.clone();
+            let account_paths = account_paths.clone();
+            let process_options = process_options.clone();
+            let checkpoint_path = checkpoint_path.clone();
+            let checkpoint = checkpoint.clone();
+            let errors = errors.clone();
+            let failed = failed.clone();
+            Builder::new()
+                .name(format!("verify-range-{}", i))
+                .spawn(move || {
+                    for range in range_receiver.iter() {
+                        // Stop picking up new ranges once one of them failed
+                        if failed.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        println!("Verifying {}", range);
+                        let result = verify_range(
+                            &range,
+                            &genesis_config,
+                            &blockstore,
+                            &account_paths,
+                            &process_options,
+                        )
+                        .and_then(|()| {
+                            println!("Verified {}", range);
+                            match (range.end_slot(), &checkpoint_path) {
+                                (Some(end_slot), Some(checkpoint_path)) => {
+                                    let mut checkpoint = checkpoint.lock().unwrap();
+                                    checkpoint
+                                        .verified_ranges
+                                        .insert((range.start_slot(), end_slot));
+                                    checkpoint.save(checkpoint_path)
+                                }
+                                _ => Ok(()),
+                            }
+                        });
+                        if let Err(err) = result {
+                            failed.store(true, Ordering::Relaxed);
+                            errors.lock().unwrap().push(err);
+                        }
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let errors = errors.lock().unwrap();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
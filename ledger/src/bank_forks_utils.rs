@@ -12,14 +12,18 @@ use {
     solana_runtime::{
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         bank_forks::BankForks,
-        snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_archive_info::{FullSnapshotArchiveInfo, SnapshotArchiveInfoGetter},
         snapshot_config::SnapshotConfig,
         snapshot_hash::{FullSnapshotHash, IncrementalSnapshotHash, StartingSnapshotHashes},
         snapshot_package::AccountsPackageSender,
         snapshot_utils,
     },
     solana_sdk::{clock::Slot, genesis_config::GenesisConfig},
-    std::{fs, path::PathBuf, process, result},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        process, result,
+    },
 };
 
 pub type LoadResult = result::Result<
@@ -120,6 +124,76 @@ pub fn load(
     )
 }
 
+/// Load the banks and accounts from the given full snapshot archive, rather than from the latest
+/// one in a snapshot archives directory
+///
+/// No snapshots are taken while processing the blockstore.  This allows multiple ranges of the
+/// ledger to be loaded side by side, each one from its own snapshot archive.
+pub fn load_from_full_snapshot_archive(
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    account_paths: Vec<PathBuf>,
+    bank_snapshots_dir: &Path,
+    full_snapshot_archive_info: &FullSnapshotArchiveInfo,
+    process_options: ProcessOptions,
+    accounts_package_sender: AccountsPackageSender,
+) -> LoadResult {
+    let _ = fs::remove_dir_all(bank_snapshots_dir);
+    fs::create_dir_all(bank_snapshots_dir).expect("Couldn't create snapshot directory");
+
+    let (deserialized_bank, timings) = snapshot_utils::bank_from_snapshot_archives(
+        &account_paths,
+        bank_snapshots_dir,
+        full_snapshot_archive_info,
+        None,
+        genesis_config,
+        process_options.debug_keys.clone(),
+        Some(&crate::builtins::get(process_options.bpf_jit)),
+        process_options.account_indexes.clone(),
+        process_options.accounts_db_caching_enabled,
+        process_options.limit_load_slot_count_from_snapshot,
+        process_options.shrink_ratio,
+        process_options.accounts_db_test_hash_calculation,
+        process_options.accounts_db_skip_shrink,
+        process_options.verify_index,
+        process_options.accounts_db_config.clone(),
+        None,
+    )
+    .map_err(|err| {
+        BlockstoreProcessorError::FailedToLoadSnapshot(format!(
+            "{}: {}",
+            full_snapshot_archive_info.path().display(),
+            err
+        ))
+    })?;
+
+    let starting_snapshot_hashes = StartingSnapshotHashes {
+        full: FullSnapshotHash {
+            hash: (
+                full_snapshot_archive_info.slot(),
+                *full_snapshot_archive_info.hash(),
+            ),
+        },
+        incremental: None,
+    };
+
+    to_loadresult(
+        blockstore_processor::process_blockstore_from_root(
+            blockstore,
+            deserialized_bank,
+            &process_options,
+            &VerifyRecyclers::default(),
+            None,
+            None,
+            None,
+            accounts_package_sender,
+            timings,
+            full_snapshot_archive_info.slot(),
+        ),
+        Some(starting_snapshot_hashes),
+    )
+}
+
 fn load_from_genesis(
     genesis_config: &GenesisConfig,
     blockstore: &Blockstore,
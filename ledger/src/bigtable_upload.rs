@@ -3,9 +3,13 @@ use {
     crossbeam_channel::bounded,
     log::*,
     solana_measure::measure::Measure,
-    solana_sdk::clock::Slot,
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    solana_storage_bigtable::LedgerStorage,
+    solana_transaction_status::{VersionedConfirmedBlock, VersionedTransactionWithStatusMeta},
     std::{
         collections::HashSet,
+        fs,
+        path::{Path, PathBuf},
         result::Result,
         sync::{
             atomic::{AtomicBool, Ordering},
@@ -18,20 +22,140 @@ use {
 // Attempt to upload this many blocks in parallel
 const NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL: usize = 32;
 
-// Read up to this many blocks from blockstore before blocking on the upload process
-const BLOCK_READ_AHEAD_DEPTH: usize = NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL * 2;
+// Delay before the first retry of a failed block upload, doubled on every further retry
+const INITIAL_UPLOAD_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Debug)]
+pub struct ConfirmedBlockUploadConfig {
+    /// Upload blocks that are already present in bigtable too
+    pub force_reupload: bool,
+    /// Upload blocks with transactions missing their metadata instead of panicking
+    pub allow_missing_metadata: bool,
+    /// Only upload the blocks with a transaction that mentions one of these addresses. All
+    /// blocks are uploaded if this is empty.
+    pub addresses: HashSet<Pubkey>,
+    /// Skip the blocks without any transaction other than votes
+    pub skip_vote_only_blocks: bool,
+    /// List the blocks that would be uploaded instead of uploading them
+    pub dry_run: bool,
+    /// File recording the last slot up to which every block has been uploaded. An upload
+    /// resumes after that slot, and updates it as blocks get uploaded.
+    pub progress_path: Option<PathBuf>,
+    /// Number of blocks uploaded in parallel
+    pub num_blocks_to_upload_in_parallel: usize,
+    /// Number of times the upload of a block is retried, with exponential backoff, before it is
+    /// counted as failed
+    pub max_upload_retries: usize,
+}
+
+impl Default for ConfirmedBlockUploadConfig {
+    fn default() -> Self {
+        Self {
+            force_reupload: false,
+            allow_missing_metadata: false,
+            addresses: HashSet::new(),
+            skip_vote_only_blocks: false,
+            dry_run: false,
+            progress_path: None,
+            num_blocks_to_upload_in_parallel: NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL,
+            max_upload_retries: 0,
+        }
+    }
+}
+
+impl ConfirmedBlockUploadConfig {
+    /// Whether `block` passes the address and vote filters
+    fn includes(&self, block: &VersionedConfirmedBlock) -> bool {
+        if self.skip_vote_only_blocks && block.transactions.iter().all(is_vote_transaction) {
+            return false;
+        }
+        self.addresses.is_empty()
+            || block.transactions.iter().any(|transaction| {
+                transaction
+                    .account_keys()
+                    .iter()
+                    .any(|key| self.addresses.contains(key))
+            })
+    }
+}
+
+fn is_vote_transaction(transaction: &VersionedTransactionWithStatusMeta) -> bool {
+    let message = &transaction.transaction.message;
+    message.instructions().iter().all(|instruction| {
+        solana_vote_program::check_id(instruction.program_id(message.static_account_keys()))
+    })
+}
+
+fn load_upload_progress(progress_path: &Path) -> Result<Option<Slot>, Box<dyn std::error::Error>> {
+    if !progress_path.exists() {
+        return Ok(None);
+    }
+    let progress = fs::read_to_string(progress_path)?;
+    let slot = progress.trim().parse().map_err(|err| {
+        format!(
+            "Invalid upload progress in {}: {}",
+            progress_path.display(),
+            err
+        )
+    })?;
+    Ok(Some(slot))
+}
+
+fn save_upload_progress(progress_path: &Path, slot: Slot) -> std::io::Result<()> {
+    // Write to a temporary file first so an interruption never leaves a truncated progress file
+    let tmp_path = progress_path.with_extension("tmp");
+    fs::write(&tmp_path, slot.to_string())?;
+    fs::rename(&tmp_path, progress_path)
+}
+
+async fn upload_confirmed_block_with_backoff(
+    bigtable: &dyn LedgerStorage,
+    slot: Slot,
+    confirmed_block: VersionedConfirmedBlock,
+    max_upload_retries: usize,
+) -> solana_storage_bigtable::Result<()> {
+    let mut retry_delay = INITIAL_UPLOAD_RETRY_DELAY;
+    for _ in 0..max_upload_retries {
+        match bigtable
+            .upload_confirmed_block(slot, confirmed_block.clone())
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                warn!(
+                    "upload_confirmed_block({}) failed, retrying in {:?}: {:?}",
+                    slot, retry_delay, err
+                );
+                tokio::time::sleep(retry_delay).await;
+                retry_delay *= 2;
+            }
+        }
+    }
+    bigtable.upload_confirmed_block(slot, confirmed_block).await
+}
 
 pub async fn upload_confirmed_blocks(
     blockstore: Arc<Blockstore>,
-    bigtable: solana_storage_bigtable::LedgerStorage,
+    bigtable: Arc<dyn LedgerStorage>,
     starting_slot: Slot,
     ending_slot: Option<Slot>,
-    allow_missing_metadata: bool,
-    force_reupload: bool,
+    config: ConfirmedBlockUploadConfig,
     exit: Arc<AtomicBool>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut measure = Measure::start("entire upload");
 
+    let starting_slot = match config.progress_path.as_deref().map(load_upload_progress) {
+        Some(Ok(Some(uploaded_slot))) if uploaded_slot >= starting_slot => {
+            info!(
+                "Resuming upload after slot {}, the last uploaded slot",
+                uploaded_slot
+            );
+            uploaded_slot + 1
+        }
+        Some(Err(err)) => return Err(err),
+        _ => starting_slot,
+    };
+
     info!("Loading ledger slots starting at {}...", starting_slot);
     let blockstore_slots: Vec<_> = blockstore
         .slot_meta_iterator(starting_slot)
@@ -67,7 +191,7 @@ pub async fn upload_confirmed_blocks(
     );
 
     // Gather the blocks that are already present in bigtable, by slot
-    let bigtable_slots = if !force_reupload {
+    let bigtable_slots = if !config.force_reupload {
         let mut bigtable_slots = vec![];
         let first_blockstore_slot = *blockstore_slots.first().unwrap();
         let last_blockstore_slot = *blockstore_slots.last().unwrap();
@@ -127,11 +251,16 @@ pub async fn upload_confirmed_blocks(
         blocks_to_upload.last().unwrap()
     );
 
+    let num_blocks_to_upload_in_parallel = config.num_blocks_to_upload_in_parallel.max(1);
+    // Read up to this many blocks from blockstore before blocking on the upload process
+    let block_read_ahead_depth = num_blocks_to_upload_in_parallel * 2;
+
     // Load the blocks out of blockstore in a separate thread to allow for concurrent block uploading
     let (_loader_thread, receiver) = {
         let exit = exit.clone();
+        let config = config.clone();
 
-        let (sender, receiver) = bounded(BLOCK_READ_AHEAD_DEPTH);
+        let (sender, receiver) = bounded(block_read_ahead_depth);
         (
             std::thread::spawn(move || {
                 let mut measure = Measure::start("block loader thread");
@@ -141,6 +270,10 @@ pub async fn upload_confirmed_blocks(
                     }
 
                     let _ = match blockstore.get_rooted_block(*slot, true) {
+                        Ok(confirmed_block) if !config.includes(&confirmed_block) => {
+                            debug!("Skipping block {} excluded by the upload filters", slot);
+                            sender.send((*slot, None))
+                        }
                         Ok(confirmed_block) => sender.send((*slot, Some(confirmed_block))),
                         Err(err) => {
                             warn!(
@@ -151,7 +284,7 @@ pub async fn upload_confirmed_blocks(
                         }
                     };
 
-                    if i > 0 && i % NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL == 0 {
+                    if i > 0 && i % num_blocks_to_upload_in_parallel == 0 {
                         info!(
                             "{}% of blocks processed ({}/{})",
                             i * 100 / blocks_to_upload.len(),
@@ -171,7 +304,7 @@ pub async fn upload_confirmed_blocks(
     use futures::stream::StreamExt;
 
     let mut stream =
-        tokio_stream::iter(receiver.into_iter()).chunks(NUM_BLOCKS_TO_UPLOAD_IN_PARALLEL);
+        tokio_stream::iter(receiver.into_iter()).chunks(num_blocks_to_upload_in_parallel);
 
     while let Some(blocks) = stream.next().await {
         if exit.load(Ordering::Relaxed) {
@@ -180,8 +313,22 @@ pub async fn upload_confirmed_blocks(
 
         let mut measure_upload = Measure::start("Upload");
         let mut num_blocks = blocks.len();
+        let last_slot = blocks.last().map(|(slot, _)| *slot);
         info!("Preparing the next {} blocks for upload", num_blocks);
 
+        if config.dry_run {
+            for (slot, block) in blocks {
+                if let Some(confirmed_block) = block {
+                    info!(
+                        "Would upload block {} with {} transactions",
+                        slot,
+                        confirmed_block.transactions.len()
+                    );
+                }
+            }
+            continue;
+        }
+
         let uploads = blocks.into_iter().filter_map(|(slot, block)| match block {
             None => {
                 num_blocks -= 1;
@@ -193,13 +340,18 @@ pub async fn upload_confirmed_blocks(
                     .iter()
                     .any(|transaction| transaction.meta.is_none())
                 {
-                    if allow_missing_metadata {
+                    if config.allow_missing_metadata {
                         info!("Transaction metadata missing from slot {}", slot);
                     } else {
                         panic!("Transaction metadata missing from slot {}", slot);
                     }
                 }
-                Some(bigtable.upload_confirmed_block(slot, confirmed_block))
+                Some(upload_confirmed_block_with_backoff(
+                    bigtable.as_ref(),
+                    slot,
+                    confirmed_block,
+                    config.max_upload_retries,
+                ))
             }
         });
 
@@ -210,6 +362,19 @@ pub async fn upload_confirmed_blocks(
             }
         }
 
+        // Progress only advances while every block before it has been uploaded
+        if let (0, Some(progress_path), Some(last_slot)) =
+            (failures, &config.progress_path, last_slot)
+        {
+            save_upload_progress(progress_path, last_slot).map_err(|err| {
+                format!(
+                    "Unable to save upload progress to {}: {}",
+                    progress_path.display(),
+                    err
+                )
+            })?;
+        }
+
         measure_upload.stop();
         info!("{} for {} blocks", measure_upload, num_blocks);
     }
@@ -222,3 +387,83 @@ pub async fn upload_confirmed_blocks(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash,
+            signature::{Keypair, Signer},
+            system_transaction,
+            transaction::Transaction,
+        },
+        solana_vote_program::vote_transaction,
+    };
+
+    fn new_block(transactions: Vec<Transaction>) -> VersionedConfirmedBlock {
+        VersionedConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::default().to_string(),
+            parent_slot: 0,
+            transactions: transactions
+                .into_iter()
+                .map(|transaction| VersionedTransactionWithStatusMeta {
+                    transaction: transaction.into(),
+                    meta: None,
+                })
+                .collect(),
+            rewards: vec![],
+            block_time: None,
+            block_height: None,
+        }
+    }
+
+    #[test]
+    fn test_upload_config_includes() {
+        let keypair = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let vote = vote_transaction::new_vote_transaction(
+            vec![0],
+            Hash::default(),
+            Hash::default(),
+            &keypair,
+            &keypair,
+            &keypair,
+            None,
+        );
+        let transfer = system_transaction::transfer(&keypair, &recipient, 1, Hash::default());
+        let vote_only_block = new_block(vec![vote.clone()]);
+        let transfer_block = new_block(vec![vote, transfer]);
+
+        let config = ConfirmedBlockUploadConfig::default();
+        assert!(config.includes(&vote_only_block));
+        assert!(config.includes(&transfer_block));
+
+        let config = ConfirmedBlockUploadConfig {
+            skip_vote_only_blocks: true,
+            ..ConfirmedBlockUploadConfig::default()
+        };
+        assert!(!config.includes(&vote_only_block));
+        assert!(config.includes(&transfer_block));
+
+        let config = ConfirmedBlockUploadConfig {
+            addresses: [recipient].into_iter().collect(),
+            ..ConfirmedBlockUploadConfig::default()
+        };
+        assert!(!config.includes(&vote_only_block));
+        assert!(config.includes(&transfer_block));
+    }
+
+    #[test]
+    fn test_upload_progress() {
+        let progress_dir = tempfile::tempdir().unwrap();
+        let progress_path = progress_dir.path().join("progress");
+        assert_eq!(load_upload_progress(&progress_path).unwrap(), None);
+        save_upload_progress(&progress_path, 42).unwrap();
+        assert_eq!(load_upload_progress(&progress_path).unwrap(), Some(42));
+
+        fs::write(&progress_path, "not a slot").unwrap();
+        assert!(load_upload_progress(&progress_path).is_err());
+    }
+}
@@ -5,6 +5,7 @@ pub use crate::{blockstore_db::BlockstoreError, blockstore_meta::SlotMeta};
 use {
     crate::{
         ancestor_iterator::AncestorIterator,
+        blockstore_archive::{ArchivedSlot, BlockstoreArchive},
         blockstore_db::{
             columns as cf, AccessType, BlockstoreOptions, Column, Database, IteratorDirection,
             IteratorMode, LedgerColumn, Result, WriteBatch,
@@ -13,8 +14,8 @@ use {
         leader_schedule_cache::LeaderScheduleCache,
         next_slots_iterator::NextSlotsIterator,
         shred::{
-            max_ticks_per_n_shreds, ErasureSetId, Result as ShredResult, Shred, ShredId, ShredType,
-            Shredder, SHRED_PAYLOAD_SIZE,
+            max_ticks_per_n_shreds, ErasureSetId, Result as ShredResult, Shred, ShredError,
+            ShredId, ShredType, Shredder, SHRED_PAYLOAD_SIZE,
         },
     },
     bincode::deserialize,
@@ -50,7 +51,9 @@ use {
         borrow::Cow,
         cell::RefCell,
         cmp,
-        collections::{hash_map::Entry as HashMapEntry, BTreeMap, BTreeSet, HashMap, HashSet},
+        collections::{
+            hash_map::Entry as HashMapEntry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque,
+        },
         convert::TryInto,
         fs,
         io::{Error as IoError, ErrorKind},
@@ -93,6 +96,9 @@ pub const MAX_TURBINE_DELAY_IN_TICKS: u64 = MAX_TURBINE_PROPAGATION_IN_MS / MS_P
 // (32K shreds per slot * 4 TX per shred * 2.5 slots per sec)
 pub const MAX_DATA_SHREDS_PER_SLOT: usize = 32_768;
 
+// Number of full slots whose shred source stats are kept around for `get_shred_source_stats`
+pub const MAX_SHRED_SOURCE_STATS_HISTORY: usize = 1_000;
+
 pub type CompletedSlotsSender = Sender<Vec<Slot>>;
 pub type CompletedSlotsReceiver = Receiver<Vec<Slot>>;
 type CompletedRanges = Vec<(u32, u32)>;
@@ -150,6 +156,7 @@ pub struct Blockstore {
     code_shred_cf: LedgerColumn<cf::ShredCode>,
     transaction_status_cf: LedgerColumn<cf::TransactionStatus>,
     address_signatures_cf: LedgerColumn<cf::AddressSignatures>,
+    program_signatures_cf: LedgerColumn<cf::ProgramSignatures>,
     transaction_memos_cf: LedgerColumn<cf::TransactionMemos>,
     transaction_status_index_cf: LedgerColumn<cf::TransactionStatusIndex>,
     active_transaction_status_index: RwLock<u64>,
@@ -159,6 +166,7 @@ pub struct Blockstore {
     block_height_cf: LedgerColumn<cf::BlockHeight>,
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
+    archive: Option<BlockstoreArchive>,
     last_root: Arc<RwLock<Slot>>,
     insert_shreds_lock: Arc<Mutex<()>>,
     pub new_shreds_signals: Vec<Sender<bool>>,
@@ -170,7 +178,9 @@ pub struct Blockstore {
 
 struct SlotsStats {
     last_cleanup_ts: Instant,
-    stats: BTreeMap<Slot, SlotStats>,
+    stats: BTreeMap<Slot, ShredSourceStats>,
+    // Stats of the most recently filled slots, in the order they became full
+    full_slots: VecDeque<(Slot, ShredSourceStats)>,
 }
 
 impl Default for SlotsStats {
@@ -178,14 +188,18 @@ impl Default for SlotsStats {
         SlotsStats {
             last_cleanup_ts: Instant::now(),
             stats: BTreeMap::new(),
+            full_slots: VecDeque::new(),
         }
     }
 }
 
-#[derive(Default)]
-struct SlotStats {
-    num_repaired: usize,
-    num_recovered: usize,
+/// Number of shreds of a slot inserted per source. Data and coding shreds are both counted when
+/// they arrive through turbine or repair, recovered shreds are always data shreds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShredSourceStats {
+    pub num_turbine: usize,
+    pub num_repaired: usize,
+    pub num_recovered: usize,
 }
 
 pub struct IndexMetaWorkingSetEntry {
@@ -355,6 +369,12 @@ impl Blockstore {
 
         adjust_ulimit_nofile(options.enforce_ulimit_nofile)?;
 
+        let archive = options
+            .archive
+            .as_ref()
+            .map(BlockstoreArchive::open)
+            .transpose()?;
+
         // Open the database
         let mut measure = Measure::start("open");
         info!("Opening database at {:?}", blockstore_path);
@@ -378,6 +398,7 @@ impl Blockstore {
         let code_shred_cf = db.column();
         let transaction_status_cf = db.column();
         let address_signatures_cf = db.column();
+        let program_signatures_cf = db.column();
         let transaction_memos_cf = db.column();
         let transaction_status_index_cf = db.column();
         let rewards_cf = db.column();
@@ -428,6 +449,7 @@ impl Blockstore {
             code_shred_cf,
             transaction_status_cf,
             address_signatures_cf,
+            program_signatures_cf,
             transaction_memos_cf,
             transaction_status_index_cf,
             active_transaction_status_index: RwLock::new(active_transaction_status_index),
@@ -437,6 +459,7 @@ impl Blockstore {
             block_height_cf,
             program_costs_cf,
             bank_hash_cf,
+            archive,
             new_shreds_signals: vec![],
             completed_slots_senders: vec![],
             insert_shreds_lock: Arc::new(Mutex::new(())),
@@ -622,6 +645,27 @@ impl Blockstore {
         Ok(slot_iterator.take_while(move |((shred_slot, _), _)| *shred_slot == slot))
     }
 
+    /// Iterates the cached block times of the slots starting from `slot`
+    pub fn block_time_iterator(
+        &self,
+        slot: Slot,
+    ) -> Result<impl Iterator<Item = (Slot, UnixTimestamp)> + '_> {
+        let block_time_iter = self
+            .db
+            .iter::<cf::Blocktime>(IteratorMode::From(slot, IteratorDirection::Forward))?;
+        Ok(block_time_iter.map(|(slot, block_time_bytes)| {
+            (
+                slot,
+                deserialize(&block_time_bytes).unwrap_or_else(|e| {
+                    panic!(
+                        "Could not deserialize block time for slot {}: {:?}",
+                        slot, e
+                    )
+                }),
+            )
+        }))
+    }
+
     pub fn rooted_slot_iterator(&self, slot: Slot) -> Result<impl Iterator<Item = u64> + '_> {
         let slot_iterator = self
             .db
@@ -1163,10 +1207,14 @@ impl Blockstore {
             return false;
         }
 
-        if is_repaired {
+        {
             let mut slots_stats = self.slots_stats.lock().unwrap();
             let mut e = slots_stats.stats.entry(slot).or_default();
-            e.num_repaired += 1;
+            if is_repaired {
+                e.num_repaired += 1;
+            } else {
+                e.num_turbine += 1;
+            }
         }
 
         // insert coding shred into rocks
@@ -1586,18 +1634,21 @@ impl Blockstore {
             end_index,
         })
         .collect();
-        if shred_source == ShredSource::Repaired || shred_source == ShredSource::Recovered {
+        {
             let mut slots_stats = self.slots_stats.lock().unwrap();
             let mut e = slots_stats.stats.entry(slot_meta.slot).or_default();
-            if shred_source == ShredSource::Repaired {
-                e.num_repaired += 1;
-            }
-            if shred_source == ShredSource::Recovered {
-                e.num_recovered += 1;
+            match shred_source {
+                ShredSource::Turbine => e.num_turbine += 1,
+                ShredSource::Repaired => e.num_repaired += 1,
+                ShredSource::Recovered => e.num_recovered += 1,
             }
         }
         if slot_meta.is_full() {
-            let (num_repaired, num_recovered) = {
+            let ShredSourceStats {
+                num_turbine,
+                num_repaired,
+                num_recovered,
+            } = {
                 let mut slots_stats = self.slots_stats.lock().unwrap();
                 if let Some(e) = slots_stats.stats.remove(&slot_meta.slot) {
                     if slots_stats.last_cleanup_ts.elapsed().as_secs() > 30 {
@@ -1605,9 +1656,13 @@ impl Blockstore {
                         slots_stats.stats = slots_stats.stats.split_off(&root);
                         slots_stats.last_cleanup_ts = Instant::now();
                     }
-                    (e.num_repaired, e.num_recovered)
+                    if slots_stats.full_slots.len() == MAX_SHRED_SOURCE_STATS_HISTORY {
+                        slots_stats.full_slots.pop_front();
+                    }
+                    slots_stats.full_slots.push_back((slot_meta.slot, e));
+                    e
                 } else {
-                    (0, 0)
+                    ShredSourceStats::default()
                 }
             };
             datapoint_info!(
@@ -1626,6 +1681,7 @@ impl Blockstore {
                         .unwrap_or(-1),
                     i64
                 ),
+                ("num_turbine", num_turbine, i64),
                 ("num_repaired", num_repaired, i64),
                 ("num_recovered", num_recovered, i64),
             );
@@ -1634,8 +1690,18 @@ impl Blockstore {
         Ok(newly_completed_data_sets)
     }
 
+    /// Returns a data shred as stored, reading through to the archive once the shred has been
+    /// archived
+    fn get_data_shred_bytes(&self, slot: Slot, index: u64) -> Result<Option<Vec<u8>>> {
+        let data = self.data_shred_cf.get_bytes((slot, index))?;
+        match (data, &self.archive) {
+            (None, Some(archive)) => Ok(archive.get_data_shred(slot, index)?),
+            (data, _) => Ok(data),
+        }
+    }
+
     pub fn get_data_shred(&self, slot: Slot, index: u64) -> Result<Option<Vec<u8>>> {
-        self.data_shred_cf.get_bytes((slot, index)).map(|data| {
+        self.get_data_shred_bytes(slot, index).map(|data| {
             data.map(|mut d| {
                 // Only data_header.size bytes stored in the blockstore so
                 // pad the payload out to SHRED_PAYLOAD_SIZE so that the
@@ -1651,6 +1717,27 @@ impl Blockstore {
         slot: Slot,
         start_index: u64,
     ) -> ShredResult<Vec<Shred>> {
+        if let Some(archive) = self
+            .archive
+            .as_ref()
+            .filter(|archive| archive.contains(slot))
+        {
+            let archived_slot = archive.get_slot(slot).map_err(|err| {
+                ShredError::Serialize(Box::new(bincode::ErrorKind::Custom(format!(
+                    "Could not read archived slot {}: {}",
+                    slot, err
+                ))))
+            })?;
+            return archived_slot
+                .map(|archived_slot| {
+                    archived_slot
+                        .data_shreds
+                        .range(start_index..)
+                        .map(|(_, data)| Shred::new_from_serialized_shred(data.clone()))
+                        .collect()
+                })
+                .unwrap_or_else(|| Ok(vec![]));
+        }
         self.slot_data_iterator(slot, start_index)
             .expect("blockstore couldn't fetch iterator")
             .map(|data| Shred::new_from_serialized_shred(data.1.to_vec()))
@@ -1699,7 +1786,11 @@ impl Blockstore {
     }
 
     pub fn get_coding_shred(&self, slot: Slot, index: u64) -> Result<Option<Vec<u8>>> {
-        self.code_shred_cf.get_bytes((slot, index))
+        let data = self.code_shred_cf.get_bytes((slot, index))?;
+        match (data, &self.archive) {
+            (None, Some(archive)) => Ok(archive.get_coding_shred(slot, index)?),
+            (data, _) => Ok(data),
+        }
     }
 
     pub fn get_coding_shreds_for_slot(
@@ -1713,6 +1804,94 @@ impl Blockstore {
             .collect()
     }
 
+    /// Moves the shreds of the next `slots_per_pack` rooted slots following the last archived
+    /// slot into a new archive pack, provided that enough slots are rooted up to `max_slot`.
+    /// Returns the slot range covered by the pack, or `None` if no pack was written.
+    ///
+    /// A pack covers the range between the slot following the previous pack and its last rooted
+    /// slot. The shreds of the unrooted slots in that range are dropped. Only the shreds leave
+    /// RocksDB: slot metas, roots and transaction statuses stay, and shred reads fall through to
+    /// the archive.
+    pub fn archive_next_pack(
+        &self,
+        max_slot: Slot,
+        slots_per_pack: usize,
+    ) -> Result<Option<(Slot, Slot)>> {
+        let archive = match &self.archive {
+            Some(archive) => archive,
+            None => return Ok(None),
+        };
+        let max_slot = max_slot.min(self.last_root());
+        // Keep the slots from being cleaned up while they are archived
+        let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
+        let first_slot =
+            archive
+                .last_archived_slot()
+                .map_or(lowest_available_slot, |last_archived_slot| {
+                    last_archived_slot
+                        .saturating_add(1)
+                        .max(lowest_available_slot)
+                });
+        let roots: Vec<_> = self
+            .rooted_slot_iterator(first_slot)?
+            .take_while(|slot| *slot <= max_slot)
+            .take(slots_per_pack)
+            .collect();
+        if roots.is_empty() || roots.len() < slots_per_pack {
+            return Ok(None);
+        }
+        let last_slot = *roots.last().unwrap();
+
+        let mut measure = Measure::start("archive_pack");
+        let slots = roots
+            .iter()
+            .map(|slot| {
+                let data_shreds = self
+                    .slot_data_iterator(*slot, 0)?
+                    .map(|((_, index), data)| (index, data.to_vec()))
+                    .collect();
+                let coding_shreds = self
+                    .slot_coding_iterator(*slot, 0)?
+                    .map(|((_, index), data)| (index, data.to_vec()))
+                    .collect();
+                Ok((
+                    *slot,
+                    ArchivedSlot {
+                        data_shreds,
+                        coding_shreds,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let pack_size = archive.write_pack(first_slot, last_slot, &slots)?;
+
+        // The pack is readable, so the shreds can go
+        let mut write_batch = self.db.batch()?;
+        self.db.delete_range_cf::<cf::ShredData>(
+            &mut write_batch,
+            first_slot,
+            last_slot.saturating_add(1),
+        )?;
+        self.db.delete_range_cf::<cf::ShredCode>(
+            &mut write_batch,
+            first_slot,
+            last_slot.saturating_add(1),
+        )?;
+        self.db.write(write_batch)?;
+        drop(lock);
+        measure.stop();
+
+        datapoint_info!(
+            "blockstore-archive",
+            ("first_slot", first_slot as i64, i64),
+            ("last_slot", last_slot as i64, i64),
+            ("num_slots", slots.len() as i64, i64),
+            ("pack_size", pack_size as i64, i64),
+            ("archive_us", measure.as_us() as i64, i64)
+        );
+        Ok(Some((first_slot, last_slot)))
+    }
+
     // Only used by tests
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn write_entries(
@@ -2184,6 +2363,80 @@ impl Blockstore {
         Ok(())
     }
 
+    /// Records `signature` under each of the given program ids in the ProgramSignatures column,
+    /// sharing the active primary index with the TransactionStatus and AddressSignatures columns
+    /// so that the index is purged alongside them.
+    pub fn write_program_signatures(
+        &self,
+        slot: Slot,
+        signature: Signature,
+        program_ids: Vec<&Pubkey>,
+    ) -> Result<()> {
+        let w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let primary_index =
+            self.get_primary_index_to_write(slot, &w_active_transaction_status_index)?;
+        for program_id in program_ids {
+            self.program_signatures_cf
+                .put((primary_index, *program_id, slot, signature), &())?;
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` rooted signatures of transactions invoking `program_id` within
+    /// \[start_slot, end_slot\], newest slot first. Within each slot the signatures are in
+    /// descending order, NOT in the order in which the transactions exist in the block.
+    pub fn get_program_signatures(
+        &self,
+        program_id: Pubkey,
+        start_slot: Slot,
+        end_slot: Slot,
+        limit: usize,
+    ) -> Result<Vec<(Slot, Signature)>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_program_signatures", String)
+        );
+        let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
+        let start_slot = start_slot.max(lowest_available_slot);
+
+        let mut signatures: Vec<(Slot, Signature)> = vec![];
+        for transaction_status_cf_primary_index in 0..=1 {
+            let index_iterator = self.program_signatures_cf.iter(IteratorMode::From(
+                (
+                    transaction_status_cf_primary_index,
+                    program_id,
+                    end_slot.saturating_add(1),
+                    Signature::default(),
+                ),
+                IteratorDirection::Reverse,
+            ))?;
+            let mut found = 0;
+            for ((i, key_program_id, slot, signature), _) in index_iterator {
+                if found >= limit {
+                    break;
+                }
+                if slot > end_slot {
+                    continue;
+                }
+                if i != transaction_status_cf_primary_index
+                    || key_program_id != program_id
+                    || slot < start_slot
+                {
+                    break;
+                }
+                if self.is_root(slot) {
+                    signatures.push((slot, signature));
+                    found += 1;
+                }
+            }
+        }
+        drop(lock);
+        signatures.sort_unstable_by(|a, b| b.cmp(a));
+        signatures.truncate(limit);
+        Ok(signatures)
+    }
+
     pub fn read_transaction_memos(&self, signature: Signature) -> Result<Option<String>> {
         self.transaction_memos_cf.get(signature)
     }
@@ -2437,6 +2690,71 @@ impl Blockstore {
         Ok(signatures)
     }
 
+    /// Returns up to `limit` rooted signatures of transactions mentioning `address` within
+    /// \[start_slot, end_slot\]. `IteratorDirection::Forward` returns the oldest signatures of the
+    /// range first, `IteratorDirection::Reverse` the newest. Within each slot the signatures are
+    /// ordered by signature, NOT by the order in which the transactions exist in the block.
+    ///
+    /// Each primary index is entered at the bound of the range being read from and left as soon
+    /// as the range or the address ends, so the signatures of other addresses and slots are never
+    /// scanned.
+    pub fn get_address_signatures_in_range(
+        &self,
+        address: Pubkey,
+        start_slot: Slot,
+        end_slot: Slot,
+        limit: usize,
+        direction: IteratorDirection,
+    ) -> Result<Vec<(Slot, Signature)>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_address_signatures_in_range", String)
+        );
+        let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
+        let start_slot = start_slot.max(lowest_available_slot);
+
+        let mut signatures: Vec<(Slot, Signature)> = vec![];
+        for transaction_status_cf_primary_index in 0..=1 {
+            let from_slot = match direction {
+                IteratorDirection::Forward => start_slot,
+                IteratorDirection::Reverse => end_slot.saturating_add(1),
+            };
+            let index_iterator = self.address_signatures_cf.iter(IteratorMode::From(
+                (
+                    transaction_status_cf_primary_index,
+                    address,
+                    from_slot,
+                    Signature::default(),
+                ),
+                direction,
+            ))?;
+            let mut found = 0;
+            for ((i, key_address, slot, signature), _) in index_iterator {
+                if found >= limit {
+                    break;
+                }
+                if i != transaction_status_cf_primary_index
+                    || key_address != address
+                    || slot < start_slot
+                    || slot > end_slot
+                {
+                    break;
+                }
+                if self.is_root(slot) {
+                    signatures.push((slot, signature));
+                    found += 1;
+                }
+            }
+        }
+        drop(lock);
+        signatures.sort_unstable();
+        if matches!(direction, IteratorDirection::Reverse) {
+            signatures.reverse();
+        }
+        signatures.truncate(limit);
+        Ok(signatures)
+    }
+
     // DEPRECATED
     pub fn get_confirmed_signatures_for_address(
         &self,
@@ -2855,13 +3173,10 @@ impl Blockstore {
         end_index: u32,
         slot_meta: Option<&SlotMeta>,
     ) -> Result<Vec<Entry>> {
-        let data_shred_cf = self.db.column::<cf::ShredData>();
-
         // Short circuit on first error
         let data_shreds: Result<Vec<Shred>> = (start_index..=end_index)
             .map(|i| {
-                data_shred_cf
-                    .get_bytes((slot, u64::from(i)))
+                self.get_data_shred_bytes(slot, u64::from(i))
                     .and_then(|serialized_shred| {
                         if serialized_shred.is_none() {
                             if let Some(slot_meta) = slot_meta {
@@ -3087,6 +3402,13 @@ impl Blockstore {
         self.duplicate_slots_cf.put(slot, &duplicate_slot_proof)
     }
 
+    /// Returns where the shreds of the last `MAX_SHRED_SOURCE_STATS_HISTORY` slots to become full
+    /// came from, in the order the slots became full
+    pub fn get_shred_source_stats(&self) -> Vec<(Slot, ShredSourceStats)> {
+        let slots_stats = self.slots_stats.lock().unwrap();
+        slots_stats.full_slots.iter().copied().collect()
+    }
+
     pub fn get_duplicate_slot(&self, slot: u64) -> Option<DuplicateSlotProof> {
         self.duplicate_slots_cf
             .get(slot)
@@ -3164,6 +3486,23 @@ impl Blockstore {
         self.db.storage_size()
     }
 
+    /// Persists all buffered writes so that no WAL replay is needed on the next open
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()
+    }
+
+    /// Manually compacts the named columns, one after another. Intended for
+    /// maintenance as a full compaction of a large column is IO heavy.
+    pub fn compact_columns(&self, column_names: &[&str]) -> Result<()> {
+        for column_name in column_names {
+            let mut compact_timer = Measure::start("compact_column");
+            self.db.compact_column(column_name)?;
+            compact_timer.stop();
+            info!("Compacted column {} in {}", column_name, compact_timer);
+        }
+        Ok(())
+    }
+
     /// Returns the total physical storage size contributed by all data shreds.
     ///
     /// Note that the reported size does not include those recently inserted
@@ -3774,6 +4113,7 @@ pub fn create_new_ledger(
             access_type,
             recovery_mode: None,
             enforce_ulimit_nofile: false,
+            ..BlockstoreOptions::default()
         },
     )?;
     let ticks_per_slot = genesis_config.ticks_per_slot;
@@ -4147,6 +4487,10 @@ pub mod tests {
     use {
         super::*,
         crate::{
+            blockstore_archive::ArchiveStorageConfig,
+            blockstore_db::{
+                BlockstoreCompressionType, BlockstoreTuningProfile, ColumnName, ColumnTuning,
+            },
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
             leader_schedule::{FixedSchedule, LeaderSchedule},
             shred::{max_ticks_per_n_shreds, DataShredHeader},
@@ -4167,9 +4511,12 @@ pub mod tests {
             pubkey::Pubkey,
             signature::Signature,
             transaction::{Transaction, TransactionError},
+            transaction_context::TransactionReturnData,
         },
         solana_storage_proto::convert::generated,
-        solana_transaction_status::{InnerInstructions, Reward, Rewards, TransactionTokenBalance},
+        solana_transaction_status::{
+            InnerInstructions, Reward, Rewards, TokenBalanceExtensions, TransactionTokenBalance,
+        },
         std::{thread::Builder, time::Duration},
     };
 
@@ -4233,6 +4580,166 @@ pub mod tests {
         assert_eq!(last_shred, deserialized_shred);
     }
 
+    #[test]
+    fn test_compact_columns() {
+        let (shreds, _) = make_slot_entries(0, 0, 10);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+
+        blockstore
+            .compact_columns(&Database::column_names())
+            .unwrap();
+        assert_eq!(blockstore.get_slot_entries(0, 0).unwrap().len(), 10,);
+        assert!(matches!(
+            blockstore.compact_columns(&["not_a_column"]),
+            Err(BlockstoreError::UnknownColumn(_))
+        ));
+    }
+
+    #[test]
+    fn test_shred_compression() {
+        let (shreds, entries) = make_slot_entries(0, 0, 100);
+        let write_shreds = |ledger_path: &Path, shred_compression| {
+            let blockstore = Blockstore::open_with_options(
+                ledger_path,
+                BlockstoreOptions {
+                    shred_compression,
+                    ..BlockstoreOptions::default()
+                },
+            )
+            .unwrap();
+            blockstore
+                .insert_shreds(shreds.clone(), None, false)
+                .unwrap();
+            // Flush the shreds into sst files
+            blockstore.compact_columns(&[cf::ShredData::NAME]).unwrap();
+            assert_eq!(blockstore.get_slot_entries(0, 0).unwrap(), entries);
+            blockstore.total_data_shred_storage_size().unwrap()
+        };
+        let uncompressed_path = get_tmp_ledger_path_auto_delete!();
+        let uncompressed_size =
+            write_shreds(uncompressed_path.path(), BlockstoreCompressionType::None);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let compressed_size = write_shreds(ledger_path.path(), BlockstoreCompressionType::Lz4);
+        assert!(compressed_size > 0);
+        assert!(compressed_size < uncompressed_size);
+
+        // Ledgers written with compression remain readable without it
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        assert_eq!(blockstore.get_slot_entries(0, 0).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_tuning_profile() {
+        let (shreds, entries) = make_slot_entries(0, 0, 100);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let mut tuning = BlockstoreTuningProfile {
+            default: ColumnTuning {
+                bloom_filter_bits_per_key: Some(10),
+                ..ColumnTuning::default()
+            },
+            ..BlockstoreTuningProfile::default()
+        };
+        tuning.columns.insert(
+            cf::ShredData::NAME.to_string(),
+            ColumnTuning {
+                block_cache_size: Some(1024 * 1024),
+                block_size: Some(16 * 1024),
+                compression_per_level: Some(vec![
+                    BlockstoreCompressionType::None,
+                    BlockstoreCompressionType::Lz4,
+                ]),
+                ..ColumnTuning::default()
+            },
+        );
+        let blockstore = Blockstore::open_with_options(
+            ledger_path.path(),
+            BlockstoreOptions {
+                tuning,
+                ..BlockstoreOptions::default()
+            },
+        )
+        .unwrap();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.compact_columns(&[cf::ShredData::NAME]).unwrap();
+        assert_eq!(blockstore.get_slot_entries(0, 0).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_archive_next_pack() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let archive_dir = tempfile::TempDir::new().unwrap();
+        let options = || BlockstoreOptions {
+            archive: Some(ArchiveStorageConfig::Local(
+                archive_dir.path().to_path_buf(),
+            )),
+            ..BlockstoreOptions::default()
+        };
+        let blockstore = Blockstore::open_with_options(ledger_path.path(), options()).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 12, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        // Slot 4 is on a dead fork
+        blockstore
+            .set_roots([0, 1, 2, 3, 5, 6, 7, 8, 9, 10].iter())
+            .unwrap();
+        let entries: Vec<_> = (0..12)
+            .map(|slot| blockstore.get_slot_entries(slot, 0).unwrap())
+            .collect();
+        let data_shred = blockstore.get_data_shred(6, 0).unwrap().unwrap();
+        let data_shreds = blockstore.get_data_shreds_for_slot(6, 0).unwrap();
+
+        // Only full packs are written. Slot 0 is below the lowest available slot and stays where
+        // it is.
+        assert_eq!(blockstore.archive_next_pack(10, 4).unwrap(), Some((1, 5)));
+        assert_eq!(blockstore.archive_next_pack(10, 4).unwrap(), Some((6, 9)));
+        assert_eq!(blockstore.archive_next_pack(10, 4).unwrap(), None);
+        for slot in 1..=9 {
+            assert!(blockstore
+                .slot_data_iterator(slot, 0)
+                .unwrap()
+                .next()
+                .is_none());
+        }
+        assert!(blockstore
+            .slot_data_iterator(0, 0)
+            .unwrap()
+            .next()
+            .is_some());
+        assert!(blockstore
+            .slot_data_iterator(10, 0)
+            .unwrap()
+            .next()
+            .is_some());
+
+        // Archived shreds are read through
+        for slot in [0, 1, 2, 3, 5, 6, 7, 8, 9, 10, 11] {
+            assert_eq!(
+                blockstore.get_slot_entries(slot, 0).unwrap(),
+                entries[slot as usize]
+            );
+        }
+        assert_eq!(
+            blockstore.get_data_shred(6, 0).unwrap().unwrap(),
+            data_shred
+        );
+        assert_eq!(
+            blockstore.get_data_shreds_for_slot(6, 0).unwrap(),
+            data_shreds
+        );
+        assert!(blockstore
+            .get_data_shreds_for_slot(4, 0)
+            .unwrap()
+            .is_empty());
+        assert!(blockstore.get_data_shred(4, 0).unwrap().is_none());
+
+        // The archive is found again after a restart
+        drop(blockstore);
+        let blockstore = Blockstore::open_with_options(ledger_path.path(), options()).unwrap();
+        assert_eq!(blockstore.get_slot_entries(5, 0).unwrap(), entries[5]);
+        assert_eq!(blockstore.archive_next_pack(20, 4).unwrap(), None);
+    }
+
     #[test]
     fn test_write_entries() {
         solana_logger::setup();
@@ -6278,6 +6785,8 @@ pub mod tests {
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
                     loaded_addresses: LoadedAddresses::default(),
+                    return_data: None,
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6295,6 +6804,8 @@ pub mod tests {
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
                     loaded_addresses: LoadedAddresses::default(),
+                    return_data: None,
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6312,6 +6823,8 @@ pub mod tests {
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
                     loaded_addresses: LoadedAddresses::default(),
+                    return_data: None,
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -6331,6 +6844,8 @@ pub mod tests {
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
                         loaded_addresses: LoadedAddresses::default(),
+                        return_data: None,
+                        compute_units_consumed: None,
                     }),
                 }
             })
@@ -6442,6 +6957,12 @@ pub mod tests {
             writable: vec![Pubkey::new_unique()],
             readonly: vec![Pubkey::new_unique()],
         };
+        let test_return_data = TransactionReturnData {
+            program_id: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+        };
+        let compute_units_consumed_1 = Some(3812649u64);
+        let compute_units_consumed_2 = Some(42u64);
 
         // result not found
         assert!(transaction_status_cf
@@ -6461,6 +6982,8 @@ pub mod tests {
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
             loaded_addresses: test_loaded_addresses.clone(),
+            return_data: Some(test_return_data.clone()),
+            compute_units_consumed: compute_units_consumed_1,
         }
         .into();
         assert!(transaction_status_cf
@@ -6479,6 +7002,8 @@ pub mod tests {
             post_token_balances,
             rewards,
             loaded_addresses,
+            return_data,
+            compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((0, Signature::default(), 0))
             .unwrap()
@@ -6495,7 +7020,8 @@ pub mod tests {
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
         assert_eq!(loaded_addresses, test_loaded_addresses);
-
+        assert_eq!(return_data.unwrap(), test_return_data);
+        assert_eq!(compute_units_consumed, compute_units_consumed_1);
         // insert value
         let status = TransactionStatusMeta {
             status: solana_sdk::transaction::Result::<()>::Ok(()),
@@ -6508,6 +7034,8 @@ pub mod tests {
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
             loaded_addresses: test_loaded_addresses.clone(),
+            return_data: Some(test_return_data.clone()),
+            compute_units_consumed: compute_units_consumed_2,
         }
         .into();
         assert!(transaction_status_cf
@@ -6526,6 +7054,8 @@ pub mod tests {
             post_token_balances,
             rewards,
             loaded_addresses,
+            return_data,
+            compute_units_consumed,
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                 0,
@@ -6548,6 +7078,8 @@ pub mod tests {
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
         assert_eq!(loaded_addresses, test_loaded_addresses);
+        assert_eq!(return_data.unwrap(), test_return_data);
+        assert_eq!(compute_units_consumed, compute_units_consumed_2);
     }
 
     #[test]
@@ -6776,6 +7308,8 @@ pub mod tests {
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
             loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
         }
         .into();
 
@@ -6971,6 +7505,8 @@ pub mod tests {
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
             loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
         }
         .into();
 
@@ -7153,6 +7689,8 @@ pub mod tests {
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
                     loaded_addresses: LoadedAddresses::default(),
+                    return_data: None,
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -7172,6 +7710,8 @@ pub mod tests {
                         post_token_balances,
                         rewards,
                         loaded_addresses: LoadedAddresses::default(),
+                        return_data: None,
+                        compute_units_consumed: None,
                     }),
                 }
             })
@@ -7255,6 +7795,8 @@ pub mod tests {
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
                     loaded_addresses: LoadedAddresses::default(),
+                    return_data: None,
+                    compute_units_consumed: None,
                 }
                 .into();
                 blockstore
@@ -7274,6 +7816,8 @@ pub mod tests {
                         post_token_balances,
                         rewards,
                         loaded_addresses: LoadedAddresses::default(),
+                        return_data: None,
+                        compute_units_consumed: None,
                     }),
                 }
             })
@@ -7459,6 +8003,82 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_address_signatures_in_range() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let address0 = solana_sdk::pubkey::new_rand();
+        let address1 = solana_sdk::pubkey::new_rand();
+        let write_signatures = |slot, signatures: std::ops::Range<u8>, address| {
+            for x in signatures {
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        Signature::new(&[x; 64]),
+                        vec![address],
+                        vec![],
+                        TransactionStatusMeta::default(),
+                    )
+                    .unwrap();
+            }
+        };
+
+        write_signatures(10, 1..5, &address0);
+        // Purge to freeze index 0
+        blockstore.run_purge(0, 1, PurgeType::PrimaryIndex).unwrap();
+        write_signatures(20, 5..9, &address0);
+        write_signatures(20, 9..11, &address1);
+        write_signatures(30, 11..13, &address0);
+        blockstore.set_roots(vec![10, 20].iter()).unwrap();
+
+        let signatures = |x: std::ops::Range<u8>, slot| -> Vec<(Slot, Signature)> {
+            x.map(|x| (slot, Signature::new(&[x; 64]))).collect()
+        };
+
+        // Unrooted slot 30 and the signatures of address1 are excluded
+        assert_eq!(
+            blockstore
+                .get_address_signatures_in_range(address0, 0, 50, 100, IteratorDirection::Forward)
+                .unwrap(),
+            [signatures(1..5, 10), signatures(5..9, 20)].concat()
+        );
+        // Oldest first, across primary indexes
+        assert_eq!(
+            blockstore
+                .get_address_signatures_in_range(address0, 0, 50, 6, IteratorDirection::Forward)
+                .unwrap(),
+            [signatures(1..5, 10), signatures(5..7, 20)].concat()
+        );
+        // Newest first
+        let mut newest = signatures(3..5, 10);
+        newest.extend(signatures(5..9, 20));
+        newest.reverse();
+        assert_eq!(
+            blockstore
+                .get_address_signatures_in_range(address0, 0, 50, 6, IteratorDirection::Reverse)
+                .unwrap(),
+            newest
+        );
+        // Bounded by the slot range in both directions
+        assert_eq!(
+            blockstore
+                .get_address_signatures_in_range(address0, 11, 20, 100, IteratorDirection::Forward)
+                .unwrap(),
+            signatures(5..9, 20)
+        );
+        assert_eq!(
+            blockstore
+                .get_address_signatures_in_range(address0, 0, 19, 100, IteratorDirection::Reverse)
+                .unwrap(),
+            signatures(1..5, 10).into_iter().rev().collect::<Vec<_>>()
+        );
+        assert!(blockstore
+            .get_address_signatures_in_range(address0, 21, 50, 100, IteratorDirection::Forward)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_find_address_signatures_for_slot() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -7547,6 +8167,83 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_program_signatures() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let program0 = solana_sdk::pubkey::new_rand();
+        let program1 = solana_sdk::pubkey::new_rand();
+
+        let slot0 = 10;
+        for x in 1..5 {
+            let signature = Signature::new(&[x; 64]);
+            blockstore
+                .write_program_signatures(slot0, signature, vec![&program0])
+                .unwrap();
+        }
+        // Purge to freeze index 0
+        blockstore.run_purge(0, 1, PurgeType::PrimaryIndex).unwrap();
+        let slot1 = 20;
+        for x in 5..9 {
+            let signature = Signature::new(&[x; 64]);
+            blockstore
+                .write_program_signatures(slot1, signature, vec![&program0, &program1])
+                .unwrap();
+        }
+        // Unrooted slots are not returned
+        let slot2 = 30;
+        blockstore
+            .write_program_signatures(slot2, Signature::new(&[9; 64]), vec![&program0])
+            .unwrap();
+        blockstore.set_roots(vec![slot0, slot1].iter()).unwrap();
+
+        let all0 = blockstore
+            .get_program_signatures(program0, 0, 50, 100)
+            .unwrap();
+        assert_eq!(
+            all0,
+            (1..9)
+                .rev()
+                .map(|x| (if x < 5 { slot0 } else { slot1 }, Signature::new(&[x; 64])))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            blockstore
+                .get_program_signatures(program0, 0, 10, 100)
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(
+            blockstore
+                .get_program_signatures(program0, 0, 50, 6)
+                .unwrap(),
+            all0[..6].to_vec()
+        );
+        assert_eq!(
+            blockstore
+                .get_program_signatures(program1, 0, 50, 100)
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(blockstore
+            .get_program_signatures(program1, 0, 10, 100)
+            .unwrap()
+            .is_empty());
+
+        blockstore
+            .run_purge(0, 15, PurgeType::PrimaryIndex)
+            .unwrap();
+        assert_eq!(
+            blockstore
+                .get_program_signatures(program0, 0, 50, 100)
+                .unwrap(),
+            all0[..4].to_vec()
+        );
+    }
+
     #[test]
     fn test_get_confirmed_signatures_for_address2() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -8038,6 +8735,8 @@ pub mod tests {
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
                 loaded_addresses: LoadedAddresses::default(),
+                return_data: None,
+                compute_units_consumed: None,
             }
             .into();
             transaction_status_cf
@@ -8342,6 +9041,51 @@ pub mod tests {
         assert_eq!(num_coding_in_index, num_coding);
     }
 
+    #[test]
+    fn test_shred_source_stats() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let (mut shreds, _) = make_slot_entries(1, 0, 100);
+        let num_shreds = shreds.len();
+        assert!(num_shreds > 1);
+        let repaired_shreds = shreds.split_off(1);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        // The slot isn't full yet
+        assert!(blockstore.get_shred_source_stats().is_empty());
+
+        let num_repaired = repaired_shreds.len();
+        blockstore
+            .insert_shreds_handle_duplicate(
+                repaired_shreds,
+                vec![true; num_repaired],
+                None,
+                false,
+                None,
+                &|_| {},
+                &mut BlockstoreInsertionMetrics::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            blockstore.get_shred_source_stats(),
+            vec![(
+                1,
+                ShredSourceStats {
+                    num_turbine: 1,
+                    num_repaired: num_shreds - 1,
+                    num_recovered: 0,
+                }
+            )]
+        );
+
+        let (shreds, _) = make_slot_entries(2, 1, 1);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        let stats = blockstore.get_shred_source_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[1].0, 2);
+        assert_eq!(stats[1].1.num_repaired, 0);
+    }
+
     #[test]
     fn test_duplicate_slot() {
         let slot = 0;
@@ -8570,6 +9314,8 @@ pub mod tests {
                     ui_amount_string: "1.1".to_string(),
                 },
                 owner: Pubkey::new_unique().to_string(),
+                program_id: Pubkey::new_unique().to_string(),
+                extensions: None,
             }]),
             post_token_balances: Some(vec![TransactionTokenBalance {
                 account_index: 0,
@@ -8581,6 +9327,17 @@ pub mod tests {
                     ui_amount_string: "1.1".to_string(),
                 },
                 owner: Pubkey::new_unique().to_string(),
+                program_id: Pubkey::new_unique().to_string(),
+                extensions: Some(TokenBalanceExtensions {
+                    withheld_transfer_fee: Some(UiTokenAmount {
+                        ui_amount: Some(0.1),
+                        decimals: 1,
+                        amount: "1".to_string(),
+                        ui_amount_string: "0.1".to_string(),
+                    }),
+                    interest_bearing_ui_amount: Some("1.2".to_string()),
+                    confidential_credits_allowed: Some(false),
+                }),
             }]),
             rewards: Some(vec![Reward {
                 pubkey: "My11111111111111111111111111111111111111111".to_string(),
@@ -8590,6 +9347,8 @@ pub mod tests {
                 commission: None,
             }]),
             loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
         };
         let deprecated_status: StoredTransactionStatusMeta = status.clone().try_into().unwrap();
         let protobuf_status: generated::TransactionStatusMeta = status.into();
@@ -283,6 +283,10 @@ impl Blockstore {
                 .address_signatures_cf
                 .compact_range(0, 2)
                 .unwrap_or(false)
+            && self
+                .program_signatures_cf
+                .compact_range(0, 2)
+                .unwrap_or(false)
             && self
                 .transaction_status_index_cf
                 .compact_range(0, 2)
@@ -346,6 +350,13 @@ impl Blockstore {
                         batch.delete::<cf::AddressSignatures>((0, *pubkey, slot, signature))?;
                         batch.delete::<cf::AddressSignatures>((1, *pubkey, slot, signature))?;
                     }
+
+                    let static_account_keys = transaction.message.static_account_keys();
+                    for instruction in transaction.message.instructions() {
+                        let program_id = *instruction.program_id(static_account_keys);
+                        batch.delete::<cf::ProgramSignatures>((0, program_id, slot, signature))?;
+                        batch.delete::<cf::ProgramSignatures>((1, program_id, slot, signature))?;
+                    }
                 }
             }
         }
@@ -389,6 +400,14 @@ impl Blockstore {
                         purged_index,
                         purged_index + 1,
                     )
+                    .is_ok()
+                & self
+                    .db
+                    .delete_range_cf::<cf::ProgramSignatures>(
+                        write_batch,
+                        purged_index,
+                        purged_index + 1,
+                    )
                     .is_ok();
         }
         Ok(())
@@ -1,6 +1,10 @@
 pub use rocksdb::Direction as IteratorDirection;
 use {
-    crate::blockstore_meta,
+    crate::{
+        blockstore_archive::ArchiveStorageConfig,
+        blockstore_meta,
+        shred::{MAX_DATA_SHREDS_PER_FEC_BLOCK, SHRED_PAYLOAD_SIZE},
+    },
     bincode::{deserialize, serialize},
     byteorder::{BigEndian, ByteOrder},
     log::*,
@@ -9,10 +13,11 @@ use {
         self,
         compaction_filter::CompactionFilter,
         compaction_filter_factory::{CompactionFilterContext, CompactionFilterFactory},
-        ColumnFamily, ColumnFamilyDescriptor, CompactionDecision, DBIterator, DBRawIterator,
-        DBRecoveryMode, IteratorMode as RocksIteratorMode, Options, WriteBatch as RWriteBatch, DB,
+        BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, CompactionDecision,
+        DBCompactionStyle, DBCompressionType, DBIterator, DBRawIterator, DBRecoveryMode,
+        IteratorMode as RocksIteratorMode, Options, WriteBatch as RWriteBatch, DB,
     },
-    serde::{de::DeserializeOwned, Serialize},
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
     solana_runtime::hardened_unpack::UnpackError,
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
@@ -61,6 +66,8 @@ const CODE_SHRED_CF: &str = "code_shred";
 const TRANSACTION_STATUS_CF: &str = "transaction_status";
 /// Column family for Address Signatures
 const ADDRESS_SIGNATURES_CF: &str = "address_signatures";
+/// Column family for Program Signatures
+const PROGRAM_SIGNATURES_CF: &str = "program_signatures";
 /// Column family for TransactionMemos
 const TRANSACTION_MEMOS_CF: &str = "transaction_memos";
 /// Column family for the Transaction Status Index.
@@ -102,6 +109,7 @@ pub enum BlockstoreError {
     ParentEntriesUnavailable,
     SlotUnavailable,
     UnsupportedTransactionVersion,
+    UnknownColumn(String),
 }
 pub type Result<T> = std::result::Result<T, BlockstoreError>;
 
@@ -166,6 +174,10 @@ pub mod columns {
     /// The address signatures column
     pub struct AddressSignatures;
 
+    #[derive(Debug)]
+    /// The program signatures column
+    pub struct ProgramSignatures;
+
     #[derive(Debug)]
     /// The transaction memos column
     pub struct TransactionMemos;
@@ -251,6 +263,248 @@ impl From<BlockstoreRecoveryMode> for DBRecoveryMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockstoreCompactionStyle {
+    Level,
+    Universal,
+}
+
+impl Default for BlockstoreCompactionStyle {
+    fn default() -> Self {
+        BlockstoreCompactionStyle::Level
+    }
+}
+
+impl From<&str> for BlockstoreCompactionStyle {
+    fn from(string: &str) -> Self {
+        match string {
+            "level" => BlockstoreCompactionStyle::Level,
+            "universal" => BlockstoreCompactionStyle::Universal,
+            bad_style => panic!("Invalid compaction style: {}", bad_style),
+        }
+    }
+}
+
+impl From<BlockstoreCompactionStyle> for DBCompactionStyle {
+    fn from(style: BlockstoreCompactionStyle) -> Self {
+        match style {
+            BlockstoreCompactionStyle::Level => DBCompactionStyle::Level,
+            BlockstoreCompactionStyle::Universal => DBCompactionStyle::Universal,
+        }
+    }
+}
+
+/// Scheduling priority of the background threads running rocksdb compactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockstoreCompactionPriority {
+    Normal,
+    /// Lower the CPU and IO priority of the compaction threads so that they
+    /// yield to replay and flushes
+    Low,
+}
+
+impl Default for BlockstoreCompactionPriority {
+    fn default() -> Self {
+        BlockstoreCompactionPriority::Normal
+    }
+}
+
+impl From<&str> for BlockstoreCompactionPriority {
+    fn from(string: &str) -> Self {
+        match string {
+            "normal" => BlockstoreCompactionPriority::Normal,
+            "low" => BlockstoreCompactionPriority::Low,
+            bad_priority => panic!("Invalid compaction priority: {}", bad_priority),
+        }
+    }
+}
+
+/// Compression applied to the data shred column.
+///
+/// Rocksdb records the compression of each block in its sst file, so switching between types
+/// never requires rewriting the ledger: existing data stays readable and is rewritten with the
+/// configured type as it gets compacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockstoreCompressionType {
+    None,
+    Lz4,
+}
+
+impl Default for BlockstoreCompressionType {
+    fn default() -> Self {
+        BlockstoreCompressionType::None
+    }
+}
+
+impl From<&str> for BlockstoreCompressionType {
+    fn from(string: &str) -> Self {
+        match string {
+            "none" => BlockstoreCompressionType::None,
+            "lz4" => BlockstoreCompressionType::Lz4,
+            bad_type => panic!("Invalid compression type: {}", bad_type),
+        }
+    }
+}
+
+impl From<BlockstoreCompressionType> for DBCompressionType {
+    fn from(compression_type: BlockstoreCompressionType) -> Self {
+        match compression_type {
+            BlockstoreCompressionType::None => DBCompressionType::None,
+            BlockstoreCompressionType::Lz4 => DBCompressionType::Lz4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockstoreCompactionOptions {
+    /// Compaction style of the columns without an entry in `column_styles`
+    pub default_style: BlockstoreCompactionStyle,
+    /// Per-column compaction style overrides, keyed by column name
+    pub column_styles: HashMap<String, BlockstoreCompactionStyle>,
+    pub priority: BlockstoreCompactionPriority,
+}
+
+impl BlockstoreCompactionOptions {
+    pub fn style(&self, column_name: &str) -> BlockstoreCompactionStyle {
+        self.column_styles
+            .get(column_name)
+            .copied()
+            .unwrap_or(self.default_style)
+    }
+}
+
+/// Rocksdb options of a column, see [`BlockstoreTuningProfile`]. Options that are not set keep
+/// their built-in values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnTuning {
+    /// Size in bytes of the LRU cache of uncompressed table blocks dedicated to the column
+    pub block_cache_size: Option<usize>,
+    /// Size in bytes of the table blocks, the unit rocksdb reads, caches and compresses
+    pub block_size: Option<usize>,
+    /// Bits per key of the bloom filters that let point reads skip tables without the key
+    pub bloom_filter_bits_per_key: Option<u32>,
+    /// Compression of each level of the LSM tree, starting at level 0. Levels past the end of
+    /// the list use the compression of the last entry.
+    pub compression_per_level: Option<Vec<BlockstoreCompressionType>>,
+}
+
+impl ColumnTuning {
+    /// Most levels rocksdb creates with the default `num_levels`
+    const MAX_LEVELS: usize = 7;
+    const MAX_BLOOM_FILTER_BITS_PER_KEY: u32 = 64;
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        if self.block_cache_size == Some(0) {
+            return Err("block_cache_size must be greater than 0".to_string());
+        }
+        if self.block_size == Some(0) {
+            return Err("block_size must be greater than 0".to_string());
+        }
+        if let Some(bits_per_key) = self.bloom_filter_bits_per_key {
+            if !(1..=Self::MAX_BLOOM_FILTER_BITS_PER_KEY).contains(&bits_per_key) {
+                return Err(format!(
+                    "bloom_filter_bits_per_key must be between 1 and {}",
+                    Self::MAX_BLOOM_FILTER_BITS_PER_KEY
+                ));
+            }
+        }
+        if let Some(compression_per_level) = &self.compression_per_level {
+            if compression_per_level.is_empty() || compression_per_level.len() > Self::MAX_LEVELS {
+                return Err(format!(
+                    "compression_per_level must list between 1 and {} levels",
+                    Self::MAX_LEVELS
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills in the options that are not set with those of `defaults`
+    fn or(self, defaults: &ColumnTuning) -> Self {
+        Self {
+            block_cache_size: self.block_cache_size.or(defaults.block_cache_size),
+            block_size: self.block_size.or(defaults.block_size),
+            bloom_filter_bits_per_key: self
+                .bloom_filter_bits_per_key
+                .or(defaults.bloom_filter_bits_per_key),
+            compression_per_level: self
+                .compression_per_level
+                .or_else(|| defaults.compression_per_level.clone()),
+        }
+    }
+}
+
+/// Per-column rocksdb options loaded from a TOML or JSON file, so that they can be tuned without
+/// rebuilding the validator. In TOML:
+///
+/// ```toml
+/// [default]
+/// bloom_filter_bits_per_key = 10
+///
+/// [columns.data_shred]
+/// block_cache_size = 1073741824
+/// compression_per_level = ["none", "none", "lz4"]
+/// ```
+///
+/// A `compression_per_level` entry takes precedence over the shred compression for the
+/// `data_shred` column.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockstoreTuningProfile {
+    /// Options of every column, unless overridden in `columns`
+    #[serde(default)]
+    pub default: ColumnTuning,
+    /// Per-column overrides of `default`, keyed by column name
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnTuning>,
+}
+
+impl BlockstoreTuningProfile {
+    /// Reads and validates the profile at `path`, a TOML file if its extension is `toml` and a
+    /// JSON file if it is `json`
+    pub fn load(path: &Path) -> std::result::Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+        let profile: Self = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|err| err.to_string()),
+            Some("json") => serde_json::from_str(&contents).map_err(|err| err.to_string()),
+            _ => Err("expected a .toml or .json file".to_string()),
+        }
+        .map_err(|err| format!("Invalid tuning profile {}: {}", path.display(), err))?;
+        profile
+            .validate()
+            .map_err(|err| format!("Invalid tuning profile {}: {}", path.display(), err))?;
+        Ok(profile)
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        self.default
+            .validate()
+            .map_err(|err| format!("default: {}", err))?;
+        let column_names = Rocks::columns();
+        for (column_name, column_tuning) in &self.columns {
+            if !column_names.contains(&column_name.as_str()) {
+                return Err(format!("unknown column: {}", column_name));
+            }
+            column_tuning
+                .validate()
+                .map_err(|err| format!("{}: {}", column_name, err))?;
+        }
+        Ok(())
+    }
+
+    /// The options of the column named `column_name`
+    pub fn column(&self, column_name: &str) -> ColumnTuning {
+        self.columns
+            .get(column_name)
+            .cloned()
+            .unwrap_or_default()
+            .or(&self.default)
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 struct OldestSlot(Arc<AtomicU64>);
 
@@ -281,6 +535,9 @@ impl Rocks {
         use columns::*;
         let access_type = options.access_type;
         let recovery_mode = options.recovery_mode;
+        let compaction = options.compaction;
+        let shred_compression = options.shred_compression;
+        let tuning = options.tuning;
 
         fs::create_dir_all(&path)?;
 
@@ -288,7 +545,7 @@ impl Rocks {
         if matches!(access_type, AccessType::PrimaryOnlyForMaintenance) {
             warn!("Disabling rocksdb's auto compaction for maintenance bulk ledger update...");
         }
-        let mut db_options = get_db_options(&access_type);
+        let mut db_options = get_db_options(&access_type, compaction.priority);
         if let Some(recovery_mode) = recovery_mode {
             db_options.set_wal_recovery_mode(recovery_mode.into());
         }
@@ -297,25 +554,57 @@ impl Rocks {
 
         // Get column family descriptors and names
         let cfs = vec![
-            new_cf_descriptor::<SlotMeta>(&access_type, &oldest_slot),
-            new_cf_descriptor::<DeadSlots>(&access_type, &oldest_slot),
-            new_cf_descriptor::<DuplicateSlots>(&access_type, &oldest_slot),
-            new_cf_descriptor::<ErasureMeta>(&access_type, &oldest_slot),
-            new_cf_descriptor::<Orphans>(&access_type, &oldest_slot),
-            new_cf_descriptor::<BankHash>(&access_type, &oldest_slot),
-            new_cf_descriptor::<Root>(&access_type, &oldest_slot),
-            new_cf_descriptor::<Index>(&access_type, &oldest_slot),
-            new_cf_descriptor::<ShredData>(&access_type, &oldest_slot),
-            new_cf_descriptor::<ShredCode>(&access_type, &oldest_slot),
-            new_cf_descriptor::<TransactionStatus>(&access_type, &oldest_slot),
-            new_cf_descriptor::<AddressSignatures>(&access_type, &oldest_slot),
-            new_cf_descriptor::<TransactionMemos>(&access_type, &oldest_slot),
-            new_cf_descriptor::<TransactionStatusIndex>(&access_type, &oldest_slot),
-            new_cf_descriptor::<Rewards>(&access_type, &oldest_slot),
-            new_cf_descriptor::<Blocktime>(&access_type, &oldest_slot),
-            new_cf_descriptor::<PerfSamples>(&access_type, &oldest_slot),
-            new_cf_descriptor::<BlockHeight>(&access_type, &oldest_slot),
-            new_cf_descriptor::<ProgramCosts>(&access_type, &oldest_slot),
+            new_cf_descriptor::<SlotMeta>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<DeadSlots>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<DuplicateSlots>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<ErasureMeta>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<Orphans>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<BankHash>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<Root>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<Index>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_shred_data_cf_descriptor(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+                shred_compression,
+            )?,
+            new_cf_descriptor::<ShredCode>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<TransactionStatus>(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+            )?,
+            new_cf_descriptor::<AddressSignatures>(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+            )?,
+            new_cf_descriptor::<ProgramSignatures>(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+            )?,
+            new_cf_descriptor::<TransactionMemos>(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+            )?,
+            new_cf_descriptor::<TransactionStatusIndex>(
+                &access_type,
+                &oldest_slot,
+                &compaction,
+                &tuning,
+            )?,
+            new_cf_descriptor::<Rewards>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<Blocktime>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<PerfSamples>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<BlockHeight>(&access_type, &oldest_slot, &compaction, &tuning)?,
+            new_cf_descriptor::<ProgramCosts>(&access_type, &oldest_slot, &compaction, &tuning)?,
         ];
         let cf_names = Self::columns();
         // The names and descriptors don't have to be in the same
@@ -432,6 +721,7 @@ impl Rocks {
             ShredCode::NAME,
             TransactionStatus::NAME,
             AddressSignatures::NAME,
+            ProgramSignatures::NAME,
             TransactionMemos::NAME,
             TransactionStatusIndex::NAME,
             Rewards::NAME,
@@ -498,6 +788,17 @@ impl Rocks {
         Ok(())
     }
 
+    fn flush(&self) -> Result<()> {
+        for cf in Self::columns() {
+            self.0.flush_cf(self.cf_handle(cf))?;
+        }
+        Ok(())
+    }
+
+    fn compact_cf(&self, cf: &ColumnFamily) {
+        self.0.compact_range_cf::<&[u8], &[u8]>(cf, None, None);
+    }
+
     fn is_primary_access(&self) -> bool {
         self.1 == ActualAccessType::Primary
     }
@@ -546,6 +847,10 @@ impl TypedColumn for columns::AddressSignatures {
     type Type = blockstore_meta::AddressSignatureMeta;
 }
 
+impl TypedColumn for columns::ProgramSignatures {
+    type Type = ();
+}
+
 impl TypedColumn for columns::TransactionMemos {
     type Type = String;
 }
@@ -664,6 +969,44 @@ impl ColumnName for columns::AddressSignatures {
     const NAME: &'static str = ADDRESS_SIGNATURES_CF;
 }
 
+impl Column for columns::ProgramSignatures {
+    type Index = (u64, Pubkey, Slot, Signature);
+
+    fn key((index, program_id, slot, signature): (u64, Pubkey, Slot, Signature)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 32 + 8 + 64]; // size_of u64 + size_of Pubkey + size_of Slot + size_of Signature
+        BigEndian::write_u64(&mut key[0..8], index);
+        key[8..40].clone_from_slice(&program_id.as_ref()[0..32]);
+        BigEndian::write_u64(&mut key[40..48], slot);
+        key[48..112].clone_from_slice(&signature.as_ref()[0..64]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (u64, Pubkey, Slot, Signature) {
+        let index = BigEndian::read_u64(&key[0..8]);
+        let program_id = Pubkey::new(&key[8..40]);
+        let slot = BigEndian::read_u64(&key[40..48]);
+        let signature = Signature::new(&key[48..112]);
+        (index, program_id, slot, signature)
+    }
+
+    fn primary_index(index: Self::Index) -> u64 {
+        index.0
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.2
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(index: u64) -> Self::Index {
+        (index, Pubkey::default(), 0, Signature::default())
+    }
+}
+
+impl ColumnName for columns::ProgramSignatures {
+    const NAME: &'static str = PROGRAM_SIGNATURES_CF;
+}
+
 impl Column for columns::TransactionMemos {
     type Index = Signature;
 
@@ -959,6 +1302,11 @@ pub struct BlockstoreOptions {
     pub access_type: AccessType,
     pub recovery_mode: Option<BlockstoreRecoveryMode>,
     pub enforce_ulimit_nofile: bool,
+    pub compaction: BlockstoreCompactionOptions,
+    pub shred_compression: BlockstoreCompressionType,
+    pub tuning: BlockstoreTuningProfile,
+    /// Storage of the archived shreds to read through when they are no longer in RocksDB
+    pub archive: Option<ArchiveStorageConfig>,
 }
 
 impl Default for BlockstoreOptions {
@@ -968,6 +1316,10 @@ impl Default for BlockstoreOptions {
             access_type: AccessType::PrimaryOnly,
             recovery_mode: None,
             enforce_ulimit_nofile: true,
+            compaction: BlockstoreCompactionOptions::default(),
+            shred_compression: BlockstoreCompressionType::default(),
+            tuning: BlockstoreTuningProfile::default(),
+            archive: None,
         }
     }
 }
@@ -1050,6 +1402,26 @@ impl Database {
         self.backend.write(batch.write_batch)
     }
 
+    /// Flushes the memtables of every column to disk
+    pub fn flush(&self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Names of all the columns of the database
+    pub fn column_names() -> Vec<&'static str> {
+        Rocks::columns()
+    }
+
+    /// Manually compacts the entire key range of the named column. This rewrites
+    /// every SST file of the column and can take a long time on a large ledger.
+    pub fn compact_column(&self, column_name: &str) -> Result<()> {
+        if !Rocks::columns().contains(&column_name) {
+            return Err(BlockstoreError::UnknownColumn(column_name.to_string()));
+        }
+        self.backend.compact_cf(self.backend.cf_handle(column_name));
+        Ok(())
+    }
+
     pub fn storage_size(&self) -> Result<u64> {
         Ok(fs_extra::dir::get_size(&self.path)?)
     }
@@ -1318,15 +1690,55 @@ impl<C: Column + ColumnName> CompactionFilterFactory for PurgedSlotFilterFactory
 fn new_cf_descriptor<C: 'static + Column + ColumnName>(
     access_type: &AccessType,
     oldest_slot: &OldestSlot,
-) -> ColumnFamilyDescriptor {
-    ColumnFamilyDescriptor::new(C::NAME, get_cf_options::<C>(access_type, oldest_slot))
+    compaction: &BlockstoreCompactionOptions,
+    tuning: &BlockstoreTuningProfile,
+) -> Result<ColumnFamilyDescriptor> {
+    Ok(ColumnFamilyDescriptor::new(
+        C::NAME,
+        get_cf_options::<C>(
+            access_type,
+            oldest_slot,
+            compaction,
+            &tuning.column(C::NAME),
+        )?,
+    ))
+}
+
+fn new_shred_data_cf_descriptor(
+    access_type: &AccessType,
+    oldest_slot: &OldestSlot,
+    compaction: &BlockstoreCompactionOptions,
+    tuning: &BlockstoreTuningProfile,
+    shred_compression: BlockstoreCompressionType,
+) -> Result<ColumnFamilyDescriptor> {
+    let mut column_tuning = tuning.column(columns::ShredData::NAME);
+    if shred_compression != BlockstoreCompressionType::None {
+        // Rocksdb compresses each table block on its own. Data shreds are keyed by
+        // (slot, index), so a block about the size of an FEC set holds enough neighbouring
+        // shreds for their zero padding and repeated account keys to compress well, while a
+        // point read still only decompresses that much. Blocks are cut by size, not on FEC
+        // set boundaries, so a block usually spans the tail and head of two FEC sets.
+        column_tuning
+            .block_size
+            .get_or_insert(MAX_DATA_SHREDS_PER_FEC_BLOCK as usize * SHRED_PAYLOAD_SIZE);
+    }
+    let mut options =
+        get_cf_options::<columns::ShredData>(access_type, oldest_slot, compaction, &column_tuning)?;
+    options.set_compression_type(shred_compression.into());
+    Ok(ColumnFamilyDescriptor::new(
+        columns::ShredData::NAME,
+        options,
+    ))
 }
 
 fn get_cf_options<C: 'static + Column + ColumnName>(
     access_type: &AccessType,
     oldest_slot: &OldestSlot,
-) -> Options {
+    compaction: &BlockstoreCompactionOptions,
+    tuning: &ColumnTuning,
+) -> Result<Options> {
     let mut options = Options::default();
+    options.set_compaction_style(compaction.style(C::NAME).into());
     // 256 * 8 = 2GB. 6 of these columns should take at most 12GB of RAM
     options.set_max_write_buffer_number(8);
     options.set_write_buffer_size(MAX_WRITE_BUFFER_SIZE as usize);
@@ -1354,10 +1766,37 @@ fn get_cf_options<C: 'static + Column + ColumnName>(
         options.set_disable_auto_compactions(true);
     }
 
-    options
+    if let Some(compression_per_level) = &tuning.compression_per_level {
+        let compression_per_level: Vec<DBCompressionType> = compression_per_level
+            .iter()
+            .map(|compression_type| (*compression_type).into())
+            .collect();
+        options.set_compression_per_level(&compression_per_level);
+    }
+    if tuning.block_cache_size.is_some()
+        || tuning.block_size.is_some()
+        || tuning.bloom_filter_bits_per_key.is_some()
+    {
+        let mut block_options = BlockBasedOptions::default();
+        if let Some(block_cache_size) = tuning.block_cache_size {
+            block_options.set_block_cache(&Cache::new_lru_cache(block_cache_size)?);
+        }
+        if let Some(block_size) = tuning.block_size {
+            block_options.set_block_size(block_size);
+        }
+        if let Some(bits_per_key) = tuning.bloom_filter_bits_per_key {
+            block_options.set_bloom_filter(bits_per_key as i32, false);
+        }
+        options.set_block_based_table_factory(&block_options);
+    }
+
+    Ok(options)
 }
 
-fn get_db_options(access_type: &AccessType) -> Options {
+fn get_db_options(
+    access_type: &AccessType,
+    compaction_priority: BlockstoreCompactionPriority,
+) -> Options {
     let mut options = Options::default();
     options.create_if_missing(true);
     options.create_missing_column_families(true);
@@ -1371,6 +1810,11 @@ fn get_db_options(access_type: &AccessType) -> Options {
     // need to flush the memtable. Add some high-priority background threads
     // which can service these writes.
     env.set_high_priority_background_threads(4);
+    if compaction_priority == BlockstoreCompactionPriority::Low {
+        // Compactions run in the low priority pool, flushes in the high priority one
+        env.lower_thread_pool_cpu_priority();
+        env.lower_thread_pool_io_priority();
+    }
     options.set_env(&env);
 
     // Set max total wal size to 4G.
@@ -1399,6 +1843,26 @@ fn excludes_from_compaction(cf_name: &str) -> bool {
 pub mod tests {
     use {super::*, crate::blockstore_db::columns::ShredData};
 
+    #[test]
+    fn test_compaction_options_style() {
+        let mut compaction = BlockstoreCompactionOptions {
+            default_style: BlockstoreCompactionStyle::from("universal"),
+            ..BlockstoreCompactionOptions::default()
+        };
+        compaction.column_styles.insert(
+            columns::ShredData::NAME.to_string(),
+            BlockstoreCompactionStyle::Level,
+        );
+        assert_eq!(
+            compaction.style(columns::ShredData::NAME),
+            BlockstoreCompactionStyle::Level
+        );
+        assert_eq!(
+            compaction.style(columns::ShredCode::NAME),
+            BlockstoreCompactionStyle::Universal
+        );
+    }
+
     #[test]
     fn test_compaction_filter() {
         // this doesn't implement Clone...
@@ -1457,4 +1921,81 @@ pub mod tests {
         assert!(excludes_from_compaction(columns::TransactionMemos::NAME));
         assert!(!excludes_from_compaction("something else"));
     }
+
+    #[test]
+    fn test_tuning_profile_load() {
+        let profile_dir = tempfile::tempdir().unwrap();
+        let toml_path = profile_dir.path().join("profile.toml");
+        fs::write(
+            &toml_path,
+            r#"
+            [default]
+            bloom_filter_bits_per_key = 10
+
+            [columns.data_shred]
+            block_cache_size = 1048576
+            compression_per_level = ["none", "lz4"]
+            "#,
+        )
+        .unwrap();
+        let json_path = profile_dir.path().join("profile.json");
+        fs::write(
+            &json_path,
+            r#"{
+                "default": {"bloom_filter_bits_per_key": 10},
+                "columns": {
+                    "data_shred": {
+                        "block_cache_size": 1048576,
+                        "compression_per_level": ["none", "lz4"]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let profile = BlockstoreTuningProfile::load(&toml_path).unwrap();
+        assert_eq!(profile, BlockstoreTuningProfile::load(&json_path).unwrap());
+        assert_eq!(
+            profile.column(ShredData::NAME),
+            ColumnTuning {
+                block_cache_size: Some(1048576),
+                block_size: None,
+                bloom_filter_bits_per_key: Some(10),
+                compression_per_level: Some(vec![
+                    BlockstoreCompressionType::None,
+                    BlockstoreCompressionType::Lz4
+                ]),
+            }
+        );
+        assert_eq!(
+            profile.column(columns::ShredCode::NAME),
+            ColumnTuning {
+                bloom_filter_bits_per_key: Some(10),
+                ..ColumnTuning::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_tuning_profile_validation() {
+        let profile_dir = tempfile::tempdir().unwrap();
+        let load = |file_name: &str, contents: &str| {
+            let path = profile_dir.path().join(file_name);
+            fs::write(&path, contents).unwrap();
+            BlockstoreTuningProfile::load(&path)
+        };
+
+        assert!(load("profile.toml", "").is_ok());
+        assert!(load("profile.yaml", "").is_err());
+        assert!(load("profile.toml", "[columns.not_a_column]").is_err());
+        assert!(load("profile.toml", "[default]\nnot_an_option = 1").is_err());
+        assert!(load("profile.toml", "[default]\nblock_cache_size = 0").is_err());
+        assert!(load("profile.toml", "[default]\nbloom_filter_bits_per_key = 65").is_err());
+        assert!(load("profile.toml", "[default]\ncompression_per_level = []").is_err());
+        assert!(load(
+            "profile.toml",
+            "[default]\ncompression_per_level = [\"zstd\"]"
+        )
+        .is_err());
+    }
 }
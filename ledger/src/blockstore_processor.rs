@@ -327,6 +327,38 @@ pub fn process_entries_for_tests(
     result
 }
 
+/// Replays the entries of a block into `bank` in order, without verifying their proof of
+/// history, allowing the block `block_cost_limit` compute units. Meant to simulate the replay of
+/// a block under modified conditions; the outcome of every transaction is sent to
+/// `transaction_status_sender`.
+pub fn process_entries_with_block_cost_limit(
+    bank: &Arc<Bank>,
+    entries: Vec<Entry>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    block_cost_limit: u64,
+) -> Result<()> {
+    let verify_transaction = {
+        let bank = bank.clone();
+        move |versioned_tx: VersionedTransaction| -> Result<SanitizedTransaction> {
+            bank.verify_transaction(versioned_tx, TransactionVerificationMode::FullVerification)
+        }
+    };
+
+    let mut timings = ExecuteTimings::default();
+    let mut entries = entry::verify_transactions(entries, Arc::new(verify_transaction))?;
+    process_entries_with_callback(
+        bank,
+        &mut entries,
+        false,
+        None,
+        transaction_status_sender,
+        None,
+        None,
+        &mut timings,
+        Arc::new(RwLock::new(BlockCostCapacityMeter::new(block_cost_limit))),
+    )
+}
+
 // Note: If randomize is true this will shuffle entries' transactions in-place.
 fn process_entries_with_callback(
     bank: &Arc<Bank>,
@@ -461,6 +493,9 @@ pub enum BlockstoreProcessorError {
 
     #[error("root bank with mismatched capitalization at {0}")]
     RootBankWithMismatchedCapitalization(Slot),
+
+    #[error("failed to load snapshot: {0}")]
+    FailedToLoadSnapshot(String),
 }
 
 /// Callback for accessing bank state while processing the blockstore
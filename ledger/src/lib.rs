@@ -11,6 +11,7 @@ pub mod block_error;
 #[macro_use]
 pub mod blockstore;
 pub mod ancestor_iterator;
+pub mod blockstore_archive;
 pub mod blockstore_db;
 pub mod blockstore_meta;
 pub mod blockstore_processor;
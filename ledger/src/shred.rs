@@ -135,6 +135,11 @@ thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::
 
 pub const MAX_DATA_SHREDS_PER_FEC_BLOCK: u32 = 32;
 
+// Coding shreds of an FEC block are indexed from the block's fec_set_index, so
+// an FEC block that is not the last in its slot can't have more coding shreds
+// than data shreds without colliding with the next block's coding shreds.
+pub const MAX_CODING_SHREDS_PER_FEC_BLOCK: u32 = MAX_DATA_SHREDS_PER_FEC_BLOCK;
+
 pub const SHRED_TICK_REFERENCE_MASK: u8 = 0b0011_1111;
 const LAST_SHRED_IN_SLOT: u8 = 0b1000_0000;
 pub const DATA_COMPLETE_SHRED: u8 = 0b0100_0000;
@@ -774,6 +779,7 @@ impl Shredder {
             &data_shreds,
             is_last_in_slot,
             next_code_index,
+            MAX_CODING_SHREDS_PER_FEC_BLOCK,
             &mut stats,
         )
         .unwrap();
@@ -850,11 +856,14 @@ impl Shredder {
         data_shreds
     }
 
+    /// Generates `coding_shreds_per_fec_block` coding shreds for every full
+    /// FEC block of data shreds, see `generate_coding_shreds_at_rate`.
     pub fn data_shreds_to_coding_shreds(
         keypair: &Keypair,
         data_shreds: &[Shred],
         is_last_in_slot: bool,
         next_code_index: u32,
+        coding_shreds_per_fec_block: u32,
         process_stats: &mut ProcessShredsStats,
     ) -> Result<Vec<Shred>> {
         if data_shreds.is_empty() {
@@ -869,8 +878,8 @@ impl Shredder {
                     .enumerate()
                     .flat_map(|(i, shred_data_batch)| {
                         // Assumption here is that, for now, each fec block has
-                        // as many coding shreds as data shreds (except for the
-                        // last one in the slot).
+                        // at most as many coding shreds as data shreds (except
+                        // for the last one in the slot).
                         // TODO: tie this more closely with
                         // generate_coding_shreds.
                         let next_code_index = next_code_index
@@ -881,10 +890,11 @@ impl Shredder {
                                     .unwrap(),
                             )
                             .unwrap();
-                        Shredder::generate_coding_shreds(
+                        Shredder::generate_coding_shreds_at_rate(
                             shred_data_batch,
                             is_last_in_slot,
                             next_code_index,
+                            coding_shreds_per_fec_block,
                         )
                     })
                     .collect()
@@ -947,6 +957,25 @@ impl Shredder {
         data: &[Shred],
         is_last_in_slot: bool,
         next_code_index: u32,
+    ) -> Vec<Shred> {
+        Self::generate_coding_shreds_at_rate(
+            data,
+            is_last_in_slot,
+            next_code_index,
+            MAX_CODING_SHREDS_PER_FEC_BLOCK,
+        )
+    }
+
+    /// Generates coding shreds for the data shreds in the current FEC set at a
+    /// rate of `coding_shreds_per_fec_block` coding shreds per
+    /// MAX_DATA_SHREDS_PER_FEC_BLOCK data shreds, rounded up. The last FEC set
+    /// in the slot is always padded up to 2 * MAX_DATA_SHREDS_PER_FEC_BLOCK
+    /// shreds in total.
+    pub fn generate_coding_shreds_at_rate(
+        data: &[Shred],
+        is_last_in_slot: bool,
+        next_code_index: u32,
+        coding_shreds_per_fec_block: u32,
     ) -> Vec<Shred> {
         const PAYLOAD_ENCODE_SIZE: usize = SHRED_PAYLOAD_SIZE - SIZE_OF_CODING_SHRED_HEADERS;
         let ShredCommonHeader {
@@ -961,12 +990,17 @@ impl Shredder {
             && shred.common_header.version == version
             && shred.fec_set_index() == fec_set_index));
         let num_data = data.len();
+        let coding_shreds_per_fec_block =
+            coding_shreds_per_fec_block.clamp(1, MAX_CODING_SHREDS_PER_FEC_BLOCK) as usize;
+        let num_coding =
+            (num_data * coding_shreds_per_fec_block + MAX_DATA_SHREDS_PER_FEC_BLOCK as usize - 1)
+                / MAX_DATA_SHREDS_PER_FEC_BLOCK as usize;
         let num_coding = if is_last_in_slot {
             (2 * MAX_DATA_SHREDS_PER_FEC_BLOCK as usize)
                 .saturating_sub(num_data)
-                .max(num_data)
+                .max(num_coding)
         } else {
-            num_data
+            num_coding
         };
         let data: Vec<_> = data
             .iter()
@@ -1941,6 +1975,7 @@ pub mod tests {
                 &data_shreds[..count],
                 false, // is_last_in_slot
                 next_code_index,
+                MAX_CODING_SHREDS_PER_FEC_BLOCK,
                 &mut stats,
             )
             .unwrap();
@@ -1950,6 +1985,7 @@ pub mod tests {
                 &data_shreds[..count],
                 true, // is_last_in_slot
                 next_code_index,
+                MAX_CODING_SHREDS_PER_FEC_BLOCK,
                 &mut stats,
             )
             .unwrap();
@@ -1964,6 +2000,7 @@ pub mod tests {
             &data_shreds[..MAX_DATA_SHREDS_PER_FEC_BLOCK as usize + 1],
             false, // is_last_in_slot
             next_code_index,
+            MAX_CODING_SHREDS_PER_FEC_BLOCK,
             &mut stats,
         )
         .unwrap();
@@ -1976,6 +2013,7 @@ pub mod tests {
             &data_shreds[..MAX_DATA_SHREDS_PER_FEC_BLOCK as usize + 1],
             true, // is_last_in_slot
             next_code_index,
+            MAX_CODING_SHREDS_PER_FEC_BLOCK,
             &mut stats,
         )
         .unwrap();
@@ -1985,6 +2023,58 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_coding_shreds_at_rate() {
+        let keypair = Keypair::new();
+        let shredder = Shredder::new(0, 0, 0, 0).unwrap();
+        let entries: Vec<_> = (0..500)
+            .map(|_| {
+                let keypair0 = Keypair::new();
+                let keypair1 = Keypair::new();
+                let tx0 =
+                    system_transaction::transfer(&keypair0, &keypair1.pubkey(), 1, Hash::default());
+                Entry::new(&Hash::default(), 1, vec![tx0])
+            })
+            .collect();
+        let mut stats = ProcessShredsStats::default();
+        let next_shred_index = 0;
+        let data_shreds = shredder.entries_to_data_shreds(
+            &keypair,
+            &entries,
+            false, // is_last_in_slot
+            next_shred_index,
+            next_shred_index, // fec_set_offset
+            &mut stats,
+        );
+        let num_fec_blocks = 2;
+        let data_shreds = &data_shreds[..num_fec_blocks * MAX_DATA_SHREDS_PER_FEC_BLOCK as usize];
+
+        let coding_shreds = Shredder::data_shreds_to_coding_shreds(
+            &keypair,
+            data_shreds,
+            false, // is_last_in_slot
+            0,     // next_code_index
+            8,     // coding_shreds_per_fec_block
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(coding_shreds.len(), num_fec_blocks * 8);
+        // Coding shreds are still indexed from the fec_set_index of their FEC block
+        for shred in &coding_shreds {
+            assert!(shred.sanitize());
+            assert!(shred.index() >= shred.fec_set_index());
+            assert!(shred.index() < shred.fec_set_index() + 8);
+        }
+
+        // Rates are rounded up and clamped to the supported range
+        let coding_shreds =
+            Shredder::generate_coding_shreds_at_rate(&data_shreds[..3], false, 0, 1);
+        assert_eq!(coding_shreds.len(), 1);
+        let coding_shreds =
+            Shredder::generate_coding_shreds_at_rate(&data_shreds[..3], false, 0, 1000);
+        assert_eq!(coding_shreds.len(), 3);
+    }
+
     #[test]
     fn test_invalid_parent_offset() {
         let shred = Shred::new_from_data(10, 0, 1000, Some(&[1, 2, 3]), false, false, 0, 1, 0);
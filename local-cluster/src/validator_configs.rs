@@ -20,7 +20,10 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         pubsub_config: config.pubsub_config.clone(),
         snapshot_config: config.snapshot_config.clone(),
         max_ledger_shreds: config.max_ledger_shreds,
+        max_ledger_age: config.max_ledger_age,
+        ledger_cleanup_dry_run: config.ledger_cleanup_dry_run,
         broadcast_stage_type: config.broadcast_stage_type.clone(),
+        broadcast_config: config.broadcast_config,
         enable_partition: config.enable_partition.clone(),
         enforce_ulimit_nofile: config.enforce_ulimit_nofile,
         fixed_leader_schedule: config.fixed_leader_schedule.clone(),
@@ -38,6 +41,10 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accounts_hash_interval_slots: config.accounts_hash_interval_slots,
         max_genesis_archive_unpacked_size: config.max_genesis_archive_unpacked_size,
         wal_recovery_mode: config.wal_recovery_mode.clone(),
+        blockstore_compaction: config.blockstore_compaction.clone(),
+        blockstore_shred_compression: config.blockstore_shred_compression,
+        blockstore_tuning: config.blockstore_tuning.clone(),
+        blockstore_archive: config.blockstore_archive.clone(),
         poh_verify: config.poh_verify,
         require_tower: config.require_tower,
         tower_storage: config.tower_storage.clone(),
@@ -56,7 +63,9 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
         accounts_db_use_index_hash_calculation: config.accounts_db_use_index_hash_calculation,
         tpu_coalesce_ms: config.tpu_coalesce_ms,
+        tpu_quic_connection_table_config: config.tpu_quic_connection_table_config,
         validator_exit: Arc::new(RwLock::new(Exit::default())),
+        validator_drain: Arc::default(),
         poh_hashes_per_batch: config.poh_hashes_per_batch,
         no_wait_for_vote_to_start_leader: config.no_wait_for_vote_to_start_leader,
         accounts_shrink_ratio: config.accounts_shrink_ratio,
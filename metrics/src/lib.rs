@@ -2,6 +2,7 @@
 pub mod counter;
 pub mod datapoint;
 mod metrics;
+mod prometheus;
 pub use crate::metrics::{flush, query, set_host_id, set_panic_hook, submit};
 use std::sync::Arc;
 
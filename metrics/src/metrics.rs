@@ -1,7 +1,11 @@
 //! The `metrics` module enables sending measurements to an `InfluxDB` instance
 
 use {
-    crate::{counter::CounterPoint, datapoint::DataPoint},
+    crate::{
+        counter::CounterPoint,
+        datapoint::DataPoint,
+        prometheus::{PrometheusConfig, PrometheusMetricsWriter},
+    },
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
     gethostname::gethostname,
     lazy_static::lazy_static,
@@ -40,7 +44,7 @@ struct MetricsAgent {
     sender: Sender<MetricsCommand>,
 }
 
-trait MetricsWriter {
+pub(crate) trait MetricsWriter {
     // Write the points and empty the vector.  Called on the internal
     // MetricsAgent worker thread.
     fn write(&self, points: Vec<DataPoint>);
@@ -140,7 +144,7 @@ impl Default for MetricsAgent {
             .unwrap_or(4000);
 
         Self::new(
-            Arc::new(InfluxDbMetricsWriter::new()),
+            Self::default_writer(),
             Duration::from_secs(10),
             max_points_per_sec,
         )
@@ -148,6 +152,25 @@ impl Default for MetricsAgent {
 }
 
 impl MetricsAgent {
+    /// Exposes the points on a Prometheus endpoint if `SOLANA_METRICS_PROMETHEUS_ADDR` is set,
+    /// and sends them to InfluxDB otherwise
+    fn default_writer() -> Arc<dyn MetricsWriter + Send + Sync> {
+        let prometheus_writer = PrometheusConfig::from_env().and_then(|config| {
+            if config.addr.is_none() {
+                return Ok(None);
+            }
+            PrometheusMetricsWriter::new(config).map(Some)
+        });
+        match prometheus_writer {
+            Ok(Some(writer)) => Arc::new(writer),
+            Ok(None) => Arc::new(InfluxDbMetricsWriter::new()),
+            Err(err) => {
+                warn!("prometheus metrics disabled: {}", err);
+                Arc::new(InfluxDbMetricsWriter::new())
+            }
+        }
+    }
+
     fn new(
         writer: Arc<dyn MetricsWriter + Send + Sync>,
         write_frequency: Duration,
@@ -330,7 +353,7 @@ fn get_singleton_agent() -> Arc<Mutex<MetricsAgent>> {
 }
 
 lazy_static! {
-    static ref HOST_ID: Arc<RwLock<String>> = {
+    pub(crate) static ref HOST_ID: Arc<RwLock<String>> = {
         Arc::new(RwLock::new({
             let hostname: String = gethostname()
                 .into_string()
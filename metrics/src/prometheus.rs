@@ -0,0 +1,280 @@
+//! The `prometheus` module exposes the submitted measurements on a local `/metrics` endpoint for
+//! Prometheus to scrape, as an alternative to sending them to an `InfluxDB` instance
+
+use {
+    crate::{
+        datapoint::DataPoint,
+        metrics::{MetricsWriter, HOST_ID},
+    },
+    log::*,
+    std::{
+        collections::BTreeMap,
+        env,
+        fmt::Write as _,
+        io::{BufRead, BufReader, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{Arc, RwLock},
+        thread,
+        time::Duration,
+    },
+};
+
+const METRIC_PREFIX: &str = "solana";
+
+/// Requests are served one at a time, so a client that stalls must not hold up the others
+const STREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A scrape request is a request line and a few headers
+const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+
+/// The latest value of each field of each measurement, keyed by metric name
+type Gauges = BTreeMap<String, f64>;
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PrometheusConfig {
+    pub addr: Option<SocketAddr>,
+    /// Measurements to expose, all of them if empty. An entry ending with `*` matches every
+    /// measurement starting with what precedes it.
+    pub allowlist: Vec<String>,
+}
+
+impl PrometheusConfig {
+    /// Reads `SOLANA_METRICS_PROMETHEUS_ADDR`, the address to serve `/metrics` on, and
+    /// `SOLANA_METRICS_PROMETHEUS_ALLOWLIST`, a comma separated list of measurements to expose
+    pub fn from_env() -> Result<Self, String> {
+        let addr = match env::var("SOLANA_METRICS_PROMETHEUS_ADDR") {
+            Ok(addr) => Some(
+                addr.parse()
+                    .map_err(|err| format!("SOLANA_METRICS_PROMETHEUS_ADDR is invalid: {}", err))?,
+            ),
+            Err(_) => None,
+        };
+        let allowlist = env::var("SOLANA_METRICS_PROMETHEUS_ALLOWLIST")
+            .map(|allowlist| Self::parse_allowlist(&allowlist))
+            .unwrap_or_default();
+        Ok(Self { addr, allowlist })
+    }
+
+    fn parse_allowlist(allowlist: &str) -> Vec<String> {
+        allowlist
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowlist.is_empty()
+            || self
+                .allowlist
+                .iter()
+                .any(|allowed| match allowed.strip_suffix('*') {
+                    Some(prefix) => name.starts_with(prefix),
+                    None => name == allowed,
+                })
+    }
+}
+
+/// Keeps the latest value of every numeric and boolean field of the points it is given as a
+/// gauge named `solana_<measurement>_<field>`. Counters are exposed the way they are reported to
+/// `InfluxDB`, as the count over the last write interval.
+pub(crate) struct PrometheusMetricsWriter {
+    config: PrometheusConfig,
+    gauges: Arc<RwLock<Gauges>>,
+}
+
+impl PrometheusMetricsWriter {
+    /// Starts serving `/metrics` on `config.addr`
+    pub fn new(config: PrometheusConfig) -> Result<Self, String> {
+        let addr = config
+            .addr
+            .ok_or_else(|| "SOLANA_METRICS_PROMETHEUS_ADDR is not set".to_string())?;
+        let listener = TcpListener::bind(addr)
+            .map_err(|err| format!("Unable to bind the metrics endpoint to {}: {}", addr, err))?;
+        info!("serving prometheus metrics on http://{}/metrics", addr);
+
+        let gauges = Arc::new(RwLock::new(Gauges::new()));
+        let server_gauges = gauges.clone();
+        thread::Builder::new()
+            .name("solana-metrics-prometheus".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let host_id = HOST_ID.read().unwrap().clone();
+                            if let Err(err) = Self::serve(stream, &server_gauges, &host_id) {
+                                debug!("metrics request failed: {}", err);
+                            }
+                        }
+                        Err(err) => warn!("metrics connection failed: {}", err),
+                    }
+                }
+            })
+            .map_err(|err| err.to_string())?;
+        Ok(Self { config, gauges })
+    }
+
+    fn serve(mut stream: TcpStream, gauges: &RwLock<Gauges>, host_id: &str) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(STREAM_TIMEOUT))?;
+        stream.set_write_timeout(Some(STREAM_TIMEOUT))?;
+        let mut reader = BufReader::new((&stream).take(MAX_REQUEST_SIZE));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Consume the headers so that closing the connection doesn't reset it
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+        let mut request = request_line.split_whitespace();
+        let response = match (request.next(), request.next()) {
+            (Some("GET"), Some("/metrics")) => {
+                let body = Self::render(&gauges.read().unwrap(), host_id);
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            }
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+        stream.write_all(response.as_bytes())
+    }
+
+    /// Formats the gauges in the Prometheus text exposition format
+    fn render(gauges: &Gauges, host_id: &str) -> String {
+        let mut body = String::new();
+        for (name, value) in gauges {
+            let _ = writeln!(body, "# TYPE {} gauge", name);
+            let _ = writeln!(body, "{}{{host_id=\"{}\"}} {}", name, host_id, value);
+        }
+        body
+    }
+
+    /// Replaces every character Prometheus doesn't allow in metric names with an underscore
+    fn metric_name(measurement: &str, field: &str) -> String {
+        format!("{}_{}_{}", METRIC_PREFIX, measurement, field)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    /// Parses a field value as formatted by `DataPoint`, skipping strings
+    fn parse_value(value: &str) -> Option<f64> {
+        match value {
+            "true" => Some(1.0),
+            "false" => Some(0.0),
+            _ => value.strip_suffix('i').unwrap_or(value).parse().ok(),
+        }
+    }
+}
+
+impl MetricsWriter for PrometheusMetricsWriter {
+    fn write(&self, points: Vec<DataPoint>) {
+        let mut gauges = self.gauges.write().unwrap();
+        for point in points {
+            if !self.config.is_allowed(point.name) {
+                continue;
+            }
+            for (field, value) in point.fields {
+                if let Some(value) = Self::parse_value(&value) {
+                    gauges.insert(Self::metric_name(point.name, field), value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allowlist() {
+        let config = PrometheusConfig {
+            addr: None,
+            allowlist: PrometheusConfig::parse_allowlist("replay-slot-stats, banking_stage-*,"),
+        };
+        assert!(config.is_allowed("replay-slot-stats"));
+        assert!(config.is_allowed("banking_stage-leader_slot_packet_counts"));
+        assert!(!config.is_allowed("replay-slot-stats-extra"));
+        assert!(!config.is_allowed("cost-tracker-stats"));
+        assert!(PrometheusConfig::default().is_allowed("cost-tracker-stats"));
+    }
+
+    #[test]
+    fn test_prometheus_metrics_writer() {
+        let config = PrometheusConfig {
+            addr: Some("127.0.0.1:0".parse().unwrap()),
+            allowlist: vec!["replay-*".to_string()],
+        };
+        let writer = PrometheusMetricsWriter::new(config).unwrap();
+        writer.write(vec![
+            DataPoint::new("replay-slot-stats")
+                .add_field_i64("slot", 41)
+                .add_field_f64("ratio", 0.5)
+                .add_field_bool("is_leader", true)
+                .add_field_str("leader", "skipped")
+                .to_owned(),
+            DataPoint::new("other").add_field_i64("count", 1).to_owned(),
+        ]);
+        writer.write(vec![DataPoint::new("replay-slot-stats")
+            .add_field_i64("slot", 42)
+            .to_owned()]);
+
+        assert_eq!(
+            PrometheusMetricsWriter::render(&writer.gauges.read().unwrap(), "test-host"),
+            "# TYPE solana_replay_slot_stats_is_leader gauge\n\
+             solana_replay_slot_stats_is_leader{host_id=\"test-host\"} 1\n\
+             # TYPE solana_replay_slot_stats_ratio gauge\n\
+             solana_replay_slot_stats_ratio{host_id=\"test-host\"} 0.5\n\
+             # TYPE solana_replay_slot_stats_slot gauge\n\
+             solana_replay_slot_stats_slot{host_id=\"test-host\"} 42\n"
+        );
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let gauges = Arc::new(RwLock::new(Gauges::new()));
+        gauges
+            .write()
+            .unwrap()
+            .insert("solana_a_b".to_string(), 3.0);
+        let server_gauges = gauges.clone();
+        let server = thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                PrometheusMetricsWriter::serve(stream.unwrap(), &server_gauges, "h").unwrap();
+            }
+        });
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = request("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("solana_a_b{host_id=\"h\"} 3\n"));
+        assert!(request("/").starts_with("HTTP/1.1 404 Not Found"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_serve_stalled_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let gauges = RwLock::new(Gauges::new());
+
+        // A client that connects and never sends its request times out instead of blocking the
+        // endpoint
+        let _stream = TcpStream::connect(addr).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        assert!(PrometheusMetricsWriter::serve(stream, &gauges, "h").is_err());
+    }
+}
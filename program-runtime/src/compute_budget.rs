@@ -57,6 +57,20 @@ pub struct ComputeBudget {
     pub syscall_base_cost: u64,
     /// Number of compute units consumed to call zktoken_crypto_op
     pub zk_token_elgamal_op_cost: u64,
+    /// Number of compute units consumed to validate a curve25519 ristretto point
+    pub curve25519_ristretto_validate_point_cost: u64,
+    /// Number of compute units consumed to add two curve25519 ristretto points
+    pub curve25519_ristretto_add_cost: u64,
+    /// Number of compute units consumed to subtract two curve25519 ristretto points
+    pub curve25519_ristretto_subtract_cost: u64,
+    /// Number of compute units consumed to multiply a curve25519 ristretto point
+    pub curve25519_ristretto_multiply_cost: u64,
+    /// Base number of compute units consumed by a multiscalar multiplication of curve25519
+    /// ristretto points
+    pub curve25519_ristretto_msm_base_cost: u64,
+    /// Number of compute units consumed by each additional point of a multiscalar multiplication
+    /// of curve25519 ristretto points
+    pub curve25519_ristretto_msm_incremental_cost: u64,
     /// Optional program heap region size, if `None` then loader default
     pub heap_size: Option<usize>,
     /// Number of compute units per additional 32k heap above the default (~.5
@@ -91,6 +105,12 @@ impl ComputeBudget {
             secp256k1_recover_cost: 25_000,
             syscall_base_cost: 100,
             zk_token_elgamal_op_cost: 25_000,
+            curve25519_ristretto_validate_point_cost: 169,
+            curve25519_ristretto_add_cost: 521,
+            curve25519_ristretto_subtract_cost: 519,
+            curve25519_ristretto_multiply_cost: 2_208,
+            curve25519_ristretto_msm_base_cost: 2_303,
+            curve25519_ristretto_msm_incremental_cost: 788,
             heap_size: None,
             heap_cost: 8,
             mem_op_base_cost: 10,
@@ -840,6 +840,16 @@ impl<'a> InvokeContext<'a> {
             );
         }
 
+        if let Some(log_collector) = &self.log_collector {
+            if let Ok(mut log_collector) = log_collector.try_borrow_mut() {
+                log_collector.begin_invocation(
+                    program_id,
+                    instruction_data,
+                    stack_height.saturating_add(1),
+                );
+            }
+        }
+
         let result = self
             .push(instruction_accounts, program_indices, instruction_data)
             .and_then(|_| {
@@ -884,6 +894,12 @@ impl<'a> InvokeContext<'a> {
 
         // Pop the invoke_stack to restore previous state
         let _ = self.pop();
+
+        if let Some(log_collector) = &self.log_collector {
+            if let Ok(mut log_collector) = log_collector.try_borrow_mut() {
+                log_collector.end_invocation(*compute_units_consumed, &result);
+            }
+        }
         result
     }
 
@@ -1,5 +1,10 @@
 pub use log;
-use std::{cell::RefCell, rc::Rc};
+use {
+    solana_sdk::{
+        instruction::InstructionError, pubkey::Pubkey, transaction_context::InvocationRecord,
+    },
+    std::{cell::RefCell, rc::Rc},
+};
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
 
@@ -8,6 +13,16 @@ pub struct LogCollector {
     bytes_written: usize,
     bytes_limit: Option<usize>,
     limit_warning: bool,
+    /// `Some` if invocations are recorded
+    invocations: Option<InvocationRecords>,
+}
+
+#[derive(Default)]
+struct InvocationRecords {
+    /// The invocations that completed at the transaction level
+    completed: Vec<InvocationRecord>,
+    /// The invocations in progress, innermost last
+    stack: Vec<InvocationRecord>,
 }
 
 impl Default for LogCollector {
@@ -17,6 +32,7 @@ impl Default for LogCollector {
             bytes_written: 0,
             bytes_limit: Some(LOG_MESSAGES_BYTES_LIMIT),
             limit_warning: false,
+            invocations: None,
         }
     }
 }
@@ -35,11 +51,60 @@ impl LogCollector {
         if bytes_written >= limit {
             if !self.limit_warning {
                 self.limit_warning = true;
-                self.messages.push(String::from("Log truncated"));
+                self.push_message(String::from("Log truncated"));
             }
         } else {
             self.bytes_written = bytes_written;
-            self.messages.push(message.to_string());
+            self.push_message(message.to_string());
+        }
+    }
+
+    fn push_message(&mut self, message: String) {
+        if let Some(invocation) = self
+            .invocations
+            .as_mut()
+            .and_then(|invocations| invocations.stack.last_mut())
+        {
+            invocation.logs.push(message.clone());
+        }
+        self.messages.push(message);
+    }
+
+    /// Starts recording an invocation, if invocations are recorded
+    pub fn begin_invocation(
+        &mut self,
+        program_id: Pubkey,
+        instruction_data: &[u8],
+        stack_height: usize,
+    ) {
+        if let Some(invocations) = self.invocations.as_mut() {
+            invocations.stack.push(InvocationRecord {
+                program_id,
+                instruction_data: instruction_data.to_vec(),
+                stack_height,
+                compute_units_consumed: 0,
+                logs: Vec::new(),
+                result: Ok(()),
+                inner_invocations: Vec::new(),
+            });
+        }
+    }
+
+    /// Completes the innermost invocation in progress
+    pub fn end_invocation(
+        &mut self,
+        compute_units_consumed: u64,
+        result: &Result<(), InstructionError>,
+    ) {
+        if let Some(invocations) = self.invocations.as_mut() {
+            if let Some(mut invocation) = invocations.stack.pop() {
+                invocation.compute_units_consumed = compute_units_consumed;
+                invocation.result = result.clone();
+                match invocations.stack.last_mut() {
+                    Some(caller) => caller.inner_invocations.push(invocation),
+                    None => invocations.completed.push(invocation),
+                }
+            }
         }
     }
 
@@ -47,6 +112,22 @@ impl LogCollector {
         self.messages.as_slice()
     }
 
+    /// The invocations made at the transaction level that completed, if invocations are recorded
+    pub fn get_recorded_invocations(&self) -> Option<&[InvocationRecord]> {
+        self.invocations
+            .as_ref()
+            .map(|invocations| invocations.completed.as_slice())
+    }
+
+    /// Returns the log messages and, if invocations are recorded, the invocations made at the
+    /// transaction level
+    pub fn into_messages_and_invocations(self) -> (Vec<String>, Option<Vec<InvocationRecord>>) {
+        (
+            self.messages,
+            self.invocations.map(|invocations| invocations.completed),
+        )
+    }
+
     pub fn new_ref() -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Self::default()))
     }
@@ -57,6 +138,15 @@ impl LogCollector {
             ..Self::default()
         }))
     }
+
+    /// Creates a log collector which also records the tree of invocations, along with the log
+    /// messages each of them emitted
+    pub fn new_ref_with_invocation_recording() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            invocations: Some(InvocationRecords::default()),
+            ..Self::default()
+        }))
+    }
 }
 
 impl From<LogCollector> for Vec<String> {
@@ -124,4 +214,52 @@ pub(crate) mod tests {
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_invocation_recording() {
+        let program_a = Pubkey::new_unique();
+        let program_b = Pubkey::new_unique();
+        let mut lc = LogCollector::default();
+        lc.invocations = Some(InvocationRecords::default());
+
+        lc.begin_invocation(program_a, &[1], 1);
+        lc.log("a");
+        lc.begin_invocation(program_b, &[2], 2);
+        lc.log("b");
+        lc.end_invocation(10, &Err(InstructionError::Custom(42)));
+        lc.log("a again");
+        lc.end_invocation(30, &Ok(()));
+
+        let (messages, invocations) = lc.into_messages_and_invocations();
+        assert_eq!(messages, vec!["a", "b", "a again"]);
+        assert_eq!(
+            invocations,
+            Some(vec![InvocationRecord {
+                program_id: program_a,
+                instruction_data: vec![1],
+                stack_height: 1,
+                compute_units_consumed: 30,
+                logs: vec!["a".to_string(), "a again".to_string()],
+                result: Ok(()),
+                inner_invocations: vec![InvocationRecord {
+                    program_id: program_b,
+                    instruction_data: vec![2],
+                    stack_height: 2,
+                    compute_units_consumed: 10,
+                    logs: vec!["b".to_string()],
+                    result: Err(InstructionError::Custom(42)),
+                    inner_invocations: vec![],
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_invocation_recording_disabled() {
+        let mut lc = LogCollector::default();
+        lc.begin_invocation(Pubkey::new_unique(), &[], 1);
+        lc.log("x");
+        lc.end_invocation(0, &Ok(()));
+        assert_eq!(lc.get_recorded_invocations(), None);
+    }
 }
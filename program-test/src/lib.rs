@@ -9,6 +9,7 @@ use {
     log::*,
     solana_banks_client::start_client,
     solana_banks_server::banks_server::start_local_server,
+    solana_client::{nonblocking::rpc_client::RpcClient, rpc_response::RpcKeyedAccount},
     solana_program_runtime::{
         compute_budget::ComputeBudget, ic_msg, invoke_context::ProcessInstructionWithContext,
         stable_log, timings::ExecuteTimings,
@@ -23,7 +24,9 @@ use {
     solana_sdk::{
         account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
         account_info::AccountInfo,
-        clock::Slot,
+        account_utils::StateMut,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        clock::{Epoch, Slot},
         entrypoint::{ProgramResult, SUCCESS},
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         genesis_config::{ClusterType, GenesisConfig},
@@ -39,8 +42,9 @@ use {
     },
     solana_vote_program::vote_state::{VoteState, VoteStateVersions},
     std::{
+        any::Any,
         cell::RefCell,
-        collections::HashSet,
+        collections::{HashMap, HashSet, VecDeque},
         convert::TryFrom,
         fs::File,
         io::{self, Read},
@@ -57,7 +61,12 @@ use {
     tokio::task::JoinHandle,
 };
 // Export types so test clients can limit their solana crate dependencies
-pub use {solana_banks_client::BanksClient, solana_program_runtime::invoke_context::InvokeContext};
+pub use {
+    solana_banks_client::{BanksClient, BanksTransactionResultWithMetadata, TransactionMetadata},
+    solana_program_runtime::invoke_context::InvokeContext,
+    solana_runtime::bank::RewardInfo,
+    solana_sdk::transaction_context::InvocationRecord,
+};
 
 pub mod programs;
 
@@ -75,6 +84,31 @@ pub enum ProgramTestError {
 thread_local! {
     static INVOKE_CONTEXT: RefCell<Option<usize>> = RefCell::new(None);
 }
+
+/// Overrides of what the syscall stubs return to a natively built program
+#[derive(Clone, Debug, Default)]
+struct SyscallOverrides {
+    /// Serialized sysvars, by sysvar id
+    sysvars: HashMap<Pubkey, Vec<u8>>,
+    remaining_compute_units: Option<u64>,
+}
+
+lazy_static::lazy_static! {
+    /// The syscall overrides of each natively built program, by program id
+    static ref SYSCALL_OVERRIDES: RwLock<HashMap<Pubkey, SyscallOverrides>> =
+        RwLock::default();
+}
+
+fn get_overridden_sysvar_data(program_id: &Pubkey, sysvar_id: &Pubkey) -> Option<Vec<u8>> {
+    SYSCALL_OVERRIDES
+        .read()
+        .unwrap()
+        .get(program_id)?
+        .sysvars
+        .get(sysvar_id)
+        .cloned()
+}
+
 fn set_invoke_context(new: &mut InvokeContext) {
     INVOKE_CONTEXT
         .with(|invoke_context| unsafe { invoke_context.replace(Some(transmute::<_, usize>(new))) });
@@ -117,11 +151,13 @@ pub fn builtin_process_instruction(
         .map(|index_in_instruction| {
             let borrowed_account = instruction_context
                 .try_borrow_account(transaction_context, *index_in_instruction)?;
+            let data = get_overridden_sysvar_data(program_id, borrowed_account.get_key())
+                .unwrap_or_else(|| borrowed_account.get_data().to_vec());
             Ok((
                 *borrowed_account.get_key(),
                 *borrowed_account.get_owner(),
                 borrowed_account.get_lamports(),
-                borrowed_account.get_data().to_vec(),
+                data,
             ))
         })
         .collect::<Result<Vec<_>, InstructionError>>()?;
@@ -211,6 +247,17 @@ fn get_sysvar<T: Default + Sysvar + Sized + serde::de::DeserializeOwned + Clone>
     var_addr: *mut u8,
 ) -> u64 {
     let invoke_context = get_invoke_context();
+    let sysvar = match invoke_context
+        .transaction_context
+        .get_program_key()
+        .ok()
+        .and_then(|program_id| get_overridden_sysvar_data(program_id, &T::id()))
+    {
+        Some(data) => Ok(Arc::new(
+            bincode::deserialize(&data).expect("overridden sysvar must deserialize"),
+        )),
+        None => sysvar,
+    };
     if invoke_context
         .get_compute_meter()
         .try_borrow_mut()
@@ -238,6 +285,27 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
         ic_msg!(invoke_context, "Program log: {}", message);
     }
 
+    fn sol_log_compute_units(&self) {
+        let invoke_context = get_invoke_context();
+        let remaining_compute_units = invoke_context
+            .transaction_context
+            .get_program_key()
+            .ok()
+            .and_then(|program_id| {
+                SYSCALL_OVERRIDES
+                    .read()
+                    .unwrap()
+                    .get(program_id)?
+                    .remaining_compute_units
+            })
+            .unwrap_or_else(|| invoke_context.get_compute_meter().borrow().get_remaining());
+        ic_msg!(
+            invoke_context,
+            "Program consumption: {} units remaining",
+            remaining_compute_units
+        );
+    }
+
     fn sol_invoke_signed(
         &self,
         instruction: &Instruction,
@@ -415,6 +483,21 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> Vec<u8> {
     file_data
 }
 
+fn read_account_fixture(address: &Pubkey) -> Account {
+    let filename = format!("{}.json", address);
+    let path = find_file(&filename).unwrap_or_else(|| {
+        panic!("Unable to locate {}", filename);
+    });
+    let keyed_account: RpcKeyedAccount = serde_json::from_slice(&read_file(&path))
+        .unwrap_or_else(|err| panic!("Failed to deserialize \"{}\": {}", path.display(), err));
+    keyed_account.account.decode().unwrap_or_else(|| {
+        panic!(
+            "Failed to decode the account data of \"{}\"",
+            path.display()
+        )
+    })
+}
+
 fn setup_fees(bank: Bank) -> Bank {
     // Realistic fees part 1: Fake a single signature by calling
     // `bank.commit_transactions()` so that the fee in the child bank will be
@@ -456,6 +539,7 @@ pub struct ProgramTest {
     compute_max_units: Option<u64>,
     prefer_bpf: bool,
     use_bpf_jit: bool,
+    syscall_overrides: SyscallOverrides,
 }
 
 impl Default for ProgramTest {
@@ -486,6 +570,7 @@ impl Default for ProgramTest {
             compute_max_units: None,
             prefer_bpf,
             use_bpf_jit: false,
+            syscall_overrides: SyscallOverrides::default(),
         }
     }
 }
@@ -530,6 +615,23 @@ impl ProgramTest {
         self.use_bpf_jit = use_bpf_jit;
     }
 
+    /// Override the contents of a sysvar as seen by the natively built programs of the test, both
+    /// through `Sysvar::get()` and through the sysvar's account
+    ///
+    /// Overrides are registered by program id when the test starts, so concurrently running tests
+    /// that add the same natively built program should not override syscalls differently.
+    pub fn override_sysvar<T: Sysvar>(&mut self, sysvar: &T) {
+        self.syscall_overrides
+            .sysvars
+            .insert(T::id(), bincode::serialize(sysvar).unwrap());
+    }
+
+    /// Override the remaining compute units that `sol_log_compute_units()` logs in the natively
+    /// built programs of the test
+    pub fn override_remaining_compute_units(&mut self, remaining_compute_units: u64) {
+        self.syscall_overrides.remaining_compute_units = Some(remaining_compute_units);
+    }
+
     /// Add an account to the test environment
     pub fn add_account(&mut self, address: Pubkey, account: Account) {
         self.accounts
@@ -580,6 +682,46 @@ impl ProgramTest {
         );
     }
 
+    /// Add accounts cloned from the cluster at `rpc_url` to the test environment
+    ///
+    /// The programdata account of an upgradeable program is cloned along with the program.
+    ///
+    /// An account that can't be fetched from the cluster is read from `<address>.json` instead,
+    /// which is searched for like BPF program shared objects and can be created with
+    /// `solana account <address> --output json --output-file <address>.json`
+    pub async fn add_accounts_from_cluster(&mut self, rpc_url: &str, addresses: &[Pubkey]) {
+        let rpc_client = RpcClient::new(rpc_url.to_string());
+        let mut pending_addresses = addresses.iter().copied().collect::<VecDeque<_>>();
+        let mut added_addresses = HashSet::new();
+        while let Some(address) = pending_addresses.pop_front() {
+            if !added_addresses.insert(address) {
+                continue;
+            }
+            let account = match rpc_client.get_account(&address).await {
+                Ok(account) => {
+                    info!("Cloned {} from {}", address, rpc_url);
+                    account
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to fetch {} from {}: {}, reading it from a fixture file instead",
+                        address, rpc_url, err
+                    );
+                    read_account_fixture(&address)
+                }
+            };
+            if bpf_loader_upgradeable::check_id(&account.owner) {
+                if let Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) = account.state()
+                {
+                    pending_addresses.push_back(programdata_address);
+                }
+            }
+            self.add_account(address, account);
+        }
+    }
+
     /// Add a BPF program to the test environment.
     ///
     /// `program_name` will also be used to locate the BPF shared object in the current or fixtures
@@ -788,6 +930,10 @@ impl ProgramTest {
                 &builtin.id,
                 builtin.process_instruction_with_context,
             );
+            SYSCALL_OVERRIDES
+                .write()
+                .unwrap()
+                .insert(builtin.id, self.syscall_overrides.clone());
         }
 
         for (address, account) in self.accounts.iter() {
@@ -950,6 +1096,47 @@ impl ProgramTestBanksClientExt for BanksClient {
     }
 }
 
+/// Compute unit assertions on the metadata of transactions processed with
+/// `BanksClient::process_transaction_with_metadata()`, to catch programs getting more expensive
+pub trait ProgramTestTransactionMetadataExt {
+    /// Get the compute units consumed by instruction `ix_index`, if it was executed
+    fn instruction_compute_units(&self, ix_index: usize) -> Option<u64>;
+    /// Panic if instruction `ix_index` wasn't executed or consumed more than `limit` compute units
+    fn assert_max_cu(&self, ix_index: usize, limit: u64);
+    /// Panic if the transaction consumed more than `limit` compute units
+    fn assert_max_total_cu(&self, limit: u64);
+}
+
+impl ProgramTestTransactionMetadataExt for TransactionMetadata {
+    fn instruction_compute_units(&self, ix_index: usize) -> Option<u64> {
+        self.instructions_compute_units_consumed
+            .get(ix_index)
+            .copied()
+    }
+
+    fn assert_max_cu(&self, ix_index: usize, limit: u64) {
+        let compute_units = self
+            .instruction_compute_units(ix_index)
+            .unwrap_or_else(|| panic!("Instruction {} was not executed", ix_index));
+        assert!(
+            compute_units <= limit,
+            "Instruction {} consumed {} compute units, over the limit of {}",
+            ix_index,
+            compute_units,
+            limit
+        );
+    }
+
+    fn assert_max_total_cu(&self, limit: u64) {
+        assert!(
+            self.compute_units_consumed <= limit,
+            "Transaction consumed {} compute units, over the limit of {}",
+            self.compute_units_consumed,
+            limit
+        );
+    }
+}
+
 struct DroppableTask<T>(Arc<AtomicBool>, JoinHandle<T>);
 
 impl<T> Drop for DroppableTask<T> {
@@ -1057,12 +1244,112 @@ impl ProgramTestContext {
     /// that would be difficult to replicate on a new test cluster. Beware
     /// that it can be used to create states that would not be reachable
     /// under normal conditions!
-    pub fn set_sysvar<T: SysvarId + Sysvar>(&self, sysvar: &T) {
+    ///
+    /// Setting `Rent` also changes the rent the runtime collects and checks
+    /// accounts against, which moves the working bank to the next slot.
+    pub fn set_sysvar<T: SysvarId + Sysvar + 'static>(&mut self, sysvar: &T) {
+        if let Some(rent) = (sysvar as &dyn Any).downcast_ref::<Rent>() {
+            self.advance_working_bank(|bank| bank.set_rent_for_tests(*rent));
+        }
         let bank_forks = self.bank_forks.read().unwrap();
         let bank = bank_forks.working_bank();
         bank.set_sysvar_for_tests(sysvar);
     }
 
+    /// Move the Unix timestamp of the working bank's clock forward by `duration`.
+    ///
+    /// Later banks keep the clock at least this far ahead.
+    pub fn advance_clock_by(&mut self, duration: Duration) {
+        let mut clock = self.bank_forks.read().unwrap().working_bank().clock();
+        clock.unix_timestamp = clock
+            .unix_timestamp
+            .saturating_add(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX));
+        self.set_sysvar(&clock);
+    }
+
+    /// Force the working bank ahead to the first slot of a new epoch.
+    ///
+    /// Every epoch boundary in between is crossed, so stake activation, stake
+    /// history and rewards progress as they would on a running cluster.
+    pub fn warp_to_epoch(&mut self, warp_epoch: Epoch) -> Result<(), ProgramTestError> {
+        let working_epoch = self.bank_forks.read().unwrap().working_bank().epoch();
+        if warp_epoch <= working_epoch {
+            return Err(ProgramTestError::InvalidWarpSlot);
+        }
+        for epoch in working_epoch + 1..=warp_epoch {
+            let first_slot = self
+                .genesis_config
+                .epoch_schedule
+                .get_first_slot_in_epoch(epoch);
+            self.warp_to_slot(first_slot)?;
+        }
+        Ok(())
+    }
+
+    /// Force the working bank ahead to the first slot of a new epoch, like
+    /// `warp_to_epoch()`, but simulate validators voting along the way: every
+    /// vote account earns `credits_per_epoch` credits before each epoch
+    /// boundary, so stakes delegated to them accrue rewards.
+    ///
+    /// Returns the rewards distributed at each epoch boundary crossed, in order.
+    pub fn warp_to_epoch_with_vote_credits(
+        &mut self,
+        warp_epoch: Epoch,
+        credits_per_epoch: u64,
+    ) -> Result<Vec<Vec<(Pubkey, RewardInfo)>>, ProgramTestError> {
+        let working_epoch = self.bank_forks.read().unwrap().working_bank().epoch();
+        if warp_epoch <= working_epoch {
+            return Err(ProgramTestError::InvalidWarpSlot);
+        }
+        let mut rewards = Vec::new();
+        for epoch in working_epoch + 1..=warp_epoch {
+            let vote_accounts = self
+                .bank_forks
+                .read()
+                .unwrap()
+                .working_bank()
+                .vote_accounts();
+            for vote_account_address in vote_accounts.keys() {
+                self.increment_vote_account_credits(vote_account_address, credits_per_epoch);
+            }
+
+            let first_slot = self
+                .genesis_config
+                .epoch_schedule
+                .get_first_slot_in_epoch(epoch);
+            self.warp_to_slot(first_slot)?;
+
+            let bank = self.bank_forks.read().unwrap().working_bank();
+            let epoch_rewards = bank.rewards.read().unwrap().clone();
+            rewards.push(epoch_rewards);
+        }
+        Ok(rewards)
+    }
+
+    /// Replace the working bank with a child bank at the next slot, configured
+    /// by `configure` before it is used
+    fn advance_working_bank(&mut self, configure: impl FnOnce(&mut Bank)) {
+        let mut bank_forks = self.bank_forks.write().unwrap();
+        let bank = bank_forks.working_bank();
+
+        // Force ticks until a new blockhash, otherwise retried transactions will have
+        // the same signature
+        let last_blockhash = bank.last_blockhash();
+        while last_blockhash == bank.last_blockhash() {
+            bank.register_tick(&Hash::new_unique());
+        }
+
+        let slot = bank.slot() + 1;
+        let mut new_bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        configure(&mut new_bank);
+        let new_bank = bank_forks.insert(new_bank);
+        self.block_commitment_cache
+            .write()
+            .unwrap()
+            .set_all_slots(slot, slot);
+        self.last_blockhash = new_bank.last_blockhash();
+    }
+
     /// Force the working bank ahead to a new slot
     pub fn warp_to_slot(&mut self, warp_slot: Slot) -> Result<(), ProgramTestError> {
         let mut bank_forks = self.bank_forks.write().unwrap();
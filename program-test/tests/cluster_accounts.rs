@@ -0,0 +1,50 @@
+use {
+    solana_program_test::ProgramTest,
+    solana_sdk::{
+        account_utils::StateMut, bpf_loader_upgradeable::UpgradeableLoaderState, pubkey::Pubkey,
+    },
+    std::str::FromStr,
+};
+
+#[tokio::test]
+async fn add_accounts_from_fixtures() {
+    let program_id = Pubkey::from_str("8PwfqtRExJqsJfcLzDxqNmgGUevzmRAUW5CrAGPo2yRD").unwrap();
+    let programdata_address =
+        Pubkey::from_str("GJi4xZeMYHsDa66CFBDvuQqPYgdAiFP6xaJnSshCgRkk").unwrap();
+
+    // Nothing listens on this port, so the accounts are read from `tests/fixtures`
+    let mut program_test = ProgramTest::default();
+    program_test
+        .add_accounts_from_cluster("http://127.0.0.1:1", &[program_id])
+        .await;
+    let mut context = program_test.start_with_context().await;
+
+    let program_account = context
+        .banks_client
+        .get_account(program_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(program_account.executable);
+    assert_eq!(
+        program_account.state(),
+        Ok(UpgradeableLoaderState::Program {
+            programdata_address
+        })
+    );
+
+    // The programdata account is cloned along with the program
+    let programdata_account = context
+        .banks_client
+        .get_account(programdata_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        programdata_account.state(),
+        Ok(UpgradeableLoaderState::ProgramData {
+            slot: 42,
+            upgrade_authority_address: None,
+        })
+    );
+}
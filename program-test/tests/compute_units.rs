@@ -0,0 +1,64 @@
+use {
+    solana_program_test::{
+        BanksTransactionResultWithMetadata, ProgramTest, ProgramTestTransactionMetadataExt,
+    },
+    solana_sdk::{pubkey::Pubkey, signature::Signer, system_instruction, transaction::Transaction},
+};
+
+#[tokio::test]
+async fn assert_compute_units() {
+    let mut context = ProgramTest::default().start_with_context().await;
+    let instructions = vec![
+        system_instruction::transfer(&context.payer.pubkey(), &Pubkey::new_unique(), 1_000_000),
+        system_instruction::transfer(&context.payer.pubkey(), &Pubkey::new_unique(), 1_000_000),
+    ];
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let BanksTransactionResultWithMetadata { result, metadata } = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+    let metadata = metadata.unwrap();
+    assert_eq!(metadata.instructions_compute_units_consumed.len(), 2);
+    assert_eq!(
+        metadata.compute_units_consumed,
+        metadata
+            .instructions_compute_units_consumed
+            .iter()
+            .sum::<u64>()
+    );
+    metadata.assert_max_cu(0, 1_000);
+    metadata.assert_max_cu(1, 1_000);
+    metadata.assert_max_total_cu(2_000);
+    assert_eq!(metadata.instruction_compute_units(2), None);
+}
+
+#[tokio::test]
+#[should_panic(expected = "Instruction 1 was not executed")]
+async fn assert_compute_units_of_missing_instruction() {
+    let mut context = ProgramTest::default().start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            &Pubkey::new_unique(),
+            1_000_000,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let BanksTransactionResultWithMetadata { metadata, .. } = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    metadata.unwrap().assert_max_cu(1, 1_000);
+}
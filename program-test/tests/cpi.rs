@@ -1,5 +1,5 @@
 use {
-    solana_program_test::{processor, ProgramTest},
+    solana_program_test::{processor, BanksTransactionResultWithMetadata, ProgramTest},
     solana_sdk::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
@@ -77,3 +77,64 @@ async fn cpi() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn cpi_invocations() {
+    let invoker_program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "invoker",
+        invoker_program_id,
+        processor!(invoker_process_instruction),
+    );
+    let invoked_program_id = Pubkey::new_unique();
+    program_test.add_program(
+        "invoked",
+        invoked_program_id,
+        processor!(invoked_process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let instructions = vec![Instruction::new_with_bincode(
+        invoker_program_id,
+        &[0],
+        vec![AccountMeta::new_readonly(invoked_program_id, false)],
+    )];
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let BanksTransactionResultWithMetadata { result, metadata } = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+    let invocations = metadata.unwrap().invocations;
+    assert_eq!(invocations.len(), 1);
+
+    let invoker = &invocations[0];
+    assert_eq!(invoker.program_id, invoker_program_id);
+    assert_eq!(invoker.instruction_data, instructions[0].data);
+    assert_eq!(invoker.stack_height, 1);
+    assert_eq!(invoker.result, Ok(()));
+    assert!(invoker
+        .logs
+        .contains(&"Program log: Processing invoker instruction after CPI".to_string()));
+    assert_eq!(invoker.inner_invocations.len(), 1);
+
+    let invoked = &invoker.inner_invocations[0];
+    assert_eq!(invoked.program_id, invoked_program_id);
+    assert_eq!(invoked.stack_height, 2);
+    assert_eq!(invoked.result, Ok(()));
+    assert!(invoked
+        .logs
+        .contains(&"Program log: Processing invoked instruction".to_string()));
+    assert!(!invoker
+        .logs
+        .contains(&"Program log: Processing invoked instruction".to_string()));
+    assert!(invoked.inner_invocations.is_empty());
+}
@@ -1,15 +1,18 @@
 use {
+    solana_banks_client::BanksTransactionResultWithSimulation,
     solana_program_test::{processor, ProgramTest},
     solana_sdk::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
-        instruction::{AccountMeta, Instruction},
+        instruction::{AccountMeta, CompiledInstruction, Instruction},
         msg,
         program::{get_return_data, invoke, set_return_data},
         pubkey::Pubkey,
         signature::Signer,
         transaction::Transaction,
+        transaction_context::TransactionReturnData,
     },
+    solana_transaction_status::InnerInstructions,
     std::str::from_utf8,
 };
 
@@ -85,3 +88,72 @@ async fn return_data() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn simulate_return_data() {
+    let get_return_data_program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "get_return_data",
+        get_return_data_program_id,
+        processor!(get_return_data_process_instruction),
+    );
+    let set_return_data_program_id = Pubkey::new_unique();
+    program_test.add_program(
+        "set_return_data",
+        set_return_data_program_id,
+        processor!(set_return_data_process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let data = vec![240, 159, 166, 150];
+    let instructions = vec![Instruction {
+        program_id: get_return_data_program_id,
+        accounts: vec![AccountMeta::new_readonly(set_return_data_program_id, false)],
+        data: data.clone(),
+    }];
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let set_return_data_program_index = transaction
+        .message
+        .account_keys
+        .iter()
+        .position(|key| key == &set_return_data_program_id)
+        .unwrap();
+
+    let BanksTransactionResultWithSimulation {
+        result,
+        simulation_details,
+    } = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result, Some(Ok(())));
+    let simulation_details = simulation_details.unwrap();
+    assert!(simulation_details.logs.contains(
+        &"Program log: Processing invoked instruction after set_return_data".to_string()
+    ));
+    assert_eq!(
+        simulation_details.return_data,
+        Some(TransactionReturnData {
+            program_id: set_return_data_program_id,
+            data: data.clone(),
+        })
+    );
+    assert_eq!(
+        simulation_details.inner_instructions,
+        Some(vec![InnerInstructions {
+            index: 0,
+            instructions: vec![CompiledInstruction {
+                program_id_index: set_return_data_program_index as u8,
+                accounts: vec![],
+                data,
+            }],
+        }])
+    );
+}
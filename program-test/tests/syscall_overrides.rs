@@ -0,0 +1,82 @@
+use {
+    solana_program_test::{processor, BanksTransactionResultWithMetadata, ProgramTest},
+    solana_sdk::{
+        account_info::{next_account_info, AccountInfo},
+        clock::Clock,
+        entrypoint::ProgramResult,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        log::sol_log_compute_units,
+        msg,
+        pubkey::Pubkey,
+        signature::Signer,
+        slot_hashes::SlotHashes,
+        sysvar::{self, Sysvar},
+        transaction::Transaction,
+    },
+};
+
+fn overridden_syscalls_process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let clock = Clock::get()?;
+    assert_eq!(clock.slot, 1_000);
+    assert_eq!(clock.unix_timestamp, 1_650_000_000);
+
+    let slot_hashes_info = next_account_info(&mut accounts.iter())?;
+    let slot_hashes = SlotHashes::from_account_info(slot_hashes_info)?;
+    assert_eq!(
+        slot_hashes.slot_hashes(),
+        &[(999, Hash::new_from_array([7; 32]))]
+    );
+
+    sol_log_compute_units();
+    msg!("overridden syscalls checked");
+    Ok(())
+}
+
+#[tokio::test]
+async fn override_syscalls() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "overridden_syscalls",
+        program_id,
+        processor!(overridden_syscalls_process_instruction),
+    );
+    program_test.override_sysvar(&Clock {
+        slot: 1_000,
+        unix_timestamp: 1_650_000_000,
+        ..Clock::default()
+    });
+    program_test.override_sysvar(&SlotHashes::new(&[(999, Hash::new_from_array([7; 32]))]));
+    program_test.override_remaining_compute_units(12_345);
+
+    let mut context = program_test.start_with_context().await;
+    let instructions = vec![Instruction::new_with_bincode(
+        program_id,
+        &(),
+        vec![AccountMeta::new_readonly(sysvar::slot_hashes::id(), false)],
+    )];
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let BanksTransactionResultWithMetadata { result, metadata } = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+    let log_messages = metadata.unwrap().log_messages;
+    assert!(log_messages.contains(&"Program consumption: 12345 units remaining".to_string()));
+    assert!(log_messages.contains(&"Program log: overridden syscalls checked".to_string()));
+
+    // The bank itself is unaffected
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert_ne!(clock.unix_timestamp, 1_650_000_000);
+}
@@ -53,3 +53,29 @@ async fn get_sysvar() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn set_rent() {
+    let program_test = ProgramTest::default();
+    let mut context = program_test.start_with_context().await;
+
+    let rent = Rent {
+        lamports_per_byte_year: Rent::default().lamports_per_byte_year * 2,
+        ..Rent::default()
+    };
+    context.set_sysvar(&rent);
+    assert_eq!(context.banks_client.get_rent().await.unwrap(), rent);
+
+    // The working bank moved to a new slot, with a new blockhash to send transactions with
+    let transaction = Transaction::new_signed_with_payer(
+        &[],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
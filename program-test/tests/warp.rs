@@ -30,7 +30,7 @@ use {
         vote_instruction,
         vote_state::{VoteInit, VoteState},
     },
-    std::convert::TryInto,
+    std::{convert::TryInto, time::Duration},
 };
 
 // Use a big number to be sure that we get the right error
@@ -391,3 +391,119 @@ async fn get_blockhash_post_warp() {
     tx.sign(&[&context.payer], new_blockhash);
     context.banks_client.process_transaction(tx).await.unwrap();
 }
+
+#[tokio::test]
+async fn stake_history_from_warp_to_epoch() {
+    let program_test = ProgramTest::default();
+    let mut context = program_test.start_with_context().await;
+    let vote_address = setup_vote(&mut context).await;
+    let user_keypair = Keypair::new();
+    setup_stake(
+        &mut context,
+        &user_keypair,
+        &vote_address,
+        1_000_000_000_000,
+    )
+    .await;
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let warp_epoch = clock.epoch + 3;
+    context.warp_to_epoch(warp_epoch).unwrap();
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert_eq!(clock.epoch, warp_epoch);
+    assert_eq!(
+        clock.slot,
+        context
+            .genesis_config()
+            .epoch_schedule
+            .get_first_slot_in_epoch(warp_epoch)
+    );
+
+    // Every epoch boundary was crossed, so each epoch has its stake history entry
+    let stake_history = context
+        .banks_client
+        .get_sysvar::<StakeHistory>()
+        .await
+        .unwrap();
+    for epoch in warp_epoch - 3..warp_epoch {
+        assert!(stake_history.get(&epoch).is_some());
+    }
+
+    // Warping to the current epoch or back is not allowed
+    assert_eq!(
+        context.warp_to_epoch(warp_epoch).unwrap_err(),
+        ProgramTestError::InvalidWarpSlot,
+    );
+}
+
+#[tokio::test]
+async fn advance_clock() {
+    let program_test = ProgramTest::default();
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(10).unwrap();
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let one_day = 24 * 60 * 60;
+    context.advance_clock_by(Duration::from_secs(one_day));
+    let advanced_clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert_eq!(
+        advanced_clock.unix_timestamp,
+        clock.unix_timestamp + one_day as i64
+    );
+
+    // The clock doesn't go back in later banks
+    context.warp_to_slot(20).unwrap();
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert!(clock.unix_timestamp >= advanced_clock.unix_timestamp);
+}
+
+#[tokio::test]
+async fn stake_rewards_from_warp_to_epoch_with_vote_credits() {
+    let program_test = ProgramTest::default();
+    let mut context = program_test.start_with_context().await;
+    let vote_address = setup_vote(&mut context).await;
+    let user_keypair = Keypair::new();
+    let stake_lamports = 1_000_000_000_000;
+    let stake_address =
+        setup_stake(&mut context, &user_keypair, &vote_address, stake_lamports).await;
+
+    // The stake activates at the first boundary and earns rewards at the following ones
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let warp_epoch = clock.epoch + 3;
+    let rewards = context
+        .warp_to_epoch_with_vote_credits(warp_epoch, 100)
+        .unwrap();
+    assert_eq!(rewards.len(), 3);
+    assert!(!rewards[0]
+        .iter()
+        .any(|(address, _)| *address == stake_address));
+
+    let mut expected_lamports = stake_lamports;
+    for epoch_rewards in &rewards[1..] {
+        let (_, reward) = epoch_rewards
+            .iter()
+            .find(|(address, _)| *address == stake_address)
+            .expect("stake rewarded");
+        assert!(reward.lamports > 0);
+        expected_lamports += reward.lamports as u64;
+        assert_eq!(reward.post_balance, expected_lamports);
+    }
+
+    let account = context
+        .banks_client
+        .get_account(stake_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.lamports, expected_lamports);
+    let stake_state: StakeState = deserialize(&account.data).unwrap();
+    assert_eq!(stake_state.stake().unwrap().credits_observed, 300);
+
+    assert_eq!(
+        context
+            .warp_to_epoch_with_vote_credits(warp_epoch, 100)
+            .unwrap_err(),
+        ProgramTestError::InvalidWarpSlot,
+    );
+}
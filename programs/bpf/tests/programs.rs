@@ -385,6 +385,10 @@ fn execute_transactions(
                         log_messages,
                         inner_instructions,
                         durable_nonce_fee,
+                        return_data,
+                        executed_units,
+                        instructions_executed_units: _,
+                        invocations: _,
                     } = details;
 
                     let lamports_per_signature = match durable_nonce_fee {
@@ -425,6 +429,8 @@ fn execute_transactions(
                         log_messages,
                         rewards: None,
                         loaded_addresses: LoadedAddresses::default(),
+                        return_data,
+                        compute_units_consumed: Some(executed_units),
                     };
 
                     Ok(ConfirmedTransactionWithStatusMeta {
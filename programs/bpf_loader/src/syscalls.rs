@@ -23,10 +23,11 @@ use {
         entrypoint::{BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
         feature_set::{
             self, add_get_processed_sibling_instruction_syscall, blake3_syscall_enabled,
-            disable_fees_sysvar, do_support_realloc, fixed_memcpy_nonoverlapping_check,
-            libsecp256k1_0_5_upgrade_enabled, prevent_calling_precompiles_as_programs,
-            return_data_syscall_enabled, secp256k1_recover_syscall_enabled,
-            sol_log_data_syscall_enabled, update_syscall_base_costs,
+            curve25519_syscall_enabled, disable_fees_sysvar, do_support_realloc,
+            fixed_memcpy_nonoverlapping_check, libsecp256k1_0_5_upgrade_enabled,
+            prevent_calling_precompiles_as_programs, return_data_syscall_enabled,
+            secp256k1_recover_syscall_enabled, sol_log_data_syscall_enabled,
+            update_syscall_base_costs,
         },
         hash::{Hasher, HASH_BYTES},
         instruction::{
@@ -176,6 +177,22 @@ pub fn register_syscalls(
         )?;
     }
 
+    if invoke_context
+        .feature_set
+        .is_active(&curve25519_syscall_enabled::id())
+    {
+        syscall_registry.register_syscall_by_name(
+            b"sol_curve_validate_point",
+            SyscallCurvePointValidation::call,
+        )?;
+        syscall_registry
+            .register_syscall_by_name(b"sol_curve_group_op", SyscallCurveGroupOps::call)?;
+        syscall_registry.register_syscall_by_name(
+            b"sol_curve_multiscalar_mul",
+            SyscallCurveMultiscalarMultiplication::call,
+        )?;
+    }
+
     syscall_registry
         .register_syscall_by_name(b"sol_get_clock_sysvar", SyscallGetClockSysvar::call)?;
     syscall_registry.register_syscall_by_name(
@@ -286,6 +303,9 @@ pub fn bind_syscall_context_objects<'a, 'b>(
     let add_get_processed_sibling_instruction_syscall = invoke_context
         .feature_set
         .is_active(&add_get_processed_sibling_instruction_syscall::id());
+    let is_curve25519_syscall_active = invoke_context
+        .feature_set
+        .is_active(&curve25519_syscall_enabled::id());
 
     let loader_id = invoke_context
         .transaction_context
@@ -416,6 +436,28 @@ pub fn bind_syscall_context_objects<'a, 'b>(
         }),
     );
 
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_curve25519_syscall_active,
+        Box::new(SyscallCurvePointValidation {
+            invoke_context: invoke_context.clone(),
+        }),
+    );
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_curve25519_syscall_active,
+        Box::new(SyscallCurveGroupOps {
+            invoke_context: invoke_context.clone(),
+        }),
+    );
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_curve25519_syscall_active,
+        Box::new(SyscallCurveMultiscalarMultiplication {
+            invoke_context: invoke_context.clone(),
+        }),
+    );
+
     vm.bind_syscall_context_object(
         Box::new(SyscallGetClockSysvar {
             invoke_context: invoke_context.clone(),
@@ -1907,6 +1949,231 @@ impl<'a, 'b> SyscallObject<BpfError> for SyscallZkTokenElgamalOpWithScalar<'a, '
     }
 }
 
+pub struct SyscallCurvePointValidation<'a, 'b> {
+    invoke_context: Rc<RefCell<&'a mut InvokeContext<'b>>>,
+}
+
+impl<'a, 'b> SyscallObject<BpfError> for SyscallCurvePointValidation<'a, 'b> {
+    fn call(
+        &mut self,
+        curve_id: u64,
+        point_addr: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        use solana_zk_token_sdk::{curve25519, zk_token_elgamal::pod};
+
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        if curve_id != curve25519::CURVE25519_RISTRETTO {
+            *result = Ok(1);
+            return;
+        }
+
+        let cost = invoke_context
+            .get_compute_budget()
+            .curve25519_ristretto_validate_point_cost;
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        let loader_id = question_mark!(
+            invoke_context
+                .transaction_context
+                .get_loader_key()
+                .map_err(SyscallError::InstructionError),
+            result
+        );
+
+        let point = question_mark!(
+            translate_type::<pod::CompressedRistretto>(memory_mapping, point_addr, &loader_id),
+            result
+        );
+
+        if curve25519::validate_ristretto(point) {
+            *result = Ok(0);
+        } else {
+            *result = Ok(1);
+        }
+    }
+}
+
+pub struct SyscallCurveGroupOps<'a, 'b> {
+    invoke_context: Rc<RefCell<&'a mut InvokeContext<'b>>>,
+}
+
+impl<'a, 'b> SyscallObject<BpfError> for SyscallCurveGroupOps<'a, 'b> {
+    fn call(
+        &mut self,
+        curve_id: u64,
+        op: u64,
+        left_input_addr: u64,
+        right_input_addr: u64,
+        result_point_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        use solana_zk_token_sdk::{curve25519, zk_token_elgamal::pod};
+
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        if curve_id != curve25519::CURVE25519_RISTRETTO {
+            *result = Ok(1);
+            return;
+        }
+
+        let compute_budget = invoke_context.get_compute_budget();
+        let cost = match op {
+            curve25519::OP_ADD => compute_budget.curve25519_ristretto_add_cost,
+            curve25519::OP_SUB => compute_budget.curve25519_ristretto_subtract_cost,
+            curve25519::OP_MUL => compute_budget.curve25519_ristretto_multiply_cost,
+            _ => {
+                *result = Ok(1);
+                return;
+            }
+        };
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        let loader_id = question_mark!(
+            invoke_context
+                .transaction_context
+                .get_loader_key()
+                .map_err(SyscallError::InstructionError),
+            result
+        );
+
+        let right_point = question_mark!(
+            translate_type::<pod::CompressedRistretto>(
+                memory_mapping,
+                right_input_addr,
+                &loader_id
+            ),
+            result
+        );
+
+        let result_point = if op == curve25519::OP_MUL {
+            let scalar = question_mark!(
+                translate_type::<pod::Scalar>(memory_mapping, left_input_addr, &loader_id),
+                result
+            );
+            curve25519::multiply_ristretto(scalar, right_point)
+        } else {
+            let left_point = question_mark!(
+                translate_type::<pod::CompressedRistretto>(
+                    memory_mapping,
+                    left_input_addr,
+                    &loader_id
+                ),
+                result
+            );
+            if op == curve25519::OP_ADD {
+                curve25519::add_ristretto(left_point, right_point)
+            } else {
+                curve25519::subtract_ristretto(left_point, right_point)
+            }
+        };
+
+        if let Some(result_point) = result_point {
+            *question_mark!(
+                translate_type_mut::<pod::CompressedRistretto>(
+                    memory_mapping,
+                    result_point_addr,
+                    &loader_id,
+                ),
+                result
+            ) = result_point;
+            *result = Ok(0);
+        } else {
+            *result = Ok(1);
+        }
+    }
+}
+
+pub struct SyscallCurveMultiscalarMultiplication<'a, 'b> {
+    invoke_context: Rc<RefCell<&'a mut InvokeContext<'b>>>,
+}
+
+impl<'a, 'b> SyscallObject<BpfError> for SyscallCurveMultiscalarMultiplication<'a, 'b> {
+    fn call(
+        &mut self,
+        curve_id: u64,
+        scalars_addr: u64,
+        points_addr: u64,
+        points_len: u64,
+        result_point_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        use solana_zk_token_sdk::{curve25519, zk_token_elgamal::pod};
+
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        if curve_id != curve25519::CURVE25519_RISTRETTO {
+            *result = Ok(1);
+            return;
+        }
+
+        let compute_budget = invoke_context.get_compute_budget();
+        let cost = compute_budget
+            .curve25519_ristretto_msm_incremental_cost
+            .saturating_mul(points_len.saturating_sub(1))
+            .saturating_add(compute_budget.curve25519_ristretto_msm_base_cost);
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        let loader_id = question_mark!(
+            invoke_context
+                .transaction_context
+                .get_loader_key()
+                .map_err(SyscallError::InstructionError),
+            result
+        );
+
+        let scalars = question_mark!(
+            translate_slice::<pod::Scalar>(memory_mapping, scalars_addr, points_len, &loader_id),
+            result
+        );
+        let points = question_mark!(
+            translate_slice::<pod::CompressedRistretto>(
+                memory_mapping,
+                points_addr,
+                points_len,
+                &loader_id
+            ),
+            result
+        );
+
+        if let Some(result_point) = curve25519::multiscalar_multiply_ristretto(scalars, points) {
+            *question_mark!(
+                translate_type_mut::<pod::CompressedRistretto>(
+                    memory_mapping,
+                    result_point_addr,
+                    &loader_id,
+                ),
+                result
+            ) = result_point;
+            *result = Ok(0);
+        } else {
+            *result = Ok(1);
+        }
+    }
+}
+
 // Blake3
 pub struct SyscallBlake3<'a, 'b> {
     invoke_context: Rc<RefCell<&'a mut InvokeContext<'b>>>,
@@ -1,18 +1,33 @@
+//! The ZkToken Proof program
+//!
+//! The program verifies the zero-knowledge proofs of the confidential token extension so that the
+//! token program does not need to re-implement their verification: each `Verify*` instruction
+//! runs `Verifiable::verify` on its proof data and fails the transaction if the proof is invalid.
+//! A token instruction then checks, through instruction introspection, that the proof
+//! instruction it depends on is present in the same transaction.
 #![forbid(unsafe_code)]
 
 use {
-    bytemuck::Pod,
+    num_traits::FromPrimitive,
     solana_program_runtime::{ic_msg, invoke_context::InvokeContext},
-    solana_sdk::instruction::{InstructionError, TRANSACTION_LEVEL_STACK_HEIGHT},
-    solana_zk_token_sdk::zk_token_proof_instruction::*,
+    solana_sdk::{
+        account::{ReadableAccount, WritableAccount},
+        instruction::{InstructionError, TRANSACTION_LEVEL_STACK_HEIGHT},
+        keyed_account::keyed_account_at_index,
+        pubkey::Pubkey,
+    },
+    solana_zk_token_sdk::{
+        zk_token_proof_instruction::*, zk_token_proof_program::id,
+        zk_token_proof_state::ProofContextStateHeader,
+    },
     std::result::Result,
 };
 
-fn verify<T: Pod + Verifiable>(
-    input: &[u8],
-    invoke_context: &mut InvokeContext,
+fn verify<T: ProofData + Verifiable>(
+    proof_data: &[u8],
+    invoke_context: &InvokeContext,
 ) -> Result<(), InstructionError> {
-    let proof = ProofInstruction::decode_data::<T>(input).ok_or_else(|| {
+    let proof = T::from_bytes_unaligned(proof_data).ok_or_else(|| {
         ic_msg!(invoke_context, "invalid proof data");
         InstructionError::InvalidInstructionData
     })?;
@@ -23,39 +38,235 @@ fn verify<T: Pod + Verifiable>(
     })
 }
 
-pub fn process_instruction(
-    _first_instruction_account: usize,
-    input: &[u8],
-    invoke_context: &mut InvokeContext,
+fn verify_proof(
+    proof_type: ProofInstruction,
+    proof_data: &[u8],
+    invoke_context: &InvokeContext,
 ) -> Result<(), InstructionError> {
-    if invoke_context.get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
-        // Not supported as an inner instruction
-        return Err(InstructionError::UnsupportedProgramId);
-    }
-
     // Consume compute units since proof verification is an expensive operation
     {
         let compute_meter = invoke_context.get_compute_meter();
         // TODO: Tune the number of units consumed.  The current value is just a rough estimate
-        compute_meter.borrow_mut().consume(100_000)?;
+        compute_meter
+            .borrow_mut()
+            .consume(VERIFY_PROOF_COMPUTE_UNITS)?;
     }
 
-    match ProofInstruction::decode_type(input).ok_or(InstructionError::InvalidInstructionData)? {
+    match proof_type {
         ProofInstruction::VerifyCloseAccount => {
             ic_msg!(invoke_context, "VerifyCloseAccount");
-            verify::<CloseAccountData>(input, invoke_context)
+            verify::<CloseAccountData>(proof_data, invoke_context)
         }
         ProofInstruction::VerifyWithdraw => {
             ic_msg!(invoke_context, "VerifyWithdraw");
-            verify::<WithdrawData>(input, invoke_context)
+            verify::<WithdrawData>(proof_data, invoke_context)
         }
         ProofInstruction::VerifyTransfer => {
             ic_msg!(invoke_context, "VerifyTransfer");
-            verify::<TransferData>(input, invoke_context)
+            verify::<TransferData>(proof_data, invoke_context)
         }
         ProofInstruction::VerifyTransferWithFee => {
             ic_msg!(invoke_context, "VerifyTransferWithFee");
-            verify::<TransferWithFeeData>(input, invoke_context)
+            verify::<TransferWithFeeData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyWithdrawWithheldTokens => {
+            ic_msg!(invoke_context, "VerifyWithdrawWithheldTokens");
+            verify::<WithdrawWithheldTokensData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyMint => {
+            ic_msg!(invoke_context, "VerifyMint");
+            verify::<MintData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyBurn => {
+            ic_msg!(invoke_context, "VerifyBurn");
+            verify::<BurnData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyRotateAuditor => {
+            ic_msg!(invoke_context, "VerifyRotateAuditor");
+            verify::<RotateAuditorData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyTransferBatch => {
+            ic_msg!(invoke_context, "VerifyTransferBatch");
+            verify::<TransferBatchData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyPubkeyValidity => {
+            ic_msg!(invoke_context, "VerifyPubkeyValidity");
+            verify::<PubkeyValidityData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyCiphertextCiphertextEquality => {
+            ic_msg!(invoke_context, "VerifyCiphertextCiphertextEquality");
+            verify::<CiphertextCiphertextEqualityData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyCiphertextCommitmentEquality => {
+            ic_msg!(invoke_context, "VerifyCiphertextCommitmentEquality");
+            verify::<CiphertextCommitmentEqualityData>(proof_data, invoke_context)
+        }
+        ProofInstruction::VerifyFeeParametersUpdate => {
+            ic_msg!(invoke_context, "VerifyFeeParametersUpdate");
+            verify::<FeeParametersUpdateData>(proof_data, invoke_context)
+        }
+        ProofInstruction::WriteProofChunk
+        | ProofInstruction::VerifyProofContext
+        | ProofInstruction::CloseProofContext => Err(InstructionError::InvalidInstructionData),
+    }
+}
+
+fn process_write_proof_chunk(
+    first_instruction_account: usize,
+    input: &[u8],
+    invoke_context: &InvokeContext,
+) -> Result<(), InstructionError> {
+    let (chunk_header, chunk) =
+        ProofInstruction::decode_chunk(input).ok_or(InstructionError::InvalidInstructionData)?;
+    let proof_type = ProofInstruction::from_u8(chunk_header.proof_type)
+        .ok_or(InstructionError::InvalidInstructionData)?;
+    let proof_data_len = proof_type
+        .proof_data_len()
+        .ok_or(InstructionError::InvalidInstructionData)?;
+
+    let keyed_accounts = invoke_context.get_keyed_accounts()?;
+    let context_state_account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+    let authority_account = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+
+    let authority = *authority_account
+        .signer_key()
+        .ok_or(InstructionError::MissingRequiredSignature)?;
+    if context_state_account.owner()? != id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+
+    let mut context_state = context_state_account.try_account_ref_mut()?;
+    let data = context_state.data_as_mut_slice();
+    if data.len() != ProofContextStateHeader::LEN + proof_data_len {
+        ic_msg!(invoke_context, "context-state account has the wrong size");
+        return Err(InstructionError::InvalidAccountData);
+    }
+
+    let (header, proof_data) = data.split_at_mut(ProofContextStateHeader::LEN);
+    let header: &mut ProofContextStateHeader =
+        bytemuck::try_from_bytes_mut(header).map_err(|_| InstructionError::InvalidAccountData)?;
+
+    if header.context_state_authority == Pubkey::default() {
+        header.context_state_authority = authority;
+        header.proof_type = chunk_header.proof_type;
+    } else if header.context_state_authority != authority {
+        return Err(InstructionError::IncorrectAuthority);
+    } else if header.proof_type != chunk_header.proof_type {
+        ic_msg!(
+            invoke_context,
+            "proof type does not match the context state"
+        );
+        return Err(InstructionError::InvalidInstructionData);
+    }
+    header.verified = 0;
+
+    let offset = u32::from_le_bytes(chunk_header.offset) as usize;
+    offset
+        .checked_add(chunk.len())
+        .and_then(|end| proof_data.get_mut(offset..end))
+        .ok_or(InstructionError::InvalidInstructionData)?
+        .copy_from_slice(chunk);
+
+    Ok(())
+}
+
+fn process_verify_proof_context(
+    first_instruction_account: usize,
+    invoke_context: &InvokeContext,
+) -> Result<(), InstructionError> {
+    let keyed_accounts = invoke_context.get_keyed_accounts()?;
+    let context_state_account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+    if context_state_account.owner()? != id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+
+    let mut context_state = context_state_account.try_account_ref_mut()?;
+    let data = context_state.data_as_mut_slice();
+    if data.len() < ProofContextStateHeader::LEN {
+        return Err(InstructionError::InvalidAccountData);
+    }
+
+    let (header, proof_data) = data.split_at_mut(ProofContextStateHeader::LEN);
+    let header: &mut ProofContextStateHeader =
+        bytemuck::try_from_bytes_mut(header).map_err(|_| InstructionError::InvalidAccountData)?;
+    if header.context_state_authority == Pubkey::default() {
+        return Err(InstructionError::UninitializedAccount);
+    }
+
+    let proof_type =
+        ProofInstruction::from_u8(header.proof_type).ok_or(InstructionError::InvalidAccountData)?;
+    verify_proof(proof_type, proof_data, invoke_context)?;
+    header.verified = 1;
+
+    Ok(())
+}
+
+fn process_close_proof_context(
+    first_instruction_account: usize,
+    invoke_context: &InvokeContext,
+) -> Result<(), InstructionError> {
+    let keyed_accounts = invoke_context.get_keyed_accounts()?;
+    let context_state_account = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
+    let destination_account =
+        keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+    let authority_account = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+
+    let authority = *authority_account
+        .signer_key()
+        .ok_or(InstructionError::MissingRequiredSignature)?;
+    if context_state_account.owner()? != id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    if context_state_account.unsigned_key() == destination_account.unsigned_key() {
+        return Err(InstructionError::InvalidArgument);
+    }
+
+    let mut context_state = context_state_account.try_account_ref_mut()?;
+    {
+        let header = context_state
+            .data()
+            .get(..ProofContextStateHeader::LEN)
+            .and_then(|header| bytemuck::try_from_bytes::<ProofContextStateHeader>(header).ok())
+            .ok_or(InstructionError::InvalidAccountData)?;
+        if header.context_state_authority != authority {
+            return Err(InstructionError::IncorrectAuthority);
+        }
+    }
+
+    destination_account
+        .try_account_ref_mut()?
+        .checked_add_lamports(context_state.lamports())?;
+    context_state.set_lamports(0);
+    context_state.data_as_mut_slice().fill(0);
+
+    Ok(())
+}
+
+pub fn process_instruction(
+    first_instruction_account: usize,
+    input: &[u8],
+    invoke_context: &mut InvokeContext,
+) -> Result<(), InstructionError> {
+    if invoke_context.get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        // Not supported as an inner instruction
+        return Err(InstructionError::UnsupportedProgramId);
+    }
+
+    let proof_type =
+        ProofInstruction::decode_type(input).ok_or(InstructionError::InvalidInstructionData)?;
+    match proof_type {
+        ProofInstruction::WriteProofChunk => {
+            ic_msg!(invoke_context, "WriteProofChunk");
+            process_write_proof_chunk(first_instruction_account, input, invoke_context)
+        }
+        ProofInstruction::VerifyProofContext => {
+            ic_msg!(invoke_context, "VerifyProofContext");
+            process_verify_proof_context(first_instruction_account, invoke_context)
+        }
+        ProofInstruction::CloseProofContext => {
+            ic_msg!(invoke_context, "CloseProofContext");
+            process_close_proof_context(first_instruction_account, invoke_context)
         }
+        _ => verify_proof(proof_type, &input[1..], invoke_context),
     }
 }
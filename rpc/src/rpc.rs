@@ -3,7 +3,7 @@
 use {
     crate::{
         max_slots::MaxSlots, optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_health::*,
+        parsed_token_accounts::*, rpc_health::*, rpc_method_metrics::RpcMethodMetricsConfig,
     },
     bincode::{config::Options, serialize},
     crossbeam_channel::{unbounded, Receiver, Sender},
@@ -33,7 +33,7 @@ use {
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
     solana_ledger::{
         blockstore::{Blockstore, SignatureInfosForAddress},
-        blockstore_db::BlockstoreError,
+        blockstore_db::{BlockstoreError, IteratorDirection},
         get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
     },
@@ -77,13 +77,13 @@ use {
         send_transaction_service::{SendTransactionService, TransactionInfo},
         tpu_info::NullTpuInfo,
     },
-    solana_storage_bigtable::Error as StorageError,
+    solana_storage_bigtable::{Error as StorageError, LedgerStorage, LedgerStorageConfig},
     solana_streamer::socket::SocketAddrSpace,
     solana_transaction_status::{
-        ConfirmedTransactionStatusWithSignature, Encodable,
+        map_inner_instructions, ConfirmedTransactionStatusWithSignature, Encodable,
         EncodedConfirmedTransactionWithStatusMeta, Reward, RewardType,
-        TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
-        VersionedConfirmedBlock,
+        TransactionConfirmationStatus, TransactionStatus, UiConfirmedBlock, UiInnerInstructions,
+        UiTransactionEncoding, VersionedConfirmedBlock,
     },
     solana_vote_program::vote_state::{VoteState, MAX_LOCKOUT_HISTORY},
     spl_token::{
@@ -143,15 +143,21 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_cpi_and_log_storage: bool,
+    pub enable_program_signatures_index: bool,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub enable_bigtable_ledger_storage: bool,
     pub enable_bigtable_ledger_upload: bool,
+    /// The long-term ledger storage backend used when `enable_bigtable_ledger_storage` or
+    /// `enable_bigtable_ledger_upload` is set
+    pub ledger_storage: LedgerStorageConfig,
     pub max_multiple_accounts: Option<usize>,
     pub account_indexes: AccountSecondaryIndexes,
     pub rpc_threads: usize,
     pub rpc_niceness_adj: i8,
     pub rpc_bigtable_timeout: Option<Duration>,
+    /// Records per-method latency and response size histograms if set
+    pub method_metrics: Option<RpcMethodMetricsConfig>,
     pub full_api: bool,
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
@@ -179,7 +185,7 @@ pub struct JsonRpcRequestProcessor {
     cluster_info: Arc<ClusterInfo>,
     genesis_hash: Hash,
     transaction_sender: Arc<Mutex<Sender<TransactionInfo>>>,
-    bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+    bigtable_ledger_storage: Option<Arc<dyn LedgerStorage>>,
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
     max_slots: Arc<MaxSlots>,
@@ -268,7 +274,7 @@ impl JsonRpcRequestProcessor {
         health: Arc<RpcHealth>,
         cluster_info: Arc<ClusterInfo>,
         genesis_hash: Hash,
-        bigtable_ledger_storage: Option<solana_storage_bigtable::LedgerStorage>,
+        bigtable_ledger_storage: Option<Arc<dyn LedgerStorage>>,
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
@@ -1479,6 +1485,61 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    pub fn get_signatures_for_address_in_range(
+        &self,
+        address: Pubkey,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+        limit: usize,
+        order: RpcSignatureOrder,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        if !self.config.enable_rpc_transaction_history {
+            return Err(RpcCustomError::TransactionHistoryNotAvailable.into());
+        }
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let end_slot = end_slot.map_or(highest_confirmed_root, |end_slot| {
+            min(end_slot, highest_confirmed_root)
+        });
+        if start_slot > end_slot {
+            return Ok(vec![]);
+        }
+        let direction = match order {
+            RpcSignatureOrder::Ascending => IteratorDirection::Forward,
+            RpcSignatureOrder::Descending => IteratorDirection::Reverse,
+        };
+
+        let to_rpc_error = |err: BlockstoreError| Error::invalid_params(format!("{}", err));
+        self.blockstore
+            .get_address_signatures_in_range(address, start_slot, end_slot, limit, direction)
+            .map_err(to_rpc_error)?
+            .into_iter()
+            .map(|(slot, signature)| {
+                let err = self
+                    .blockstore
+                    .get_transaction_status(signature, &[])
+                    .map_err(to_rpc_error)?
+                    .and_then(|(_slot, status)| status.status.err());
+                let memo = self
+                    .blockstore
+                    .read_transaction_memos(signature)
+                    .map_err(to_rpc_error)?;
+                let block_time = self.blockstore.get_block_time(slot).map_err(to_rpc_error)?;
+                Ok(RpcConfirmedTransactionStatusWithSignature {
+                    signature: signature.to_string(),
+                    slot,
+                    err,
+                    memo,
+                    block_time,
+                    confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                })
+            })
+            .collect()
+    }
+
     pub async fn get_signatures_for_address(
         &self,
         address: Pubkey,
@@ -3255,6 +3316,14 @@ pub mod rpc_full {
             config: Option<RpcSignaturesForAddressConfig>,
         ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
 
+        #[rpc(meta, name = "getSignaturesForAddressInRange")]
+        fn get_signatures_for_address_in_range(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressInRangeConfig>,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>>;
 
@@ -3530,6 +3599,9 @@ pub mod rpc_full {
                     logs,
                     post_simulation_accounts: _,
                     units_consumed,
+                    inner_instructions: _,
+                    return_data,
+                    invocations: _,
                 } = preflight_bank.simulate_transaction(transaction)
                 {
                     match err {
@@ -3547,6 +3619,8 @@ pub mod rpc_full {
                             logs: Some(logs),
                             accounts: None,
                             units_consumed: Some(units_consumed),
+                            return_data: return_data.map(Into::into),
+                            inner_instructions: None,
                         },
                     }
                     .into());
@@ -3598,6 +3672,9 @@ pub mod rpc_full {
                 logs,
                 post_simulation_accounts,
                 units_consumed,
+                inner_instructions,
+                return_data,
+                invocations: _,
             } = bank.simulate_transaction(transaction);
 
             let accounts = if let Some(config_accounts) = config.accounts {
@@ -3649,6 +3726,16 @@ pub mod rpc_full {
                     logs: Some(logs),
                     accounts,
                     units_consumed: Some(units_consumed),
+                    return_data: return_data.map(Into::into),
+                    inner_instructions: if config.inner_instructions {
+                        inner_instructions.map(|inner_instructions| {
+                            map_inner_instructions(inner_instructions)
+                                .map(UiInnerInstructions::from)
+                                .collect()
+                        })
+                    } else {
+                        None
+                    },
                 },
             ))
         }
@@ -3750,6 +3837,28 @@ pub mod rpc_full {
             }
         }
 
+        fn get_signatures_for_address_in_range(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressInRangeConfig>,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+            debug!(
+                "get_signatures_for_address_in_range rpc request received: {:?} {:?}",
+                address, config
+            );
+            let config = config.unwrap_or_default();
+            let (address, _, _, limit) =
+                verify_and_parse_signatures_for_address_params(address, None, None, config.limit)?;
+            meta.get_signatures_for_address_in_range(
+                address,
+                config.start_slot.unwrap_or_default(),
+                config.end_slot,
+                limit,
+                config.order.unwrap_or_default(),
+            )
+        }
+
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>> {
             debug!("get_first_available_block rpc request received");
             Box::pin(async move { Ok(meta.get_first_available_block().await) })
@@ -4342,6 +4451,7 @@ pub fn create_test_transactions_and_populate_blockstore(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
             true,
+            false,
             None,
             blockstore,
             &Arc::new(AtomicBool::new(false)),
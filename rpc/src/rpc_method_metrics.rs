@@ -0,0 +1,282 @@
+//! The `rpc_method_metrics` module records the latency and response size of each RPC method
+//! in histograms, which are periodically submitted to the metrics backend
+
+use {
+    crate::rpc::JsonRpcRequestProcessor,
+    jsonrpc_core::{
+        futures::future::Either,
+        middleware::{Middleware, NoopCallFuture, NoopFuture},
+        Call, ErrorCode, Output,
+    },
+    solana_sdk::timing::AtomicInterval,
+    std::{
+        collections::HashMap,
+        future::Future,
+        io,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+const REPORT_INTERVAL_MS: u64 = 10_000;
+
+pub const DEFAULT_LATENCY_BUCKETS_US: &[u64] = &[
+    1_000, 5_000, 10_000, 50_000, 100_000, 500_000, 1_000_000, 5_000_000, 10_000_000,
+];
+pub const DEFAULT_PAYLOAD_SIZE_BUCKETS: &[u64] =
+    &[1_000, 10_000, 100_000, 1_000_000, 10_000_000, 100_000_000];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcMethodMetricsConfig {
+    /// Upper bounds of the latency histogram buckets, in microseconds
+    pub latency_buckets_us: Vec<u64>,
+    /// Upper bounds of the response size histogram buckets, in bytes
+    pub payload_size_buckets: Vec<u64>,
+}
+
+impl Default for RpcMethodMetricsConfig {
+    fn default() -> Self {
+        Self {
+            latency_buckets_us: DEFAULT_LATENCY_BUCKETS_US.to_vec(),
+            payload_size_buckets: DEFAULT_PAYLOAD_SIZE_BUCKETS.to_vec(),
+        }
+    }
+}
+
+/// The names of the fields a histogram is reported with
+#[derive(Debug)]
+struct HistogramFields {
+    buckets: Vec<u64>,
+    /// `<prefix>_le_<bucket>` for each bucket, then `<prefix>_le_inf`
+    bucket_names: Vec<&'static str>,
+    sum_name: &'static str,
+}
+
+impl HistogramFields {
+    // The names are leaked as datapoint fields must be static, which is fine as there are only
+    // a few of them and they live as long as the RPC service
+    fn new(prefix: &str, buckets: &[u64]) -> Self {
+        let mut buckets = buckets.to_vec();
+        buckets.sort_unstable();
+        buckets.dedup();
+        let bucket_names = buckets
+            .iter()
+            .map(|bucket| format!("{}_le_{}", prefix, bucket))
+            .chain(std::iter::once(format!("{}_le_inf", prefix)))
+            .map(|name| &*Box::leak(name.into_boxed_str()))
+            .collect();
+        Self {
+            buckets,
+            bucket_names,
+            sum_name: Box::leak(format!("{}_sum", prefix).into_boxed_str()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Histogram {
+    /// The number of values of each bucket, the last one counting the values above every bucket
+    counts: Vec<u64>,
+    sum: u64,
+}
+
+impl Histogram {
+    fn new(fields: &HistogramFields) -> Self {
+        Self {
+            counts: vec![0; fields.buckets.len() + 1],
+            sum: 0,
+        }
+    }
+
+    fn record(&mut self, fields: &HistogramFields, value: u64) {
+        let bucket = fields.buckets.partition_point(|bucket| *bucket < value);
+        self.counts[bucket] += 1;
+        self.sum = self.sum.saturating_add(value);
+    }
+
+    /// Adds the cumulative count of each bucket and the sum to `point`, then resets the histogram
+    fn report(
+        &mut self,
+        fields: &HistogramFields,
+        point: &mut solana_metrics::datapoint::DataPoint,
+    ) {
+        let mut cumulative_count = 0;
+        for (name, count) in fields.bucket_names.iter().zip(&self.counts) {
+            cumulative_count += count;
+            point.add_field_i64(*name, cumulative_count as i64);
+        }
+        point.add_field_i64(fields.sum_name, self.sum as i64);
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.sum = 0;
+    }
+}
+
+#[derive(Debug)]
+struct MethodStats {
+    /// `rpc-method-<method>`
+    datapoint_name: &'static str,
+    count: u64,
+    latency_us: Histogram,
+    payload_size: Histogram,
+}
+
+#[derive(Debug)]
+pub struct RpcMethodMetrics {
+    latency_fields: HistogramFields,
+    payload_size_fields: HistogramFields,
+    methods: Mutex<HashMap<String, MethodStats>>,
+    last_report: AtomicInterval,
+}
+
+impl RpcMethodMetrics {
+    pub fn new(config: &RpcMethodMetricsConfig) -> Self {
+        Self {
+            latency_fields: HistogramFields::new("latency_us", &config.latency_buckets_us),
+            payload_size_fields: HistogramFields::new("payload_size", &config.payload_size_buckets),
+            methods: Mutex::default(),
+            last_report: AtomicInterval::default(),
+        }
+    }
+
+    fn record(&self, method: &str, latency: Duration, output: &Output) {
+        // Only methods the service knows are recorded, so that clients calling arbitrary method
+        // names cannot grow the set of reported datapoints
+        if let Output::Failure(failure) = output {
+            if failure.error.code == ErrorCode::MethodNotFound {
+                return;
+            }
+        }
+        let mut payload_size = ByteCounter::default();
+        let _ = serde_json::to_writer(&mut payload_size, output);
+
+        let mut methods = self.methods.lock().unwrap();
+        let stats = methods
+            .entry(method.to_string())
+            .or_insert_with(|| MethodStats {
+                datapoint_name: Box::leak(format!("rpc-method-{}", method).into_boxed_str()),
+                count: 0,
+                latency_us: Histogram::new(&self.latency_fields),
+                payload_size: Histogram::new(&self.payload_size_fields),
+            });
+        stats.count += 1;
+        stats
+            .latency_us
+            .record(&self.latency_fields, latency.as_micros() as u64);
+        stats
+            .payload_size
+            .record(&self.payload_size_fields, payload_size.0 as u64);
+
+        if self.last_report.should_update(REPORT_INTERVAL_MS) {
+            self.report(&mut methods);
+        }
+    }
+
+    fn report(&self, methods: &mut HashMap<String, MethodStats>) {
+        for stats in methods.values_mut().filter(|stats| stats.count > 0) {
+            let mut point = solana_metrics::datapoint::DataPoint::new(stats.datapoint_name);
+            point.add_field_i64("count", stats.count as i64);
+            stats.latency_us.report(&self.latency_fields, &mut point);
+            stats
+                .payload_size
+                .report(&self.payload_size_fields, &mut point);
+            stats.count = 0;
+            solana_metrics::submit(point, log::Level::Info);
+        }
+    }
+}
+
+/// Counts the bytes of the serialized response without buffering them
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Records the metrics of every method call if `metrics` is set
+#[derive(Clone, Default)]
+pub struct RpcMethodMetricsMiddleware {
+    metrics: Option<Arc<RpcMethodMetrics>>,
+}
+
+impl RpcMethodMetricsMiddleware {
+    pub fn new(config: Option<&RpcMethodMetricsConfig>) -> Self {
+        Self {
+            metrics: config.map(|config| Arc::new(RpcMethodMetrics::new(config))),
+        }
+    }
+}
+
+impl Middleware<JsonRpcRequestProcessor> for RpcMethodMetricsMiddleware {
+    type Future = NoopFuture;
+    type CallFuture = NoopCallFuture;
+
+    fn on_call<F, X>(
+        &self,
+        call: Call,
+        meta: JsonRpcRequestProcessor,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, JsonRpcRequestProcessor) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        let (metrics, method) = match (&self.metrics, &call) {
+            (Some(metrics), Call::MethodCall(method_call)) => {
+                (metrics.clone(), method_call.method.clone())
+            }
+            _ => return Either::Right(next(call, meta)),
+        };
+        let start = Instant::now();
+        let output = next(call, meta);
+        Either::Left(Box::pin(async move {
+            let output = output.await;
+            if let Some(output) = &output {
+                metrics.record(&method, start.elapsed(), output);
+            }
+            output
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_metrics::datapoint::DataPoint};
+
+    #[test]
+    fn test_histogram() {
+        let fields = HistogramFields::new("latency_us", &[100, 10, 100]);
+        assert_eq!(
+            fields.bucket_names,
+            vec!["latency_us_le_10", "latency_us_le_100", "latency_us_le_inf"]
+        );
+
+        let mut histogram = Histogram::new(&fields);
+        for value in [1, 10, 11, 100, 1_000] {
+            histogram.record(&fields, value);
+        }
+        let mut point = DataPoint::new("test");
+        histogram.report(&fields, &mut point);
+        assert_eq!(
+            point.fields,
+            vec![
+                ("latency_us_le_10", "2i".to_string()),
+                ("latency_us_le_100", "4i".to_string()),
+                ("latency_us_le_inf", "5i".to_string()),
+                ("latency_us_sum", "1122i".to_string()),
+            ]
+        );
+
+        // Reporting resets the histogram
+        let mut point = DataPoint::new("test");
+        histogram.report(&fields, &mut point);
+        assert!(point.fields.iter().all(|(_, value)| value == "0i"));
+    }
+}
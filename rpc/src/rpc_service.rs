@@ -10,6 +10,7 @@ use {
             rpc_full::*, rpc_minimal::*, rpc_obsolete_v1_7::*, *,
         },
         rpc_health::*,
+        rpc_method_metrics::RpcMethodMetricsMiddleware,
     },
     crossbeam_channel::unbounded,
     jsonrpc_core::{futures::prelude::*, MetaIoHandler},
@@ -345,13 +346,12 @@ impl JsonRpcService {
         let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
             if config.enable_bigtable_ledger_storage || config.enable_bigtable_ledger_upload {
                 runtime
-                    .block_on(solana_storage_bigtable::LedgerStorage::new(
+                    .block_on(config.ledger_storage.connect(
                         !config.enable_bigtable_ledger_upload,
                         config.rpc_bigtable_timeout,
-                        None,
                     ))
                     .map(|bigtable_ledger_storage| {
-                        info!("BigTable ledger storage initialized");
+                        info!("{} ledger storage initialized", config.ledger_storage);
 
                         let bigtable_ledger_upload_service = if config.enable_bigtable_ledger_upload
                         {
@@ -373,7 +373,10 @@ impl JsonRpcService {
                         )
                     })
                     .unwrap_or_else(|err| {
-                        error!("Failed to initialize BigTable ledger storage: {:?}", err);
+                        error!(
+                            "Failed to initialize {} ledger storage: {:?}",
+                            config.ledger_storage, err
+                        );
                         (None, None)
                     })
             } else {
@@ -382,6 +385,8 @@ impl JsonRpcService {
 
         let full_api = config.full_api;
         let obsolete_v1_7_api = config.obsolete_v1_7_api;
+        let method_metrics_middleware =
+            RpcMethodMetricsMiddleware::new(config.method_metrics.as_ref());
         let (request_processor, receiver) = JsonRpcRequestProcessor::new(
             config,
             snapshot_config.clone(),
@@ -421,7 +426,7 @@ impl JsonRpcService {
             .spawn(move || {
                 renice_this_thread(rpc_niceness_adj).unwrap();
 
-                let mut io = MetaIoHandler::default();
+                let mut io = MetaIoHandler::with_middleware(method_metrics_middleware);
 
                 io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
                 if full_api {
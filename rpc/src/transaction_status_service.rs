@@ -10,9 +10,10 @@ use {
         Bank, DurableNonceFee, TransactionExecutionDetails, TransactionExecutionResult,
     },
     solana_transaction_status::{
-        extract_and_fmt_memos, InnerInstructions, Reward, TransactionStatusMeta,
+        extract_and_fmt_memos, map_inner_instructions, Reward, TransactionStatusMeta,
     },
     std::{
+        collections::HashSet,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
             Arc,
@@ -32,6 +33,7 @@ impl TransactionStatusService {
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         enable_rpc_transaction_history: bool,
+        enable_program_signatures_index: bool,
         transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: Arc<Blockstore>,
         exit: &Arc<AtomicBool>,
@@ -48,6 +50,7 @@ impl TransactionStatusService {
                     &write_transaction_status_receiver,
                     &max_complete_transaction_status_slot,
                     enable_rpc_transaction_history,
+                    enable_program_signatures_index,
                     transaction_notifier.clone(),
                     &blockstore,
                 ) {
@@ -62,6 +65,7 @@ impl TransactionStatusService {
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
         enable_rpc_transaction_history: bool,
+        enable_program_signatures_index: bool,
         transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: &Arc<Blockstore>,
     ) -> Result<(), RecvTimeoutError> {
@@ -98,6 +102,10 @@ impl TransactionStatusService {
                             log_messages,
                             inner_instructions,
                             durable_nonce_fee,
+                            return_data,
+                            executed_units,
+                            instructions_executed_units: _,
+                            invocations: _,
                         } = details;
                         let lamports_per_signature = match durable_nonce_fee {
                             Some(DurableNonceFee::Valid(lamports_per_signature)) => {
@@ -116,15 +124,7 @@ impl TransactionStatusService {
                         let tx_account_locks = transaction.get_account_locks_unchecked();
 
                         let inner_instructions = inner_instructions.map(|inner_instructions| {
-                            inner_instructions
-                                .into_iter()
-                                .enumerate()
-                                .map(|(index, instructions)| InnerInstructions {
-                                    index: index as u8,
-                                    instructions,
-                                })
-                                .filter(|i| !i.instructions.is_empty())
-                                .collect()
+                            map_inner_instructions(inner_instructions).collect()
                         });
 
                         let pre_token_balances = Some(pre_token_balances);
@@ -153,6 +153,8 @@ impl TransactionStatusService {
                             post_token_balances,
                             rewards,
                             loaded_addresses,
+                            return_data,
+                            compute_units_consumed: Some(executed_units),
                         };
 
                         if let Some(transaction_notifier) = transaction_notifier.as_ref() {
@@ -179,6 +181,21 @@ impl TransactionStatusService {
                                     transaction_status_meta,
                                 )
                                 .expect("Expect database write to succeed: TransactionStatus");
+
+                            if enable_program_signatures_index {
+                                let program_ids: HashSet<_> = transaction
+                                    .message()
+                                    .program_instructions_iter()
+                                    .map(|(program_id, _)| program_id)
+                                    .collect();
+                                blockstore
+                                    .write_program_signatures(
+                                        slot,
+                                        *transaction.signature(),
+                                        program_ids.into_iter().collect(),
+                                    )
+                                    .expect("Expect database write to succeed: ProgramSignatures");
+                            }
                         }
                     }
                 }
@@ -338,6 +355,10 @@ pub(crate) mod tests {
                     )
                     .unwrap(),
                 )),
+                return_data: None,
+                executed_units: 0,
+                instructions_executed_units: vec![],
+                invocations: None,
             });
 
         let balances = TransactionBalancesSet {
@@ -351,6 +372,8 @@ pub(crate) mod tests {
             mint: Pubkey::new_unique().to_string(),
             ui_token_amount: token_amount_to_ui_amount(42, 2),
             owner: owner.clone(),
+            program_id: Pubkey::new_unique().to_string(),
+            extensions: None,
         };
 
         let post_token_balance = TransactionTokenBalance {
@@ -358,6 +381,8 @@ pub(crate) mod tests {
             mint: Pubkey::new_unique().to_string(),
             ui_token_amount: token_amount_to_ui_amount(58, 2),
             owner,
+            program_id: Pubkey::new_unique().to_string(),
+            extensions: None,
         };
 
         let token_balances = TransactionTokenBalancesSet {
@@ -383,6 +408,7 @@ pub(crate) mod tests {
             transaction_status_receiver,
             Arc::new(AtomicU64::default()),
             false,
+            false,
             Some(test_notifier.clone()),
             blockstore,
             &exit,
@@ -913,6 +913,26 @@ impl Accounts {
         )
     }
 
+    /// Like `load_all`, but hands each loadable account to `func` instead of
+    /// collecting them all in memory
+    pub fn scan_all<F>(&self, ancestors: &Ancestors, bank_id: BankId, mut func: F) -> ScanResult<()>
+    where
+        F: FnMut(&Pubkey, AccountSharedData, Slot),
+    {
+        self.accounts_db.scan_accounts(
+            ancestors,
+            bank_id,
+            |_collector: &mut (), some_account_tuple| {
+                if let Some((pubkey, account, slot)) = some_account_tuple
+                    .filter(|(_, account, _)| Self::is_loadable(account.lamports()))
+                {
+                    func(pubkey, account, slot)
+                }
+            },
+            &ScanConfig::default(),
+        )
+    }
+
     pub fn hold_range_in_memory<R>(
         &self,
         range: &R,
@@ -1350,6 +1370,10 @@ mod tests {
             log_messages: None,
             inner_instructions: None,
             durable_nonce_fee: nonce.map(DurableNonceFee::from),
+            return_data: None,
+            executed_units: 0,
+            instructions_executed_units: vec![],
+            invocations: None,
         })
     }
 
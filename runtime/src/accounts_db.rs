@@ -3115,11 +3115,11 @@ impl AccountsDb {
         &self,
         ancestors: &Ancestors,
         bank_id: BankId,
-        scan_func: F,
+        mut scan_func: F,
         config: &ScanConfig,
     ) -> ScanResult<A>
     where
-        F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
+        F: FnMut(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
         A: Default,
     {
         let mut collector = A::default();
@@ -127,7 +127,10 @@ use {
             Result, SanitizedTransaction, Transaction, TransactionError,
             TransactionVerificationMode, VersionedTransaction,
         },
-        transaction_context::{InstructionTrace, TransactionAccount, TransactionContext},
+        transaction_context::{
+            InstructionTrace, InvocationRecord, TransactionAccount, TransactionContext,
+            TransactionReturnData,
+        },
     },
     solana_stake_program::stake_state::{
         self, InflationPointCalculationEvent, PointValue, StakeState,
@@ -576,6 +579,14 @@ pub struct TransactionExecutionDetails {
     pub log_messages: Option<Vec<String>>,
     pub inner_instructions: Option<InnerInstructionsList>,
     pub durable_nonce_fee: Option<DurableNonceFee>,
+    pub return_data: Option<TransactionReturnData>,
+    /// Compute units consumed by the transaction's instructions
+    pub executed_units: u64,
+    /// Compute units consumed by each of the transaction's instructions that was executed
+    pub instructions_executed_units: Vec<u64>,
+    /// The tree of program invocations, recorded along with inner instructions when log
+    /// messages are recorded
+    pub invocations: Option<Vec<InvocationRecord>>,
 }
 
 /// Type safe representation of a transaction execution attempt which
@@ -667,6 +678,9 @@ pub struct TransactionSimulationResult {
     pub logs: TransactionLogMessages,
     pub post_simulation_accounts: Vec<TransactionAccount>,
     pub units_consumed: u64,
+    pub inner_instructions: Option<InnerInstructionsList>,
+    pub return_data: Option<TransactionReturnData>,
+    pub invocations: Option<Vec<InvocationRecord>>,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -2245,6 +2259,14 @@ impl Bank {
         self.fill_missing_sysvar_cache_entries();
     }
 
+    /// Replace the rent the bank collects and checks accounts against, along with the Rent sysvar
+    pub fn set_rent_for_tests(&mut self, rent: sysvar::rent::Rent) {
+        self.rent_collector.rent = rent;
+        self.update_rent();
+        self.reset_sysvar_cache();
+        self.fill_missing_sysvar_cache_entries();
+    }
+
     fn update_slot_history(&self) {
         self.update_sysvar_account(&sysvar::slot_history::id(), |account| {
             let mut slot_history = account
@@ -3478,7 +3500,7 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            true,
             true,
             &mut timings,
         );
@@ -3510,17 +3532,24 @@ impl Bank {
 
         let execution_result = execution_results.pop().unwrap();
         let flattened_result = execution_result.flattened_result();
-        let logs = match execution_result {
-            TransactionExecutionResult::Executed(details) => details.log_messages,
-            TransactionExecutionResult::NotExecuted(_) => None,
-        }
-        .unwrap_or_default();
+        let (logs, inner_instructions, return_data, invocations) = match execution_result {
+            TransactionExecutionResult::Executed(details) => (
+                details.log_messages,
+                details.inner_instructions,
+                details.return_data,
+                details.invocations,
+            ),
+            TransactionExecutionResult::NotExecuted(_) => (None, None, None, None),
+        };
 
         TransactionSimulationResult {
             result: flattened_result,
-            logs,
+            logs: logs.unwrap_or_default(),
             post_simulation_accounts,
             units_consumed,
+            inner_instructions,
+            return_data,
+            invocations,
         }
     }
 
@@ -3828,13 +3857,18 @@ impl Bank {
             self.get_transaction_account_state_info(&transaction_context, tx.message());
 
         let log_collector = if enable_log_recording {
-            Some(LogCollector::new_ref())
+            if enable_cpi_recording {
+                Some(LogCollector::new_ref_with_invocation_recording())
+            } else {
+                Some(LogCollector::new_ref())
+            }
         } else {
             None
         };
 
         let (blockhash, lamports_per_signature) = self.last_blockhash_and_lamports_per_signature();
 
+        let mut instructions_executed_units = Vec::with_capacity(tx.message().instructions().len());
         let mut process_message_time = Measure::start("process_message_time");
         let process_result = MessageProcessor::process_message(
             &self.builtin_programs.vec,
@@ -3851,6 +3885,7 @@ impl Bank {
             blockhash,
             lamports_per_signature,
             self.load_accounts_data_len(),
+            &mut instructions_executed_units,
         );
         process_message_time.stop();
         saturating_add_assign!(
@@ -3892,13 +3927,16 @@ impl Bank {
                 err
             });
 
-        let log_messages: Option<TransactionLogMessages> =
-            log_collector.and_then(|log_collector| {
+        let (log_messages, invocations): (Option<TransactionLogMessages>, _) = log_collector
+            .and_then(|log_collector| {
                 Rc::try_unwrap(log_collector)
-                    .map(|log_collector| log_collector.into_inner().into())
+                    .map(|log_collector| log_collector.into_inner().into_messages_and_invocations())
                     .ok()
-            });
+            })
+            .map(|(log_messages, invocations)| (Some(log_messages), invocations))
+            .unwrap_or_default();
 
+        let return_data = transaction_context.get_transaction_return_data();
         let (accounts, instruction_trace) = transaction_context.deconstruct();
         loaded_transaction.accounts = accounts;
 
@@ -3915,6 +3953,12 @@ impl Bank {
             log_messages,
             inner_instructions,
             durable_nonce_fee,
+            return_data,
+            executed_units: instructions_executed_units
+                .iter()
+                .fold(0, |acc: u64, units| acc.saturating_add(*units)),
+            instructions_executed_units,
+            invocations,
         })
     }
 
@@ -5155,6 +5199,34 @@ impl Bank {
             .map_or(Ok(()), |sig| self.get_signature_status(sig).unwrap())
     }
 
+    /// Process a Transaction and return its execution details, recorded with logs and inner
+    /// instructions. This is used for tests and the banks service.
+    #[must_use]
+    pub fn process_transaction_with_metadata(
+        &self,
+        tx: &Transaction,
+    ) -> TransactionExecutionResult {
+        let batch = match self.prepare_entry_batch(vec![VersionedTransaction::from(tx.clone())]) {
+            Ok(batch) => batch,
+            Err(err) => return TransactionExecutionResult::NotExecuted(err),
+        };
+        let (
+            TransactionResults {
+                mut execution_results,
+                ..
+            },
+            _,
+        ) = self.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            false,
+            true,
+            true,
+            &mut ExecuteTimings::default(),
+        );
+        execution_results.remove(0)
+    }
+
     /// Process multiple transaction in a single batch. This is used for benches and unit tests.
     ///
     /// # Panics
@@ -5515,6 +5587,17 @@ impl Bank {
         self.rc.accounts.load_all(&self.ancestors, self.bank_id)
     }
 
+    /// Calls `func` on every account of the bank without collecting them, see
+    /// `get_all_accounts_with_modified_slots`
+    pub fn scan_all_accounts_with_modified_slots<F>(&self, func: F) -> ScanResult<()>
+    where
+        F: FnMut(&Pubkey, AccountSharedData, Slot),
+    {
+        self.rc
+            .accounts
+            .scan_all(&self.ancestors, self.bank_id, func)
+    }
+
     pub fn get_program_accounts_modified_since_parent(
         &self,
         program_id: &Pubkey,
@@ -6690,6 +6773,10 @@ pub(crate) mod tests {
             log_messages: None,
             inner_instructions: None,
             durable_nonce_fee: nonce.map(DurableNonceFee::from),
+            return_data: None,
+            executed_units: 0,
+            instructions_executed_units: vec![],
+            invocations: None,
         })
     }
 
@@ -9038,6 +9125,59 @@ pub(crate) mod tests {
         assert_eq!(bank.get_signature_status(&t2.signatures[0]), None);
     }
 
+    #[test]
+    fn test_process_transaction_with_metadata() {
+        let (genesis_config, mint_keypair) = create_genesis_config(10);
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let bank = Bank::new_for_tests(&genesis_config);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&mint_keypair.pubkey(), &key1, 1),
+                system_instruction::transfer(&mint_keypair.pubkey(), &key2, 1),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            genesis_config.hash(),
+        );
+        match bank.process_transaction_with_metadata(&tx) {
+            TransactionExecutionResult::Executed(details) => {
+                assert_eq!(details.status, Ok(()));
+                assert!(details.log_messages.is_some());
+                assert_eq!(details.instructions_executed_units.len(), 2);
+                assert_eq!(
+                    details.executed_units,
+                    details.instructions_executed_units.iter().sum::<u64>()
+                );
+                let invocations = details.invocations.unwrap();
+                assert_eq!(invocations.len(), 2);
+                assert!(invocations.iter().all(|invocation| {
+                    invocation.program_id == system_program::id()
+                        && invocation.stack_height == 1
+                        && invocation.result == Ok(())
+                        && invocation.inner_invocations.is_empty()
+                }));
+                assert_eq!(
+                    invocations[0].logs,
+                    vec![
+                        format!("Program {} invoke [1]", system_program::id()),
+                        format!("Program {} success", system_program::id()),
+                    ]
+                );
+            }
+            TransactionExecutionResult::NotExecuted(err) => panic!("not executed: {}", err),
+        }
+        assert_eq!(bank.get_balance(&key1), 1);
+        assert_eq!(bank.get_balance(&key2), 1);
+
+        // Transactions that can't be executed are reported as such
+        assert!(matches!(
+            bank.process_transaction_with_metadata(&tx),
+            TransactionExecutionResult::NotExecuted(TransactionError::AlreadyProcessed)
+        ));
+    }
+
     #[test]
     fn test_one_tx_two_out_atomic_fail() {
         let (genesis_config, mint_keypair) = create_genesis_config(1);
@@ -10715,6 +10855,22 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_bank_scan_all_accounts_with_modified_slots() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(500);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let mut scanned_accounts = vec![];
+        bank.scan_all_accounts_with_modified_slots(|pubkey, account, slot| {
+            scanned_accounts.push((*pubkey, account, slot))
+        })
+        .unwrap();
+        let mut all_accounts = bank.get_all_accounts_with_modified_slots().unwrap();
+        scanned_accounts.sort_by_key(|(pubkey, _, _)| *pubkey);
+        all_accounts.sort_by_key(|(pubkey, _, _)| *pubkey);
+        assert_eq!(scanned_accounts, all_accounts);
+    }
+
     #[test]
     fn test_get_filtered_indexed_accounts_limit_exceeded() {
         let (genesis_config, _mint_keypair) = create_genesis_config(500);
@@ -64,6 +64,7 @@ impl MessageProcessor {
         blockhash: Hash,
         lamports_per_signature: u64,
         current_accounts_data_len: u64,
+        instructions_executed_units: &mut Vec<u64>,
     ) -> Result<ProcessedMessageInfo, TransactionError> {
         let mut invoke_context = InvokeContext::new(
             transaction_context,
@@ -91,6 +92,7 @@ impl MessageProcessor {
                 && is_precompile(program_id, |id| invoke_context.feature_set.is_active(id))
             {
                 // Precompiled programs don't have an instruction processor
+                instructions_executed_units.push(0);
                 continue;
             }
 
@@ -134,6 +136,7 @@ impl MessageProcessor {
                 timings,
             );
             time.stop();
+            instructions_executed_units.push(compute_units_consumed);
             timings.details.accumulate_program(
                 program_id,
                 time.as_us(),
@@ -278,6 +281,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert!(result.is_ok());
         assert_eq!(
@@ -320,6 +324,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -352,6 +357,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -487,6 +493,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -520,6 +527,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert!(result.is_ok());
 
@@ -550,6 +558,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert!(result.is_ok());
         assert_eq!(
@@ -629,6 +638,7 @@ mod tests {
             Hash::default(),
             0,
             0,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
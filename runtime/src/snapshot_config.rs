@@ -1,5 +1,5 @@
 use {
-    crate::snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+    crate::snapshot_utils::{self, ArchiveCompressionOptions, ArchiveFormat, SnapshotVersion},
     solana_sdk::clock::Slot,
     std::path::PathBuf,
 };
@@ -22,6 +22,9 @@ pub struct SnapshotConfig {
     /// The archive format to use for snapshots
     pub archive_format: ArchiveFormat,
 
+    /// The compressor settings to use for snapshot archives
+    pub archive_compression: ArchiveCompressionOptions,
+
     /// Snapshot version to generate
     pub snapshot_version: SnapshotVersion,
 
@@ -52,6 +55,7 @@ impl Default for SnapshotConfig {
             snapshot_archives_dir: PathBuf::default(),
             bank_snapshots_dir: PathBuf::default(),
             archive_format: ArchiveFormat::TarBzip2,
+            archive_compression: ArchiveCompressionOptions::default(),
             snapshot_version: SnapshotVersion::default(),
             maximum_full_snapshot_archives_to_retain:
                 snapshot_utils::DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
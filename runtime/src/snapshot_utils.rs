@@ -33,6 +33,7 @@ use {
         fmt,
         fs::{self, File},
         io::{BufReader, BufWriter, Error as IoError, ErrorKind, Read, Seek, Write},
+        ops::RangeInclusive,
         path::{Path, PathBuf},
         process::ExitStatus,
         str::FromStr,
@@ -54,8 +55,8 @@ pub const TMP_SNAPSHOT_ARCHIVE_PREFIX: &str = "tmp-snapshot-archive-";
 pub const MAX_BANK_SNAPSHOTS_TO_RETAIN: usize = 8; // Save some bank snapshots but not too many
 pub const DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 2;
 pub const DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 4;
-pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
-pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
+pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
+pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
@@ -118,9 +119,33 @@ pub enum ArchiveFormat {
     TarBzip2,
     TarGzip,
     TarZstd,
+    TarLz4,
     Tar,
 }
 
+impl ArchiveFormat {
+    /// The compression levels accepted by the format, or `None` if it isn't compressed
+    pub fn compression_levels(self) -> Option<RangeInclusive<i32>> {
+        match self {
+            ArchiveFormat::TarBzip2 => Some(1..=9),
+            ArchiveFormat::TarGzip => Some(0..=9),
+            ArchiveFormat::TarZstd => Some(1..=22),
+            ArchiveFormat::TarLz4 => Some(0..=16),
+            ArchiveFormat::Tar => None,
+        }
+    }
+}
+
+/// Compressor settings used when creating snapshot archives
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ArchiveCompressionOptions {
+    /// Compression level, or the format's default level if `None`
+    pub level: Option<i32>,
+    /// Number of worker threads compressing in the background.  Only zstd supports this; `0`
+    /// compresses on the archiving thread itself
+    pub num_threads: u32,
+}
+
 /// A slot and the path to its bank snapshot
 #[derive(PartialEq, Eq, Debug)]
 pub struct BankSnapshotInfo {
@@ -214,6 +239,7 @@ fn get_archive_ext(archive_format: ArchiveFormat) -> &'static str {
         ArchiveFormat::TarBzip2 => "tar.bz2",
         ArchiveFormat::TarGzip => "tar.gz",
         ArchiveFormat::TarZstd => "tar.zst",
+        ArchiveFormat::TarLz4 => "tar.lz4",
         ArchiveFormat::Tar => "tar",
     }
 }
@@ -244,6 +270,7 @@ pub fn remove_tmp_snapshot_archives(snapshot_archives_dir: impl AsRef<Path>) {
 /// Make a snapshot archive out of the snapshot package
 pub fn archive_snapshot_package(
     snapshot_package: &SnapshotPackage,
+    archive_compression: ArchiveCompressionOptions,
     maximum_full_snapshot_archives_to_retain: usize,
     maximum_incremental_snapshot_archives_to_retain: usize,
 ) -> Result<()> {
@@ -332,6 +359,7 @@ pub fn archive_snapshot_package(
         file_ext
     ));
 
+    let mut compress_timer = Measure::start("snapshot_package-compress");
     {
         let mut archive_file = fs::File::create(&archive_path)?;
 
@@ -345,29 +373,47 @@ pub fn archive_snapshot_package(
             Ok(())
         };
 
+        let level = archive_compression.level;
         match snapshot_package.archive_format() {
             ArchiveFormat::TarBzip2 => {
-                let mut encoder =
-                    bzip2::write::BzEncoder::new(archive_file, bzip2::Compression::best());
+                let compression = level.map_or(bzip2::Compression::best(), |level| {
+                    bzip2::Compression::new(level as u32)
+                });
+                let mut encoder = bzip2::write::BzEncoder::new(archive_file, compression);
                 do_archive_files(&mut encoder)?;
                 encoder.finish()?;
             }
             ArchiveFormat::TarGzip => {
-                let mut encoder =
-                    flate2::write::GzEncoder::new(archive_file, flate2::Compression::default());
+                let compression = level.map_or(flate2::Compression::default(), |level| {
+                    flate2::Compression::new(level as u32)
+                });
+                let mut encoder = flate2::write::GzEncoder::new(archive_file, compression);
                 do_archive_files(&mut encoder)?;
                 encoder.finish()?;
             }
             ArchiveFormat::TarZstd => {
-                let mut encoder = zstd::stream::Encoder::new(archive_file, 0)?;
+                // zstd treats level 0 as its default level
+                let mut encoder = zstd::stream::Encoder::new(archive_file, level.unwrap_or(0))?;
+                if archive_compression.num_threads > 0 {
+                    encoder.multithread(archive_compression.num_threads)?;
+                }
                 do_archive_files(&mut encoder)?;
                 encoder.finish()?;
             }
+            ArchiveFormat::TarLz4 => {
+                let mut encoder = lz4::EncoderBuilder::new()
+                    .level(level.unwrap_or(0) as u32)
+                    .build(archive_file)?;
+                do_archive_files(&mut encoder)?;
+                let (_, result) = encoder.finish();
+                result?;
+            }
             ArchiveFormat::Tar => {
                 do_archive_files(&mut archive_file)?;
             }
         };
     }
+    compress_timer.stop();
 
     // Atomically move the archive into position for other validators to find
     let metadata = fs::metadata(&archive_path)
@@ -383,17 +429,26 @@ pub fn archive_snapshot_package(
 
     timer.stop();
     info!(
-        "Successfully created {:?}. slot: {}, elapsed ms: {}, size={}",
+        "Successfully created {:?}. slot: {}, elapsed ms: {}, compress ms: {}, size={}",
         snapshot_package.path(),
         snapshot_package.slot(),
         timer.as_ms(),
+        compress_timer.as_ms(),
         metadata.len()
     );
     datapoint_info!(
         "snapshot-package",
         ("slot", snapshot_package.slot(), i64),
         ("duration_ms", timer.as_ms(), i64),
-        ("size", metadata.len(), i64)
+        ("compress_ms", compress_timer.as_ms(), i64),
+        ("size", metadata.len(), i64),
+        ("archive_format", file_ext, String),
+        (
+            "compression_level",
+            archive_compression.level.unwrap_or_default(),
+            i64
+        ),
+        ("compression_threads", archive_compression.num_threads, i64)
     );
     Ok(())
 }
@@ -933,6 +988,10 @@ fn verify_bank_against_expected_slot_hash(
 /// Perform the common tasks when unarchiving a snapshot.  Handles creating the temporary
 /// directories, untaring, reading the version file, and then returning those fields plus the
 /// unpacked append vec map.
+///
+/// The archive is decompressed as a stream and never lands on disk in full: the append vecs are
+/// extracted straight into `account_paths`, where the accounts db picks them up, and only the
+/// bank snapshot, status cache and version file are extracted into the temporary directory.
 fn unarchive_snapshot<P, Q>(
     bank_snapshots_dir: P,
     unpacked_snapshots_dir_prefix: &'static str,
@@ -1068,6 +1127,7 @@ fn archive_format_from_str(archive_format: &str) -> Option<ArchiveFormat> {
         "tar.bz2" => Some(ArchiveFormat::TarBzip2),
         "tar.gz" => Some(ArchiveFormat::TarGzip),
         "tar.zst" => Some(ArchiveFormat::TarZstd),
+        "tar.lz4" => Some(ArchiveFormat::TarLz4),
         "tar" => Some(ArchiveFormat::Tar),
         _ => None,
     }
@@ -1386,6 +1446,12 @@ fn untar_snapshot_in<P: AsRef<Path>>(
             account_paths,
             parallel_divisions,
         )?,
+        ArchiveFormat::TarLz4 => unpack_snapshot_local(
+            || lz4::Decoder::new(BufReader::new(open_file())).unwrap(),
+            unpack_dir,
+            account_paths,
+            parallel_divisions,
+        )?,
         ArchiveFormat::Tar => unpack_snapshot_local(
             || BufReader::new(open_file()),
             unpack_dir,
@@ -1696,6 +1762,7 @@ pub fn bank_to_full_snapshot_archive(
     snapshot_version: Option<SnapshotVersion>,
     snapshot_archives_dir: impl AsRef<Path>,
     archive_format: ArchiveFormat,
+    archive_compression: ArchiveCompressionOptions,
     maximum_full_snapshot_archives_to_retain: usize,
     maximum_incremental_snapshot_archives_to_retain: usize,
 ) -> Result<FullSnapshotArchiveInfo> {
@@ -1720,6 +1787,7 @@ pub fn bank_to_full_snapshot_archive(
         snapshot_archives_dir,
         snapshot_storages,
         archive_format,
+        archive_compression,
         snapshot_version,
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
@@ -1739,6 +1807,7 @@ pub fn bank_to_incremental_snapshot_archive(
     snapshot_version: Option<SnapshotVersion>,
     snapshot_archives_dir: impl AsRef<Path>,
     archive_format: ArchiveFormat,
+    archive_compression: ArchiveCompressionOptions,
     maximum_full_snapshot_archives_to_retain: usize,
     maximum_incremental_snapshot_archives_to_retain: usize,
 ) -> Result<IncrementalSnapshotArchiveInfo> {
@@ -1765,6 +1834,7 @@ pub fn bank_to_incremental_snapshot_archive(
         snapshot_archives_dir,
         snapshot_storages,
         archive_format,
+        archive_compression,
         snapshot_version,
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
@@ -1779,6 +1849,7 @@ pub fn package_and_archive_full_snapshot(
     snapshot_archives_dir: impl AsRef<Path>,
     snapshot_storages: SnapshotStorages,
     archive_format: ArchiveFormat,
+    archive_compression: ArchiveCompressionOptions,
     snapshot_version: SnapshotVersion,
     maximum_full_snapshot_archives_to_retain: usize,
     maximum_incremental_snapshot_archives_to_retain: usize,
@@ -1799,6 +1870,7 @@ pub fn package_and_archive_full_snapshot(
     let snapshot_package = SnapshotPackage::from(accounts_package);
     archive_snapshot_package(
         &snapshot_package,
+        archive_compression,
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
     )?;
@@ -1818,6 +1890,7 @@ pub fn package_and_archive_incremental_snapshot(
     snapshot_archives_dir: impl AsRef<Path>,
     snapshot_storages: SnapshotStorages,
     archive_format: ArchiveFormat,
+    archive_compression: ArchiveCompressionOptions,
     snapshot_version: SnapshotVersion,
     maximum_full_snapshot_archives_to_retain: usize,
     maximum_incremental_snapshot_archives_to_retain: usize,
@@ -1840,6 +1913,7 @@ pub fn package_and_archive_incremental_snapshot(
     let snapshot_package = SnapshotPackage::from(accounts_package);
     archive_snapshot_package(
         &snapshot_package,
+        archive_compression,
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
     )?;
@@ -2054,6 +2128,14 @@ mod tests {
             .unwrap(),
             (43, Hash::default(), ArchiveFormat::TarZstd)
         );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-45-{}.tar.lz4",
+                Hash::default()
+            ))
+            .unwrap(),
+            (45, Hash::default(), ArchiveFormat::TarLz4)
+        );
         assert_eq!(
             parse_full_snapshot_archive_filename(&format!("snapshot-44-{}.tar", Hash::default()))
                 .unwrap(),
@@ -2665,6 +2747,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -2693,6 +2776,66 @@ mod tests {
         assert_eq!(original_bank, roundtrip_bank);
     }
 
+    /// Test roundtrip of bank to a full snapshot, then back again, for every archive format with
+    /// an explicit compression level and compression threads
+    #[test]
+    fn test_roundtrip_bank_to_and_from_full_snapshot_compression_options() {
+        solana_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new_for_tests(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        for (archive_format, level, num_threads) in [
+            (ArchiveFormat::TarBzip2, Some(1), 0),
+            (ArchiveFormat::TarGzip, Some(9), 0),
+            (ArchiveFormat::TarZstd, Some(3), 2),
+            (ArchiveFormat::TarLz4, Some(4), 0),
+            (ArchiveFormat::Tar, None, 0),
+        ] {
+            let accounts_dir = tempfile::TempDir::new().unwrap();
+            let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+
+            let snapshot_archive_info = bank_to_full_snapshot_archive(
+                &bank_snapshots_dir,
+                &original_bank,
+                None,
+                snapshot_archives_dir.path(),
+                archive_format,
+                ArchiveCompressionOptions { level, num_threads },
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+            assert_eq!(snapshot_archive_info.archive_format(), archive_format);
+
+            let (roundtrip_bank, _) = bank_from_snapshot_archives(
+                &[PathBuf::from(accounts_dir.path())],
+                bank_snapshots_dir.path(),
+                &snapshot_archive_info,
+                None,
+                &genesis_config,
+                None,
+                None,
+                AccountSecondaryIndexes::default(),
+                false,
+                None,
+                AccountShrinkThreshold::default(),
+                false,
+                false,
+                false,
+                Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(original_bank, roundtrip_bank);
+        }
+    }
+
     /// Test roundtrip of bank to a full snapshot, then back again.  This test is more involved
     /// than the simple version above; creating multiple banks over multiple slots and doing
     /// multiple transfers.  So this full snapshot should contain more data.
@@ -2756,6 +2899,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -2833,6 +2977,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -2866,6 +3011,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -2933,6 +3079,7 @@ mod tests {
             None,
             &snapshot_archives_dir,
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -2966,6 +3113,7 @@ mod tests {
             None,
             &snapshot_archives_dir,
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -3064,6 +3212,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -3104,6 +3253,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
@@ -3165,6 +3315,7 @@ mod tests {
             None,
             snapshot_archives_dir.path(),
             snapshot_archive_format,
+            ArchiveCompressionOptions::default(),
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         )
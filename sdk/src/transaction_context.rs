@@ -15,6 +15,29 @@ use std::{
 
 pub type TransactionAccount = (Pubkey, AccountSharedData);
 
+/// The data returned by the last instruction of a transaction that set any, along with the
+/// program that set it
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// A program invocation recorded during execution, along with the invocations it made
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvocationRecord {
+    pub program_id: Pubkey,
+    pub instruction_data: Vec<u8>,
+    /// 1 for the transaction's instructions, incremented by each cross-program invocation
+    pub stack_height: usize,
+    /// Compute units consumed by this invocation, including the ones it made
+    pub compute_units_consumed: u64,
+    /// Log messages emitted while this invocation was the innermost one
+    pub logs: Vec<String>,
+    pub result: Result<(), InstructionError>,
+    pub inner_invocations: Vec<InvocationRecord>,
+}
+
 #[derive(Clone, Debug)]
 pub struct InstructionAccount {
     pub index_in_transaction: usize,
@@ -227,6 +250,19 @@ impl TransactionContext {
         (&self.return_data.0, &self.return_data.1)
     }
 
+    /// Returns the return data left by the transaction, if any instruction set some
+    pub fn get_transaction_return_data(&self) -> Option<TransactionReturnData> {
+        let (program_id, data) = self.get_return_data();
+        if data.is_empty() {
+            None
+        } else {
+            Some(TransactionReturnData {
+                program_id: *program_id,
+                data: data.to_vec(),
+            })
+        }
+    }
+
     /// Set the return data of the current InstructionContext
     pub fn set_return_data(
         &mut self,
@@ -819,6 +819,8 @@ mod tests {
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
                 loaded_addresses: LoadedAddresses::default(),
+                return_data: None,
+                compute_units_consumed: None,
             }),
         };
         let block = ConfirmedBlock {
@@ -1,5 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
 use {
+    async_trait::async_trait,
     log::*,
     serde::{Deserialize, Serialize},
     solana_metrics::inc_new_counter_debug,
@@ -22,6 +23,10 @@ use {
     std::{
         collections::{HashMap, HashSet},
         convert::TryInto,
+        fmt,
+        str::FromStr,
+        sync::Arc,
+        time::Duration,
     },
     thiserror::Error,
 };
@@ -35,13 +40,30 @@ extern crate serde_derive;
 mod access_token;
 mod bigtable;
 mod compression;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod root_ca_certificate;
+#[cfg(feature = "s3")]
+mod s3;
+
+#[cfg(feature = "postgres")]
+pub use crate::postgres::PostgresLedgerStorage;
+#[cfg(feature = "s3")]
+pub use crate::s3::S3LedgerStorage;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
     BigTableError(bigtable::Error),
 
+    #[cfg(feature = "s3")]
+    #[error("S3: {0}")]
+    S3Error(s3::Error),
+
+    #[cfg(feature = "postgres")]
+    #[error("PostgreSQL: {0}")]
+    PostgresError(postgres::Error),
+
     #[error("I/O Error: {0}")]
     IoError(std::io::Error),
 
@@ -61,6 +83,20 @@ impl std::convert::From<bigtable::Error> for Error {
     }
 }
 
+#[cfg(feature = "s3")]
+impl std::convert::From<s3::Error> for Error {
+    fn from(err: s3::Error) -> Self {
+        Self::S3Error(err)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl std::convert::From<postgres::Error> for Error {
+    fn from(err: postgres::Error) -> Self {
+        Self::PostgresError(err)
+    }
+}
+
 impl std::convert::From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
         Self::IoError(err)
@@ -218,6 +254,8 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             post_token_balances: None,
             rewards: None,
             loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
         }
     }
 }
@@ -341,12 +379,283 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+/// Long-term storage of confirmed blocks, and of the indexes needed to serve transaction history
+/// queries of the blocks that have been purged from the blockstore
+#[async_trait]
+pub trait LedgerStorage: Send + Sync {
+    /// Return the available slot that contains a block
+    async fn get_first_available_block(&self) -> Result<Option<Slot>>;
+
+    /// Fetch the next slots after the provided slot that contains a block
+    ///
+    /// start_slot: slot to start the search from (inclusive)
+    /// limit: stop after this many slots have been found; if limit==0, all records in the table
+    /// after start_slot will be read
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>>;
+
+    /// Fetch the confirmed block from the desired slot
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<VersionedConfirmedBlock>;
+
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus>;
+
+    /// Fetch a confirmed transaction
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<VersionedConfirmedTransactionWithStatusMeta>>;
+
+    /// Get confirmed signatures for the provided address, in descending ledger order
+    ///
+    /// address: address to search for
+    /// before_signature: start with the first signature older than this one
+    /// until_signature: end with the last signature more recent than this one
+    /// limit: stop after this many signatures; if limit==0, all records in the table will be read
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    >;
+
+    // Upload a new confirmed block and associated meta data.
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlock,
+    ) -> Result<()>;
+
+    // Delete a confirmed block and associated meta data.
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()>;
+}
+
+/// The backend of a `LedgerStorage`, as selected on the command line:
+///
+/// * `bigtable`: the `solana-ledger` BigTable instance of the project named by the
+///   `GOOGLE_APPLICATION_CREDENTIALS` environment variable
+/// * `s3://BUCKET`: an S3 compatible bucket, in the region of the `AWS_DEFAULT_REGION` or
+///   `AWS_REGION` environment variables, or served at `AWS_ENDPOINT_URL` if it is set
+/// * `postgres://...` or `postgresql://...`: a PostgreSQL database
+///
+/// The S3 and PostgreSQL backends are only available with the `s3` and `postgres` features.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LedgerStorageConfig {
+    BigTable {
+        credential_path: Option<String>,
+    },
+    #[cfg(feature = "s3")]
+    S3 {
+        bucket: String,
+    },
+    #[cfg(feature = "postgres")]
+    Postgres {
+        connection_string: String,
+    },
+}
+
+impl Default for LedgerStorageConfig {
+    fn default() -> Self {
+        Self::BigTable {
+            credential_path: None,
+        }
+    }
+}
+
+impl fmt::Display for LedgerStorageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BigTable { .. } => write!(f, "BigTable"),
+            #[cfg(feature = "s3")]
+            Self::S3 { bucket } => write!(f, "S3 bucket {}", bucket),
+            // The connection string may contain a password
+            #[cfg(feature = "postgres")]
+            Self::Postgres { .. } => write!(f, "PostgreSQL"),
+        }
+    }
+}
+
+impl FromStr for LedgerStorageConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s == "bigtable" {
+            Ok(Self::default())
+        } else if let Some(bucket) = s.strip_prefix("s3://") {
+            Self::parse_s3(s, bucket)
+        } else if s.starts_with("postgres://") || s.starts_with("postgresql://") {
+            Self::parse_postgres(s)
+        } else {
+            Err(format!(
+                "Unknown ledger storage: {}, expected bigtable, s3://BUCKET or postgres://...",
+                s
+            ))
+        }
+    }
+}
+
+impl LedgerStorageConfig {
+    #[cfg(feature = "s3")]
+    fn parse_s3(s: &str, bucket: &str) -> std::result::Result<Self, String> {
+        let bucket = bucket.trim_end_matches('/');
+        if bucket.is_empty() || bucket.contains('/') {
+            return Err(format!("Invalid S3 bucket: {}", s));
+        }
+        Ok(Self::S3 {
+            bucket: bucket.to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "s3"))]
+    fn parse_s3(_s: &str, _bucket: &str) -> std::result::Result<Self, String> {
+        Err("S3 ledger storage is not supported by this build, \
+             it requires the `s3` feature of solana-storage-bigtable"
+            .to_string())
+    }
+
+    #[cfg(feature = "postgres")]
+    fn parse_postgres(s: &str) -> std::result::Result<Self, String> {
+        Ok(Self::Postgres {
+            connection_string: s.to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    fn parse_postgres(_s: &str) -> std::result::Result<Self, String> {
+        Err("PostgreSQL ledger storage is not supported by this build, \
+             it requires the `postgres` feature of solana-storage-bigtable"
+            .to_string())
+    }
+
+    /// Connects to the configured backend. Read-only connections never write to the backend,
+    /// read-write ones create the tables the backend needs if they don't exist.
+    pub async fn connect(
+        &self,
+        read_only: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Arc<dyn LedgerStorage>> {
+        Ok(match self {
+            Self::BigTable { credential_path } => Arc::new(
+                BigTableLedgerStorage::new(read_only, timeout, credential_path.clone()).await?,
+            ),
+            #[cfg(feature = "s3")]
+            Self::S3 { bucket } => Arc::new(S3LedgerStorage::new(bucket, timeout)),
+            #[cfg(feature = "postgres")]
+            Self::Postgres { connection_string } => {
+                Arc::new(PostgresLedgerStorage::new(connection_string, read_only).await?)
+            }
+        })
+    }
+}
+
+// Objects of the `blocks` and `tx-by-addr` tables are stored as compressed protobuf, and those of
+// the `tx` table as compressed bincode, by every `LedgerStorage`
+fn serialize_protobuf<T: prost::Message>(data: &T) -> std::io::Result<Vec<u8>> {
+    compression::compress_best(&data.encode_to_vec())
+}
+
+fn deserialize_protobuf<T: prost::Message + Default>(data: &[u8]) -> Option<T> {
+    let data = compression::decompress(data).ok()?;
+    T::decode(&data[..]).ok()
+}
+
+fn serialize_bincode<T: serde::Serialize>(data: &T) -> std::io::Result<Vec<u8>> {
+    compression::compress_best(&bincode::serialize(data).unwrap())
+}
+
+fn deserialize_bincode<T: serde::de::DeserializeOwned>(data: &[u8]) -> Option<T> {
+    let data = compression::decompress(data).ok()?;
+    bincode::deserialize(&data).ok()
+}
+
+/// The transactions of a block, and the transactions each address of the block was involved in,
+/// as indexed in the `tx` and `tx-by-addr` tables
+fn index_confirmed_block(
+    slot: Slot,
+    confirmed_block: &VersionedConfirmedBlock,
+) -> (
+    Vec<(Signature, TransactionInfo)>,
+    HashMap<&Pubkey, Vec<TransactionByAddrInfo>>,
+) {
+    let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
+    let mut tx_infos = vec![];
+    for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+        let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
+        let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
+        let index = index as u32;
+        let signature = transaction.signatures[0];
+        let memo = extract_and_fmt_memos(transaction_with_meta);
+
+        for address in transaction_with_meta.account_keys().iter() {
+            if !is_sysvar_id(address) {
+                by_addr
+                    .entry(address)
+                    .or_default()
+                    .push(TransactionByAddrInfo {
+                        signature,
+                        err: err.clone(),
+                        index,
+                        memo: memo.clone(),
+                        block_time: confirmed_block.block_time,
+                    });
+            }
+        }
+
+        tx_infos.push((
+            signature,
+            TransactionInfo {
+                slot,
+                index,
+                err,
+                memo,
+            },
+        ));
+    }
+    (tx_infos, by_addr)
+}
+
+/// Picks the transaction at `index` of the block of `slot`, checking that it is the transaction
+/// indexed under `signature`
+fn find_confirmed_transaction(
+    signature: &Signature,
+    slot: Slot,
+    index: u32,
+    block: VersionedConfirmedBlock,
+) -> Option<VersionedConfirmedTransactionWithStatusMeta> {
+    match block.transactions.into_iter().nth(index as usize) {
+        None => {
+            // report this somewhere actionable?
+            warn!("Transaction info for {} is corrupt", signature);
+            None
+        }
+        Some(bucket_block_transaction) => {
+            if bucket_block_transaction.transaction.signatures[0] != *signature {
+                warn!(
+                    "Transaction info or confirmed block for {} is corrupt",
+                    signature
+                );
+                None
+            } else {
+                Some(VersionedConfirmedTransactionWithStatusMeta {
+                    slot,
+                    tx_with_meta: bucket_block_transaction,
+                    block_time: block.block_time,
+                })
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct LedgerStorage {
+pub struct BigTableLedgerStorage {
     connection: bigtable::BigTableConnection,
 }
 
-impl LedgerStorage {
+impl BigTableLedgerStorage {
     pub async fn new(
         read_only: bool,
         timeout: Option<std::time::Duration>,
@@ -357,9 +666,11 @@ impl LedgerStorage {
                 .await?;
         Ok(Self { connection })
     }
+}
 
-    /// Return the available slot that contains a block
-    pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
+#[async_trait]
+impl LedgerStorage for BigTableLedgerStorage {
+    async fn get_first_available_block(&self) -> Result<Option<Slot>> {
         debug!("LedgerStorage::get_first_available_block request received");
         inc_new_counter_debug!("storage-bigtable-query", 1);
         let mut bigtable = self.connection.client();
@@ -370,12 +681,7 @@ impl LedgerStorage {
         Ok(key_to_slot(&blocks[0]))
     }
 
-    /// Fetch the next slots after the provided slot that contains a block
-    ///
-    /// start_slot: slot to start the search from (inclusive)
-    /// limit: stop after this many slots have been found; if limit==0, all records in the table
-    /// after start_slot will be read
-    pub async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
+    async fn get_confirmed_blocks(&self, start_slot: Slot, limit: usize) -> Result<Vec<Slot>> {
         debug!(
             "LedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
             start_slot, limit
@@ -393,8 +699,7 @@ impl LedgerStorage {
         Ok(blocks.into_iter().filter_map(|s| key_to_slot(&s)).collect())
     }
 
-    /// Fetch the confirmed block from the desired slot
-    pub async fn get_confirmed_block(&self, slot: Slot) -> Result<VersionedConfirmedBlock> {
+    async fn get_confirmed_block(&self, slot: Slot) -> Result<VersionedConfirmedBlock> {
         debug!(
             "LedgerStorage::get_confirmed_block request received: {:?}",
             slot
@@ -419,7 +724,7 @@ impl LedgerStorage {
         })
     }
 
-    pub async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
+    async fn get_signature_status(&self, signature: &Signature) -> Result<TransactionStatus> {
         debug!(
             "LedgerStorage::get_signature_status request received: {:?}",
             signature
@@ -436,8 +741,7 @@ impl LedgerStorage {
         Ok(transaction_info.into())
     }
 
-    /// Fetch a confirmed transaction
-    pub async fn get_confirmed_transaction(
+    async fn get_confirmed_transaction(
         &self,
         signature: &Signature,
     ) -> Result<Option<VersionedConfirmedTransactionWithStatusMeta>> {
@@ -459,37 +763,10 @@ impl LedgerStorage {
 
         // Load the block and return the transaction
         let block = self.get_confirmed_block(slot).await?;
-        match block.transactions.into_iter().nth(index as usize) {
-            None => {
-                // report this somewhere actionable?
-                warn!("Transaction info for {} is corrupt", signature);
-                Ok(None)
-            }
-            Some(bucket_block_transaction) => {
-                if bucket_block_transaction.transaction.signatures[0] != *signature {
-                    warn!(
-                        "Transaction info or confirmed block for {} is corrupt",
-                        signature
-                    );
-                    Ok(None)
-                } else {
-                    Ok(Some(VersionedConfirmedTransactionWithStatusMeta {
-                        slot,
-                        tx_with_meta: bucket_block_transaction,
-                        block_time: block.block_time,
-                    }))
-                }
-            }
-        }
+        Ok(find_confirmed_transaction(signature, slot, index, block))
     }
 
-    /// Get confirmed signatures for the provided address, in descending ledger order
-    ///
-    /// address: address to search for
-    /// before_signature: start with the first signature older than this one
-    /// until_signature: end with the last signature more recent than this one
-    /// limit: stop after this many signatures; if limit==0, all records in the table will be read
-    pub async fn get_confirmed_signatures_for_address(
+    async fn get_confirmed_signatures_for_address(
         &self,
         address: &Pubkey,
         before_signature: Option<&Signature>,
@@ -625,49 +902,18 @@ impl LedgerStorage {
         Ok(infos)
     }
 
-    // Upload a new confirmed block and associated meta data.
-    pub async fn upload_confirmed_block(
+    async fn upload_confirmed_block(
         &self,
         slot: Slot,
         confirmed_block: VersionedConfirmedBlock,
     ) -> Result<()> {
         let mut bytes_written = 0;
 
-        let mut by_addr: HashMap<&Pubkey, Vec<TransactionByAddrInfo>> = HashMap::new();
-
-        let mut tx_cells = vec![];
-        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
-            let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
-            let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
-            let index = index as u32;
-            let signature = transaction.signatures[0];
-            let memo = extract_and_fmt_memos(transaction_with_meta);
-
-            for address in transaction_with_meta.account_keys().iter() {
-                if !is_sysvar_id(address) {
-                    by_addr
-                        .entry(address)
-                        .or_default()
-                        .push(TransactionByAddrInfo {
-                            signature,
-                            err: err.clone(),
-                            index,
-                            memo: memo.clone(),
-                            block_time: confirmed_block.block_time,
-                        });
-                }
-            }
-
-            tx_cells.push((
-                signature.to_string(),
-                TransactionInfo {
-                    slot,
-                    index,
-                    err,
-                    memo,
-                },
-            ));
-        }
+        let (tx_infos, by_addr) = index_confirmed_block(slot, &confirmed_block);
+        let tx_cells: Vec<_> = tx_infos
+            .into_iter()
+            .map(|(signature, tx_info)| (signature.to_string(), tx_info))
+            .collect();
 
         let tx_by_addr_cells: Vec<_> = by_addr
             .into_iter()
@@ -719,8 +965,7 @@ impl LedgerStorage {
         Ok(())
     }
 
-    // Delete a confirmed block and associated meta data.
-    pub async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
         let mut addresses: HashSet<&Pubkey> = HashSet::new();
         let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
         let confirmed_block = self.get_confirmed_block(slot).await?;
@@ -830,4 +1075,37 @@ mod test {
         assert_eq!(slot_to_key(0), "0000000000000000");
         assert_eq!(slot_to_key(!0), "ffffffffffffffff");
     }
+
+    #[test]
+    fn test_ledger_storage_config_from_str() {
+        assert_eq!(
+            "bigtable".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::default())
+        );
+        assert!("s3://".parse::<LedgerStorageConfig>().is_err());
+        assert!("s3://ledger/blocks".parse::<LedgerStorageConfig>().is_err());
+        assert!("mysql://localhost".parse::<LedgerStorageConfig>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "s3")]
+    fn test_ledger_storage_config_from_str_s3() {
+        assert_eq!(
+            "s3://ledger/".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::S3 {
+                bucket: "ledger".to_string()
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn test_ledger_storage_config_from_str_postgres() {
+        assert_eq!(
+            "postgresql://localhost/ledger".parse::<LedgerStorageConfig>(),
+            Ok(LedgerStorageConfig::Postgres {
+                connection_string: "postgresql://localhost/ledger".to_string()
+            })
+        );
+    }
 }
@@ -0,0 +1,424 @@
+// Long-term ledger storage in a PostgreSQL database. The rows of the BigTable tables are stored,
+// encoded the same way, in the `ledger_block`, `ledger_transaction` and
+// `ledger_transaction_by_address` tables, keyed by slot, signature and address and slot.
+
+use {
+    crate::{
+        deserialize_bincode, deserialize_protobuf, find_confirmed_transaction,
+        index_confirmed_block, serialize_bincode, serialize_protobuf, LedgerStorage,
+        TransactionInfo,
+    },
+    async_trait::async_trait,
+    log::*,
+    solana_metrics::inc_new_counter_debug,
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_storage_proto::convert::{generated, tx_by_addr},
+    solana_transaction_status::{
+        ConfirmedTransactionStatusWithSignature, TransactionByAddrInfo, TransactionStatus,
+        VersionedConfirmedBlock, VersionedConfirmedTransactionWithStatusMeta,
+    },
+    std::{convert::TryInto, sync::Arc},
+    thiserror::Error,
+    tokio_postgres::{Client, NoTls},
+};
+
+const CREATE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS ledger_block (
+    slot BIGINT PRIMARY KEY,
+    block BYTEA NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ledger_transaction (
+    signature VARCHAR(88) PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    info BYTEA NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ledger_transaction_slot ON ledger_transaction (slot);
+CREATE TABLE IF NOT EXISTS ledger_transaction_by_address (
+    address VARCHAR(44) NOT NULL,
+    slot BIGINT NOT NULL,
+    tx_by_addr BYTEA NOT NULL,
+    PRIMARY KEY (address, slot)
+);
+CREATE INDEX IF NOT EXISTS ledger_transaction_by_address_slot
+    ON ledger_transaction_by_address (slot);
+";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Connection: {0}")]
+    Connection(tokio_postgres::Error),
+
+    #[error("Row is corrupt: {0}")]
+    RowCorrupt(String),
+}
+
+impl std::convert::From<tokio_postgres::Error> for Error {
+    fn from(err: tokio_postgres::Error) -> Self {
+        Self::Connection(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone)]
+pub struct PostgresLedgerStorage {
+    client: Arc<Client>,
+}
+
+impl PostgresLedgerStorage {
+    /// Connects to the database of `connection_string`, which is either a `postgres://` URL or a
+    /// list of `key=value` settings. The tables are created if they don't exist, unless
+    /// `read_only` is set. Connections are not encrypted.
+    pub async fn new(connection_string: &str, read_only: bool) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(connection_string, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                error!("PostgreSQL ledger storage connection failed: {}", err);
+            }
+        });
+        if !read_only {
+            client.batch_execute(CREATE_SCHEMA).await?;
+        }
+        Ok(Self {
+            client: Arc::new(client),
+        })
+    }
+
+    async fn get_transaction_info(&self, signature: &Signature) -> crate::Result<TransactionInfo> {
+        let signature = signature.to_string();
+        let row = self
+            .client
+            .query_opt(
+                "SELECT info FROM ledger_transaction WHERE signature = $1",
+                &[&signature],
+            )
+            .await
+            .map_err(Error::from)?
+            .ok_or(crate::Error::SignatureNotFound)?;
+        Ok(deserialize_bincode(row.get(0))
+            .ok_or_else(|| Error::RowCorrupt(format!("ledger_transaction/{}", signature)))?)
+    }
+}
+
+#[async_trait]
+impl LedgerStorage for PostgresLedgerStorage {
+    async fn get_first_available_block(&self) -> crate::Result<Option<Slot>> {
+        debug!("PostgresLedgerStorage::get_first_available_block request received");
+        inc_new_counter_debug!("storage-postgres-query", 1);
+        let row = self
+            .client
+            .query_opt("SELECT MIN(slot) FROM ledger_block", &[])
+            .await
+            .map_err(Error::from)?;
+        Ok(row
+            .and_then(|row| row.get::<_, Option<i64>>(0))
+            .map(|slot| slot as Slot))
+    }
+
+    async fn get_confirmed_blocks(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> crate::Result<Vec<Slot>> {
+        debug!(
+            "PostgresLedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
+            start_slot, limit
+        );
+        inc_new_counter_debug!("storage-postgres-query", 1);
+        // LIMIT NULL doesn't limit the number of rows
+        let limit = (limit > 0).then(|| limit as i64);
+        let rows = self
+            .client
+            .query(
+                "SELECT slot FROM ledger_block WHERE slot >= $1 ORDER BY slot LIMIT $2",
+                &[&(start_slot as i64), &limit],
+            )
+            .await
+            .map_err(Error::from)?;
+        Ok(rows
+            .iter()
+            .map(|row| row.get::<_, i64>(0) as Slot)
+            .collect())
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> crate::Result<VersionedConfirmedBlock> {
+        debug!(
+            "PostgresLedgerStorage::get_confirmed_block request received: {:?}",
+            slot
+        );
+        inc_new_counter_debug!("storage-postgres-query", 1);
+        let row = self
+            .client
+            .query_opt(
+                "SELECT block FROM ledger_block WHERE slot = $1",
+                &[&(slot as i64)],
+            )
+            .await
+            .map_err(Error::from)?
+            .ok_or(crate::Error::BlockNotFound(slot))?;
+        let corrupt = || Error::RowCorrupt(format!("ledger_block/{}", slot));
+        let block =
+            deserialize_protobuf::<generated::ConfirmedBlock>(row.get(0)).ok_or_else(corrupt)?;
+        Ok(block.try_into().map_err(|_err| corrupt())?)
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> crate::Result<TransactionStatus> {
+        debug!(
+            "PostgresLedgerStorage::get_signature_status request received: {:?}",
+            signature
+        );
+        inc_new_counter_debug!("storage-postgres-query", 1);
+        Ok(self.get_transaction_info(signature).await?.into())
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> crate::Result<Option<VersionedConfirmedTransactionWithStatusMeta>> {
+        debug!(
+            "PostgresLedgerStorage::get_confirmed_transaction request received: {:?}",
+            signature
+        );
+        inc_new_counter_debug!("storage-postgres-query", 1);
+        let TransactionInfo { slot, index, .. } = self.get_transaction_info(signature).await?;
+        let block = self.get_confirmed_block(slot).await?;
+        Ok(find_confirmed_transaction(signature, slot, index, block))
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> crate::Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    > {
+        debug!(
+            "PostgresLedgerStorage::get_confirmed_signatures_for_address request received: {:?}",
+            address
+        );
+        inc_new_counter_debug!("storage-postgres-query", 1);
+
+        // Figure out where to start listing from based on `before_signature`
+        let (first_slot, before_transaction_index) = match before_signature {
+            None => (Slot::MAX, 0),
+            Some(before_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(before_signature).await?;
+                (slot, index)
+            }
+        };
+
+        // Figure out where to end listing from based on `until_signature`
+        let (last_slot, until_transaction_index) = match until_signature {
+            None => (0, u32::MAX),
+            Some(until_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(until_signature).await?;
+                (slot, index)
+            }
+        };
+
+        // Every row holds at least one transaction, and only the row of `first_slot` may have
+        // all of its transactions filtered out
+        let row_limit = (limit > 0).then(|| limit as i64 + 1);
+        let rows = self
+            .client
+            .query(
+                "SELECT slot, tx_by_addr FROM ledger_transaction_by_address \
+                 WHERE address = $1 AND slot <= $2 AND slot >= $3 \
+                 ORDER BY slot DESC LIMIT $4",
+                &[
+                    &address.to_string(),
+                    &(first_slot.min(i64::MAX as Slot) as i64),
+                    &(last_slot as i64),
+                    &row_limit,
+                ],
+            )
+            .await
+            .map_err(Error::from)?;
+
+        let mut infos = vec![];
+        'outer: for row in rows {
+            let slot = row.get::<_, i64>(0) as Slot;
+            let mut cell_data: Vec<TransactionByAddrInfo> =
+                deserialize_protobuf::<tx_by_addr::TransactionByAddr>(row.get(1))
+                    .and_then(|tx_by_addr| tx_by_addr.try_into().ok())
+                    .ok_or_else(|| {
+                        Error::RowCorrupt(format!(
+                            "ledger_transaction_by_address/{}/{}",
+                            address, slot
+                        ))
+                    })?;
+
+            cell_data.reverse();
+            for tx_by_addr_info in cell_data.into_iter() {
+                // Filter out records before `before_transaction_index`
+                if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
+                    continue;
+                }
+                // Filter out records after `until_transaction_index`
+                if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
+                    continue;
+                }
+                infos.push((
+                    ConfirmedTransactionStatusWithSignature {
+                        signature: tx_by_addr_info.signature,
+                        slot,
+                        err: tx_by_addr_info.err,
+                        memo: tx_by_addr_info.memo,
+                        block_time: tx_by_addr_info.block_time,
+                    },
+                    tx_by_addr_info.index,
+                ));
+                // Respect limit
+                if infos.len() >= limit {
+                    break 'outer;
+                }
+            }
+        }
+        Ok(infos)
+    }
+
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlock,
+    ) -> crate::Result<()> {
+        let mut bytes_written = 0;
+        let (tx_infos, by_addr) = index_confirmed_block(slot, &confirmed_block);
+
+        let mut tx_rows = vec![];
+        for (signature, tx_info) in tx_infos {
+            let info = serialize_bincode(&tx_info)?;
+            bytes_written += info.len();
+            tx_rows.push((signature.to_string(), info));
+        }
+        let mut tx_by_addr_rows = vec![];
+        for (address, transaction_info_by_addr) in by_addr {
+            let tx_by_addr = serialize_protobuf(&tx_by_addr::TransactionByAddr {
+                tx_by_addrs: transaction_info_by_addr
+                    .into_iter()
+                    .map(|by_addr| by_addr.into())
+                    .collect(),
+            })?;
+            bytes_written += tx_by_addr.len();
+            tx_by_addr_rows.push((address.to_string(), tx_by_addr));
+        }
+
+        // Queries sent concurrently are pipelined over the connection
+        let slot_param = slot as i64;
+        futures::future::try_join_all(tx_rows.iter().map(|(signature, info)| async move {
+            self.client
+                .execute(
+                    "INSERT INTO ledger_transaction (signature, slot, info) VALUES ($1, $2, $3) \
+                     ON CONFLICT (signature) DO UPDATE SET slot = $2, info = $3",
+                    &[signature, &slot_param, info],
+                )
+                .await
+        }))
+        .await
+        .map_err(Error::from)?;
+        futures::future::try_join_all(tx_by_addr_rows.iter().map(
+            |(address, tx_by_addr)| async move {
+                self.client
+                    .execute(
+                        "INSERT INTO ledger_transaction_by_address (address, slot, tx_by_addr) \
+                         VALUES ($1, $2, $3) \
+                         ON CONFLICT (address, slot) DO UPDATE SET tx_by_addr = $3",
+                        &[address, &slot_param, tx_by_addr],
+                    )
+                    .await
+            },
+        ))
+        .await
+        .map_err(Error::from)?;
+
+        let num_transactions = confirmed_block.transactions.len();
+
+        // Store the block itself last, after all other metadata about the block has been
+        // successfully stored.  This avoids partial uploaded blocks from becoming visible to
+        // `get_confirmed_block()` and `get_confirmed_blocks()`
+        let block: generated::ConfirmedBlock = confirmed_block.into();
+        let block = serialize_protobuf(&block)?;
+        bytes_written += block.len();
+        self.client
+            .execute(
+                "INSERT INTO ledger_block (slot, block) VALUES ($1, $2) \
+                 ON CONFLICT (slot) DO UPDATE SET block = $2",
+                &[&slot_param, &block],
+            )
+            .await
+            .map_err(Error::from)?;
+        info!(
+            "uploaded block for slot {}: {} transactions, {} bytes",
+            slot, num_transactions, bytes_written
+        );
+
+        Ok(())
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> crate::Result<()> {
+        // Make sure the block exists, like the other backends do
+        self.get_confirmed_block(slot).await?;
+
+        let slot_param = slot as i64;
+        let (num_tx_rows, num_address_slot_rows) = if dry_run {
+            let count = |table| {
+                let query = format!("SELECT COUNT(*) FROM {} WHERE slot = $1", table);
+                async move {
+                    self.client
+                        .query_one(query.as_str(), &[&slot_param])
+                        .await
+                        .map(|row| row.get::<_, i64>(0) as u64)
+                }
+            };
+            (
+                count("ledger_transaction").await.map_err(Error::from)?,
+                count("ledger_transaction_by_address")
+                    .await
+                    .map_err(Error::from)?,
+            )
+        } else {
+            // Only the transaction rows that still point at this block are deleted
+            let num_address_slot_rows = self
+                .client
+                .execute(
+                    "DELETE FROM ledger_transaction_by_address WHERE slot = $1",
+                    &[&slot_param],
+                )
+                .await
+                .map_err(Error::from)?;
+            let num_tx_rows = self
+                .client
+                .execute(
+                    "DELETE FROM ledger_transaction WHERE slot = $1",
+                    &[&slot_param],
+                )
+                .await
+                .map_err(Error::from)?;
+            self.client
+                .execute("DELETE FROM ledger_block WHERE slot = $1", &[&slot_param])
+                .await
+                .map_err(Error::from)?;
+            (num_tx_rows, num_address_slot_rows)
+        };
+
+        info!(
+            "{}deleted ledger data for slot {}: {} transaction rows, {} address slot rows",
+            if dry_run { "[dry run] " } else { "" },
+            slot,
+            num_tx_rows,
+            num_address_slot_rows
+        );
+
+        Ok(())
+    }
+}
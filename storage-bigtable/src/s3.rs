@@ -0,0 +1,497 @@
+// Long-term ledger storage in an S3 compatible bucket, with one object per row of the BigTable
+// tables: `blocks/<slot>`, `tx/<signature>` and `tx-by-addr/<address>/<!slot>`. Object keys sort
+// like the BigTable row keys, so the listings of the bucket are in the same order as the scans of
+// the tables.
+
+use {
+    crate::{
+        deserialize_bincode, deserialize_protobuf, find_confirmed_transaction,
+        index_confirmed_block, key_to_slot, serialize_bincode, serialize_protobuf,
+        slot_to_blocks_key, slot_to_tx_by_addr_key, LedgerStorage, TransactionInfo,
+        UploadedTransaction,
+    },
+    async_trait::async_trait,
+    log::*,
+    rusoto_core::{Region, RusotoError},
+    rusoto_s3::{
+        DeleteObjectRequest, GetObjectError, GetObjectRequest, ListObjectsV2Request,
+        PutObjectRequest, S3Client, S3,
+    },
+    solana_metrics::inc_new_counter_debug,
+    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_storage_proto::convert::{generated, tx_by_addr},
+    solana_transaction_status::{
+        ConfirmedTransactionStatusWithSignature, TransactionByAddrInfo, TransactionStatus,
+        VersionedConfirmedBlock, VersionedConfirmedTransactionWithStatusMeta,
+    },
+    std::{convert::TryInto, env, future::Future, time::Duration},
+    thiserror::Error,
+    tokio::io::AsyncReadExt,
+};
+
+// The most keys S3 returns from one listing
+const MAX_LIST_KEYS: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O: {0}")]
+    Io(std::io::Error),
+
+    #[error("Request failed: {0}")]
+    Request(String),
+
+    #[error("Object not found: {0}")]
+    ObjectNotFound(String),
+
+    #[error("Object is corrupt: {0}")]
+    ObjectCorrupt(String),
+
+    #[error("Timeout")]
+    Timeout,
+}
+
+impl std::convert::From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::convert::From<RusotoError<E>> for Error {
+    fn from(err: RusotoError<E>) -> Self {
+        Self::Request(err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone)]
+pub struct S3LedgerStorage {
+    client: S3Client,
+    bucket: String,
+    timeout: Option<Duration>,
+}
+
+impl S3LedgerStorage {
+    /// Uses the bucket named `bucket`, in the region of the `AWS_DEFAULT_REGION` or `AWS_REGION`
+    /// environment variables. The bucket is served at `AWS_ENDPOINT_URL` instead if it is set, to
+    /// support S3 compatible object stores.
+    ///
+    /// Credentials are looked up like the AWS command line does, from the environment, the
+    /// credentials file or the instance metadata.
+    pub fn new(bucket: &str, timeout: Option<Duration>) -> Self {
+        let region = match env::var("AWS_ENDPOINT_URL") {
+            Ok(endpoint) => Region::Custom {
+                name: Region::default().name().to_string(),
+                endpoint,
+            },
+            Err(_) => Region::default(),
+        };
+        info!("S3 ledger storage: bucket {} in {:?}", bucket, region);
+        Self {
+            client: S3Client::new(region),
+            bucket: bucket.to_string(),
+            timeout,
+        }
+    }
+
+    async fn with_timeout<T>(&self, request: impl Future<Output = Result<T>>) -> Result<T> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => request.await,
+        }
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>> {
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            ..GetObjectRequest::default()
+        };
+        self.with_timeout(async {
+            let output = self
+                .client
+                .get_object(request)
+                .await
+                .map_err(|err| match err {
+                    RusotoError::Service(GetObjectError::NoSuchKey(_)) => {
+                        Error::ObjectNotFound(key.to_string())
+                    }
+                    RusotoError::Unknown(ref response) if response.status.as_u16() == 404 => {
+                        Error::ObjectNotFound(key.to_string())
+                    }
+                    err => err.into(),
+                })?;
+            let mut data = vec![];
+            output
+                .body
+                .ok_or_else(|| Error::ObjectCorrupt(key.to_string()))?
+                .into_async_read()
+                .read_to_end(&mut data)
+                .await?;
+            Ok(data)
+        })
+        .await
+    }
+
+    async fn put_object(&self, key: String, data: Vec<u8>) -> Result<()> {
+        let request = PutObjectRequest {
+            bucket: self.bucket.clone(),
+            key,
+            body: Some(data.into()),
+            ..PutObjectRequest::default()
+        };
+        self.with_timeout(async {
+            self.client.put_object(request).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_object(&self, key: String) -> Result<()> {
+        let request = DeleteObjectRequest {
+            bucket: self.bucket.clone(),
+            key,
+            ..DeleteObjectRequest::default()
+        };
+        self.with_timeout(async {
+            self.client.delete_object(request).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Lists the keys starting with `prefix` that sort after `start_after`, in order. If
+    /// limit==0, every key after `start_after` is listed.
+    async fn list_keys(
+        &self,
+        prefix: &str,
+        start_after: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let mut keys = vec![];
+        let mut continuation_token = None;
+        loop {
+            let max_keys = if limit == 0 {
+                MAX_LIST_KEYS
+            } else {
+                (limit - keys.len()).min(MAX_LIST_KEYS)
+            };
+            let request = ListObjectsV2Request {
+                bucket: self.bucket.clone(),
+                prefix: Some(prefix.to_string()),
+                start_after: start_after.clone(),
+                continuation_token: continuation_token.take(),
+                max_keys: Some(max_keys as i64),
+                ..ListObjectsV2Request::default()
+            };
+            let output = self
+                .with_timeout(async { Ok(self.client.list_objects_v2(request).await?) })
+                .await?;
+            keys.extend(
+                output
+                    .contents
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|object| object.key),
+            );
+            if limit > 0 && keys.len() >= limit {
+                break;
+            }
+            match output.next_continuation_token {
+                Some(token) if output.is_truncated == Some(true) => {
+                    continuation_token = Some(token)
+                }
+                _ => break,
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn get_transaction_info(&self, signature: &Signature) -> crate::Result<TransactionInfo> {
+        let key = format!("tx/{}", signature);
+        let data = self.get_object(&key).await.map_err(|err| match err {
+            Error::ObjectNotFound(_) => crate::Error::SignatureNotFound,
+            _ => err.into(),
+        })?;
+        Ok(deserialize_bincode(&data).ok_or_else(|| Error::ObjectCorrupt(key))?)
+    }
+}
+
+fn blocks_key(slot: Slot) -> String {
+    format!("blocks/{}", slot_to_blocks_key(slot))
+}
+
+fn tx_by_addr_key(address: &Pubkey, slot: Slot) -> String {
+    format!("tx-by-addr/{}/{}", address, slot_to_tx_by_addr_key(slot))
+}
+
+#[async_trait]
+impl LedgerStorage for S3LedgerStorage {
+    async fn get_first_available_block(&self) -> crate::Result<Option<Slot>> {
+        debug!("S3LedgerStorage::get_first_available_block request received");
+        inc_new_counter_debug!("storage-s3-query", 1);
+        let keys = self.list_keys("blocks/", None, 1).await?;
+        Ok(keys
+            .first()
+            .and_then(|key| key_to_slot(&key["blocks/".len()..])))
+    }
+
+    async fn get_confirmed_blocks(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> crate::Result<Vec<Slot>> {
+        debug!(
+            "S3LedgerStorage::get_confirmed_blocks request received: {:?} {:?}",
+            start_slot, limit
+        );
+        inc_new_counter_debug!("storage-s3-query", 1);
+        let start_after = start_slot.checked_sub(1).map(blocks_key);
+        let keys = self.list_keys("blocks/", start_after, limit).await?;
+        Ok(keys
+            .iter()
+            .filter_map(|key| key_to_slot(&key["blocks/".len()..]))
+            .collect())
+    }
+
+    async fn get_confirmed_block(&self, slot: Slot) -> crate::Result<VersionedConfirmedBlock> {
+        debug!(
+            "S3LedgerStorage::get_confirmed_block request received: {:?}",
+            slot
+        );
+        inc_new_counter_debug!("storage-s3-query", 1);
+        let key = blocks_key(slot);
+        let data = self.get_object(&key).await.map_err(|err| match err {
+            Error::ObjectNotFound(_) => crate::Error::BlockNotFound(slot),
+            _ => err.into(),
+        })?;
+        let block = deserialize_protobuf::<generated::ConfirmedBlock>(&data)
+            .ok_or_else(|| Error::ObjectCorrupt(key.clone()))?;
+        Ok(block.try_into().map_err(|_err| Error::ObjectCorrupt(key))?)
+    }
+
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> crate::Result<TransactionStatus> {
+        debug!(
+            "S3LedgerStorage::get_signature_status request received: {:?}",
+            signature
+        );
+        inc_new_counter_debug!("storage-s3-query", 1);
+        Ok(self.get_transaction_info(signature).await?.into())
+    }
+
+    async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> crate::Result<Option<VersionedConfirmedTransactionWithStatusMeta>> {
+        debug!(
+            "S3LedgerStorage::get_confirmed_transaction request received: {:?}",
+            signature
+        );
+        inc_new_counter_debug!("storage-s3-query", 1);
+        let TransactionInfo { slot, index, .. } = self.get_transaction_info(signature).await?;
+        let block = self.get_confirmed_block(slot).await?;
+        Ok(find_confirmed_transaction(signature, slot, index, block))
+    }
+
+    async fn get_confirmed_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before_signature: Option<&Signature>,
+        until_signature: Option<&Signature>,
+        limit: usize,
+    ) -> crate::Result<
+        Vec<(
+            ConfirmedTransactionStatusWithSignature,
+            u32, /*slot index*/
+        )>,
+    > {
+        debug!(
+            "S3LedgerStorage::get_confirmed_signatures_for_address request received: {:?}",
+            address
+        );
+        inc_new_counter_debug!("storage-s3-query", 1);
+        let prefix = format!("tx-by-addr/{}/", address);
+
+        // Figure out where to start listing from based on `before_signature`
+        let (first_slot, before_transaction_index) = match before_signature {
+            None => (Slot::MAX, 0),
+            Some(before_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(before_signature).await?;
+                (slot, index)
+            }
+        };
+
+        // Figure out where to end listing from based on `until_signature`
+        let (last_slot, until_transaction_index) = match until_signature {
+            None => (0, u32::MAX),
+            Some(until_signature) => {
+                let TransactionInfo { slot, index, .. } =
+                    self.get_transaction_info(until_signature).await?;
+                (slot, index)
+            }
+        };
+
+        let mut infos = vec![];
+        // Keys sort in descending slot order, so listing after the key of the next slot starts
+        // at `first_slot`
+        let mut start_after = first_slot
+            .checked_add(1)
+            .map(|slot| tx_by_addr_key(address, slot));
+        'outer: loop {
+            let keys = self
+                .list_keys(&prefix, start_after.take(), MAX_LIST_KEYS)
+                .await?;
+            if keys.is_empty() {
+                break;
+            }
+            start_after = keys.last().cloned();
+
+            for key in keys {
+                let slot = !key_to_slot(&key[prefix.len()..])
+                    .ok_or_else(|| Error::ObjectCorrupt(key.clone()))?;
+                if slot < last_slot {
+                    break 'outer;
+                }
+                let data = self.get_object(&key).await?;
+                let mut cell_data: Vec<TransactionByAddrInfo> =
+                    deserialize_protobuf::<tx_by_addr::TransactionByAddr>(&data)
+                        .and_then(|tx_by_addr| tx_by_addr.try_into().ok())
+                        .ok_or_else(|| Error::ObjectCorrupt(key.clone()))?;
+
+                cell_data.reverse();
+                for tx_by_addr_info in cell_data.into_iter() {
+                    // Filter out records before `before_transaction_index`
+                    if slot == first_slot && tx_by_addr_info.index >= before_transaction_index {
+                        continue;
+                    }
+                    // Filter out records after `until_transaction_index`
+                    if slot == last_slot && tx_by_addr_info.index <= until_transaction_index {
+                        continue;
+                    }
+                    infos.push((
+                        ConfirmedTransactionStatusWithSignature {
+                            signature: tx_by_addr_info.signature,
+                            slot,
+                            err: tx_by_addr_info.err,
+                            memo: tx_by_addr_info.memo,
+                            block_time: tx_by_addr_info.block_time,
+                        },
+                        tx_by_addr_info.index,
+                    ));
+                    // Respect limit
+                    if infos.len() >= limit {
+                        break 'outer;
+                    }
+                }
+            }
+        }
+        Ok(infos)
+    }
+
+    async fn upload_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: VersionedConfirmedBlock,
+    ) -> crate::Result<()> {
+        let mut objects = vec![];
+        let (tx_infos, by_addr) = index_confirmed_block(slot, &confirmed_block);
+        for (signature, tx_info) in tx_infos {
+            objects.push((format!("tx/{}", signature), serialize_bincode(&tx_info)?));
+        }
+        for (address, transaction_info_by_addr) in by_addr {
+            let tx_by_addr = tx_by_addr::TransactionByAddr {
+                tx_by_addrs: transaction_info_by_addr
+                    .into_iter()
+                    .map(|by_addr| by_addr.into())
+                    .collect(),
+            };
+            objects.push((
+                tx_by_addr_key(address, slot),
+                serialize_protobuf(&tx_by_addr)?,
+            ));
+        }
+        let mut bytes_written: usize = objects.iter().map(|(_, data)| data.len()).sum();
+        futures::future::try_join_all(
+            objects
+                .into_iter()
+                .map(|(key, data)| self.put_object(key, data)),
+        )
+        .await?;
+
+        let num_transactions = confirmed_block.transactions.len();
+
+        // Store the block itself last, after all other metadata about the block has been
+        // successfully stored.  This avoids partial uploaded blocks from becoming visible to
+        // `get_confirmed_block()` and `get_confirmed_blocks()`
+        let block: generated::ConfirmedBlock = confirmed_block.into();
+        let block_data = serialize_protobuf(&block)?;
+        bytes_written += block_data.len();
+        self.put_object(blocks_key(slot), block_data).await?;
+        info!(
+            "uploaded block for slot {}: {} transactions, {} bytes",
+            slot, num_transactions, bytes_written
+        );
+
+        Ok(())
+    }
+
+    async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> crate::Result<()> {
+        let confirmed_block = self.get_confirmed_block(slot).await?;
+        let (tx_infos, by_addr) = index_confirmed_block(slot, &confirmed_block);
+        let address_slot_keys: Vec<_> = by_addr
+            .keys()
+            .map(|address| tx_by_addr_key(address, slot))
+            .collect();
+
+        // Only delete the transaction objects that still point at this block
+        let mut tx_keys = vec![];
+        for (signature, expected_tx_info) in tx_infos {
+            let expected_tx_info = UploadedTransaction::from(expected_tx_info);
+            match self.get_transaction_info(&signature).await {
+                Ok(tx_info) if UploadedTransaction::from(tx_info) == expected_tx_info => {
+                    tx_keys.push(format!("tx/{}", signature));
+                }
+                Ok(tx_info) => {
+                    warn!(
+                        "skipped tx object {} because it ({:?}) did not match to {:?}",
+                        signature,
+                        UploadedTransaction::from(tx_info),
+                        &expected_tx_info,
+                    );
+                }
+                Err(err) => {
+                    warn!("skipped tx object {}: {:?}", signature, err);
+                }
+            }
+        }
+
+        let num_tx_keys = tx_keys.len();
+        let num_address_slot_keys = address_slot_keys.len();
+        if !dry_run {
+            futures::future::try_join_all(
+                address_slot_keys
+                    .into_iter()
+                    .chain(tx_keys)
+                    .map(|key| self.delete_object(key)),
+            )
+            .await?;
+            self.delete_object(blocks_key(slot)).await?;
+        }
+
+        info!(
+            "{}deleted ledger data for slot {}: {} transaction objects, {} address slot objects",
+            if dry_run { "[dry run] " } else { "" },
+            slot,
+            num_tx_keys,
+            num_address_slot_keys
+        );
+
+        Ok(())
+    }
+}
@@ -12,11 +12,12 @@ use {
         pubkey::Pubkey,
         signature::Signature,
         transaction::{Transaction, TransactionError, VersionedTransaction},
+        transaction_context::TransactionReturnData,
     },
     solana_transaction_status::{
-        ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionByAddrInfo,
-        TransactionStatusMeta, TransactionTokenBalance, TransactionWithStatusMeta,
-        VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+        ConfirmedBlock, InnerInstructions, Reward, RewardType, TokenBalanceExtensions,
+        TransactionByAddrInfo, TransactionStatusMeta, TransactionTokenBalance,
+        TransactionWithStatusMeta, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
     },
     std::{
         convert::{TryFrom, TryInto},
@@ -381,6 +382,8 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             post_token_balances,
             rewards,
             loaded_addresses,
+            return_data,
+            compute_units_consumed,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -421,6 +424,11 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
             .collect();
+        let return_data = return_data.map(|return_data| return_data.into());
+        let compute_units_consumed =
+            compute_units_consumed.map(|compute_units_consumed| generated::ComputeUnitsConsumed {
+                compute_units_consumed,
+            });
 
         Self {
             err,
@@ -436,6 +444,8 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             rewards,
             loaded_writable_addresses,
             loaded_readonly_addresses,
+            return_data,
+            compute_units_consumed,
         }
     }
 }
@@ -465,6 +475,8 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             rewards,
             loaded_writable_addresses,
             loaded_readonly_addresses,
+            return_data,
+            compute_units_consumed,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -508,6 +520,9 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .map(|key| Pubkey::new(&key))
                 .collect(),
         };
+        let return_data = return_data.map(|return_data| return_data.into());
+        let compute_units_consumed =
+            compute_units_consumed.map(|compute_units| compute_units.compute_units_consumed);
         Ok(Self {
             status,
             fee,
@@ -519,10 +534,30 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             post_token_balances,
             rewards,
             loaded_addresses,
+            return_data,
+            compute_units_consumed,
         })
     }
 }
 
+impl From<TransactionReturnData> for generated::ReturnData {
+    fn from(value: TransactionReturnData) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::ReturnData> for TransactionReturnData {
+    fn from(value: generated::ReturnData) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
 impl From<InnerInstructions> for generated::InnerInstructions {
     fn from(value: InnerInstructions) -> Self {
         Self {
@@ -541,46 +576,91 @@ impl From<generated::InnerInstructions> for InnerInstructions {
     }
 }
 
+impl From<UiTokenAmount> for generated::UiTokenAmount {
+    fn from(value: UiTokenAmount) -> Self {
+        Self {
+            ui_amount: value.ui_amount.unwrap_or_default(),
+            decimals: value.decimals as u32,
+            amount: value.amount,
+            ui_amount_string: value.ui_amount_string,
+        }
+    }
+}
+
+impl From<generated::UiTokenAmount> for UiTokenAmount {
+    fn from(value: generated::UiTokenAmount) -> Self {
+        Self {
+            ui_amount: if (value.ui_amount - f64::default()).abs() > f64::EPSILON {
+                Some(value.ui_amount)
+            } else {
+                None
+            },
+            decimals: value.decimals as u8,
+            amount: value.amount.clone(),
+            ui_amount_string: if !value.ui_amount_string.is_empty() {
+                value.ui_amount_string
+            } else {
+                real_number_string_trimmed(
+                    u64::from_str(&value.amount).unwrap_or_default(),
+                    value.decimals as u8,
+                )
+            },
+        }
+    }
+}
+
+impl From<TokenBalanceExtensions> for generated::TokenBalanceExtensions {
+    fn from(value: TokenBalanceExtensions) -> Self {
+        Self {
+            withheld_transfer_fee: value.withheld_transfer_fee.map(|amount| amount.into()),
+            interest_bearing_ui_amount: value.interest_bearing_ui_amount.unwrap_or_default(),
+            confidential_credits_allowed: value.confidential_credits_allowed.map(
+                |confidential_credits_allowed| generated::ConfidentialCreditsAllowed {
+                    confidential_credits_allowed,
+                },
+            ),
+        }
+    }
+}
+
+impl From<generated::TokenBalanceExtensions> for TokenBalanceExtensions {
+    fn from(value: generated::TokenBalanceExtensions) -> Self {
+        Self {
+            withheld_transfer_fee: value.withheld_transfer_fee.map(|amount| amount.into()),
+            interest_bearing_ui_amount: if !value.interest_bearing_ui_amount.is_empty() {
+                Some(value.interest_bearing_ui_amount)
+            } else {
+                None
+            },
+            confidential_credits_allowed: value
+                .confidential_credits_allowed
+                .map(|allowed| allowed.confidential_credits_allowed),
+        }
+    }
+}
+
 impl From<TransactionTokenBalance> for generated::TokenBalance {
     fn from(value: TransactionTokenBalance) -> Self {
         Self {
             account_index: value.account_index as u32,
             mint: value.mint,
-            ui_token_amount: Some(generated::UiTokenAmount {
-                ui_amount: value.ui_token_amount.ui_amount.unwrap_or_default(),
-                decimals: value.ui_token_amount.decimals as u32,
-                amount: value.ui_token_amount.amount,
-                ui_amount_string: value.ui_token_amount.ui_amount_string,
-            }),
+            ui_token_amount: Some(value.ui_token_amount.into()),
             owner: value.owner,
+            program_id: value.program_id,
+            extensions: value.extensions.map(|extensions| extensions.into()),
         }
     }
 }
 
 impl From<generated::TokenBalance> for TransactionTokenBalance {
     fn from(value: generated::TokenBalance) -> Self {
-        let ui_token_amount = value.ui_token_amount.unwrap_or_default();
         Self {
             account_index: value.account_index as u8,
             mint: value.mint,
-            ui_token_amount: UiTokenAmount {
-                ui_amount: if (ui_token_amount.ui_amount - f64::default()).abs() > f64::EPSILON {
-                    Some(ui_token_amount.ui_amount)
-                } else {
-                    None
-                },
-                decimals: ui_token_amount.decimals as u8,
-                amount: ui_token_amount.amount.clone(),
-                ui_amount_string: if !ui_token_amount.ui_amount_string.is_empty() {
-                    ui_token_amount.ui_amount_string
-                } else {
-                    real_number_string_trimmed(
-                        u64::from_str(&ui_token_amount.amount).unwrap_or_default(),
-                        ui_token_amount.decimals as u8,
-                    )
-                },
-            },
+            ui_token_amount: value.ui_token_amount.unwrap_or_default().into(),
             owner: value.owner,
+            program_id: value.program_id,
+            extensions: value.extensions.map(|extensions| extensions.into()),
         }
     }
 }
@@ -6,9 +6,11 @@ use {
     },
     solana_sdk::{
         deserialize_utils::default_on_eof, message::v0::LoadedAddresses, transaction::Result,
+        transaction_context::TransactionReturnData,
     },
     solana_transaction_status::{
-        InnerInstructions, Reward, RewardType, TransactionStatusMeta, TransactionTokenBalance,
+        InnerInstructions, Reward, RewardType, TokenBalanceExtensions, TransactionStatusMeta,
+        TransactionTokenBalance,
     },
     std::str::FromStr,
 };
@@ -108,6 +110,43 @@ impl From<UiTokenAmount> for StoredTokenAmount {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct StoredTokenBalanceExtensions {
+    pub withheld_transfer_fee: Option<StoredTokenAmount>,
+    pub interest_bearing_ui_amount: Option<String>,
+    pub confidential_credits_allowed: Option<bool>,
+}
+
+impl From<StoredTokenBalanceExtensions> for TokenBalanceExtensions {
+    fn from(value: StoredTokenBalanceExtensions) -> Self {
+        let StoredTokenBalanceExtensions {
+            withheld_transfer_fee,
+            interest_bearing_ui_amount,
+            confidential_credits_allowed,
+        } = value;
+        Self {
+            withheld_transfer_fee: withheld_transfer_fee.map(|amount| amount.into()),
+            interest_bearing_ui_amount,
+            confidential_credits_allowed,
+        }
+    }
+}
+
+impl From<TokenBalanceExtensions> for StoredTokenBalanceExtensions {
+    fn from(value: TokenBalanceExtensions) -> Self {
+        let TokenBalanceExtensions {
+            withheld_transfer_fee,
+            interest_bearing_ui_amount,
+            confidential_credits_allowed,
+        } = value;
+        Self {
+            withheld_transfer_fee: withheld_transfer_fee.map(|amount| amount.into()),
+            interest_bearing_ui_amount,
+            confidential_credits_allowed,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct StoredTransactionTokenBalance {
     pub account_index: u8,
@@ -115,6 +154,10 @@ pub struct StoredTransactionTokenBalance {
     pub ui_token_amount: StoredTokenAmount,
     #[serde(deserialize_with = "default_on_eof")]
     pub owner: String,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub program_id: String,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub extensions: Option<StoredTokenBalanceExtensions>,
 }
 
 impl From<StoredTransactionTokenBalance> for TransactionTokenBalance {
@@ -124,12 +167,16 @@ impl From<StoredTransactionTokenBalance> for TransactionTokenBalance {
             mint,
             ui_token_amount,
             owner,
+            program_id,
+            extensions,
         } = value;
         Self {
             account_index,
             mint,
             ui_token_amount: ui_token_amount.into(),
             owner,
+            program_id,
+            extensions: extensions.map(|extensions| extensions.into()),
         }
     }
 }
@@ -141,12 +188,16 @@ impl From<TransactionTokenBalance> for StoredTransactionTokenBalance {
             mint,
             ui_token_amount,
             owner,
+            program_id,
+            extensions,
         } = value;
         Self {
             account_index,
             mint,
             ui_token_amount: ui_token_amount.into(),
             owner,
+            program_id,
+            extensions: extensions.map(|extensions| extensions.into()),
         }
     }
 }
@@ -167,6 +218,10 @@ pub struct StoredTransactionStatusMeta {
     pub post_token_balances: Option<Vec<StoredTransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Vec<StoredExtendedReward>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub compute_units_consumed: Option<u64>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -181,6 +236,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            compute_units_consumed,
         } = value;
         Self {
             status,
@@ -196,6 +253,8 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
             loaded_addresses: LoadedAddresses::default(),
+            return_data,
+            compute_units_consumed,
         }
     }
 }
@@ -214,6 +273,8 @@ impl TryFrom<TransactionStatusMeta> for StoredTransactionStatusMeta {
             post_token_balances,
             rewards,
             loaded_addresses,
+            return_data,
+            compute_units_consumed,
         } = value;
 
         if !loaded_addresses.is_empty() {
@@ -237,6 +298,8 @@ impl TryFrom<TransactionStatusMeta> for StoredTransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            compute_units_consumed,
         })
     }
 }
@@ -9,16 +9,19 @@ use {
     solana_sdk::{
         packet::{Packet, PACKET_DATA_SIZE},
         signature::Keypair,
+        timing::timestamp,
     },
     std::{
+        collections::HashMap,
         error::Error,
         net::{IpAddr, SocketAddr, UdpSocket},
+        str::FromStr,
         sync::{
-            atomic::{AtomicBool, Ordering},
-            Arc,
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
         },
         thread,
-        time::Duration,
+        time::{Duration, Instant},
     },
     tokio::{
         runtime::{Builder, Runtime},
@@ -124,6 +127,257 @@ pub fn rt() -> Runtime {
     Builder::new_current_thread().enable_all().build().unwrap()
 }
 
+pub const DEFAULT_MAX_QUIC_CONNECTIONS: usize = 2000;
+const CONNECTION_TABLE_STATS_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+// Every stream error costs a connection this many delivered packets worth of reputation
+const REPUTATION_ERROR_PENALTY: u64 = 10;
+
+/// Stake of the nodes allowed to hold QUIC connections, keyed by their TPU address
+pub type StakedNodes = Arc<RwLock<HashMap<IpAddr, u64>>>;
+
+/// Decides which connection is dropped when the connection table is full
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionEvictionPolicy {
+    /// Evict the connection with the lowest stake
+    Stake,
+    /// Evict the connection with the lowest stake, preferring the least recently active one
+    StakeAndRecency,
+    /// Evict the connection in the lowest stake tier with the lowest delivery score. Stakes
+    /// within a factor of two of each other share a tier (the bit length of the stake), and
+    /// the delivery score is `packets - 10 * errors`: delivered packets minus a penalty for
+    /// every stream error. Stake only separates connections across tiers, so the delivery
+    /// history decides between peers of similar stake.
+    Reputation,
+}
+
+impl Default for ConnectionEvictionPolicy {
+    fn default() -> Self {
+        Self::StakeAndRecency
+    }
+}
+
+impl ConnectionEvictionPolicy {
+    pub const STRINGS: [&'static str; 3] = ["stake", "stake-and-recency", "reputation"];
+}
+
+impl FromStr for ConnectionEvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stake" => Ok(Self::Stake),
+            "stake-and-recency" => Ok(Self::StakeAndRecency),
+            "reputation" => Ok(Self::Reputation),
+            _ => Err(format!("{} is not a connection eviction policy", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionTableConfig {
+    pub max_connections: usize,
+    pub eviction_policy: ConnectionEvictionPolicy,
+}
+
+impl Default for ConnectionTableConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_QUIC_CONNECTIONS,
+            eviction_policy: ConnectionEvictionPolicy::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ConnectionTableStats {
+    evicted_by_stake: AtomicU64,
+    evicted_by_recency: AtomicU64,
+    evicted_by_reputation: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl ConnectionTableStats {
+    fn report(&self, num_connections: usize) {
+        datapoint_info!(
+            "quic-connection-table",
+            ("connections", num_connections, i64),
+            (
+                "evicted_by_stake",
+                self.evicted_by_stake.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "evicted_by_recency",
+                self.evicted_by_recency.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "evicted_by_reputation",
+                self.evicted_by_reputation.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            ("rejected", self.rejected.swap(0, Ordering::Relaxed), i64),
+        );
+    }
+}
+
+#[derive(Default)]
+struct ConnectionActivity {
+    last_update: AtomicU64,
+    packets: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl ConnectionActivity {
+    fn new(now: u64) -> Self {
+        Self {
+            last_update: AtomicU64::new(now),
+            ..Self::default()
+        }
+    }
+
+    fn delivery_score(&self) -> u64 {
+        let errors = self.errors.load(Ordering::Relaxed);
+        self.packets
+            .load(Ordering::Relaxed)
+            .saturating_sub(errors.saturating_mul(REPUTATION_ERROR_PENALTY))
+    }
+}
+
+// Stakes within a factor of two of each other share a tier
+fn stake_tier(stake: u64) -> u64 {
+    u64::from(u64::BITS - stake.leading_zeros())
+}
+
+struct ConnectionEntry {
+    id: u64,
+    stake: u64,
+    exit: Arc<AtomicBool>,
+    activity: Arc<ConnectionActivity>,
+    connection: Option<quinn::Connection>,
+}
+
+impl ConnectionEntry {
+    // Lower keys are evicted first. The first element ranks the connection against a new
+    // one, the second breaks ties between existing connections.
+    fn eviction_key(&self, policy: ConnectionEvictionPolicy) -> ((u64, u64), u64) {
+        let last_update = self.activity.last_update.load(Ordering::Relaxed);
+        match policy {
+            ConnectionEvictionPolicy::Stake => ((self.stake, 0), 0),
+            ConnectionEvictionPolicy::StakeAndRecency => ((self.stake, 0), last_update),
+            ConnectionEvictionPolicy::Reputation => (
+                (stake_tier(self.stake), self.activity.delivery_score()),
+                last_update,
+            ),
+        }
+    }
+
+    fn close(&self) {
+        self.exit.store(true, Ordering::Relaxed);
+        if let Some(connection) = &self.connection {
+            connection.close(0u32.into(), b"evicted");
+        }
+    }
+}
+
+struct ConnectionTable {
+    config: ConnectionTableConfig,
+    table: HashMap<IpAddr, Vec<ConnectionEntry>>,
+    total_size: usize,
+    next_id: u64,
+    stats: Arc<ConnectionTableStats>,
+}
+
+impl ConnectionTable {
+    fn new(config: ConnectionTableConfig) -> Self {
+        Self {
+            config,
+            table: HashMap::default(),
+            total_size: 0,
+            next_id: 0,
+            stats: Arc::default(),
+        }
+    }
+
+    // Admits a new connection, evicting an existing one according to the configured policy
+    // if the table is full. Returns `None` if the new connection ranks below every existing
+    // connection and was rejected.
+    fn try_add(
+        &mut self,
+        addr: IpAddr,
+        stake: u64,
+        now: u64,
+        connection: Option<quinn::Connection>,
+    ) -> Option<(u64, Arc<AtomicBool>, Arc<ConnectionActivity>)> {
+        if self.total_size >= self.config.max_connections && !self.evict_one(stake) {
+            self.stats.rejected.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let exit = Arc::new(AtomicBool::new(false));
+        let activity = Arc::new(ConnectionActivity::new(now));
+        self.table.entry(addr).or_default().push(ConnectionEntry {
+            id,
+            stake,
+            exit: exit.clone(),
+            activity: activity.clone(),
+            connection,
+        });
+        self.total_size += 1;
+        Some((id, exit, activity))
+    }
+
+    // Evicts the lowest ranked connection unless it outranks a new connection of `new_stake`
+    fn evict_one(&mut self, new_stake: u64) -> bool {
+        let policy = self.config.eviction_policy;
+        let victim = self
+            .table
+            .iter()
+            .flat_map(|(addr, entries)| entries.iter().map(move |entry| (addr, entry)))
+            .min_by_key(|(_, entry)| entry.eviction_key(policy))
+            .map(|(addr, entry)| (*addr, entry.id, entry.eviction_key(policy).0, entry.stake));
+        let (addr, id, rank, victim_stake) = match victim {
+            Some(victim) => victim,
+            None => return false,
+        };
+        // A new connection has no delivery history, so its rank is its stake alone
+        let new_rank = match policy {
+            ConnectionEvictionPolicy::Stake | ConnectionEvictionPolicy::StakeAndRecency => {
+                (new_stake, 0)
+            }
+            ConnectionEvictionPolicy::Reputation => (stake_tier(new_stake), 0),
+        };
+        if rank > new_rank {
+            return false;
+        }
+        let counter = match policy {
+            ConnectionEvictionPolicy::Stake => &self.stats.evicted_by_stake,
+            ConnectionEvictionPolicy::StakeAndRecency if victim_stake < new_stake => {
+                &self.stats.evicted_by_stake
+            }
+            ConnectionEvictionPolicy::StakeAndRecency => &self.stats.evicted_by_recency,
+            ConnectionEvictionPolicy::Reputation => &self.stats.evicted_by_reputation,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(entry) = self.remove_entry(addr, id) {
+            entry.close();
+        }
+        true
+    }
+
+    fn remove_entry(&mut self, addr: IpAddr, id: u64) -> Option<ConnectionEntry> {
+        let entries = self.table.get_mut(&addr)?;
+        let index = entries.iter().position(|entry| entry.id == id)?;
+        let entry = entries.swap_remove(index);
+        if entries.is_empty() {
+            self.table.remove(&addr);
+        }
+        self.total_size -= 1;
+        Some(entry)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum QuicServerError {
     #[error("Server configure failed")]
@@ -139,18 +393,22 @@ fn handle_chunk(
     maybe_batch: &mut Option<PacketBatch>,
     remote_addr: &SocketAddr,
     packet_sender: &Sender<PacketBatch>,
+    activity: &ConnectionActivity,
 ) -> bool {
     match chunk {
         Ok(maybe_chunk) => {
             if let Some(chunk) = maybe_chunk {
                 trace!("got chunk: {:?}", chunk);
                 let chunk_len = chunk.bytes.len() as u64;
+                activity.last_update.store(timestamp(), Ordering::Relaxed);
 
                 // shouldn't happen, but sanity check the size and offsets
                 if chunk.offset > PACKET_DATA_SIZE as u64 || chunk_len > PACKET_DATA_SIZE as u64 {
+                    activity.errors.fetch_add(1, Ordering::Relaxed);
                     return true;
                 }
                 if chunk.offset + chunk_len > PACKET_DATA_SIZE as u64 {
+                    activity.errors.fetch_add(1, Ordering::Relaxed);
                     return true;
                 }
 
@@ -176,6 +434,7 @@ fn handle_chunk(
                     if let Err(e) = packet_sender.send(batch) {
                         info!("send error: {}", e);
                     } else {
+                        activity.packets.fetch_add(1, Ordering::Relaxed);
                         trace!("sent {} byte packet", len);
                     }
                 }
@@ -184,6 +443,7 @@ fn handle_chunk(
         }
         Err(e) => {
             debug!("Received stream error: {:?}", e);
+            activity.errors.fetch_add(1, Ordering::Relaxed);
             return true;
         }
     }
@@ -196,6 +456,8 @@ pub fn spawn_server(
     gossip_host: IpAddr,
     packet_sender: Sender<PacketBatch>,
     exit: Arc<AtomicBool>,
+    staked_nodes: StakedNodes,
+    connection_table_config: ConnectionTableConfig,
 ) -> Result<thread::JoinHandle<()>, QuicServerError> {
     let (config, _cert) = configure_server(keypair, gossip_host)?;
 
@@ -206,9 +468,18 @@ pub fn spawn_server(
             .map_err(|_e| QuicServerError::EndpointFailed)?
     };
 
+    let connection_table = Arc::new(Mutex::new(ConnectionTable::new(connection_table_config)));
+    let stats = connection_table.lock().unwrap().stats.clone();
+
     let handle = thread::spawn(move || {
         let handle = runtime.spawn(async move {
+            let mut last_stats_report = Instant::now();
             while !exit.load(Ordering::Relaxed) {
+                if last_stats_report.elapsed() > CONNECTION_TABLE_STATS_REPORT_INTERVAL {
+                    stats.report(connection_table.lock().unwrap().total_size);
+                    last_stats_report = Instant::now();
+                }
+
                 const WAIT_FOR_CONNECTION_TIMEOUT_MS: u64 = 1000;
                 let timeout_connection = timeout(
                     Duration::from_millis(WAIT_FOR_CONNECTION_TIMEOUT_MS),
@@ -226,22 +497,50 @@ pub fn spawn_server(
                         } = new_connection;
 
                         let remote_addr = connection.remote_address();
+                        let stake = staked_nodes
+                            .read()
+                            .unwrap()
+                            .get(&remote_addr.ip())
+                            .copied()
+                            .unwrap_or_default();
+                        let added = connection_table.lock().unwrap().try_add(
+                            remote_addr.ip(),
+                            stake,
+                            timestamp(),
+                            Some(connection.clone()),
+                        );
+                        let (id, connection_exit, activity) = match added {
+                            Some(added) => added,
+                            None => {
+                                debug!("rejected connection {}", remote_addr);
+                                connection.close(0u32.into(), b"connection table full");
+                                continue;
+                            }
+                        };
+                        let connection_table = connection_table.clone();
                         let packet_sender = packet_sender.clone();
                         tokio::spawn(async move {
                             debug!("new connection {}", remote_addr);
                             while let Some(Ok(mut stream)) = uni_streams.next().await {
                                 let mut maybe_batch = None;
-                                while !exit.load(Ordering::Relaxed) {
+                                while !exit.load(Ordering::Relaxed)
+                                    && !connection_exit.load(Ordering::Relaxed)
+                                {
                                     if handle_chunk(
                                         &stream.read_chunk(PACKET_DATA_SIZE, false).await,
                                         &mut maybe_batch,
                                         &remote_addr,
                                         &packet_sender,
+                                        &activity,
                                     ) {
                                         break;
                                     }
                                 }
                             }
+                            connection_table
+                                .lock()
+                                .unwrap()
+                                .remove_entry(remote_addr.ip(), id);
                         });
                     }
                 }
@@ -298,11 +597,125 @@ mod test {
         let (sender, _receiver) = unbounded();
         let keypair = Keypair::new();
         let ip = "127.0.0.1".parse().unwrap();
-        let t = spawn_server(s, &keypair, ip, sender, exit.clone()).unwrap();
+        let t = spawn_server(
+            s,
+            &keypair,
+            ip,
+            sender,
+            exit.clone(),
+            StakedNodes::default(),
+            ConnectionTableConfig::default(),
+        )
+        .unwrap();
         exit.store(true, Ordering::Relaxed);
         t.join().unwrap();
     }
 
+    #[test]
+    fn test_connection_table_evicts_lowest_stake() {
+        let mut table = ConnectionTable::new(ConnectionTableConfig {
+            max_connections: 2,
+            eviction_policy: ConnectionEvictionPolicy::Stake,
+        });
+        let ip = |i| IpAddr::from([127, 0, 0, i]);
+        let (_, low_exit, _) = table.try_add(ip(1), 10, 0, None).unwrap();
+        let (_, high_exit, _) = table.try_add(ip(2), 100, 0, None).unwrap();
+
+        // Unstaked connections can't displace staked ones
+        assert!(table.try_add(ip(3), 0, 1, None).is_none());
+        assert_eq!(table.stats.rejected.load(Ordering::Relaxed), 1);
+
+        assert!(table.try_add(ip(3), 50, 1, None).is_some());
+        assert!(low_exit.load(Ordering::Relaxed));
+        assert!(!high_exit.load(Ordering::Relaxed));
+        assert!(!table.table.contains_key(&ip(1)));
+        assert_eq!(table.total_size, 2);
+        assert_eq!(table.stats.evicted_by_stake.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_connection_table_evicts_least_recent() {
+        let mut table = ConnectionTable::new(ConnectionTableConfig {
+            max_connections: 2,
+            eviction_policy: ConnectionEvictionPolicy::StakeAndRecency,
+        });
+        let ip = |i| IpAddr::from([127, 0, 0, i]);
+        let (_, old_exit, _) = table.try_add(ip(1), 10, 0, None).unwrap();
+        let (_, new_exit, _) = table.try_add(ip(2), 10, 5, None).unwrap();
+
+        assert!(table.try_add(ip(3), 10, 10, None).is_some());
+        assert!(old_exit.load(Ordering::Relaxed));
+        assert!(!new_exit.load(Ordering::Relaxed));
+        assert_eq!(table.stats.evicted_by_recency.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_connection_table_evicts_lowest_reputation() {
+        let mut table = ConnectionTable::new(ConnectionTableConfig {
+            max_connections: 2,
+            eviction_policy: ConnectionEvictionPolicy::Reputation,
+        });
+        let ip = |i| IpAddr::from([127, 0, 0, i]);
+        let (_, good_exit, good) = table.try_add(ip(1), 0, 0, None).unwrap();
+        let (_, bad_exit, bad) = table.try_add(ip(2), 0, 5, None).unwrap();
+        good.packets.fetch_add(5, Ordering::Relaxed);
+        bad.packets.fetch_add(5, Ordering::Relaxed);
+        bad.errors.fetch_add(1, Ordering::Relaxed);
+
+        assert!(table.try_add(ip(3), 0, 10, None).is_some());
+        assert!(bad_exit.load(Ordering::Relaxed));
+        assert!(!good_exit.load(Ordering::Relaxed));
+        assert_eq!(table.stats.evicted_by_reputation.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_connection_table_reputation_stake_tiers() {
+        let mut table = ConnectionTable::new(ConnectionTableConfig {
+            max_connections: 2,
+            eviction_policy: ConnectionEvictionPolicy::Reputation,
+        });
+        let ip = |i| IpAddr::from([127, 0, 0, i]);
+        // Both stakes are in the same tier, so the delivery history decides
+        let (_, rich_exit, rich) = table.try_add(ip(1), 1_900, 0, None).unwrap();
+        let (_, poor_exit, poor) = table.try_add(ip(2), 1_100, 0, None).unwrap();
+        rich.errors.fetch_add(1, Ordering::Relaxed);
+        poor.packets.fetch_add(5, Ordering::Relaxed);
+
+        assert!(table.try_add(ip(3), 1_024, 1, None).is_some());
+        assert!(rich_exit.load(Ordering::Relaxed));
+        assert!(!poor_exit.load(Ordering::Relaxed));
+
+        // A connection in a lower tier can't displace either of them
+        assert!(table.try_add(ip(4), 1_000, 2, None).is_none());
+        assert!(!poor_exit.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_connection_table_remove() {
+        let mut table = ConnectionTable::new(ConnectionTableConfig::default());
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let (id1, _, _) = table.try_add(ip, 0, 0, None).unwrap();
+        let (id2, _, _) = table.try_add(ip, 0, 0, None).unwrap();
+        assert_eq!(table.total_size, 2);
+        assert!(table.remove_entry(ip, id1).is_some());
+        assert!(table.remove_entry(ip, id1).is_none());
+        assert_eq!(table.total_size, 1);
+        assert!(table.remove_entry(ip, id2).is_some());
+        assert!(table.table.is_empty());
+    }
+
+    #[test]
+    fn test_connection_eviction_policy_from_str() {
+        for policy in ConnectionEvictionPolicy::STRINGS {
+            assert!(policy.parse::<ConnectionEvictionPolicy>().is_ok());
+        }
+        assert_eq!(
+            "stake-and-recency".parse::<ConnectionEvictionPolicy>(),
+            Ok(ConnectionEvictionPolicy::default())
+        );
+        assert!("lowest-stake".parse::<ConnectionEvictionPolicy>().is_err());
+    }
+
     fn make_client_endpoint(runtime: &Runtime, addr: &SocketAddr) -> NewConnection {
         let client_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
         let mut endpoint = quinn::Endpoint::new(EndpointConfig::default(), None, client_socket)
@@ -323,7 +736,16 @@ mod test {
         let keypair = Keypair::new();
         let ip = "127.0.0.1".parse().unwrap();
         let server_address = s.local_addr().unwrap();
-        let t = spawn_server(s, &keypair, ip, sender, exit.clone()).unwrap();
+        let t = spawn_server(
+            s,
+            &keypair,
+            ip,
+            sender,
+            exit.clone(),
+            StakedNodes::default(),
+            ConnectionTableConfig::default(),
+        )
+        .unwrap();
 
         let runtime = rt();
         let _rt_guard = runtime.enter();
@@ -378,7 +800,16 @@ mod test {
         let keypair = Keypair::new();
         let ip = "127.0.0.1".parse().unwrap();
         let server_address = s.local_addr().unwrap();
-        let t = spawn_server(s, &keypair, ip, sender, exit.clone()).unwrap();
+        let t = spawn_server(
+            s,
+            &keypair,
+            ip,
+            sender,
+            exit.clone(),
+            StakedNodes::default(),
+            ConnectionTableConfig::default(),
+        )
+        .unwrap();
 
         let runtime = rt();
         let _rt_guard = runtime.enter();
@@ -5,14 +5,17 @@ use {
     solana_client::{nonblocking, rpc_client::RpcClient},
     solana_core::{
         tower_storage::TowerStorage,
-        validator::{Validator, ValidatorConfig, ValidatorStartProgress},
+        validator::{Validator, ValidatorConfig, ValidatorDrain, ValidatorStartProgress},
     },
     solana_gossip::{
         cluster_info::{ClusterInfo, Node},
         gossip_service::discover_cluster,
         socketaddr,
     },
-    solana_ledger::{blockstore::create_new_ledger, create_new_tmp_ledger},
+    solana_ledger::{
+        blockstore::{create_new_ledger, Blockstore},
+        create_new_tmp_ledger,
+    },
     solana_net_utils::PortRange,
     solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
     solana_runtime::{
@@ -21,18 +24,20 @@ use {
     },
     solana_sdk::{
         account::{Account, AccountSharedData},
-        clock::{Slot, DEFAULT_MS_PER_SLOT},
+        clock::{Slot, UnixTimestamp, DEFAULT_MS_PER_SLOT},
         commitment_config::CommitmentConfig,
         epoch_schedule::EpochSchedule,
         exit::Exit,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
-        hash::Hash,
+        genesis_config::GenesisConfig,
+        hash::{hashv, Hash},
         instruction::{AccountMeta, Instruction},
         message::Message,
         native_token::sol_to_lamports,
+        poh_config::PohConfig,
         pubkey::Pubkey,
         rent::Rent,
-        signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+        signature::{keypair_from_seed, read_keypair_file, write_keypair_file, Keypair, Signer},
     },
     solana_streamer::socket::SocketAddrSpace,
     std::{
@@ -48,6 +53,17 @@ use {
     tokio::time::sleep,
 };
 
+/// Genesis creation time of deterministic test validators, 2022-01-01T00:00:00Z
+const DETERMINISTIC_CREATION_TIME: UnixTimestamp = 1_640_995_200;
+/// Hashes per tick of deterministic test validators, fixed so that the PoH sequence doesn't
+/// depend on how long the validator sleeps between ticks
+const DETERMINISTIC_HASHES_PER_TICK: u64 = 1_000;
+
+/// Derive the keypair called `name` from `seed`, for deterministic test validators
+pub fn deterministic_keypair(seed: u64, name: &str) -> Keypair {
+    keypair_from_seed(hashv(&[&seed.to_le_bytes(), name.as_bytes()]).as_ref()).unwrap()
+}
+
 #[derive(Clone)]
 pub struct AccountInfo<'a> {
     pub address: Pubkey,
@@ -100,12 +116,15 @@ pub struct TestValidatorGenesis {
     epoch_schedule: Option<EpochSchedule>,
     node_config: TestValidatorNodeConfig,
     pub validator_exit: Arc<RwLock<Exit>>,
+    pub validator_drain: Arc<ValidatorDrain>,
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub max_ledger_shreds: Option<u64>,
     pub max_genesis_archive_unpacked_size: Option<u64>,
     pub accountsdb_plugin_config_files: Option<Vec<PathBuf>>,
     pub accounts_db_caching_enabled: bool,
+    geyser_plugins: Vec<(PathBuf, serde_json::Value)>,
+    deterministic_seed: Option<u64>,
 }
 
 impl Default for TestValidatorGenesis {
@@ -129,12 +148,15 @@ impl Default for TestValidatorGenesis {
             epoch_schedule: Option::<EpochSchedule>::default(),
             node_config: TestValidatorNodeConfig::default(),
             validator_exit: Arc::<RwLock<Exit>>::default(),
+            validator_drain: Arc::<ValidatorDrain>::default(),
             start_progress: Arc::<RwLock<ValidatorStartProgress>>::default(),
             authorized_voter_keypairs: Arc::<RwLock<Vec<Arc<Keypair>>>>::default(),
             max_ledger_shreds: Option::<u64>::default(),
             max_genesis_archive_unpacked_size: Option::<u64>::default(),
             accountsdb_plugin_config_files: Option::<Vec<PathBuf>>::default(),
             accounts_db_caching_enabled: bool::default(),
+            geyser_plugins: Vec::<(PathBuf, serde_json::Value)>::default(),
+            deterministic_seed: Option::<u64>::default(),
         }
     }
 }
@@ -170,6 +192,26 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Make runs reproducible: the validator and mint keypairs are derived from `seed`, and the
+    /// genesis creation time and PoH hashes per tick are fixed, so the same seed and genesis
+    /// accounts always produce the same genesis hash, leader schedule and epoch boundaries.
+    ///
+    /// Transactions, including the validator's votes, are still recorded as they arrive, so
+    /// later blockhashes only repeat when the same transactions land in the same slots.
+    pub fn deterministic(&mut self, seed: u64) -> &mut Self {
+        self.deterministic_seed = Some(seed);
+        self
+    }
+
+    /// Create the keypair called `name`, derived from the seed in deterministic mode and random
+    /// otherwise
+    pub fn new_keypair(&self, name: &str) -> Keypair {
+        match self.deterministic_seed {
+            Some(seed) => deterministic_keypair(seed, name),
+            None => Keypair::new(),
+        }
+    }
+
     pub fn rent(&mut self, rent: Rent) -> &mut Self {
         self.rent = rent;
         self
@@ -225,6 +267,20 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Load the AccountsDb (Geyser) plugin library at `path` into the validator
+    ///
+    /// `config` must be a JSON object holding the plugin's configuration. It is written along
+    /// with `libpath` to a config file in the ledger directory, which is the file the plugin's
+    /// `on_load` is called with.
+    pub fn add_geyser_plugin<P: Into<PathBuf>>(
+        &mut self,
+        path: P,
+        config: serde_json::Value,
+    ) -> &mut Self {
+        self.geyser_plugins.push((path.into(), config));
+        self
+    }
+
     /// Add an account to the test environment
     pub fn add_account(&mut self, address: Pubkey, account: AccountSharedData) -> &mut Self {
         self.accounts.insert(address, account);
@@ -393,7 +449,7 @@ impl TestValidatorGenesis {
         &self,
         socket_addr_space: SocketAddrSpace,
     ) -> (TestValidator, Keypair) {
-        let mint_keypair = Keypair::new();
+        let mint_keypair = self.new_keypair("mint");
         match TestValidator::start(mint_keypair.pubkey(), self, socket_addr_space) {
             Ok(test_validator) => {
                 let runtime = tokio::runtime::Builder::new_current_thread()
@@ -419,7 +475,7 @@ impl TestValidatorGenesis {
         &self,
         socket_addr_space: SocketAddrSpace,
     ) -> (TestValidator, Keypair) {
-        let mint_keypair = Keypair::new();
+        let mint_keypair = self.new_keypair("mint");
         match TestValidator::start(mint_keypair.pubkey(), self, socket_addr_space) {
             Ok(test_validator) => {
                 test_validator.wait_for_nonzero_fees().await;
@@ -485,19 +541,13 @@ impl TestValidator {
             .expect("validator start failed")
     }
 
-    /// Initialize the ledger directory
-    ///
-    /// If `ledger_path` is `None`, a temporary ledger will be created.  Otherwise the ledger will
-    /// be initialized in the provided directory if it doesn't already exist.
-    ///
-    /// Returns the path to the ledger directory.
-    fn initialize_ledger(
+    fn create_genesis_config(
         mint_address: Pubkey,
+        validator_identity: &Pubkey,
+        validator_vote_account: &Pubkey,
+        validator_stake_account: &Pubkey,
         config: &TestValidatorGenesis,
-    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let validator_identity = Keypair::new();
-        let validator_vote_account = Keypair::new();
-        let validator_stake_account = Keypair::new();
+    ) -> GenesisConfig {
         let validator_identity_lamports = sol_to_lamports(500.);
         let validator_stake_lamports = sol_to_lamports(1_000_000.);
         let mint_lamports = sol_to_lamports(500_000_000.);
@@ -523,9 +573,9 @@ impl TestValidator {
         let mut genesis_config = create_genesis_config_with_leader_ex(
             mint_lamports,
             &mint_address,
-            &validator_identity.pubkey(),
-            &validator_vote_account.pubkey(),
-            &validator_stake_account.pubkey(),
+            validator_identity,
+            validator_vote_account,
+            validator_stake_account,
             validator_stake_lamports,
             validator_identity_lamports,
             config.fee_rate_governor.clone(),
@@ -541,6 +591,37 @@ impl TestValidator {
             genesis_config.ticks_per_slot = ticks_per_slot;
         }
 
+        if config.deterministic_seed.is_some() {
+            genesis_config.creation_time = DETERMINISTIC_CREATION_TIME;
+            genesis_config.poh_config = PohConfig {
+                hashes_per_tick: Some(DETERMINISTIC_HASHES_PER_TICK),
+                ..PohConfig::default()
+            };
+        }
+        genesis_config
+    }
+
+    /// Initialize the ledger directory
+    ///
+    /// If `ledger_path` is `None`, a temporary ledger will be created.  Otherwise the ledger will
+    /// be initialized in the provided directory if it doesn't already exist.
+    ///
+    /// Returns the path to the ledger directory.
+    fn initialize_ledger(
+        mint_address: Pubkey,
+        config: &TestValidatorGenesis,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let validator_identity = config.new_keypair("validator-identity");
+        let validator_vote_account = config.new_keypair("validator-vote-account");
+        let validator_stake_account = config.new_keypair("validator-stake-account");
+        let genesis_config = Self::create_genesis_config(
+            mint_address,
+            &validator_identity.pubkey(),
+            &validator_vote_account.pubkey(),
+            &validator_stake_account.pubkey(),
+            config,
+        );
+
         let ledger_path = match &config.ledger_path {
             None => create_new_tmp_ledger!(&genesis_config).0,
             Some(ledger_path) => {
@@ -631,8 +712,17 @@ impl TestValidator {
             }
         }
 
+        let mut accountsdb_plugin_config_files = config.accountsdb_plugin_config_files.clone();
+        for (index, (libpath, plugin_config)) in config.geyser_plugins.iter().enumerate() {
+            let config_file = ledger_path.join(format!("geyser-plugin-{}.json", index));
+            write_geyser_plugin_config(&config_file, libpath, plugin_config)?;
+            accountsdb_plugin_config_files
+                .get_or_insert_with(Vec::new)
+                .push(config_file);
+        }
+
         let mut validator_config = ValidatorConfig {
-            accountsdb_plugin_config_files: config.accountsdb_plugin_config_files.clone(),
+            accountsdb_plugin_config_files,
             accounts_db_caching_enabled: config.accounts_db_caching_enabled,
             rpc_addrs: Some((
                 SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), node.info.rpc.port()),
@@ -657,6 +747,7 @@ impl TestValidator {
             warp_slot: config.warp_slot,
             bpf_jit: !config.no_bpf_jit,
             validator_exit: config.validator_exit.clone(),
+            validator_drain: config.validator_drain.clone(),
             rocksdb_compaction_interval: Some(100), // Compact every 100 slots
             max_ledger_shreds: config.max_ledger_shreds,
             no_wait_for_vote_to_start_leader: true,
@@ -806,6 +897,10 @@ impl TestValidator {
     pub fn cluster_info(&self) -> Arc<ClusterInfo> {
         self.validator.as_ref().unwrap().cluster_info.clone()
     }
+
+    pub fn blockstore(&self) -> Arc<Blockstore> {
+        self.validator.as_ref().unwrap().blockstore.clone()
+    }
 }
 
 impl Drop for TestValidator {
@@ -825,9 +920,34 @@ impl Drop for TestValidator {
     }
 }
 
+/// Write the config file of a plugin added with `TestValidatorGenesis::add_geyser_plugin()`
+fn write_geyser_plugin_config(
+    config_file: &Path,
+    libpath: &Path,
+    plugin_config: &serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut plugin_config = match plugin_config {
+        serde_json::Value::Object(plugin_config) => plugin_config.clone(),
+        serde_json::Value::Null => serde_json::Map::new(),
+        _ => {
+            return Err(format!(
+                "The config of the plugin {} is not a JSON object",
+                libpath.display()
+            )
+            .into())
+        }
+    };
+    let libpath = libpath
+        .to_str()
+        .ok_or_else(|| format!("Invalid plugin library path {}", libpath.display()))?;
+    plugin_config.insert("libpath".to_string(), libpath.into());
+    serde_json::to_writer_pretty(File::create(config_file)?, &plugin_config)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {super::*, solana_ledger::get_tmp_ledger_path_auto_delete};
 
     #[test]
     fn get_health() {
@@ -843,6 +963,76 @@ mod test {
         rpc_client.get_health().await.expect("health");
     }
 
+    #[test]
+    fn geyser_plugin_config() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        std::fs::create_dir_all(ledger_path.path()).unwrap();
+        let config_file = ledger_path.path().join("geyser-plugin-0.json");
+
+        write_geyser_plugin_config(
+            &config_file,
+            Path::new("/plugins/libplugin.so"),
+            &serde_json::json!({ "accounts_selector": { "accounts": ["*"] } }),
+        )
+        .unwrap();
+        let mut contents = String::new();
+        File::open(&config_file)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&contents).unwrap(),
+            serde_json::json!({
+                "libpath": "/plugins/libplugin.so",
+                "accounts_selector": { "accounts": ["*"] },
+            })
+        );
+
+        assert!(write_geyser_plugin_config(
+            &config_file,
+            Path::new("/plugins/libplugin.so"),
+            &serde_json::json!(["not", "an", "object"]),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn deterministic_genesis() {
+        let create_genesis_config = |config: &TestValidatorGenesis| {
+            let mint_keypair = config.new_keypair("mint");
+            TestValidator::create_genesis_config(
+                mint_keypair.pubkey(),
+                &config.new_keypair("validator-identity").pubkey(),
+                &config.new_keypair("validator-vote-account").pubkey(),
+                &config.new_keypair("validator-stake-account").pubkey(),
+                config,
+            )
+        };
+
+        let mut config = TestValidatorGenesis::default();
+        config.deterministic(42);
+        let genesis_config = create_genesis_config(&config);
+        assert_eq!(genesis_config.creation_time, DETERMINISTIC_CREATION_TIME);
+        assert_eq!(
+            genesis_config.poh_config.hashes_per_tick,
+            Some(DETERMINISTIC_HASHES_PER_TICK)
+        );
+
+        let mut same_seed_config = TestValidatorGenesis::default();
+        same_seed_config.deterministic(42);
+        assert_eq!(
+            genesis_config.hash(),
+            create_genesis_config(&same_seed_config).hash()
+        );
+
+        let mut other_seed_config = TestValidatorGenesis::default();
+        other_seed_config.deterministic(43);
+        assert_ne!(
+            genesis_config.hash(),
+            create_genesis_config(&other_seed_config).hash()
+        );
+    }
+
     #[tokio::test]
     #[should_panic]
     async fn document_tokio_panic() {
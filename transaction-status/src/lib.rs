@@ -8,6 +8,7 @@ pub mod extract_memos;
 pub mod parse_accounts;
 pub mod parse_associated_token;
 pub mod parse_bpf_loader;
+pub mod parse_configured;
 pub mod parse_instruction;
 pub mod parse_stake;
 pub mod parse_system;
@@ -22,6 +23,7 @@ use {
         parse_instruction::{parse, ParsedInstruction},
     },
     solana_account_decoder::parse_token::UiTokenAmount,
+    solana_runtime::bank::InnerInstructionsList,
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         commitment_config::CommitmentConfig,
@@ -30,6 +32,7 @@ use {
         sanitize::Sanitize,
         signature::Signature,
         transaction::{Result, Transaction, TransactionError, VersionedTransaction},
+        transaction_context::TransactionReturnData,
     },
     std::fmt,
 };
@@ -150,6 +153,21 @@ pub struct InnerInstructions {
     pub instructions: Vec<CompiledInstruction>,
 }
 
+/// Index the inner instructions recorded for each instruction of a transaction, leaving out the
+/// instructions that didn't invoke any
+pub fn map_inner_instructions(
+    inner_instructions: InnerInstructionsList,
+) -> impl Iterator<Item = InnerInstructions> {
+    inner_instructions
+        .into_iter()
+        .enumerate()
+        .map(|(index, instructions)| InnerInstructions {
+            index: index as u8,
+            instructions,
+        })
+        .filter(|inner_instructions| !inner_instructions.instructions.is_empty())
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UiInnerInstructions {
@@ -186,12 +204,38 @@ impl From<InnerInstructions> for UiInnerInstructions {
     }
 }
 
+/// The state of a token-2022 account and of its mint that affects what the account's balance
+/// is worth to its owner
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalanceExtensions {
+    /// Transfer fees withheld in the account, which are part of its balance but can only be
+    /// withdrawn by the mint's withdraw authority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withheld_transfer_fee: Option<UiTokenAmount>,
+    /// The balance with the interest accrued by an interest-bearing mint, as of the block time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interest_bearing_ui_amount: Option<String>,
+    /// Whether the account accepts confidential transfers, if it is configured for them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidential_credits_allowed: Option<bool>,
+}
+
+impl TokenBalanceExtensions {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TransactionTokenBalance {
     pub account_index: u8,
     pub mint: String,
     pub ui_token_amount: UiTokenAmount,
     pub owner: String,
+    pub program_id: String,
+    /// Only set for token-2022 accounts with balance-affecting extensions
+    pub extensions: Option<TokenBalanceExtensions>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -202,6 +246,10 @@ pub struct UiTransactionTokenBalance {
     pub ui_token_amount: UiTokenAmount,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<TokenBalanceExtensions>,
 }
 
 impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
@@ -215,6 +263,12 @@ impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
             } else {
                 None
             },
+            program_id: if !token_balance.program_id.is_empty() {
+                Some(token_balance.program_id)
+            } else {
+                None
+            },
+            extensions: token_balance.extensions,
         }
     }
 }
@@ -231,6 +285,8 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
     pub loaded_addresses: LoadedAddresses,
+    pub return_data: Option<TransactionReturnData>,
+    pub compute_units_consumed: Option<u64>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -246,6 +302,8 @@ impl Default for TransactionStatusMeta {
             post_token_balances: None,
             rewards: None,
             loaded_addresses: LoadedAddresses::default(),
+            return_data: None,
+            compute_units_consumed: None,
         }
     }
 }
@@ -264,6 +322,35 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_units_consumed: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UiReturnDataEncoding {
+    Base64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionReturnData {
+    pub program_id: String,
+    pub data: (String, UiReturnDataEncoding),
+}
+
+impl From<TransactionReturnData> for UiTransactionReturnData {
+    fn from(return_data: TransactionReturnData) -> Self {
+        Self {
+            program_id: return_data.program_id.to_string(),
+            data: (
+                base64::encode(return_data.data),
+                UiReturnDataEncoding::Base64,
+            ),
+        }
+    }
 }
 
 impl UiTransactionStatusMeta {
@@ -287,6 +374,8 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(Into::into).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(Into::into),
+            compute_units_consumed: meta.compute_units_consumed,
         }
     }
 }
@@ -310,6 +399,8 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(Into::into).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(Into::into),
+            compute_units_consumed: meta.compute_units_consumed,
         }
     }
 }
@@ -0,0 +1,199 @@
+use {
+    crate::parse_instruction::{
+        register_instruction_parser, InstructionParser, ParseInstructionError,
+        ParsedInstructionEnum,
+    },
+    serde_json::{Map, Value},
+    solana_sdk::{instruction::CompiledInstruction, message::AccountKeys, pubkey::Pubkey},
+    std::{fs::File, path::Path, str::FromStr, sync::Arc},
+};
+
+/// An instruction of a `ConfiguredProgram`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredInstruction {
+    /// The leading bytes of the instruction data that identify the instruction
+    pub discriminator: Vec<u8>,
+    #[serde(rename = "type")]
+    pub instruction_type: String,
+    /// The names of the instruction's accounts, in order
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+/// A program whose instructions are described by the operator rather than by a built-in parser,
+/// for example:
+///
+/// ```text
+/// {
+///     "programId": "Counter111111111111111111111111111111111111",
+///     "name": "counter",
+///     "instructions": [
+///         { "discriminator": [0], "type": "initialize", "accounts": ["payer", "counter"] },
+///         { "discriminator": [1], "type": "increment", "accounts": ["counter"] }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredProgram {
+    pub program_id: String,
+    pub name: String,
+    pub instructions: Vec<ConfiguredInstruction>,
+}
+
+impl InstructionParser for ConfiguredProgram {
+    fn program_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn parse(
+        &self,
+        instruction: &CompiledInstruction,
+        account_keys: &AccountKeys,
+    ) -> Result<Value, ParseInstructionError> {
+        let not_parsable =
+            || ParseInstructionError::RegisteredInstructionNotParsable(self.name.clone());
+        // The longest matching discriminator is the most specific description of the instruction
+        let configured_instruction = self
+            .instructions
+            .iter()
+            .filter(|configured_instruction| {
+                instruction
+                    .data
+                    .starts_with(&configured_instruction.discriminator)
+            })
+            .max_by_key(|configured_instruction| configured_instruction.discriminator.len())
+            .ok_or_else(not_parsable)?;
+        if instruction.accounts.len() < configured_instruction.accounts.len() {
+            return Err(not_parsable());
+        }
+        let mut keys = vec![];
+        for index in &instruction.accounts {
+            keys.push(
+                account_keys
+                    .get(*index as usize)
+                    .ok_or_else(not_parsable)?
+                    .to_string(),
+            );
+        }
+
+        let mut info = Map::new();
+        let mut keys = keys.into_iter();
+        for (name, key) in configured_instruction.accounts.iter().zip(keys.by_ref()) {
+            info.insert(name.clone(), Value::String(key));
+        }
+        let remaining_accounts: Vec<_> = keys.map(Value::String).collect();
+        if !remaining_accounts.is_empty() {
+            info.insert(
+                "remainingAccounts".to_string(),
+                Value::Array(remaining_accounts),
+            );
+        }
+        let data = &instruction.data[configured_instruction.discriminator.len()..];
+        if !data.is_empty() {
+            info.insert(
+                "data".to_string(),
+                Value::String(bs58::encode(data).into_string()),
+            );
+        }
+        Ok(serde_json::to_value(ParsedInstructionEnum {
+            instruction_type: configured_instruction.instruction_type.clone(),
+            info: Value::Object(info),
+        })?)
+    }
+}
+
+/// Registers a parser for each program of `config_file`, a JSON array of `ConfiguredProgram`s,
+/// returning the number of programs registered
+pub fn register_configured_instruction_parsers(config_file: &Path) -> Result<usize, String> {
+    let file = File::open(config_file)
+        .map_err(|err| format!("Unable to open {}: {}", config_file.display(), err))?;
+    let programs: Vec<ConfiguredProgram> = serde_json::from_reader(file)
+        .map_err(|err| format!("Unable to parse {}: {}", config_file.display(), err))?;
+    let num_programs = programs.len();
+    for program in programs {
+        let program_id = Pubkey::from_str(&program.program_id)
+            .map_err(|err| format!("Invalid program id {}: {}", program.program_id, err))?;
+        register_instruction_parser(program_id, Arc::new(program));
+    }
+    Ok(num_programs)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::parse_instruction::{parse, ParsedInstruction},
+        serde_json::json,
+    };
+
+    #[test]
+    fn test_parse_configured() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let counter = Pubkey::new_unique();
+        let extra = Pubkey::new_unique();
+        let program: ConfiguredProgram = serde_json::from_value(json!({
+            "programId": program_id.to_string(),
+            "name": "counter",
+            "instructions": [
+                { "discriminator": [0], "type": "initialize", "accounts": ["payer", "counter"] },
+                { "discriminator": [0, 1], "type": "initializeTwice" },
+            ],
+        }))
+        .unwrap();
+        register_instruction_parser(program_id, Arc::new(program));
+
+        let keys = [payer, counter, extra];
+        let account_keys = AccountKeys::new(&keys, None);
+        let instruction = CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0, 1, 2],
+            data: vec![0, 42],
+        };
+        assert_eq!(
+            parse(&program_id, &instruction, &account_keys).unwrap(),
+            ParsedInstruction {
+                program: "counter".to_string(),
+                program_id: program_id.to_string(),
+                parsed: json!({
+                    "type": "initialize",
+                    "info": {
+                        "payer": payer.to_string(),
+                        "counter": counter.to_string(),
+                        "remainingAccounts": [extra.to_string()],
+                        "data": bs58::encode([42]).into_string(),
+                    },
+                }),
+            }
+        );
+
+        // The most specific discriminator wins
+        let instruction = CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![],
+            data: vec![0, 1],
+        };
+        assert_eq!(
+            parse(&program_id, &instruction, &account_keys)
+                .unwrap()
+                .parsed,
+            json!({ "type": "initializeTwice", "info": {} })
+        );
+
+        // Unknown instructions and missing accounts are not parsable
+        let instruction = CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0],
+            data: vec![2],
+        };
+        assert!(parse(&program_id, &instruction, &account_keys).is_err());
+        let instruction = CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![0],
+            data: vec![0],
+        };
+        assert!(parse(&program_id, &instruction, &account_keys).is_err());
+    }
+}
@@ -18,6 +18,7 @@ use {
     std::{
         collections::HashMap,
         str::{from_utf8, Utf8Error},
+        sync::{Arc, RwLock},
     },
     thiserror::Error,
 };
@@ -51,6 +52,8 @@ lazy_static! {
         m.insert(*VOTE_PROGRAM_ID, ParsableProgram::Vote);
         m
     };
+    static ref REGISTERED_PARSERS: RwLock<HashMap<Pubkey, Arc<dyn InstructionParser>>> =
+        RwLock::new(HashMap::new());
 }
 
 #[derive(Error, Debug)]
@@ -64,6 +67,9 @@ pub enum ParseInstructionError {
     #[error("Program not parsable")]
     ProgramNotParsable,
 
+    #[error("{0} instruction not parsable")]
+    RegisteredInstructionNotParsable(String),
+
     #[error("Internal error, please report")]
     SerdeJsonError(#[from] serde_json::error::Error),
 }
@@ -98,14 +104,36 @@ pub enum ParsableProgram {
     Vote,
 }
 
+/// A parser of the instructions of a program the crate has no built-in parser for
+pub trait InstructionParser: Send + Sync {
+    /// The name of the program, reported as the `program` of its parsed instructions
+    fn program_name(&self) -> String;
+
+    fn parse(
+        &self,
+        instruction: &CompiledInstruction,
+        account_keys: &AccountKeys,
+    ) -> Result<Value, ParseInstructionError>;
+}
+
+/// Registers `parser` for the instructions of `program_id`, replacing any parser previously
+/// registered for it. The built-in parsers take precedence over registered ones.
+pub fn register_instruction_parser(program_id: Pubkey, parser: Arc<dyn InstructionParser>) {
+    REGISTERED_PARSERS
+        .write()
+        .unwrap()
+        .insert(program_id, parser);
+}
+
 pub fn parse(
     program_id: &Pubkey,
     instruction: &CompiledInstruction,
     account_keys: &AccountKeys,
 ) -> Result<ParsedInstruction, ParseInstructionError> {
-    let program_name = PARSABLE_PROGRAM_IDS
-        .get(program_id)
-        .ok_or(ParseInstructionError::ProgramNotParsable)?;
+    let program_name = match PARSABLE_PROGRAM_IDS.get(program_id) {
+        Some(program_name) => program_name,
+        None => return parse_registered(program_id, instruction, account_keys),
+    };
     let parsed_json = match program_name {
         ParsableProgram::SplAssociatedTokenAccount => {
             serde_json::to_value(parse_associated_token(instruction, account_keys)?)?
@@ -129,6 +157,25 @@ pub fn parse(
     })
 }
 
+/// Parses `instruction` with the parser registered for `program_id`, ignoring built-in parsers
+pub fn parse_registered(
+    program_id: &Pubkey,
+    instruction: &CompiledInstruction,
+    account_keys: &AccountKeys,
+) -> Result<ParsedInstruction, ParseInstructionError> {
+    let parser = REGISTERED_PARSERS
+        .read()
+        .unwrap()
+        .get(program_id)
+        .cloned()
+        .ok_or(ParseInstructionError::ProgramNotParsable)?;
+    Ok(ParsedInstruction {
+        program: parser.program_name(),
+        program_id: program_id.to_string(),
+        parsed: parser.parse(instruction, account_keys)?,
+    })
+}
+
 fn parse_memo(instruction: &CompiledInstruction) -> Result<Value, ParseInstructionError> {
     parse_memo_data(&instruction.data)
         .map(Value::String)
@@ -1,20 +1,28 @@
 use {
-    crate::TransactionTokenBalance,
+    crate::{TokenBalanceExtensions, TransactionTokenBalance},
     solana_account_decoder::parse_token::{
-        pubkey_from_spl_token, spl_token_id, spl_token_native_mint, token_amount_to_ui_amount,
-        UiTokenAmount,
+        is_known_spl_token_id, spl_token_2022_id, spl_token_2022_native_mint,
+        spl_token_native_mint, token_amount_to_ui_amount, UiTokenAmount,
     },
     solana_measure::measure::Measure,
     solana_metrics::datapoint_debug,
     solana_runtime::{bank::Bank, transaction_batch::TransactionBatch},
-    solana_sdk::{account::ReadableAccount, pubkey::Pubkey},
-    spl_token::{
-        solana_program::program_pack::Pack,
+    solana_sdk::{account::ReadableAccount, clock::UnixTimestamp, pubkey::Pubkey},
+    spl_token_2022::{
+        extension::{
+            confidential_transfer::ConfidentialTransferAccount,
+            interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeAmount,
+            StateWithExtensions,
+        },
         state::{Account as TokenAccount, Mint},
     },
     std::collections::HashMap,
 };
 
+// The year length interest-bearing mints accrue interest over
+const SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+const ONE_IN_BASIS_POINTS: f64 = 10_000.0;
+
 pub type TransactionTokenBalances = Vec<Vec<TransactionTokenBalance>>;
 
 pub struct TransactionTokenBalancesSet {
@@ -36,17 +44,17 @@ impl TransactionTokenBalancesSet {
 }
 
 fn is_token_program(program_id: &Pubkey) -> bool {
-    program_id == &spl_token_id()
+    is_known_spl_token_id(program_id)
 }
 
 fn get_mint_decimals(bank: &Bank, mint: &Pubkey) -> Option<u8> {
-    if mint == &spl_token_native_mint() {
+    if mint == &spl_token_native_mint() || mint == &spl_token_2022_native_mint() {
         Some(spl_token::native_mint::DECIMALS)
     } else {
         let mint_account = bank.get_account(mint)?;
 
-        let decimals = Mint::unpack(mint_account.data())
-            .map(|mint| mint.decimals)
+        let decimals = StateWithExtensions::<Mint>::unpack(mint_account.data())
+            .map(|mint| mint.base.decimals)
             .ok()?;
 
         Some(decimals)
@@ -76,6 +84,8 @@ pub fn collect_token_balances(
                     mint,
                     ui_token_amount,
                     owner,
+                    program_id,
+                    extensions,
                 }) = collect_token_balance_from_account(bank, account_id, mint_decimals)
                 {
                     transaction_balances.push(TransactionTokenBalance {
@@ -83,6 +93,8 @@ pub fn collect_token_balances(
                         mint,
                         ui_token_amount,
                         owner,
+                        program_id,
+                        extensions,
                     });
                 }
             }
@@ -101,6 +113,8 @@ struct TokenBalanceData {
     mint: String,
     owner: String,
     ui_token_amount: UiTokenAmount,
+    program_id: String,
+    extensions: Option<TokenBalanceExtensions>,
 }
 
 fn collect_token_balance_from_account(
@@ -109,9 +123,12 @@ fn collect_token_balance_from_account(
     mint_decimals: &mut HashMap<Pubkey, u8>,
 ) -> Option<TokenBalanceData> {
     let account = bank.get_account(account_id)?;
+    if !is_token_program(account.owner()) {
+        return None;
+    }
 
-    let token_account = TokenAccount::unpack(account.data()).ok()?;
-    let mint = pubkey_from_spl_token(&token_account.mint);
+    let token_account = StateWithExtensions::<TokenAccount>::unpack(account.data()).ok()?;
+    let mint = Pubkey::new_from_array(token_account.base.mint.to_bytes());
 
     let decimals = mint_decimals.get(&mint).cloned().or_else(|| {
         let decimals = get_mint_decimals(bank, &mint)?;
@@ -119,9 +136,119 @@ fn collect_token_balance_from_account(
         Some(decimals)
     })?;
 
+    let extensions = if account.owner() == &spl_token_2022_id() {
+        collect_token_balance_extensions(bank, &mint, &token_account, decimals)
+    } else {
+        None
+    };
+
     Some(TokenBalanceData {
-        mint: token_account.mint.to_string(),
-        owner: token_account.owner.to_string(),
-        ui_token_amount: token_amount_to_ui_amount(token_account.amount, decimals),
+        mint: token_account.base.mint.to_string(),
+        owner: token_account.base.owner.to_string(),
+        ui_token_amount: token_amount_to_ui_amount(token_account.base.amount, decimals),
+        program_id: account.owner().to_string(),
+        extensions,
     })
 }
+
+fn collect_token_balance_extensions(
+    bank: &Bank,
+    mint: &Pubkey,
+    token_account: &StateWithExtensions<TokenAccount>,
+    decimals: u8,
+) -> Option<TokenBalanceExtensions> {
+    let withheld_transfer_fee =
+        token_account
+            .get_extension::<TransferFeeAmount>()
+            .ok()
+            .map(|transfer_fee_amount| {
+                token_amount_to_ui_amount(u64::from(transfer_fee_amount.withheld_amount), decimals)
+            });
+    let confidential_credits_allowed = token_account
+        .get_extension::<ConfidentialTransferAccount>()
+        .ok()
+        .map(|confidential_transfer_account| {
+            bool::from(&confidential_transfer_account.allow_balance_credits)
+        });
+    let interest_bearing_ui_amount = bank.get_account(mint).and_then(|mint_account| {
+        let mint = StateWithExtensions::<Mint>::unpack(mint_account.data()).ok()?;
+        let interest_bearing_config = mint.get_extension::<InterestBearingConfig>().ok()?;
+        Some(interest_bearing_ui_amount(
+            interest_bearing_config,
+            token_account.base.amount,
+            decimals,
+            bank.clock().unix_timestamp,
+        ))
+    });
+
+    let extensions = TokenBalanceExtensions {
+        withheld_transfer_fee,
+        interest_bearing_ui_amount,
+        confidential_credits_allowed,
+    };
+    (!extensions.is_empty()).then(|| extensions)
+}
+
+/// The UI amount of `amount` with the interest accrued as of `unix_timestamp`, continuously
+/// compounded at the average rate before the config's last update and its current rate since
+fn interest_bearing_ui_amount(
+    config: &InterestBearingConfig,
+    amount: u64,
+    decimals: u8,
+    unix_timestamp: UnixTimestamp,
+) -> String {
+    let compound = |rate: i16, seconds: i64| {
+        (f64::from(rate) * seconds as f64 / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS).exp()
+    };
+    let initialization_timestamp = i64::from(config.initialization_timestamp);
+    let last_update_timestamp = i64::from(config.last_update_timestamp);
+    let scale = compound(
+        i16::from(config.pre_update_average_rate),
+        last_update_timestamp.saturating_sub(initialization_timestamp),
+    ) * compound(
+        i16::from(config.current_rate),
+        unix_timestamp.saturating_sub(last_update_timestamp),
+    );
+    let ui_amount = amount as f64 * scale / 10_f64.powi(i32::from(decimals));
+    let ui_amount = format!("{:.*}", usize::from(decimals), ui_amount);
+    if decimals > 0 {
+        ui_amount
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        ui_amount
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, spl_token_2022::pod::PodI64};
+
+    #[test]
+    fn test_interest_bearing_ui_amount() {
+        let mut config = InterestBearingConfig {
+            initialization_timestamp: PodI64::from(0),
+            last_update_timestamp: PodI64::from(0),
+            ..InterestBearingConfig::default()
+        };
+        // Without interest, the amount is unchanged
+        assert_eq!(interest_bearing_ui_amount(&config, 1_500, 2, 0), "15");
+
+        // A year at 5% continuously compounded is worth e^0.05 times as much
+        config.current_rate = 500.into();
+        assert_eq!(
+            interest_bearing_ui_amount(&config, 1_000_000, 6, SECONDS_PER_YEAR as i64),
+            "1.051271"
+        );
+
+        // The average rate applies before the last update, the current rate after it
+        config.pre_update_average_rate = 500.into();
+        config.last_update_timestamp = PodI64::from(SECONDS_PER_YEAR as i64);
+        config.current_rate = 0.into();
+        assert_eq!(
+            interest_bearing_ui_amount(&config, 1_000_000, 6, 2 * SECONDS_PER_YEAR as i64),
+            "1.051271"
+        );
+    }
+}
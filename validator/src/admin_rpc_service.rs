@@ -6,11 +6,22 @@ use {
     jsonrpc_server_utils::tokio,
     log::*,
     serde::{Deserialize, Serialize},
+    solana_accountsdb_plugin_manager::{
+        accountsdb_plugin_manager::AccountsDbPluginManager,
+        accountsdb_plugin_service::AccountsDbPluginService,
+    },
     solana_core::{
-        consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
+        consensus::Tower,
+        tower_storage::TowerStorage,
+        validator::{ValidatorDrain, ValidatorStartProgress},
     },
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
+    solana_ledger::{
+        blockstore::{Blockstore, ShredSourceStats},
+        blockstore_db::Database,
+    },
     solana_sdk::{
+        clock::Slot,
         exit::Exit,
         signature::{read_keypair_file, Keypair, Signer},
     },
@@ -24,18 +35,42 @@ use {
     },
 };
 
+/// Process exit code of a validator that shut down through `drainAndExit`
+pub const DRAINED_EXIT_CODE: i32 = 3;
+
+/// Time a drain may take before the validator falls back to a normal exit
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 600;
+
 #[derive(Clone)]
 pub struct AdminRpcRequestMetadata {
     pub rpc_addr: Option<SocketAddr>,
     pub start_time: SystemTime,
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub validator_exit: Arc<RwLock<Exit>>,
+    pub validator_drain: Arc<ValidatorDrain>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub cluster_info: Arc<RwLock<Option<Arc<ClusterInfo>>>>,
+    pub blockstore: Arc<RwLock<Option<Arc<Blockstore>>>>,
+    pub accountsdb_plugin_manager: Arc<RwLock<Option<Arc<RwLock<AccountsDbPluginManager>>>>>,
     pub tower_storage: Arc<dyn TowerStorage>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
+impl AdminRpcRequestMetadata {
+    fn accountsdb_plugin_manager(&self) -> Result<Arc<RwLock<AccountsDbPluginManager>>> {
+        self.accountsdb_plugin_manager
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params(
+                    "The validator was not started with any AccountsDb plugin, or start up is \
+                     not complete yet",
+                )
+            })
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AdminRpcContactInfo {
     pub id: String,
@@ -106,6 +141,41 @@ impl Display for AdminRpcContactInfo {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcShredSourceStats {
+    pub slot: Slot,
+    pub num_turbine: usize,
+    pub num_repaired: usize,
+    pub num_recovered: usize,
+}
+
+impl From<(Slot, ShredSourceStats)> for AdminRpcShredSourceStats {
+    fn from((slot, stats): (Slot, ShredSourceStats)) -> Self {
+        let ShredSourceStats {
+            num_turbine,
+            num_repaired,
+            num_recovered,
+        } = stats;
+        Self {
+            slot,
+            num_turbine,
+            num_repaired,
+            num_recovered,
+        }
+    }
+}
+
+impl Display for AdminRpcShredSourceStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Slot {}: {} turbine, {} repaired, {} recovered",
+            self.slot, self.num_turbine, self.num_repaired, self.num_recovered
+        )
+    }
+}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -113,6 +183,9 @@ pub trait AdminRpc {
     #[rpc(meta, name = "exit")]
     fn exit(&self, meta: Self::Metadata) -> Result<()>;
 
+    #[rpc(meta, name = "drainAndExit")]
+    fn drain_and_exit(&self, meta: Self::Metadata, timeout_secs: u64) -> Result<()>;
+
     #[rpc(meta, name = "rpcAddress")]
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>>;
 
@@ -136,6 +209,28 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "contactInfo")]
     fn contact_info(&self, meta: Self::Metadata) -> Result<AdminRpcContactInfo>;
+
+    #[rpc(meta, name = "compactLedger")]
+    fn compact_ledger(&self, meta: Self::Metadata, columns: Vec<String>) -> Result<()>;
+
+    #[rpc(meta, name = "accountsDbPluginList")]
+    fn accountsdb_plugin_list(&self, meta: Self::Metadata) -> Result<Vec<String>>;
+
+    #[rpc(meta, name = "accountsDbPluginLoad")]
+    fn accountsdb_plugin_load(&self, meta: Self::Metadata, config_file: String) -> Result<String>;
+
+    #[rpc(meta, name = "accountsDbPluginUnload")]
+    fn accountsdb_plugin_unload(&self, meta: Self::Metadata, name: String) -> Result<()>;
+
+    #[rpc(meta, name = "accountsDbPluginReload")]
+    fn accountsdb_plugin_reload(&self, meta: Self::Metadata, name: String) -> Result<()>;
+
+    #[rpc(meta, name = "shredSourceStats")]
+    fn shred_source_stats(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<Vec<AdminRpcShredSourceStats>>;
 }
 
 pub struct AdminRpcImpl;
@@ -164,6 +259,40 @@ impl AdminRpc for AdminRpcImpl {
         Ok(())
     }
 
+    fn drain_and_exit(&self, meta: Self::Metadata, timeout_secs: u64) -> Result<()> {
+        debug!("drain_and_exit admin rpc request received");
+
+        if meta.validator_drain.is_requested() {
+            return Err(jsonrpc_core::error::Error::invalid_params(
+                "Drain already in progress",
+            ));
+        }
+        meta.validator_drain
+            .request(Duration::from_secs(timeout_secs));
+        warn!("validator drain requested, timeout {}s", timeout_secs);
+
+        thread::spawn(move || {
+            // ReplayStage stops starting leader slots and marks the validator drained once the
+            // next root has been made. The final snapshot and blockstore flush happen as the
+            // validator shuts down, and are cut short by the same deadline.
+            while !meta.validator_drain.is_drained() {
+                if meta.validator_drain.is_timed_out() {
+                    warn!(
+                        "validator drain timed out after {}s, exiting without draining",
+                        timeout_secs
+                    );
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            if meta.validator_drain.is_drained() {
+                warn!("validator drained, exiting");
+            }
+            meta.validator_exit.write().unwrap().exit();
+        });
+        Ok(())
+    }
+
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>> {
         debug!("rpc_addr admin rpc request received");
         Ok(meta.rpc_addr)
@@ -252,6 +381,92 @@ impl AdminRpc for AdminRpcImpl {
             ))
         }
     }
+
+    fn compact_ledger(&self, meta: Self::Metadata, columns: Vec<String>) -> Result<()> {
+        debug!("compact_ledger admin rpc request received");
+
+        let column_names = Database::column_names();
+        if let Some(column) = columns
+            .iter()
+            .find(|column| !column_names.contains(&column.as_str()))
+        {
+            return Err(jsonrpc_core::error::Error::invalid_params(format!(
+                "Unknown column: {}",
+                column
+            )));
+        }
+
+        let blockstore = meta.blockstore.read().unwrap().clone().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Retry once validator start up is complete")
+        })?;
+
+        // A full compaction can take a long time, so don't hold up the RPC response for it
+        Builder::new()
+            .name("solana-compact-ledger".to_string())
+            .spawn(move || {
+                let columns: Vec<_> = columns.iter().map(String::as_str).collect();
+                warn!("Manual ledger compaction of {:?} started", columns);
+                match blockstore.compact_columns(&columns) {
+                    Ok(()) => warn!("Manual ledger compaction of {:?} finished", columns),
+                    Err(err) => error!("Manual ledger compaction failed: {:?}", err),
+                }
+            })
+            .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{}", err)))?;
+        Ok(())
+    }
+
+    fn accountsdb_plugin_list(&self, meta: Self::Metadata) -> Result<Vec<String>> {
+        let plugin_manager = meta.accountsdb_plugin_manager()?;
+        let plugin_names = plugin_manager.read().unwrap().plugin_names();
+        Ok(plugin_names)
+    }
+
+    fn accountsdb_plugin_load(&self, meta: Self::Metadata, config_file: String) -> Result<String> {
+        debug!("accountsdb_plugin_load admin rpc request received");
+        let plugin_manager = meta.accountsdb_plugin_manager()?;
+        let mut plugin_manager = plugin_manager.write().unwrap();
+        let name =
+            AccountsDbPluginService::load_plugin(&mut plugin_manager, Path::new(&config_file))
+                .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{:?}", err)))?;
+        warn!("Loaded AccountsDb plugin {} from {}", name, config_file);
+        Ok(name)
+    }
+
+    fn accountsdb_plugin_unload(&self, meta: Self::Metadata, name: String) -> Result<()> {
+        debug!("accountsdb_plugin_unload admin rpc request received");
+        let plugin_manager = meta.accountsdb_plugin_manager()?;
+        AccountsDbPluginService::unload_plugin(&plugin_manager, &name)
+            .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{:?}", err)))?;
+        warn!("Unloaded AccountsDb plugin {}", name);
+        Ok(())
+    }
+
+    fn accountsdb_plugin_reload(&self, meta: Self::Metadata, name: String) -> Result<()> {
+        debug!("accountsdb_plugin_reload admin rpc request received");
+        let plugin_manager = meta.accountsdb_plugin_manager()?;
+        AccountsDbPluginService::reload_plugin(&plugin_manager, &name)
+            .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{:?}", err)))?;
+        warn!("Reloaded AccountsDb plugin {}", name);
+        Ok(())
+    }
+
+    fn shred_source_stats(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<Vec<AdminRpcShredSourceStats>> {
+        debug!("shred_source_stats admin rpc request received");
+
+        let blockstore = meta.blockstore.read().unwrap().clone().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("Retry once validator start up is complete")
+        })?;
+        Ok(blockstore
+            .get_shred_source_stats()
+            .into_iter()
+            .filter(|(stats_slot, _)| slot.map_or(true, |slot| slot == *stats_slot))
+            .map(AdminRpcShredSourceStats::from)
+            .collect())
+    }
 }
 
 // Start the Admin RPC interface
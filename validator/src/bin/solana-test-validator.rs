@@ -311,6 +311,18 @@ fn main() {
                      If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("deterministic_seed")
+                .long("deterministic-seed")
+                .takes_value(true)
+                .value_name("SEED")
+                .validator(is_parsable::<u64>)
+                .help(
+                    "Derive the validator, mint and faucet keypairs from this seed and fix the \
+                     genesis creation time and PoH speed, for reproducible runs. \
+                     If the ledger already exists then this parameter is silently ignored",
+                ),
+        )
         .arg(
             Arg::with_name("accountsdb_plugin_config")
                 .long("accountsdb-plugin-config")
@@ -401,12 +413,18 @@ fn main() {
         .map(normalize_to_url_if_moniker)
         .map(RpcClient::new);
 
+    let deterministic_seed = value_t!(matches, "deterministic_seed", u64).ok();
+    let new_keypair = |name| match deterministic_seed {
+        Some(seed) => deterministic_keypair(seed, name),
+        None => Keypair::new(),
+    };
+
     let (mint_address, random_mint) = pubkey_of(&matches, "mint_address")
         .map(|pk| (pk, false))
         .unwrap_or_else(|| {
             read_keypair_file(&cli_config.keypair_path)
                 .map(|kp| (kp.pubkey(), false))
-                .unwrap_or_else(|_| (Keypair::new().pubkey(), true))
+                .unwrap_or_else(|_| (new_keypair("mint").pubkey(), true))
         });
 
     let rpc_port = value_t_or_exit!(matches, "rpc_port", u16);
@@ -517,16 +535,18 @@ fn main() {
     let faucet_lamports = sol_to_lamports(value_of(&matches, "faucet_sol").unwrap());
     let faucet_keypair_file = ledger_path.join("faucet-keypair.json");
     if !faucet_keypair_file.exists() {
-        write_keypair_file(&Keypair::new(), faucet_keypair_file.to_str().unwrap()).unwrap_or_else(
-            |err| {
-                println!(
-                    "Error: Failed to write {}: {}",
-                    faucet_keypair_file.display(),
-                    err
-                );
-                exit(1);
-            },
-        );
+        write_keypair_file(
+            &new_keypair("faucet"),
+            faucet_keypair_file.to_str().unwrap(),
+        )
+        .unwrap_or_else(|err| {
+            println!(
+                "Error: Failed to write {}: {}",
+                faucet_keypair_file.display(),
+                err
+            );
+            exit(1);
+        });
     }
 
     let faucet_keypair =
@@ -558,6 +578,7 @@ fn main() {
             ("ticks_per_slot", "--ticks-per-slot"),
             ("slots_per_epoch", "--slots-per-epoch"),
             ("faucet_sol", "--faucet-sol"),
+            ("deterministic_seed", "--deterministic-seed"),
         ] {
             if matches.is_present(name) {
                 println!("{} argument ignored, ledger already exists", long);
@@ -578,6 +599,7 @@ fn main() {
     let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
 
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_blockstore = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -588,8 +610,11 @@ fn main() {
             start_progress: genesis.start_progress.clone(),
             start_time: std::time::SystemTime::now(),
             validator_exit: genesis.validator_exit.clone(),
+            validator_drain: genesis.validator_drain.clone(),
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
+            blockstore: admin_service_blockstore.clone(),
+            accountsdb_plugin_manager: Arc::new(RwLock::new(None)),
             tower_storage: tower_storage.clone(),
         },
     );
@@ -641,6 +666,10 @@ fn main() {
         genesis.warp_slot(warp_slot);
     }
 
+    if let Some(deterministic_seed) = deterministic_seed {
+        genesis.deterministic(deterministic_seed);
+    }
+
     if let Some(ticks_per_slot) = ticks_per_slot {
         genesis.ticks_per_slot(ticks_per_slot);
     }
@@ -683,6 +712,7 @@ fn main() {
     match genesis.start_with_mint_address(mint_address, socket_addr_space) {
         Ok(test_validator) => {
             *admin_service_cluster_info.write().unwrap() = Some(test_validator.cluster_info());
+            *admin_service_blockstore.write().unwrap() = Some(test_validator.blockstore());
             if let Some(dashboard) = dashboard {
                 dashboard.run(Duration::from_millis(250));
             }
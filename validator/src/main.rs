@@ -22,21 +22,35 @@ use {
         rpc_request::MAX_MULTIPLE_ACCOUNTS,
     },
     solana_core::{
+        broadcast_stage::BroadcastConfig,
         ledger_cleanup_service::{DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS},
         system_monitor_service::SystemMonitorService,
         tower_storage,
         tpu::DEFAULT_TPU_COALESCE_MS,
         validator::{is_snapshot_config_valid, Validator, ValidatorConfig, ValidatorStartProgress},
     },
+    solana_entry::entry::PohVerifyBackend,
     solana_gossip::{
-        cluster_info::{Node, VALIDATOR_PORT_RANGE},
+        cluster_info::{Node, NodeInterface, NodeInterfaces, VALIDATOR_PORT_RANGE},
         contact_info::ContactInfo,
     },
-    solana_ledger::blockstore_db::BlockstoreRecoveryMode,
+    solana_ledger::{
+        blockstore_archive::{
+            ArchiveStorageConfig, BlockstoreArchiveConfig, S3StorageConfig,
+            DEFAULT_ARCHIVE_MIN_SLOT_AGE, DEFAULT_ARCHIVE_SLOTS_PER_PACK,
+        },
+        blockstore_db::{
+            BlockstoreCompactionOptions, BlockstoreCompactionPriority, BlockstoreCompactionStyle,
+            BlockstoreCompressionType, BlockstoreRecoveryMode, BlockstoreTuningProfile, Database,
+        },
+    },
     solana_perf::recycler::enable_recycler_warming,
     solana_poh::poh_service,
     solana_replica_lib::accountsdb_repl_server::AccountsDbReplServiceConfig,
-    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
+    solana_rpc::{
+        rpc::JsonRpcConfig, rpc_method_metrics::RpcMethodMetricsConfig,
+        rpc_pubsub_service::PubSubConfig,
+    },
     solana_runtime::{
         accounts_db::{
             AccountShrinkThreshold, AccountsDbConfig, DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE,
@@ -49,7 +63,8 @@ use {
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
         snapshot_config::SnapshotConfig,
         snapshot_utils::{
-            self, ArchiveFormat, SnapshotVersion, DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
+            self, ArchiveCompressionOptions, ArchiveFormat, SnapshotVersion,
+            DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
             DEFAULT_INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
@@ -63,7 +78,12 @@ use {
         signature::{Keypair, Signer},
     },
     solana_send_transaction_service::send_transaction_service,
-    solana_streamer::socket::SocketAddrSpace,
+    solana_storage_bigtable::LedgerStorageConfig,
+    solana_streamer::{
+        quic::{ConnectionEvictionPolicy, ConnectionTableConfig, DEFAULT_MAX_QUIC_CONNECTIONS},
+        socket::SocketAddrSpace,
+    },
+    solana_transaction_status::parse_configured::register_configured_instruction_parsers,
     solana_validator::{
         admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile, lock_ledger,
         new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
@@ -350,6 +370,19 @@ fn wait_for_restart_window(
     Ok(())
 }
 
+fn column_compaction_style_validator(value: String) -> Result<(), String> {
+    let (column, style) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected COLUMN=STYLE, got {}", value))?;
+    if !Database::column_names().contains(&column) {
+        return Err(format!("unknown column: {}", column));
+    }
+    if !matches!(style, "level" | "universal") {
+        return Err(format!("unknown compaction style: {}", style));
+    }
+    Ok(())
+}
+
 fn hash_validator(hash: String) -> Result<(), String> {
     Hash::from_str(&hash)
         .map(|_| ())
@@ -447,6 +480,11 @@ pub fn main() {
     let default_accounts_shrink_optimize_total_space =
         &DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE.to_string();
     let default_accounts_shrink_ratio = &DEFAULT_ACCOUNTS_SHRINK_RATIO.to_string();
+    let default_drain_timeout_secs = &admin_rpc_service::DEFAULT_DRAIN_TIMEOUT_SECS.to_string();
+    let default_tpu_quic_max_connections = &DEFAULT_MAX_QUIC_CONNECTIONS.to_string();
+    let default_blockstore_archive_min_slot_age = &DEFAULT_ARCHIVE_MIN_SLOT_AGE.to_string();
+    let default_blockstore_archive_slots_per_pack = &DEFAULT_ARCHIVE_SLOTS_PER_PACK.to_string();
+    let blockstore_column_names = Database::column_names();
 
     let matches = App::new(crate_name!()).about(crate_description!())
         .version(solana_version::version!())
@@ -612,6 +650,15 @@ pub fn main() {
                        including the 'getConfirmedBlock' API.  \
                        This will cause an increase in disk usage and IOPS"),
         )
+        .arg(
+            Arg::with_name("instruction_parser_config")
+                .long("instruction-parser-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .help("JSON file describing the instructions of additional programs, \
+                       which are then decoded by the jsonParsed encoding of RPC responses"),
+        )
         .arg(
             Arg::with_name("enable_rpc_bigtable_ledger_storage")
                 .long("enable-rpc-bigtable-ledger-storage")
@@ -627,6 +674,19 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("rpc_ledger_storage")
+                .long("rpc-ledger-storage")
+                .value_name("STORAGE")
+                .takes_value(true)
+                .default_value("bigtable")
+                .validator(is_parsable::<LedgerStorageConfig>)
+                .help("Long-term ledger storage used by --enable-rpc-bigtable-ledger-storage \
+                       and --enable-bigtable-ledger-upload: bigtable, s3://BUCKET for an \
+                       S3-compatible object store, or a postgres:// connection string. \
+                       The S3 and PostgreSQL backends require a validator built with the \
+                       ledger-storage-s3 and ledger-storage-postgres features"),
+        )
         .arg(
             Arg::with_name("enable_cpi_and_log_storage")
                 .long("enable-cpi-and-log-storage")
@@ -635,6 +695,14 @@ pub fn main() {
                 .help("Include CPI inner instructions and logs in the \
                         historical transaction info stored"),
         )
+        .arg(
+            Arg::with_name("enable_program_signatures_index")
+                .long("enable-program-signatures-index")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Index the signatures of stored transactions by the programs \
+                       they invoke. This will cause an increase in disk usage and IOPS"),
+        )
         .arg(
             Arg::with_name("rpc_max_multiple_accounts")
                 .long("rpc-max-multiple-accounts")
@@ -923,6 +991,23 @@ pub fn main() {
                 /* .default_value() intentionally not used here! */
                 .help("Keep this amount of shreds in root slots."),
         )
+        .arg(
+            Arg::with_name("limit_ledger_age")
+                .long("limit-ledger-age")
+                .value_name("HOURS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .conflicts_with("limit_ledger_size")
+                .help("Keep the root slots whose block time is within this many hours \
+                       of the newest block time"),
+        )
+        .arg(
+            Arg::with_name("limit_ledger_dry_run")
+                .long("limit-ledger-dry-run")
+                .takes_value(false)
+                .help("Only log the slots that --limit-ledger-size or --limit-ledger-age \
+                       would purge, without purging them"),
+        )
         .arg(
             Arg::with_name("skip_poh_verify")
                 .long("skip-poh-verify")
@@ -935,6 +1020,22 @@ pub fn main() {
                 .takes_value(false)
                 .help("Use CUDA"),
         )
+        .arg(
+            Arg::with_name("poh_verify_backend")
+                .long("poh-verify-backend")
+                .value_name("BACKEND")
+                .takes_value(true)
+                .possible_values(&PohVerifyBackend::STRINGS)
+                .validator(is_parsable::<PohVerifyBackend>)
+                .default_value("auto")
+                .help("Backend used to verify the PoH hashes of entries. \
+                       auto: the fastest available backend; \
+                       cpu: portable CPU verification; \
+                       cpu-simd: AVX2/AVX512 CPU verification; \
+                       cuda: GPU verification, implies --cuda. \
+                       Falls back to auto if the backend is not available. \
+                       OpenCL is not supported"),
+        )
         .arg(
             clap::Arg::with_name("require_tower")
                 .long("require-tower")
@@ -1070,6 +1171,58 @@ pub fn main() {
                 .validator(is_parsable::<u64>)
                 .help("Milliseconds to wait in the TPU receiver for packet coalescing."),
         )
+        .arg(
+            Arg::with_name("broadcast_coding_shreds_per_fec_block")
+                .long("broadcast-coding-shreds-per-fec-block")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<u32>)
+                .help("Number of coding shreds broadcast per 32 data shreds, at most 32 \
+                       [default: 32]"),
+        )
+        .arg(
+            Arg::with_name("broadcast_adaptive_fec")
+                .long("broadcast-adaptive-fec")
+                .takes_value(false)
+                .help("Raise the broadcast coding rate when peers request repairs for \
+                       the slots this node recently produced as leader"),
+        )
+        .arg(
+            Arg::with_name("broadcast_max_entries_per_batch")
+                .long("broadcast-max-entries-per-batch")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Maximum number of entries shredded together by the broadcast stage \
+                       [default: 8]"),
+        )
+        .arg(
+            Arg::with_name("tpu_quic_max_connections")
+                .long("tpu-quic-max-connections")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value(default_tpu_quic_max_connections)
+                .help("Maximum number of concurrent QUIC connections accepted by the TPU"),
+        )
+        .arg(
+            Arg::with_name("tpu_quic_eviction_policy")
+                .long("tpu-quic-eviction-policy")
+                .value_name("POLICY")
+                .takes_value(true)
+                .possible_values(&ConnectionEvictionPolicy::STRINGS)
+                .validator(is_parsable::<ConnectionEvictionPolicy>)
+                .default_value("stake-and-recency")
+                .help(
+                    "Connection to evict when the TPU QUIC connection table is full. \
+                     stake: the lowest staked connection; \
+                     stake-and-recency: the lowest staked connection, preferring the least \
+                     recently active one; \
+                     reputation: the connection in the lowest stake tier, where stakes \
+                     within a factor of two share a tier, with the fewest delivered packets \
+                     less 10 per stream error",
+                ),
+        )
         .arg(
             Arg::with_name("rocksdb_max_compaction_jitter")
                 .long("rocksdb-max-compaction-jitter-slots")
@@ -1086,6 +1239,57 @@ pub fn main() {
                 .default_value("0.0.0.0")
                 .help("IP address to bind the validator ports"),
         )
+        .arg(
+            Arg::with_name("tvu_bind_address")
+                .long("tvu-bind-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to bind the TVU, retransmit and broadcast ports [default: use --bind-address]"),
+        )
+        .arg(
+            Arg::with_name("tvu_public_address")
+                .long("tvu-public-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to advertise in gossip for the TVU, retransmit and broadcast ports \
+                       [default: use --gossip-host]"),
+        )
+        .arg(
+            Arg::with_name("tpu_bind_address")
+                .long("tpu-bind-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to bind the TPU and TPU vote ports [default: use --bind-address]"),
+        )
+        .arg(
+            Arg::with_name("tpu_public_address")
+                .long("tpu-public-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to advertise in gossip for the TPU and TPU vote ports \
+                       [default: use --gossip-host]"),
+        )
+        .arg(
+            Arg::with_name("repair_bind_address")
+                .long("repair-bind-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to bind the repair and serve repair ports [default: use --bind-address]"),
+        )
+        .arg(
+            Arg::with_name("repair_public_address")
+                .long("repair-public-address")
+                .value_name("HOST")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host)
+                .help("IP address to advertise in gossip for the repair and serve repair ports \
+                       [default: use --gossip-host]"),
+        )
         .arg(
             Arg::with_name("rpc_bind_address")
                 .long("rpc-bind-address")
@@ -1122,6 +1326,35 @@ pub fn main() {
                 .default_value("30")
                 .help("Number of seconds before timing out RPC requests backed by BigTable"),
         )
+        .arg(
+            Arg::with_name("enable_rpc_method_metrics")
+                .long("enable-rpc-method-metrics")
+                .takes_value(false)
+                .help("Report latency and response size histograms of each RPC method \
+                       to the metrics backend"),
+        )
+        .arg(
+            Arg::with_name("rpc_method_latency_buckets")
+                .long("rpc-method-latency-buckets")
+                .value_name("MICROSECONDS")
+                .takes_value(true)
+                .use_delimiter(true)
+                .requires("enable_rpc_method_metrics")
+                .validator(is_parsable::<u64>)
+                .help("Comma separated upper bounds of the RPC method latency histogram \
+                       buckets [default: 1000,5000,10000,50000,100000,500000,1000000,5000000,10000000]"),
+        )
+        .arg(
+            Arg::with_name("rpc_method_payload_size_buckets")
+                .long("rpc-method-payload-size-buckets")
+                .value_name("BYTES")
+                .takes_value(true)
+                .use_delimiter(true)
+                .requires("enable_rpc_method_metrics")
+                .validator(is_parsable::<u64>)
+                .help("Comma separated upper bounds of the RPC method response size histogram \
+                       buckets [default: 1000,10000,100000,1000000,10000000,100000000]"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_worker_threads")
                 .long("rpc-pubsub-worker-threads")
@@ -1310,6 +1543,17 @@ pub fn main() {
                 .hidden(true)
                 .help("Specify the configuration file for the AccountsDb plugin."),
         )
+        .arg(
+            Arg::with_name("accountsdb_plugin_startup_accounts_per_second")
+                .long("accountsdb-plugin-startup-accounts-per-second")
+                .value_name("COUNT")
+                .takes_value(true)
+                .requires("accountsdb_plugin_config")
+                .validator(is_parsable::<u64>)
+                .hidden(true)
+                .help("Maximum number of accounts restored from the snapshot to notify the \
+                       AccountsDb plugins of per second at startup [default: unlimited]"),
+        )
         .arg(
             Arg::with_name("halt_on_known_validators_accounts_hash_mismatch")
                 .alias("halt-on-trusted-validators-accounts-hash-mismatch")
@@ -1322,12 +1566,33 @@ pub fn main() {
             Arg::with_name("snapshot_archive_format")
                 .long("snapshot-archive-format")
                 .alias("snapshot-compression") // Legacy name used by Solana v1.5.x and older
-                .possible_values(&["bz2", "gzip", "zstd", "tar", "none"])
+                .possible_values(&["bz2", "gzip", "zstd", "lz4", "tar", "none"])
                 .default_value("zstd")
                 .value_name("ARCHIVE_TYPE")
                 .takes_value(true)
                 .help("Snapshot archive format to use."),
         )
+        .arg(
+            Arg::with_name("snapshot_compression_level")
+                .long("snapshot-compression-level")
+                .value_name("LEVEL")
+                .takes_value(true)
+                .validator(is_parsable::<i32>)
+                .help("Compression level to use for snapshot archives. \
+                       The valid range depends on --snapshot-archive-format: \
+                       bz2 1-9, gzip 0-9, zstd 1-22, lz4 0-16. \
+                       [default: the format's own default level]"),
+        )
+        .arg(
+            Arg::with_name("snapshot_compression_threads")
+                .long("snapshot-compression-threads")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value("0")
+                .validator(is_parsable::<u32>)
+                .help("Number of worker threads used to compress zstd snapshot archives. \
+                       0 compresses on the snapshot packager thread itself"),
+        )
         .arg(
             Arg::with_name("max_genesis_archive_unpacked_size")
                 .long("max-genesis-archive-unpacked-size")
@@ -1352,6 +1617,107 @@ pub fn main() {
                     "Mode to recovery the ledger db write ahead log."
                 ),
         )
+        .arg(
+            Arg::with_name("rocksdb_compaction_style")
+                .long("rocksdb-compaction-style")
+                .value_name("STYLE")
+                .takes_value(true)
+                .possible_values(&["level", "universal"])
+                .default_value("level")
+                .help("Compaction style of the ledger db columns"),
+        )
+        .arg(
+            Arg::with_name("rocksdb_column_compaction_style")
+                .long("rocksdb-column-compaction-style")
+                .value_name("COLUMN=STYLE")
+                .takes_value(true)
+                .multiple(true)
+                .validator(column_compaction_style_validator)
+                .help("Override the compaction style of a single ledger db column. \
+                       May be specified multiple times"),
+        )
+        .arg(
+            Arg::with_name("rocksdb_compaction_priority")
+                .long("rocksdb-compaction-priority")
+                .value_name("PRIORITY")
+                .takes_value(true)
+                .possible_values(&["normal", "low"])
+                .default_value("normal")
+                .help("Scheduling priority of the ledger db compaction threads. \
+                       \"low\" lowers their CPU and IO priority so that they yield to replay"),
+        )
+        .arg(
+            Arg::with_name("rocksdb_shred_compression")
+                .long("rocksdb-shred-compression")
+                .value_name("COMPRESSION_TYPE")
+                .takes_value(true)
+                .possible_values(&["none", "lz4"])
+                .default_value("none")
+                .help("Rocksdb block compression of the data shreds stored in the \
+                       ledger db. Blocks are about one FEC set in size but are not \
+                       aligned to FEC sets. Existing shreds remain readable and are \
+                       rewritten with this compression as they get compacted. \
+                       See also `solana-validator compact-ledger --column data_shred`"),
+        )
+        .arg(
+            Arg::with_name("rocksdb_tuning_profile")
+                .long("rocksdb-tuning-profile")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("TOML or JSON file with per-column rocksdb options of the ledger db: \
+                       block_cache_size, block_size, bloom_filter_bits_per_key and \
+                       compression_per_level, set under [default] for every column \
+                       or under [columns.<COLUMN>] for a single column"),
+        )
+        .arg(
+            Arg::with_name("blockstore_archive")
+                .long("blockstore-archive")
+                .value_name("DIR|s3://BUCKET/PREFIX")
+                .takes_value(true)
+                .help("Move the shreds of old rooted slots out of the ledger db into immutable \
+                       packs stored in this directory or S3-compatible bucket, from which they \
+                       are read back as needed. S3 credentials are taken from the \
+                       AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables"),
+        )
+        .arg(
+            Arg::with_name("blockstore_archive_min_slot_age")
+                .long("blockstore-archive-min-slot-age")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .requires("blockstore_archive")
+                .validator(is_parsable::<u64>)
+                .default_value(default_blockstore_archive_min_slot_age)
+                .help("Archive rooted slots once they are this many slots older than the latest \
+                       root"),
+        )
+        .arg(
+            Arg::with_name("blockstore_archive_slots_per_pack")
+                .long("blockstore-archive-slots-per-pack")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .requires("blockstore_archive")
+                .validator(is_parsable::<usize>)
+                .default_value(default_blockstore_archive_slots_per_pack)
+                .help("Number of rooted slots in each archive pack"),
+        )
+        .arg(
+            Arg::with_name("blockstore_archive_s3_endpoint")
+                .long("blockstore-archive-s3-endpoint")
+                .value_name("URL")
+                .takes_value(true)
+                .requires("blockstore_archive")
+                .help("Endpoint of the S3-compatible service of an s3:// blockstore archive \
+                       [default: the AWS endpoint of --blockstore-archive-s3-region]"),
+        )
+        .arg(
+            Arg::with_name("blockstore_archive_s3_region")
+                .long("blockstore-archive-s3-region")
+                .value_name("REGION")
+                .takes_value(true)
+                .requires("blockstore_archive")
+                .default_value("us-east-1")
+                .help("Region of an s3:// blockstore archive"),
+        )
         .arg(
             Arg::with_name("no_bpf_jit")
                 .long("no-bpf-jit")
@@ -1559,6 +1925,24 @@ pub fn main() {
                     .takes_value(false)
                     .help("Request the validator exit immediately instead of waiting for a restart window")
             )
+            .arg(
+                Arg::with_name("drain")
+                    .long("drain")
+                    .takes_value(false)
+                    .help("Drain the validator before it exits: stop producing blocks, wait for \
+                           the next root, create a final snapshot and flush the blockstore. \
+                           The validator then exits with status code 3")
+            )
+            .arg(
+                Arg::with_name("drain_timeout")
+                    .long("drain-timeout")
+                    .takes_value(true)
+                    .validator(is_parsable::<u64>)
+                    .value_name("SECONDS")
+                    .default_value(default_drain_timeout_secs)
+                    .help("With --drain, the time the drain may take before the validator \
+                           gives up on it and exits normally")
+            )
             .arg(
                 Arg::with_name("monitor")
                     .short("m")
@@ -1623,6 +2007,86 @@ pub fn main() {
                     .help("Output display mode")
             )
         )
+        .subcommand(
+            SubCommand::with_name("compact-ledger")
+            .about("Manually compact ledger db columns of the running validator")
+            .arg(
+                Arg::with_name("columns")
+                    .long("column")
+                    .value_name("COLUMN")
+                    .takes_value(true)
+                    .multiple(true)
+                    .required(true)
+                    .possible_values(&blockstore_column_names)
+                    .help("Column to compact. May be specified multiple times")
+            )
+            .after_help("Note: a full compaction of a large column is IO heavy and may \
+                         slow down replay while it runs")
+        )
+        .subcommand(
+            SubCommand::with_name("shred-source-stats")
+            .about("Display how many shreds of the recently completed slots arrived through \
+                    turbine, arrived through repair or were recovered from erasure coding")
+            .arg(
+                Arg::with_name("slot")
+                    .long("slot")
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .validator(is_slot)
+                    .help("Only display the stats of this slot")
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("plugin")
+            .about("Manage the AccountsDb plugins of the running validator")
+            .long_about("Manage the AccountsDb plugins of the running validator. \
+                         Plugins can only be managed if the validator was started with at least \
+                         one --accountsdb-plugin-config, and a loaded plugin only receives the \
+                         kinds of notifications that the plugins the validator started with \
+                         enabled.")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(
+                SubCommand::with_name("list")
+                .about("List the loaded plugins")
+            )
+            .subcommand(
+                SubCommand::with_name("load")
+                .about("Load a plugin")
+                .arg(
+                    Arg::with_name("config")
+                        .index(1)
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Plugin config file")
+                )
+            )
+            .subcommand(
+                SubCommand::with_name("unload")
+                .about("Unload a plugin once the notifications in flight are delivered")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the plugin")
+                )
+            )
+            .subcommand(
+                SubCommand::with_name("reload")
+                .about("Unload a plugin and load it again from its config file, \
+                        without dropping any notification in between")
+                .arg(
+                    Arg::with_name("name")
+                        .index(1)
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the plugin")
+                )
+            )
+        )
         .subcommand(
             SubCommand::with_name("init")
             .about("Initialize the ledger directory then exit")
@@ -1770,10 +2234,108 @@ pub fn main() {
             }
             return;
         }
+        ("compact-ledger", Some(subcommand_matches)) => {
+            let columns = values_t_or_exit!(subcommand_matches, "columns", String);
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.compact_ledger(columns).await })
+                .unwrap_or_else(|err| {
+                    println!("compactLedger request failed: {}", err);
+                    exit(1);
+                });
+            println!("Ledger compaction started, see the validator log for progress");
+            return;
+        }
+        ("shred-source-stats", Some(subcommand_matches)) => {
+            let slot = value_t!(subcommand_matches, "slot", Slot).ok();
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let shred_source_stats = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.shred_source_stats(slot).await })
+                .unwrap_or_else(|err| {
+                    println!("shredSourceStats request failed: {}", err);
+                    exit(1);
+                });
+            if shred_source_stats.is_empty() {
+                println!("No shred source stats available");
+            }
+            for stats in shred_source_stats {
+                print!("{}", stats);
+            }
+            return;
+        }
+        ("plugin", Some(plugin_subcommand_matches)) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let runtime = admin_rpc_service::runtime();
+            match plugin_subcommand_matches.subcommand() {
+                ("list", _) => {
+                    let plugin_names = runtime
+                        .block_on(async move { admin_client.await?.accountsdb_plugin_list().await })
+                        .unwrap_or_else(|err| {
+                            println!("accountsDbPluginList request failed: {}", err);
+                            exit(1);
+                        });
+                    if plugin_names.is_empty() {
+                        println!("No plugins loaded");
+                    }
+                    for plugin_name in plugin_names {
+                        println!("{}", plugin_name);
+                    }
+                }
+                ("load", Some(subcommand_matches)) => {
+                    let config_file =
+                        fs::canonicalize(value_t_or_exit!(subcommand_matches, "config", PathBuf))
+                            .unwrap_or_else(|err| {
+                                println!("Unable to access plugin config file: {}", err);
+                                exit(1);
+                            });
+                    let config_file = config_file.to_str().unwrap().to_string();
+                    let plugin_name = runtime
+                        .block_on(async move {
+                            admin_client
+                                .await?
+                                .accountsdb_plugin_load(config_file)
+                                .await
+                        })
+                        .unwrap_or_else(|err| {
+                            println!("accountsDbPluginLoad request failed: {}", err);
+                            exit(1);
+                        });
+                    println!("Loaded plugin {}", plugin_name);
+                }
+                ("unload", Some(subcommand_matches)) => {
+                    let name = value_t_or_exit!(subcommand_matches, "name", String);
+                    runtime
+                        .block_on(async move {
+                            admin_client.await?.accountsdb_plugin_unload(name).await
+                        })
+                        .unwrap_or_else(|err| {
+                            println!("accountsDbPluginUnload request failed: {}", err);
+                            exit(1);
+                        });
+                    println!("Plugin unloaded");
+                }
+                ("reload", Some(subcommand_matches)) => {
+                    let name = value_t_or_exit!(subcommand_matches, "name", String);
+                    runtime
+                        .block_on(async move {
+                            admin_client.await?.accountsdb_plugin_reload(name).await
+                        })
+                        .unwrap_or_else(|err| {
+                            println!("accountsDbPluginReload request failed: {}", err);
+                            exit(1);
+                        });
+                    println!("Plugin reloaded");
+                }
+                _ => unreachable!(),
+            }
+            return;
+        }
         ("init", _) => Operation::Initialize,
         ("exit", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
             let force = subcommand_matches.is_present("force");
+            let drain = subcommand_matches.is_present("drain");
+            let drain_timeout_secs = value_t_or_exit!(subcommand_matches, "drain_timeout", u64);
             let monitor = subcommand_matches.is_present("monitor");
             let max_delinquent_stake =
                 value_t_or_exit!(subcommand_matches, "max_delinquent_stake", u8);
@@ -1787,13 +2349,25 @@ pub fn main() {
             }
 
             let admin_client = admin_rpc_service::connect(&ledger_path);
-            admin_rpc_service::runtime()
-                .block_on(async move { admin_client.await?.exit().await })
-                .unwrap_or_else(|err| {
-                    println!("exit request failed: {}", err);
-                    exit(1);
-                });
-            println!("Exit request sent");
+            if drain {
+                admin_rpc_service::runtime()
+                    .block_on(async move {
+                        admin_client.await?.drain_and_exit(drain_timeout_secs).await
+                    })
+                    .unwrap_or_else(|err| {
+                        println!("drainAndExit request failed: {}", err);
+                        exit(1);
+                    });
+                println!("Drain and exit request sent");
+            } else {
+                admin_rpc_service::runtime()
+                    .block_on(async move { admin_client.await?.exit().await })
+                    .unwrap_or_else(|err| {
+                        println!("exit request failed: {}", err);
+                        exit(1);
+                    });
+                println!("Exit request sent");
+            }
 
             if monitor {
                 monitor_validator(&ledger_path);
@@ -1881,7 +2455,8 @@ pub fn main() {
     info!("{} {}", crate_name!(), solana_version::version!());
     info!("Starting validator with: {:#?}", std::env::args_os());
 
-    let cuda = matches.is_present("cuda");
+    let poh_verify_backend = value_t_or_exit!(matches, "poh_verify_backend", PohVerifyBackend);
+    let cuda = matches.is_present("cuda") || poh_verify_backend == PohVerifyBackend::Cuda;
     if cuda {
         solana_perf::perf_libs::init_cuda();
         enable_recycler_warming();
@@ -1921,9 +2496,99 @@ pub fn main() {
         value_t!(matches, "rocksdb_max_compaction_jitter", u64).ok();
     let tpu_coalesce_ms =
         value_t!(matches, "tpu_coalesce_ms", u64).unwrap_or(DEFAULT_TPU_COALESCE_MS);
+    let broadcast_config = {
+        let default = BroadcastConfig::default();
+        BroadcastConfig {
+            coding_shreds_per_fec_block: value_t!(
+                matches,
+                "broadcast_coding_shreds_per_fec_block",
+                u32
+            )
+            .unwrap_or(default.coding_shreds_per_fec_block),
+            adaptive_fec: matches.is_present("broadcast_adaptive_fec"),
+            max_entries_per_batch: value_t!(matches, "broadcast_max_entries_per_batch", usize)
+                .unwrap_or(default.max_entries_per_batch),
+        }
+    };
+    if !(1..=32).contains(&broadcast_config.coding_shreds_per_fec_block) {
+        eprintln!("--broadcast-coding-shreds-per-fec-block must be between 1 and 32");
+        exit(1);
+    }
+    let blockstore_compaction = BlockstoreCompactionOptions {
+        default_style: BlockstoreCompactionStyle::from(
+            matches.value_of("rocksdb_compaction_style").unwrap(),
+        ),
+        column_styles: matches
+            .values_of("rocksdb_column_compaction_style")
+            .unwrap_or_default()
+            .map(|value| {
+                let (column, style) = value.split_once('=').unwrap();
+                (column.to_string(), BlockstoreCompactionStyle::from(style))
+            })
+            .collect(),
+        priority: BlockstoreCompactionPriority::from(
+            matches.value_of("rocksdb_compaction_priority").unwrap(),
+        ),
+    };
+    let blockstore_tuning = matches
+        .value_of("rocksdb_tuning_profile")
+        .map(|path| {
+            BlockstoreTuningProfile::load(Path::new(path)).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            })
+        })
+        .unwrap_or_default();
+    let blockstore_archive = matches.value_of("blockstore_archive").map(|location| {
+        let storage = if location.starts_with("s3://") {
+            let credential = |name| {
+                env::var(name).unwrap_or_else(|_| {
+                    eprintln!("{} must be set to use an s3:// blockstore archive", name);
+                    exit(1);
+                })
+            };
+            S3StorageConfig::from_location(
+                location,
+                matches
+                    .value_of("blockstore_archive_s3_endpoint")
+                    .map(|endpoint| endpoint.to_string()),
+                matches
+                    .value_of("blockstore_archive_s3_region")
+                    .unwrap()
+                    .to_string(),
+                credential("AWS_ACCESS_KEY_ID"),
+                credential("AWS_SECRET_ACCESS_KEY"),
+            )
+            .map(ArchiveStorageConfig::S3)
+            .unwrap_or_else(|err| {
+                eprintln!("Invalid --blockstore-archive: {}", err);
+                exit(1);
+            })
+        } else {
+            ArchiveStorageConfig::Local(PathBuf::from(location))
+        };
+        let slots_per_pack = value_t_or_exit!(matches, "blockstore_archive_slots_per_pack", usize);
+        if slots_per_pack == 0 {
+            eprintln!("--blockstore-archive-slots-per-pack must be greater than zero");
+            exit(1);
+        }
+        BlockstoreArchiveConfig {
+            storage,
+            min_slot_age: value_t_or_exit!(matches, "blockstore_archive_min_slot_age", Slot),
+            slots_per_pack,
+        }
+    });
     let wal_recovery_mode = matches
         .value_of("wal_recovery_mode")
         .map(BlockstoreRecoveryMode::from);
+    let tpu_quic_connection_table_config = ConnectionTableConfig {
+        max_connections: value_t_or_exit!(matches, "tpu_quic_max_connections", usize),
+        eviction_policy: value_t_or_exit!(
+            matches,
+            "tpu_quic_eviction_policy",
+            ConnectionEvictionPolicy
+        ),
+    };
 
     // Canonicalize ledger path to avoid issues with symlink creation
     let _ = fs::create_dir_all(&ledger_path);
@@ -2130,6 +2795,31 @@ pub fn main() {
     } else {
         None
     };
+    let accountsdb_plugin_startup_accounts_per_second = value_t!(
+        matches,
+        "accountsdb_plugin_startup_accounts_per_second",
+        u64
+    )
+    .ok();
+    if accountsdb_plugin_startup_accounts_per_second == Some(0) {
+        eprintln!("--accountsdb-plugin-startup-accounts-per-second must be greater than zero");
+        exit(1);
+    }
+
+    if let Some(instruction_parser_configs) = matches.values_of("instruction_parser_config") {
+        for instruction_parser_config in instruction_parser_configs {
+            match register_configured_instruction_parsers(Path::new(instruction_parser_config)) {
+                Ok(num_programs) => info!(
+                    "Registered instruction parsers of {} programs from {}",
+                    num_programs, instruction_parser_config
+                ),
+                Err(err) => {
+                    eprintln!("Failed to load instruction parser config: {}", err);
+                    exit(1);
+                }
+            }
+        }
+    }
 
     if matches.is_present("minimal_rpc_api") {
         warn!("--minimal-rpc-api is now the default behavior. This flag is deprecated and can be removed from the launch args")
@@ -2150,9 +2840,11 @@ pub fn main() {
         rpc_config: JsonRpcConfig {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_cpi_and_log_storage: matches.is_present("enable_cpi_and_log_storage"),
+            enable_program_signatures_index: matches.is_present("enable_program_signatures_index"),
             enable_bigtable_ledger_storage: matches
                 .is_present("enable_rpc_bigtable_ledger_storage"),
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
+            ledger_storage: value_t_or_exit!(matches, "rpc_ledger_storage", LedgerStorageConfig),
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
             }),
@@ -2173,11 +2865,25 @@ pub fn main() {
             rpc_bigtable_timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            method_metrics: matches.is_present("enable_rpc_method_metrics").then(|| {
+                let default_config = RpcMethodMetricsConfig::default();
+                RpcMethodMetricsConfig {
+                    latency_buckets_us: values_t!(matches, "rpc_method_latency_buckets", u64)
+                        .unwrap_or(default_config.latency_buckets_us),
+                    payload_size_buckets: values_t!(
+                        matches,
+                        "rpc_method_payload_size_buckets",
+                        u64
+                    )
+                    .unwrap_or(default_config.payload_size_buckets),
+                }
+            }),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
         },
         accountsdb_repl_service_config,
         accountsdb_plugin_config_files,
+        accountsdb_plugin_startup_accounts_per_second,
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
             (
                 SocketAddr::new(rpc_bind_address, rpc_port),
@@ -2217,6 +2923,12 @@ pub fn main() {
         rocksdb_compaction_interval,
         rocksdb_max_compaction_jitter,
         wal_recovery_mode,
+        blockstore_compaction,
+        blockstore_shred_compression: BlockstoreCompressionType::from(
+            matches.value_of("rocksdb_shred_compression").unwrap(),
+        ),
+        blockstore_tuning,
+        blockstore_archive,
         poh_verify: !matches.is_present("skip_poh_verify"),
         debug_keys,
         contact_debug_interval,
@@ -2253,6 +2965,8 @@ pub fn main() {
         accounts_db_skip_shrink: matches.is_present("accounts_db_skip_shrink"),
         accounts_db_use_index_hash_calculation: matches.is_present("accounts_db_index_hashing"),
         tpu_coalesce_ms,
+        broadcast_config,
+        tpu_quic_connection_table_config,
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         accounts_shrink_ratio,
         ..ValidatorConfig::default()
@@ -2354,11 +3068,39 @@ pub fn main() {
             "bz2" => ArchiveFormat::TarBzip2,
             "gzip" => ArchiveFormat::TarGzip,
             "zstd" => ArchiveFormat::TarZstd,
+            "lz4" => ArchiveFormat::TarLz4,
             "tar" | "none" => ArchiveFormat::Tar,
             _ => panic!("Archive format not recognized: {}", archive_format_str),
         }
     };
 
+    let archive_compression = ArchiveCompressionOptions {
+        level: value_t!(matches, "snapshot_compression_level", i32).ok(),
+        num_threads: value_t_or_exit!(matches, "snapshot_compression_threads", u32),
+    };
+    if let Some(level) = archive_compression.level {
+        match archive_format.compression_levels() {
+            Some(levels) if levels.contains(&level) => {}
+            Some(levels) => {
+                eprintln!(
+                    "Invalid --snapshot-compression-level {}: must be between {} and {} for {:?}",
+                    level,
+                    levels.start(),
+                    levels.end(),
+                    archive_format,
+                );
+                exit(1);
+            }
+            None => {
+                eprintln!(
+                    "--snapshot-compression-level is not supported for {:?}",
+                    archive_format
+                );
+                exit(1);
+            }
+        }
+    }
+
     let snapshot_version =
         matches
             .value_of("snapshot_version")
@@ -2391,6 +3133,7 @@ pub fn main() {
         bank_snapshots_dir,
         snapshot_archives_dir: snapshot_archives_dir.clone(),
         archive_format,
+        archive_compression,
         snapshot_version,
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
@@ -2435,6 +3178,14 @@ pub fn main() {
         }
         validator_config.max_ledger_shreds = Some(limit_ledger_size);
     }
+    if let Ok(limit_ledger_age) = value_t!(matches, "limit_ledger_age", u64) {
+        if limit_ledger_age == 0 {
+            eprintln!("The provided --limit-ledger-age value must be at least one hour");
+            exit(1);
+        }
+        validator_config.max_ledger_age = Some(Duration::from_secs(limit_ledger_age * 60 * 60));
+    }
+    validator_config.ledger_cleanup_dry_run = matches.is_present("limit_ledger_dry_run");
 
     if matches.is_present("halt_on_known_validators_accounts_hash_mismatch") {
         validator_config.halt_on_known_validators_accounts_hash_mismatch = true;
@@ -2460,15 +3211,20 @@ pub fn main() {
 
     let start_progress = Arc::new(RwLock::new(ValidatorStartProgress::default()));
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_blockstore = Arc::new(RwLock::new(None));
+    let admin_service_accountsdb_plugin_manager = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
             rpc_addr: validator_config.rpc_addrs.map(|(rpc_addr, _)| rpc_addr),
             start_time: std::time::SystemTime::now(),
             validator_exit: validator_config.validator_exit.clone(),
+            validator_drain: validator_config.validator_drain.clone(),
             start_progress: start_progress.clone(),
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
+            blockstore: admin_service_blockstore.clone(),
+            accountsdb_plugin_manager: admin_service_accountsdb_plugin_manager.clone(),
             tower_storage: validator_config.tower_storage.clone(),
         },
     );
@@ -2530,11 +3286,35 @@ pub fn main() {
         .map(ContactInfo::new_gossip_entry_point)
         .collect::<Vec<_>>();
 
-    let mut node = Node::new_with_external_ip(
+    let parse_ip = |name: &str| {
+        matches.value_of(name).map(|host| {
+            solana_net_utils::parse_host(host).unwrap_or_else(|err| {
+                eprintln!("Failed to parse --{}: {}", name.replace('_', "-"), err);
+                exit(1);
+            })
+        })
+    };
+    let node_interfaces = NodeInterfaces {
+        tvu: NodeInterface {
+            bind_ip: parse_ip("tvu_bind_address"),
+            public_ip: parse_ip("tvu_public_address"),
+        },
+        tpu: NodeInterface {
+            bind_ip: parse_ip("tpu_bind_address"),
+            public_ip: parse_ip("tpu_public_address"),
+        },
+        repair: NodeInterface {
+            bind_ip: parse_ip("repair_bind_address"),
+            public_ip: parse_ip("repair_public_address"),
+        },
+    };
+
+    let mut node = Node::new_multihomed(
         &identity_keypair.pubkey(),
         &gossip_addr,
         dynamic_port_range,
         bind_address,
+        &node_interfaces,
     );
 
     if restricted_repair_only_mode {
@@ -2566,6 +3346,12 @@ pub fn main() {
     solana_metrics::set_panic_hook("validator");
 
     solana_entry::entry::init_poh();
+    info!(
+        "Available PoH verify backends: {:?}",
+        PohVerifyBackend::detect()
+    );
+    let poh_verify_backend = solana_entry::entry::set_poh_verify_backend(poh_verify_backend);
+    info!("Using PoH verify backend: {:?}", poh_verify_backend);
     snapshot_utils::remove_tmp_snapshot_archives(&snapshot_archives_dir);
 
     let identity_keypair = Arc::new(identity_keypair);
@@ -2612,6 +3398,9 @@ pub fn main() {
         socket_addr_space,
     );
     *admin_service_cluster_info.write().unwrap() = Some(validator.cluster_info.clone());
+    *admin_service_blockstore.write().unwrap() = Some(validator.blockstore.clone());
+    *admin_service_accountsdb_plugin_manager.write().unwrap() =
+        validator.accountsdb_plugin_manager();
 
     if let Some(filename) = init_complete_file {
         File::create(filename).unwrap_or_else(|_| {
@@ -2622,6 +3411,9 @@ pub fn main() {
     info!("Validator initialized");
     validator.join();
     info!("Validator exiting..");
+    if validator_config.validator_drain.is_drained() {
+        exit(admin_rpc_service::DRAINED_EXIT_CODE);
+    }
 }
 
 fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
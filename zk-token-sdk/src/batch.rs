@@ -0,0 +1,140 @@
+//! Batched verification of proof equations.
+//!
+//! The proofs in this crate are verified by checking that a multiscalar multiplication evaluates
+//! to the identity. Instead of checking each of these multiscalar multiplications on its own,
+//! their terms can be collected into a `MultiscalarMulBatch`. Each equation is scaled by an
+//! independent weight so that the combined multiscalar multiplication evaluates to the identity
+//! only if, with overwhelming probability, each of the equations does.
+//!
+//! The weights are challenges of a transcript of all the equations in the batch rather than
+//! random scalars, so that verification is deterministic and does not depend on the randomness of
+//! the host, which is not available to on-chain programs.
+
+use {
+    crate::{errors::ProofError, transcript::TranscriptProtocol},
+    curve25519_dalek::{
+        ristretto::RistrettoPoint,
+        scalar::Scalar,
+        traits::{IsIdentity, VartimeMultiscalarMul},
+    },
+    merlin::Transcript,
+};
+
+/// Terms of the proof equations that are yet to be checked.
+#[derive(Default)]
+pub struct MultiscalarMulBatch {
+    scalars: Vec<Scalar>,
+    points: Vec<RistrettoPoint>,
+    /// The end of the terms of each equation and the error that identifies the proof it belongs to
+    equations: Vec<(usize, ProofError)>,
+}
+
+impl MultiscalarMulBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an equation `sum(scalars[i] * points[i]) == identity` of the proof that is identified by
+    /// `error` to the batch.
+    pub(crate) fn append<I, J>(&mut self, error: ProofError, scalars: I, points: J)
+    where
+        I: IntoIterator<Item = Scalar>,
+        J: IntoIterator<Item = RistrettoPoint>,
+    {
+        self.scalars.extend(scalars);
+        self.points.extend(points);
+        debug_assert_eq!(self.scalars.len(), self.points.len());
+        self.equations.push((self.scalars.len(), error));
+    }
+
+    /// Derives the weight of each equation from a transcript of every equation in the batch.
+    fn weights(&self) -> Vec<Scalar> {
+        let mut transcript = Transcript::new(b"multiscalar-mul-batch");
+        transcript.append_u64(b"n", self.equations.len() as u64);
+
+        let mut start = 0;
+        for (end, _) in &self.equations {
+            transcript.append_u64(b"m", (*end - start) as u64);
+            for (scalar, point) in self.scalars[start..*end]
+                .iter()
+                .zip(&self.points[start..*end])
+            {
+                transcript.append_scalar(b"scalar", scalar);
+                transcript.append_point(b"point", &point.compress());
+            }
+            start = *end;
+        }
+
+        self.equations
+            .iter()
+            .map(|_| transcript.challenge_scalar(b"weight"))
+            .collect()
+    }
+
+    /// Checks every equation in the batch with a single multiscalar multiplication.
+    pub fn verify(&self) -> bool {
+        let mut start = 0;
+        let mut weighted_scalars = Vec::with_capacity(self.scalars.len());
+        for ((end, _), weight) in self.equations.iter().zip(self.weights()) {
+            weighted_scalars.extend(
+                self.scalars[start..*end]
+                    .iter()
+                    .map(|scalar| weight * scalar),
+            );
+            start = *end;
+        }
+
+        RistrettoPoint::vartime_multiscalar_mul(&weighted_scalars, &self.points).is_identity()
+    }
+
+    /// Checks every equation in the batch, identifying the proof of the first equation that does
+    /// not hold if the batch fails to verify.
+    ///
+    /// The equations are only checked one by one once the batch as a whole has failed, so valid
+    /// batches cost a single multiscalar multiplication.
+    pub fn check(&self) -> Result<(), ProofError> {
+        if self.verify() {
+            return Ok(());
+        }
+
+        let mut start = 0;
+        for (end, error) in &self.equations {
+            let scalars = &self.scalars[start..*end];
+            let points = &self.points[start..*end];
+            if !RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity() {
+                return Err(error.clone());
+            }
+            start = *end;
+        }
+
+        Err(ProofError::Verification)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT, rand::rngs::OsRng};
+
+    #[test]
+    fn test_multiscalar_mul_batch() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let x = Scalar::random(&mut OsRng);
+
+        let mut batch = MultiscalarMulBatch::new();
+        assert!(batch.verify());
+
+        // x * g - x * g == identity
+        batch.append(ProofError::EqualityProofFailed, vec![x, -x], vec![g, g]);
+        assert!(batch.verify());
+        assert_eq!(batch.check(), Ok(()));
+
+        // x * g - g != identity
+        batch.append(
+            ProofError::RangeProofFailed,
+            vec![x, -Scalar::one()],
+            vec![g, g],
+        );
+        assert!(!batch.verify());
+        assert_eq!(batch.check(), Err(ProofError::RangeProofFailed));
+    }
+}
@@ -0,0 +1,104 @@
+//! Cooperative cancellation of proof generation
+//!
+//! The async provers yield to the executor between sub-proofs through `checkpoint`, which also
+//! aborts the generation once its `CancellationToken` is cancelled. The synchronous provers drive
+//! the same code to completion with `block_on`.
+use {
+    crate::errors::ProofError,
+    std::{
+        future::Future,
+        pin::Pin,
+        ptr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    },
+};
+
+/// A handle that aborts the proof generations that it was passed to
+///
+/// Clones of a token share the same cancellation state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aborts the proof generations that use this token at their next checkpoint
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Yields to the executor once, then fails with `ProofError::Cancelled` if `token` is cancelled
+pub(crate) async fn checkpoint(token: &CancellationToken) -> Result<(), ProofError> {
+    YieldNow(false).await;
+    if token.is_cancelled() {
+        Err(ProofError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs a future that only ever suspends at a `checkpoint` to completion on the current thread
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    // Safety: the vtable functions ignore the data pointer, so they uphold the `RawWaker`
+    // contract trivially
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let token = CancellationToken::new();
+        assert!(block_on(checkpoint(&token)).is_ok());
+
+        token.clone().cancel();
+        assert_eq!(block_on(checkpoint(&token)), Err(ProofError::Cancelled));
+    }
+}
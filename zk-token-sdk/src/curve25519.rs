@@ -0,0 +1,217 @@
+//! Group operations on curve25519 ristretto points.
+//!
+//! On-chain, the operations are carried out by the `sol_curve_*` syscalls; off-chain, they are
+//! computed directly. All of the operations take and return points in their compressed encoding
+//! and return `None` if one of the inputs is not a valid encoding.
+
+pub use target_arch::*;
+
+#[cfg(not(target_arch = "bpf"))]
+mod target_arch {
+    use {
+        crate::zk_token_elgamal::pod,
+        curve25519_dalek::{
+            ristretto::{CompressedRistretto, RistrettoPoint},
+            scalar::Scalar,
+            traits::VartimeMultiscalarMul,
+        },
+    };
+
+    fn decompress(point: &pod::CompressedRistretto) -> Option<RistrettoPoint> {
+        CompressedRistretto(point.0).decompress()
+    }
+
+    fn from_canonical(scalar: &pod::Scalar) -> Option<Scalar> {
+        Scalar::from_canonical_bytes(scalar.0)
+    }
+
+    fn compress(point: RistrettoPoint) -> pod::CompressedRistretto {
+        pod::CompressedRistretto(point.compress().to_bytes())
+    }
+
+    pub fn validate_ristretto(point: &pod::CompressedRistretto) -> bool {
+        decompress(point).is_some()
+    }
+
+    pub fn add_ristretto(
+        left_point: &pod::CompressedRistretto,
+        right_point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        Some(compress(decompress(left_point)? + decompress(right_point)?))
+    }
+
+    pub fn subtract_ristretto(
+        left_point: &pod::CompressedRistretto,
+        right_point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        Some(compress(decompress(left_point)? - decompress(right_point)?))
+    }
+
+    pub fn multiply_ristretto(
+        scalar: &pod::Scalar,
+        point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        Some(compress(from_canonical(scalar)? * decompress(point)?))
+    }
+
+    pub fn multiscalar_multiply_ristretto(
+        scalars: &[pod::Scalar],
+        points: &[pod::CompressedRistretto],
+    ) -> Option<pod::CompressedRistretto> {
+        if scalars.len() != points.len() {
+            return None;
+        }
+
+        let scalars = scalars
+            .iter()
+            .map(from_canonical)
+            .collect::<Option<Vec<_>>>()?;
+        let points = points.iter().map(decompress).collect::<Option<Vec<_>>>()?;
+
+        Some(compress(RistrettoPoint::vartime_multiscalar_mul(
+            scalars, points,
+        )))
+    }
+}
+
+#[cfg(target_arch = "bpf")]
+#[allow(unused_variables)]
+mod target_arch {
+    use {super::*, crate::zk_token_elgamal::pod, bytemuck::Zeroable};
+
+    fn group_op(
+        op: u64,
+        left_input: &[u8; 32],
+        right_input: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        let mut result_point = pod::CompressedRistretto::zeroed();
+        let result = unsafe {
+            sol_curve_group_op(
+                CURVE25519_RISTRETTO,
+                op,
+                left_input as *const u8,
+                &right_input.0 as *const u8,
+                &mut result_point.0 as *mut u8,
+            )
+        };
+
+        if result == 0 {
+            Some(result_point)
+        } else {
+            None
+        }
+    }
+
+    pub fn validate_ristretto(point: &pod::CompressedRistretto) -> bool {
+        let result =
+            unsafe { sol_curve_validate_point(CURVE25519_RISTRETTO, &point.0 as *const u8) };
+        result == 0
+    }
+
+    pub fn add_ristretto(
+        left_point: &pod::CompressedRistretto,
+        right_point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        group_op(OP_ADD, &left_point.0, right_point)
+    }
+
+    pub fn subtract_ristretto(
+        left_point: &pod::CompressedRistretto,
+        right_point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        group_op(OP_SUB, &left_point.0, right_point)
+    }
+
+    pub fn multiply_ristretto(
+        scalar: &pod::Scalar,
+        point: &pod::CompressedRistretto,
+    ) -> Option<pod::CompressedRistretto> {
+        group_op(OP_MUL, &scalar.0, point)
+    }
+
+    pub fn multiscalar_multiply_ristretto(
+        scalars: &[pod::Scalar],
+        points: &[pod::CompressedRistretto],
+    ) -> Option<pod::CompressedRistretto> {
+        if scalars.len() != points.len() {
+            return None;
+        }
+
+        let mut result_point = pod::CompressedRistretto::zeroed();
+        let result = unsafe {
+            sol_curve_multiscalar_mul(
+                CURVE25519_RISTRETTO,
+                scalars.as_ptr() as *const u8,
+                points.as_ptr() as *const u8,
+                points.len() as u64,
+                &mut result_point.0 as *mut u8,
+            )
+        };
+
+        if result == 0 {
+            Some(result_point)
+        } else {
+            None
+        }
+    }
+}
+
+/// Identifier of the ristretto group over curve25519
+pub const CURVE25519_RISTRETTO: u64 = 1;
+
+pub const OP_ADD: u64 = 0;
+pub const OP_SUB: u64 = 1;
+/// Multiplies the point by a scalar, which is passed as the left input of the operation
+pub const OP_MUL: u64 = 2;
+
+extern "C" {
+    pub fn sol_curve_validate_point(curve_id: u64, point: *const u8) -> u64;
+    pub fn sol_curve_group_op(
+        curve_id: u64,
+        op: u64,
+        left_input: *const u8,
+        right_input: *const u8,
+        result_point: *mut u8,
+    ) -> u64;
+    pub fn sol_curve_multiscalar_mul(
+        curve_id: u64,
+        scalars: *const u8,
+        points: *const u8,
+        points_len: u64,
+        result_point: *mut u8,
+    ) -> u64;
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::zk_token_elgamal::pod,
+        curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar},
+    };
+
+    #[test]
+    fn test_ristretto_group_ops() {
+        let g = pod::CompressedRistretto(RISTRETTO_BASEPOINT_POINT.compress().to_bytes());
+        let two = pod::Scalar(Scalar::from(2_u64).to_bytes());
+        let three = pod::Scalar(Scalar::from(3_u64).to_bytes());
+
+        assert!(validate_ristretto(&g));
+        assert!(!validate_ristretto(&pod::CompressedRistretto([0xff; 32])));
+
+        let g_2 = multiply_ristretto(&two, &g).unwrap();
+        let g_3 = add_ristretto(&g_2, &g).unwrap();
+        assert!(g_3 == multiply_ristretto(&three, &g).unwrap());
+        assert!(g == subtract_ristretto(&g_3, &g_2).unwrap());
+
+        // 2 * g + 3 * (2 * g) == 8 * g
+        let g_8 = multiscalar_multiply_ristretto(&[two, three], &[g, g_2]).unwrap();
+        let eight = pod::Scalar(Scalar::from(8_u64).to_bytes());
+        assert!(g_8 == multiply_ristretto(&eight, &g).unwrap());
+
+        // invalid points and non-canonical scalars are rejected
+        assert!(add_ristretto(&g, &pod::CompressedRistretto([0xff; 32])).is_none());
+        assert!(multiply_ristretto(&pod::Scalar([0xff; 32]), &g).is_none());
+        assert!(multiscalar_multiply_ristretto(&[two], &[g, g_2]).is_none());
+    }
+}
@@ -1,15 +1,28 @@
 #![cfg(not(target_arch = "bpf"))]
 
 use {
-    curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity},
+    crate::errors::ProofError,
+    curve25519_dalek::{
+        constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+        traits::Identity,
+    },
+    memmap2::Mmap,
+    rayon::prelude::*,
     serde::{Deserialize, Serialize},
-    std::collections::HashMap,
+    sha3::{Digest, Sha3_256},
+    std::{
+        collections::HashMap,
+        convert::TryInto,
+        fs::File,
+        io::{self, BufWriter, Write},
+        ops::Range,
+        path::Path,
+    },
+    subtle::{Choice, ConditionallySelectable, CtOption},
 };
 
-const TWO15: u32 = 32768;
-const TWO14: u32 = 16384; // 2^14
-                          // const TWO16: u32 = 65536; // 2^16
-const TWO18: u32 = 262144; // 2^18
+/// Number of bits of the solution covered by the default pre-computed table
+const DEFAULT_TABLE_BITS: u32 = 18;
 
 /// Type that captures a discrete log challenge.
 ///
@@ -25,30 +38,236 @@ pub struct DiscreteLog {
 #[derive(Serialize, Deserialize, Default)]
 pub struct DecodeU32Precomputation(HashMap<[u8; 32], u32>);
 
-/// Builds a HashMap of 2^18 elements
-fn decode_u32_precomputation(generator: RistrettoPoint) -> DecodeU32Precomputation {
+/// Lookup table of the pre-computed points needed to solve a 32-bit discrete log instance.
+pub trait DecodeU32PrecomputationTable {
+    /// Returns the `x_hi` such that the compressed point is
+    /// `x_hi * 2^(32 - table_bits) * generator`.
+    fn get(&self, point: &[u8; 32]) -> Option<u32>;
+
+    /// Number of entries of the table, which is 2^`table_bits`.
+    fn num_entries(&self) -> u64;
+
+    /// Number of bits of the solution covered by the table.
+    fn table_bits(&self) -> u32 {
+        self.num_entries().trailing_zeros().min(32)
+    }
+}
+
+impl DecodeU32PrecomputationTable for DecodeU32Precomputation {
+    fn get(&self, point: &[u8; 32]) -> Option<u32> {
+        self.0.get(point).copied()
+    }
+
+    fn num_entries(&self) -> u64 {
+        self.0.len() as u64
+    }
+}
+
+/// Magic bytes at the start of a persisted precomputation table, ending with the format version
+const MAPPED_TABLE_MAGIC: &[u8; 8] = b"DLOGU32\x01";
+/// Length of the header of a persisted table: the magic bytes, the table bits as a little-endian
+/// u32, and the SHA3-256 checksum of the entries
+const MAPPED_TABLE_HEADER_LEN: usize = 44;
+/// Length of a persisted table entry: a 32-byte compressed point followed by a little-endian u32
+const MAPPED_TABLE_ENTRY_LEN: usize = 36;
+
+impl DecodeU32Precomputation {
+    /// Writes the table to a file in a flat format that can be memory-mapped by
+    /// `MappedDecodeU32Precomputation::open`.
+    ///
+    /// The file consists of a header followed by the entries of the table sorted by compressed
+    /// point. The header holds the number of bits covered by the table and a checksum of the
+    /// entries.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if !self.num_entries().is_power_of_two() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "discrete log precomputation table size is not a power of two",
+            ));
+        }
+
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by_key(|(point, _)| *point);
+
+        let mut hasher = Sha3_256::new();
+        for (point, x_hi) in &entries {
+            hasher.update(point);
+            hasher.update(x_hi.to_le_bytes());
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAPPED_TABLE_MAGIC)?;
+        writer.write_all(&self.table_bits().to_le_bytes())?;
+        writer.write_all(&hasher.finalize())?;
+        for (point, x_hi) in entries {
+            writer.write_all(point)?;
+            writer.write_all(&x_hi.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+}
+
+/// Pre-computed table that is memory-mapped from a file written by
+/// `DecodeU32Precomputation::write_to_file`.
+///
+/// Loading the table only maps the file, so the table can be used without the cost of
+/// rebuilding or deserializing the HashMap. Lookups binary search the sorted entries.
+pub struct MappedDecodeU32Precomputation(Mmap);
+
+impl MappedDecodeU32Precomputation {
+    /// Memory-maps a persisted table, checking its header and length.
+    ///
+    /// The entries are not read, so opening the table is cheap regardless of its size; use
+    /// `verify_checksum` to check the entries against the checksum in the header. The file must
+    /// not be modified while it is mapped.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the table is only read, and the caller guarantees that the file is not modified
+        // while it is mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        if mmap.len() < MAPPED_TABLE_HEADER_LEN
+            || &mmap[..MAPPED_TABLE_MAGIC.len()] != MAPPED_TABLE_MAGIC
+        {
+            return Err(invalid("not a discrete log precomputation table"));
+        }
+        let table = Self(mmap);
+        if table.header_table_bits() > 32 {
+            return Err(invalid("invalid discrete log precomputation table size"));
+        }
+        if table.entries().len() as u64 != table.num_entries() * MAPPED_TABLE_ENTRY_LEN as u64 {
+            return Err(invalid("truncated discrete log precomputation table"));
+        }
+
+        Ok(table)
+    }
+
+    /// Reads every entry of the table and checks them against the checksum in the header.
+    pub fn verify_checksum(&self) -> bool {
+        Sha3_256::digest(self.entries()).as_slice() == &self.0[12..MAPPED_TABLE_HEADER_LEN]
+    }
+
+    fn header_table_bits(&self) -> u32 {
+        u32::from_le_bytes(self.0[8..12].try_into().unwrap())
+    }
+
+    fn entries(&self) -> &[u8] {
+        &self.0[MAPPED_TABLE_HEADER_LEN..]
+    }
+}
+
+impl DecodeU32PrecomputationTable for MappedDecodeU32Precomputation {
+    fn get(&self, point: &[u8; 32]) -> Option<u32> {
+        let entries = self.entries();
+        let (mut lo, mut hi) = (0, entries.len() / MAPPED_TABLE_ENTRY_LEN);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let entry = &entries[mid * MAPPED_TABLE_ENTRY_LEN..(mid + 1) * MAPPED_TABLE_ENTRY_LEN];
+            match entry[..32].cmp(&point[..]) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    return Some(u32::from_le_bytes(entry[32..].try_into().unwrap()))
+                }
+            }
+        }
+        None
+    }
+
+    fn num_entries(&self) -> u64 {
+        1 << self.header_table_bits()
+    }
+
+    fn table_bits(&self) -> u32 {
+        self.header_table_bits()
+    }
+}
+
+/// Builds a HashMap of 2^`table_bits` elements
+fn decode_u32_precomputation(
+    generator: RistrettoPoint,
+    table_bits: u32,
+) -> DecodeU32Precomputation {
+    DecodeU32Precomputation(precomputation_hashmap(generator, 32, table_bits))
+}
+
+/// Builds a HashMap of the 2^`table_bits` points `x_hi * 2^(total_bits - table_bits) * generator`
+fn precomputation_hashmap(
+    generator: RistrettoPoint,
+    total_bits: u32,
+    table_bits: u32,
+) -> HashMap<[u8; 32], u32> {
     let mut hashmap = HashMap::new();
 
-    let two12_scalar = Scalar::from(TWO14);
+    let online_bits = total_bits - table_bits;
+    let table_size = 1_u64 << table_bits;
+    let step_scalar = Scalar::from(1_u64 << online_bits);
     let identity = RistrettoPoint::identity(); // 0 * G
-    let generator = two12_scalar * generator; // 2^12 * G
+    let generator = step_scalar * generator; // 2^online_bits * G
 
-    // iterator for 2^12*0G , 2^12*1G, 2^12*2G, ...
+    // iterator for 2^online_bits*0G , 2^online_bits*1G, 2^online_bits*2G, ...
     let ristretto_iter = RistrettoIterator::new(identity, generator);
     let mut steps_for_breakpoint = 0;
-    ristretto_iter.zip(0..TWO18).for_each(|(elem, x_hi)| {
+    ristretto_iter.zip(0..table_size).for_each(|(elem, x_hi)| {
         let key = elem.compress().to_bytes();
-        hashmap.insert(key, x_hi);
+        hashmap.insert(key, x_hi as u32);
 
         // unclean way to print status update; will clean up later
-        if x_hi % TWO15 == 0 {
+        if x_hi % (table_size / 8).max(1) == 0 {
             println!("     [{:?}/8] completed", steps_for_breakpoint);
             steps_for_breakpoint += 1;
         }
     });
     println!("     [8/8] completed");
 
-    DecodeU32Precomputation(hashmap)
+    hashmap
+}
+
+impl DecodeU32Precomputation {
+    /// Builds the pre-computed HashMap for the generator `G` with 2^`table_bits` elements.
+    ///
+    /// A bigger table uses more memory but speeds up decryption, which enumerates
+    /// 2^(32 - `table_bits`) candidates. The default table `DECODE_U32_PRECOMPUTATION_FOR_G` uses
+    /// 18 bits.
+    ///
+    /// Fails if `table_bits` is greater than 32.
+    pub fn with_table_bits(table_bits: u32) -> Result<Self, ProofError> {
+        if table_bits > 32 {
+            return Err(ProofError::InvalidTableBits);
+        }
+        Ok(decode_u32_precomputation(
+            RISTRETTO_BASEPOINT_POINT,
+            table_bits,
+        ))
+    }
+}
+
+/// Pre-computed HashMap needed to solve 48-bit discrete log instances with baby-step giant-step.
+#[derive(Serialize, Deserialize, Default)]
+pub struct DecodeU48Precomputation(HashMap<[u8; 32], u32>);
+
+impl DecodeU48Precomputation {
+    /// Builds the pre-computed HashMap for the generator `G` with 2^`table_bits` elements.
+    ///
+    /// Decryption enumerates 2^(48 - `table_bits`) candidates, so the table should be much
+    /// bigger than the 32-bit table; 2^24 elements take a few gigabytes of memory.
+    ///
+    /// Fails if `table_bits` is greater than 32.
+    pub fn with_table_bits(table_bits: u32) -> Result<Self, ProofError> {
+        if table_bits > 32 {
+            return Err(ProofError::InvalidTableBits);
+        }
+        Ok(Self(precomputation_hashmap(
+            RISTRETTO_BASEPOINT_POINT,
+            48,
+            table_bits,
+        )))
+    }
+
+    fn table_bits(&self) -> u32 {
+        (self.0.len() as u64).trailing_zeros().min(32)
+    }
 }
 
 lazy_static::lazy_static! {
@@ -60,29 +279,191 @@ lazy_static::lazy_static! {
     };
 }
 
-/// Solves the discrete log instance using a 18/14 bit offline/online split
+/// Solves the discrete log instance using an offline/online split, which is 18/14 bits for the
+/// default table
 impl DiscreteLog {
     /// Solves the discrete log problem under the assumption that the solution
     /// is a 32-bit number.
     pub(crate) fn decode_u32(self) -> Option<u32> {
-        self.decode_u32_online(&decode_u32_precomputation(self.generator))
+        self.decode_u32_online(&decode_u32_precomputation(
+            self.generator,
+            DEFAULT_TABLE_BITS,
+        ))
+        .into()
+    }
+
+    /// Solves the discrete log instance using the pre-computed HashMap by enumerating through
+    /// 2^(32 - `table_bits`) possible solutions
+    ///
+    /// All candidates are always enumerated and the solution is selected with `subtle::Choice`,
+    /// so the number of steps does not depend on where the solution is found.
+    ///
+    /// This is not constant time: the table lookups take a data-dependent amount of time.
+    /// Looking a candidate up in constant time means comparing it against every entry of the
+    /// table, 2^32 comparisons per decryption whatever the table size, so constant-time
+    /// decryption is not offered.
+    pub fn decode_u32_online<T: DecodeU32PrecomputationTable + ?Sized>(
+        self,
+        hashmap: &T,
+    ) -> CtOption<u32> {
+        let online_bits = 32 - hashmap.table_bits();
+        let (decoded, is_decoded) = self.decode_u32_range(hashmap, 0..1_u64 << online_bits);
+        CtOption::new(decoded, is_decoded)
     }
 
-    /// Solves the discrete log instance using the pre-computed HashMap by enumerating through 2^14
-    /// possible solutions
-    pub fn decode_u32_online(self, hashmap: &DecodeU32Precomputation) -> Option<u32> {
+    /// Solves the discrete log instance using the pre-computed HashMap, splitting the
+    /// enumeration of the possible solutions across the threads of the rayon thread pool
+    ///
+    /// As in `decode_u32_online`, every candidate is enumerated regardless of where the solution
+    /// is found.
+    pub fn decode_u32_parallel<T: DecodeU32PrecomputationTable + Sync + ?Sized>(
+        self,
+        hashmap: &T,
+    ) -> CtOption<u32> {
+        let online_bits = 32 - hashmap.table_bits();
+        let num_candidates = 1_u64 << online_bits;
+        let num_chunks = (rayon::current_num_threads() as u64).clamp(1, num_candidates);
+        let chunk_size = (num_candidates + num_chunks - 1) / num_chunks;
+
+        let (decoded, is_decoded) = (0..num_chunks)
+            .into_par_iter()
+            .map(|chunk| {
+                let start = chunk * chunk_size;
+                let end = (start + chunk_size).min(num_candidates);
+                self.decode_u32_range(hashmap, start..end)
+            })
+            .reduce(
+                || (0_u32, Choice::from(0)),
+                |(decoded, is_decoded), (chunk_decoded, chunk_is_decoded)| {
+                    (
+                        u32::conditional_select(&decoded, &chunk_decoded, chunk_is_decoded),
+                        is_decoded | chunk_is_decoded,
+                    )
+                },
+            );
+        CtOption::new(decoded, is_decoded)
+    }
+
+    /// Solves the discrete log problem under the assumption that the solution is a 48-bit
+    /// number, using the baby-step giant-step pre-computed HashMap
+    ///
+    /// Unlike `decode_u32_online`, the enumeration stops as soon as the solution is found.
+    pub fn decode_u48_online(self, hashmap: &DecodeU48Precomputation) -> Option<u64> {
+        let online_bits = 48 - hashmap.table_bits();
+
         // iterator for 0G, -1G, -2G, ...
         let ristretto_iter = RistrettoIterator::new(self.target, -self.generator);
+        ristretto_iter
+            .zip(0..1_u64 << online_bits)
+            .find_map(|(elem, x_lo)| {
+                let x_hi = hashmap.0.get(&elem.compress().to_bytes())?;
+                Some(x_lo + (u64::from(*x_hi) << online_bits))
+            })
+    }
 
-        let mut decoded = None;
-        ristretto_iter.zip(0..TWO14).for_each(|(elem, x_lo)| {
-            let key = elem.compress().to_bytes();
-            if hashmap.0.contains_key(&key) {
-                let x_hi = hashmap.0[&key];
-                decoded = Some(x_lo + TWO14 * x_hi);
+    /// Solves the discrete log problem under the assumption that the solution is a 48-bit
+    /// number, trying the baby-step giant-step pre-computed HashMap first if one is given and
+    /// falling back to Pollard's lambda (kangaroo) method
+    ///
+    /// The kangaroo method requires no pre-computation and takes on the order of 2^25 group
+    /// operations. It only runs if no HashMap is given or if the HashMap does not yield a
+    /// solution, for instance because it is empty.
+    pub fn decode_u48(self, hashmap: Option<&DecodeU48Precomputation>) -> Option<u64> {
+        hashmap
+            .and_then(|hashmap| self.decode_u48_online(hashmap))
+            .or_else(|| self.decode_kangaroo(48))
+    }
+
+    /// Pollard's lambda method with distinguished points for a solution in `[0, 2^bits)`
+    fn decode_kangaroo(self, bits: u32) -> Option<u64> {
+        let range = 1_u64 << bits;
+        let sqrt_range = 1_u64 << (bits / 2);
+
+        // jumps of size 2^i with a mean close to sqrt(range) / 2
+        let mut num_jumps = 1;
+        while ((1_u64 << num_jumps) - 1) / num_jumps < sqrt_range / 2 {
+            num_jumps += 1;
+        }
+        let jumps: Vec<(u64, RistrettoPoint)> = (0..num_jumps)
+            .map(|i| (1_u64 << i, Scalar::from(1_u64 << i) * self.generator))
+            .collect();
+
+        // a point is distinguished if the low `distinguished_bits` bits of its encoding are zero
+        let distinguished_bits = bits / 4;
+        let distinguished_mask = (1_u32 << distinguished_bits) - 1;
+
+        // the tame kangaroo starts in the middle of the range and the wild kangaroo at the target;
+        // positions are the discrete logs of the current points
+        let tame_start = range / 2;
+        let mut tame = (Scalar::from(tame_start) * self.generator, tame_start);
+        let mut wild = (self.target, 0_u64);
+        let mut tame_points: HashMap<[u8; 32], u64> = HashMap::new();
+        let mut wild_points: HashMap<[u8; 32], u64> = HashMap::new();
+
+        let max_steps = 16 * sqrt_range;
+        for _ in 0..max_steps {
+            for is_tame in [true, false] {
+                let (point, distance) = if is_tame { &mut tame } else { &mut wild };
+                let key = point.compress().to_bytes();
+
+                let hash = u32::from_le_bytes(key[..4].try_into().unwrap());
+                if hash & distinguished_mask == 0 {
+                    // the solution is `tame_position - wild_distance`
+                    let solution = if is_tame {
+                        wild_points
+                            .get(&key)
+                            .map(|wild| distance.wrapping_sub(*wild))
+                    } else {
+                        tame_points
+                            .get(&key)
+                            .map(|tame| tame.wrapping_sub(*distance))
+                    };
+                    if let Some(x) = solution {
+                        if x < range && Scalar::from(x) * self.generator == self.target {
+                            return Some(x);
+                        }
+                    }
+
+                    if is_tame {
+                        tame_points.insert(key, *distance);
+                    } else {
+                        wild_points.insert(key, *distance);
+                    }
+                }
+
+                let (jump, jump_point) = jumps[(hash >> distinguished_bits) as usize % jumps.len()];
+                *point += jump_point;
+                *distance += jump;
             }
+        }
+        None
+    }
+
+    /// Enumerates the candidates `x_lo` in `range`, looking up `target - x_lo * generator` in the
+    /// pre-computed HashMap
+    fn decode_u32_range<T: DecodeU32PrecomputationTable + ?Sized>(
+        self,
+        hashmap: &T,
+        range: Range<u64>,
+    ) -> (u32, Choice) {
+        let online_bits = 32 - hashmap.table_bits();
+
+        // iterator for (target - start * G), (target - (start + 1) * G), ...
+        let start = self.target - Scalar::from(range.start) * self.generator;
+        let ristretto_iter = RistrettoIterator::new(start, -self.generator);
+
+        let mut decoded = 0_u32;
+        let mut is_decoded = Choice::from(0);
+        ristretto_iter.zip(range).for_each(|(elem, x_lo)| {
+            let key = elem.compress().to_bytes();
+            let x_hi = hashmap.get(&key);
+            let is_found = Choice::from(x_hi.is_some() as u8);
+
+            let candidate = (x_lo + (u64::from(x_hi.unwrap_or_default()) << online_bits)) as u32;
+            decoded.conditional_assign(&candidate, is_found);
+            is_decoded |= is_found;
         });
-        decoded
+        (decoded, is_decoded)
     }
 }
 
@@ -120,7 +501,7 @@ mod tests {
     #[test]
     #[allow(non_snake_case)]
     fn test_serialize_decode_u32_precomputation_for_G() {
-        let decode_u32_precomputation_for_G = decode_u32_precomputation(G);
+        let decode_u32_precomputation_for_G = decode_u32_precomputation(G, DEFAULT_TABLE_BITS);
 
         if decode_u32_precomputation_for_G.0 != DECODE_U32_PRECOMPUTATION_FOR_G.0 {
             use std::{fs::File, io::Write, path::PathBuf};
@@ -150,7 +531,7 @@ mod tests {
 
         // Very informal measurements for now
         let start_precomputation = Instant::now();
-        let precomputed_hashmap = decode_u32_precomputation(G);
+        let precomputed_hashmap = decode_u32_precomputation(G, DEFAULT_TABLE_BITS);
         let precomputation_secs = start_precomputation.elapsed().as_secs_f64();
 
         let start_online = Instant::now();
@@ -159,7 +540,136 @@ mod tests {
 
         assert_eq!(amount, computed_amount);
 
+        let out_of_range = DiscreteLog {
+            generator: G,
+            target: Scalar::from(u32::MAX as u64 + 1) * G,
+        };
+        assert!(bool::from(
+            out_of_range
+                .decode_u32_online(&precomputed_hashmap)
+                .is_none()
+        ));
+
         println!("16/16 Split precomputation: {:?} sec", precomputation_secs);
         println!("16/16 Split online computation: {:?} sec", online_secs);
     }
+
+    #[test]
+    fn test_decode_u48() {
+        // with a 16-bit table, the online phase enumerates up to 2^32 candidates, so only check an
+        // amount whose low 32 bits are small
+        let precomputed_hashmap = DecodeU48Precomputation::with_table_bits(16).unwrap();
+        let amount: u64 = (1 << 47) + 5;
+        let instance = DiscreteLog {
+            generator: G,
+            target: Scalar::from(amount) * G,
+        };
+        assert_eq!(
+            instance.decode_u48_online(&precomputed_hashmap),
+            Some(amount)
+        );
+        assert_eq!(
+            instance.decode_u48(Some(&precomputed_hashmap)),
+            Some(amount)
+        );
+
+        // run the kangaroos over a 24-bit range to keep the test fast
+        for amount in [0_u64, 1, 77_777, (1 << 24) - 1] {
+            let instance = DiscreteLog {
+                generator: G,
+                target: Scalar::from(amount) * G,
+            };
+            assert_eq!(instance.decode_kangaroo(24), Some(amount));
+        }
+    }
+
+    #[test]
+    fn test_decode_parallel() {
+        for amount in [0_u32, 65545, 4294967295] {
+            let instance = DiscreteLog {
+                generator: G,
+                target: Scalar::from(amount) * G,
+            };
+            assert_eq!(
+                instance
+                    .decode_u32_parallel(&*DECODE_U32_PRECOMPUTATION_FOR_G)
+                    .unwrap(),
+                amount
+            );
+        }
+
+        let out_of_range = DiscreteLog {
+            generator: G,
+            target: Scalar::from(u32::MAX as u64 + 1) * G,
+        };
+        assert!(bool::from(
+            out_of_range
+                .decode_u32_parallel(&*DECODE_U32_PRECOMPUTATION_FOR_G)
+                .is_none()
+        ));
+    }
+
+    #[test]
+    fn test_decode_with_table_bits() {
+        let amount: u32 = 4294967295;
+        let instance = DiscreteLog {
+            generator: G,
+            target: Scalar::from(amount) * G,
+        };
+
+        for table_bits in [16, 20] {
+            let precomputed_hashmap = DecodeU32Precomputation::with_table_bits(table_bits).unwrap();
+            assert_eq!(precomputed_hashmap.table_bits(), table_bits);
+            assert_eq!(
+                instance.decode_u32_online(&precomputed_hashmap).unwrap(),
+                amount
+            );
+        }
+
+        assert_eq!(
+            DecodeU32Precomputation::with_table_bits(33).err(),
+            Some(ProofError::InvalidTableBits)
+        );
+        assert_eq!(
+            DecodeU48Precomputation::with_table_bits(33).err(),
+            Some(ProofError::InvalidTableBits)
+        );
+    }
+
+    #[test]
+    fn test_mapped_precomputation() {
+        let path = std::env::temp_dir().join(format!(
+            "decode_u32_precomputation_{}.bin",
+            std::process::id()
+        ));
+        DECODE_U32_PRECOMPUTATION_FOR_G
+            .write_to_file(&path)
+            .unwrap();
+        let mapped = MappedDecodeU32Precomputation::open(&path).unwrap();
+        assert!(mapped.verify_checksum());
+
+        let amount: u32 = 4294967295;
+        let instance = DiscreteLog {
+            generator: G,
+            target: Scalar::from(amount) * G,
+        };
+        assert_eq!(instance.decode_u32_online(&mapped).unwrap(), amount);
+
+        drop(mapped);
+
+        // truncated tables are rejected
+        let mut bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(MappedDecodeU32Precomputation::open(&path).is_err());
+
+        // corrupted entries are caught by the checksum
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        let mapped = MappedDecodeU32Precomputation::open(&path).unwrap();
+        assert!(!mapped.verify_checksum());
+        drop(mapped);
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -13,11 +13,15 @@
 //! As the messages are encrypted as scalar elements (a.k.a. in the "exponent"), the encryption
 //! scheme requires solving discrete log to recover the original plaintext.
 
+#[cfg(feature = "prover")]
 use {
-    crate::encryption::{
-        discrete_log::{DecodeU32Precomputation, DiscreteLog},
-        pedersen::{Pedersen, PedersenCommitment, PedersenOpening, G, H},
+    crate::encryption::discrete_log::{
+        DecodeU32Precomputation, DecodeU48Precomputation, DiscreteLog,
     },
+    subtle::CtOption,
+};
+use {
+    crate::encryption::pedersen::{Pedersen, PedersenCommitment, PedersenOpening, G, H},
     arrayref::{array_ref, array_refs},
     core::ops::{Add, Mul, Sub},
     curve25519_dalek::{
@@ -39,8 +43,8 @@ use {
 };
 #[cfg(not(target_arch = "bpf"))]
 use {
-    rand::rngs::OsRng,
-    sha3::Sha3_512,
+    rand::{rngs::OsRng, CryptoRng, RngCore},
+    sha3::{Digest, Sha3_512},
     std::{
         fmt,
         fs::{self, File, OpenOptions},
@@ -58,8 +62,14 @@ impl ElGamal {
     #[cfg(not(target_arch = "bpf"))]
     #[allow(non_snake_case)]
     fn keygen() -> ElGamalKeypair {
+        Self::keygen_with_rng(&mut OsRng)
+    }
+
+    /// Generates an ElGamal keypair from a secret scalar that is sampled from `rng`.
+    #[cfg(not(target_arch = "bpf"))]
+    fn keygen_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> ElGamalKeypair {
         // secret scalar should be zero with negligible probability
-        let mut s = Scalar::random(&mut OsRng);
+        let mut s = Scalar::random(rng);
         let keypair = Self::keygen_with_scalar(&s);
 
         s.zeroize();
@@ -86,7 +96,18 @@ impl ElGamal {
     /// This function is randomized. It internally samples a scalar element using `OsRng`.
     #[cfg(not(target_arch = "bpf"))]
     fn encrypt<T: Into<Scalar>>(public: &ElGamalPubkey, amount: T) -> ElGamalCiphertext {
-        let (commitment, opening) = Pedersen::new(amount);
+        Self::encrypt_with_rng(public, amount, &mut OsRng)
+    }
+
+    /// On input an ElGamal public key and a mesage to be encrypted, the function returns a
+    /// corresponding ElGamal ciphertext whose Pedersen opening is sampled from `rng`.
+    #[cfg(not(target_arch = "bpf"))]
+    fn encrypt_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        public: &ElGamalPubkey,
+        amount: T,
+        rng: &mut R,
+    ) -> ElGamalCiphertext {
+        let (commitment, opening) = Pedersen::new_with_rng(amount, rng);
         let handle = public.decrypt_handle(&opening);
 
         ElGamalCiphertext { commitment, handle }
@@ -121,7 +142,7 @@ impl ElGamal {
     ///
     /// The output of this function is of type `DiscreteLog`. To recover, the originally encrypted
     /// message, use `DiscreteLog::decode`.
-    #[cfg(not(target_arch = "bpf"))]
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
     fn decrypt(secret: &ElGamalSecretKey, ciphertext: &ElGamalCiphertext) -> DiscreteLog {
         DiscreteLog {
             generator: *G,
@@ -131,7 +152,7 @@ impl ElGamal {
 
     /// On input a secret key and a ciphertext, the function returns the decrypted message
     /// interpretted as type `u32`.
-    #[cfg(not(target_arch = "bpf"))]
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
     fn decrypt_u32(secret: &ElGamalSecretKey, ciphertext: &ElGamalCiphertext) -> Option<u32> {
         let discrete_log_instance = Self::decrypt(secret, ciphertext);
         discrete_log_instance.decode_u32()
@@ -139,15 +160,52 @@ impl ElGamal {
 
     /// On input a secret key, a ciphertext, and a pre-computed hashmap, the function returns the
     /// decrypted message interpretted as type `u32`.
-    #[cfg(not(target_arch = "bpf"))]
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
     fn decrypt_u32_online(
         secret: &ElGamalSecretKey,
         ciphertext: &ElGamalCiphertext,
         hashmap: &DecodeU32Precomputation,
-    ) -> Option<u32> {
+    ) -> CtOption<u32> {
         let discrete_log_instance = Self::decrypt(secret, ciphertext);
         discrete_log_instance.decode_u32_online(hashmap)
     }
+
+    /// On input a secret key, a ciphertext, and a pre-computed hashmap, the function returns the
+    /// decrypted message interpretted as type `u32`, solving the discrete log in parallel.
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+    fn decrypt_u32_parallel(
+        secret: &ElGamalSecretKey,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: &DecodeU32Precomputation,
+    ) -> CtOption<u32> {
+        let discrete_log_instance = Self::decrypt(secret, ciphertext);
+        discrete_log_instance.decode_u32_parallel(hashmap)
+    }
+
+    /// On input a secret key, a ciphertext, and a pre-computed hashmap, the function returns the
+    /// decrypted message interpretted as type `u48`.
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+    fn decrypt_u48_online(
+        secret: &ElGamalSecretKey,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: &DecodeU48Precomputation,
+    ) -> Option<u64> {
+        let discrete_log_instance = Self::decrypt(secret, ciphertext);
+        discrete_log_instance.decode_u48_online(hashmap)
+    }
+
+    /// On input a secret key, a ciphertext, and an optional pre-computed hashmap, the function
+    /// returns the decrypted message interpretted as type `u48`, falling back to a search without
+    /// pre-computation if the hashmap is missing or does not yield the message.
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+    fn decrypt_u48(
+        secret: &ElGamalSecretKey,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: Option<&DecodeU48Precomputation>,
+    ) -> Option<u64> {
+        let discrete_log_instance = Self::decrypt(secret, ciphertext);
+        discrete_log_instance.decode_u48(hashmap)
+    }
 }
 
 /// A (twisted) ElGamal encryption keypair.
@@ -190,6 +248,26 @@ impl ElGamalKeypair {
         Ok(keypair)
     }
 
+    /// Deterministically derives an ElGamal keypair from an Ed25519 signer and an arbitrary seed.
+    ///
+    /// The signer only needs to sign a raw domain-separated message, so this can be used with
+    /// wallets that do not expose the signing key itself. The same signer and seed always yield
+    /// the same keypair.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn new_from_signer(signer: &dyn Signer, seed: &[u8]) -> Result<Self, SignerError> {
+        let secret = ElGamalSecretKey::new_from_signer(signer, seed)?;
+        Ok(ElGamal::keygen_with_scalar(secret.get_scalar()))
+    }
+
+    /// Deterministically derives the child keypair associated with a token account address.
+    ///
+    /// See `ElGamalSecretKey::derive_child`.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn derive_child(&self, address: &Pubkey) -> Self {
+        let secret = self.secret.derive_child(address);
+        ElGamal::keygen_with_scalar(secret.get_scalar())
+    }
+
     /// Generates the public and secret keys for ElGamal encryption.
     ///
     /// This function is randomized. It internally samples a scalar element using `OsRng`.
@@ -199,6 +277,14 @@ impl ElGamalKeypair {
         ElGamal::keygen()
     }
 
+    /// Generates the public and secret keys for ElGamal encryption from the randomness of `rng`.
+    ///
+    /// This allows a hardware or deterministic entropy source to be used in place of `OsRng`.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn new_rand_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        ElGamal::keygen_with_rng(rng)
+    }
+
     pub fn to_bytes(&self) -> [u8; 64] {
         let mut bytes = [0u8; 64];
         bytes[..32].copy_from_slice(&self.public.to_bytes());
@@ -207,6 +293,10 @@ impl ElGamalKeypair {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 64 {
+            return None;
+        }
+
         Some(Self {
             public: ElGamalPubkey::from_bytes(bytes[..32].try_into().ok()?)?,
             secret: ElGamalSecretKey::from_bytes(bytes[32..].try_into().ok()?)?,
@@ -271,7 +361,7 @@ impl ElGamalKeypair {
 
 /// Public key for the ElGamal encryption scheme.
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, Zeroize)]
-pub struct ElGamalPubkey(RistrettoPoint);
+pub struct ElGamalPubkey(pub(crate) RistrettoPoint);
 impl ElGamalPubkey {
     /// Derives the `ElGamalPubkey` that uniquely corresponds to an `ElGamalSecretKey`.
     #[allow(non_snake_case)]
@@ -288,6 +378,7 @@ impl ElGamalPubkey {
         self.0.compress().to_bytes()
     }
 
+    /// Decodes a pubkey, rejecting bytes that are not the canonical encoding of a Ristretto point
     pub fn from_bytes(bytes: &[u8; 32]) -> Option<ElGamalPubkey> {
         Some(ElGamalPubkey(
             CompressedRistretto::from_slice(bytes).decompress()?,
@@ -302,6 +393,17 @@ impl ElGamalPubkey {
         ElGamal::encrypt(self, amount)
     }
 
+    /// Encrypts an amount under the public key with a Pedersen opening that is sampled from
+    /// `rng`.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn encrypt_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        &self,
+        amount: T,
+        rng: &mut R,
+    ) -> ElGamalCiphertext {
+        ElGamal::encrypt_with_rng(self, amount, rng)
+    }
+
     /// Encrypts an amount under the public key and an input Pedersen opening.
     pub fn encrypt_with<T: Into<Scalar>>(
         &self,
@@ -355,11 +457,48 @@ impl ElGamalSecretKey {
         }
     }
 
+    /// Deterministically derives an ElGamal secret key from an Ed25519 signer and an arbitrary
+    /// seed.
+    ///
+    /// The key is derived from the signature over the seed, prefixed with a domain separator.
+    pub fn new_from_signer(signer: &dyn Signer, seed: &[u8]) -> Result<Self, SignerError> {
+        let message = [b"ElGamalSecretKey".as_ref(), seed].concat();
+        let signature = signer.try_sign_message(&message)?;
+
+        // Some `Signer` implementations return the default signature, which is not suitable for
+        // use as key material
+        if signature == Signature::default() {
+            Err(SignerError::Custom("Rejecting default signature".into()))
+        } else {
+            Ok(ElGamalSecretKey(Scalar::hash_from_bytes::<Sha3_512>(
+                signature.as_ref(),
+            )))
+        }
+    }
+
+    /// Deterministically derives the child secret key associated with a token account address.
+    ///
+    /// The derivation is hardened: the child key is a hash of the parent secret key and the
+    /// address, so neither a child key nor the parent public key reveal any other key. Child keys
+    /// can themselves be used as parents to derive keys along a path of addresses.
+    pub fn derive_child(&self, address: &Pubkey) -> Self {
+        let hasher = Sha3_512::new()
+            .chain(b"ElGamalSecretKey-child")
+            .chain(self.0.as_bytes())
+            .chain(address.as_ref());
+        ElGamalSecretKey(Scalar::from_hash(hasher))
+    }
+
     /// Randomly samples an ElGamal secret key.
     ///
     /// This function is randomized. It internally samples a scalar element using `OsRng`.
     pub fn new_rand() -> Self {
-        ElGamalSecretKey(Scalar::random(&mut OsRng))
+        Self::new_rand_with_rng(&mut OsRng)
+    }
+
+    /// Samples an ElGamal secret key from `rng`.
+    pub fn new_rand_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        ElGamalSecretKey(Scalar::random(rng))
     }
 
     pub fn get_scalar(&self) -> &Scalar {
@@ -370,25 +509,62 @@ impl ElGamalSecretKey {
     ///
     /// The output of this function is of type `DiscreteLog`. To recover, the originally encrypted
     /// message, use `DiscreteLog::decode`.
+    #[cfg(feature = "prover")]
     pub fn decrypt(&self, ciphertext: &ElGamalCiphertext) -> DiscreteLog {
         ElGamal::decrypt(self, ciphertext)
     }
 
     /// Decrypts a ciphertext using the ElGamal secret key interpretting the message as type `u32`.
+    #[cfg(feature = "prover")]
     pub fn decrypt_u32(&self, ciphertext: &ElGamalCiphertext) -> Option<u32> {
         ElGamal::decrypt_u32(self, ciphertext)
     }
 
     /// Decrypts a ciphertext using the ElGamal secret key and a pre-computed hashmap. It
     /// interprets the decrypted message as type `u32`.
+    #[cfg(feature = "prover")]
     pub fn decrypt_u32_online(
         &self,
         ciphertext: &ElGamalCiphertext,
         hashmap: &DecodeU32Precomputation,
-    ) -> Option<u32> {
+    ) -> CtOption<u32> {
         ElGamal::decrypt_u32_online(self, ciphertext, hashmap)
     }
 
+    /// Decrypts a ciphertext using the ElGamal secret key and a pre-computed hashmap, solving the
+    /// discrete log on the rayon thread pool. It interprets the decrypted message as type `u32`.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u32_parallel(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: &DecodeU32Precomputation,
+    ) -> CtOption<u32> {
+        ElGamal::decrypt_u32_parallel(self, ciphertext, hashmap)
+    }
+
+    /// Decrypts a ciphertext using the ElGamal secret key and a pre-computed hashmap. It
+    /// interprets the decrypted message as a 48-bit number.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u48_online(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: &DecodeU48Precomputation,
+    ) -> Option<u64> {
+        ElGamal::decrypt_u48_online(self, ciphertext, hashmap)
+    }
+
+    /// Decrypts a ciphertext using the ElGamal secret key, trying the pre-computed hashmap first
+    /// if one is given and falling back to a search without pre-computation. It interprets the
+    /// decrypted message as a 48-bit number.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u48(
+        &self,
+        ciphertext: &ElGamalCiphertext,
+        hashmap: Option<&DecodeU48Precomputation>,
+    ) -> Option<u64> {
+        ElGamal::decrypt_u48(self, ciphertext, hashmap)
+    }
+
     pub fn as_bytes(&self) -> &[u8; 32] {
         self.0.as_bytes()
     }
@@ -397,6 +573,7 @@ impl ElGamalSecretKey {
         self.0.to_bytes()
     }
 
+    /// Decodes a secret key, rejecting scalars that are not reduced modulo the group order
     pub fn from_bytes(bytes: [u8; 32]) -> Option<ElGamalSecretKey> {
         Scalar::from_canonical_bytes(bytes).map(ElGamalSecretKey)
     }
@@ -452,7 +629,13 @@ impl ElGamalCiphertext {
         bytes
     }
 
+    /// Decodes a ciphertext, rejecting input that is not exactly 64 bytes or that does not consist
+    /// of two canonically encoded Ristretto points
     pub fn from_bytes(bytes: &[u8]) -> Option<ElGamalCiphertext> {
+        if bytes.len() != 64 {
+            return None;
+        }
+
         let bytes = array_ref![bytes, 0, 64];
         let (commitment, handle) = array_refs![bytes, 32, 32];
 
@@ -469,24 +652,62 @@ impl ElGamalCiphertext {
     ///
     /// The output of this function is of type `DiscreteLog`. To recover, the originally encrypted
     /// message, use `DiscreteLog::decode`.
+    #[cfg(feature = "prover")]
     pub fn decrypt(&self, secret: &ElGamalSecretKey) -> DiscreteLog {
         ElGamal::decrypt(secret, self)
     }
 
     /// Decrypts the ciphertext using an ElGamal secret key interpretting the message as type `u32`.
+    #[cfg(feature = "prover")]
     pub fn decrypt_u32(&self, secret: &ElGamalSecretKey) -> Option<u32> {
         ElGamal::decrypt_u32(secret, self)
     }
 
     /// Decrypts the ciphertext using an ElGamal secret key and a pre-computed hashmap. It
     /// interprets the decrypted message as type `u32`.
+    #[cfg(feature = "prover")]
     pub fn decrypt_u32_online(
         &self,
         secret: &ElGamalSecretKey,
         hashmap: &DecodeU32Precomputation,
-    ) -> Option<u32> {
+    ) -> CtOption<u32> {
         ElGamal::decrypt_u32_online(secret, self, hashmap)
     }
+
+    /// Decrypts the ciphertext using an ElGamal secret key and a pre-computed hashmap, solving
+    /// the discrete log on the rayon thread pool. It interprets the decrypted message as type
+    /// `u32`.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u32_parallel(
+        &self,
+        secret: &ElGamalSecretKey,
+        hashmap: &DecodeU32Precomputation,
+    ) -> CtOption<u32> {
+        ElGamal::decrypt_u32_parallel(secret, self, hashmap)
+    }
+
+    /// Decrypts the ciphertext using an ElGamal secret key and a pre-computed hashmap. It
+    /// interprets the decrypted message as a 48-bit number.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u48_online(
+        &self,
+        secret: &ElGamalSecretKey,
+        hashmap: &DecodeU48Precomputation,
+    ) -> Option<u64> {
+        ElGamal::decrypt_u48_online(secret, self, hashmap)
+    }
+
+    /// Decrypts the ciphertext using an ElGamal secret key, trying the pre-computed hashmap first
+    /// if one is given and falling back to a search without pre-computation. It interprets the
+    /// decrypted message as a 48-bit number.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_u48(
+        &self,
+        secret: &ElGamalSecretKey,
+        hashmap: Option<&DecodeU48Precomputation>,
+    ) -> Option<u64> {
+        ElGamal::decrypt_u48(secret, self, hashmap)
+    }
 }
 
 impl<'a, 'b> Add<&'b ElGamalCiphertext> for &'a ElGamalCiphertext {
@@ -540,6 +761,48 @@ define_mul_variants!(
     Output = ElGamalCiphertext
 );
 
+impl<'a, 'b> Add<&'b u64> for &'a ElGamalCiphertext {
+    type Output = ElGamalCiphertext;
+
+    fn add(self, amount: &'b u64) -> ElGamalCiphertext {
+        self.add_amount(*amount)
+    }
+}
+
+define_add_variants!(
+    LHS = ElGamalCiphertext,
+    RHS = u64,
+    Output = ElGamalCiphertext
+);
+
+impl<'a, 'b> Sub<&'b u64> for &'a ElGamalCiphertext {
+    type Output = ElGamalCiphertext;
+
+    fn sub(self, amount: &'b u64) -> ElGamalCiphertext {
+        self.subtract_amount(*amount)
+    }
+}
+
+define_sub_variants!(
+    LHS = ElGamalCiphertext,
+    RHS = u64,
+    Output = ElGamalCiphertext
+);
+
+impl<'a, 'b> Mul<&'b u64> for &'a ElGamalCiphertext {
+    type Output = ElGamalCiphertext;
+
+    fn mul(self, amount: &'b u64) -> ElGamalCiphertext {
+        self * Scalar::from(*amount)
+    }
+}
+
+define_mul_variants!(
+    LHS = ElGamalCiphertext,
+    RHS = u64,
+    Output = ElGamalCiphertext
+);
+
 /// Decryption handle for Pedersen commitment.
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DecryptHandle(RistrettoPoint);
@@ -557,9 +820,11 @@ impl DecryptHandle {
         self.0.compress().to_bytes()
     }
 
+    /// Decodes a handle, rejecting input that is not the 32-byte canonical encoding of a
+    /// Ristretto point
     pub fn from_bytes(bytes: &[u8]) -> Option<DecryptHandle> {
         Some(DecryptHandle(
-            CompressedRistretto::from_slice(bytes).decompress()?,
+            CompressedRistretto(bytes.try_into().ok()?).decompress()?,
         ))
     }
 }
@@ -628,6 +893,12 @@ mod tests {
                 .decrypt_u32_online(&ciphertext, &(*DECODE_U32_PRECOMPUTATION_FOR_G))
                 .unwrap()
         );
+        assert_eq!(
+            57_u32,
+            secret
+                .decrypt_u32_parallel(&ciphertext, &(*DECODE_U32_PRECOMPUTATION_FOR_G))
+                .unwrap()
+        );
     }
 
     #[test]
@@ -689,6 +960,7 @@ mod tests {
         let ciphertext_sum = ElGamal::encrypt_with(amount_0 + amount_1, &public, &opening);
 
         assert_eq!(ciphertext_sum, ciphertext.add_amount(amount_1));
+        assert_eq!(ciphertext_sum, ciphertext + amount_1);
     }
 
     #[test]
@@ -715,6 +987,7 @@ mod tests {
         let ciphertext_sub = ElGamal::encrypt_with(amount_0 - amount_1, &public, &opening);
 
         assert_eq!(ciphertext_sub, ciphertext.subtract_amount(amount_1));
+        assert_eq!(ciphertext_sub, ciphertext - amount_1);
     }
 
     #[test]
@@ -732,6 +1005,7 @@ mod tests {
             ElGamal::encrypt_with(amount_0 * amount_1, &public, &(&opening * scalar));
 
         assert_eq!(ciphertext_prod, ciphertext * scalar);
+        assert_eq!(ciphertext_prod, ciphertext * amount_1);
     }
 
     #[test]
@@ -857,6 +1131,58 @@ mod tests {
         assert!(ElGamalSecretKey::new(&null_signer, &Pubkey::default()).is_err());
     }
 
+    #[test]
+    fn test_keypair_new_from_signer() {
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+
+        let elgamal_keypair = ElGamalKeypair::new_from_signer(&keypair1, b"seed").unwrap();
+        assert_eq!(
+            elgamal_keypair,
+            ElGamalKeypair::new_from_signer(&keypair1, b"seed").unwrap()
+        );
+
+        let ciphertext = elgamal_keypair.public.encrypt(57_u64);
+        assert_eq!(Some(57), elgamal_keypair.secret.decrypt_u32(&ciphertext));
+
+        assert_ne!(
+            elgamal_keypair,
+            ElGamalKeypair::new_from_signer(&keypair1, b"other seed").unwrap()
+        );
+        assert_ne!(
+            elgamal_keypair,
+            ElGamalKeypair::new_from_signer(&keypair2, b"seed").unwrap()
+        );
+
+        let null_signer = NullSigner::new(&Pubkey::default());
+        assert!(ElGamalKeypair::new_from_signer(&null_signer, b"seed").is_err());
+    }
+
+    #[test]
+    fn test_keypair_derive_child() {
+        let root = ElGamalKeypair::new_rand();
+        let address1 = Pubkey::new_unique();
+        let address2 = Pubkey::new_unique();
+
+        let child = root.derive_child(&address1);
+        assert_eq!(child, root.derive_child(&address1));
+        assert_ne!(child, root.derive_child(&address2));
+        assert_ne!(child, root);
+
+        // derivation along a path depends on the order of the addresses
+        assert_ne!(
+            child.derive_child(&address2),
+            root.derive_child(&address2).derive_child(&address1)
+        );
+
+        let amount: u64 = 57;
+        let ciphertext = child.public.encrypt(amount);
+        assert_eq!(
+            Some(amount),
+            child.secret.decrypt_u32(&ciphertext).map(u64::from)
+        );
+    }
+
     #[test]
     fn test_decrypt_handle_bytes() {
         let handle = DecryptHandle(RistrettoPoint::default());
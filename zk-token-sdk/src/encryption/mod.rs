@@ -7,10 +7,14 @@
 //! is used for the Ristretto group implementation.
 //! - The twisted ElGamal scheme, which converts Pedersen commitments into a public-key encryption
 //! scheme.
+//! - Threshold decryption for the twisted ElGamal scheme.
 //! - Basic type-wrapper around the AES-GCM-SIV symmetric authenticated encryption scheme
 //! implemented by [aes-gcm-siv](https://docs.rs/aes-gcm-siv/latest/aes_gcm_siv/) crate.
 
 pub mod auth_encryption;
+#[cfg(feature = "prover")]
 pub mod discrete_log;
 pub mod elgamal;
 pub mod pedersen;
+#[cfg(feature = "prover")]
+pub mod threshold;
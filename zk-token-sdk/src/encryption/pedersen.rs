@@ -1,7 +1,7 @@
 //! Pedersen commitment implementation using the Ristretto prime-order group.
 
 #[cfg(not(target_arch = "bpf"))]
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
 use {
     core::ops::{Add, Mul, Sub},
     curve25519_dalek::{
@@ -35,7 +35,17 @@ impl Pedersen {
     #[cfg(not(target_arch = "bpf"))]
     #[allow(clippy::new_ret_no_self)]
     pub fn new<T: Into<Scalar>>(message: T) -> (PedersenCommitment, PedersenOpening) {
-        let opening = PedersenOpening::new_rand();
+        Self::new_with_rng(message, &mut OsRng)
+    }
+
+    /// On input a message, the function returns a Pedersen commitment of the message and the
+    /// corresponding opening, which is sampled from `rng`.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn new_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        message: T,
+        rng: &mut R,
+    ) -> (PedersenCommitment, PedersenOpening) {
+        let opening = PedersenOpening::new_rand_with_rng(rng);
         let commitment = Pedersen::with(message, &opening);
 
         (commitment, opening)
@@ -74,7 +84,12 @@ impl PedersenOpening {
 
     #[cfg(not(target_arch = "bpf"))]
     pub fn new_rand() -> Self {
-        PedersenOpening(Scalar::random(&mut OsRng))
+        Self::new_rand_with_rng(&mut OsRng)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn new_rand_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        PedersenOpening(Scalar::random(rng))
     }
 
     #[allow(clippy::wrong_self_convention)]
@@ -161,9 +176,11 @@ impl PedersenCommitment {
         self.0.compress().to_bytes()
     }
 
+    /// Decodes a commitment, rejecting input that is not the 32-byte canonical encoding of a
+    /// Ristretto point
     pub fn from_bytes(bytes: &[u8]) -> Option<PedersenCommitment> {
         Some(PedersenCommitment(
-            CompressedRistretto::from_slice(bytes).decompress()?,
+            CompressedRistretto(bytes.try_into().ok()?).decompress()?,
         ))
     }
 }
@@ -0,0 +1,447 @@
+//! Threshold decryption for the twisted ElGamal encryption scheme.
+//!
+//! An ElGamal secret key `s` is split into Shamir shares so that any `threshold` of the `n`
+//! shareholders can jointly decrypt a ciphertext, while fewer shareholders learn nothing about
+//! the key. This is intended for auditor keys: each shareholder computes a `DecryptionShare` from
+//! the auditor ciphertext of a transfer (see `TransferData::ciphertext_lo`), and the shares are
+//! combined into the same discrete log instance that a single auditor secret key would produce.
+//!
+//! The shares can either be dealt from an existing secret key by a trusted dealer
+//! (`SecretKeyShare::split`) or generated without any party ever learning the secret key using
+//! the distributed key generation (`DkgParticipant`). Since a twisted ElGamal public key is
+//! `s^-1 * H`, the key generation jointly inverts the shared key by opening a product `k * s`
+//! with a random shared mask `k`. The product is a polynomial of degree `2 * (threshold - 1)`, so
+//! the key generation requires at least `2 * threshold - 1` participants.
+//!
+//! The distributed key generation assumes that all participants follow the protocol and that
+//! the dealings are sent over private, authenticated channels.
+#![cfg(not(target_arch = "bpf"))]
+
+use {
+    crate::{
+        encryption::{
+            discrete_log::DiscreteLog,
+            elgamal::{ElGamalCiphertext, ElGamalPubkey, ElGamalSecretKey},
+            pedersen::{G, H},
+        },
+        errors::ThresholdError,
+    },
+    curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::Identity},
+    rand::rngs::OsRng,
+    serde::{Deserialize, Serialize},
+    std::collections::HashSet,
+    zeroize::Zeroize,
+};
+
+/// A Shamir share of an ElGamal secret key.
+///
+/// Instances of the secret key share are zeroized on drop.
+#[derive(Clone, Debug, Deserialize, Serialize, Zeroize)]
+#[zeroize(drop)]
+pub struct SecretKeyShare {
+    index: u32,
+    scalar: Scalar,
+}
+
+impl SecretKeyShare {
+    /// Splits an ElGamal secret key into `num_shares` shares, any `threshold` of which can
+    /// decrypt ciphertexts under the corresponding public key.
+    pub fn split(
+        secret: &ElGamalSecretKey,
+        threshold: usize,
+        num_shares: usize,
+    ) -> Result<Vec<Self>, ThresholdError> {
+        check_parameters(threshold, num_shares)?;
+
+        let mut polynomial = random_polynomial(threshold);
+        polynomial[0] = *secret.get_scalar();
+
+        let shares = (1..=num_shares as u32)
+            .map(|index| Self {
+                index,
+                scalar: evaluate(&polynomial, index),
+            })
+            .collect();
+
+        polynomial.zeroize();
+        Ok(shares)
+    }
+
+    /// The index of the shareholder, which is used as the evaluation point of the share.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Computes the decryption share of a ciphertext.
+    pub fn decrypt_share(&self, ciphertext: &ElGamalCiphertext) -> DecryptionShare {
+        DecryptionShare {
+            index: self.index,
+            point: &self.scalar * ciphertext.handle.get_point(),
+        }
+    }
+}
+
+/// A partial decryption of a ciphertext computed by a single shareholder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DecryptionShare {
+    index: u32,
+    point: RistrettoPoint,
+}
+
+impl DecryptionShare {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// Combines at least `threshold` decryption shares of a ciphertext.
+///
+/// The output of this function is of type `DiscreteLog`. To recover the originally encrypted
+/// message, use `DiscreteLog::decode`.
+pub fn combine_decryption_shares(
+    ciphertext: &ElGamalCiphertext,
+    shares: &[DecryptionShare],
+    threshold: usize,
+) -> Result<DiscreteLog, ThresholdError> {
+    if threshold == 0 {
+        return Err(ThresholdError::InvalidParameters);
+    }
+    let shares = &shares[..threshold.min(shares.len())];
+    let indices = check_indices(shares.iter().map(|share| share.index), threshold)?;
+
+    let secret_handle = shares
+        .iter()
+        .zip(lagrange_coefficients(&indices))
+        .fold(RistrettoPoint::identity(), |acc, (share, coefficient)| {
+            acc + coefficient * share.point
+        });
+
+    Ok(DiscreteLog {
+        generator: *G,
+        target: ciphertext.commitment.get_point() - secret_handle,
+    })
+}
+
+/// The state of a single participant of the distributed key generation.
+///
+/// The key generation proceeds as follows:
+/// 1. Each participant creates a `DkgParticipant` and privately sends the `DkgDealing`
+///    generated by `DkgParticipant::dealing` to every participant, including itself.
+/// 2. Each participant calls `DkgParticipant::finalize` on the dealings that it received. This
+///    yields its secret key share and a `DkgPublicShare` that is broadcast to everyone.
+/// 3. The public key is recovered from the public shares using `combine_public_shares`.
+#[derive(Clone, Debug, Zeroize)]
+#[zeroize(drop)]
+pub struct DkgParticipant {
+    index: u32,
+    num_participants: usize,
+    /// Polynomial sharing this participant's contribution to the secret key
+    secret_polynomial: Vec<Scalar>,
+    /// Polynomial sharing this participant's contribution to the mask used to invert the key
+    mask_polynomial: Vec<Scalar>,
+    /// Polynomial of degree `2 * (threshold - 1)` with a zero constant term that re-randomizes
+    /// the product of the key and mask shares
+    zero_polynomial: Vec<Scalar>,
+}
+
+/// The shares that a participant privately sends to another participant.
+///
+/// Instances of the dealing are zeroized on drop.
+#[derive(Clone, Debug, Deserialize, Serialize, Zeroize)]
+#[zeroize(drop)]
+pub struct DkgDealing {
+    from: u32,
+    to: u32,
+    secret: Scalar,
+    mask: Scalar,
+    zero: Scalar,
+}
+
+/// The values that a participant broadcasts once it has received all dealings.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DkgPublicShare {
+    index: u32,
+    masked_product: Scalar,
+    masked_point: RistrettoPoint,
+}
+
+impl DkgParticipant {
+    /// Creates the participant with index `index` among `num_participants` participants
+    /// numbered from `1`.
+    pub fn new(
+        index: u32,
+        threshold: usize,
+        num_participants: usize,
+    ) -> Result<Self, ThresholdError> {
+        check_parameters(threshold, num_participants)?;
+        if num_participants < 2 * threshold - 1 {
+            return Err(ThresholdError::InvalidParameters);
+        }
+        if index == 0 || index as usize > num_participants {
+            return Err(ThresholdError::InvalidShareIndex);
+        }
+
+        let mut zero_polynomial = random_polynomial(2 * threshold - 1);
+        zero_polynomial[0] = Scalar::zero();
+
+        Ok(Self {
+            index,
+            num_participants,
+            secret_polynomial: random_polynomial(threshold),
+            mask_polynomial: random_polynomial(threshold),
+            zero_polynomial,
+        })
+    }
+
+    /// Generates the dealing that is privately sent to the participant with index `to`.
+    pub fn dealing(&self, to: u32) -> Result<DkgDealing, ThresholdError> {
+        if to == 0 || to as usize > self.num_participants {
+            return Err(ThresholdError::InvalidShareIndex);
+        }
+
+        Ok(DkgDealing {
+            from: self.index,
+            to,
+            secret: evaluate(&self.secret_polynomial, to),
+            mask: evaluate(&self.mask_polynomial, to),
+            zero: evaluate(&self.zero_polynomial, to),
+        })
+    }
+
+    /// Combines the dealings received from all participants into a secret key share and the
+    /// public share that must be broadcast to the other participants.
+    pub fn finalize(
+        &self,
+        dealings: &[DkgDealing],
+    ) -> Result<(SecretKeyShare, DkgPublicShare), ThresholdError> {
+        if dealings.iter().any(|dealing| dealing.to != self.index) {
+            return Err(ThresholdError::InvalidShareIndex);
+        }
+        // there is exactly one dealing from every participant
+        check_indices(
+            dealings.iter().map(|dealing| dealing.from),
+            self.num_participants,
+        )?;
+        if dealings
+            .iter()
+            .any(|dealing| dealing.from as usize > self.num_participants)
+        {
+            return Err(ThresholdError::InvalidShareIndex);
+        }
+
+        let mut secret = Scalar::zero();
+        let mut mask = Scalar::zero();
+        let mut zero = Scalar::zero();
+        for dealing in dealings {
+            secret += dealing.secret;
+            mask += dealing.mask;
+            zero += dealing.zero;
+        }
+
+        let public_share = DkgPublicShare {
+            index: self.index,
+            masked_product: mask * secret + zero,
+            masked_point: mask * &(*H),
+        };
+        let secret_share = SecretKeyShare {
+            index: self.index,
+            scalar: secret,
+        };
+
+        mask.zeroize();
+        zero.zeroize();
+        Ok((secret_share, public_share))
+    }
+}
+
+/// Recovers the ElGamal public key from at least `2 * threshold - 1` public shares of the
+/// distributed key generation.
+pub fn combine_public_shares(
+    shares: &[DkgPublicShare],
+    threshold: usize,
+) -> Result<ElGamalPubkey, ThresholdError> {
+    if threshold == 0 {
+        return Err(ThresholdError::InvalidParameters);
+    }
+    if shares.len() < 2 * threshold - 1 {
+        return Err(ThresholdError::NotEnoughShares);
+    }
+
+    // the masked products lie on a polynomial of degree `2 * (threshold - 1)`
+    let product_shares = &shares[..2 * threshold - 1];
+    let product_indices = check_indices(
+        product_shares.iter().map(|share| share.index),
+        2 * threshold - 1,
+    )?;
+    let product: Scalar = product_shares
+        .iter()
+        .zip(lagrange_coefficients(&product_indices))
+        .map(|(share, coefficient)| coefficient * share.masked_product)
+        .sum();
+
+    // the masked points lie on a polynomial of degree `threshold - 1`
+    let point_shares = &product_shares[..threshold];
+    let point_indices = &product_indices[..threshold];
+    let point = point_shares
+        .iter()
+        .zip(lagrange_coefficients(point_indices))
+        .fold(RistrettoPoint::identity(), |acc, (share, coefficient)| {
+            acc + coefficient * share.masked_point
+        });
+
+    // the product is zero only if the secret key or the mask is zero, which happens with
+    // negligible probability
+    if product == Scalar::zero() {
+        return Err(ThresholdError::InvalidParameters);
+    }
+
+    // `(k * s)^-1 * (k * H) = s^-1 * H`
+    Ok(ElGamalPubkey(product.invert() * point))
+}
+
+fn check_parameters(threshold: usize, num_shares: usize) -> Result<(), ThresholdError> {
+    if threshold == 0 || threshold > num_shares || num_shares > u32::MAX as usize {
+        return Err(ThresholdError::InvalidParameters);
+    }
+    Ok(())
+}
+
+/// Checks that there are at least `required` indices and that they are distinct and non-zero.
+fn check_indices<I: Iterator<Item = u32>>(
+    indices: I,
+    required: usize,
+) -> Result<Vec<u32>, ThresholdError> {
+    let indices: Vec<u32> = indices.collect();
+    if indices.len() < required {
+        return Err(ThresholdError::NotEnoughShares);
+    }
+
+    let mut seen = HashSet::new();
+    if indices
+        .iter()
+        .any(|index| *index == 0 || !seen.insert(*index))
+    {
+        return Err(ThresholdError::InvalidShareIndex);
+    }
+    Ok(indices)
+}
+
+fn random_polynomial(num_coefficients: usize) -> Vec<Scalar> {
+    (0..num_coefficients)
+        .map(|_| Scalar::random(&mut OsRng))
+        .collect()
+}
+
+fn evaluate(polynomial: &[Scalar], index: u32) -> Scalar {
+    let x = Scalar::from(index);
+    polynomial
+        .iter()
+        .rev()
+        .fold(Scalar::zero(), |acc, coefficient| acc * x + coefficient)
+}
+
+/// Lagrange coefficients for interpolating at zero from distinct non-zero indices.
+fn lagrange_coefficients(indices: &[u32]) -> Vec<Scalar> {
+    indices
+        .iter()
+        .map(|i| {
+            let x_i = Scalar::from(*i);
+            let (numerator, denominator) = indices.iter().filter(|j| *j != i).fold(
+                (Scalar::one(), Scalar::one()),
+                |(numerator, denominator), j| {
+                    let x_j = Scalar::from(*j);
+                    (numerator * x_j, denominator * (x_j - x_i))
+                },
+            );
+            numerator * denominator.invert()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::encryption::elgamal::ElGamalKeypair};
+
+    #[test]
+    fn test_split_and_combine() {
+        let keypair = ElGamalKeypair::new_rand();
+        let shares = SecretKeyShare::split(&keypair.secret, 3, 5).unwrap();
+
+        let amount: u32 = 77;
+        let ciphertext = keypair.public.encrypt(amount);
+        let decryption_shares: Vec<DecryptionShare> = shares
+            .iter()
+            .map(|share| share.decrypt_share(&ciphertext))
+            .collect();
+
+        // any three shares decrypt the ciphertext
+        let subset = [
+            decryption_shares[4],
+            decryption_shares[1],
+            decryption_shares[2],
+        ];
+        let discrete_log = combine_decryption_shares(&ciphertext, &subset, 3).unwrap();
+        assert_eq!(discrete_log.decode_u32(), Some(amount));
+
+        // two shares are not enough
+        assert_eq!(
+            combine_decryption_shares(&ciphertext, &decryption_shares[..2], 3),
+            Err(ThresholdError::NotEnoughShares)
+        );
+
+        // duplicate shares are rejected
+        let duplicates = [
+            decryption_shares[0],
+            decryption_shares[0],
+            decryption_shares[1],
+        ];
+        assert_eq!(
+            combine_decryption_shares(&ciphertext, &duplicates, 3),
+            Err(ThresholdError::InvalidShareIndex)
+        );
+
+        assert!(SecretKeyShare::split(&keypair.secret, 0, 5).is_err());
+        assert!(SecretKeyShare::split(&keypair.secret, 6, 5).is_err());
+    }
+
+    #[test]
+    fn test_distributed_key_generation() {
+        let threshold = 2;
+        let num_participants = 3;
+
+        let participants: Vec<DkgParticipant> = (1..=num_participants as u32)
+            .map(|index| DkgParticipant::new(index, threshold, num_participants).unwrap())
+            .collect();
+
+        let (secret_shares, public_shares): (Vec<_>, Vec<_>) = participants
+            .iter()
+            .map(|participant| {
+                let dealings: Vec<DkgDealing> = participants
+                    .iter()
+                    .map(|dealer| dealer.dealing(participant.index).unwrap())
+                    .collect();
+                participant.finalize(&dealings).unwrap()
+            })
+            .unzip();
+
+        let pubkey = combine_public_shares(&public_shares, threshold).unwrap();
+
+        let amount: u32 = 55;
+        let ciphertext = pubkey.encrypt(amount);
+        let decryption_shares: Vec<DecryptionShare> = secret_shares[1..]
+            .iter()
+            .map(|share| share.decrypt_share(&ciphertext))
+            .collect();
+
+        let discrete_log =
+            combine_decryption_shares(&ciphertext, &decryption_shares, threshold).unwrap();
+        assert_eq!(discrete_log.decode_u32(), Some(amount));
+
+        // the key generation requires at least `2 * threshold - 1` participants
+        assert!(DkgParticipant::new(1, 3, 4).is_err());
+        assert!(matches!(
+            combine_public_shares(&public_shares[..2], threshold),
+            Err(ThresholdError::NotEnoughShares)
+        ));
+    }
+}
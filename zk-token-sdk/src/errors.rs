@@ -10,19 +10,55 @@ pub enum ProofError {
     #[error("proof failed to verify")]
     Verification,
     #[error("range proof failed to verify")]
-    RangeProof,
+    RangeProofFailed,
     #[error("equality proof failed to verify")]
-    EqualityProof,
-    #[error("fee proof failed to verify")]
-    FeeProof,
+    EqualityProofFailed,
+    #[error("fee sigma proof failed to verify")]
+    FeeSigmaProofFailed,
     #[error("zero-balance proof failed to verify")]
-    ZeroBalanceProof,
+    ZeroBalanceProofFailed,
     #[error("validity proof failed to verify")]
-    ValidityProof,
+    ValidityProofFailed,
+    #[error("public-key validity proof failed to verify")]
+    PubkeyValidityProofFailed,
+    #[error("proof data field `{0}` is malformed")]
+    MalformedPod(&'static str),
+    #[error("fee parameters are malformed or invalid")]
+    InvalidFeeParameters,
+    #[error("encoding is not the canonical encoding of a curve point or scalar")]
+    NonCanonical,
     #[error(
         "`zk_token_elgamal::pod::ElGamalCiphertext` contains invalid ElGamalCiphertext ciphertext"
     )]
     InconsistentCTData,
+    #[error("proof data chunk is out of order or out of bounds")]
+    InvalidProofDataChunk,
+    #[error("proof generation was cancelled")]
+    Cancelled,
+    #[error("proof version is not supported")]
+    UnsupportedVersion,
+    #[error("discrete log precomputation tables cover at most 32 bits")]
+    InvalidTableBits,
+}
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum DecryptionError {
+    #[error("discrete log solution not found for the decrypted amount")]
+    DiscreteLogNotFound,
+    #[error("ciphertext is malformed")]
+    MalformedCiphertext,
+    #[error("no ciphertext is associated with the given role")]
+    WrongRole,
+}
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum ThresholdError {
+    #[error("invalid threshold parameters")]
+    InvalidParameters,
+    #[error("share index is zero, out of range, or duplicated")]
+    InvalidShareIndex,
+    #[error("not enough shares")]
+    NotEnoughShares,
 }
 
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
@@ -32,30 +68,55 @@ pub enum TranscriptError {
 }
 
 impl From<RangeProofError> for ProofError {
-    fn from(_err: RangeProofError) -> Self {
-        Self::RangeProof
+    fn from(err: RangeProofError) -> Self {
+        match err {
+            RangeProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::RangeProofFailed,
+        }
     }
 }
 
 impl From<EqualityProofError> for ProofError {
-    fn from(_err: EqualityProofError) -> Self {
-        Self::EqualityProof
+    fn from(err: EqualityProofError) -> Self {
+        match err {
+            EqualityProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::EqualityProofFailed,
+        }
     }
 }
 
 impl From<FeeSigmaProofError> for ProofError {
-    fn from(_err: FeeSigmaProofError) -> Self {
-        Self::FeeProof
+    fn from(err: FeeSigmaProofError) -> Self {
+        match err {
+            FeeSigmaProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::FeeSigmaProofFailed,
+        }
     }
 }
 
 impl From<ZeroBalanceProofError> for ProofError {
-    fn from(_err: ZeroBalanceProofError) -> Self {
-        Self::ZeroBalanceProof
+    fn from(err: ZeroBalanceProofError) -> Self {
+        match err {
+            ZeroBalanceProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::ZeroBalanceProofFailed,
+        }
     }
 }
+
 impl From<ValidityProofError> for ProofError {
-    fn from(_err: ValidityProofError) -> Self {
-        Self::ValidityProof
+    fn from(err: ValidityProofError) -> Self {
+        match err {
+            ValidityProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::ValidityProofFailed,
+        }
+    }
+}
+
+impl From<PubkeyValidityProofError> for ProofError {
+    fn from(err: PubkeyValidityProofError) -> Self {
+        match err {
+            PubkeyValidityProofError::NonCanonical => Self::NonCanonical,
+            _ => Self::PubkeyValidityProofFailed,
+        }
     }
 }
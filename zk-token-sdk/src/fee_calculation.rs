@@ -0,0 +1,145 @@
+//! Calculation of confidential transfer fees.
+//!
+//! `TransferWithFeeData` proves that the encrypted fee is the transfer amount times the fee rate,
+//! rounded up to a whole token and capped at the maximum fee. The functions here compute the same
+//! fee in the clear, so that a wallet can display it before it builds the proof.
+
+use crate::instruction::transfer_with_fee::FeeParameters;
+
+/// How a fee that is not a whole number of tokens is rounded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoundingMode {
+    /// Round up to the next token; this is the rounding that `TransferWithFeeData` proves
+    Ceil,
+    /// Round down to the previous token
+    Floor,
+}
+
+/// The fee that is assessed on a transfer and the amount that is left after it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeeBreakdown {
+    /// The amount that is transferred out of the source account
+    pub transfer_amount: u64,
+    /// The fee at the fee rate, before it is capped at the maximum fee
+    pub uncapped_fee: u64,
+    /// The fee that is encrypted in the proof, i.e. `min(uncapped_fee, maximum_fee)`
+    pub fee: u64,
+    /// The amount that remains for the destination once the fee is withheld
+    pub net_amount: u64,
+}
+
+impl FeeBreakdown {
+    /// Whether the fee is capped at the maximum fee
+    pub fn is_capped(&self) -> bool {
+        self.fee < self.uncapped_fee
+    }
+}
+
+/// Calculates the fee at the fee rate, before it is capped at the maximum fee.
+///
+/// Returns the fee along with its rounding error scaled by the fee denominator, i.e.
+/// `|fee * fee_denominator - transfer_amount * fee_rate|`, which is always less than the fee
+/// denominator. Returns `None` if the fee denominator is zero or if the fee does not fit in a
+/// `u64`.
+pub fn calculate_fee(
+    transfer_amount: u64,
+    fee_parameters: &FeeParameters,
+    rounding_mode: RoundingMode,
+) -> Option<(u64, u64)> {
+    let fee_denominator = fee_parameters.fee_denominator as u128;
+    if fee_denominator == 0 {
+        return None;
+    }
+
+    let fee_scaled = (transfer_amount as u128) * (fee_parameters.fee_rate as u128);
+    let fee = fee_scaled / fee_denominator;
+    let rem = fee_scaled % fee_denominator;
+
+    let (fee, delta_fee) = match rounding_mode {
+        RoundingMode::Ceil if rem != 0 => (fee + 1, fee_denominator - rem),
+        _ => (fee, rem),
+    };
+
+    // the rounding error is less than `fee_denominator`, so it always fits in a `u64`
+    Some((u64::try_from(fee).ok()?, delta_fee as u64))
+}
+
+/// Previews the fee that `TransferWithFeeData` encrypts for a transfer of `transfer_amount`.
+///
+/// Returns `None` if the fee cannot be calculated or if it exceeds the transfer amount.
+pub fn preview_fee(transfer_amount: u64, fee_parameters: &FeeParameters) -> Option<FeeBreakdown> {
+    let (uncapped_fee, _) = calculate_fee(transfer_amount, fee_parameters, RoundingMode::Ceil)?;
+    let fee = uncapped_fee.min(fee_parameters.maximum_fee);
+    let net_amount = transfer_amount.checked_sub(fee)?;
+
+    Some(FeeBreakdown {
+        transfer_amount,
+        uncapped_fee,
+        fee,
+        net_amount,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::instruction::transfer_with_fee::FEE_DENOMINATOR_BASIS_POINTS};
+
+    #[test]
+    fn test_calculate_fee_rounding() {
+        // 0.0025% fee rate
+        let fee_parameters = FeeParameters {
+            fee_rate: 25,
+            maximum_fee: 1_000,
+            fee_denominator: 1_000_000,
+        };
+
+        assert_eq!(
+            calculate_fee(1_234_567, &fee_parameters, RoundingMode::Ceil),
+            Some((31, 135_825))
+        );
+        assert_eq!(
+            calculate_fee(1_234_567, &fee_parameters, RoundingMode::Floor),
+            Some((30, 864_175))
+        );
+
+        // both modes agree on an exact fee
+        assert_eq!(
+            calculate_fee(1_240_000, &fee_parameters, RoundingMode::Ceil),
+            Some((31, 0))
+        );
+        assert_eq!(
+            calculate_fee(1_240_000, &fee_parameters, RoundingMode::Floor),
+            Some((31, 0))
+        );
+
+        let fee_parameters = FeeParameters {
+            fee_denominator: 0,
+            ..fee_parameters
+        };
+        assert_eq!(
+            calculate_fee(1_234_567, &fee_parameters, RoundingMode::Ceil),
+            None
+        );
+    }
+
+    #[test]
+    fn test_preview_fee() {
+        // 1% fee rate
+        let fee_parameters = FeeParameters {
+            fee_rate: 100,
+            maximum_fee: 3,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
+        };
+
+        let breakdown = preview_fee(150, &fee_parameters).unwrap();
+        assert_eq!(breakdown.fee, 2);
+        assert_eq!(breakdown.net_amount, 148);
+        assert!(!breakdown.is_capped());
+
+        let breakdown = preview_fee(1_000, &fee_parameters).unwrap();
+        assert_eq!(breakdown.uncapped_fee, 10);
+        assert_eq!(breakdown.fee, 3);
+        assert_eq!(breakdown.net_amount, 997);
+        assert!(breakdown.is_capped());
+    }
+}
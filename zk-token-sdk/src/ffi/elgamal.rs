@@ -0,0 +1,132 @@
+//! `ElGamalKeypair` C interface
+use {
+    super::{
+        input, output, SOLANA_ZK_TOKEN_ERROR_DECRYPTION, SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+        SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING, SOLANA_ZK_TOKEN_SUCCESS,
+    },
+    crate::encryption::elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+    std::ptr,
+};
+
+/// Creates a new random `ElGamalKeypair`
+///
+/// The returned handle must be released with `solana_zk_token_elgamal_keypair_free`.
+#[no_mangle]
+pub extern "C" fn solana_zk_token_elgamal_keypair_new() -> *mut ElGamalKeypair {
+    Box::into_raw(Box::new(ElGamalKeypair::new_rand()))
+}
+
+/// Recovers an `ElGamalKeypair` from its 64-byte encoding, or returns null if the encoding is
+/// invalid
+///
+/// The returned handle must be released with `solana_zk_token_elgamal_keypair_free`.
+///
+/// # Safety
+///
+/// `bytes` must be valid for reads of 64 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_keypair_from_bytes(
+    bytes: *const u8,
+) -> *mut ElGamalKeypair {
+    input::<64>(bytes)
+        .and_then(|bytes| ElGamalKeypair::from_bytes(&bytes[..]))
+        .map(|keypair| Box::into_raw(Box::new(keypair)))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Writes the 64-byte encoding of the keypair to `out`
+///
+/// # Safety
+///
+/// `keypair` must be a live handle and `out` must be valid for writes of 64 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_keypair_to_bytes(
+    keypair: *const ElGamalKeypair,
+    out: *mut u8,
+) -> i32 {
+    match keypair.as_ref() {
+        Some(keypair) => output(&keypair.to_bytes(), out),
+        None => SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Writes the 32-byte `ElGamalPubkey` of the keypair to `out`
+///
+/// # Safety
+///
+/// `keypair` must be a live handle and `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_keypair_pubkey(
+    keypair: *const ElGamalKeypair,
+    out: *mut u8,
+) -> i32 {
+    match keypair.as_ref() {
+        Some(keypair) => output(&keypair.public.to_bytes(), out),
+        None => SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Releases a keypair handle, which may be null
+///
+/// # Safety
+///
+/// `keypair` must be null or a live handle, which must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_keypair_free(keypair: *mut ElGamalKeypair) {
+    if !keypair.is_null() {
+        drop(Box::from_raw(keypair));
+    }
+}
+
+/// Encrypts `amount` under a 32-byte `ElGamalPubkey` and writes the 64-byte ciphertext to `out`
+///
+/// # Safety
+///
+/// `pubkey` must be valid for reads of 32 bytes and `out` must be valid for writes of 64 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_encrypt(
+    pubkey: *const u8,
+    amount: u64,
+    out: *mut u8,
+) -> i32 {
+    let pubkey = match input::<32>(pubkey) {
+        Some(bytes) => ElGamalPubkey::from_bytes(bytes),
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    };
+
+    match pubkey {
+        Some(pubkey) => output(&pubkey.encrypt(amount).to_bytes(), out),
+        None => SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING,
+    }
+}
+
+/// Decrypts a 64-byte ciphertext that encrypts a 32-bit amount and writes the amount to `amount`
+///
+/// # Safety
+///
+/// `keypair` must be a live handle, `ciphertext` must be valid for reads of 64 bytes, and
+/// `amount` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_elgamal_decrypt_u32(
+    keypair: *const ElGamalKeypair,
+    ciphertext: *const u8,
+    amount: *mut u64,
+) -> i32 {
+    let (keypair, ciphertext) = match (keypair.as_ref(), input::<64>(ciphertext)) {
+        (Some(keypair), Some(bytes)) if !amount.is_null() => (keypair, bytes),
+        _ => return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    };
+
+    let ciphertext = match ElGamalCiphertext::from_bytes(ciphertext) {
+        Some(ciphertext) => ciphertext,
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING,
+    };
+
+    match keypair.secret.decrypt_u32(&ciphertext) {
+        Some(decrypted) => {
+            *amount = decrypted.into();
+            SOLANA_ZK_TOKEN_SUCCESS
+        }
+        None => SOLANA_ZK_TOKEN_ERROR_DECRYPTION,
+    }
+}
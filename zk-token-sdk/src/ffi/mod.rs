@@ -0,0 +1,51 @@
+//! solana-zk-token-sdk C interface
+//!
+//! Objects that hold secrets, such as `ElGamalKeypair`, are exposed as opaque handles that are
+//! created and freed by the library. All other values are passed as byte buffers in their `pod`
+//! encoding: ElGamal pubkeys are 32 bytes, ciphertexts are 64 bytes, and proof data has the size
+//! returned by `solana_zk_token_proof_data_len`.
+//!
+//! Every function that can fail returns one of the `SOLANA_ZK_TOKEN_*` status codes.
+#![cfg(not(target_arch = "bpf"))]
+
+pub mod elgamal;
+pub mod proof;
+
+use std::slice;
+
+/// The call succeeded
+pub const SOLANA_ZK_TOKEN_SUCCESS: i32 = 0;
+/// A pointer argument was null or a buffer argument has the wrong length
+pub const SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT: i32 = 1;
+/// A pubkey, ciphertext, or keypair argument is not a valid encoding
+pub const SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING: i32 = 2;
+/// The proof data could not be generated
+pub const SOLANA_ZK_TOKEN_ERROR_PROOF_GENERATION: i32 = 3;
+/// The proof data failed to verify
+pub const SOLANA_ZK_TOKEN_ERROR_PROOF_VERIFICATION: i32 = 4;
+/// The proof type is unknown or does not verify proof data
+pub const SOLANA_ZK_TOKEN_ERROR_INVALID_PROOF_TYPE: i32 = 5;
+/// The ciphertext does not decrypt to a 32-bit amount
+pub const SOLANA_ZK_TOKEN_ERROR_DECRYPTION: i32 = 6;
+
+/// Borrows the `N` bytes at `ptr`, or `None` if `ptr` is null
+///
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of `N` bytes.
+unsafe fn input<'a, const N: usize>(ptr: *const u8) -> Option<&'a [u8; N]> {
+    (ptr as *const [u8; N]).as_ref()
+}
+
+/// Copies `bytes` to `out`
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of `bytes.len()` bytes.
+unsafe fn output(bytes: &[u8], out: *mut u8) -> i32 {
+    if out.is_null() {
+        return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT;
+    }
+    slice::from_raw_parts_mut(out, bytes.len()).copy_from_slice(bytes);
+    SOLANA_ZK_TOKEN_SUCCESS
+}
@@ -0,0 +1,293 @@
+//! Proof data C interface
+use {
+    super::{
+        input, output, SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+        SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING, SOLANA_ZK_TOKEN_ERROR_INVALID_PROOF_TYPE,
+        SOLANA_ZK_TOKEN_ERROR_PROOF_GENERATION, SOLANA_ZK_TOKEN_ERROR_PROOF_VERIFICATION,
+        SOLANA_ZK_TOKEN_SUCCESS,
+    },
+    crate::{
+        encryption::elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+        errors::ProofError,
+        instruction::Verifiable,
+        zk_token_proof_instruction::*,
+    },
+    bytemuck::{bytes_of, try_from_bytes, Pod},
+    num_traits::FromPrimitive,
+    std::{mem::size_of, slice},
+};
+
+/// Returns the size of the proof data that is verified by the `ProofInstruction` with
+/// discriminant `proof_type`, or 0 if the instruction does not verify proof data
+#[no_mangle]
+pub extern "C" fn solana_zk_token_proof_data_len(proof_type: u8) -> usize {
+    ProofInstruction::from_u8(proof_type)
+        .and_then(|proof_type| proof_type.proof_data_len())
+        .unwrap_or(0)
+}
+
+/// Creates a `CloseAccountData` for the 64-byte ciphertext of an account and writes it to `out`
+///
+/// # Safety
+///
+/// `keypair` must be a live handle, `ciphertext` must be valid for reads of 64 bytes, and `out`
+/// must be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_close_account_data_new(
+    keypair: *const ElGamalKeypair,
+    ciphertext: *const u8,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    let keypair = match keypair.as_ref() {
+        Some(keypair) => keypair,
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    };
+    let ciphertext = match ciphertext_from_ptr(ciphertext) {
+        Ok(ciphertext) => ciphertext,
+        Err(code) => return code,
+    };
+
+    output_proof_data(CloseAccountData::new(keypair, &ciphertext), out, out_len)
+}
+
+/// Creates a `WithdrawData` for withdrawing `amount` from an account whose 64-byte ciphertext
+/// decrypts to `current_balance` and writes it to `out`
+///
+/// # Safety
+///
+/// `keypair` must be a live handle, `current_ciphertext` must be valid for reads of 64 bytes, and
+/// `out` must be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_withdraw_data_new(
+    amount: u64,
+    keypair: *const ElGamalKeypair,
+    current_balance: u64,
+    current_ciphertext: *const u8,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    let keypair = match keypair.as_ref() {
+        Some(keypair) => keypair,
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    };
+    let current_ciphertext = match ciphertext_from_ptr(current_ciphertext) {
+        Ok(ciphertext) => ciphertext,
+        Err(code) => return code,
+    };
+
+    output_proof_data(
+        WithdrawData::new(amount, keypair, current_balance, &current_ciphertext),
+        out,
+        out_len,
+    )
+}
+
+/// Creates a `TransferData` and writes it to `out`
+///
+/// `ciphertext_old_source` is the 64-byte spendable balance ciphertext of the source account,
+/// which decrypts to `spendable_balance`, and `pubkeys_auditor` is the concatenation of
+/// `auditor_count` 32-byte auditor pubkeys.
+///
+/// # Safety
+///
+/// `keypair_source` must be a live handle, `ciphertext_old_source` must be valid for reads of 64
+/// bytes, `pubkey_dest` must be valid for reads of 32 bytes, `pubkeys_auditor` must be valid for
+/// reads of `32 * auditor_count` bytes, and `out` must be valid for writes of `out_len` bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn solana_zk_token_transfer_data_new(
+    transfer_amount: u64,
+    spendable_balance: u64,
+    ciphertext_old_source: *const u8,
+    keypair_source: *const ElGamalKeypair,
+    pubkey_dest: *const u8,
+    pubkeys_auditor: *const u8,
+    auditor_count: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    let keypair_source = match keypair_source.as_ref() {
+        Some(keypair) => keypair,
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    };
+    let ciphertext_old_source = match ciphertext_from_ptr(ciphertext_old_source) {
+        Ok(ciphertext) => ciphertext,
+        Err(code) => return code,
+    };
+    let pubkey_dest = match pubkey_from_ptr(pubkey_dest) {
+        Ok(pubkey) => pubkey,
+        Err(code) => return code,
+    };
+
+    if auditor_count > 0 && pubkeys_auditor.is_null() {
+        return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT;
+    }
+    let pubkeys_auditor = match (0..auditor_count)
+        .map(|i| pubkey_from_ptr(pubkeys_auditor.add(32 * i)))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(pubkeys) => pubkeys,
+        Err(code) => return code,
+    };
+
+    output_proof_data(
+        TransferData::new(
+            transfer_amount,
+            (spendable_balance, &ciphertext_old_source),
+            keypair_source,
+            (&pubkey_dest, &pubkeys_auditor),
+        ),
+        out,
+        out_len,
+    )
+}
+
+/// Verifies the proof data of the `ProofInstruction` with discriminant `proof_type`
+///
+/// This performs the same check as the ZkToken Proof program, so that a wallet can validate
+/// proof data before it is submitted.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `data_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn solana_zk_token_verify_proof(
+    proof_type: u8,
+    data: *const u8,
+    data_len: usize,
+) -> i32 {
+    if data.is_null() {
+        return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT;
+    }
+    let data = slice::from_raw_parts(data, data_len);
+
+    let proof_type = match ProofInstruction::from_u8(proof_type) {
+        Some(proof_type) => proof_type,
+        None => return SOLANA_ZK_TOKEN_ERROR_INVALID_PROOF_TYPE,
+    };
+
+    match proof_type {
+        ProofInstruction::VerifyCloseAccount => verify::<CloseAccountData>(data),
+        ProofInstruction::VerifyWithdraw => verify::<WithdrawData>(data),
+        ProofInstruction::VerifyTransfer => verify::<TransferData>(data),
+        ProofInstruction::VerifyTransferWithFee => verify::<TransferWithFeeData>(data),
+        ProofInstruction::VerifyWithdrawWithheldTokens => {
+            verify::<WithdrawWithheldTokensData>(data)
+        }
+        ProofInstruction::VerifyMint => verify::<MintData>(data),
+        ProofInstruction::VerifyBurn => verify::<BurnData>(data),
+        ProofInstruction::VerifyRotateAuditor => verify::<RotateAuditorData>(data),
+        ProofInstruction::VerifyTransferBatch => verify::<TransferBatchData>(data),
+        ProofInstruction::VerifyPubkeyValidity => verify::<PubkeyValidityData>(data),
+        ProofInstruction::VerifyCiphertextCiphertextEquality => {
+            verify::<CiphertextCiphertextEqualityData>(data)
+        }
+        ProofInstruction::VerifyCiphertextCommitmentEquality => {
+            verify::<CiphertextCommitmentEqualityData>(data)
+        }
+        ProofInstruction::VerifyFeeParametersUpdate => verify::<FeeParametersUpdateData>(data),
+        ProofInstruction::WriteProofChunk
+        | ProofInstruction::VerifyProofContext
+        | ProofInstruction::CloseProofContext => SOLANA_ZK_TOKEN_ERROR_INVALID_PROOF_TYPE,
+    }
+}
+
+fn verify<T: Pod + Verifiable>(data: &[u8]) -> i32 {
+    match try_from_bytes::<T>(data) {
+        Ok(proof_data) if proof_data.verify().is_ok() => SOLANA_ZK_TOKEN_SUCCESS,
+        Ok(_) => SOLANA_ZK_TOKEN_ERROR_PROOF_VERIFICATION,
+        Err(_) => SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of 64 bytes.
+unsafe fn ciphertext_from_ptr(ptr: *const u8) -> Result<ElGamalCiphertext, i32> {
+    let bytes = input::<64>(ptr).ok_or(SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT)?;
+    ElGamalCiphertext::from_bytes(bytes).ok_or(SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING)
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of 32 bytes.
+unsafe fn pubkey_from_ptr(ptr: *const u8) -> Result<ElGamalPubkey, i32> {
+    let bytes = input::<32>(ptr).ok_or(SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT)?;
+    ElGamalPubkey::from_bytes(bytes).ok_or(SOLANA_ZK_TOKEN_ERROR_INVALID_ENCODING)
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes of `out_len` bytes.
+unsafe fn output_proof_data<T: Pod>(
+    proof_data: Result<T, ProofError>,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    if out_len != size_of::<T>() {
+        return SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT;
+    }
+    match proof_data {
+        Ok(proof_data) => output(bytes_of(&proof_data), out),
+        Err(_) => SOLANA_ZK_TOKEN_ERROR_PROOF_GENERATION,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::ffi::elgamal::{
+            solana_zk_token_elgamal_encrypt, solana_zk_token_elgamal_keypair_free,
+            solana_zk_token_elgamal_keypair_new, solana_zk_token_elgamal_keypair_pubkey,
+        },
+        num_traits::ToPrimitive,
+    };
+
+    #[test]
+    fn test_withdraw_data_roundtrip() {
+        unsafe {
+            let keypair = solana_zk_token_elgamal_keypair_new();
+
+            let mut pubkey = [0u8; 32];
+            assert_eq!(
+                solana_zk_token_elgamal_keypair_pubkey(keypair, pubkey.as_mut_ptr()),
+                SOLANA_ZK_TOKEN_SUCCESS
+            );
+
+            let mut ciphertext = [0u8; 64];
+            assert_eq!(
+                solana_zk_token_elgamal_encrypt(pubkey.as_ptr(), 55, ciphertext.as_mut_ptr()),
+                SOLANA_ZK_TOKEN_SUCCESS
+            );
+
+            let proof_type = ProofInstruction::VerifyWithdraw.to_u8().unwrap();
+            let mut proof_data = vec![0u8; solana_zk_token_proof_data_len(proof_type)];
+            assert_eq!(
+                solana_zk_token_withdraw_data_new(
+                    20,
+                    keypair,
+                    55,
+                    ciphertext.as_ptr(),
+                    proof_data.as_mut_ptr(),
+                    proof_data.len(),
+                ),
+                SOLANA_ZK_TOKEN_SUCCESS
+            );
+            assert_eq!(
+                solana_zk_token_verify_proof(proof_type, proof_data.as_ptr(), proof_data.len()),
+                SOLANA_ZK_TOKEN_SUCCESS
+            );
+
+            // the proof data must match the proof type
+            let proof_type = ProofInstruction::VerifyCloseAccount.to_u8().unwrap();
+            assert_eq!(
+                solana_zk_token_verify_proof(proof_type, proof_data.as_ptr(), proof_data.len()),
+                SOLANA_ZK_TOKEN_ERROR_INVALID_ARGUMENT
+            );
+
+            solana_zk_token_elgamal_keypair_free(keypair);
+        }
+    }
+}
@@ -0,0 +1,611 @@
+#[cfg(feature = "prover")]
+use crate::{
+    encryption::{
+        discrete_log::*,
+        elgamal::{ElGamalKeypair, ElGamalSecretKey},
+    },
+    errors::DecryptionError,
+    instruction::{combine_u32_ciphertexts, pad_auditor_pubkeys, split_u64_into_u32, Role, TWO_32},
+    sigma_proofs::validity_proof::AggregatedValidityProof,
+};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::{
+            elgamal::{DecryptHandle, ElGamalCiphertext, ElGamalPubkey},
+            pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
+        },
+        errors::ProofError,
+        instruction::{decode_field, num_auditors, ProofVersion, Verifiable, MAX_AUDITORS},
+        range_proof::RangeProof,
+        sigma_proofs::equality_proof::EqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    arrayref::{array_ref, array_refs},
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+#[derive(Clone)]
+#[repr(C)]
+#[cfg(not(target_arch = "bpf"))]
+pub struct BurnAmountEncryption {
+    pub commitment: PedersenCommitment,
+    pub source: DecryptHandle,
+    /// The decryption handles of the auditors, which are zeroed for the unused auditor slots
+    pub auditors: [DecryptHandle; MAX_AUDITORS],
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl BurnAmountEncryption {
+    pub fn new(
+        amount: u32,
+        pubkey_source: &ElGamalPubkey,
+        pubkeys_auditor: &[ElGamalPubkey; MAX_AUDITORS],
+    ) -> (Self, PedersenOpening) {
+        let (commitment, opening) = Pedersen::new(amount);
+        let mut auditors = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, pubkey) in auditors.iter_mut().zip(pubkeys_auditor) {
+            *handle = pubkey.decrypt_handle(&opening);
+        }
+        let burn_amount_encryption = Self {
+            commitment,
+            source: pubkey_source.decrypt_handle(&opening),
+            auditors,
+        };
+
+        (burn_amount_encryption, opening)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 2)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 2)];
+        bytes[..32].copy_from_slice(&self.commitment.to_bytes());
+        bytes[32..64].copy_from_slice(&self.source.to_bytes());
+        for (chunk, auditor) in bytes[64..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 2)];
+        let (commitment, source, auditors) = array_refs![bytes, 32, 32, 32 * MAX_AUDITORS];
+
+        let commitment =
+            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::NonCanonical)?;
+        let source = DecryptHandle::from_bytes(source).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_handles = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, bytes) in auditor_handles.iter_mut().zip(auditors.chunks(32)) {
+            *handle = DecryptHandle::from_bytes(bytes).ok_or(ProofError::NonCanonical)?;
+        }
+
+        Ok(Self {
+            commitment,
+            source,
+            auditors: auditor_handles,
+        })
+    }
+}
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call BurnData::verify(&self)
+/// - The actual program should check that `ciphertext_new_source` is the available balance of the
+///   source account minus the burned amount, and that `burn_pubkeys` is consistent with the
+///   ElGamal pubkeys of the source account and the auditors of the mint
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct BurnData {
+    /// The context data needed to verify the proof
+    pub context: BurnProofContext,
+
+    /// Zero-knowledge proofs for Burn
+    pub proof: BurnProof,
+}
+
+/// The public inputs of a `BurnData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct BurnProofContext {
+    /// Group encryption of the low 32 bits of the burned amount
+    pub ciphertext_lo: pod::BurnAmountEncryption,
+
+    /// Group encryption of the high 32 bits of the burned amount
+    pub ciphertext_hi: pod::BurnAmountEncryption,
+
+    /// The public encryption keys associated with the burn: source and auditors
+    pub burn_pubkeys: pod::BurnPubkeys,
+
+    /// The final spendable ciphertext after the burn
+    pub ciphertext_new_source: pod::ElGamalCiphertext,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl BurnData {
+    #[cfg(feature = "prover")]
+    pub fn new(
+        burn_amount: u64,
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+        pubkeys_auditor: &[ElGamalPubkey],
+    ) -> Result<Self, ProofError> {
+        let padded_pubkeys_auditor = pad_auditor_pubkeys(pubkeys_auditor)?;
+
+        // split and encrypt burn amount
+        let (amount_lo, amount_hi) = split_u64_into_u32(burn_amount);
+
+        let (ciphertext_lo, opening_lo) =
+            BurnAmountEncryption::new(amount_lo, &keypair_source.public, &padded_pubkeys_auditor);
+        let (ciphertext_hi, opening_hi) =
+            BurnAmountEncryption::new(amount_hi, &keypair_source.public, &padded_pubkeys_auditor);
+
+        // subtract burn amount from the spendable ciphertext
+        let new_spendable_balance = spendable_balance
+            .checked_sub(burn_amount)
+            .ok_or(ProofError::Generation)?;
+
+        let burn_amount_lo_source = ElGamalCiphertext {
+            commitment: ciphertext_lo.commitment,
+            handle: ciphertext_lo.source,
+        };
+
+        let burn_amount_hi_source = ElGamalCiphertext {
+            commitment: ciphertext_hi.commitment,
+            handle: ciphertext_hi.source,
+        };
+
+        let ciphertext_new_source = ciphertext_old_source
+            - combine_u32_ciphertexts(&burn_amount_lo_source, &burn_amount_hi_source);
+
+        // generate transcript and append all public inputs
+        let pod_burn_pubkeys =
+            pod::BurnPubkeys::new(&keypair_source.public, &padded_pubkeys_auditor);
+        let pod_ciphertext_lo: pod::BurnAmountEncryption = ciphertext_lo.into();
+        let pod_ciphertext_hi: pod::BurnAmountEncryption = ciphertext_hi.into();
+        let pod_ciphertext_new_source: pod::ElGamalCiphertext = ciphertext_new_source.into();
+
+        let mut transcript = BurnProof::transcript_new(
+            &pod_burn_pubkeys,
+            &pod_ciphertext_lo,
+            &pod_ciphertext_hi,
+            &pod_ciphertext_new_source,
+        );
+
+        let proof = BurnProof::new(
+            (amount_lo, amount_hi),
+            keypair_source,
+            pubkeys_auditor,
+            (&opening_lo, &opening_hi),
+            (new_spendable_balance, &ciphertext_new_source),
+            &mut transcript,
+        )?;
+
+        Ok(Self {
+            context: BurnProofContext {
+                ciphertext_lo: pod_ciphertext_lo,
+                ciphertext_hi: pod_ciphertext_hi,
+                burn_pubkeys: pod_burn_pubkeys,
+                ciphertext_new_source: pod_ciphertext_new_source,
+            },
+            proof,
+        })
+    }
+
+    /// Extracts the lo ciphertexts associated with a burn data
+    #[cfg(feature = "prover")]
+    fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_lo: BurnAmountEncryption = self
+            .context
+            .ciphertext_lo
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let handle_lo = match role {
+            Role::Source => ciphertext_lo.source,
+            Role::Dest => return Err(DecryptionError::WrongRole),
+            Role::Auditor(index) => *ciphertext_lo
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
+        };
+
+        Ok(ElGamalCiphertext {
+            commitment: ciphertext_lo.commitment,
+            handle: handle_lo,
+        })
+    }
+
+    /// Extracts the hi ciphertexts associated with a burn data
+    #[cfg(feature = "prover")]
+    fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_hi: BurnAmountEncryption = self
+            .context
+            .ciphertext_hi
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let handle_hi = match role {
+            Role::Source => ciphertext_hi.source,
+            Role::Dest => return Err(DecryptionError::WrongRole),
+            Role::Auditor(index) => *ciphertext_hi
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
+        };
+
+        Ok(ElGamalCiphertext {
+            commitment: ciphertext_hi.commitment,
+            handle: handle_hi,
+        })
+    }
+
+    /// Decrypts burned amount from burn data
+    ///
+    /// Both halves of the amount are always decrypted before they are combined; the only branch
+    /// is on whether the decryption succeeded as a whole. The discrete log lookups are not
+    /// constant time.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount(
+        &self,
+        role: Role,
+        sk: &ElGamalSecretKey,
+    ) -> Result<u64, DecryptionError> {
+        let ciphertext_lo = self.ciphertext_lo(role)?;
+        let ciphertext_hi = self.ciphertext_hi(role)?;
+
+        let amount_lo = ciphertext_lo.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+        let amount_hi = ciphertext_hi.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+
+        let amount = amount_lo.and_then(|amount_lo| {
+            amount_hi.map(|amount_hi| (amount_lo as u64) + (TWO_32 * amount_hi as u64))
+        });
+        Option::<u64>::from(amount).ok_or(DecryptionError::DiscreteLogNotFound)
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for BurnData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        // generate transcript and append all public inputs
+        let mut transcript = BurnProof::transcript_new(
+            &self.context.burn_pubkeys,
+            &self.context.ciphertext_lo,
+            &self.context.ciphertext_hi,
+            &self.context.ciphertext_new_source,
+        );
+
+        let ciphertext_lo = decode_field(self.context.ciphertext_lo, "ciphertext_lo")?;
+        let ciphertext_hi = decode_field(self.context.ciphertext_hi, "ciphertext_hi")?;
+        let burn_pubkeys = decode_field(self.context.burn_pubkeys, "burn_pubkeys")?;
+        let new_spendable_ciphertext =
+            decode_field(self.context.ciphertext_new_source, "ciphertext_new_source")?;
+
+        self.proof.verify_batched(
+            &ciphertext_lo,
+            &ciphertext_hi,
+            &burn_pubkeys,
+            &new_spendable_ciphertext,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct BurnProof {
+    /// Version of the proof format
+    pub version: u8,
+
+    /// New Pedersen commitment for the remaining balance in source
+    pub commitment_new_source: pod::PedersenCommitment,
+
+    /// Associated equality proof
+    pub equality_proof: pod::EqualityProof,
+
+    /// Associated ciphertext validity proof
+    pub validity_proof: pod::AggregatedValidityProof,
+
+    /// Associated range proof
+    pub range_proof: pod::RangeProof128,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl BurnProof {
+    pub fn transcript_new(
+        burn_pubkeys: &pod::BurnPubkeys,
+        ciphertext_lo: &pod::BurnAmountEncryption,
+        ciphertext_hi: &pod::BurnAmountEncryption,
+        ciphertext_new_source: &pod::ElGamalCiphertext,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"burn-proof-v1");
+
+        transcript.append_message(b"burn-pubkeys", &burn_pubkeys.0);
+        transcript.append_message(b"ciphertext-lo", &ciphertext_lo.0);
+        transcript.append_message(b"ciphertext-hi", &ciphertext_hi.0);
+        transcript.append_message(b"ciphertext-new-source", &ciphertext_new_source.0);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        (burn_amount_lo, burn_amount_hi): (u32, u32),
+        keypair_source: &ElGamalKeypair,
+        pubkeys_auditor: &[ElGamalPubkey],
+        (opening_lo, opening_hi): (&PedersenOpening, &PedersenOpening),
+        (source_new_balance, ciphertext_new_source): (u64, &ElGamalCiphertext),
+        transcript: &mut Transcript,
+    ) -> Result<Self, ProofError> {
+        // generate a Pedersen commitment for the remaining balance in source
+        let (commitment_new_source, opening_source) = Pedersen::new(source_new_balance);
+
+        let pod_commitment_new_source: pod::PedersenCommitment = commitment_new_source.into();
+        transcript.append_commitment(b"commitment-new-source", &pod_commitment_new_source);
+
+        // generate equality_proof
+        let equality_proof = EqualityProof::new(
+            keypair_source,
+            ciphertext_new_source,
+            source_new_balance,
+            &opening_source,
+            transcript,
+        );
+
+        // generate ciphertext validity proof for the source and auditor handles; the source
+        // handle determines the amount that is deducted from the source balance
+        let pubkeys_validity: Vec<&ElGamalPubkey> = std::iter::once(&keypair_source.public)
+            .chain(pubkeys_auditor)
+            .collect();
+        let validity_proof = AggregatedValidityProof::new(
+            &pubkeys_validity,
+            (burn_amount_lo, burn_amount_hi),
+            (opening_lo, opening_hi),
+            transcript,
+        );
+
+        // generate the range proof
+        let range_proof = RangeProof::new(
+            vec![
+                source_new_balance,
+                burn_amount_lo as u64,
+                burn_amount_hi as u64,
+            ],
+            vec![64, 32, 32],
+            vec![&opening_source, opening_lo, opening_hi],
+            transcript,
+        )?;
+
+        Ok(Self {
+            version: Self::VERSION,
+            commitment_new_source: pod_commitment_new_source,
+            equality_proof: equality_proof.into(),
+            validity_proof: validity_proof.try_into()?,
+            range_proof: range_proof.try_into()?,
+        })
+    }
+
+    pub fn verify_batched(
+        &self,
+        ciphertext_lo: &BurnAmountEncryption,
+        ciphertext_hi: &BurnAmountEncryption,
+        burn_pubkeys: &BurnPubkeys,
+        new_spendable_ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        transcript.append_commitment(b"commitment-new-source", &self.commitment_new_source);
+
+        let commitment_new_source: PedersenCommitment =
+            decode_field(self.commitment_new_source, "commitment_new_source")?;
+        let equality_proof: EqualityProof = self.equality_proof.try_into()?;
+        let range_proof: RangeProof = self.range_proof.try_into()?;
+
+        let num_auditors = num_auditors(
+            &burn_pubkeys.auditors,
+            &[&ciphertext_lo.auditors, &ciphertext_hi.auditors],
+        )?;
+        let aggregated_validity_proof = self.validity_proof.to_proof(num_auditors + 1)?;
+
+        // verify equality proof
+        equality_proof.verify_batched(
+            &burn_pubkeys.source,
+            new_spendable_ciphertext,
+            &commitment_new_source,
+            transcript,
+            batch,
+        )?;
+
+        // verify validity proof
+        let pubkeys: Vec<&ElGamalPubkey> = std::iter::once(&burn_pubkeys.source)
+            .chain(&burn_pubkeys.auditors[..num_auditors])
+            .collect();
+        let handles_lo: Vec<&DecryptHandle> = std::iter::once(&ciphertext_lo.source)
+            .chain(&ciphertext_lo.auditors[..num_auditors])
+            .collect();
+        let handles_hi: Vec<&DecryptHandle> = std::iter::once(&ciphertext_hi.source)
+            .chain(&ciphertext_hi.auditors[..num_auditors])
+            .collect();
+        aggregated_validity_proof.verify_batched(
+            &pubkeys,
+            (&ciphertext_lo.commitment, &ciphertext_hi.commitment),
+            (&handles_lo, &handles_hi),
+            transcript,
+            batch,
+        )?;
+
+        // verify range proof
+        range_proof.verify_batched(
+            vec![
+                &commitment_new_source,
+                &ciphertext_lo.commitment,
+                &ciphertext_hi.commitment,
+            ],
+            vec![64_usize, 32_usize, 32_usize],
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The ElGamal public keys needed for a burn
+#[derive(Clone)]
+#[repr(C)]
+#[cfg(not(target_arch = "bpf"))]
+pub struct BurnPubkeys {
+    pub source: ElGamalPubkey,
+    /// The pubkeys of the auditors, followed by zeroed pubkeys for the unused auditor slots
+    pub auditors: [ElGamalPubkey; MAX_AUDITORS],
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl BurnPubkeys {
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 1)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 1)];
+        bytes[..32].copy_from_slice(&self.source.to_bytes());
+        for (chunk, auditor) in bytes[32..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 1)];
+        let (source, auditors) = array_refs![bytes, 32, 32 * MAX_AUDITORS];
+
+        let source = ElGamalPubkey::from_bytes(source).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_pubkeys = [ElGamalPubkey::default(); MAX_AUDITORS];
+        for (pubkey, bytes) in auditor_pubkeys.iter_mut().zip(auditors.chunks(32)) {
+            *pubkey = ElGamalPubkey::from_bytes(array_ref![bytes, 0, 32])
+                .ok_or(ProofError::NonCanonical)?;
+        }
+
+        Ok(Self {
+            source,
+            auditors: auditor_pubkeys,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl pod::BurnPubkeys {
+    pub fn new(source: &ElGamalPubkey, auditors: &[ElGamalPubkey; MAX_AUDITORS]) -> Self {
+        Self(
+            BurnPubkeys {
+                source: *source,
+                auditors: *auditors,
+            }
+            .to_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::encryption::elgamal::ElGamalKeypair};
+
+    #[test]
+    fn test_burn_correctness() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let auditor_keypair = ElGamalKeypair::new_rand();
+
+        let spendable_balance: u64 = 77;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        let burn_amount: u64 = 55;
+
+        let burn_data = BurnData::new(
+            burn_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            &[auditor_keypair.public],
+        )
+        .unwrap();
+        assert!(burn_data.verify().is_ok());
+
+        assert_eq!(
+            burn_data
+                .decrypt_amount(Role::Source, &source_keypair.secret)
+                .unwrap(),
+            burn_amount,
+        );
+        assert_eq!(
+            burn_data
+                .decrypt_amount(Role::Auditor(0), &auditor_keypair.secret)
+                .unwrap(),
+            burn_amount,
+        );
+        assert_eq!(
+            burn_data.decrypt_amount(Role::Dest, &source_keypair.secret),
+            Err(DecryptionError::WrongRole),
+        );
+
+        // the remaining balance decrypts to the spendable balance minus the burned amount
+        let new_spendable_ciphertext: ElGamalCiphertext =
+            burn_data.context.ciphertext_new_source.try_into().unwrap();
+        assert_eq!(
+            new_spendable_ciphertext
+                .decrypt_u32_online(&source_keypair.secret, &DECODE_U32_PRECOMPUTATION_FOR_G)
+                .unwrap(),
+            22_u32,
+        );
+
+        // cannot burn more than the spendable balance
+        assert!(BurnData::new(
+            spendable_balance + 1,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            &[],
+        )
+        .is_err());
+
+        // proof with a wrong spendable balance fails to verify
+        let burn_data = BurnData::new(
+            burn_amount,
+            (99, &spendable_ciphertext),
+            &source_keypair,
+            &[],
+        )
+        .unwrap();
+        assert!(burn_data.verify().is_err());
+    }
+}
@@ -0,0 +1,274 @@
+#[cfg(feature = "prover")]
+use crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        errors::ProofError,
+        instruction::{decode_field, ProofVersion, Verifiable},
+        sigma_proofs::equality_proof::CtxtCtxtEqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call CiphertextCiphertextEqualityData::verify(&self)
+/// - The actual program should check that the pubkeys and ciphertexts are consistent with the
+///   accounts that the instruction operates on
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCiphertextEqualityData {
+    /// The context data needed to verify the proof
+    pub context: CiphertextCiphertextEqualityProofContext,
+
+    /// Proof that the two ciphertexts encrypt the same amount
+    pub proof: CiphertextCiphertextEqualityProof, // 225 bytes
+}
+
+/// The public inputs of a `CiphertextCiphertextEqualityData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCiphertextEqualityProofContext {
+    /// The ElGamal pubkey of the source ciphertext
+    pub source_pubkey: pod::ElGamalPubkey, // 32 bytes
+
+    /// The ElGamal pubkey of the destination ciphertext
+    pub destination_pubkey: pod::ElGamalPubkey, // 32 bytes
+
+    /// The amount encrypted under the source pubkey
+    pub source_ciphertext: pod::ElGamalCiphertext, // 64 bytes
+
+    /// The amount encrypted under the destination pubkey
+    pub destination_ciphertext: pod::ElGamalCiphertext, // 64 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl CiphertextCiphertextEqualityData {
+    /// Proves that `source_ciphertext` and `destination_ciphertext` both encrypt `amount`.
+    ///
+    /// The prover must hold the source keypair and the opening that `destination_ciphertext` was
+    /// encrypted with.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        source_keypair: &ElGamalKeypair,
+        destination_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        destination_ciphertext: &ElGamalCiphertext,
+        destination_opening: &PedersenOpening,
+        amount: u64,
+    ) -> Result<Self, ProofError> {
+        let pod_source_pubkey = pod::ElGamalPubkey(source_keypair.public.to_bytes());
+        let pod_destination_pubkey = pod::ElGamalPubkey(destination_pubkey.to_bytes());
+        let pod_source_ciphertext: pod::ElGamalCiphertext = (*source_ciphertext).into();
+        let pod_destination_ciphertext: pod::ElGamalCiphertext = (*destination_ciphertext).into();
+
+        let mut transcript = CiphertextCiphertextEqualityProof::transcript_new(
+            &pod_source_pubkey,
+            &pod_destination_pubkey,
+            &pod_source_ciphertext,
+            &pod_destination_ciphertext,
+        );
+
+        let proof = CiphertextCiphertextEqualityProof::new(
+            source_keypair,
+            destination_pubkey,
+            source_ciphertext,
+            amount,
+            destination_opening,
+            &mut transcript,
+        );
+
+        Ok(Self {
+            context: CiphertextCiphertextEqualityProofContext {
+                source_pubkey: pod_source_pubkey,
+                destination_pubkey: pod_destination_pubkey,
+                source_ciphertext: pod_source_ciphertext,
+                destination_ciphertext: pod_destination_ciphertext,
+            },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for CiphertextCiphertextEqualityData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript = CiphertextCiphertextEqualityProof::transcript_new(
+            &self.context.source_pubkey,
+            &self.context.destination_pubkey,
+            &self.context.source_ciphertext,
+            &self.context.destination_ciphertext,
+        );
+
+        let source_pubkey = decode_field(self.context.source_pubkey, "source_pubkey")?;
+        let destination_pubkey =
+            decode_field(self.context.destination_pubkey, "destination_pubkey")?;
+        let source_ciphertext = decode_field(self.context.source_ciphertext, "source_ciphertext")?;
+        let destination_ciphertext = decode_field(
+            self.context.destination_ciphertext,
+            "destination_ciphertext",
+        )?;
+
+        self.proof.verify_batched(
+            &source_pubkey,
+            &destination_pubkey,
+            &source_ciphertext,
+            &destination_ciphertext,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that two ciphertexts
+/// under different pubkeys encrypt the same amount
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCiphertextEqualityProof {
+    pub version: u8,
+    pub proof: pod::CtxtCtxtEqualityProof,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl CiphertextCiphertextEqualityProof {
+    pub fn transcript_new(
+        source_pubkey: &pod::ElGamalPubkey,
+        destination_pubkey: &pod::ElGamalPubkey,
+        source_ciphertext: &pod::ElGamalCiphertext,
+        destination_ciphertext: &pod::ElGamalCiphertext,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"CiphertextCiphertextEqualityProof-v1");
+
+        transcript.append_pubkey(b"source-pubkey", source_pubkey);
+        transcript.append_pubkey(b"destination-pubkey", destination_pubkey);
+
+        transcript.append_ciphertext(b"source-ciphertext", source_ciphertext);
+        transcript.append_ciphertext(b"destination-ciphertext", destination_ciphertext);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        source_keypair: &ElGamalKeypair,
+        destination_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        destination_opening: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let proof = CtxtCtxtEqualityProof::new(
+            source_keypair,
+            destination_pubkey,
+            source_ciphertext,
+            amount,
+            destination_opening,
+            transcript,
+        );
+
+        Self {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        source_pubkey: &ElGamalPubkey,
+        destination_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        destination_ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: CtxtCtxtEqualityProof = self.proof.try_into()?;
+        proof.verify_batched(
+            source_pubkey,
+            destination_pubkey,
+            source_ciphertext,
+            destination_ciphertext,
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ciphertext_ciphertext_equality_correctness() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let destination_pubkey = ElGamalKeypair::new_rand().public;
+
+        let amount: u64 = 55;
+        let source_ciphertext = source_keypair.public.encrypt(amount);
+
+        let destination_opening = PedersenOpening::new_rand();
+        let destination_ciphertext = destination_pubkey.encrypt_with(amount, &destination_opening);
+
+        let equality_data = CiphertextCiphertextEqualityData::new(
+            &source_keypair,
+            &destination_pubkey,
+            &source_ciphertext,
+            &destination_ciphertext,
+            &destination_opening,
+            amount,
+        )
+        .unwrap();
+        assert!(equality_data.verify().is_ok());
+
+        // the two ciphertexts must encrypt the same amount
+        let destination_ciphertext = destination_pubkey.encrypt_with(77_u64, &destination_opening);
+
+        let equality_data = CiphertextCiphertextEqualityData::new(
+            &source_keypair,
+            &destination_pubkey,
+            &source_ciphertext,
+            &destination_ciphertext,
+            &destination_opening,
+            amount,
+        )
+        .unwrap();
+        assert!(equality_data.verify().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+#[cfg(feature = "prover")]
+use crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::{
+            elgamal::{ElGamalCiphertext, ElGamalPubkey},
+            pedersen::PedersenCommitment,
+        },
+        errors::ProofError,
+        instruction::{decode_field, ProofVersion, Verifiable},
+        sigma_proofs::equality_proof::EqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call CiphertextCommitmentEqualityData::verify(&self)
+/// - The actual program should check that `pubkey` and `ciphertext` are consistent with the
+///   account that the instruction operates on and that `commitment` is the commitment that is
+///   being revealed against
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCommitmentEqualityData {
+    /// The context data needed to verify the proof
+    pub context: CiphertextCommitmentEqualityProofContext,
+
+    /// Proof that the ciphertext and the commitment hold the same amount
+    pub proof: CiphertextCommitmentEqualityProof, // 193 bytes
+}
+
+/// The public inputs of a `CiphertextCommitmentEqualityData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCommitmentEqualityProofContext {
+    /// The ElGamal pubkey of the ciphertext
+    pub pubkey: pod::ElGamalPubkey, // 32 bytes
+
+    /// The amount encrypted under the pubkey
+    pub ciphertext: pod::ElGamalCiphertext, // 64 bytes
+
+    /// The Pedersen commitment to the amount
+    pub commitment: pod::PedersenCommitment, // 32 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl CiphertextCommitmentEqualityData {
+    /// Proves that `ciphertext` encrypts the `amount` that `commitment` commits to.
+    ///
+    /// The prover must hold the keypair of the ciphertext and the opening of the commitment.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+        commitment: &PedersenCommitment,
+        opening: &PedersenOpening,
+        amount: u64,
+    ) -> Result<Self, ProofError> {
+        let pod_pubkey = pod::ElGamalPubkey(keypair.public.to_bytes());
+        let pod_ciphertext: pod::ElGamalCiphertext = (*ciphertext).into();
+        let pod_commitment: pod::PedersenCommitment = (*commitment).into();
+
+        let mut transcript = CiphertextCommitmentEqualityProof::transcript_new(
+            &pod_pubkey,
+            &pod_ciphertext,
+            &pod_commitment,
+        );
+
+        let proof = CiphertextCommitmentEqualityProof::new(
+            keypair,
+            ciphertext,
+            amount,
+            opening,
+            &mut transcript,
+        );
+
+        Ok(Self {
+            context: CiphertextCommitmentEqualityProofContext {
+                pubkey: pod_pubkey,
+                ciphertext: pod_ciphertext,
+                commitment: pod_commitment,
+            },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for CiphertextCommitmentEqualityData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript = CiphertextCommitmentEqualityProof::transcript_new(
+            &self.context.pubkey,
+            &self.context.ciphertext,
+            &self.context.commitment,
+        );
+
+        let pubkey = decode_field(self.context.pubkey, "pubkey")?;
+        let ciphertext = decode_field(self.context.ciphertext, "ciphertext")?;
+        let commitment = decode_field(self.context.commitment, "commitment")?;
+
+        self.proof
+            .verify_batched(&pubkey, &ciphertext, &commitment, &mut transcript, batch)
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that a ciphertext and
+/// a Pedersen commitment hold the same amount
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CiphertextCommitmentEqualityProof {
+    pub version: u8,
+    pub proof: pod::EqualityProof,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl CiphertextCommitmentEqualityProof {
+    pub fn transcript_new(
+        pubkey: &pod::ElGamalPubkey,
+        ciphertext: &pod::ElGamalCiphertext,
+        commitment: &pod::PedersenCommitment,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"CiphertextCommitmentEqualityProof-v1");
+
+        transcript.append_pubkey(b"pubkey", pubkey);
+        transcript.append_ciphertext(b"ciphertext", ciphertext);
+        transcript.append_commitment(b"commitment", commitment);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        opening: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let proof = EqualityProof::new(keypair, ciphertext, amount, opening, transcript);
+
+        Self {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        pubkey: &ElGamalPubkey,
+        ciphertext: &ElGamalCiphertext,
+        commitment: &PedersenCommitment,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: EqualityProof = self.proof.try_into()?;
+        proof.verify_batched(pubkey, ciphertext, commitment, transcript, batch)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::encryption::pedersen::Pedersen};
+
+    #[test]
+    fn test_ciphertext_commitment_equality_correctness() {
+        let keypair = ElGamalKeypair::new_rand();
+
+        let amount: u64 = 55;
+        let ciphertext = keypair.public.encrypt(amount);
+        let (commitment, opening) = Pedersen::new(amount);
+
+        let equality_data = CiphertextCommitmentEqualityData::new(
+            &keypair,
+            &ciphertext,
+            &commitment,
+            &opening,
+            amount,
+        )
+        .unwrap();
+        assert!(equality_data.verify().is_ok());
+
+        // the commitment must hold the amount that is encrypted by the ciphertext
+        let (commitment, opening) = Pedersen::new(77_u64);
+
+        let equality_data = CiphertextCommitmentEqualityData::new(
+            &keypair,
+            &ciphertext,
+            &commitment,
+            &opening,
+            amount,
+        )
+        .unwrap();
+        assert!(equality_data.verify().is_err());
+    }
+}
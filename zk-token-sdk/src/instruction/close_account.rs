@@ -1,3 +1,5 @@
+#[cfg(feature = "prover")]
+use crate::encryption::elgamal::ElGamalKeypair;
 use {
     crate::zk_token_elgamal::pod,
     bytemuck::{Pod, Zeroable},
@@ -5,9 +7,10 @@ use {
 #[cfg(not(target_arch = "bpf"))]
 use {
     crate::{
-        encryption::elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
         errors::ProofError,
-        instruction::Verifiable,
+        instruction::{decode_field, ProofVersion, Verifiable},
         sigma_proofs::zero_balance_proof::ZeroBalanceProof,
         transcript::TranscriptProtocol,
     },
@@ -18,25 +21,50 @@ use {
 /// This struct includes the cryptographic proof *and* the account data information needed to verify
 /// the proof
 ///
-/// - The pre-instruction should call CloseAccountData::verify_proof(&self)
+/// - The pre-instruction should call CloseAccountData::verify(&self)
 /// - The actual program should check that `balance` is consistent with what is
 ///   currently stored in the confidential token account
 ///
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 pub struct CloseAccountData {
+    /// The context data needed to verify the proof
+    pub context: CloseAccountProofContext,
+
+    /// Proof that the source account available balance is zero
+    pub proof: CloseAccountProof, // 65 bytes
+}
+
+/// The public inputs of a `CloseAccountData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct CloseAccountProofContext {
     /// The source account ElGamal pubkey
     pub pubkey: pod::ElGamalPubkey, // 32 bytes
 
     /// The source account available balance in encrypted form
     pub ciphertext: pod::ElGamalCiphertext, // 64 bytes
-
-    /// Proof that the source account available balance is zero
-    pub proof: CloseAccountProof, // 64 bytes
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl CloseAccountData {
+    #[cfg(feature = "prover")]
     pub fn new(
         keypair: &ElGamalKeypair,
         ciphertext: &ElGamalCiphertext,
@@ -48,9 +76,11 @@ impl CloseAccountData {
 
         let proof = CloseAccountProof::new(keypair, ciphertext, &mut transcript);
 
-        Ok(CloseAccountData {
-            pubkey: pod_pubkey,
-            ciphertext: pod_ciphertext,
+        Ok(Self {
+            context: CloseAccountProofContext {
+                pubkey: pod_pubkey,
+                ciphertext: pod_ciphertext,
+            },
             proof,
         })
     }
@@ -58,12 +88,14 @@ impl CloseAccountData {
 
 #[cfg(not(target_arch = "bpf"))]
 impl Verifiable for CloseAccountData {
-    fn verify(&self) -> Result<(), ProofError> {
-        let mut transcript = CloseAccountProof::transcript_new(&self.pubkey, &self.ciphertext);
-
-        let pubkey = self.pubkey.try_into()?;
-        let ciphertext = self.ciphertext.try_into()?;
-        self.proof.verify(&pubkey, &ciphertext, &mut transcript)
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript =
+            CloseAccountProof::transcript_new(&self.context.pubkey, &self.context.ciphertext);
+
+        let pubkey = decode_field(self.context.pubkey, "pubkey")?;
+        let ciphertext = decode_field(self.context.ciphertext, "ciphertext")?;
+        self.proof
+            .verify_batched(&pubkey, &ciphertext, &mut transcript, batch)
     }
 }
 
@@ -71,19 +103,28 @@ impl Verifiable for CloseAccountData {
 /// balance is zero
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 #[allow(non_snake_case)]
 pub struct CloseAccountProof {
+    pub version: u8,
     pub proof: pod::ZeroBalanceProof,
 }
 
 #[allow(non_snake_case)]
 #[cfg(not(target_arch = "bpf"))]
 impl CloseAccountProof {
-    fn transcript_new(
+    pub fn transcript_new(
         pubkey: &pod::ElGamalPubkey,
         ciphertext: &pod::ElGamalCiphertext,
     ) -> Transcript {
-        let mut transcript = Transcript::new(b"CloseAccountProof");
+        let mut transcript = Transcript::new(b"CloseAccountProof-v1");
 
         transcript.append_pubkey(b"pubkey", pubkey);
         transcript.append_ciphertext(b"ciphertext", ciphertext);
@@ -91,6 +132,7 @@ impl CloseAccountProof {
         transcript
     }
 
+    #[cfg(feature = "prover")]
     pub fn new(
         keypair: &ElGamalKeypair,
         ciphertext: &ElGamalCiphertext,
@@ -99,18 +141,22 @@ impl CloseAccountProof {
         let proof = ZeroBalanceProof::new(keypair, ciphertext, transcript);
 
         CloseAccountProof {
+            version: Self::VERSION,
             proof: proof.into(),
         }
     }
 
-    pub fn verify(
+    pub fn verify_batched(
         &self,
         pubkey: &ElGamalPubkey,
         ciphertext: &ElGamalCiphertext,
         transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), ProofError> {
+        self.check_version()?;
+
         let proof: ZeroBalanceProof = self.proof.try_into()?;
-        proof.verify(pubkey, ciphertext, transcript)?;
+        proof.verify_batched(pubkey, ciphertext, transcript, batch)?;
 
         Ok(())
     }
@@ -132,6 +178,23 @@ mod test {
         // general case: encryption of > 0
         let ciphertext = keypair.public.encrypt(1_u64);
         let close_account_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
+        assert_eq!(
+            close_account_data.verify(),
+            Err(ProofError::ZeroBalanceProofFailed)
+        );
+
+        // malformed pubkey
+        let mut malformed_data = close_account_data;
+        malformed_data.context.pubkey = pod::ElGamalPubkey([0xff; 32]);
+        assert_eq!(
+            malformed_data.verify(),
+            Err(ProofError::MalformedPod("pubkey"))
+        );
+
+        // encryption of 0 under a different pubkey
+        let other_keypair = ElGamalKeypair::new_rand();
+        let ciphertext = other_keypair.public.encrypt(0_u64);
+        let close_account_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
         assert!(close_account_data.verify().is_err());
     }
 }
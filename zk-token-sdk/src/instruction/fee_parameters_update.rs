@@ -0,0 +1,341 @@
+#[cfg(feature = "prover")]
+use crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        errors::ProofError,
+        instruction::{decode_field, transfer_with_fee::FeeParameters, ProofVersion, Verifiable},
+        sigma_proofs::equality_proof::CtxtCtxtEqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// The withheld fees of a mint are carried over to a fresh ciphertext when its fee parameters are
+/// updated, so that the fees that were withheld under the old parameters are fixed by the update
+/// and cannot be altered retroactively.
+///
+/// - The pre-instruction should call FeeParametersUpdateData::verify(&self)
+/// - The actual program should check that `old_fee_parameters`, `pubkey_old_authority`, and
+///   `ciphertext_old_authority` are the fee parameters, withdraw-withheld authority pubkey, and
+///   withheld fees currently stored in the mint, and then replace them with their new
+///   counterparts
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct FeeParametersUpdateData {
+    /// The context data needed to verify the proof
+    pub context: FeeParametersUpdateProofContext,
+
+    /// Proof that the two ciphertexts encrypt the same amount
+    pub proof: FeeParametersUpdateProof, // 225 bytes
+}
+
+/// The public inputs of a `FeeParametersUpdateData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct FeeParametersUpdateProofContext {
+    /// The fee parameters before the update
+    pub old_fee_parameters: pod::FeeParameters, // 24 bytes
+
+    /// The fee parameters after the update
+    pub new_fee_parameters: pod::FeeParameters, // 24 bytes
+
+    /// The ElGamal pubkey of the withdraw-withheld authority before the update
+    pub pubkey_old_authority: pod::ElGamalPubkey, // 32 bytes
+
+    /// The ElGamal pubkey of the withdraw-withheld authority after the update
+    pub pubkey_new_authority: pod::ElGamalPubkey, // 32 bytes
+
+    /// The withheld fees encrypted under the old authority pubkey
+    pub ciphertext_old_authority: pod::ElGamalCiphertext, // 64 bytes
+
+    /// The withheld fees re-encrypted under the new authority pubkey
+    pub ciphertext_new_authority: pod::ElGamalCiphertext, // 64 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl FeeParametersUpdateData {
+    /// Carries the withheld fees of a mint over to an update of its fee parameters.
+    ///
+    /// The `withheld_amount` is the amount encrypted by `ciphertext_old_authority`, which the old
+    /// authority can decrypt locally. If the withdraw-withheld authority does not change, the
+    /// old authority pubkey can be passed as `pubkey_new_authority`.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        old_fee_parameters: FeeParameters,
+        new_fee_parameters: FeeParameters,
+        keypair_old_authority: &ElGamalKeypair,
+        pubkey_new_authority: &ElGamalPubkey,
+        ciphertext_old_authority: &ElGamalCiphertext,
+        withheld_amount: u64,
+    ) -> Result<Self, ProofError> {
+        new_fee_parameters.validate()?;
+
+        // re-encrypt the withheld fees under the new authority pubkey
+        let opening_new_authority = PedersenOpening::new_rand();
+        let ciphertext_new_authority =
+            pubkey_new_authority.encrypt_with(withheld_amount, &opening_new_authority);
+
+        let pod_old_fee_parameters: pod::FeeParameters = old_fee_parameters.into();
+        let pod_new_fee_parameters: pod::FeeParameters = new_fee_parameters.into();
+        let pod_pubkey_old_authority = pod::ElGamalPubkey(keypair_old_authority.public.to_bytes());
+        let pod_pubkey_new_authority = pod::ElGamalPubkey(pubkey_new_authority.to_bytes());
+        let pod_ciphertext_old_authority: pod::ElGamalCiphertext =
+            (*ciphertext_old_authority).into();
+        let pod_ciphertext_new_authority: pod::ElGamalCiphertext = ciphertext_new_authority.into();
+
+        let mut transcript = FeeParametersUpdateProof::transcript_new(
+            &pod_old_fee_parameters,
+            &pod_new_fee_parameters,
+            &pod_pubkey_old_authority,
+            &pod_pubkey_new_authority,
+            &pod_ciphertext_old_authority,
+            &pod_ciphertext_new_authority,
+        );
+
+        let proof = FeeParametersUpdateProof::new(
+            keypair_old_authority,
+            pubkey_new_authority,
+            ciphertext_old_authority,
+            withheld_amount,
+            &opening_new_authority,
+            &mut transcript,
+        );
+
+        Ok(Self {
+            context: FeeParametersUpdateProofContext {
+                old_fee_parameters: pod_old_fee_parameters,
+                new_fee_parameters: pod_new_fee_parameters,
+                pubkey_old_authority: pod_pubkey_old_authority,
+                pubkey_new_authority: pod_pubkey_new_authority,
+                ciphertext_old_authority: pod_ciphertext_old_authority,
+                ciphertext_new_authority: pod_ciphertext_new_authority,
+            },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for FeeParametersUpdateData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        // the new fee parameters must be usable by subsequent transfers with fee
+        decode_field::<_, FeeParameters>(self.context.new_fee_parameters, "new_fee_parameters")?;
+
+        let mut transcript = FeeParametersUpdateProof::transcript_new(
+            &self.context.old_fee_parameters,
+            &self.context.new_fee_parameters,
+            &self.context.pubkey_old_authority,
+            &self.context.pubkey_new_authority,
+            &self.context.ciphertext_old_authority,
+            &self.context.ciphertext_new_authority,
+        );
+
+        let pubkey_old_authority =
+            decode_field(self.context.pubkey_old_authority, "pubkey_old_authority")?;
+        let pubkey_new_authority =
+            decode_field(self.context.pubkey_new_authority, "pubkey_new_authority")?;
+        let ciphertext_old_authority = decode_field(
+            self.context.ciphertext_old_authority,
+            "ciphertext_old_authority",
+        )?;
+        let ciphertext_new_authority = decode_field(
+            self.context.ciphertext_new_authority,
+            "ciphertext_new_authority",
+        )?;
+
+        self.proof.verify_batched(
+            &pubkey_old_authority,
+            &pubkey_new_authority,
+            &ciphertext_old_authority,
+            &ciphertext_new_authority,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that the withheld
+/// fees are carried over the fee parameters update without change
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct FeeParametersUpdateProof {
+    pub version: u8,
+    pub proof: pod::CtxtCtxtEqualityProof,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl FeeParametersUpdateProof {
+    pub fn transcript_new(
+        old_fee_parameters: &pod::FeeParameters,
+        new_fee_parameters: &pod::FeeParameters,
+        pubkey_old_authority: &pod::ElGamalPubkey,
+        pubkey_new_authority: &pod::ElGamalPubkey,
+        ciphertext_old_authority: &pod::ElGamalCiphertext,
+        ciphertext_new_authority: &pod::ElGamalCiphertext,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"FeeParametersUpdateProof-v1");
+
+        transcript.append_message(b"old-fee-parameters", &old_fee_parameters.0);
+        transcript.append_message(b"new-fee-parameters", &new_fee_parameters.0);
+
+        transcript.append_pubkey(b"pubkey-old-authority", pubkey_old_authority);
+        transcript.append_pubkey(b"pubkey-new-authority", pubkey_new_authority);
+
+        transcript.append_ciphertext(b"ciphertext-old-authority", ciphertext_old_authority);
+        transcript.append_ciphertext(b"ciphertext-new-authority", ciphertext_new_authority);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair_old_authority: &ElGamalKeypair,
+        pubkey_new_authority: &ElGamalPubkey,
+        ciphertext_old_authority: &ElGamalCiphertext,
+        withheld_amount: u64,
+        opening_new_authority: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let proof = CtxtCtxtEqualityProof::new(
+            keypair_old_authority,
+            pubkey_new_authority,
+            ciphertext_old_authority,
+            withheld_amount,
+            opening_new_authority,
+            transcript,
+        );
+
+        Self {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        pubkey_old_authority: &ElGamalPubkey,
+        pubkey_new_authority: &ElGamalPubkey,
+        ciphertext_old_authority: &ElGamalCiphertext,
+        ciphertext_new_authority: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: CtxtCtxtEqualityProof = self.proof.try_into()?;
+        proof.verify_batched(
+            pubkey_old_authority,
+            pubkey_new_authority,
+            ciphertext_old_authority,
+            ciphertext_new_authority,
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fee_parameters_update_correctness() {
+        let old_fee_parameters = FeeParameters {
+            fee_rate: 100,
+            maximum_fee: 3,
+            fee_denominator: 10_000,
+        };
+        let new_fee_parameters = FeeParameters {
+            fee_rate: 200,
+            maximum_fee: 5,
+            fee_denominator: 10_000,
+        };
+
+        let keypair_authority = ElGamalKeypair::new_rand();
+
+        let withheld_amount: u64 = 55;
+        let ciphertext_withheld = keypair_authority.public.encrypt(withheld_amount);
+
+        let update_data = FeeParametersUpdateData::new(
+            old_fee_parameters,
+            new_fee_parameters,
+            &keypair_authority,
+            &keypair_authority.public,
+            &ciphertext_withheld,
+            withheld_amount,
+        )
+        .unwrap();
+        assert!(update_data.verify().is_ok());
+
+        // the proof is bound to the fee parameters of the update
+        let mut tampered_data = update_data;
+        tampered_data.context.new_fee_parameters = old_fee_parameters.into();
+        assert!(tampered_data.verify().is_err());
+
+        // the withheld fees cannot change over the update
+        let update_data = FeeParametersUpdateData::new(
+            old_fee_parameters,
+            new_fee_parameters,
+            &keypair_authority,
+            &ElGamalKeypair::new_rand().public,
+            &ciphertext_withheld,
+            withheld_amount - 1,
+        )
+        .unwrap();
+        assert!(update_data.verify().is_err());
+
+        // the new fee parameters must be usable
+        let invalid_fee_parameters = FeeParameters {
+            fee_rate: 200,
+            maximum_fee: 5,
+            fee_denominator: 0,
+        };
+        assert!(FeeParametersUpdateData::new(
+            old_fee_parameters,
+            invalid_fee_parameters,
+            &keypair_authority,
+            &keypair_authority.public,
+            &ciphertext_withheld,
+            withheld_amount,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,501 @@
+#[cfg(feature = "prover")]
+use crate::{
+    encryption::{
+        discrete_log::*,
+        elgamal::{ElGamalCiphertext, ElGamalSecretKey},
+    },
+    errors::DecryptionError,
+    instruction::{pad_auditor_pubkeys, split_u64_into_u32, Role, TWO_32},
+    sigma_proofs::validity_proof::AggregatedValidityProof,
+};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::{
+            elgamal::{DecryptHandle, ElGamalPubkey},
+            pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
+        },
+        errors::ProofError,
+        instruction::{decode_field, num_auditors, ProofVersion, Verifiable, MAX_AUDITORS},
+        range_proof::RangeProof,
+    },
+    arrayref::{array_ref, array_refs},
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+#[derive(Clone)]
+#[repr(C)]
+#[cfg(not(target_arch = "bpf"))]
+pub struct MintAmountEncryption {
+    pub commitment: PedersenCommitment,
+    pub dest: DecryptHandle,
+    /// The decryption handles of the auditors, which are zeroed for the unused auditor slots
+    pub auditors: [DecryptHandle; MAX_AUDITORS],
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl MintAmountEncryption {
+    pub fn new(
+        amount: u32,
+        pubkey_dest: &ElGamalPubkey,
+        pubkeys_auditor: &[ElGamalPubkey; MAX_AUDITORS],
+    ) -> (Self, PedersenOpening) {
+        let (commitment, opening) = Pedersen::new(amount);
+        let mut auditors = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, pubkey) in auditors.iter_mut().zip(pubkeys_auditor) {
+            *handle = pubkey.decrypt_handle(&opening);
+        }
+        let mint_amount_encryption = Self {
+            commitment,
+            dest: pubkey_dest.decrypt_handle(&opening),
+            auditors,
+        };
+
+        (mint_amount_encryption, opening)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 2)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 2)];
+        bytes[..32].copy_from_slice(&self.commitment.to_bytes());
+        bytes[32..64].copy_from_slice(&self.dest.to_bytes());
+        for (chunk, auditor) in bytes[64..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 2)];
+        let (commitment, dest, auditors) = array_refs![bytes, 32, 32, 32 * MAX_AUDITORS];
+
+        let commitment =
+            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::NonCanonical)?;
+        let dest = DecryptHandle::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_handles = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, bytes) in auditor_handles.iter_mut().zip(auditors.chunks(32)) {
+            *handle = DecryptHandle::from_bytes(bytes).ok_or(ProofError::NonCanonical)?;
+        }
+
+        Ok(Self {
+            commitment,
+            dest,
+            auditors: auditor_handles,
+        })
+    }
+}
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call MintData::verify(&self)
+/// - The actual program should check that `mint_pubkeys` is consistent with the ElGamal pubkeys
+///   of the destination account and the auditors of the mint
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct MintData {
+    /// The context data needed to verify the proof
+    pub context: MintProofContext,
+
+    /// Zero-knowledge proofs for Mint
+    pub proof: MintProof,
+}
+
+/// The public inputs of a `MintData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct MintProofContext {
+    /// Group encryption of the low 32 bits of the minted amount
+    pub ciphertext_lo: pod::MintAmountEncryption,
+
+    /// Group encryption of the high 32 bits of the minted amount
+    pub ciphertext_hi: pod::MintAmountEncryption,
+
+    /// The public encryption keys associated with the mint: dest and auditors
+    pub mint_pubkeys: pod::MintPubkeys,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl MintData {
+    #[cfg(feature = "prover")]
+    pub fn new(
+        mint_amount: u64,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+    ) -> Result<Self, ProofError> {
+        let padded_pubkeys_auditor = pad_auditor_pubkeys(pubkeys_auditor)?;
+
+        // split and encrypt mint amount
+        let (amount_lo, amount_hi) = split_u64_into_u32(mint_amount);
+
+        let (ciphertext_lo, opening_lo) =
+            MintAmountEncryption::new(amount_lo, pubkey_dest, &padded_pubkeys_auditor);
+        let (ciphertext_hi, opening_hi) =
+            MintAmountEncryption::new(amount_hi, pubkey_dest, &padded_pubkeys_auditor);
+
+        // generate transcript and append all public inputs
+        let pod_mint_pubkeys = pod::MintPubkeys::new(pubkey_dest, &padded_pubkeys_auditor);
+        let pod_ciphertext_lo: pod::MintAmountEncryption = ciphertext_lo.into();
+        let pod_ciphertext_hi: pod::MintAmountEncryption = ciphertext_hi.into();
+
+        let mut transcript =
+            MintProof::transcript_new(&pod_mint_pubkeys, &pod_ciphertext_lo, &pod_ciphertext_hi);
+
+        let proof = MintProof::new(
+            (amount_lo, amount_hi),
+            (pubkey_dest, pubkeys_auditor),
+            (&opening_lo, &opening_hi),
+            &mut transcript,
+        )?;
+
+        Ok(Self {
+            context: MintProofContext {
+                ciphertext_lo: pod_ciphertext_lo,
+                ciphertext_hi: pod_ciphertext_hi,
+                mint_pubkeys: pod_mint_pubkeys,
+            },
+            proof,
+        })
+    }
+
+    /// Extracts the lo ciphertexts associated with a mint data
+    #[cfg(feature = "prover")]
+    fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_lo: MintAmountEncryption = self
+            .context
+            .ciphertext_lo
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let handle_lo = match role {
+            Role::Source => return Err(DecryptionError::WrongRole),
+            Role::Dest => ciphertext_lo.dest,
+            Role::Auditor(index) => *ciphertext_lo
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
+        };
+
+        Ok(ElGamalCiphertext {
+            commitment: ciphertext_lo.commitment,
+            handle: handle_lo,
+        })
+    }
+
+    /// Extracts the hi ciphertexts associated with a mint data
+    #[cfg(feature = "prover")]
+    fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_hi: MintAmountEncryption = self
+            .context
+            .ciphertext_hi
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let handle_hi = match role {
+            Role::Source => return Err(DecryptionError::WrongRole),
+            Role::Dest => ciphertext_hi.dest,
+            Role::Auditor(index) => *ciphertext_hi
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
+        };
+
+        Ok(ElGamalCiphertext {
+            commitment: ciphertext_hi.commitment,
+            handle: handle_hi,
+        })
+    }
+
+    /// Decrypts minted amount from mint data
+    ///
+    /// Both halves of the amount are always decrypted before they are combined; the only branch
+    /// is on whether the decryption succeeded as a whole. The discrete log lookups are not
+    /// constant time.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount(
+        &self,
+        role: Role,
+        sk: &ElGamalSecretKey,
+    ) -> Result<u64, DecryptionError> {
+        let ciphertext_lo = self.ciphertext_lo(role)?;
+        let ciphertext_hi = self.ciphertext_hi(role)?;
+
+        let amount_lo = ciphertext_lo.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+        let amount_hi = ciphertext_hi.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+
+        let amount = amount_lo.and_then(|amount_lo| {
+            amount_hi.map(|amount_hi| (amount_lo as u64) + (TWO_32 * amount_hi as u64))
+        });
+        Option::<u64>::from(amount).ok_or(DecryptionError::DiscreteLogNotFound)
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for MintData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        // generate transcript and append all public inputs
+        let mut transcript = MintProof::transcript_new(
+            &self.context.mint_pubkeys,
+            &self.context.ciphertext_lo,
+            &self.context.ciphertext_hi,
+        );
+
+        let ciphertext_lo = decode_field(self.context.ciphertext_lo, "ciphertext_lo")?;
+        let ciphertext_hi = decode_field(self.context.ciphertext_hi, "ciphertext_hi")?;
+        let mint_pubkeys = decode_field(self.context.mint_pubkeys, "mint_pubkeys")?;
+
+        self.proof.verify_batched(
+            &ciphertext_lo,
+            &ciphertext_hi,
+            &mint_pubkeys,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct MintProof {
+    /// Version of the proof format
+    pub version: u8,
+
+    /// Associated ciphertext validity proof
+    pub validity_proof: pod::AggregatedValidityProof,
+
+    /// Associated range proof
+    pub range_proof: pod::RangeProof64,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl MintProof {
+    pub fn transcript_new(
+        mint_pubkeys: &pod::MintPubkeys,
+        ciphertext_lo: &pod::MintAmountEncryption,
+        ciphertext_hi: &pod::MintAmountEncryption,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"mint-proof-v1");
+
+        transcript.append_message(b"mint-pubkeys", &mint_pubkeys.0);
+        transcript.append_message(b"ciphertext-lo", &ciphertext_lo.0);
+        transcript.append_message(b"ciphertext-hi", &ciphertext_hi.0);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        (mint_amount_lo, mint_amount_hi): (u32, u32),
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        (opening_lo, opening_hi): (&PedersenOpening, &PedersenOpening),
+        transcript: &mut Transcript,
+    ) -> Result<Self, ProofError> {
+        // generate ciphertext validity proof for the destination and auditor handles
+        let pubkeys_validity: Vec<&ElGamalPubkey> = std::iter::once(pubkey_dest)
+            .chain(pubkeys_auditor)
+            .collect();
+        let validity_proof = AggregatedValidityProof::new(
+            &pubkeys_validity,
+            (mint_amount_lo, mint_amount_hi),
+            (opening_lo, opening_hi),
+            transcript,
+        );
+
+        // generate the range proof
+        let range_proof = RangeProof::new(
+            vec![mint_amount_lo as u64, mint_amount_hi as u64],
+            vec![32, 32],
+            vec![opening_lo, opening_hi],
+            transcript,
+        )?;
+
+        Ok(Self {
+            version: Self::VERSION,
+            validity_proof: validity_proof.try_into()?,
+            range_proof: range_proof.try_into()?,
+        })
+    }
+
+    pub fn verify_batched(
+        &self,
+        ciphertext_lo: &MintAmountEncryption,
+        ciphertext_hi: &MintAmountEncryption,
+        mint_pubkeys: &MintPubkeys,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let range_proof: RangeProof = self.range_proof.try_into()?;
+
+        let num_auditors = num_auditors(
+            &mint_pubkeys.auditors,
+            &[&ciphertext_lo.auditors, &ciphertext_hi.auditors],
+        )?;
+        let aggregated_validity_proof = self.validity_proof.to_proof(num_auditors + 1)?;
+
+        // verify validity proof
+        let pubkeys: Vec<&ElGamalPubkey> = std::iter::once(&mint_pubkeys.dest)
+            .chain(&mint_pubkeys.auditors[..num_auditors])
+            .collect();
+        let handles_lo: Vec<&DecryptHandle> = std::iter::once(&ciphertext_lo.dest)
+            .chain(&ciphertext_lo.auditors[..num_auditors])
+            .collect();
+        let handles_hi: Vec<&DecryptHandle> = std::iter::once(&ciphertext_hi.dest)
+            .chain(&ciphertext_hi.auditors[..num_auditors])
+            .collect();
+        aggregated_validity_proof.verify_batched(
+            &pubkeys,
+            (&ciphertext_lo.commitment, &ciphertext_hi.commitment),
+            (&handles_lo, &handles_hi),
+            transcript,
+            batch,
+        )?;
+
+        // verify range proof
+        range_proof.verify_batched(
+            vec![&ciphertext_lo.commitment, &ciphertext_hi.commitment],
+            vec![32_usize, 32_usize],
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The ElGamal public keys needed for a mint
+#[derive(Clone)]
+#[repr(C)]
+#[cfg(not(target_arch = "bpf"))]
+pub struct MintPubkeys {
+    pub dest: ElGamalPubkey,
+    /// The pubkeys of the auditors, followed by zeroed pubkeys for the unused auditor slots
+    pub auditors: [ElGamalPubkey; MAX_AUDITORS],
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl MintPubkeys {
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 1)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 1)];
+        bytes[..32].copy_from_slice(&self.dest.to_bytes());
+        for (chunk, auditor) in bytes[32..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 1)];
+        let (dest, auditors) = array_refs![bytes, 32, 32 * MAX_AUDITORS];
+
+        let dest = ElGamalPubkey::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_pubkeys = [ElGamalPubkey::default(); MAX_AUDITORS];
+        for (pubkey, bytes) in auditor_pubkeys.iter_mut().zip(auditors.chunks(32)) {
+            *pubkey = ElGamalPubkey::from_bytes(array_ref![bytes, 0, 32])
+                .ok_or(ProofError::NonCanonical)?;
+        }
+
+        Ok(Self {
+            dest,
+            auditors: auditor_pubkeys,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl pod::MintPubkeys {
+    pub fn new(dest: &ElGamalPubkey, auditors: &[ElGamalPubkey; MAX_AUDITORS]) -> Self {
+        Self(
+            MintPubkeys {
+                dest: *dest,
+                auditors: *auditors,
+            }
+            .to_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::encryption::elgamal::ElGamalKeypair};
+
+    #[test]
+    fn test_mint_correctness() {
+        let dest_keypair = ElGamalKeypair::new_rand();
+        let auditor_keypair = ElGamalKeypair::new_rand();
+
+        let mint_amount: u64 = (77 << 32) + 55;
+
+        let mint_data = MintData::new(
+            mint_amount,
+            (&dest_keypair.public, &[auditor_keypair.public]),
+        )
+        .unwrap();
+        assert!(mint_data.verify().is_ok());
+
+        assert_eq!(
+            mint_data
+                .decrypt_amount(Role::Dest, &dest_keypair.secret)
+                .unwrap(),
+            mint_amount,
+        );
+        assert_eq!(
+            mint_data
+                .decrypt_amount(Role::Auditor(0), &auditor_keypair.secret)
+                .unwrap(),
+            mint_amount,
+        );
+        assert_eq!(
+            mint_data.decrypt_amount(Role::Source, &dest_keypair.secret),
+            Err(DecryptionError::WrongRole),
+        );
+
+        // a mint can have no auditor
+        let mint_data = MintData::new(mint_amount, (&dest_keypair.public, &[])).unwrap();
+        assert!(mint_data.verify().is_ok());
+
+        // the destination handle cannot be replaced
+        let mut mint_data = MintData::new(mint_amount, (&dest_keypair.public, &[])).unwrap();
+        let mut ciphertext_lo: MintAmountEncryption =
+            mint_data.context.ciphertext_lo.try_into().unwrap();
+        ciphertext_lo.dest = auditor_keypair
+            .public
+            .decrypt_handle(&PedersenOpening::new_rand());
+        mint_data.context.ciphertext_lo = ciphertext_lo.into();
+        assert!(mint_data.verify().is_err());
+    }
+}
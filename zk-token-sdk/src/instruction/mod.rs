@@ -1,31 +1,141 @@
+pub mod burn;
+pub mod ciphertext_ciphertext_equality;
+pub mod ciphertext_commitment_equality;
 pub mod close_account;
+pub mod fee_parameters_update;
+pub mod mint;
+pub mod pubkey_validity;
+pub mod rotate_auditor;
 pub mod transfer;
+pub mod transfer_batch;
 pub mod transfer_with_fee;
 pub mod withdraw;
+pub mod withdraw_withheld;
 
+#[cfg(not(target_arch = "bpf"))]
+pub use crate::batch::MultiscalarMulBatch;
+pub use {
+    crate::zk_token_elgamal::pod::MAX_AUDITORS,
+    burn::{BurnData, BurnProofContext},
+    ciphertext_ciphertext_equality::{
+        CiphertextCiphertextEqualityData, CiphertextCiphertextEqualityProofContext,
+    },
+    ciphertext_commitment_equality::{
+        CiphertextCommitmentEqualityData, CiphertextCommitmentEqualityProofContext,
+    },
+    close_account::{CloseAccountData, CloseAccountProofContext},
+    fee_parameters_update::{FeeParametersUpdateData, FeeParametersUpdateProofContext},
+    mint::{MintData, MintProofContext},
+    pubkey_validity::{PubkeyValidityData, PubkeyValidityProofContext},
+    rotate_auditor::{RotateAuditorData, RotateAuditorProofContext},
+    transfer::{TransferData, TransferProofContext},
+    transfer_batch::TransferBatchData,
+    transfer_with_fee::{TransferWithFeeData, TransferWithFeeProofContext},
+    withdraw::{WithdrawData, WithdrawProofContext},
+    withdraw_withheld::{WithdrawWithheldTokensData, WithdrawWithheldTokensProofContext},
+};
 #[cfg(not(target_arch = "bpf"))]
 use {
     crate::{
         encryption::{
-            elgamal::ElGamalCiphertext,
+            elgamal::{DecryptHandle, ElGamalCiphertext, ElGamalPubkey},
             pedersen::{PedersenCommitment, PedersenOpening},
         },
         errors::ProofError,
     },
-    curve25519_dalek::scalar::Scalar,
-};
-pub use {
-    close_account::CloseAccountData, transfer::TransferData,
-    transfer_with_fee::TransferWithFeeData, withdraw::WithdrawData,
+    curve25519_dalek::{scalar::Scalar, traits::IsIdentity},
 };
 
 /// Constant for 2^32
 #[cfg(not(target_arch = "bpf"))]
 const TWO_32: u64 = 4294967296;
 
+/// Constant for 2^48
+#[cfg(not(target_arch = "bpf"))]
+const TWO_48: u64 = 281474976710656;
+
 #[cfg(not(target_arch = "bpf"))]
 pub trait Verifiable {
-    fn verify(&self) -> Result<(), ProofError>;
+    fn verify(&self) -> Result<(), ProofError> {
+        verify_batch(&[self])
+    }
+
+    /// Checks the proof data, except for the algebraic relations of its proofs, which are added
+    /// to `batch` to be checked together with those of other proof data
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError>;
+}
+
+/// Versioning of the proof formats
+///
+/// The version of a proof is stored in its `version` field and embedded in the label of its
+/// transcript. A proof of any other version than the current one is rejected, so a change to the
+/// format of a proof is made by bumping its version, which an on-chain program can gate on.
+pub trait ProofVersion {
+    /// The version of the proofs that are generated and verified by this build
+    const VERSION: u8;
+
+    fn version(&self) -> u8;
+
+    #[cfg(not(target_arch = "bpf"))]
+    fn check_version(&self) -> Result<(), ProofError> {
+        if self.version() == Self::VERSION {
+            Ok(())
+        } else {
+            Err(ProofError::UnsupportedVersion)
+        }
+    }
+}
+
+macro_rules! impl_proof_version {
+    ($($proof:ty => $version:literal),* $(,)?) => {
+        $(
+            impl ProofVersion for $proof {
+                const VERSION: u8 = $version;
+
+                fn version(&self) -> u8 {
+                    self.version
+                }
+            }
+        )*
+    };
+}
+
+impl_proof_version!(
+    close_account::CloseAccountProof => 1,
+    withdraw::WithdrawProof => 1,
+    transfer::TransferProof => 1,
+    transfer_with_fee::TransferWithFeeProof => 2,
+    withdraw_withheld::WithdrawWithheldTokensProof => 1,
+    mint::MintProof => 1,
+    burn::BurnProof => 1,
+    rotate_auditor::RotateAuditorProof => 1,
+    TransferBatchData => 1,
+    pubkey_validity::PubkeyValidityProof => 1,
+    ciphertext_ciphertext_equality::CiphertextCiphertextEqualityProof => 1,
+    ciphertext_commitment_equality::CiphertextCommitmentEqualityProof => 1,
+    fee_parameters_update::FeeParametersUpdateProof => 1,
+);
+
+/// Verifies several proof data with a single multiscalar multiplication, which is considerably
+/// cheaper than verifying each of them on its own.
+///
+/// If the batch fails to verify, the returned error identifies the kind of sub-proof that failed,
+/// such as `ProofError::RangeProofFailed`, but not the invalid proof data; verify each of them on
+/// its own to find it.
+#[cfg(not(target_arch = "bpf"))]
+pub fn verify_batch<T: Verifiable + ?Sized>(proofs: &[&T]) -> Result<(), ProofError> {
+    let mut batch = MultiscalarMulBatch::new();
+    for proof in proofs {
+        proof.verify_batched(&mut batch)?;
+    }
+
+    batch.check()
+}
+
+/// Decodes a field of proof data, naming the field in the error if it is malformed
+#[cfg(not(target_arch = "bpf"))]
+fn decode_field<P: TryInto<T>, T>(pod: P, field: &'static str) -> Result<T, ProofError> {
+    pod.try_into().map_err(|_| ProofError::MalformedPod(field))
 }
 
 #[cfg(not(target_arch = "bpf"))]
@@ -33,7 +143,109 @@ pub trait Verifiable {
 pub enum Role {
     Source,
     Dest,
-    Auditor,
+    /// The auditor at the given index of the transfer pubkeys
+    Auditor(usize),
+    /// The fee collector of a transfer with fee, which can only decrypt the fee
+    FeeCollector,
+}
+
+/// Pads the auditor pubkeys of a transfer to `MAX_AUDITORS` with zeroed pubkeys, which mark the
+/// unused auditor slots
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+fn pad_auditor_pubkeys(
+    pubkeys: &[ElGamalPubkey],
+) -> Result<[ElGamalPubkey; MAX_AUDITORS], ProofError> {
+    if pubkeys.len() > MAX_AUDITORS
+        || pubkeys
+            .iter()
+            .any(|pubkey| pubkey.get_point().is_identity())
+    {
+        return Err(ProofError::Generation);
+    }
+
+    let mut padded_pubkeys = [ElGamalPubkey::default(); MAX_AUDITORS];
+    padded_pubkeys[..pubkeys.len()].copy_from_slice(pubkeys);
+    Ok(padded_pubkeys)
+}
+
+/// Returns the number of auditors of a transfer, checking that the unused auditor slots come last
+/// and that the decryption handles of the unused slots are zeroed
+#[cfg(not(target_arch = "bpf"))]
+fn num_auditors(
+    pubkeys: &[ElGamalPubkey; MAX_AUDITORS],
+    handles: &[&[DecryptHandle; MAX_AUDITORS]],
+) -> Result<usize, ProofError> {
+    let num_auditors = pubkeys
+        .iter()
+        .take_while(|pubkey| !pubkey.get_point().is_identity())
+        .count();
+
+    let unused_pubkeys = pubkeys[num_auditors..]
+        .iter()
+        .all(|pubkey| pubkey.get_point().is_identity());
+    let unused_handles = handles.iter().all(|handles| {
+        handles[num_auditors..]
+            .iter()
+            .all(|handle| handle.get_point().is_identity())
+    });
+
+    if unused_pubkeys && unused_handles {
+        Ok(num_auditors)
+    } else {
+        Err(ProofError::Verification)
+    }
+}
+
+/// The split of a 64-bit amount into the low and high limbs that are encrypted separately
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AmountSplit {
+    /// A 32-bit low limb and a 32-bit high limb
+    Lo32Hi32,
+    /// A 48-bit low limb and a 16-bit high limb
+    Lo48Hi16,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl AmountSplit {
+    /// Returns the bit lengths of the low and high limbs
+    pub fn bit_lengths(self) -> (usize, usize) {
+        match self {
+            Self::Lo32Hi32 => (32, 32),
+            Self::Lo48Hi16 => (48, 16),
+        }
+    }
+
+    /// Splits `amount` into its low and high limbs
+    pub fn split(self, amount: u64) -> (u64, u64) {
+        match self {
+            Self::Lo32Hi32 => {
+                let (lo, hi) = split_u64_into_u32(amount);
+                (lo as u64, hi as u64)
+            }
+            Self::Lo48Hi16 => {
+                let (lo, hi) = split_u64_into_u48_u16(amount);
+                (lo, hi as u64)
+            }
+        }
+    }
+
+    /// Combines the low and high limbs of an amount
+    pub fn combine(self, lo: u64, hi: u64) -> u64 {
+        let (lo_bit_length, _) = self.bit_lengths();
+        lo + (hi << lo_bit_length)
+    }
+
+    #[cfg(feature = "prover")]
+    fn combine_ciphertexts(
+        self,
+        ciphertext_lo: &ElGamalCiphertext,
+        ciphertext_hi: &ElGamalCiphertext,
+    ) -> ElGamalCiphertext {
+        match self {
+            Self::Lo32Hi32 => combine_u32_ciphertexts(ciphertext_lo, ciphertext_hi),
+            Self::Lo48Hi16 => combine_u48_ciphertexts(ciphertext_lo, ciphertext_hi),
+        }
+    }
 }
 
 /// Split u64 number into two u32 numbers
@@ -68,3 +280,111 @@ pub fn combine_u32_openings(
 ) -> PedersenOpening {
     opening_lo + opening_hi * &Scalar::from(TWO_32)
 }
+
+/// Split u64 number into a 48-bit number and a u16 number
+#[cfg(not(target_arch = "bpf"))]
+pub fn split_u64_into_u48_u16(amount: u64) -> (u64, u16) {
+    let lo = amount & (TWO_48 - 1);
+    let hi = (amount >> 48) as u16;
+
+    (lo, hi)
+}
+
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+fn combine_u48_ciphertexts(
+    ciphertext_lo: &ElGamalCiphertext,
+    ciphertext_hi: &ElGamalCiphertext,
+) -> ElGamalCiphertext {
+    ciphertext_lo + &(ciphertext_hi * &Scalar::from(TWO_48))
+}
+
+#[cfg(not(target_arch = "bpf"))]
+pub fn combine_u48_commitments(
+    comm_lo: &PedersenCommitment,
+    comm_hi: &PedersenCommitment,
+) -> PedersenCommitment {
+    comm_lo + comm_hi * &Scalar::from(TWO_48)
+}
+
+#[cfg(not(target_arch = "bpf"))]
+pub fn combine_u48_openings(
+    opening_lo: &PedersenOpening,
+    opening_hi: &PedersenOpening,
+) -> PedersenOpening {
+    opening_lo + opening_hi * &Scalar::from(TWO_48)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            encryption::elgamal::ElGamalKeypair,
+            instruction::transfer_with_fee::{FeeParameters, FEE_DENOMINATOR_BASIS_POINTS},
+        },
+    };
+
+    #[test]
+    fn test_verify_batch() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let dest_pk = ElGamalKeypair::new_rand().public;
+        let auditor_pk = ElGamalKeypair::new_rand().public;
+        let fee_collector_pk = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 120;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        let transfer_data = TransferData::new(
+            55,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &[auditor_pk]),
+        )
+        .unwrap();
+
+        let fee_parameters = FeeParameters {
+            fee_rate: 100,
+            maximum_fee: 3,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
+        };
+        let transfer_with_fee_data = TransferWithFeeData::new(
+            100,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &[auditor_pk]),
+            fee_parameters,
+            &fee_collector_pk,
+        )
+        .unwrap();
+
+        let withdraw_data = WithdrawData::new(
+            20,
+            &source_keypair,
+            spendable_balance,
+            &spendable_ciphertext,
+        )
+        .unwrap();
+
+        assert!(verify_batch::<dyn Verifiable>(&[
+            &transfer_data,
+            &transfer_with_fee_data,
+            &withdraw_data,
+        ])
+        .is_ok());
+
+        // a single invalid proof data fails the whole batch
+        let wrong_balance: u64 = 99;
+        let invalid_withdraw_data =
+            WithdrawData::new(20, &source_keypair, wrong_balance, &spendable_ciphertext).unwrap();
+        assert!(invalid_withdraw_data.verify().is_err());
+
+        assert_eq!(
+            verify_batch::<dyn Verifiable>(&[
+                &transfer_data,
+                &transfer_with_fee_data,
+                &invalid_withdraw_data,
+            ]),
+            Err(ProofError::EqualityProofFailed)
+        );
+    }
+}
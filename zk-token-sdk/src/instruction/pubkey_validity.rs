@@ -0,0 +1,160 @@
+#[cfg(feature = "prover")]
+use crate::encryption::elgamal::ElGamalKeypair;
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::ElGamalPubkey,
+        errors::ProofError,
+        instruction::{decode_field, ProofVersion, Verifiable},
+        sigma_proofs::pubkey_proof::PubkeyValidityProof as PubkeySigmaProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call PubkeyValidityData::verify(&self)
+/// - The actual program should check that `pubkey` is the ElGamal pubkey that is being registered
+///   for the confidential token account
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct PubkeyValidityData {
+    /// The context data needed to verify the proof
+    pub context: PubkeyValidityProofContext,
+
+    /// Proof that the pubkey is well-formed and that its secret key is known
+    pub proof: PubkeyValidityProof, // 65 bytes
+}
+
+/// The public inputs of a `PubkeyValidityData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct PubkeyValidityProofContext {
+    /// The ElGamal pubkey that is proved to be valid
+    pub pubkey: pod::ElGamalPubkey, // 32 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl PubkeyValidityData {
+    #[cfg(feature = "prover")]
+    pub fn new(keypair: &ElGamalKeypair) -> Result<Self, ProofError> {
+        let pod_pubkey = pod::ElGamalPubkey((&keypair.public).to_bytes());
+
+        let mut transcript = PubkeyValidityProof::transcript_new(&pod_pubkey);
+
+        let proof = PubkeyValidityProof::new(keypair, &mut transcript);
+
+        Ok(Self {
+            context: PubkeyValidityProofContext { pubkey: pod_pubkey },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for PubkeyValidityData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript = PubkeyValidityProof::transcript_new(&self.context.pubkey);
+
+        let pubkey = decode_field(self.context.pubkey, "pubkey")?;
+        self.proof.verify_batched(&pubkey, &mut transcript, batch)
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that an ElGamal pubkey
+/// is well-formed and that its secret key is known
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+#[allow(non_snake_case)]
+pub struct PubkeyValidityProof {
+    pub version: u8,
+    pub proof: pod::PubkeyValidityProof,
+}
+
+#[allow(non_snake_case)]
+#[cfg(not(target_arch = "bpf"))]
+impl PubkeyValidityProof {
+    pub fn transcript_new(pubkey: &pod::ElGamalPubkey) -> Transcript {
+        let mut transcript = Transcript::new(b"PubkeyValidityProof-v1");
+
+        transcript.append_pubkey(b"pubkey", pubkey);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(keypair: &ElGamalKeypair, transcript: &mut Transcript) -> Self {
+        let proof = PubkeySigmaProof::new(keypair, transcript);
+
+        PubkeyValidityProof {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        pubkey: &ElGamalPubkey,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: PubkeySigmaProof = self.proof.try_into()?;
+        proof.verify_batched(pubkey, transcript, batch)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pubkey_validity_correctness() {
+        let keypair = ElGamalKeypair::new_rand();
+
+        let pubkey_validity_data = PubkeyValidityData::new(&keypair).unwrap();
+        assert!(pubkey_validity_data.verify().is_ok());
+
+        // the proof does not carry over to a different pubkey
+        let mut pubkey_validity_data = pubkey_validity_data;
+        pubkey_validity_data.context.pubkey =
+            pod::ElGamalPubkey((&ElGamalKeypair::new_rand().public).to_bytes());
+        assert!(pubkey_validity_data.verify().is_err());
+    }
+}
@@ -0,0 +1,272 @@
+#[cfg(feature = "prover")]
+use crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        errors::ProofError,
+        instruction::{decode_field, ProofVersion, Verifiable},
+        sigma_proofs::equality_proof::CtxtCtxtEqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call RotateAuditorData::verify(&self)
+/// - The actual program should check that `pubkey_old_auditor` is the current auditor pubkey of
+///   the mint and that `ciphertext_old_auditor` is the auditor ciphertext of the balance that is
+///   being re-encrypted
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct RotateAuditorData {
+    /// The context data needed to verify the proof
+    pub context: RotateAuditorProofContext,
+
+    /// Proof that the two ciphertexts encrypt the same amount
+    pub proof: RotateAuditorProof, // 225 bytes
+}
+
+/// The public inputs of a `RotateAuditorData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct RotateAuditorProofContext {
+    /// The ElGamal pubkey of the auditor that is being rotated out
+    pub pubkey_old_auditor: pod::ElGamalPubkey, // 32 bytes
+
+    /// The ElGamal pubkey of the new auditor
+    pub pubkey_new_auditor: pod::ElGamalPubkey, // 32 bytes
+
+    /// The balance encrypted under the old auditor pubkey
+    pub ciphertext_old_auditor: pod::ElGamalCiphertext, // 64 bytes
+
+    /// The balance re-encrypted under the new auditor pubkey
+    pub ciphertext_new_auditor: pod::ElGamalCiphertext, // 64 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl RotateAuditorData {
+    /// Re-encrypts a balance under the new auditor pubkey.
+    ///
+    /// The `amount` is the balance encrypted by `ciphertext_old_auditor`, which the old auditor
+    /// can decrypt locally. The plaintext balance is never included in the proof data.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair_old_auditor: &ElGamalKeypair,
+        pubkey_new_auditor: &ElGamalPubkey,
+        ciphertext_old_auditor: &ElGamalCiphertext,
+        amount: u64,
+    ) -> Result<Self, ProofError> {
+        // re-encrypt the balance under the new auditor pubkey
+        let opening_new_auditor = PedersenOpening::new_rand();
+        let ciphertext_new_auditor = pubkey_new_auditor.encrypt_with(amount, &opening_new_auditor);
+
+        let pod_pubkey_old_auditor = pod::ElGamalPubkey(keypair_old_auditor.public.to_bytes());
+        let pod_pubkey_new_auditor = pod::ElGamalPubkey(pubkey_new_auditor.to_bytes());
+        let pod_ciphertext_old_auditor: pod::ElGamalCiphertext = (*ciphertext_old_auditor).into();
+        let pod_ciphertext_new_auditor: pod::ElGamalCiphertext = ciphertext_new_auditor.into();
+
+        let mut transcript = RotateAuditorProof::transcript_new(
+            &pod_pubkey_old_auditor,
+            &pod_pubkey_new_auditor,
+            &pod_ciphertext_old_auditor,
+            &pod_ciphertext_new_auditor,
+        );
+
+        let proof = RotateAuditorProof::new(
+            keypair_old_auditor,
+            pubkey_new_auditor,
+            ciphertext_old_auditor,
+            amount,
+            &opening_new_auditor,
+            &mut transcript,
+        );
+
+        Ok(Self {
+            context: RotateAuditorProofContext {
+                pubkey_old_auditor: pod_pubkey_old_auditor,
+                pubkey_new_auditor: pod_pubkey_new_auditor,
+                ciphertext_old_auditor: pod_ciphertext_old_auditor,
+                ciphertext_new_auditor: pod_ciphertext_new_auditor,
+            },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for RotateAuditorData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript = RotateAuditorProof::transcript_new(
+            &self.context.pubkey_old_auditor,
+            &self.context.pubkey_new_auditor,
+            &self.context.ciphertext_old_auditor,
+            &self.context.ciphertext_new_auditor,
+        );
+
+        let pubkey_old_auditor =
+            decode_field(self.context.pubkey_old_auditor, "pubkey_old_auditor")?;
+        let pubkey_new_auditor =
+            decode_field(self.context.pubkey_new_auditor, "pubkey_new_auditor")?;
+        let ciphertext_old_auditor = decode_field(
+            self.context.ciphertext_old_auditor,
+            "ciphertext_old_auditor",
+        )?;
+        let ciphertext_new_auditor = decode_field(
+            self.context.ciphertext_new_auditor,
+            "ciphertext_new_auditor",
+        )?;
+
+        self.proof.verify_batched(
+            &pubkey_old_auditor,
+            &pubkey_new_auditor,
+            &ciphertext_old_auditor,
+            &ciphertext_new_auditor,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that a balance is
+/// re-encrypted under the new auditor pubkey without change
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct RotateAuditorProof {
+    pub version: u8,
+    pub proof: pod::CtxtCtxtEqualityProof,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl RotateAuditorProof {
+    pub fn transcript_new(
+        pubkey_old_auditor: &pod::ElGamalPubkey,
+        pubkey_new_auditor: &pod::ElGamalPubkey,
+        ciphertext_old_auditor: &pod::ElGamalCiphertext,
+        ciphertext_new_auditor: &pod::ElGamalCiphertext,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"RotateAuditorProof-v1");
+
+        transcript.append_pubkey(b"pubkey-old-auditor", pubkey_old_auditor);
+        transcript.append_pubkey(b"pubkey-new-auditor", pubkey_new_auditor);
+
+        transcript.append_ciphertext(b"ciphertext-old-auditor", ciphertext_old_auditor);
+        transcript.append_ciphertext(b"ciphertext-new-auditor", ciphertext_new_auditor);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair_old_auditor: &ElGamalKeypair,
+        pubkey_new_auditor: &ElGamalPubkey,
+        ciphertext_old_auditor: &ElGamalCiphertext,
+        amount: u64,
+        opening_new_auditor: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let proof = CtxtCtxtEqualityProof::new(
+            keypair_old_auditor,
+            pubkey_new_auditor,
+            ciphertext_old_auditor,
+            amount,
+            opening_new_auditor,
+            transcript,
+        );
+
+        Self {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        pubkey_old_auditor: &ElGamalPubkey,
+        pubkey_new_auditor: &ElGamalPubkey,
+        ciphertext_old_auditor: &ElGamalCiphertext,
+        ciphertext_new_auditor: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: CtxtCtxtEqualityProof = self.proof.try_into()?;
+        proof.verify_batched(
+            pubkey_old_auditor,
+            pubkey_new_auditor,
+            ciphertext_old_auditor,
+            ciphertext_new_auditor,
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rotate_auditor_correctness() {
+        let keypair_old_auditor = ElGamalKeypair::new_rand();
+        let pubkey_new_auditor = ElGamalKeypair::new_rand().public;
+
+        let amount: u64 = 55;
+        let ciphertext_old_auditor = keypair_old_auditor.public.encrypt(amount);
+
+        let rotate_auditor_data = RotateAuditorData::new(
+            &keypair_old_auditor,
+            &pubkey_new_auditor,
+            &ciphertext_old_auditor,
+            amount,
+        )
+        .unwrap();
+        assert!(rotate_auditor_data.verify().is_ok());
+
+        // the balance re-encrypted under the new auditor pubkey must match the original balance
+        let rotate_auditor_data = RotateAuditorData::new(
+            &keypair_old_auditor,
+            &pubkey_new_auditor,
+            &ciphertext_old_auditor,
+            77,
+        )
+        .unwrap();
+        assert!(rotate_auditor_data.verify().is_err());
+    }
+}
@@ -1,3 +1,12 @@
+#[cfg(feature = "prover")]
+use crate::{
+    encryption::{
+        discrete_log::*,
+        elgamal::{ElGamalKeypair, ElGamalSecretKey},
+    },
+    instruction::pad_auditor_pubkeys,
+    sigma_proofs::validity_proof::AggregatedValidityProof,
+};
 use {
     crate::zk_token_elgamal::pod,
     bytemuck::{Pod, Zeroable},
@@ -5,20 +14,21 @@ use {
 #[cfg(not(target_arch = "bpf"))]
 use {
     crate::{
+        batch::MultiscalarMulBatch,
         encryption::{
-            discrete_log::*,
-            elgamal::{
-                DecryptHandle, ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey,
-            },
+            elgamal::{DecryptHandle, ElGamalCiphertext, ElGamalPubkey},
             pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
         },
-        errors::ProofError,
-        instruction::{combine_u32_ciphertexts, split_u64_into_u32, Role, Verifiable, TWO_32},
+        errors::{DecryptionError, ProofError},
+        instruction::{
+            decode_field, num_auditors, AmountSplit, ProofVersion, Role, Verifiable, MAX_AUDITORS,
+        },
         range_proof::RangeProof,
-        sigma_proofs::{equality_proof::EqualityProof, validity_proof::AggregatedValidityProof},
+        sigma_proofs::equality_proof::EqualityProof,
         transcript::TranscriptProtocol,
     },
     arrayref::{array_ref, array_refs},
+    curve25519_dalek::scalar::Scalar,
     merlin::Transcript,
     std::convert::TryInto,
 };
@@ -30,98 +40,158 @@ pub struct TransferAmountEncryption {
     pub commitment: PedersenCommitment,
     pub source: DecryptHandle,
     pub dest: DecryptHandle,
-    pub auditor: DecryptHandle,
+    /// The decryption handles of the auditors, which are zeroed for the unused auditor slots
+    pub auditors: [DecryptHandle; MAX_AUDITORS],
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl TransferAmountEncryption {
-    pub fn new(
-        amount: u32,
+    pub fn new<T: Into<Scalar>>(
+        amount: T,
         pubkey_source: &ElGamalPubkey,
         pubkey_dest: &ElGamalPubkey,
-        pubkey_auditor: &ElGamalPubkey,
+        pubkeys_auditor: &[ElGamalPubkey; MAX_AUDITORS],
     ) -> (Self, PedersenOpening) {
         let (commitment, opening) = Pedersen::new(amount);
+        let mut auditors = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, pubkey) in auditors.iter_mut().zip(pubkeys_auditor) {
+            *handle = pubkey.decrypt_handle(&opening);
+        }
         let transfer_amount_encryption = Self {
             commitment,
             source: pubkey_source.decrypt_handle(&opening),
             dest: pubkey_dest.decrypt_handle(&opening),
-            auditor: pubkey_auditor.decrypt_handle(&opening),
+            auditors,
         };
 
         (transfer_amount_encryption, opening)
     }
 
-    pub fn to_bytes(&self) -> [u8; 128] {
-        let mut bytes = [0u8; 128];
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 3)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 3)];
         bytes[..32].copy_from_slice(&self.commitment.to_bytes());
         bytes[32..64].copy_from_slice(&self.source.to_bytes());
         bytes[64..96].copy_from_slice(&self.dest.to_bytes());
-        bytes[96..128].copy_from_slice(&self.auditor.to_bytes());
+        for (chunk, auditor) in bytes[96..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
         bytes
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
-        let bytes = array_ref![bytes, 0, 128];
-        let (commitment, source, dest, auditor) = array_refs![bytes, 32, 32, 32, 32];
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 3)];
+        let (commitment, source, dest, auditors) =
+            array_refs![bytes, 32, 32, 32, 32 * MAX_AUDITORS];
 
         let commitment =
-            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::Verification)?;
-        let source = DecryptHandle::from_bytes(source).ok_or(ProofError::Verification)?;
-        let dest = DecryptHandle::from_bytes(dest).ok_or(ProofError::Verification)?;
-        let auditor = DecryptHandle::from_bytes(auditor).ok_or(ProofError::Verification)?;
+            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::NonCanonical)?;
+        let source = DecryptHandle::from_bytes(source).ok_or(ProofError::NonCanonical)?;
+        let dest = DecryptHandle::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_handles = [DecryptHandle::default(); MAX_AUDITORS];
+        for (handle, bytes) in auditor_handles.iter_mut().zip(auditors.chunks(32)) {
+            *handle = DecryptHandle::from_bytes(bytes).ok_or(ProofError::NonCanonical)?;
+        }
 
         Ok(Self {
             commitment,
             source,
             dest,
-            auditor,
+            auditors: auditor_handles,
         })
     }
 }
 
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 pub struct TransferData {
+    /// The context data needed to verify the proof
+    pub context: TransferProofContext,
+
+    /// Zero-knowledge proofs for Transfer
+    pub proof: TransferProof,
+}
+
+/// The public inputs of a `TransferData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct TransferProofContext {
     /// Group encryption of the low 32 bits of the transfer amount
     pub ciphertext_lo: pod::TransferAmountEncryption,
 
     /// Group encryption of the high 32 bits of the transfer amount
     pub ciphertext_hi: pod::TransferAmountEncryption,
 
-    /// The public encryption keys associated with the transfer: source, dest, and auditor
+    /// The public encryption keys associated with the transfer: source, dest, and auditors
     pub transfer_pubkeys: pod::TransferPubkeys,
 
     /// The final spendable ciphertext after the transfer
     pub ciphertext_new_source: pod::ElGamalCiphertext,
 
-    /// Zero-knowledge proofs for Transfer
-    pub proof: TransferProof,
+    /// The split of the transfer amount into `ciphertext_lo` and `ciphertext_hi`
+    pub amount_split: pod::AmountSplit,
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl TransferData {
-    #[allow(clippy::too_many_arguments)]
+    /// Creates transfer data with the transfer amount split into 32-bit low and high limbs
+    #[cfg(feature = "prover")]
     pub fn new(
         transfer_amount: u64,
         (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
         keypair_source: &ElGamalKeypair,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+    ) -> Result<Self, ProofError> {
+        Self::new_with_amount_split(
+            transfer_amount,
+            (spendable_balance, ciphertext_old_source),
+            keypair_source,
+            (pubkey_dest, pubkeys_auditor),
+            AmountSplit::Lo32Hi32,
+        )
+    }
+
+    /// Creates transfer data with the transfer amount split into limbs according to
+    /// `amount_split`
+    #[cfg(feature = "prover")]
+    pub fn new_with_amount_split(
+        transfer_amount: u64,
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        amount_split: AmountSplit,
     ) -> Result<Self, ProofError> {
+        let padded_pubkeys_auditor = pad_auditor_pubkeys(pubkeys_auditor)?;
+
         // split and encrypt transfer amount
-        let (amount_lo, amount_hi) = split_u64_into_u32(transfer_amount);
+        let (amount_lo, amount_hi) = amount_split.split(transfer_amount);
 
         let (ciphertext_lo, opening_lo) = TransferAmountEncryption::new(
             amount_lo,
             &keypair_source.public,
             pubkey_dest,
-            pubkey_auditor,
+            &padded_pubkeys_auditor,
         );
         let (ciphertext_hi, opening_hi) = TransferAmountEncryption::new(
             amount_hi,
             &keypair_source.public,
             pubkey_dest,
-            pubkey_auditor,
+            &padded_pubkeys_auditor,
         );
 
         // subtract transfer amount from the spendable ciphertext
@@ -140,49 +210,65 @@ impl TransferData {
         };
 
         let ciphertext_new_source = ciphertext_old_source
-            - combine_u32_ciphertexts(&transfer_amount_lo_source, &transfer_amount_hi_source);
+            - amount_split
+                .combine_ciphertexts(&transfer_amount_lo_source, &transfer_amount_hi_source);
 
         // generate transcript and append all public inputs
         let pod_transfer_pubkeys =
-            pod::TransferPubkeys::new(&keypair_source.public, pubkey_dest, pubkey_auditor);
+            pod::TransferPubkeys::new(&keypair_source.public, pubkey_dest, &padded_pubkeys_auditor);
         let pod_ciphertext_lo: pod::TransferAmountEncryption = ciphertext_lo.into();
         let pod_ciphertext_hi: pod::TransferAmountEncryption = ciphertext_hi.into();
         let pod_ciphertext_new_source: pod::ElGamalCiphertext = ciphertext_new_source.into();
+        let pod_amount_split: pod::AmountSplit = amount_split.into();
 
         let mut transcript = TransferProof::transcript_new(
             &pod_transfer_pubkeys,
             &pod_ciphertext_lo,
             &pod_ciphertext_hi,
             &pod_ciphertext_new_source,
+            &pod_amount_split,
         );
 
         let proof = TransferProof::new(
             (amount_lo, amount_hi),
+            amount_split,
             keypair_source,
-            (pubkey_dest, pubkey_auditor),
+            (pubkey_dest, pubkeys_auditor),
             &opening_lo,
             &opening_hi,
             (new_spendable_balance, &ciphertext_new_source),
             &mut transcript,
-        );
+        )?;
 
         Ok(Self {
-            ciphertext_lo: pod_ciphertext_lo,
-            ciphertext_hi: pod_ciphertext_hi,
-            transfer_pubkeys: pod_transfer_pubkeys,
-            ciphertext_new_source: pod_ciphertext_new_source,
+            context: TransferProofContext {
+                ciphertext_lo: pod_ciphertext_lo,
+                ciphertext_hi: pod_ciphertext_hi,
+                transfer_pubkeys: pod_transfer_pubkeys,
+                ciphertext_new_source: pod_ciphertext_new_source,
+                amount_split: pod_amount_split,
+            },
             proof,
         })
     }
 
-    /// Extracts the lo ciphertexts associated with a transfer data
-    fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, ProofError> {
-        let ciphertext_lo: TransferAmountEncryption = self.ciphertext_lo.try_into()?;
+    /// Extracts the lo ciphertext associated with a role from a transfer data
+    pub fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_lo: TransferAmountEncryption = self
+            .context
+            .ciphertext_lo
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
 
         let handle_lo = match role {
             Role::Source => ciphertext_lo.source,
             Role::Dest => ciphertext_lo.dest,
-            Role::Auditor => ciphertext_lo.auditor,
+            Role::Auditor(index) => *ciphertext_lo
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
         };
 
         Ok(ElGamalCiphertext {
@@ -191,14 +277,23 @@ impl TransferData {
         })
     }
 
-    /// Extracts the lo ciphertexts associated with a transfer data
-    fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, ProofError> {
-        let ciphertext_hi: TransferAmountEncryption = self.ciphertext_hi.try_into()?;
+    /// Extracts the hi ciphertext associated with a role from a transfer data
+    pub fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_hi: TransferAmountEncryption = self
+            .context
+            .ciphertext_hi
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
 
         let handle_hi = match role {
             Role::Source => ciphertext_hi.source,
             Role::Dest => ciphertext_hi.dest,
-            Role::Auditor => ciphertext_hi.auditor,
+            Role::Auditor(index) => *ciphertext_hi
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
         };
 
         Ok(ElGamalCiphertext {
@@ -209,47 +304,83 @@ impl TransferData {
 
     /// Decrypts transfer amount from transfer data
     ///
-    /// TODO: This function should run in constant time. Use `subtle::Choice` for the if statement
-    /// and make sure that the function does not terminate prematurely due to errors
+    /// Both halves of the amount are always decrypted before they are combined; the only branch
+    /// is on whether the decryption succeeded as a whole. The discrete log lookups are not
+    /// constant time, see `DiscreteLog::decode_u32_online`.
     ///
-    /// TODO: Define specific error type for decryption error
-    pub fn decrypt_amount(&self, role: Role, sk: &ElGamalSecretKey) -> Result<u64, ProofError> {
+    /// The 48-bit low limb of a 48/16-bit split is decrypted as a 32-bit number first, and with
+    /// `DiscreteLog::decode_u48` if it is at least 2^32. Without a pre-computed table, that takes
+    /// on the order of 2^25 group operations; see `decrypt_amount_with_u48_precomputation`.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount(
+        &self,
+        role: Role,
+        sk: &ElGamalSecretKey,
+    ) -> Result<u64, DecryptionError> {
+        self.decrypt_amount_with_u48_precomputation(role, sk, None)
+    }
+
+    /// Decrypts transfer amount from transfer data, using `hashmap` to decrypt the 48-bit low
+    /// limb of a 48/16-bit split
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount_with_u48_precomputation(
+        &self,
+        role: Role,
+        sk: &ElGamalSecretKey,
+        hashmap: Option<&DecodeU48Precomputation>,
+    ) -> Result<u64, DecryptionError> {
+        let amount_split: AmountSplit = self
+            .context
+            .amount_split
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
         let ciphertext_lo = self.ciphertext_lo(role)?;
         let ciphertext_hi = self.ciphertext_hi(role)?;
 
         let amount_lo = ciphertext_lo.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
         let amount_hi = ciphertext_hi.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
 
-        if let (Some(amount_lo), Some(amount_hi)) = (amount_lo, amount_hi) {
-            Ok((amount_lo as u64) + (TWO_32 * amount_hi as u64))
-        } else {
-            Err(ProofError::Verification)
-        }
+        let amount = match amount_split {
+            AmountSplit::Lo32Hi32 => Option::<u64>::from(amount_lo.and_then(|amount_lo| {
+                amount_hi.map(|amount_hi| amount_split.combine(amount_lo as u64, amount_hi as u64))
+            })),
+            AmountSplit::Lo48Hi16 => Option::<u32>::from(amount_lo)
+                .map(u64::from)
+                .or_else(|| ciphertext_lo.decrypt_u48(sk, hashmap))
+                .zip(Option::<u32>::from(amount_hi))
+                .map(|(amount_lo, amount_hi)| amount_split.combine(amount_lo, amount_hi as u64)),
+        };
+        amount.ok_or(DecryptionError::DiscreteLogNotFound)
     }
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl Verifiable for TransferData {
-    fn verify(&self) -> Result<(), ProofError> {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
         // generate transcript and append all public inputs
         let mut transcript = TransferProof::transcript_new(
-            &self.transfer_pubkeys,
-            &self.ciphertext_lo,
-            &self.ciphertext_hi,
-            &self.ciphertext_new_source,
+            &self.context.transfer_pubkeys,
+            &self.context.ciphertext_lo,
+            &self.context.ciphertext_hi,
+            &self.context.ciphertext_new_source,
+            &self.context.amount_split,
         );
 
-        let ciphertext_lo = self.ciphertext_lo.try_into()?;
-        let ciphertext_hi = self.ciphertext_hi.try_into()?;
-        let transfer_pubkeys = self.transfer_pubkeys.try_into()?;
-        let new_spendable_ciphertext = self.ciphertext_new_source.try_into()?;
+        let ciphertext_lo = decode_field(self.context.ciphertext_lo, "ciphertext_lo")?;
+        let ciphertext_hi = decode_field(self.context.ciphertext_hi, "ciphertext_hi")?;
+        let transfer_pubkeys = decode_field(self.context.transfer_pubkeys, "transfer_pubkeys")?;
+        let new_spendable_ciphertext =
+            decode_field(self.context.ciphertext_new_source, "ciphertext_new_source")?;
+        let amount_split = decode_field(self.context.amount_split, "amount_split")?;
 
-        self.proof.verify(
+        self.proof.verify_batched(
             &ciphertext_lo,
             &ciphertext_hi,
             &transfer_pubkeys,
             &new_spendable_ciphertext,
+            amount_split,
             &mut transcript,
+            batch,
         )
     }
 }
@@ -257,7 +388,18 @@ impl Verifiable for TransferData {
 #[allow(non_snake_case)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 pub struct TransferProof {
+    /// Version of the proof format
+    pub version: u8,
+
     /// New Pedersen commitment for the remaining balance in source
     pub commitment_new_source: pod::PedersenCommitment,
 
@@ -274,31 +416,36 @@ pub struct TransferProof {
 #[allow(non_snake_case)]
 #[cfg(not(target_arch = "bpf"))]
 impl TransferProof {
-    fn transcript_new(
+    pub fn transcript_new(
         transfer_pubkeys: &pod::TransferPubkeys,
         ciphertext_lo: &pod::TransferAmountEncryption,
         ciphertext_hi: &pod::TransferAmountEncryption,
         ciphertext_new_source: &pod::ElGamalCiphertext,
+        amount_split: &pod::AmountSplit,
     ) -> Transcript {
-        let mut transcript = Transcript::new(b"transfer-proof");
+        let mut transcript = Transcript::new(b"transfer-proof-v1");
 
         transcript.append_message(b"transfer-pubkeys", &transfer_pubkeys.0);
         transcript.append_message(b"ciphertext-lo", &ciphertext_lo.0);
         transcript.append_message(b"ciphertext-hi", &ciphertext_hi.0);
         transcript.append_message(b"ciphertext-new-source", &ciphertext_new_source.0);
+        transcript.append_message(b"amount-split", &amount_split.0);
 
         transcript
     }
 
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        (transfer_amount_lo, transfer_amount_hi): (u32, u32),
+        (transfer_amount_lo, transfer_amount_hi): (u64, u64),
+        amount_split: AmountSplit,
         keypair_source: &ElGamalKeypair,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
         opening_lo: &PedersenOpening,
         opening_hi: &PedersenOpening,
         (source_new_balance, ciphertext_new_source): (u64, &ElGamalCiphertext),
         transcript: &mut Transcript,
-    ) -> Self {
+    ) -> Result<Self, ProofError> {
         // generate a Pedersen commitment for the remaining balance in source
         let (commitment_new_source, opening_source) = Pedersen::new(source_new_balance);
 
@@ -314,78 +461,100 @@ impl TransferProof {
             transcript,
         );
 
-        // generate ciphertext validity proof
+        // generate ciphertext validity proof for the destination and auditor handles
+        let pubkeys_validity: Vec<&ElGamalPubkey> = std::iter::once(pubkey_dest)
+            .chain(pubkeys_auditor)
+            .collect();
         let validity_proof = AggregatedValidityProof::new(
-            (pubkey_dest, pubkey_auditor),
+            &pubkeys_validity,
             (transfer_amount_lo, transfer_amount_hi),
             (opening_lo, opening_hi),
             transcript,
         );
 
         // generate the range proof
+        let (lo_bit_length, hi_bit_length) = amount_split.bit_lengths();
         let range_proof = RangeProof::new(
-            vec![
-                source_new_balance,
-                transfer_amount_lo as u64,
-                transfer_amount_hi as u64,
-            ],
-            vec![64, 32, 32],
+            vec![source_new_balance, transfer_amount_lo, transfer_amount_hi],
+            vec![64, lo_bit_length, hi_bit_length],
             vec![&opening_source, opening_lo, opening_hi],
             transcript,
-        );
+        )?;
 
-        Self {
+        Ok(Self {
+            version: Self::VERSION,
             commitment_new_source: pod_commitment_new_source,
             equality_proof: equality_proof.into(),
-            validity_proof: validity_proof.into(),
-            range_proof: range_proof.try_into().expect("range proof: length error"),
-        }
+            validity_proof: validity_proof.try_into()?,
+            range_proof: range_proof.try_into()?,
+        })
     }
 
-    pub fn verify(
+    pub fn verify_batched(
         &self,
         ciphertext_lo: &TransferAmountEncryption,
         ciphertext_hi: &TransferAmountEncryption,
         transfer_pubkeys: &TransferPubkeys,
         new_spendable_ciphertext: &ElGamalCiphertext,
+        amount_split: AmountSplit,
         transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), ProofError> {
+        self.check_version()?;
+
         transcript.append_commitment(b"commitment-new-source", &self.commitment_new_source);
 
-        let commitment: PedersenCommitment = self.commitment_new_source.try_into()?;
+        let commitment: PedersenCommitment =
+            decode_field(self.commitment_new_source, "commitment_new_source")?;
         let equality_proof: EqualityProof = self.equality_proof.try_into()?;
-        let aggregated_validity_proof: AggregatedValidityProof = self.validity_proof.try_into()?;
         let range_proof: RangeProof = self.range_proof.try_into()?;
 
+        let num_auditors = num_auditors(
+            &transfer_pubkeys.auditors,
+            &[&ciphertext_lo.auditors, &ciphertext_hi.auditors],
+        )?;
+        let aggregated_validity_proof = self.validity_proof.to_proof(num_auditors + 1)?;
+
         // verify equality proof
-        //
-        // TODO: we can also consider verifying equality and range proof in a batch
-        equality_proof.verify(
+        equality_proof.verify_batched(
             &transfer_pubkeys.source,
             new_spendable_ciphertext,
             &commitment,
             transcript,
+            batch,
         )?;
 
         // verify validity proof
-        aggregated_validity_proof.verify(
-            (&transfer_pubkeys.dest, &transfer_pubkeys.auditor),
+        let pubkeys: Vec<&ElGamalPubkey> = std::iter::once(&transfer_pubkeys.dest)
+            .chain(&transfer_pubkeys.auditors[..num_auditors])
+            .collect();
+        let handles_lo: Vec<&DecryptHandle> = std::iter::once(&ciphertext_lo.dest)
+            .chain(&ciphertext_lo.auditors[..num_auditors])
+            .collect();
+        let handles_hi: Vec<&DecryptHandle> = std::iter::once(&ciphertext_hi.dest)
+            .chain(&ciphertext_hi.auditors[..num_auditors])
+            .collect();
+        aggregated_validity_proof.verify_batched(
+            &pubkeys,
             (&ciphertext_lo.commitment, &ciphertext_hi.commitment),
-            (&ciphertext_lo.dest, &ciphertext_hi.dest),
-            (&ciphertext_lo.auditor, &ciphertext_hi.auditor),
+            (&handles_lo, &handles_hi),
             transcript,
+            batch,
         )?;
 
         // verify range proof
-        let commitment_new_source = self.commitment_new_source.try_into()?;
-        range_proof.verify(
+        let commitment_new_source =
+            decode_field(self.commitment_new_source, "commitment_new_source")?;
+        let (lo_bit_length, hi_bit_length) = amount_split.bit_lengths();
+        range_proof.verify_batched(
             vec![
                 &commitment_new_source,
                 &ciphertext_lo.commitment,
                 &ciphertext_hi.commitment,
             ],
-            vec![64_usize, 32_usize, 32_usize],
+            vec![64, lo_bit_length, hi_bit_length],
             transcript,
+            batch,
         )?;
 
         Ok(())
@@ -399,44 +568,58 @@ impl TransferProof {
 pub struct TransferPubkeys {
     pub source: ElGamalPubkey,
     pub dest: ElGamalPubkey,
-    pub auditor: ElGamalPubkey,
+    /// The pubkeys of the auditors, followed by zeroed pubkeys for the unused auditor slots
+    pub auditors: [ElGamalPubkey; MAX_AUDITORS],
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl TransferPubkeys {
     // TODO: use constructor instead
-    pub fn to_bytes(&self) -> [u8; 96] {
-        let mut bytes = [0u8; 96];
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 2)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 2)];
         bytes[..32].copy_from_slice(&self.source.to_bytes());
         bytes[32..64].copy_from_slice(&self.dest.to_bytes());
-        bytes[64..96].copy_from_slice(&self.auditor.to_bytes());
+        for (chunk, auditor) in bytes[64..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
         bytes
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
-        let bytes = array_ref![bytes, 0, 96];
-        let (source, dest, auditor) = array_refs![bytes, 32, 32, 32];
-
-        let source = ElGamalPubkey::from_bytes(source).ok_or(ProofError::Verification)?;
-        let dest = ElGamalPubkey::from_bytes(dest).ok_or(ProofError::Verification)?;
-        let auditor = ElGamalPubkey::from_bytes(auditor).ok_or(ProofError::Verification)?;
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 2)];
+        let (source, dest, auditors) = array_refs![bytes, 32, 32, 32 * MAX_AUDITORS];
+
+        let source = ElGamalPubkey::from_bytes(source).ok_or(ProofError::NonCanonical)?;
+        let dest = ElGamalPubkey::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_pubkeys = [ElGamalPubkey::default(); MAX_AUDITORS];
+        for (pubkey, bytes) in auditor_pubkeys.iter_mut().zip(auditors.chunks(32)) {
+            *pubkey = ElGamalPubkey::from_bytes(array_ref![bytes, 0, 32])
+                .ok_or(ProofError::NonCanonical)?;
+        }
 
         Ok(Self {
             source,
             dest,
-            auditor,
+            auditors: auditor_pubkeys,
         })
     }
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl pod::TransferPubkeys {
-    pub fn new(source: &ElGamalPubkey, dest: &ElGamalPubkey, auditor: &ElGamalPubkey) -> Self {
-        let mut bytes = [0u8; 96];
-        bytes[..32].copy_from_slice(&source.to_bytes());
-        bytes[32..64].copy_from_slice(&dest.to_bytes());
-        bytes[64..96].copy_from_slice(&auditor.to_bytes());
-        Self(bytes)
+    pub fn new(
+        source: &ElGamalPubkey,
+        dest: &ElGamalPubkey,
+        auditors: &[ElGamalPubkey; MAX_AUDITORS],
+    ) -> Self {
+        Self(
+            TransferPubkeys {
+                source: *source,
+                dest: *dest,
+                auditors: *auditors,
+            }
+            .to_bytes(),
+        )
     }
 }
 
@@ -463,11 +646,73 @@ mod test {
             transfer_amount,
             (spendable_balance, &spendable_ciphertext),
             &source_keypair,
-            (&dest_pk, &auditor_pk),
+            (&dest_pk, &[auditor_pk]),
+        )
+        .unwrap();
+
+        assert!(transfer_data.verify().is_ok());
+    }
+
+    #[test]
+    fn test_transfer_amount_split() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let ElGamalKeypair {
+            public: dest_pk,
+            secret: dest_sk,
+        } = ElGamalKeypair::new_rand();
+
+        // an amount whose high 32 bits cannot be decrypted efficiently, but whose high 16 bits can
+        let transfer_amount: u64 = (3 << 48) + 55;
+        let spendable_balance: u64 = u64::MAX;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        let transfer_data = TransferData::new_with_amount_split(
+            transfer_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &[]),
+            AmountSplit::Lo48Hi16,
         )
         .unwrap();
+        assert!(transfer_data.verify().is_ok());
+        assert_eq!(
+            transfer_data.decrypt_amount(Role::Dest, &dest_sk).unwrap(),
+            transfer_amount,
+        );
 
+        // a low limb of at least 2^32 is decrypted as a 48-bit number; its low 32 bits are small
+        // so that the online phase with a 16-bit table stays short
+        let transfer_amount: u64 = (3 << 48) + (5 << 40) + 55;
+        let transfer_data = TransferData::new_with_amount_split(
+            transfer_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &[]),
+            AmountSplit::Lo48Hi16,
+        )
+        .unwrap();
         assert!(transfer_data.verify().is_ok());
+        let precomputed_hashmap = DecodeU48Precomputation::with_table_bits(16).unwrap();
+        assert_eq!(
+            transfer_data
+                .decrypt_amount_with_u48_precomputation(
+                    Role::Dest,
+                    &dest_sk,
+                    Some(&precomputed_hashmap)
+                )
+                .unwrap(),
+            transfer_amount,
+        );
+
+        // the split is bound to the proof
+        let mut tampered_data = transfer_data;
+        tampered_data.context.amount_split = AmountSplit::Lo32Hi32.into();
+        assert!(tampered_data.verify().is_err());
+
+        // unknown splits are rejected
+        let mut tampered_data = transfer_data;
+        tampered_data.context.amount_split = pod::AmountSplit([2]);
+        assert!(tampered_data.verify().is_err());
     }
 
     #[test]
@@ -497,7 +742,7 @@ mod test {
             transfer_amount,
             (spendable_balance, &spendable_ciphertext),
             &source_keypair,
-            (&dest_pk, &auditor_pk),
+            (&dest_pk, &[auditor_pk]),
         )
         .unwrap();
 
@@ -515,9 +760,80 @@ mod test {
 
         assert_eq!(
             transfer_data
-                .decrypt_amount(Role::Auditor, &auditor_sk)
+                .decrypt_amount(Role::Auditor(0), &auditor_sk)
                 .unwrap(),
             55_u64,
         );
     }
+
+    #[test]
+    fn test_transfer_auditors() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let dest_pk = ElGamalKeypair::new_rand().public;
+        let auditor_keypairs = [ElGamalKeypair::new_rand(), ElGamalKeypair::new_rand()];
+        let auditor_pks = [auditor_keypairs[0].public, auditor_keypairs[1].public];
+
+        let spendable_balance: u64 = 77;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        // every auditor can decrypt the transfer amount
+        let transfer_data = TransferData::new(
+            55,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &auditor_pks),
+        )
+        .unwrap();
+        assert!(transfer_data.verify().is_ok());
+        for (index, auditor_keypair) in auditor_keypairs.iter().enumerate() {
+            assert_eq!(
+                transfer_data
+                    .decrypt_amount(Role::Auditor(index), &auditor_keypair.secret)
+                    .unwrap(),
+                55_u64,
+            );
+        }
+
+        // a transfer can have no auditor
+        let transfer_data = TransferData::new(
+            55,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &[]),
+        )
+        .unwrap();
+        assert!(transfer_data.verify().is_ok());
+        assert_eq!(
+            transfer_data.decrypt_amount(Role::Auditor(0), &auditor_keypairs[0].secret),
+            Err(DecryptionError::WrongRole),
+        );
+        assert_eq!(
+            transfer_data.decrypt_amount(Role::Dest, &auditor_keypairs[0].secret),
+            Err(DecryptionError::DiscreteLogNotFound),
+        );
+
+        // but no more than `MAX_AUDITORS`
+        let too_many_auditor_pks = vec![auditor_pks[0]; MAX_AUDITORS + 1];
+        assert!(TransferData::new(
+            55,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &too_many_auditor_pks),
+        )
+        .is_err());
+
+        // auditor handles cannot be stripped from the transfer data
+        let mut transfer_data = TransferData::new(
+            55,
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+            (&dest_pk, &auditor_pks[..1]),
+        )
+        .unwrap();
+        let mut transfer_pubkeys: TransferPubkeys =
+            transfer_data.context.transfer_pubkeys.try_into().unwrap();
+        transfer_pubkeys.auditors[0] = ElGamalPubkey::default();
+        transfer_data.context.transfer_pubkeys = transfer_pubkeys.into();
+        assert!(transfer_data.verify().is_err());
+    }
 }
@@ -0,0 +1,593 @@
+#[cfg(feature = "prover")]
+use crate::{
+    encryption::{
+        discrete_log::*,
+        elgamal::{ElGamalKeypair, ElGamalSecretKey},
+        pedersen::{Pedersen, PedersenOpening},
+    },
+    errors::DecryptionError,
+    instruction::{pad_auditor_pubkeys, split_u64_into_u32, Role, TWO_32},
+    sigma_proofs::validity_proof::AggregatedValidityProof,
+};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::{
+            elgamal::{DecryptHandle, ElGamalCiphertext, ElGamalPubkey},
+            pedersen::PedersenCommitment,
+        },
+        errors::ProofError,
+        instruction::{
+            combine_u32_ciphertexts, decode_field, num_auditors,
+            transfer::{TransferAmountEncryption, TransferPubkeys},
+            ProofVersion, Verifiable,
+        },
+        range_proof::RangeProof,
+        sigma_proofs::equality_proof::EqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    bytemuck::bytes_of,
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// Maximum number of transfers in a `TransferBatchData`
+///
+/// Each transfer contributes 128 bits to the aggregated range proof, whose total bit length must
+/// be a power of two, so the range proof always covers this many transfers and the unused ones
+/// are padded with commitments to zero.
+pub const MAX_TRANSFER_BATCH_SIZE: usize = 4;
+
+/// This struct includes the cryptographic proofs *and* the account data information needed to
+/// verify the proofs of up to `MAX_TRANSFER_BATCH_SIZE` transfers from the same source account
+///
+/// The transfers are applied in order: the first transfer spends from `ciphertext_old_source`,
+/// and each following transfer spends from `ciphertext_new_source` of the previous one. Instead
+/// of a range proof per transfer, a single range proof covers the new source balances and the
+/// amounts of all transfers.
+///
+/// - The pre-instruction should call TransferBatchData::verify(&self)
+/// - The actual program should check that `ciphertext_old_source` is the available balance of the
+///   source account, and that the pubkeys of each transfer are consistent with the accounts and
+///   the mint
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct TransferBatchData {
+    /// Version of the proof format
+    pub version: u8,
+
+    /// Number of transfers in the batch, between 1 and `MAX_TRANSFER_BATCH_SIZE`
+    pub num_transfers: u8,
+
+    /// The available balance ciphertext of the source before the first transfer
+    pub ciphertext_old_source: pod::ElGamalCiphertext,
+
+    /// The data and sigma proofs of each transfer; the entries past `num_transfers` are zeroed
+    pub transfers: [BatchedTransfer; MAX_TRANSFER_BATCH_SIZE],
+
+    /// Aggregated range proof over the new source balance and the amount of every transfer
+    pub range_proof: pod::RangeProof512,
+}
+
+/// A single transfer of a `TransferBatchData`, with its amount split into 32-bit limbs
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct BatchedTransfer {
+    /// Group encryption of the low 32 bits of the transfer amount
+    pub ciphertext_lo: pod::TransferAmountEncryption,
+
+    /// Group encryption of the high 32 bits of the transfer amount
+    pub ciphertext_hi: pod::TransferAmountEncryption,
+
+    /// The public encryption keys associated with the transfer: source, dest, and auditors
+    pub transfer_pubkeys: pod::TransferPubkeys,
+
+    /// The spendable ciphertext of the source after the transfer
+    pub ciphertext_new_source: pod::ElGamalCiphertext,
+
+    /// New Pedersen commitment for the remaining balance in source
+    pub commitment_new_source: pod::PedersenCommitment,
+
+    /// Associated equality proof
+    pub equality_proof: pod::EqualityProof,
+
+    /// Associated ciphertext validity proof
+    pub validity_proof: pod::AggregatedValidityProof,
+}
+
+// `borsh` only implements its traits for arrays of `Default` elements
+impl Default for BatchedTransfer {
+    fn default() -> Self {
+        Self::zeroed()
+    }
+}
+
+impl TransferBatchData {
+    /// The transfers of the batch, without the zeroed entries past `num_transfers`
+    pub fn transfers(&self) -> &[BatchedTransfer] {
+        let num_transfers = (self.num_transfers as usize).min(MAX_TRANSFER_BATCH_SIZE);
+        &self.transfers[..num_transfers]
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl TransferBatchData {
+    /// Creates the data for transfers of `(amount, (pubkey_dest, pubkeys_auditor))` from the
+    /// same source account, applied in order
+    ///
+    /// Fails if there are no transfers or more than `MAX_TRANSFER_BATCH_SIZE` of them.
+    #[cfg(feature = "prover")]
+    pub fn new(
+        transfers: &[(u64, (&ElGamalPubkey, &[ElGamalPubkey]))],
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+    ) -> Result<Self, ProofError> {
+        if transfers.is_empty() || transfers.len() > MAX_TRANSFER_BATCH_SIZE {
+            return Err(ProofError::Generation);
+        }
+
+        let mut source_balance = spendable_balance;
+        let mut ciphertext_source = *ciphertext_old_source;
+
+        // split and encrypt the transfer amounts, subtracting each of them from the source
+        let mut encryptions = Vec::with_capacity(transfers.len());
+        for &(transfer_amount, (pubkey_dest, pubkeys_auditor)) in transfers {
+            let padded_pubkeys_auditor = pad_auditor_pubkeys(pubkeys_auditor)?;
+            let (amount_lo, amount_hi) = split_u64_into_u32(transfer_amount);
+
+            let (ciphertext_lo, opening_lo) = TransferAmountEncryption::new(
+                amount_lo,
+                &keypair_source.public,
+                pubkey_dest,
+                &padded_pubkeys_auditor,
+            );
+            let (ciphertext_hi, opening_hi) = TransferAmountEncryption::new(
+                amount_hi,
+                &keypair_source.public,
+                pubkey_dest,
+                &padded_pubkeys_auditor,
+            );
+
+            source_balance = source_balance
+                .checked_sub(transfer_amount)
+                .ok_or(ProofError::Generation)?;
+
+            let transfer_amount_lo_source = ElGamalCiphertext {
+                commitment: ciphertext_lo.commitment,
+                handle: ciphertext_lo.source,
+            };
+            let transfer_amount_hi_source = ElGamalCiphertext {
+                commitment: ciphertext_hi.commitment,
+                handle: ciphertext_hi.source,
+            };
+            ciphertext_source = ciphertext_source
+                - combine_u32_ciphertexts(&transfer_amount_lo_source, &transfer_amount_hi_source);
+
+            encryptions.push((
+                (amount_lo as u64, amount_hi as u64),
+                (pubkey_dest, pubkeys_auditor),
+                (opening_lo, opening_hi),
+                (source_balance, ciphertext_source),
+                BatchedTransfer {
+                    ciphertext_lo: ciphertext_lo.into(),
+                    ciphertext_hi: ciphertext_hi.into(),
+                    transfer_pubkeys: pod::TransferPubkeys::new(
+                        &keypair_source.public,
+                        pubkey_dest,
+                        &padded_pubkeys_auditor,
+                    ),
+                    ciphertext_new_source: ciphertext_source.into(),
+                    ..BatchedTransfer::zeroed()
+                },
+            ));
+        }
+
+        // generate transcript and append all public inputs
+        let pod_ciphertext_old_source: pod::ElGamalCiphertext = (*ciphertext_old_source).into();
+        let mut transfers = [BatchedTransfer::zeroed(); MAX_TRANSFER_BATCH_SIZE];
+        for (transfer, (.., batched_transfer)) in transfers.iter_mut().zip(&encryptions) {
+            *transfer = *batched_transfer;
+        }
+        let mut transcript =
+            Self::transcript_new(&pod_ciphertext_old_source, &transfers[..encryptions.len()]);
+
+        // generate the sigma proofs of each transfer
+        let mut amounts = Vec::with_capacity(3 * MAX_TRANSFER_BATCH_SIZE);
+        let mut openings = Vec::with_capacity(encryptions.len());
+        for (
+            transfer,
+            (
+                (amount_lo, amount_hi),
+                (pubkey_dest, pubkeys_auditor),
+                (opening_lo, opening_hi),
+                (source_new_balance, ciphertext_new_source),
+                _,
+            ),
+        ) in transfers.iter_mut().zip(&encryptions)
+        {
+            // generate a Pedersen commitment for the remaining balance in source
+            let (commitment_new_source, opening_source) = Pedersen::new(*source_new_balance);
+
+            let pod_commitment_new_source: pod::PedersenCommitment = commitment_new_source.into();
+            transcript.append_commitment(b"commitment-new-source", &pod_commitment_new_source);
+
+            let equality_proof = EqualityProof::new(
+                keypair_source,
+                ciphertext_new_source,
+                *source_new_balance,
+                &opening_source,
+                &mut transcript,
+            );
+
+            let pubkeys_validity: Vec<&ElGamalPubkey> = std::iter::once(*pubkey_dest)
+                .chain(*pubkeys_auditor)
+                .collect();
+            let validity_proof = AggregatedValidityProof::new(
+                &pubkeys_validity,
+                (*amount_lo, *amount_hi),
+                (opening_lo, opening_hi),
+                &mut transcript,
+            );
+
+            transfer.commitment_new_source = pod_commitment_new_source;
+            transfer.equality_proof = equality_proof.into();
+            transfer.validity_proof = validity_proof.try_into()?;
+
+            amounts.extend([*source_new_balance, *amount_lo, *amount_hi]);
+            openings.push(opening_source);
+        }
+
+        // generate the aggregated range proof, padding the unused transfers with zero amounts
+        // under zero openings
+        let opening_zero = PedersenOpening::default();
+        let mut opening_refs: Vec<&PedersenOpening> = openings
+            .iter()
+            .zip(&encryptions)
+            .flat_map(|(opening_source, (_, _, (opening_lo, opening_hi), ..))| {
+                [opening_source, opening_lo, opening_hi]
+            })
+            .collect();
+        amounts.resize(3 * MAX_TRANSFER_BATCH_SIZE, 0);
+        opening_refs.resize(3 * MAX_TRANSFER_BATCH_SIZE, &opening_zero);
+        let range_proof = RangeProof::new(
+            amounts,
+            [64, 32, 32].repeat(MAX_TRANSFER_BATCH_SIZE),
+            opening_refs,
+            &mut transcript,
+        )?;
+
+        Ok(Self {
+            version: Self::VERSION,
+            num_transfers: encryptions.len() as u8,
+            ciphertext_old_source: pod_ciphertext_old_source,
+            transfers,
+            range_proof: range_proof.try_into()?,
+        })
+    }
+
+    pub fn transcript_new(
+        ciphertext_old_source: &pod::ElGamalCiphertext,
+        transfers: &[BatchedTransfer],
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"transfer-batch-proof-v1");
+
+        transcript.append_u64(b"num-transfers", transfers.len() as u64);
+        transcript.append_message(b"ciphertext-old-source", &ciphertext_old_source.0);
+        for transfer in transfers {
+            transcript.append_message(b"transfer-pubkeys", &transfer.transfer_pubkeys.0);
+            transcript.append_message(b"ciphertext-lo", &transfer.ciphertext_lo.0);
+            transcript.append_message(b"ciphertext-hi", &transfer.ciphertext_hi.0);
+            transcript.append_message(b"ciphertext-new-source", &transfer.ciphertext_new_source.0);
+        }
+
+        transcript
+    }
+
+    /// Decrypts the amount of the transfer at `index`
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount(
+        &self,
+        index: usize,
+        role: Role,
+        sk: &ElGamalSecretKey,
+    ) -> Result<u64, DecryptionError> {
+        let transfer = self
+            .transfers()
+            .get(index)
+            .ok_or(DecryptionError::WrongRole)?;
+        let ciphertext_lo = transfer_ciphertext(&transfer.ciphertext_lo, role)?;
+        let ciphertext_hi = transfer_ciphertext(&transfer.ciphertext_hi, role)?;
+
+        let amount_lo = ciphertext_lo.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+        let amount_hi = ciphertext_hi.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+
+        let amount = amount_lo.and_then(|amount_lo| {
+            amount_hi.map(|amount_hi| (amount_lo as u64) + (TWO_32 * amount_hi as u64))
+        });
+        Option::<u64>::from(amount).ok_or(DecryptionError::DiscreteLogNotFound)
+    }
+}
+
+/// Extracts the ciphertext associated with a role from a transfer amount encryption
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+fn transfer_ciphertext(
+    ciphertext: &pod::TransferAmountEncryption,
+    role: Role,
+) -> Result<ElGamalCiphertext, DecryptionError> {
+    let ciphertext: TransferAmountEncryption = (*ciphertext)
+        .try_into()
+        .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+    let handle = match role {
+        Role::Source => ciphertext.source,
+        Role::Dest => ciphertext.dest,
+        Role::Auditor(index) => *ciphertext
+            .auditors
+            .get(index)
+            .filter(|handle| **handle != DecryptHandle::default())
+            .ok_or(DecryptionError::WrongRole)?,
+        Role::FeeCollector => return Err(DecryptionError::WrongRole),
+    };
+
+    Ok(ElGamalCiphertext {
+        commitment: ciphertext.commitment,
+        handle,
+    })
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for TransferBatchData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let num_transfers = self.num_transfers as usize;
+        if num_transfers == 0 || num_transfers > MAX_TRANSFER_BATCH_SIZE {
+            return Err(ProofError::MalformedPod("num_transfers"));
+        }
+        let (transfers, unused_transfers) = self.transfers.split_at(num_transfers);
+        if unused_transfers
+            .iter()
+            .any(|transfer| bytes_of(transfer).iter().any(|byte| *byte != 0))
+        {
+            return Err(ProofError::MalformedPod("transfers"));
+        }
+
+        // generate transcript and append all public inputs
+        let mut transcript = Self::transcript_new(&self.ciphertext_old_source, transfers);
+
+        // all transfers must be from the same source
+        let source_pubkey = &transfers[0].transfer_pubkeys.0[..32];
+        if transfers
+            .iter()
+            .any(|transfer| transfer.transfer_pubkeys.0[..32] != *source_pubkey)
+        {
+            return Err(ProofError::Verification);
+        }
+
+        let mut ciphertext_source: ElGamalCiphertext =
+            decode_field(self.ciphertext_old_source, "ciphertext_old_source")?;
+        let mut commitments = Vec::with_capacity(3 * MAX_TRANSFER_BATCH_SIZE);
+        for transfer in transfers {
+            let ciphertext_lo: TransferAmountEncryption =
+                decode_field(transfer.ciphertext_lo, "ciphertext_lo")?;
+            let ciphertext_hi: TransferAmountEncryption =
+                decode_field(transfer.ciphertext_hi, "ciphertext_hi")?;
+            let transfer_pubkeys: TransferPubkeys =
+                decode_field(transfer.transfer_pubkeys, "transfer_pubkeys")?;
+            let new_spendable_ciphertext: ElGamalCiphertext =
+                decode_field(transfer.ciphertext_new_source, "ciphertext_new_source")?;
+
+            // each transfer must spend from the new source balance of the previous one
+            let transfer_amount_source = combine_u32_ciphertexts(
+                &ElGamalCiphertext {
+                    commitment: ciphertext_lo.commitment,
+                    handle: ciphertext_lo.source,
+                },
+                &ElGamalCiphertext {
+                    commitment: ciphertext_hi.commitment,
+                    handle: ciphertext_hi.source,
+                },
+            );
+            if new_spendable_ciphertext != ciphertext_source - transfer_amount_source {
+                return Err(ProofError::Verification);
+            }
+            ciphertext_source = new_spendable_ciphertext;
+
+            transcript.append_commitment(b"commitment-new-source", &transfer.commitment_new_source);
+
+            let commitment_new_source: PedersenCommitment =
+                decode_field(transfer.commitment_new_source, "commitment_new_source")?;
+            let equality_proof: EqualityProof = transfer.equality_proof.try_into()?;
+
+            let num_auditors = num_auditors(
+                &transfer_pubkeys.auditors,
+                &[&ciphertext_lo.auditors, &ciphertext_hi.auditors],
+            )?;
+            let aggregated_validity_proof = transfer.validity_proof.to_proof(num_auditors + 1)?;
+
+            // verify equality proof
+            equality_proof.verify_batched(
+                &transfer_pubkeys.source,
+                &new_spendable_ciphertext,
+                &commitment_new_source,
+                &mut transcript,
+                batch,
+            )?;
+
+            // verify validity proof
+            let pubkeys: Vec<&ElGamalPubkey> = std::iter::once(&transfer_pubkeys.dest)
+                .chain(&transfer_pubkeys.auditors[..num_auditors])
+                .collect();
+            let handles_lo: Vec<&DecryptHandle> = std::iter::once(&ciphertext_lo.dest)
+                .chain(&ciphertext_lo.auditors[..num_auditors])
+                .collect();
+            let handles_hi: Vec<&DecryptHandle> = std::iter::once(&ciphertext_hi.dest)
+                .chain(&ciphertext_hi.auditors[..num_auditors])
+                .collect();
+            aggregated_validity_proof.verify_batched(
+                &pubkeys,
+                (&ciphertext_lo.commitment, &ciphertext_hi.commitment),
+                (&handles_lo, &handles_hi),
+                &mut transcript,
+                batch,
+            )?;
+
+            commitments.extend([
+                commitment_new_source,
+                ciphertext_lo.commitment,
+                ciphertext_hi.commitment,
+            ]);
+        }
+
+        // verify the aggregated range proof, whose unused transfers are commitments to zero
+        commitments.resize(3 * MAX_TRANSFER_BATCH_SIZE, PedersenCommitment::default());
+        let range_proof: RangeProof = self.range_proof.try_into()?;
+        range_proof.verify_batched(
+            commitments.iter().collect(),
+            [64, 32, 32].repeat(MAX_TRANSFER_BATCH_SIZE),
+            &mut transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::encryption::elgamal::ElGamalKeypair};
+
+    #[test]
+    fn test_transfer_batch_correctness() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let ElGamalKeypair {
+            public: dest_pk_1,
+            secret: dest_sk_1,
+        } = ElGamalKeypair::new_rand();
+        let ElGamalKeypair {
+            public: dest_pk_2,
+            secret: dest_sk_2,
+        } = ElGamalKeypair::new_rand();
+        let auditor_pk = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 77;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        let transfer_batch_data = TransferBatchData::new(
+            &[(55, (&dest_pk_1, &[auditor_pk])), (22, (&dest_pk_2, &[]))],
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+        )
+        .unwrap();
+        assert!(transfer_batch_data.verify().is_ok());
+        assert_eq!(transfer_batch_data.transfers().len(), 2);
+
+        assert_eq!(
+            transfer_batch_data
+                .decrypt_amount(0, Role::Dest, &dest_sk_1)
+                .unwrap(),
+            55
+        );
+        assert_eq!(
+            transfer_batch_data
+                .decrypt_amount(1, Role::Dest, &dest_sk_2)
+                .unwrap(),
+            22
+        );
+        assert!(transfer_batch_data
+            .decrypt_amount(2, Role::Dest, &dest_sk_2)
+            .is_err());
+
+        // the last transfer spends the whole balance
+        let ciphertext_new_source: ElGamalCiphertext = transfer_batch_data.transfers[1]
+            .ciphertext_new_source
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            ciphertext_new_source.decrypt_u32(&source_keypair.secret),
+            Some(0)
+        );
+
+        // the transfers cannot be reordered
+        let mut tampered_data = transfer_batch_data;
+        tampered_data.transfers.swap(0, 1);
+        assert!(tampered_data.verify().is_err());
+
+        // the transfers must spend from the source balance that the batch starts from
+        let mut tampered_data = transfer_batch_data;
+        tampered_data.ciphertext_old_source = source_keypair.public.encrypt(78_u64).into();
+        assert!(tampered_data.verify().is_err());
+
+        // the unused transfers must be zeroed
+        let mut tampered_data = transfer_batch_data;
+        tampered_data.transfers[2] = tampered_data.transfers[1];
+        assert!(tampered_data.verify().is_err());
+        tampered_data.num_transfers = 3;
+        assert!(tampered_data.verify().is_err());
+
+        // the transfers cannot exceed the balance in total
+        assert!(TransferBatchData::new(
+            &[(55, (&dest_pk_1, &[])), (23, (&dest_pk_2, &[]))],
+            (spendable_balance, &spendable_ciphertext),
+            &source_keypair,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_transfer_batch_sizes() {
+        let source_keypair = ElGamalKeypair::new_rand();
+        let dest_pk = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 100;
+        let spendable_ciphertext = source_keypair.public.encrypt(spendable_balance);
+
+        for num_transfers in 1..=MAX_TRANSFER_BATCH_SIZE {
+            let transfers: Vec<_> = (0..num_transfers)
+                .map(|_| (10, (&dest_pk, &[][..])))
+                .collect();
+            let transfer_batch_data = TransferBatchData::new(
+                &transfers,
+                (spendable_balance, &spendable_ciphertext),
+                &source_keypair,
+            )
+            .unwrap();
+            assert!(transfer_batch_data.verify().is_ok());
+            assert_eq!(transfer_batch_data.transfers().len(), num_transfers);
+        }
+
+        let too_many_transfers: Vec<_> = (0..MAX_TRANSFER_BATCH_SIZE + 1)
+            .map(|_| (10, (&dest_pk, &[][..])))
+            .collect();
+        for transfers in [&[][..], &too_many_transfers] {
+            assert!(TransferBatchData::new(
+                transfers,
+                (spendable_balance, &spendable_ciphertext),
+                &source_keypair,
+            )
+            .is_err());
+        }
+    }
+}
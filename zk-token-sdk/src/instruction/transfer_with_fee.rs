@@ -5,23 +5,19 @@ use {
 #[cfg(not(target_arch = "bpf"))]
 use {
     crate::{
+        batch::MultiscalarMulBatch,
         encryption::{
-            discrete_log::*,
-            elgamal::{
-                DecryptHandle, ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey,
-            },
+            elgamal::{DecryptHandle, ElGamalCiphertext, ElGamalPubkey},
             pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
         },
         errors::ProofError,
         instruction::{
-            combine_u32_ciphertexts, combine_u32_commitments, combine_u32_openings,
-            split_u64_into_u32, transfer::TransferAmountEncryption, Role, Verifiable, TWO_32,
+            combine_u32_commitments, decode_field, num_auditors,
+            transfer::TransferAmountEncryption, ProofVersion, Verifiable, MAX_AUDITORS,
         },
         range_proof::RangeProof,
         sigma_proofs::{
-            equality_proof::EqualityProof,
-            fee_proof::FeeSigmaProof,
-            validity_proof::{AggregatedValidityProof, ValidityProof},
+            equality_proof::EqualityProof, fee_proof::FeeSigmaProof, validity_proof::ValidityProof,
         },
         transcript::TranscriptProtocol,
     },
@@ -29,28 +25,68 @@ use {
     curve25519_dalek::scalar::Scalar,
     merlin::Transcript,
     std::convert::TryInto,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::{
+        cancellation::{block_on, checkpoint, CancellationToken},
+        encryption::{
+            discrete_log::*,
+            elgamal::{ElGamalKeypair, ElGamalSecretKey},
+        },
+        errors::DecryptionError,
+        fee_calculation::{calculate_fee, RoundingMode},
+        instruction::{
+            combine_u32_ciphertexts, combine_u32_openings, pad_auditor_pubkeys, split_u64_into_u32,
+            Role, TWO_32,
+        },
+        sigma_proofs::validity_proof::AggregatedValidityProof,
+    },
     subtle::{ConditionallySelectable, ConstantTimeGreater},
 };
 
-#[cfg(not(target_arch = "bpf"))]
-const FEE_DENOMINATOR: u64 = 10000;
-
-#[cfg(not(target_arch = "bpf"))]
-lazy_static::lazy_static! {
-    pub static ref COMMITMENT_FEE_DENOMINATOR: PedersenCommitment = Pedersen::encode(FEE_DENOMINATOR);
-}
+/// Fee denominator for fee rates expressed in basis points
+pub const FEE_DENOMINATOR_BASIS_POINTS: u64 = 10_000;
 
 // #[derive(Clone, Copy, Pod, Zeroable)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 pub struct TransferWithFeeData {
+    /// The context data needed to verify the proof
+    pub context: TransferWithFeeProofContext,
+
+    // transfer fee proof
+    pub proof: TransferWithFeeProof,
+}
+
+/// The public inputs of a `TransferWithFeeData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct TransferWithFeeProofContext {
     /// Group encryption of the low 32 bites of the transfer amount
     pub ciphertext_lo: pod::TransferAmountEncryption,
 
     /// Group encryption of the high 32 bits of the transfer amount
     pub ciphertext_hi: pod::TransferAmountEncryption,
 
-    /// The public encryption keys associated with the transfer: source, dest, and auditor
+    /// The public encryption keys associated with the transfer: source, dest, auditors, and fee
+    /// collector
     pub transfer_with_fee_pubkeys: pod::TransferWithFeePubkeys,
 
     /// The final spendable ciphertext after the transfer,
@@ -61,21 +97,71 @@ pub struct TransferWithFeeData {
 
     // fee parameters
     pub fee_parameters: pod::FeeParameters,
-
-    // transfer fee proof
-    pub proof: TransferWithFeeProof,
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl TransferWithFeeData {
+    #[cfg(feature = "prover")]
     pub fn new(
         transfer_amount: u64,
         (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
         keypair_source: &ElGamalKeypair,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        fee_parameters: FeeParameters,
+        pubkey_fee_collector: &ElGamalPubkey,
+    ) -> Result<Self, ProofError> {
+        block_on(Self::generate(
+            transfer_amount,
+            (spendable_balance, ciphertext_old_source),
+            keypair_source,
+            (pubkey_dest, pubkeys_auditor),
+            fee_parameters,
+            pubkey_fee_collector,
+            None,
+        ))
+    }
+
+    /// Creates the proof data like `new`, but yields to the executor between the sub-proofs so
+    /// that it does not block an event loop for the whole generation
+    ///
+    /// The generation fails with `ProofError::Cancelled` at the next sub-proof once
+    /// `cancellation` is cancelled.
+    #[cfg(feature = "prover")]
+    pub async fn new_async(
+        transfer_amount: u64,
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        fee_parameters: FeeParameters,
+        pubkey_fee_collector: &ElGamalPubkey,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, ProofError> {
+        Self::generate(
+            transfer_amount,
+            (spendable_balance, ciphertext_old_source),
+            keypair_source,
+            (pubkey_dest, pubkeys_auditor),
+            fee_parameters,
+            pubkey_fee_collector,
+            Some(cancellation),
+        )
+        .await
+    }
+
+    #[cfg(feature = "prover")]
+    async fn generate(
+        transfer_amount: u64,
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
         fee_parameters: FeeParameters,
         pubkey_fee_collector: &ElGamalPubkey,
+        cancellation: Option<&CancellationToken>,
     ) -> Result<Self, ProofError> {
+        let padded_pubkeys_auditor = pad_auditor_pubkeys(pubkeys_auditor)?;
+
+        fee_parameters.validate()?;
+
         // split and encrypt transfer amount
         let (amount_lo, amount_hi) = split_u64_into_u32(transfer_amount);
 
@@ -83,13 +169,13 @@ impl TransferWithFeeData {
             amount_lo,
             &keypair_source.public,
             pubkey_dest,
-            pubkey_auditor,
+            &padded_pubkeys_auditor,
         );
         let (ciphertext_hi, opening_hi) = TransferAmountEncryption::new(
             amount_hi,
             &keypair_source.public,
             pubkey_dest,
-            pubkey_auditor,
+            &padded_pubkeys_auditor,
         );
 
         // subtract transfer amount from the spendable ciphertext
@@ -112,7 +198,8 @@ impl TransferWithFeeData {
 
         // calculate and encrypt fee
         let (fee_amount, delta_fee) =
-            calculate_fee(transfer_amount, fee_parameters.fee_rate_basis_points);
+            calculate_fee(transfer_amount, &fee_parameters, RoundingMode::Ceil)
+                .ok_or(ProofError::Generation)?;
 
         let below_max = u64::ct_gt(&fee_parameters.maximum_fee, &fee_amount);
         let fee_to_encrypt =
@@ -126,53 +213,69 @@ impl TransferWithFeeData {
         let pod_transfer_with_fee_pubkeys = pod::TransferWithFeePubkeys::new(
             &keypair_source.public,
             pubkey_dest,
-            pubkey_auditor,
+            &padded_pubkeys_auditor,
             pubkey_fee_collector,
         );
         let pod_ciphertext_lo = pod::TransferAmountEncryption(ciphertext_lo.to_bytes());
         let pod_ciphertext_hi = pod::TransferAmountEncryption(ciphertext_hi.to_bytes());
         let pod_ciphertext_new_source: pod::ElGamalCiphertext = ciphertext_new_source.into();
         let pod_ciphertext_fee = pod::FeeEncryption(ciphertext_fee.to_bytes());
+        let pod_fee_parameters: pod::FeeParameters = fee_parameters.into();
 
         let mut transcript = TransferWithFeeProof::transcript_new(
             &pod_transfer_with_fee_pubkeys,
             &pod_ciphertext_lo,
             &pod_ciphertext_hi,
             &pod_ciphertext_fee,
+            &pod_fee_parameters,
         );
 
-        let proof = TransferWithFeeProof::new(
+        let proof = TransferWithFeeProof::generate(
             (amount_lo, &ciphertext_lo, &opening_lo),
             (amount_hi, &ciphertext_hi, &opening_hi),
             keypair_source,
-            (pubkey_dest, pubkey_auditor),
+            (pubkey_dest, pubkeys_auditor),
             (new_spendable_balance, &ciphertext_new_source),
             (fee_amount, &ciphertext_fee, &opening_fee),
             delta_fee,
             pubkey_fee_collector,
             fee_parameters,
             &mut transcript,
-        );
+            cancellation,
+        )
+        .await?;
 
         Ok(Self {
-            ciphertext_lo: pod_ciphertext_lo,
-            ciphertext_hi: pod_ciphertext_hi,
-            transfer_with_fee_pubkeys: pod_transfer_with_fee_pubkeys,
-            ciphertext_new_source: pod_ciphertext_new_source,
-            ciphertext_fee: pod_ciphertext_fee,
-            fee_parameters: fee_parameters.into(),
+            context: TransferWithFeeProofContext {
+                ciphertext_lo: pod_ciphertext_lo,
+                ciphertext_hi: pod_ciphertext_hi,
+                transfer_with_fee_pubkeys: pod_transfer_with_fee_pubkeys,
+                ciphertext_new_source: pod_ciphertext_new_source,
+                ciphertext_fee: pod_ciphertext_fee,
+                fee_parameters: pod_fee_parameters,
+            },
             proof,
         })
     }
 
     /// Extracts the lo ciphertexts associated with a transfer-with-fee data
-    fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, ProofError> {
-        let ciphertext_lo: TransferAmountEncryption = self.ciphertext_lo.try_into()?;
+    #[cfg(feature = "prover")]
+    fn ciphertext_lo(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_lo: TransferAmountEncryption = self
+            .context
+            .ciphertext_lo
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
 
         let handle_lo = match role {
             Role::Source => ciphertext_lo.source,
             Role::Dest => ciphertext_lo.dest,
-            Role::Auditor => ciphertext_lo.auditor,
+            Role::Auditor(index) => *ciphertext_lo
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
         };
 
         Ok(ElGamalCiphertext {
@@ -182,13 +285,23 @@ impl TransferWithFeeData {
     }
 
     /// Extracts the lo ciphertexts associated with a transfer-with-fee data
-    fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, ProofError> {
-        let ciphertext_hi: TransferAmountEncryption = self.ciphertext_hi.try_into()?;
+    #[cfg(feature = "prover")]
+    fn ciphertext_hi(&self, role: Role) -> Result<ElGamalCiphertext, DecryptionError> {
+        let ciphertext_hi: TransferAmountEncryption = self
+            .context
+            .ciphertext_hi
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
 
         let handle_hi = match role {
             Role::Source => ciphertext_hi.source,
             Role::Dest => ciphertext_hi.dest,
-            Role::Auditor => ciphertext_hi.auditor,
+            Role::Auditor(index) => *ciphertext_hi
+                .auditors
+                .get(index)
+                .filter(|handle| **handle != DecryptHandle::default())
+                .ok_or(DecryptionError::WrongRole)?,
+            Role::FeeCollector => return Err(DecryptionError::WrongRole),
         };
 
         Ok(ElGamalCiphertext {
@@ -199,44 +312,80 @@ impl TransferWithFeeData {
 
     /// Decrypts transfer amount from transfer-with-fee data
     ///
-    /// TODO: This function should run in constant time. Use `subtle::Choice` for the if statement
-    /// and make sure that the function does not terminate prematurely due to errors
-    ///
-    /// TODO: Define specific error type for decryption error
-    pub fn decrypt_amount(&self, role: Role, sk: &ElGamalSecretKey) -> Result<u64, ProofError> {
+    /// Both halves of the amount are always decrypted before they are combined; the only branch
+    /// is on whether the decryption succeeded as a whole. The discrete log lookups are not
+    /// constant time.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_amount(
+        &self,
+        role: Role,
+        sk: &ElGamalSecretKey,
+    ) -> Result<u64, DecryptionError> {
         let ciphertext_lo = self.ciphertext_lo(role)?;
         let ciphertext_hi = self.ciphertext_hi(role)?;
 
         let amount_lo = ciphertext_lo.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
         let amount_hi = ciphertext_hi.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
 
-        if let (Some(amount_lo), Some(amount_hi)) = (amount_lo, amount_hi) {
-            Ok((amount_lo as u64) + (TWO_32 * amount_hi as u64))
-        } else {
-            Err(ProofError::Verification)
-        }
+        let amount = amount_lo.and_then(|amount_lo| {
+            amount_hi.map(|amount_hi| (amount_lo as u64) + (TWO_32 * amount_hi as u64))
+        });
+        Option::<u64>::from(amount).ok_or(DecryptionError::DiscreteLogNotFound)
+    }
+
+    /// Decrypts the fee of a transfer-with-fee data, which is encrypted for the destination and
+    /// the fee collector
+    ///
+    /// The fee is decrypted as a 32-bit number, so that a fee that does not fit in 32 bits cannot
+    /// be recovered by this function.
+    #[cfg(feature = "prover")]
+    pub fn decrypt_fee(&self, role: Role, sk: &ElGamalSecretKey) -> Result<u64, DecryptionError> {
+        let ciphertext_fee: FeeEncryption = self
+            .context
+            .ciphertext_fee
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let handle = match role {
+            Role::Dest => ciphertext_fee.dest,
+            Role::FeeCollector => ciphertext_fee.fee_collector,
+            Role::Source | Role::Auditor(_) => return Err(DecryptionError::WrongRole),
+        };
+
+        let ciphertext = ElGamalCiphertext {
+            commitment: ciphertext_fee.commitment,
+            handle,
+        };
+        Option::<u32>::from(ciphertext.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G))
+            .map(u64::from)
+            .ok_or(DecryptionError::DiscreteLogNotFound)
     }
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl Verifiable for TransferWithFeeData {
-    fn verify(&self) -> Result<(), ProofError> {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
         let mut transcript = TransferWithFeeProof::transcript_new(
-            &self.transfer_with_fee_pubkeys,
-            &self.ciphertext_lo,
-            &self.ciphertext_hi,
-            &self.ciphertext_fee,
+            &self.context.transfer_with_fee_pubkeys,
+            &self.context.ciphertext_lo,
+            &self.context.ciphertext_hi,
+            &self.context.ciphertext_fee,
+            &self.context.fee_parameters,
         );
 
-        let ciphertext_lo = self.ciphertext_lo.try_into()?;
-        let ciphertext_hi = self.ciphertext_hi.try_into()?;
-        let transfer_with_fee_pubkeys = self.transfer_with_fee_pubkeys.try_into()?;
-        let new_spendable_ciphertext = self.ciphertext_new_source.try_into()?;
+        let ciphertext_lo = decode_field(self.context.ciphertext_lo, "ciphertext_lo")?;
+        let ciphertext_hi = decode_field(self.context.ciphertext_hi, "ciphertext_hi")?;
+        let transfer_with_fee_pubkeys = decode_field(
+            self.context.transfer_with_fee_pubkeys,
+            "transfer_with_fee_pubkeys",
+        )?;
+        let new_spendable_ciphertext =
+            decode_field(self.context.ciphertext_new_source, "ciphertext_new_source")?;
 
-        let ciphertext_fee = self.ciphertext_fee.try_into()?;
-        let fee_parameters = self.fee_parameters.into();
+        let ciphertext_fee = decode_field(self.context.ciphertext_fee, "ciphertext_fee")?;
+        let fee_parameters = decode_field(self.context.fee_parameters, "fee_parameters")?;
 
-        self.proof.verify(
+        self.proof.verify_batched(
             &ciphertext_lo,
             &ciphertext_hi,
             &transfer_with_fee_pubkeys,
@@ -244,14 +393,24 @@ impl Verifiable for TransferWithFeeData {
             &ciphertext_fee,
             fee_parameters,
             &mut transcript,
+            batch,
         )
     }
 }
 
 // #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct TransferWithFeeProof {
+    pub version: u8,
     pub commitment_new_source: pod::PedersenCommitment,
     pub commitment_claimed: pod::PedersenCommitment,
     pub equality_proof: pod::EqualityProof,
@@ -264,29 +423,31 @@ pub struct TransferWithFeeProof {
 #[allow(non_snake_case)]
 #[cfg(not(target_arch = "bpf"))]
 impl TransferWithFeeProof {
-    fn transcript_new(
+    pub fn transcript_new(
         transfer_with_fee_pubkeys: &pod::TransferWithFeePubkeys,
         ciphertext_lo: &pod::TransferAmountEncryption,
         ciphertext_hi: &pod::TransferAmountEncryption,
         ciphertext_fee: &pod::FeeEncryption,
+        fee_parameters: &pod::FeeParameters,
     ) -> Transcript {
-        let mut transcript = Transcript::new(b"FeeProof");
+        let mut transcript = Transcript::new(b"FeeProof-v2");
 
         transcript.append_message(b"transfer-with-fee-pubkeys", &transfer_with_fee_pubkeys.0);
         transcript.append_message(b"ciphertext-lo", &ciphertext_lo.0);
         transcript.append_message(b"ciphertext-hi", &ciphertext_hi.0);
         transcript.append_message(b"ciphertext-fee", &ciphertext_fee.0);
+        transcript.append_message(b"fee-parameters", &fee_parameters.0);
 
         transcript
     }
 
+    #[cfg(feature = "prover")]
     #[allow(clippy::too_many_arguments)]
-    #[allow(clippy::many_single_char_names)]
     pub fn new(
         transfer_amount_lo_data: (u32, &TransferAmountEncryption, &PedersenOpening),
         transfer_amount_hi_data: (u32, &TransferAmountEncryption, &PedersenOpening),
         keypair_source: &ElGamalKeypair,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
         (source_new_balance, ciphertext_new_source): (u64, &ElGamalCiphertext),
 
         (fee_amount, ciphertext_fee, opening_fee): (u64, &FeeEncryption, &PedersenOpening),
@@ -294,7 +455,78 @@ impl TransferWithFeeProof {
         pubkey_fee_collector: &ElGamalPubkey,
         fee_parameters: FeeParameters,
         transcript: &mut Transcript,
-    ) -> Self {
+    ) -> Result<Self, ProofError> {
+        block_on(Self::generate(
+            transfer_amount_lo_data,
+            transfer_amount_hi_data,
+            keypair_source,
+            (pubkey_dest, pubkeys_auditor),
+            (source_new_balance, ciphertext_new_source),
+            (fee_amount, ciphertext_fee, opening_fee),
+            delta_fee,
+            pubkey_fee_collector,
+            fee_parameters,
+            transcript,
+            None,
+        ))
+    }
+
+    /// Generates the proof like `new`, yielding to the executor and checking `cancellation`
+    /// before each sub-proof
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_async(
+        transfer_amount_lo_data: (u32, &TransferAmountEncryption, &PedersenOpening),
+        transfer_amount_hi_data: (u32, &TransferAmountEncryption, &PedersenOpening),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        (source_new_balance, ciphertext_new_source): (u64, &ElGamalCiphertext),
+
+        (fee_amount, ciphertext_fee, opening_fee): (u64, &FeeEncryption, &PedersenOpening),
+        delta_fee: u64,
+        pubkey_fee_collector: &ElGamalPubkey,
+        fee_parameters: FeeParameters,
+        transcript: &mut Transcript,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, ProofError> {
+        Self::generate(
+            transfer_amount_lo_data,
+            transfer_amount_hi_data,
+            keypair_source,
+            (pubkey_dest, pubkeys_auditor),
+            (source_new_balance, ciphertext_new_source),
+            (fee_amount, ciphertext_fee, opening_fee),
+            delta_fee,
+            pubkey_fee_collector,
+            fee_parameters,
+            transcript,
+            Some(cancellation),
+        )
+        .await
+    }
+
+    /// Generates the sub-proofs in parallel if `cancellation` is `None`, and one after the other
+    /// with a checkpoint before each otherwise
+    ///
+    /// Every sub-proof is generated on its own fork of the transcript, so the proof does not
+    /// depend on the order in which the sub-proofs are computed.
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::many_single_char_names)]
+    async fn generate(
+        transfer_amount_lo_data: (u32, &TransferAmountEncryption, &PedersenOpening),
+        transfer_amount_hi_data: (u32, &TransferAmountEncryption, &PedersenOpening),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        (source_new_balance, ciphertext_new_source): (u64, &ElGamalCiphertext),
+
+        (fee_amount, ciphertext_fee, opening_fee): (u64, &FeeEncryption, &PedersenOpening),
+        delta_fee: u64,
+        pubkey_fee_collector: &ElGamalPubkey,
+        fee_parameters: FeeParameters,
+        transcript: &mut Transcript,
+        cancellation: Option<&CancellationToken>,
+    ) -> Result<Self, ProofError> {
         let (transfer_amount_lo, ciphertext_lo, opening_lo) = transfer_amount_lo_data;
         let (transfer_amount_hi, ciphertext_hi, opening_hi) = transfer_amount_hi_data;
 
@@ -307,81 +539,163 @@ impl TransferWithFeeProof {
 
         transcript.append_commitment(b"commitment-new-source", &pod_commitment_new_source);
         transcript.append_commitment(b"commitment-claimed", &pod_commitment_claimed);
-
-        // generate equality_proof
-        let equality_proof = EqualityProof::new(
-            keypair_source,
-            ciphertext_new_source,
-            source_new_balance,
-            &opening_source,
-            transcript,
-        );
-
-        // generate ciphertext validity proof
-        let ciphertext_amount_validity_proof = AggregatedValidityProof::new(
-            (pubkey_dest, pubkey_auditor),
-            (transfer_amount_lo, transfer_amount_hi),
-            (opening_lo, opening_hi),
-            transcript,
-        );
+        let transcript = &*transcript;
 
         let (commitment_delta, opening_delta) = compute_delta_commitment_and_opening(
             (&ciphertext_lo.commitment, opening_lo),
             (&ciphertext_hi.commitment, opening_hi),
             (&ciphertext_fee.commitment, opening_fee),
-            fee_parameters.fee_rate_basis_points,
-        );
-
-        let fee_sigma_proof = FeeSigmaProof::new(
-            (fee_amount, &ciphertext_fee.commitment, opening_fee),
-            (delta_fee, &commitment_delta, &opening_delta),
-            (&commitment_claimed, &opening_claimed),
-            fee_parameters.maximum_fee,
-            transcript,
+            &fee_parameters,
         );
-
-        let ciphertext_fee_validity_proof = ValidityProof::new(
-            (pubkey_dest, pubkey_fee_collector),
-            fee_amount,
-            opening_fee,
-            transcript,
-        );
-
         let opening_claimed_negated = &PedersenOpening::default() - &opening_claimed;
-        let range_proof = RangeProof::new(
-            vec![
+        let delta_fee_complement = fee_parameters
+            .fee_denominator
+            .checked_sub(delta_fee)
+            .ok_or(ProofError::Generation)?;
+
+        // generate equality_proof
+        let generate_equality_proof = || {
+            EqualityProof::new(
+                keypair_source,
+                ciphertext_new_source,
                 source_new_balance,
-                transfer_amount_lo as u64,
-                transfer_amount_hi as u64,
-                delta_fee,
-                FEE_DENOMINATOR - delta_fee,
-            ],
-            vec![
-                64, 32, 32, 64, // double check
-                64,
-            ],
-            vec![
                 &opening_source,
-                opening_lo,
-                opening_hi,
-                &opening_claimed,
-                &opening_claimed_negated,
-            ],
-            transcript,
-        );
+                &mut transcript.fork(b"equality-proof"),
+            )
+        };
 
-        Self {
+        // generate ciphertext validity proof for the destination and auditor handles
+        let generate_ciphertext_amount_validity_proof = || {
+            let pubkeys_validity: Vec<&ElGamalPubkey> = std::iter::once(pubkey_dest)
+                .chain(pubkeys_auditor)
+                .collect();
+            AggregatedValidityProof::new(
+                &pubkeys_validity,
+                (transfer_amount_lo, transfer_amount_hi),
+                (opening_lo, opening_hi),
+                &mut transcript.fork(b"ciphertext-amount-validity-proof"),
+            )
+        };
+
+        let generate_fee_sigma_proof = || {
+            FeeSigmaProof::new(
+                (fee_amount, &ciphertext_fee.commitment, opening_fee),
+                (delta_fee, &commitment_delta, &opening_delta),
+                (&commitment_claimed, &opening_claimed),
+                fee_parameters.maximum_fee,
+                &mut transcript.fork(b"fee-sigma-proof"),
+            )
+        };
+
+        let generate_ciphertext_fee_validity_proof = || {
+            ValidityProof::new(
+                &[pubkey_dest, pubkey_fee_collector],
+                fee_amount,
+                opening_fee,
+                &mut transcript.fork(b"ciphertext-fee-validity-proof"),
+            )
+        };
+
+        let generate_range_proof = || {
+            RangeProof::new(
+                vec![
+                    source_new_balance,
+                    transfer_amount_lo as u64,
+                    transfer_amount_hi as u64,
+                    delta_fee,
+                    delta_fee_complement,
+                ],
+                vec![
+                    64, 32, 32, 64, // double check
+                    64,
+                ],
+                vec![
+                    &opening_source,
+                    opening_lo,
+                    opening_hi,
+                    &opening_claimed,
+                    &opening_claimed_negated,
+                ],
+                &mut transcript.fork(b"range-proof"),
+            )
+        };
+
+        let (
+            equality_proof,
+            ciphertext_amount_validity_proof,
+            fee_sigma_proof,
+            ciphertext_fee_validity_proof,
+            range_proof,
+        ) = match cancellation {
+            None => {
+                // the range proof dominates the cost, so it runs alongside all of the sigma proofs
+                let (
+                    (
+                        (equality_proof, ciphertext_amount_validity_proof),
+                        (fee_sigma_proof, ciphertext_fee_validity_proof),
+                    ),
+                    range_proof,
+                ) = join(
+                    || {
+                        join(
+                            || {
+                                join(
+                                    generate_equality_proof,
+                                    generate_ciphertext_amount_validity_proof,
+                                )
+                            },
+                            || {
+                                join(
+                                    generate_fee_sigma_proof,
+                                    generate_ciphertext_fee_validity_proof,
+                                )
+                            },
+                        )
+                    },
+                    generate_range_proof,
+                );
+                (
+                    equality_proof,
+                    ciphertext_amount_validity_proof,
+                    fee_sigma_proof,
+                    ciphertext_fee_validity_proof,
+                    range_proof,
+                )
+            }
+            Some(cancellation) => {
+                checkpoint(cancellation).await?;
+                let equality_proof = generate_equality_proof();
+                checkpoint(cancellation).await?;
+                let ciphertext_amount_validity_proof = generate_ciphertext_amount_validity_proof();
+                checkpoint(cancellation).await?;
+                let fee_sigma_proof = generate_fee_sigma_proof();
+                checkpoint(cancellation).await?;
+                let ciphertext_fee_validity_proof = generate_ciphertext_fee_validity_proof();
+                checkpoint(cancellation).await?;
+                let range_proof = generate_range_proof();
+                (
+                    equality_proof,
+                    ciphertext_amount_validity_proof,
+                    fee_sigma_proof,
+                    ciphertext_fee_validity_proof,
+                    range_proof,
+                )
+            }
+        };
+
+        Ok(Self {
+            version: Self::VERSION,
             commitment_new_source: pod_commitment_new_source,
             commitment_claimed: pod_commitment_claimed,
             equality_proof: equality_proof.into(),
-            ciphertext_amount_validity_proof: ciphertext_amount_validity_proof.into(),
+            ciphertext_amount_validity_proof: ciphertext_amount_validity_proof.try_into()?,
             fee_sigma_proof: fee_sigma_proof.into(),
-            ciphertext_fee_validity_proof: ciphertext_fee_validity_proof.into(),
-            range_proof: range_proof.try_into().expect("range proof: length error"),
-        }
+            ciphertext_fee_validity_proof: ciphertext_fee_validity_proof.try_into()?,
+            range_proof: range_proof?.try_into()?,
+        })
     }
 
-    pub fn verify(
+    pub fn verify_batched(
         &self,
         ciphertext_lo: &TransferAmountEncryption,
         ciphertext_hi: &TransferAmountEncryption,
@@ -391,39 +705,59 @@ impl TransferWithFeeProof {
         ciphertext_fee: &FeeEncryption,
         fee_parameters: FeeParameters,
         transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        fee_parameters.validate()?;
+
         transcript.append_commitment(b"commitment-new-source", &self.commitment_new_source);
         transcript.append_commitment(b"commitment-claimed", &self.commitment_claimed);
 
-        let commitment_new_source: PedersenCommitment = self.commitment_new_source.try_into()?;
-        let commitment_claimed: PedersenCommitment = self.commitment_claimed.try_into()?;
+        let commitment_new_source: PedersenCommitment =
+            decode_field(self.commitment_new_source, "commitment_new_source")?;
+        let commitment_claimed: PedersenCommitment =
+            decode_field(self.commitment_claimed, "commitment_claimed")?;
 
         let equality_proof: EqualityProof = self.equality_proof.try_into()?;
-        let ciphertext_amount_validity_proof: AggregatedValidityProof =
-            self.ciphertext_amount_validity_proof.try_into()?;
         let fee_sigma_proof: FeeSigmaProof = self.fee_sigma_proof.try_into()?;
         let ciphertext_fee_validity_proof: ValidityProof =
             self.ciphertext_fee_validity_proof.try_into()?;
         let range_proof: RangeProof = self.range_proof.try_into()?;
 
+        let num_auditors = num_auditors(
+            &transfer_with_fee_pubkeys.auditors,
+            &[&ciphertext_lo.auditors, &ciphertext_hi.auditors],
+        )?;
+        let ciphertext_amount_validity_proof = self
+            .ciphertext_amount_validity_proof
+            .to_proof(num_auditors + 1)?;
+
         // verify equality proof
-        equality_proof.verify(
+        equality_proof.verify_batched(
             &transfer_with_fee_pubkeys.source,
             new_spendable_ciphertext,
             &commitment_new_source,
-            transcript,
+            &mut transcript.fork(b"equality-proof"),
+            batch,
         )?;
 
         // verify that the transfer amount is encrypted correctly
-        ciphertext_amount_validity_proof.verify(
-            (
-                &transfer_with_fee_pubkeys.dest,
-                &transfer_with_fee_pubkeys.auditor,
-            ),
+        let pubkeys: Vec<&ElGamalPubkey> = std::iter::once(&transfer_with_fee_pubkeys.dest)
+            .chain(&transfer_with_fee_pubkeys.auditors[..num_auditors])
+            .collect();
+        let handles_lo: Vec<&DecryptHandle> = std::iter::once(&ciphertext_lo.dest)
+            .chain(&ciphertext_lo.auditors[..num_auditors])
+            .collect();
+        let handles_hi: Vec<&DecryptHandle> = std::iter::once(&ciphertext_hi.dest)
+            .chain(&ciphertext_hi.auditors[..num_auditors])
+            .collect();
+        ciphertext_amount_validity_proof.verify_batched(
+            &pubkeys,
             (&ciphertext_lo.commitment, &ciphertext_hi.commitment),
-            (&ciphertext_lo.dest, &ciphertext_hi.dest),
-            (&ciphertext_lo.auditor, &ciphertext_hi.auditor),
-            transcript,
+            (&handles_lo, &handles_hi),
+            &mut transcript.fork(b"ciphertext-amount-validity-proof"),
+            batch,
         )?;
 
         // verify fee sigma proof
@@ -431,29 +765,32 @@ impl TransferWithFeeProof {
             &ciphertext_lo.commitment,
             &ciphertext_hi.commitment,
             &ciphertext_fee.commitment,
-            fee_parameters.fee_rate_basis_points,
+            &fee_parameters,
         );
 
-        fee_sigma_proof.verify(
+        fee_sigma_proof.verify_batched(
             &ciphertext_fee.commitment,
             &commitment_delta,
             &commitment_claimed,
             fee_parameters.maximum_fee,
-            transcript,
+            &mut transcript.fork(b"fee-sigma-proof"),
+            batch,
         )?;
 
-        ciphertext_fee_validity_proof.verify(
+        ciphertext_fee_validity_proof.verify_batched(
             &ciphertext_fee.commitment,
-            (
+            &[
                 &transfer_with_fee_pubkeys.dest,
                 &transfer_with_fee_pubkeys.fee_collector,
-            ),
-            (&ciphertext_fee.dest, &ciphertext_fee.fee_collector),
-            transcript,
+            ],
+            &[&ciphertext_fee.dest, &ciphertext_fee.fee_collector],
+            &mut transcript.fork(b"ciphertext-fee-validity-proof"),
+            batch,
         )?;
 
-        let commitment_claimed_negated = &(*COMMITMENT_FEE_DENOMINATOR) - &commitment_claimed;
-        range_proof.verify(
+        let commitment_claimed_negated =
+            &Pedersen::encode(fee_parameters.fee_denominator) - &commitment_claimed;
+        range_proof.verify_batched(
             vec![
                 &commitment_new_source,
                 &ciphertext_lo.commitment,
@@ -462,7 +799,8 @@ impl TransferWithFeeProof {
                 &commitment_claimed_negated,
             ],
             vec![64, 32, 32, 64, 64],
-            transcript,
+            &mut transcript.fork(b"range-proof"),
+            batch,
         )?;
 
         Ok(())
@@ -476,35 +814,43 @@ impl TransferWithFeeProof {
 pub struct TransferWithFeePubkeys {
     pub source: ElGamalPubkey,
     pub dest: ElGamalPubkey,
-    pub auditor: ElGamalPubkey,
+    /// The pubkeys of the auditors, followed by zeroed pubkeys for the unused auditor slots
+    pub auditors: [ElGamalPubkey; MAX_AUDITORS],
     pub fee_collector: ElGamalPubkey,
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl TransferWithFeePubkeys {
-    pub fn to_bytes(&self) -> [u8; 128] {
-        let mut bytes = [0u8; 128];
+    pub fn to_bytes(&self) -> [u8; 32 * (MAX_AUDITORS + 3)] {
+        let mut bytes = [0u8; 32 * (MAX_AUDITORS + 3)];
         bytes[..32].copy_from_slice(&self.source.to_bytes());
         bytes[32..64].copy_from_slice(&self.dest.to_bytes());
-        bytes[64..96].copy_from_slice(&self.auditor.to_bytes());
-        bytes[96..128].copy_from_slice(&self.fee_collector.to_bytes());
+        for (chunk, auditor) in bytes[64..].chunks_mut(32).zip(&self.auditors) {
+            chunk.copy_from_slice(&auditor.to_bytes());
+        }
+        bytes[32 * (MAX_AUDITORS + 2)..].copy_from_slice(&self.fee_collector.to_bytes());
         bytes
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
-        let bytes = array_ref![bytes, 0, 128];
-        let (source, dest, auditor, fee_collector) = array_refs![bytes, 32, 32, 32, 32];
-
-        let source = ElGamalPubkey::from_bytes(source).ok_or(ProofError::Verification)?;
-        let dest = ElGamalPubkey::from_bytes(dest).ok_or(ProofError::Verification)?;
-        let auditor = ElGamalPubkey::from_bytes(auditor).ok_or(ProofError::Verification)?;
+        let bytes = array_ref![bytes, 0, 32 * (MAX_AUDITORS + 3)];
+        let (source, dest, auditors, fee_collector) =
+            array_refs![bytes, 32, 32, 32 * MAX_AUDITORS, 32];
+
+        let source = ElGamalPubkey::from_bytes(source).ok_or(ProofError::NonCanonical)?;
+        let dest = ElGamalPubkey::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
+        let mut auditor_pubkeys = [ElGamalPubkey::default(); MAX_AUDITORS];
+        for (pubkey, bytes) in auditor_pubkeys.iter_mut().zip(auditors.chunks(32)) {
+            *pubkey = ElGamalPubkey::from_bytes(array_ref![bytes, 0, 32])
+                .ok_or(ProofError::NonCanonical)?;
+        }
         let fee_collector =
-            ElGamalPubkey::from_bytes(fee_collector).ok_or(ProofError::Verification)?;
+            ElGamalPubkey::from_bytes(fee_collector).ok_or(ProofError::NonCanonical)?;
 
         Ok(Self {
             source,
             dest,
-            auditor,
+            auditors: auditor_pubkeys,
             fee_collector,
         })
     }
@@ -515,15 +861,18 @@ impl pod::TransferWithFeePubkeys {
     pub fn new(
         source: &ElGamalPubkey,
         dest: &ElGamalPubkey,
-        auditor: &ElGamalPubkey,
+        auditors: &[ElGamalPubkey; MAX_AUDITORS],
         fee_collector: &ElGamalPubkey,
     ) -> Self {
-        let mut bytes = [0u8; 128];
-        bytes[..32].copy_from_slice(&source.to_bytes());
-        bytes[32..64].copy_from_slice(&dest.to_bytes());
-        bytes[64..96].copy_from_slice(&auditor.to_bytes());
-        bytes[96..128].copy_from_slice(&fee_collector.to_bytes());
-        Self(bytes)
+        Self(
+            TransferWithFeePubkeys {
+                source: *source,
+                dest: *dest,
+                auditors: *auditors,
+                fee_collector: *fee_collector,
+            }
+            .to_bytes(),
+        )
     }
 }
 
@@ -566,10 +915,10 @@ impl FeeEncryption {
         let (commitment, dest, fee_collector) = array_refs![bytes, 32, 32, 32];
 
         let commitment =
-            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::Verification)?;
-        let dest = DecryptHandle::from_bytes(dest).ok_or(ProofError::Verification)?;
+            PedersenCommitment::from_bytes(commitment).ok_or(ProofError::NonCanonical)?;
+        let dest = DecryptHandle::from_bytes(dest).ok_or(ProofError::NonCanonical)?;
         let fee_collector =
-            DecryptHandle::from_bytes(fee_collector).ok_or(ProofError::Verification)?;
+            DecryptHandle::from_bytes(fee_collector).ok_or(ProofError::NonCanonical)?;
 
         Ok(Self {
             commitment,
@@ -582,60 +931,81 @@ impl FeeEncryption {
 #[derive(Clone, Copy)]
 #[repr(C)]
 pub struct FeeParameters {
-    /// Fee rate expressed as basis points of the transfer amount, i.e. increments of 0.01%
-    pub fee_rate_basis_points: u16,
+    /// Fee rate expressed in increments of `1 / fee_denominator` of the transfer amount
+    pub fee_rate: u64,
     /// Maximum fee assessed on transfers, expressed as an amount of tokens
     pub maximum_fee: u64,
+    /// Denominator of the fee rate, e.g. `FEE_DENOMINATOR_BASIS_POINTS` for a fee rate in basis
+    /// points, 1000 for per-mille or 1000000 for per-million
+    pub fee_denominator: u64,
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl FeeParameters {
-    pub fn to_bytes(&self) -> [u8; 10] {
-        let mut bytes = [0u8; 10];
-        bytes[..2].copy_from_slice(&self.fee_rate_basis_points.to_le_bytes());
-        bytes[2..10].copy_from_slice(&self.maximum_fee.to_le_bytes());
+    /// Creates fee parameters with a fee rate expressed in basis points.
+    ///
+    /// Fails if the fee rate exceeds 100%.
+    pub fn new(fee_rate_basis_points: u16, maximum_fee: u64) -> Result<Self, ProofError> {
+        let fee_parameters = Self {
+            fee_rate: u64::from(fee_rate_basis_points),
+            maximum_fee,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
+        };
+        fee_parameters.validate()?;
 
-        bytes
+        Ok(fee_parameters)
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Self {
-        let bytes = array_ref![bytes, 0, 10];
-        let (fee_rate_basis_points, maximum_fee) = array_refs![bytes, 2, 8];
-
-        Self {
-            fee_rate_basis_points: u16::from_le_bytes(*fee_rate_basis_points),
-            maximum_fee: u64::from_le_bytes(*maximum_fee),
+    /// Checks that a fee can be calculated and proven for these parameters: the fee denominator
+    /// must be nonzero and the fee rate must not exceed 100%.
+    pub fn validate(&self) -> Result<(), ProofError> {
+        if self.fee_denominator == 0 || self.fee_rate > self.fee_denominator {
+            return Err(ProofError::InvalidFeeParameters);
         }
+        Ok(())
     }
-}
 
-#[cfg(not(target_arch = "bpf"))]
-fn calculate_fee(transfer_amount: u64, fee_rate_basis_points: u16) -> (u64, u64) {
-    let fee_scaled = (transfer_amount as u128) * (fee_rate_basis_points as u128);
+    pub fn to_bytes(&self) -> [u8; 24] {
+        let mut bytes = [0u8; 24];
+        bytes[..8].copy_from_slice(&self.fee_rate.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.maximum_fee.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.fee_denominator.to_le_bytes());
 
-    let fee = (fee_scaled / FEE_DENOMINATOR as u128) as u64;
-    let rem = (fee_scaled % FEE_DENOMINATOR as u128) as u64;
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != 24 {
+            return Err(ProofError::InvalidFeeParameters);
+        }
+        let bytes = array_ref![bytes, 0, 24];
+        let (fee_rate, maximum_fee, fee_denominator) = array_refs![bytes, 8, 8, 8];
 
-    if rem == 0 {
-        (fee, rem)
-    } else {
-        (fee + 1, rem)
+        let fee_parameters = Self {
+            fee_rate: u64::from_le_bytes(*fee_rate),
+            maximum_fee: u64::from_le_bytes(*maximum_fee),
+            fee_denominator: u64::from_le_bytes(*fee_denominator),
+        };
+        fee_parameters.validate()?;
+
+        Ok(fee_parameters)
     }
 }
 
-#[cfg(not(target_arch = "bpf"))]
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
 fn compute_delta_commitment_and_opening(
     (commitment_lo, opening_lo): (&PedersenCommitment, &PedersenOpening),
     (commitment_hi, opening_hi): (&PedersenCommitment, &PedersenOpening),
     (commitment_fee, opening_fee): (&PedersenCommitment, &PedersenOpening),
-    fee_rate_basis_points: u16,
+    fee_parameters: &FeeParameters,
 ) -> (PedersenCommitment, PedersenOpening) {
-    let fee_rate_scalar = Scalar::from(fee_rate_basis_points);
+    let fee_rate_scalar = Scalar::from(fee_parameters.fee_rate);
+    let fee_denominator_scalar = Scalar::from(fee_parameters.fee_denominator);
 
-    let commitment_delta = commitment_fee * Scalar::from(FEE_DENOMINATOR)
+    let commitment_delta = commitment_fee * fee_denominator_scalar
         - &(&combine_u32_commitments(commitment_lo, commitment_hi) * &fee_rate_scalar);
 
-    let opening_delta = opening_fee * Scalar::from(FEE_DENOMINATOR)
+    let opening_delta = opening_fee * fee_denominator_scalar
         - &(&combine_u32_openings(opening_lo, opening_hi) * &fee_rate_scalar);
 
     (commitment_delta, opening_delta)
@@ -646,14 +1016,33 @@ fn compute_delta_commitment(
     commitment_lo: &PedersenCommitment,
     commitment_hi: &PedersenCommitment,
     commitment_fee: &PedersenCommitment,
-    fee_rate_basis_points: u16,
+    fee_parameters: &FeeParameters,
 ) -> PedersenCommitment {
-    let fee_rate_scalar = Scalar::from(fee_rate_basis_points);
+    let fee_rate_scalar = Scalar::from(fee_parameters.fee_rate);
 
-    commitment_fee * Scalar::from(FEE_DENOMINATOR)
+    commitment_fee * Scalar::from(fee_parameters.fee_denominator)
         - &(&combine_u32_commitments(commitment_lo, commitment_hi) * &fee_rate_scalar)
 }
 
+/// Runs `a` and `b` on the rayon thread pool, or one after the other on targets without threads
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        rayon::join(a, b)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        (a(), b())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -671,20 +1060,213 @@ mod test {
         let transfer_amount: u64 = 100;
 
         let fee_parameters = FeeParameters {
-            fee_rate_basis_points: 100,
+            fee_rate: 100,
             maximum_fee: 3,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
+        };
+
+        let fee_data = TransferWithFeeData::new(
+            transfer_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &keypair_source,
+            (&pubkey_dest, &[pubkey_auditor]),
+            fee_parameters,
+            &pubkey_fee_collector,
+        )
+        .unwrap();
+
+        assert!(fee_data.verify().is_ok());
+    }
+
+    #[test]
+    fn test_decrypt_fee() {
+        let keypair_source = ElGamalKeypair::new_rand();
+        let keypair_dest = ElGamalKeypair::new_rand();
+        let keypair_auditor = ElGamalKeypair::new_rand();
+        let keypair_fee_collector = ElGamalKeypair::new_rand();
+
+        let spendable_balance: u64 = 10_000;
+        let spendable_ciphertext = keypair_source.public.encrypt(spendable_balance);
+
+        let transfer_amount: u64 = 5_000;
+
+        let fee_parameters = FeeParameters {
+            fee_rate: 100,
+            maximum_fee: 70,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
         };
 
         let fee_data = TransferWithFeeData::new(
             transfer_amount,
             (spendable_balance, &spendable_ciphertext),
             &keypair_source,
-            (&pubkey_dest, &pubkey_auditor),
+            (&keypair_dest.public, &[keypair_auditor.public]),
+            fee_parameters,
+            &keypair_fee_collector.public,
+        )
+        .unwrap();
+
+        // 1% of the transfer amount is below the maximum fee
+        assert_eq!(
+            fee_data
+                .decrypt_fee(Role::FeeCollector, &keypair_fee_collector.secret)
+                .unwrap(),
+            50
+        );
+        assert_eq!(
+            fee_data
+                .decrypt_fee(Role::Dest, &keypair_dest.secret)
+                .unwrap(),
+            50
+        );
+
+        // the fee is not encrypted for the source or the auditors, and the fee collector cannot
+        // decrypt the transfer amount
+        assert_eq!(
+            fee_data.decrypt_fee(Role::Source, &keypair_source.secret),
+            Err(DecryptionError::WrongRole)
+        );
+        assert_eq!(
+            fee_data.decrypt_fee(Role::Auditor(0), &keypair_auditor.secret),
+            Err(DecryptionError::WrongRole)
+        );
+        assert_eq!(
+            fee_data.decrypt_amount(Role::FeeCollector, &keypair_fee_collector.secret),
+            Err(DecryptionError::WrongRole)
+        );
+    }
+
+    #[test]
+    fn test_fee_denominator() {
+        let keypair_source = ElGamalKeypair::new_rand();
+        let pubkey_dest = ElGamalKeypair::new_rand().public;
+        let pubkey_auditor = ElGamalKeypair::new_rand().public;
+        let pubkey_fee_collector = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 2_000_000;
+        let spendable_ciphertext = keypair_source.public.encrypt(spendable_balance);
+
+        // a fee that is not a whole number of tokens, 30.864175, is rounded up
+        let transfer_amount: u64 = 1_234_567;
+
+        // 0.0025% fee rate, which cannot be expressed in basis points
+        let fee_parameters = FeeParameters {
+            fee_rate: 25,
+            maximum_fee: 1_000,
+            fee_denominator: 1_000_000,
+        };
+        assert_eq!(
+            calculate_fee(1_240_000, &fee_parameters, RoundingMode::Ceil),
+            Some((31, 0))
+        );
+        assert_eq!(
+            calculate_fee(transfer_amount, &fee_parameters, RoundingMode::Ceil),
+            Some((31, 135_825))
+        );
+
+        let mut fee_data = TransferWithFeeData::new(
+            transfer_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &keypair_source,
+            (&pubkey_dest, &[pubkey_auditor]),
             fee_parameters,
             &pubkey_fee_collector,
         )
         .unwrap();
+        assert!(fee_data.verify().is_ok());
+
+        // the fee parameters are bound to the proof
+        fee_data.context.fee_parameters = FeeParameters {
+            fee_denominator: 100_000,
+            ..fee_parameters
+        }
+        .into();
+        assert!(fee_data.verify().is_err());
 
+        let fee_parameters = FeeParameters {
+            fee_denominator: 0,
+            ..fee_parameters
+        };
+        assert!(TransferWithFeeData::new(
+            transfer_amount,
+            (spendable_balance, &spendable_ciphertext),
+            &keypair_source,
+            (&pubkey_dest, &[pubkey_auditor]),
+            fee_parameters,
+            &pubkey_fee_collector,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fee_parameters_validation() {
+        let fee_parameters = FeeParameters::new(250, 1_000).unwrap();
+        assert_eq!(fee_parameters.fee_denominator, FEE_DENOMINATOR_BASIS_POINTS);
+
+        let decoded = FeeParameters::from_bytes(&fee_parameters.to_bytes()).unwrap();
+        assert_eq!(decoded.fee_rate, 250);
+        assert_eq!(decoded.maximum_fee, 1_000);
+
+        // the fee rate cannot exceed 100%
+        assert!(FeeParameters::new(10_000, 1_000).is_ok());
+        assert_eq!(
+            FeeParameters::new(10_001, 1_000).err(),
+            Some(ProofError::InvalidFeeParameters)
+        );
+
+        // the encoding must be exactly 24 bytes and the fee denominator must be nonzero
+        assert!(FeeParameters::from_bytes(&[]).is_err());
+        assert!(FeeParameters::from_bytes(&fee_parameters.to_bytes()[..23]).is_err());
+
+        let fee_parameters = FeeParameters {
+            fee_rate: 0,
+            maximum_fee: 0,
+            fee_denominator: 0,
+        };
+        assert!(FeeParameters::from_bytes(&fee_parameters.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_fee_cancellation() {
+        let keypair_source = ElGamalKeypair::new_rand();
+        let pubkey_dest = ElGamalKeypair::new_rand().public;
+        let pubkey_fee_collector = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 120;
+        let spendable_ciphertext = keypair_source.public.encrypt(spendable_balance);
+
+        let fee_parameters = FeeParameters {
+            fee_rate: 100,
+            maximum_fee: 3,
+            fee_denominator: FEE_DENOMINATOR_BASIS_POINTS,
+        };
+
+        let cancellation = CancellationToken::new();
+        let fee_data = block_on(TransferWithFeeData::new_async(
+            100,
+            (spendable_balance, &spendable_ciphertext),
+            &keypair_source,
+            (&pubkey_dest, &[]),
+            fee_parameters,
+            &pubkey_fee_collector,
+            &cancellation,
+        ))
+        .unwrap();
         assert!(fee_data.verify().is_ok());
+
+        cancellation.cancel();
+        assert_eq!(
+            block_on(TransferWithFeeData::new_async(
+                100,
+                (spendable_balance, &spendable_ciphertext),
+                &keypair_source,
+                (&pubkey_dest, &[]),
+                fee_parameters,
+                &pubkey_fee_collector,
+                &cancellation,
+            ))
+            .err(),
+            Some(ProofError::Cancelled)
+        );
     }
 }
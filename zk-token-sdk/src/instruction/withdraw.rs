@@ -1,3 +1,12 @@
+#[cfg(feature = "prover")]
+use crate::{
+    encryption::{
+        discrete_log::DECODE_U32_PRECOMPUTATION_FOR_G,
+        elgamal::{ElGamal, ElGamalKeypair, ElGamalSecretKey},
+        pedersen::Pedersen,
+    },
+    errors::DecryptionError,
+};
 use {
     crate::zk_token_elgamal::pod,
     bytemuck::{Pod, Zeroable},
@@ -5,12 +14,13 @@ use {
 #[cfg(not(target_arch = "bpf"))]
 use {
     crate::{
+        batch::MultiscalarMulBatch,
         encryption::{
-            elgamal::{ElGamal, ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
-            pedersen::{Pedersen, PedersenCommitment},
+            elgamal::{ElGamalCiphertext, ElGamalPubkey},
+            pedersen::PedersenCommitment,
         },
         errors::ProofError,
-        instruction::Verifiable,
+        instruction::{decode_field, ProofVersion, Verifiable},
         range_proof::RangeProof,
         sigma_proofs::equality_proof::EqualityProof,
         transcript::TranscriptProtocol,
@@ -28,20 +38,44 @@ use {
 ///
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 pub struct WithdrawData {
+    /// The context data needed to verify the proof
+    pub context: WithdrawProofContext,
+
+    /// Range proof
+    pub proof: WithdrawProof, // 737 bytes
+}
+
+/// The public inputs of a `WithdrawData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct WithdrawProofContext {
     /// The source account ElGamal pubkey
     pub pubkey: pod::ElGamalPubkey, // 32 bytes
 
     /// The source account available balance *after* the withdraw (encrypted by
     /// `source_pk`
     pub final_ciphertext: pod::ElGamalCiphertext, // 64 bytes
-
-    /// Range proof
-    pub proof: WithdrawProof, // 736 bytes
 }
 
 impl WithdrawData {
-    #[cfg(not(target_arch = "bpf"))]
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
     pub fn new(
         amount: u64,
         keypair: &ElGamalKeypair,
@@ -62,25 +96,51 @@ impl WithdrawData {
         let pod_pubkey = pod::ElGamalPubkey((&keypair.public).to_bytes());
         let pod_final_ciphertext: pod::ElGamalCiphertext = final_ciphertext.into();
         let mut transcript = WithdrawProof::transcript_new(&pod_pubkey, &pod_final_ciphertext);
-        let proof = WithdrawProof::new(keypair, final_balance, &final_ciphertext, &mut transcript);
+        let proof = WithdrawProof::new(keypair, final_balance, &final_ciphertext, &mut transcript)?;
 
         Ok(Self {
-            pubkey: pod_pubkey,
-            final_ciphertext: pod_final_ciphertext,
+            context: WithdrawProofContext {
+                pubkey: pod_pubkey,
+                final_ciphertext: pod_final_ciphertext,
+            },
             proof,
         })
     }
+
+    /// Decrypts the source account available balance after the withdraw
+    ///
+    /// Only balances that fit in 32 bits can be recovered with the pre-computed discrete log table.
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+    pub fn decrypt_final_balance(&self, sk: &ElGamalSecretKey) -> Result<u64, DecryptionError> {
+        let final_ciphertext: ElGamalCiphertext = self
+            .context
+            .final_ciphertext
+            .try_into()
+            .map_err(|_| DecryptionError::MalformedCiphertext)?;
+
+        let final_balance =
+            final_ciphertext.decrypt_u32_online(sk, &DECODE_U32_PRECOMPUTATION_FOR_G);
+        Option::<u32>::from(final_balance)
+            .map(u64::from)
+            .ok_or(DecryptionError::DiscreteLogNotFound)
+    }
 }
 
 #[cfg(not(target_arch = "bpf"))]
 impl Verifiable for WithdrawData {
-    fn verify(&self) -> Result<(), ProofError> {
-        let mut transcript = WithdrawProof::transcript_new(&self.pubkey, &self.final_ciphertext);
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript =
+            WithdrawProof::transcript_new(&self.context.pubkey, &self.context.final_ciphertext);
 
-        let elgamal_pubkey = self.pubkey.try_into()?;
-        let final_balance_ciphertext = self.final_ciphertext.try_into()?;
-        self.proof
-            .verify(&elgamal_pubkey, &final_balance_ciphertext, &mut transcript)
+        let elgamal_pubkey = decode_field(self.context.pubkey, "pubkey")?;
+        let final_balance_ciphertext =
+            decode_field(self.context.final_ciphertext, "final_ciphertext")?;
+        self.proof.verify_batched(
+            &elgamal_pubkey,
+            &final_balance_ciphertext,
+            &mut transcript,
+            batch,
+        )
     }
 }
 
@@ -88,8 +148,19 @@ impl Verifiable for WithdrawData {
 /// for withdrawal
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
 #[allow(non_snake_case)]
 pub struct WithdrawProof {
+    /// Version of the proof format
+    pub version: u8,
+
     /// New Pedersen commitment
     pub commitment: pod::PedersenCommitment,
 
@@ -103,11 +174,11 @@ pub struct WithdrawProof {
 #[allow(non_snake_case)]
 #[cfg(not(target_arch = "bpf"))]
 impl WithdrawProof {
-    fn transcript_new(
+    pub fn transcript_new(
         pubkey: &pod::ElGamalPubkey,
         ciphertext: &pod::ElGamalCiphertext,
     ) -> Transcript {
-        let mut transcript = Transcript::new(b"WithdrawProof");
+        let mut transcript = Transcript::new(b"WithdrawProof-v1");
 
         transcript.append_pubkey(b"pubkey", pubkey);
         transcript.append_ciphertext(b"ciphertext", ciphertext);
@@ -115,12 +186,13 @@ impl WithdrawProof {
         transcript
     }
 
+    #[cfg(feature = "prover")]
     pub fn new(
         keypair: &ElGamalKeypair,
         final_balance: u64,
         final_ciphertext: &ElGamalCiphertext,
         transcript: &mut Transcript,
-    ) -> Self {
+    ) -> Result<Self, ProofError> {
         // generate a Pedersen commitment for `final_balance`
         let (commitment, opening) = Pedersen::new(final_balance);
         let pod_commitment: pod::PedersenCommitment = commitment.into();
@@ -137,36 +209,38 @@ impl WithdrawProof {
         );
 
         let range_proof =
-            RangeProof::new(vec![final_balance], vec![64], vec![&opening], transcript);
+            RangeProof::new(vec![final_balance], vec![64], vec![&opening], transcript)?;
 
-        WithdrawProof {
+        Ok(WithdrawProof {
+            version: Self::VERSION,
             commitment: pod_commitment,
-            equality_proof: equality_proof.try_into().expect("equality proof"),
-            range_proof: range_proof.try_into().expect("range proof"),
-        }
+            equality_proof: equality_proof.into(),
+            range_proof: range_proof.try_into()?,
+        })
     }
 
-    pub fn verify(
+    pub fn verify_batched(
         &self,
         pubkey: &ElGamalPubkey,
         final_ciphertext: &ElGamalCiphertext,
         transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), ProofError> {
+        self.check_version()?;
+
         transcript.append_commitment(b"commitment", &self.commitment);
 
-        let commitment: PedersenCommitment = self.commitment.try_into()?;
+        let commitment: PedersenCommitment = decode_field(self.commitment, "commitment")?;
         let equality_proof: EqualityProof = self.equality_proof.try_into()?;
         let range_proof: RangeProof = self.range_proof.try_into()?;
 
         // verify equality proof
-        //
-        // TODO: we can also consider verifying equality and range proof in a batch
-        equality_proof.verify(pubkey, final_ciphertext, &commitment, transcript)?;
+        equality_proof.verify_batched(pubkey, final_ciphertext, &commitment, transcript, batch)?;
 
         // verify range proof
         //
         // TODO: double compressing here - consider modifying range proof input type to `PedersenCommitment`
-        range_proof.verify(vec![&commitment], vec![64_usize], transcript)?;
+        range_proof.verify_batched(vec![&commitment], vec![64_usize], transcript, batch)?;
 
         Ok(())
     }
@@ -194,6 +268,7 @@ mod test {
         )
         .unwrap();
         assert!(data.verify().is_ok());
+        assert_eq!(data.decrypt_final_balance(&keypair.secret).unwrap(), 22_u64);
 
         // generate and verify proof with wrong balance
         let wrong_balance: u64 = 99;
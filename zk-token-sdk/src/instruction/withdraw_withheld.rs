@@ -0,0 +1,274 @@
+#[cfg(feature = "prover")]
+use crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening};
+use {
+    crate::zk_token_elgamal::pod,
+    bytemuck::{Pod, Zeroable},
+};
+#[cfg(not(target_arch = "bpf"))]
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        errors::ProofError,
+        instruction::{decode_field, ProofVersion, Verifiable},
+        sigma_proofs::equality_proof::CtxtCtxtEqualityProof,
+        transcript::TranscriptProtocol,
+    },
+    merlin::Transcript,
+    std::convert::TryInto,
+};
+
+/// This struct includes the cryptographic proof *and* the account data information needed to verify
+/// the proof
+///
+/// - The pre-instruction should call WithdrawWithheldTokensData::verify(&self)
+/// - The actual program should check that `ciphertext_withdraw_withheld_authority` is consistent
+///   with the withheld fees that are currently stored in the token accounts
+///
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct WithdrawWithheldTokensData {
+    /// The context data needed to verify the proof
+    pub context: WithdrawWithheldTokensProofContext,
+
+    /// Proof that the two ciphertexts encrypt the same amount
+    pub proof: WithdrawWithheldTokensProof, // 225 bytes
+}
+
+/// The public inputs of a `WithdrawWithheldTokensData` proof
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct WithdrawWithheldTokensProofContext {
+    /// The ElGamal pubkey of the withdraw-withheld authority (the fee collector)
+    pub pubkey_withdraw_withheld_authority: pod::ElGamalPubkey, // 32 bytes
+
+    /// The ElGamal pubkey of the destination account
+    pub pubkey_dest: pod::ElGamalPubkey, // 32 bytes
+
+    /// The withheld fees encrypted under the withdraw-withheld authority pubkey
+    pub ciphertext_withdraw_withheld_authority: pod::ElGamalCiphertext, // 64 bytes
+
+    /// The withheld fees re-encrypted under the destination pubkey
+    pub ciphertext_dest: pod::ElGamalCiphertext, // 64 bytes
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl WithdrawWithheldTokensData {
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair_withdraw_withheld_authority: &ElGamalKeypair,
+        pubkey_dest: &ElGamalPubkey,
+        ciphertext_withdraw_withheld_authority: &ElGamalCiphertext,
+        amount: u64,
+    ) -> Result<Self, ProofError> {
+        // re-encrypt the withheld amount under the destination pubkey
+        let opening_dest = PedersenOpening::new_rand();
+        let ciphertext_dest = pubkey_dest.encrypt_with(amount, &opening_dest);
+
+        let pod_pubkey_withdraw_withheld_authority =
+            pod::ElGamalPubkey(keypair_withdraw_withheld_authority.public.to_bytes());
+        let pod_pubkey_dest = pod::ElGamalPubkey(pubkey_dest.to_bytes());
+        let pod_ciphertext_withdraw_withheld_authority: pod::ElGamalCiphertext =
+            (*ciphertext_withdraw_withheld_authority).into();
+        let pod_ciphertext_dest: pod::ElGamalCiphertext = ciphertext_dest.into();
+
+        let mut transcript = WithdrawWithheldTokensProof::transcript_new(
+            &pod_pubkey_withdraw_withheld_authority,
+            &pod_pubkey_dest,
+            &pod_ciphertext_withdraw_withheld_authority,
+            &pod_ciphertext_dest,
+        );
+
+        let proof = WithdrawWithheldTokensProof::new(
+            keypair_withdraw_withheld_authority,
+            pubkey_dest,
+            ciphertext_withdraw_withheld_authority,
+            amount,
+            &opening_dest,
+            &mut transcript,
+        );
+
+        Ok(Self {
+            context: WithdrawWithheldTokensProofContext {
+                pubkey_withdraw_withheld_authority: pod_pubkey_withdraw_withheld_authority,
+                pubkey_dest: pod_pubkey_dest,
+                ciphertext_withdraw_withheld_authority: pod_ciphertext_withdraw_withheld_authority,
+                ciphertext_dest: pod_ciphertext_dest,
+            },
+            proof,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl Verifiable for WithdrawWithheldTokensData {
+    fn verify_batched(&self, batch: &mut MultiscalarMulBatch) -> Result<(), ProofError> {
+        let mut transcript = WithdrawWithheldTokensProof::transcript_new(
+            &self.context.pubkey_withdraw_withheld_authority,
+            &self.context.pubkey_dest,
+            &self.context.ciphertext_withdraw_withheld_authority,
+            &self.context.ciphertext_dest,
+        );
+
+        let pubkey_withdraw_withheld_authority = decode_field(
+            self.context.pubkey_withdraw_withheld_authority,
+            "pubkey_withdraw_withheld_authority",
+        )?;
+        let pubkey_dest = decode_field(self.context.pubkey_dest, "pubkey_dest")?;
+        let ciphertext_withdraw_withheld_authority = decode_field(
+            self.context.ciphertext_withdraw_withheld_authority,
+            "ciphertext_withdraw_withheld_authority",
+        )?;
+        let ciphertext_dest = decode_field(self.context.ciphertext_dest, "ciphertext_dest")?;
+
+        self.proof.verify_batched(
+            &pubkey_withdraw_withheld_authority,
+            &pubkey_dest,
+            &ciphertext_withdraw_withheld_authority,
+            &ciphertext_dest,
+            &mut transcript,
+            batch,
+        )
+    }
+}
+
+/// This struct represents the cryptographic proof component that certifies that the withheld
+/// fees are re-encrypted under the destination pubkey without change
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+#[cfg_attr(
+    all(feature = "serde-traits", not(target_arch = "bpf")),
+    derive(serde::Deserialize, serde::Serialize)
+)]
+#[cfg_attr(
+    feature = "borsh-traits",
+    derive(borsh::BorshDeserialize, borsh::BorshSerialize)
+)]
+pub struct WithdrawWithheldTokensProof {
+    pub version: u8,
+    pub proof: pod::CtxtCtxtEqualityProof,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl WithdrawWithheldTokensProof {
+    pub fn transcript_new(
+        pubkey_withdraw_withheld_authority: &pod::ElGamalPubkey,
+        pubkey_dest: &pod::ElGamalPubkey,
+        ciphertext_withdraw_withheld_authority: &pod::ElGamalCiphertext,
+        ciphertext_dest: &pod::ElGamalCiphertext,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"WithdrawWithheldTokensProof-v1");
+
+        transcript.append_pubkey(
+            b"pubkey-withdraw-withheld-authority",
+            pubkey_withdraw_withheld_authority,
+        );
+        transcript.append_pubkey(b"pubkey-dest", pubkey_dest);
+
+        transcript.append_ciphertext(
+            b"ciphertext-withdraw-withheld-authority",
+            ciphertext_withdraw_withheld_authority,
+        );
+        transcript.append_ciphertext(b"ciphertext-dest", ciphertext_dest);
+
+        transcript
+    }
+
+    #[cfg(feature = "prover")]
+    pub fn new(
+        keypair_withdraw_withheld_authority: &ElGamalKeypair,
+        pubkey_dest: &ElGamalPubkey,
+        ciphertext_withdraw_withheld_authority: &ElGamalCiphertext,
+        amount: u64,
+        opening_dest: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let proof = CtxtCtxtEqualityProof::new(
+            keypair_withdraw_withheld_authority,
+            pubkey_dest,
+            ciphertext_withdraw_withheld_authority,
+            amount,
+            opening_dest,
+            transcript,
+        );
+
+        Self {
+            version: Self::VERSION,
+            proof: proof.into(),
+        }
+    }
+
+    pub fn verify_batched(
+        &self,
+        pubkey_withdraw_withheld_authority: &ElGamalPubkey,
+        pubkey_dest: &ElGamalPubkey,
+        ciphertext_withdraw_withheld_authority: &ElGamalCiphertext,
+        ciphertext_dest: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ProofError> {
+        self.check_version()?;
+
+        let proof: CtxtCtxtEqualityProof = self.proof.try_into()?;
+        proof.verify_batched(
+            pubkey_withdraw_withheld_authority,
+            pubkey_dest,
+            ciphertext_withdraw_withheld_authority,
+            ciphertext_dest,
+            transcript,
+            batch,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_withdraw_withheld_correctness() {
+        let keypair_withdraw_withheld_authority = ElGamalKeypair::new_rand();
+        let pubkey_dest = ElGamalKeypair::new_rand().public;
+
+        let amount: u64 = 55;
+        let ciphertext_withdraw_withheld_authority =
+            keypair_withdraw_withheld_authority.public.encrypt(amount);
+
+        let withdraw_withheld_data = WithdrawWithheldTokensData::new(
+            &keypair_withdraw_withheld_authority,
+            &pubkey_dest,
+            &ciphertext_withdraw_withheld_authority,
+            amount,
+        )
+        .unwrap();
+        assert!(withdraw_withheld_data.verify().is_ok());
+
+        // the amount re-encrypted under the destination pubkey must match the withheld amount
+        let withdraw_withheld_data = WithdrawWithheldTokensData::new(
+            &keypair_withdraw_withheld_authority,
+            &pubkey_dest,
+            &ciphertext_withdraw_withheld_authority,
+            77,
+        )
+        .unwrap();
+        assert!(withdraw_withheld_data.verify().is_err());
+    }
+}
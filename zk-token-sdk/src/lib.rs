@@ -1,3 +1,20 @@
+//! Zero-knowledge proofs for confidential token transfers
+//!
+//! # Features
+//!
+//! * `std` (default): everything but the pod types in `zk_token_elgamal::pod`, which build under
+//!   `no_std` so that proof data can be handled by targets such as embedded signers and enclaves.
+//! * `prover` (default): proof generation and decryption, including the discrete log
+//!   precomputation tables. Without it only the proof verifiers are built.
+//! * `serde-traits`: `serde` (de)serialization of the pod types and proof data, using base64
+//!   strings for human-readable formats and raw bytes otherwise. Implies `std`.
+//! * `borsh-traits`: `borsh` (de)serialization of the pod types and proof data, whose encoding
+//!   coincides with the pod byte layout. Implies `std`.
+//! * `ffi`: the C interface in `ffi`, for keygen, encryption, and proof data generation and
+//!   verification. Implies `prover`.
+//! * `prover-service`: a JSON-RPC server and client for generating `TransferWithFeeData` on a
+//!   remote prover, over TLS with client authentication. Implies `prover`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(clippy::integer_arithmetic, clippy::op_ref)]
 
 // The warning `clippy::op_ref` is disabled to allow efficient operator arithmetic of structs that
@@ -17,22 +34,41 @@
 //
 // `clippy::op_ref` is turned off to prevent clippy from warning that this is not idiomatic code.
 
-#[cfg(not(target_arch = "bpf"))]
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
 #[macro_use]
 pub(crate) mod macros;
-#[cfg(not(target_arch = "bpf"))]
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
+mod batch;
+#[cfg(all(feature = "prover", not(target_arch = "bpf")))]
+pub mod cancellation;
+#[cfg(feature = "std")]
+pub mod curve25519;
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
 pub mod encryption;
-#[cfg(not(target_arch = "bpf"))]
-mod errors;
-#[cfg(not(target_arch = "bpf"))]
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
+pub mod errors;
+#[cfg(feature = "std")]
+pub mod fee_calculation;
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
 mod range_proof;
-#[cfg(not(target_arch = "bpf"))]
-mod sigma_proofs;
-#[cfg(not(target_arch = "bpf"))]
-mod transcript;
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
+pub mod sigma_proofs;
+#[cfg(all(feature = "std", not(target_arch = "bpf")))]
+pub mod transcript;
 
 // TODO: re-organize visibility
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
 pub mod instruction;
+#[cfg(feature = "prover-service")]
+pub mod prover_service;
+#[cfg(feature = "prover")]
+pub mod wasm;
 pub mod zk_token_elgamal;
+#[cfg(feature = "std")]
 pub mod zk_token_proof_instruction;
+#[cfg(feature = "std")]
 pub mod zk_token_proof_program;
+#[cfg(feature = "std")]
+pub mod zk_token_proof_state;
@@ -0,0 +1,472 @@
+//! Remote proving of `TransferWithFeeData` over JSON-RPC
+//!
+//! Generating the proofs of a transfer with fee takes several seconds on low-end devices, so a
+//! wallet can delegate it to a prover service that it operates. The request carries the secret
+//! `ElGamalKeypair` of the source account, so the service only speaks JSON-RPC over TLS and only
+//! accepts clients that present a certificate issued by one of its client roots.
+#![cfg(not(target_arch = "bpf"))]
+
+use {
+    crate::{
+        encryption::elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+        instruction::{transfer_with_fee::FeeParameters, TransferWithFeeData},
+    },
+    bytemuck::{bytes_of, try_from_bytes},
+    hyper::{
+        body::HttpBody, client, header, server::conn::Http, service::service_fn, Body, Method,
+        Request, Response, StatusCode,
+    },
+    jsonrpc_core::{Error, Id, IoHandler, MethodCall, Output, Params, Result, Value, Version},
+    jsonrpc_derive::rpc,
+    serde::{Deserialize, Serialize},
+    std::{convert::TryInto, io, net::SocketAddr, sync::Arc, time::Duration},
+    thiserror::Error,
+    tokio::{
+        net::{TcpListener, TcpStream},
+        runtime::{self, Runtime},
+        time::timeout,
+    },
+    tokio_rustls::{
+        rustls::{
+            server::AllowAnyAuthenticatedClient, Certificate, ClientConfig, PrivateKey,
+            RootCertStore, ServerConfig, ServerName,
+        },
+        TlsAcceptor, TlsConnector,
+    },
+};
+
+/// How long a client may take to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound on the size of a request body; a request for a transfer with the largest number
+/// of auditors is a few kilobytes
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+/// Witness of a transfer with fee, with every key and ciphertext encoded in base64
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProveTransferWithFeeRequest {
+    pub transfer_amount: u64,
+    pub spendable_balance: u64,
+    pub ciphertext_old_source: String,
+    pub keypair_source: String,
+    pub pubkey_dest: String,
+    pub pubkeys_auditor: Vec<String>,
+    pub fee_rate: u64,
+    pub maximum_fee: u64,
+    pub fee_denominator: u64,
+    pub pubkey_fee_collector: String,
+}
+
+impl ProveTransferWithFeeRequest {
+    /// Encodes the arguments of `TransferWithFeeData::new` as a request
+    pub fn new(
+        transfer_amount: u64,
+        (spendable_balance, ciphertext_old_source): (u64, &ElGamalCiphertext),
+        keypair_source: &ElGamalKeypair,
+        (pubkey_dest, pubkeys_auditor): (&ElGamalPubkey, &[ElGamalPubkey]),
+        fee_parameters: FeeParameters,
+        pubkey_fee_collector: &ElGamalPubkey,
+    ) -> Self {
+        Self {
+            transfer_amount,
+            spendable_balance,
+            ciphertext_old_source: base64::encode(ciphertext_old_source.to_bytes()),
+            keypair_source: base64::encode(keypair_source.to_bytes()),
+            pubkey_dest: base64::encode(pubkey_dest.to_bytes()),
+            pubkeys_auditor: pubkeys_auditor
+                .iter()
+                .map(|pubkey| base64::encode(pubkey.to_bytes()))
+                .collect(),
+            fee_rate: fee_parameters.fee_rate,
+            maximum_fee: fee_parameters.maximum_fee,
+            fee_denominator: fee_parameters.fee_denominator,
+            pubkey_fee_collector: base64::encode(pubkey_fee_collector.to_bytes()),
+        }
+    }
+
+    fn prove(&self) -> Result<TransferWithFeeData> {
+        let ciphertext_old_source = decode_base64(&self.ciphertext_old_source)
+            .and_then(|bytes| ElGamalCiphertext::from_bytes(&bytes))
+            .ok_or_else(|| Error::invalid_params("Invalid ciphertextOldSource"))?;
+        let keypair_source = decode_base64(&self.keypair_source)
+            .and_then(|bytes| ElGamalKeypair::from_bytes(&bytes))
+            .ok_or_else(|| Error::invalid_params("Invalid keypairSource"))?;
+        let pubkey_dest = decode_pubkey(&self.pubkey_dest)
+            .ok_or_else(|| Error::invalid_params("Invalid pubkeyDest"))?;
+        let pubkeys_auditor = self
+            .pubkeys_auditor
+            .iter()
+            .map(String::as_str)
+            .map(decode_pubkey)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::invalid_params("Invalid pubkeysAuditor"))?;
+        let pubkey_fee_collector = decode_pubkey(&self.pubkey_fee_collector)
+            .ok_or_else(|| Error::invalid_params("Invalid pubkeyFeeCollector"))?;
+
+        let fee_parameters = FeeParameters {
+            fee_rate: self.fee_rate,
+            maximum_fee: self.maximum_fee,
+            fee_denominator: self.fee_denominator,
+        };
+
+        TransferWithFeeData::new(
+            self.transfer_amount,
+            (self.spendable_balance, &ciphertext_old_source),
+            &keypair_source,
+            (&pubkey_dest, &pubkeys_auditor),
+            fee_parameters,
+            &pubkey_fee_collector,
+        )
+        .map_err(|err| Error::invalid_params(err.to_string()))
+    }
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    base64::decode(encoded).ok()
+}
+
+fn decode_pubkey(encoded: &str) -> Option<ElGamalPubkey> {
+    let bytes: [u8; 32] = decode_base64(encoded)?.try_into().ok()?;
+    ElGamalPubkey::from_bytes(&bytes)
+}
+
+#[rpc(server)]
+pub trait ProverRpc {
+    /// Returns the base64-encoded `TransferWithFeeData` of the transfer
+    #[rpc(name = "proveTransferWithFee")]
+    fn prove_transfer_with_fee(&self, request: ProveTransferWithFeeRequest) -> Result<String>;
+}
+
+pub struct ProverRpcImpl;
+impl ProverRpc for ProverRpcImpl {
+    fn prove_transfer_with_fee(&self, request: ProveTransferWithFeeRequest) -> Result<String> {
+        let proof_data = request.prove()?;
+        Ok(base64::encode(bytes_of(&proof_data)))
+    }
+}
+
+/// TLS configuration of a prover service
+pub struct ProverServiceTlsConfig {
+    /// The certificate chain the service presents to its clients
+    pub cert_chain: Vec<Certificate>,
+    pub private_key: PrivateKey,
+    /// Clients must present a certificate that chains to one of these roots
+    pub client_roots: RootCertStore,
+}
+
+/// A running prover service, which is shut down when dropped
+pub struct ProverService {
+    local_addr: SocketAddr,
+    // Dropping the runtime stops the accept loop and every open connection
+    _runtime: Runtime,
+}
+
+impl ProverService {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Starts a prover service on `rpc_addr` that handles requests on `threads` threads
+pub fn run(
+    rpc_addr: SocketAddr,
+    threads: usize,
+    tls_config: ProverServiceTlsConfig,
+) -> io::Result<ProverService> {
+    let server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(tls_config.client_roots))
+        .with_single_cert(tls_config.cert_chain, tls_config.private_key)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let tls_acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+    let runtime = runtime::Builder::new_multi_thread()
+        .worker_threads(threads)
+        .thread_name("zkProverService")
+        .enable_all()
+        .build()?;
+    let listener = runtime.block_on(TcpListener::bind(rpc_addr))?;
+    let local_addr = listener.local_addr()?;
+
+    let mut io = IoHandler::default();
+    io.extend_with(ProverRpcImpl.to_delegate());
+    let io = Arc::new(io);
+
+    runtime.spawn(async move {
+        loop {
+            // Ignore accept errors
+            let stream = match listener.accept().await {
+                Ok((stream, _peer_addr)) => stream,
+                Err(_) => continue,
+            };
+            let tls_acceptor = tls_acceptor.clone();
+            let io = io.clone();
+            tokio::spawn(async move {
+                // The handshake fails unless the client authenticates with its certificate
+                if let Ok(Ok(stream)) =
+                    timeout(HANDSHAKE_TIMEOUT, tls_acceptor.accept(stream)).await
+                {
+                    let service = service_fn(move |request| handle_request(io.clone(), request));
+                    let _ = Http::new()
+                        .http1_only(true)
+                        .serve_connection(stream, service)
+                        .await;
+                }
+            });
+        }
+    });
+
+    Ok(ProverService {
+        local_addr,
+        _runtime: runtime,
+    })
+}
+
+async fn handle_request(
+    io: Arc<IoHandler>,
+    request: Request<Body>,
+) -> std::result::Result<Response<Body>, hyper::Error> {
+    if request.method() != Method::POST {
+        return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        bytes.extend_from_slice(&chunk?);
+        if bytes.len() > MAX_REQUEST_SIZE {
+            return Ok(status_response(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+    }
+    let request = match String::from_utf8(bytes) {
+        Ok(request) => request,
+        Err(_) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+    };
+
+    Ok(match io.handle_request(&request).await {
+        Some(response) => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(response))
+            .unwrap(),
+        // notifications have no response
+        None => status_response(StatusCode::NO_CONTENT),
+    })
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[derive(Debug, Error)]
+pub enum ProverClientError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("http error: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("invalid TLS server name")]
+    InvalidServerName,
+    #[error("rpc error: {0}")]
+    Rpc(Error),
+    #[error("invalid response: {0}")]
+    InvalidResponse(&'static str),
+}
+
+/// A client of a prover service, which authenticates with the client certificate of its
+/// `ClientConfig`
+#[derive(Clone)]
+pub struct ProverClient {
+    addr: SocketAddr,
+    host: String,
+    server_name: ServerName,
+    tls_connector: TlsConnector,
+}
+
+impl ProverClient {
+    /// Creates a client of the prover service at `addr`, whose certificate is verified against
+    /// `server_name`
+    pub fn new(
+        addr: SocketAddr,
+        server_name: &str,
+        tls_config: Arc<ClientConfig>,
+    ) -> std::result::Result<Self, ProverClientError> {
+        let tls_server_name =
+            ServerName::try_from(server_name).map_err(|_| ProverClientError::InvalidServerName)?;
+        Ok(Self {
+            addr,
+            host: server_name.to_string(),
+            server_name: tls_server_name,
+            tls_connector: TlsConnector::from(tls_config),
+        })
+    }
+
+    /// Requests the `TransferWithFeeData` of a transfer from the prover service
+    pub async fn prove_transfer_with_fee(
+        &self,
+        request: ProveTransferWithFeeRequest,
+    ) -> std::result::Result<TransferWithFeeData, ProverClientError> {
+        let params = serde_json::to_value(request)
+            .map_err(|_| ProverClientError::InvalidResponse("unserializable request"))?;
+        let encoded = match self.call("proveTransferWithFee", params).await? {
+            Value::String(encoded) => encoded,
+            _ => return Err(ProverClientError::InvalidResponse("expected a string")),
+        };
+        decode_base64(&encoded)
+            .and_then(|bytes| try_from_bytes::<TransferWithFeeData>(&bytes).ok().copied())
+            .ok_or(ProverClientError::InvalidResponse(
+                "invalid TransferWithFeeData",
+            ))
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        param: Value,
+    ) -> std::result::Result<Value, ProverClientError> {
+        let stream = TcpStream::connect(self.addr).await?;
+        let stream = self
+            .tls_connector
+            .connect(self.server_name.clone(), stream)
+            .await?;
+        let (mut sender, connection) = client::conn::handshake(stream).await?;
+        tokio::spawn(connection);
+
+        let method_call = MethodCall {
+            jsonrpc: Some(Version::V2),
+            method: method.to_string(),
+            params: Params::Array(vec![param]),
+            id: Id::Num(0),
+        };
+        let request = Request::post("/")
+            .header(header::HOST, self.host.as_str())
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&method_call).unwrap()))
+            .unwrap();
+        let response = sender.send_request(request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(ProverClientError::InvalidResponse("unexpected http status"));
+        }
+
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        match serde_json::from_slice(&body) {
+            Ok(Output::Success(success)) => Ok(success.result),
+            Ok(Output::Failure(failure)) => Err(ProverClientError::Rpc(failure.error)),
+            Err(_) => Err(ProverClientError::InvalidResponse(
+                "malformed json-rpc response",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::instruction::Verifiable};
+
+    fn test_request() -> ProveTransferWithFeeRequest {
+        let keypair_source = ElGamalKeypair::new_rand();
+        let pubkey_dest = ElGamalKeypair::new_rand().public;
+        let pubkey_auditor = ElGamalKeypair::new_rand().public;
+        let pubkey_fee_collector = ElGamalKeypair::new_rand().public;
+
+        let spendable_balance: u64 = 120;
+        let ciphertext_old_source = keypair_source.public.encrypt(spendable_balance);
+
+        let fee_parameters = FeeParameters {
+            fee_rate: 400,
+            maximum_fee: 5,
+            fee_denominator: 10_000,
+        };
+
+        ProveTransferWithFeeRequest::new(
+            100,
+            (spendable_balance, &ciphertext_old_source),
+            &keypair_source,
+            (&pubkey_dest, &[pubkey_auditor]),
+            fee_parameters,
+            &pubkey_fee_collector,
+        )
+    }
+
+    #[test]
+    fn test_prove_transfer_with_fee() {
+        let mut request = test_request();
+
+        let encoded = ProverRpcImpl
+            .prove_transfer_with_fee(request.clone())
+            .unwrap();
+        let bytes = base64::decode(encoded).unwrap();
+        let proof_data = try_from_bytes::<TransferWithFeeData>(&bytes).unwrap();
+        assert!(proof_data.verify().is_ok());
+
+        // malformed witnesses are rejected as invalid params
+        request.pubkey_dest = "invalid".to_string();
+        assert!(ProverRpcImpl.prove_transfer_with_fee(request).is_err());
+    }
+
+    #[test]
+    fn test_prover_service_requires_client_certificate() {
+        let server_certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let server_certificate_der = Certificate(server_certificate.serialize_der().unwrap());
+        let client_certificate =
+            rcgen::generate_simple_self_signed(vec!["client".to_string()]).unwrap();
+        let client_certificate_der = Certificate(client_certificate.serialize_der().unwrap());
+
+        let mut client_roots = RootCertStore::empty();
+        client_roots.add(&client_certificate_der).unwrap();
+        let service = run(
+            "127.0.0.1:0".parse().unwrap(),
+            1,
+            ProverServiceTlsConfig {
+                cert_chain: vec![server_certificate_der.clone()],
+                private_key: PrivateKey(server_certificate.serialize_private_key_der()),
+                client_roots,
+            },
+        )
+        .unwrap();
+
+        let mut server_roots = RootCertStore::empty();
+        server_roots.add(&server_certificate_der).unwrap();
+        let authenticated_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(server_roots.clone())
+            .with_single_cert(
+                vec![client_certificate_der],
+                PrivateKey(client_certificate.serialize_private_key_der()),
+            )
+            .unwrap();
+        let anonymous_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(server_roots)
+            .with_no_client_auth();
+
+        Runtime::new().unwrap().block_on(async {
+            let client = ProverClient::new(
+                service.local_addr(),
+                "localhost",
+                Arc::new(authenticated_config),
+            )
+            .unwrap();
+            let proof_data = client
+                .prove_transfer_with_fee(test_request())
+                .await
+                .unwrap();
+            assert!(proof_data.verify().is_ok());
+
+            // a client without a certificate is turned away before it can send the witness
+            let client = ProverClient::new(
+                service.local_addr(),
+                "localhost",
+                Arc::new(anonymous_config),
+            )
+            .unwrap();
+            assert!(client
+                .prove_transfer_with_fee(test_request())
+                .await
+                .is_err());
+        });
+    }
+}
@@ -8,6 +8,8 @@ pub enum RangeProofError {
     AlgebraicRelation,
     #[error("malformed proof")]
     Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
     #[error("attempted to create a proof with a non-power-of-two bitsize or bitsize too big")]
     InvalidBitsize,
     #[error("insufficient generators for the proof")]
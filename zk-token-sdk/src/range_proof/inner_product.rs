@@ -35,6 +35,7 @@ impl InnerProductProof {
     ///
     /// The lengths of the vectors must all be the same, and must all be
     /// either 0 or a power of 2.
+    #[cfg(feature = "prover")]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         Q: &RistrettoPoint,
@@ -298,13 +299,13 @@ impl InnerProductProof {
         let Ls = self
             .L_vec
             .iter()
-            .map(|p| p.decompress().ok_or(RangeProofError::Format))
+            .map(|p| p.decompress().ok_or(RangeProofError::NonCanonical))
             .collect::<Result<Vec<_>, _>>()?;
 
         let Rs = self
             .R_vec
             .iter()
-            .map(|p| p.decompress().ok_or(RangeProofError::Format))
+            .map(|p| p.decompress().ok_or(RangeProofError::NonCanonical))
             .collect::<Result<Vec<_>, _>>()?;
 
         let expect_P = RistrettoPoint::vartime_multiscalar_mul(
@@ -384,9 +385,9 @@ impl InnerProductProof {
 
         let pos = 2 * lg_n * 32;
         let a = Scalar::from_canonical_bytes(util::read32(&slice[pos..]))
-            .ok_or(RangeProofError::Format)?;
+            .ok_or(RangeProofError::NonCanonical)?;
         let b = Scalar::from_canonical_bytes(util::read32(&slice[pos + 32..]))
-            .ok_or(RangeProofError::Format)?;
+            .ok_or(RangeProofError::NonCanonical)?;
 
         Ok(InnerProductProof { L_vec, R_vec, a, b })
     }
@@ -1,6 +1,10 @@
 #[cfg(not(target_arch = "bpf"))]
+use crate::{
+    batch::MultiscalarMulBatch, encryption::pedersen::PedersenCommitment, errors::ProofError,
+};
+#[cfg(feature = "prover")]
 use {
-    crate::encryption::pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
+    crate::encryption::pedersen::{Pedersen, PedersenOpening},
     curve25519_dalek::traits::MultiscalarMul,
     rand::rngs::OsRng,
     subtle::{Choice, ConditionallySelectable},
@@ -17,7 +21,6 @@ use {
     curve25519_dalek::{
         ristretto::{CompressedRistretto, RistrettoPoint},
         scalar::Scalar,
-        traits::{IsIdentity, VartimeMultiscalarMul},
     },
     merlin::Transcript,
 };
@@ -25,7 +28,9 @@ use {
 pub mod errors;
 pub mod generators;
 pub mod inner_product;
+pub mod plus;
 pub mod util;
+pub mod weighted_inner_product;
 
 #[allow(non_snake_case)]
 #[derive(Clone)]
@@ -50,23 +55,24 @@ impl RangeProof {
     /// - bit-lengths of the committed amounts
     /// - Pedersen openings for each commitments
     ///
-    /// The sum of the bit-lengths of the commitments amounts must be a power-of-two
+    /// The sum of the bit-lengths of the commitments amounts must be a power-of-two, and each
+    /// bit-length must be at most 64
     #[allow(clippy::many_single_char_names)]
-    #[cfg(not(target_arch = "bpf"))]
+    #[cfg(all(feature = "prover", not(target_arch = "bpf")))]
     pub fn new(
         amounts: Vec<u64>,
         bit_lengths: Vec<usize>,
         openings: Vec<&PedersenOpening>,
         transcript: &mut Transcript,
-    ) -> Self {
+    ) -> Result<Self, RangeProofError> {
         // amounts, bit-lengths, openings must be same length vectors
         let m = amounts.len();
-        assert_eq!(bit_lengths.len(), m);
-        assert_eq!(openings.len(), m);
+        if openings.len() != m {
+            return Err(RangeProofError::WrongNumBlindingFactors);
+        }
 
         // total vector dimension to compute the ultimate inner product proof for
-        let nm: usize = bit_lengths.iter().sum();
-        assert!(nm.is_power_of_two());
+        let nm = total_bit_length(&bit_lengths, m)?;
 
         // TODO: precompute generators
         // TODO: double check Pedersen generators and range proof generators does not interfere
@@ -205,7 +211,7 @@ impl RangeProof {
             transcript,
         );
 
-        RangeProof {
+        Ok(RangeProof {
             A,
             S,
             T_1,
@@ -214,26 +220,67 @@ impl RangeProof {
             t_x_blinding,
             e_blinding,
             ipp_proof,
-        }
+        })
     }
 
-    #[allow(clippy::many_single_char_names)]
     pub fn verify(
         &self,
         comms: Vec<&PedersenCommitment>,
         bit_lengths: Vec<usize>,
         transcript: &mut Transcript,
     ) -> Result<(), RangeProofError> {
-        // commitments and bit-lengths must be same length vectors
-        assert_eq!(comms.len(), bit_lengths.len());
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(comms, bit_lengths, transcript, &mut batch)?;
 
-        let m = bit_lengths.len();
-        let nm: usize = bit_lengths.iter().sum();
-        let bp_gens = BulletproofGens::new(nm);
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(RangeProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Verifies multiple range proofs at once.
+    ///
+    /// The algebraic relations of all the proofs are folded into a single multiscalar
+    /// multiplication with independent random weights, which is considerably faster than
+    /// verifying each proof on its own. Each item consists of a proof together with the
+    /// arguments that would be passed to `RangeProof::verify`.
+    pub fn verify_batch<'a, I>(proofs: I) -> Result<(), RangeProofError>
+    where
+        I: IntoIterator<
+            Item = (
+                &'a RangeProof,
+                Vec<&'a PedersenCommitment>,
+                Vec<usize>,
+                &'a mut Transcript,
+            ),
+        >,
+    {
+        let mut batch = MultiscalarMulBatch::new();
+        for (proof, comms, bit_lengths, transcript) in proofs {
+            proof.verify_batched(comms, bit_lengths, transcript, &mut batch)?;
+        }
 
-        if !nm.is_power_of_two() {
-            return Err(RangeProofError::InvalidBitsize);
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(RangeProofError::AlgebraicRelation)
         }
+    }
+
+    /// Range proof verifier that defers the check of the algebraic relation to `batch`.
+    #[allow(clippy::many_single_char_names)]
+    pub fn verify_batched(
+        &self,
+        comms: Vec<&PedersenCommitment>,
+        bit_lengths: Vec<usize>,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), RangeProofError> {
+        // commitments and bit-lengths must be same length vectors
+        let m = comms.len();
+        let nm = total_bit_length(&bit_lengths, m)?;
+        let bp_gens = BulletproofGens::new(nm);
 
         // append proof data to transcript and derive appropriate challenge scalars
         transcript.validate_and_append_point(b"A", &self.A)?;
@@ -286,7 +333,22 @@ impl RangeProof {
             w * (self.t_x - a * b) + c * (delta(&bit_lengths, &y, &z) - self.t_x);
         let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
+        let points = iter::once(self.A.decompress())
+            .chain(iter::once(self.S.decompress()))
+            .chain(iter::once(self.T_1.decompress()))
+            .chain(iter::once(self.T_2.decompress()))
+            .chain(iter::once(Some(*H)))
+            .chain(iter::once(Some(*G)))
+            .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
+            .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
+            .chain(bp_gens.G(nm).map(|&x| Some(x)))
+            .chain(bp_gens.H(nm).map(|&x| Some(x)))
+            .chain(comms.iter().map(|V| Some(*V.get_point())))
+            .collect::<Option<Vec<RistrettoPoint>>>()
+            .ok_or(RangeProofError::NonCanonical)?;
+
+        batch.append(
+            ProofError::RangeProofFailed,
             iter::once(Scalar::one())
                 .chain(iter::once(x))
                 .chain(iter::once(c * x))
@@ -298,25 +360,10 @@ impl RangeProof {
                 .chain(gs)
                 .chain(hs)
                 .chain(value_commitment_scalars),
-            iter::once(self.A.decompress())
-                .chain(iter::once(self.S.decompress()))
-                .chain(iter::once(self.T_1.decompress()))
-                .chain(iter::once(self.T_2.decompress()))
-                .chain(iter::once(Some(*H)))
-                .chain(iter::once(Some(*G)))
-                .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
-                .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
-                .chain(bp_gens.G(nm).map(|&x| Some(x)))
-                .chain(bp_gens.H(nm).map(|&x| Some(x)))
-                .chain(comms.iter().map(|V| Some(*V.get_point()))),
-        )
-        .ok_or(RangeProofError::MultiscalarMul)?;
+            points,
+        );
 
-        if mega_check.is_identity() {
-            Ok(())
-        } else {
-            Err(RangeProofError::AlgebraicRelation)
-        }
+        Ok(())
     }
 
     // Following the dalek rangeproof library signature for now. The exact method signature can be
@@ -350,11 +397,11 @@ impl RangeProof {
         let T_2 = CompressedRistretto(util::read32(&slice[3 * 32..]));
 
         let t_x = Scalar::from_canonical_bytes(util::read32(&slice[4 * 32..]))
-            .ok_or(RangeProofError::Format)?;
+            .ok_or(RangeProofError::NonCanonical)?;
         let t_x_blinding = Scalar::from_canonical_bytes(util::read32(&slice[5 * 32..]))
-            .ok_or(RangeProofError::Format)?;
+            .ok_or(RangeProofError::NonCanonical)?;
         let e_blinding = Scalar::from_canonical_bytes(util::read32(&slice[6 * 32..]))
-            .ok_or(RangeProofError::Format)?;
+            .ok_or(RangeProofError::NonCanonical)?;
 
         let ipp_proof = InnerProductProof::from_bytes(&slice[7 * 32..])?;
 
@@ -389,6 +436,21 @@ fn delta(bit_lengths: &[usize], y: &Scalar, z: &Scalar) -> Scalar {
     agg_delta
 }
 
+/// Checks that there is a bit-length for each of the `m` committed amounts, that none of the
+/// bit-lengths exceeds 64, and that they sum to a power of two. Returns the sum.
+pub(crate) fn total_bit_length(bit_lengths: &[usize], m: usize) -> Result<usize, RangeProofError> {
+    if bit_lengths.len() != m || bit_lengths.iter().any(|n| *n > 64) {
+        return Err(RangeProofError::InvalidBitsize);
+    }
+
+    let nm: usize = bit_lengths.iter().sum();
+    if !nm.is_power_of_two() {
+        return Err(RangeProofError::InvalidBitsize);
+    }
+
+    Ok(nm)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,7 +462,8 @@ mod tests {
         let mut transcript_create = Transcript::new(b"Test");
         let mut transcript_verify = Transcript::new(b"Test");
 
-        let proof = RangeProof::new(vec![55], vec![32], vec![&open], &mut transcript_create);
+        let proof =
+            RangeProof::new(vec![55], vec![32], vec![&open], &mut transcript_create).unwrap();
 
         assert!(proof
             .verify(vec![&comm], vec![32], &mut transcript_verify)
@@ -421,7 +484,8 @@ mod tests {
             vec![64, 32, 32],
             vec![&open_1, &open_2, &open_3],
             &mut transcript_create,
-        );
+        )
+        .unwrap();
 
         assert!(proof
             .verify(
@@ -432,5 +496,92 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_rangeproof_invalid_inputs() {
+        let (_, open_1) = Pedersen::new(55_u64);
+        let (_, open_2) = Pedersen::new(77_u64);
+
+        // every amount needs an opening
+        assert_eq!(
+            RangeProof::new(
+                vec![55, 77],
+                vec![32, 32],
+                vec![&open_1],
+                &mut Transcript::new(b"Test"),
+            )
+            .err(),
+            Some(RangeProofError::WrongNumBlindingFactors)
+        );
+
+        // every amount needs a bit-length, and the bit-lengths must sum to a power of two
+        for bit_lengths in [vec![64], vec![32, 16], vec![128, 0], vec![]] {
+            assert_eq!(
+                RangeProof::new(
+                    vec![55, 77],
+                    bit_lengths,
+                    vec![&open_1, &open_2],
+                    &mut Transcript::new(b"Test"),
+                )
+                .err(),
+                Some(RangeProofError::InvalidBitsize)
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_rangeproof() {
+        let (comm_1, open_1) = Pedersen::new(55_u64);
+        let (comm_2, open_2) = Pedersen::new(77_u64);
+        let (comm_3, open_3) = Pedersen::new(99_u64);
+
+        let proof_1 = RangeProof::new(
+            vec![55],
+            vec![64],
+            vec![&open_1],
+            &mut Transcript::new(b"Test"),
+        )
+        .unwrap();
+        let proof_2 = RangeProof::new(
+            vec![77, 99],
+            vec![32, 32],
+            vec![&open_2, &open_3],
+            &mut Transcript::new(b"Test"),
+        )
+        .unwrap();
+
+        assert!(RangeProof::verify_batch(vec![
+            (
+                &proof_1,
+                vec![&comm_1],
+                vec![64],
+                &mut Transcript::new(b"Test")
+            ),
+            (
+                &proof_2,
+                vec![&comm_2, &comm_3],
+                vec![32, 32],
+                &mut Transcript::new(b"Test"),
+            ),
+        ])
+        .is_ok());
+
+        // a single invalid proof invalidates the whole batch
+        assert!(RangeProof::verify_batch(vec![
+            (
+                &proof_1,
+                vec![&comm_1],
+                vec![64],
+                &mut Transcript::new(b"Test")
+            ),
+            (
+                &proof_2,
+                vec![&comm_3, &comm_2],
+                vec![32, 32],
+                &mut Transcript::new(b"Test"),
+            ),
+        ])
+        .is_err());
+    }
+
     // TODO: write test for serialization/deserialization
 }
@@ -0,0 +1,341 @@
+//! Range proofs based on Bulletproofs+.
+//!
+//! Bulletproofs+ replaces the inner product argument of Bulletproofs with a weighted inner
+//! product argument, which removes three group elements and two scalars from the proof and saves
+//! a number of scalar multiplications on verification. The interface mirrors that of
+//! `RangeProof`, so that the two backends can be used interchangeably.
+
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::pedersen::PedersenOpening,
+    rand::rngs::OsRng,
+    subtle::{Choice, ConditionallySelectable},
+};
+use {
+    crate::{
+        batch::MultiscalarMulBatch,
+        encryption::pedersen::{PedersenCommitment, G, H},
+        errors::ProofError,
+        range_proof::{
+            errors::RangeProofError, generators::BulletproofGens, total_bit_length, util,
+            weighted_inner_product::WeightedInnerProductProof,
+        },
+        transcript::TranscriptProtocol,
+    },
+    core::iter,
+    curve25519_dalek::{
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+    },
+    merlin::Transcript,
+};
+
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct RangeProofPlus {
+    pub A: CompressedRistretto,               // 32 bytes
+    pub wip_proof: WeightedInnerProductProof, // 544 bytes for withdraw; 608 for transfer
+}
+
+#[allow(non_snake_case)]
+impl RangeProofPlus {
+    /// Create an aggregated Bulletproofs+ range proof.
+    ///
+    /// The arguments are the same as for `RangeProof::new`. The sum of the bit-lengths of the
+    /// commitments amounts must be a power-of-two
+    #[cfg(feature = "prover")]
+    #[allow(clippy::many_single_char_names)]
+    pub fn new(
+        amounts: Vec<u64>,
+        bit_lengths: Vec<usize>,
+        openings: Vec<&PedersenOpening>,
+        transcript: &mut Transcript,
+    ) -> Result<Self, RangeProofError> {
+        // amounts, bit-lengths, openings must be same length vectors
+        let m = amounts.len();
+        if openings.len() != m {
+            return Err(RangeProofError::WrongNumBlindingFactors);
+        }
+
+        // total vector dimension to compute the ultimate inner product proof for
+        let nm = total_bit_length(&bit_lengths, m)?;
+
+        transcript.rangeproof_plus_domain_sep(nm as u64);
+
+        let bp_gens = BulletproofGens::new(nm);
+
+        // bit-decompose values and generate their Pedersen vector commitment
+        let a_blinding = Scalar::random(&mut OsRng);
+        let mut A = a_blinding * &(*H);
+
+        let mut a_L = Vec::with_capacity(nm);
+        let mut gens_iter = bp_gens.G(nm).zip(bp_gens.H(nm));
+        for (amount_i, n_i) in amounts.iter().zip(bit_lengths.iter()) {
+            for j in 0..(*n_i) {
+                let (G_ij, H_ij) = gens_iter.next().unwrap();
+                let v_ij = Choice::from(((amount_i >> j) & 1) as u8);
+                let mut point = -H_ij;
+                point.conditional_assign(G_ij, v_ij);
+                A += point;
+                a_L.push(Scalar::from((amount_i >> j) & 1));
+            }
+        }
+        let A = A.compress();
+
+        transcript.append_point(b"A", &A);
+
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // y^1, ..., y^{nm + 1}
+        let y_powers: Vec<Scalar> = util::exp_iter(y).skip(1).take(nm + 1).collect();
+        let y_nm_1 = y_powers[nm];
+
+        // define the vectors for the weighted inner product argument:
+        // - a_L - z*1
+        // - a_R + d o (y^nm, ..., y^1) + z*1,
+        //   where d = [z^2*2^n | z^4*2^n | ... | z^2m*2^n]
+        let d = concat_z_and_2(&bit_lengths, &z);
+        let a_vec: Vec<Scalar> = a_L.iter().map(|a_L_i| a_L_i - z).collect();
+        let b_vec: Vec<Scalar> = a_L
+            .iter()
+            .zip(d.iter())
+            .zip(y_powers[..nm].iter().rev())
+            .map(|((a_L_i, d_i), exp_y)| a_L_i - Scalar::one() + d_i * exp_y + z)
+            .collect();
+
+        // fold the openings of the commitments into the blinding factor of `A`
+        let mut alpha = a_blinding;
+        let mut exp_z = Scalar::one();
+        for opening in openings {
+            exp_z *= z * z;
+            alpha += exp_z * y_nm_1 * opening.get_scalar();
+        }
+
+        let wip_proof = WeightedInnerProductProof::new(
+            bp_gens.G(nm).cloned().collect(),
+            bp_gens.H(nm).cloned().collect(),
+            a_vec,
+            b_vec,
+            alpha,
+            &y,
+            &y_powers[..nm],
+            transcript,
+        );
+
+        Ok(RangeProofPlus { A, wip_proof })
+    }
+
+    pub fn verify(
+        &self,
+        comms: Vec<&PedersenCommitment>,
+        bit_lengths: Vec<usize>,
+        transcript: &mut Transcript,
+    ) -> Result<(), RangeProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(comms, bit_lengths, transcript, &mut batch)?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(RangeProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Range proof verifier that defers the check of the algebraic relation to `batch`.
+    #[allow(clippy::many_single_char_names)]
+    pub fn verify_batched(
+        &self,
+        comms: Vec<&PedersenCommitment>,
+        bit_lengths: Vec<usize>,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), RangeProofError> {
+        // commitments and bit-lengths must be same length vectors
+        let nm = total_bit_length(&bit_lengths, comms.len())?;
+
+        let bp_gens = BulletproofGens::new(nm);
+
+        transcript.rangeproof_plus_domain_sep(nm as u64);
+
+        transcript.validate_and_append_point(b"A", &self.A)?;
+
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        let scalars = self.wip_proof.verification_scalars(nm, &y, transcript)?;
+        let e = scalars.e;
+        let ee = e * e;
+
+        let r = self.wip_proof.r;
+        let s = self.wip_proof.s;
+
+        let y_powers: Vec<Scalar> = util::exp_iter(y).skip(1).take(nm + 1).collect();
+        let y_nm_1 = y_powers[nm];
+
+        let d = concat_z_and_2(&bit_lengths, &z);
+
+        let gs = scalars.g_scalars.iter().map(|g_i| -z * ee - r * e * g_i);
+        let hs = scalars
+            .h_scalars
+            .iter()
+            .zip(d.iter())
+            .zip(y_powers[..nm].iter().rev())
+            .map(|((h_i, d_i), exp_y)| (d_i * exp_y + z) * ee - s * e * h_i);
+
+        // (z - z^2) * <1, (y^1, ..., y^nm)> - z * y^{nm + 1} * <1, d>
+        let zeta =
+            (z - z * z) * y * util::sum_of_powers(&y, nm) - z * y_nm_1 * d.iter().sum::<Scalar>();
+        let basepoint_scalar = zeta * ee - r * y * s;
+
+        let value_commitment_scalars = util::exp_iter(z * z)
+            .skip(1)
+            .take(comms.len())
+            .map(|exp_z| exp_z * y_nm_1 * ee);
+
+        let points = iter::once(self.A.decompress())
+            .chain(iter::once(self.wip_proof.A.decompress()))
+            .chain(iter::once(self.wip_proof.B.decompress()))
+            .chain(iter::once(Some(*G)))
+            .chain(iter::once(Some(*H)))
+            .chain(self.wip_proof.L_vec.iter().map(|L| L.decompress()))
+            .chain(self.wip_proof.R_vec.iter().map(|R| R.decompress()))
+            .chain(bp_gens.G(nm).map(|&x| Some(x)))
+            .chain(bp_gens.H(nm).map(|&x| Some(x)))
+            .chain(comms.iter().map(|V| Some(*V.get_point())))
+            .collect::<Option<Vec<RistrettoPoint>>>()
+            .ok_or(RangeProofError::NonCanonical)?;
+
+        batch.append(
+            ProofError::RangeProofFailed,
+            iter::once(ee)
+                .chain(iter::once(e))
+                .chain(iter::once(Scalar::one()))
+                .chain(iter::once(basepoint_scalar))
+                .chain(iter::once(-self.wip_proof.delta))
+                .chain(scalars.challenges_sq.iter().map(|e_j_sq| e_j_sq * ee))
+                .chain(
+                    scalars
+                        .challenges_inv_sq
+                        .iter()
+                        .map(|e_j_inv_sq| e_j_inv_sq * ee),
+                )
+                .chain(gs)
+                .chain(hs)
+                .chain(value_commitment_scalars),
+            points,
+        );
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(32 + self.wip_proof.serialized_size());
+        buf.extend_from_slice(self.A.as_bytes());
+        buf.extend_from_slice(&self.wip_proof.to_bytes());
+        buf
+    }
+
+    pub fn from_bytes(slice: &[u8]) -> Result<RangeProofPlus, RangeProofError> {
+        if slice.len() < 32 {
+            return Err(RangeProofError::Format);
+        }
+
+        let A = CompressedRistretto(util::read32(&slice[0..]));
+        let wip_proof = WeightedInnerProductProof::from_bytes(&slice[32..])?;
+
+        Ok(RangeProofPlus { A, wip_proof })
+    }
+}
+
+/// Computes the vector
+/// \\[
+/// z^2 \cdot \mathbf{2}^{n_1} || z^4 \cdot \mathbf{2}^{n_2} || \cdots || z^{2m} \cdot \mathbf{2}^{n_m}
+/// \\]
+fn concat_z_and_2(bit_lengths: &[usize], z: &Scalar) -> Vec<Scalar> {
+    util::exp_iter(z * z)
+        .skip(1)
+        .zip(bit_lengths.iter())
+        .flat_map(|(exp_z, n_i)| {
+            util::exp_iter(Scalar::from(2u64))
+                .take(*n_i)
+                .map(move |exp_2| exp_2 * exp_z)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{encryption::pedersen::Pedersen, zk_token_elgamal::pod},
+        std::convert::TryInto,
+    };
+
+    #[test]
+    fn test_single_rangeproof_plus() {
+        let (comm, open) = Pedersen::new(55_u64);
+
+        let mut transcript_create = Transcript::new(b"Test");
+        let mut transcript_verify = Transcript::new(b"Test");
+
+        let proof =
+            RangeProofPlus::new(vec![55], vec![32], vec![&open], &mut transcript_create).unwrap();
+
+        assert!(proof
+            .verify(vec![&comm], vec![32], &mut transcript_verify)
+            .is_ok());
+
+        // the amount must fit in the claimed bit-length
+        let (comm, open) = Pedersen::new(1_u64 << 32);
+        let proof = RangeProofPlus::new(
+            vec![1_u64 << 32],
+            vec![32],
+            vec![&open],
+            &mut Transcript::new(b"Test"),
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify(vec![&comm], vec![32], &mut Transcript::new(b"Test"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_aggregated_rangeproof_plus() {
+        let (comm_1, open_1) = Pedersen::new(55_u64);
+        let (comm_2, open_2) = Pedersen::new(77_u64);
+        let (comm_3, open_3) = Pedersen::new(99_u64);
+
+        let mut transcript_create = Transcript::new(b"Test");
+        let mut transcript_verify = Transcript::new(b"Test");
+
+        let proof = RangeProofPlus::new(
+            vec![55, 77, 99],
+            vec![64, 32, 32],
+            vec![&open_1, &open_2, &open_3],
+            &mut transcript_create,
+        )
+        .unwrap();
+
+        let pod_proof: pod::RangeProofPlus128 = proof.try_into().unwrap();
+        let proof: RangeProofPlus = pod_proof.try_into().unwrap();
+
+        assert!(proof
+            .verify(
+                vec![&comm_1, &comm_2, &comm_3],
+                vec![64, 32, 32],
+                &mut transcript_verify,
+            )
+            .is_ok());
+
+        // the commitments must be in the same order as the amounts in the proof
+        assert!(proof
+            .verify(
+                vec![&comm_2, &comm_1, &comm_3],
+                vec![64, 32, 32],
+                &mut Transcript::new(b"Test"),
+            )
+            .is_err());
+    }
+}
@@ -4,8 +4,10 @@
 use curve25519_dalek::scalar::Scalar;
 
 /// Represents a degree-1 vector polynomial \\(\mathbf{a} + \mathbf{b} \cdot x\\).
+#[cfg(feature = "prover")]
 pub struct VecPoly1(pub Vec<Scalar>, pub Vec<Scalar>);
 
+#[cfg(feature = "prover")]
 impl VecPoly1 {
     pub fn zero(n: usize) -> Self {
         VecPoly1(vec![Scalar::zero(); n], vec![Scalar::zero(); n])
@@ -39,8 +41,10 @@ impl VecPoly1 {
 }
 
 /// Represents a degree-2 scalar polynomial \\(a + b \cdot x + c \cdot x^2\\)
+#[cfg(feature = "prover")]
 pub struct Poly2(pub Scalar, pub Scalar, pub Scalar);
 
+#[cfg(feature = "prover")]
 impl Poly2 {
     pub fn eval(&self, x: Scalar) -> Scalar {
         self.0 + x * (self.1 + x * self.2)
@@ -75,6 +79,7 @@ pub fn exp_iter(x: Scalar) -> ScalarExp {
     ScalarExp { x, next_exp_x }
 }
 
+#[cfg(feature = "prover")]
 pub fn add_vec(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
     if a.len() != b.len() {
         // throw some error
@@ -99,6 +104,7 @@ pub fn read32(data: &[u8]) -> [u8; 32] {
 ///    {\langle {\mathbf{a}}, {\mathbf{b}} \rangle} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i.
 /// \\]
 /// Panics if the lengths of \\(\mathbf{a}\\) and \\(\mathbf{b}\\) are not equal.
+#[cfg(feature = "prover")]
 pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
     let mut out = Scalar::zero();
     if a.len() != b.len() {
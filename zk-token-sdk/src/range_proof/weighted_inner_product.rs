@@ -0,0 +1,334 @@
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::pedersen::{G, H},
+    curve25519_dalek::{ristretto::RistrettoPoint, traits::MultiscalarMul},
+    rand::rngs::OsRng,
+};
+use {
+    crate::{
+        range_proof::{errors::RangeProofError, util},
+        transcript::TranscriptProtocol,
+    },
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
+    merlin::Transcript,
+};
+
+/// Weighted inner product argument of Bulletproofs+.
+///
+/// For generators \\(\mathbf{g}\\), \\(\mathbf{h}\\) and a weight \\(y\\), the proof certifies
+/// knowledge of vectors \\(\mathbf{a}\\), \\(\mathbf{b}\\) and a scalar \\(\alpha\\) such that
+/// \\(P = \langle \mathbf{a}, \mathbf{g} \rangle + \langle \mathbf{b}, \mathbf{h} \rangle +
+/// (\mathbf{a} \odot_y \mathbf{b}) G + \alpha H\\), where
+/// \\(\mathbf{a} \odot_y \mathbf{b} = \sum\_{i=1}^{n} a\_i b\_i y^i\\).
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct WeightedInnerProductProof {
+    pub L_vec: Vec<CompressedRistretto>, // 32 * log(bit_length)
+    pub R_vec: Vec<CompressedRistretto>, // 32 * log(bit_length)
+    pub A: CompressedRistretto,          // 32 bytes
+    pub B: CompressedRistretto,          // 32 bytes
+    pub r: Scalar,                       // 32 bytes
+    pub s: Scalar,                       // 32 bytes
+    pub delta: Scalar,                   // 32 bytes
+}
+
+/// Scalars that the verifier needs to check a weighted inner product proof.
+pub(crate) struct WeightedInnerProductVerificationScalars {
+    /// Squares of the round challenges
+    pub challenges_sq: Vec<Scalar>,
+    /// Squares of the inverses of the round challenges
+    pub challenges_inv_sq: Vec<Scalar>,
+    /// Coefficients of the generators \\(\mathbf{g}\\) after all the rounds of folding
+    pub g_scalars: Vec<Scalar>,
+    /// Coefficients of the generators \\(\mathbf{h}\\) after all the rounds of folding
+    pub h_scalars: Vec<Scalar>,
+    /// Challenge of the final round
+    pub e: Scalar,
+}
+
+#[allow(non_snake_case)]
+impl WeightedInnerProductProof {
+    /// Create a weighted inner product proof.
+    ///
+    /// The `y_powers` vector must contain the powers \\(y^1, \ldots, y^n\\) of the weight. The
+    /// lengths of the vectors must all be the same and must be a power of two.
+    #[cfg(feature = "prover")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        mut g_vec: Vec<RistrettoPoint>,
+        mut h_vec: Vec<RistrettoPoint>,
+        mut a_vec: Vec<Scalar>,
+        mut b_vec: Vec<Scalar>,
+        mut alpha: Scalar,
+        y: &Scalar,
+        y_powers: &[Scalar],
+        transcript: &mut Transcript,
+    ) -> Self {
+        let mut g = &mut g_vec[..];
+        let mut h = &mut h_vec[..];
+        let mut a = &mut a_vec[..];
+        let mut b = &mut b_vec[..];
+
+        let mut n = g.len();
+
+        // All of the input vectors must have the same length.
+        assert_eq!(h.len(), n);
+        assert_eq!(a.len(), n);
+        assert_eq!(b.len(), n);
+        assert_eq!(y_powers.len(), n);
+
+        // All of the input vectors must have a length that is a power of two.
+        assert!(n.is_power_of_two());
+
+        transcript.weighted_innerproduct_domain_sep(n as u64);
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+
+        while n != 1 {
+            n /= 2;
+            let (a_L, a_R) = a.split_at_mut(n);
+            let (b_L, b_R) = b.split_at_mut(n);
+            let (g_L, g_R) = g.split_at_mut(n);
+            let (h_L, h_R) = h.split_at_mut(n);
+
+            let y_n = y_powers[n - 1];
+            let y_n_inv = y_n.invert();
+
+            let c_L = weighted_inner_product(a_L, b_R, y_powers);
+            let c_R = y_n * weighted_inner_product(a_R, b_L, y_powers);
+
+            let d_L = Scalar::random(&mut OsRng);
+            let d_R = Scalar::random(&mut OsRng);
+
+            let L = RistrettoPoint::multiscalar_mul(
+                a_L.iter()
+                    .map(|a_L_i| a_L_i * y_n_inv)
+                    .chain(b_R.iter().cloned())
+                    .chain([c_L, d_L]),
+                g_R.iter().chain(h_L.iter()).chain([&(*G), &(*H)]),
+            )
+            .compress();
+
+            let R = RistrettoPoint::multiscalar_mul(
+                a_R.iter()
+                    .map(|a_R_i| a_R_i * y_n)
+                    .chain(b_L.iter().cloned())
+                    .chain([c_R, d_R]),
+                g_L.iter().chain(h_R.iter()).chain([&(*G), &(*H)]),
+            )
+            .compress();
+
+            L_vec.push(L);
+            R_vec.push(R);
+
+            transcript.append_point(b"L", &L);
+            transcript.append_point(b"R", &R);
+
+            let e = transcript.challenge_scalar(b"e");
+            let e_inv = e.invert();
+
+            for i in 0..n {
+                a_L[i] = a_L[i] * e + a_R[i] * e_inv * y_n;
+                b_L[i] = b_L[i] * e_inv + b_R[i] * e;
+                g_L[i] = RistrettoPoint::multiscalar_mul(&[e_inv, e * y_n_inv], &[g_L[i], g_R[i]]);
+                h_L[i] = RistrettoPoint::multiscalar_mul(&[e, e_inv], &[h_L[i], h_R[i]]);
+            }
+            alpha += e * e * d_L + e_inv * e_inv * d_R;
+
+            a = a_L;
+            b = b_L;
+            g = g_L;
+            h = h_L;
+        }
+
+        // the final round proves knowledge of the remaining length-one vectors in zero-knowledge
+        let r = Scalar::random(&mut OsRng);
+        let s = Scalar::random(&mut OsRng);
+        let delta = Scalar::random(&mut OsRng);
+        let eta = Scalar::random(&mut OsRng);
+
+        let A = RistrettoPoint::multiscalar_mul(
+            &[r, s, y * (r * b[0] + s * a[0]), delta],
+            &[g[0], h[0], *G, *H],
+        )
+        .compress();
+        let B = RistrettoPoint::multiscalar_mul(&[r * y * s, eta], &[*G, *H]).compress();
+
+        transcript.append_point(b"A", &A);
+        transcript.append_point(b"B", &B);
+
+        let e = transcript.challenge_scalar(b"e");
+
+        WeightedInnerProductProof {
+            L_vec,
+            R_vec,
+            A,
+            B,
+            r: r + a[0] * e,
+            s: s + b[0] * e,
+            delta: eta + delta * e + alpha * e * e,
+        }
+    }
+
+    /// Computes the challenges and the folded generator coefficients of the proof.
+    ///
+    /// The proof is valid if and only if
+    /// \\[
+    ///   e^2 (P + \sum\_j e\_j^2 L\_j + e\_j^{-2} R\_j) + e A + B =
+    ///   r e \langle \mathbf{s\_g}, \mathbf{g} \rangle + s e \langle \mathbf{s\_h}, \mathbf{h} \rangle
+    ///   + r y s G + \delta H,
+    /// \\]
+    /// which is left for the caller to check so that it can be combined with other checks in a
+    /// single multiscalar multiplication.
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        y: &Scalar,
+        transcript: &mut Transcript,
+    ) -> Result<WeightedInnerProductVerificationScalars, RangeProofError> {
+        let lg_n = self.L_vec.len();
+        if lg_n >= 32 || self.R_vec.len() != lg_n {
+            return Err(RangeProofError::InvalidBitsize);
+        }
+        if n != (1 << lg_n) {
+            return Err(RangeProofError::InvalidBitsize);
+        }
+
+        transcript.weighted_innerproduct_domain_sep(n as u64);
+
+        let mut challenges = Vec::with_capacity(lg_n);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            transcript.validate_and_append_point(b"L", L)?;
+            transcript.validate_and_append_point(b"R", R)?;
+            challenges.push(transcript.challenge_scalar(b"e"));
+        }
+
+        transcript.validate_and_append_point(b"A", &self.A)?;
+        transcript.validate_and_append_point(b"B", &self.B)?;
+
+        let e = transcript.challenge_scalar(b"e");
+
+        let mut challenges_inv = challenges.clone();
+        Scalar::batch_invert(&mut challenges_inv);
+
+        // The generators are folded from the most significant bit of their index: in round `j`,
+        // the generators with a zero bit at position `lg_n - 1 - j` are scaled by `(e_j^-1, e_j)`
+        // and the generators with a one bit are scaled by `(e_j * y^{-n_j}, e_j^-1)`, where `n_j`
+        // is the half-length of the vectors in that round.
+        let y_inv = y.invert();
+        let mut g_scalars = vec![Scalar::one()];
+        let mut h_scalars = vec![Scalar::one()];
+        for (j, (e_j, e_j_inv)) in challenges.iter().zip(challenges_inv.iter()).enumerate() {
+            let y_inv_n_j = util::exp_iter(y_inv)
+                .nth(n >> (j + 1))
+                .ok_or(RangeProofError::InvalidBitsize)?;
+
+            g_scalars = g_scalars
+                .iter()
+                .flat_map(|s| [s * e_j_inv, s * e_j * y_inv_n_j])
+                .collect();
+            h_scalars = h_scalars
+                .iter()
+                .flat_map(|s| [s * e_j, s * e_j_inv])
+                .collect();
+        }
+
+        Ok(WeightedInnerProductVerificationScalars {
+            challenges_sq: challenges.iter().map(|e_j| e_j * e_j).collect(),
+            challenges_inv_sq: challenges_inv.iter().map(|e_j| e_j * e_j).collect(),
+            g_scalars,
+            h_scalars,
+            e,
+        })
+    }
+
+    /// Returns the size in bytes required to serialize the weighted inner product proof.
+    ///
+    /// For vectors of length `n` the proof size is \\(32 \cdot (2\lg n+5)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        (self.L_vec.len() * 2 + 5) * 32
+    }
+
+    /// Serializes the proof into a byte array of \\(2\lg n+5\\) 32-byte elements.
+    /// The layout of the weighted inner product proof is:
+    /// * \\(\lg n\\) pairs of compressed Ristretto points \\(L_0, R_0, \dots, L_{\lg n-1}, R_{\lg n-1}\\),
+    /// * two compressed Ristretto points \\(A, B\\),
+    /// * three scalars \\(r, s, \delta\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        for (l, r) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            buf.extend_from_slice(l.as_bytes());
+            buf.extend_from_slice(r.as_bytes());
+        }
+        buf.extend_from_slice(self.A.as_bytes());
+        buf.extend_from_slice(self.B.as_bytes());
+        buf.extend_from_slice(self.r.as_bytes());
+        buf.extend_from_slice(self.s.as_bytes());
+        buf.extend_from_slice(self.delta.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    /// Returns an error in the following cases:
+    /// * the slice does not have \\(2\lg n+5\\) 32-byte elements,
+    /// * \\(\lg n\\) is larger or equal to 32 (proof is too big),
+    /// * any of the scalars are not canonical scalars modulo Ristretto group order.
+    pub fn from_bytes(slice: &[u8]) -> Result<WeightedInnerProductProof, RangeProofError> {
+        if slice.len() % 32 != 0 {
+            return Err(RangeProofError::Format);
+        }
+        let num_elements = slice.len() / 32;
+        if num_elements < 5 || (num_elements - 5) % 2 != 0 {
+            return Err(RangeProofError::Format);
+        }
+        let lg_n = (num_elements - 5) / 2;
+        if lg_n >= 32 {
+            return Err(RangeProofError::Format);
+        }
+
+        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            let pos = 2 * i * 32;
+            L_vec.push(CompressedRistretto(util::read32(&slice[pos..])));
+            R_vec.push(CompressedRistretto(util::read32(&slice[pos + 32..])));
+        }
+
+        let pos = 2 * lg_n * 32;
+        let A = CompressedRistretto(util::read32(&slice[pos..]));
+        let B = CompressedRistretto(util::read32(&slice[pos + 32..]));
+        let r = Scalar::from_canonical_bytes(util::read32(&slice[pos + 2 * 32..]))
+            .ok_or(RangeProofError::NonCanonical)?;
+        let s = Scalar::from_canonical_bytes(util::read32(&slice[pos + 3 * 32..]))
+            .ok_or(RangeProofError::NonCanonical)?;
+        let delta = Scalar::from_canonical_bytes(util::read32(&slice[pos + 4 * 32..]))
+            .ok_or(RangeProofError::NonCanonical)?;
+
+        Ok(WeightedInnerProductProof {
+            L_vec,
+            R_vec,
+            A,
+            B,
+            r,
+            s,
+            delta,
+        })
+    }
+}
+
+/// Computes the weighted inner product
+/// \\[
+///    \mathbf{a} \odot_y \mathbf{b} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i \cdot y^{i+1},
+/// \\]
+/// where `y_powers` holds the powers \\(y^1, y^2, \ldots\\) of the weight.
+#[cfg(feature = "prover")]
+pub(crate) fn weighted_inner_product(a: &[Scalar], b: &[Scalar], y_powers: &[Scalar]) -> Scalar {
+    assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b.iter())
+        .zip(y_powers.iter())
+        .map(|((a_i, b_i), y_i)| a_i * b_i * y_i)
+        .sum()
+}
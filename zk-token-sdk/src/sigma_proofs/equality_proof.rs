@@ -5,27 +5,34 @@
 //! commitment pair encrypts/encodes the same message. To generate the proof, a prover must provide
 //! the decryption key for the ciphertext and the Pedersen opening for the commitment.
 //!
+//! A ciphertext-ciphertext equality proof is defined with respect to two twisted ElGamal
+//! ciphertexts under (possibly) different public keys. The proof certifies that the two
+//! ciphertexts encrypt the same message. To generate the proof, a prover must provide the
+//! decryption key for the first ciphertext and the Pedersen opening for the second ciphertext.
+//!
 //! The protocol guarantees computationally soundness (by the hardness of discrete log) and perfect
 //! zero-knowledge in the random oracle model.
 
 #[cfg(not(target_arch = "bpf"))]
-use {
-    crate::encryption::{
-        elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
-        pedersen::{PedersenCommitment, PedersenOpening, G, H},
+use crate::{
+    batch::MultiscalarMulBatch,
+    encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        pedersen::{PedersenCommitment, G, H},
     },
-    curve25519_dalek::traits::MultiscalarMul,
-    rand::rngs::OsRng,
+    errors::ProofError,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::{elgamal::ElGamalKeypair, pedersen::PedersenOpening},
+    curve25519_dalek::{ristretto::RistrettoPoint, traits::MultiscalarMul},
+    rand::{rngs::OsRng, CryptoRng, RngCore},
     zeroize::Zeroize,
 };
 use {
     crate::{sigma_proofs::errors::EqualityProofError, transcript::TranscriptProtocol},
     arrayref::{array_ref, array_refs},
-    curve25519_dalek::{
-        ristretto::{CompressedRistretto, RistrettoPoint},
-        scalar::Scalar,
-        traits::{IsIdentity, VartimeMultiscalarMul},
-    },
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
     merlin::Transcript,
 };
 
@@ -62,12 +69,33 @@ impl EqualityProof {
     /// * `amount` - The message associated with the ElGamal ciphertext and Pedersen commitment
     /// * `opening` - The opening associated with the main Pedersen commitment to be proved
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
     pub fn new(
         elgamal_keypair: &ElGamalKeypair,
         ciphertext: &ElGamalCiphertext,
         amount: u64,
         opening: &PedersenOpening,
         transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(
+            elgamal_keypair,
+            ciphertext,
+            amount,
+            opening,
+            transcript,
+            &mut OsRng,
+        )
+    }
+
+    /// Like `EqualityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        elgamal_keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        opening: &PedersenOpening,
+        transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         transcript.equality_proof_domain_sep();
 
@@ -80,9 +108,9 @@ impl EqualityProof {
         let r = opening.get_scalar();
 
         // generate random masking factors that also serves as nonces
-        let mut y_s = Scalar::random(&mut OsRng);
-        let mut y_x = Scalar::random(&mut OsRng);
-        let mut y_r = Scalar::random(&mut OsRng);
+        let mut y_s = Scalar::random(rng);
+        let mut y_x = Scalar::random(rng);
+        let mut y_r = Scalar::random(rng);
 
         let Y_0 = (&y_s * P_EG).compress();
         let Y_1 = RistrettoPoint::multiscalar_mul(vec![&y_x, &y_s], vec![&(*G), D_EG]).compress();
@@ -128,6 +156,37 @@ impl EqualityProof {
         ciphertext: &ElGamalCiphertext,
         commitment: &PedersenCommitment,
         transcript: &mut Transcript,
+    ) -> Result<(), EqualityProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(
+            elgamal_pubkey,
+            ciphertext,
+            commitment,
+            transcript,
+            &mut batch,
+        )?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(EqualityProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Equality proof verifier that defers the check of the algebraic relation to `batch`.
+    ///
+    /// * `elgamal_pubkey` - The ElGamal pubkey associated with the ciphertext to be proved
+    /// * `ciphertext` - The main ElGamal ciphertext to be proved
+    /// * `commitment` - The main Pedersen commitment to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        elgamal_pubkey: &ElGamalPubkey,
+        ciphertext: &ElGamalCiphertext,
+        commitment: &PedersenCommitment,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), EqualityProofError> {
         transcript.equality_proof_domain_sep();
 
@@ -149,45 +208,51 @@ impl EqualityProof {
         let w_negated = -&w;
         let ww_negated = -&ww;
 
-        // check that the required algebraic condition holds
-        let Y_0 = self.Y_0.decompress().ok_or(EqualityProofError::Format)?;
-        let Y_1 = self.Y_1.decompress().ok_or(EqualityProofError::Format)?;
-        let Y_2 = self.Y_2.decompress().ok_or(EqualityProofError::Format)?;
-
-        let check = RistrettoPoint::vartime_multiscalar_mul(
+        // add the required algebraic condition to the batch
+        let Y_0 = self
+            .Y_0
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+        let Y_1 = self
+            .Y_1
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+        let Y_2 = self
+            .Y_2
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+
+        batch.append(
+            ProofError::EqualityProofFailed,
             vec![
-                &self.z_s,           // z_s
-                &(-&c),              // -c
-                &(-&Scalar::one()),  // -identity
-                &(&w * &self.z_x),   // w * z_x
-                &(&w * &self.z_s),   // w * z_s
-                &(&w_negated * &c),  // -w * c
-                &w_negated,          // -w
-                &(&ww * &self.z_x),  // ww * z_x
-                &(&ww * &self.z_r),  // ww * z_r
-                &(&ww_negated * &c), // -ww * c
-                &ww_negated,         // -ww
+                self.z_s,         // z_s
+                -&c,              // -c
+                -&Scalar::one(),  // -identity
+                &w * &self.z_x,   // w * z_x
+                &w * &self.z_s,   // w * z_s
+                &w_negated * &c,  // -w * c
+                w_negated,        // -w
+                &ww * &self.z_x,  // ww * z_x
+                &ww * &self.z_r,  // ww * z_r
+                &ww_negated * &c, // -ww * c
+                ww_negated,       // -ww
             ],
             vec![
-                P_EG,  // P_EG
-                &(*H), // H
-                &Y_0,  // Y_0
-                &(*G), // G
-                D_EG,  // D_EG
-                C_EG,  // C_EG
-                &Y_1,  // Y_1
-                &(*G), // G
-                &(*H), // H
-                C_Ped, // C_Ped
-                &Y_2,  // Y_2
+                *P_EG,  // P_EG
+                *H,     // H
+                Y_0,    // Y_0
+                *G,     // G
+                *D_EG,  // D_EG
+                *C_EG,  // C_EG
+                Y_1,    // Y_1
+                *G,     // G
+                *H,     // H
+                *C_Ped, // C_Ped
+                Y_2,    // Y_2
             ],
         );
 
-        if check.is_identity() {
-            Ok(())
-        } else {
-            Err(EqualityProofError::AlgebraicRelation)
-        }
+        Ok(())
     }
 
     pub fn to_bytes(&self) -> [u8; 192] {
@@ -202,6 +267,10 @@ impl EqualityProof {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, EqualityProofError> {
+        if bytes.len() != 192 {
+            return Err(EqualityProofError::Format);
+        }
+
         let bytes = array_ref![bytes, 0, 192];
         let (Y_0, Y_1, Y_2, z_s, z_x, z_r) = array_refs![bytes, 32, 32, 32, 32, 32, 32];
 
@@ -209,9 +278,9 @@ impl EqualityProof {
         let Y_1 = CompressedRistretto::from_slice(Y_1);
         let Y_2 = CompressedRistretto::from_slice(Y_2);
 
-        let z_s = Scalar::from_canonical_bytes(*z_s).ok_or(EqualityProofError::Format)?;
-        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(EqualityProofError::Format)?;
-        let z_r = Scalar::from_canonical_bytes(*z_r).ok_or(EqualityProofError::Format)?;
+        let z_s = Scalar::from_canonical_bytes(*z_s).ok_or(EqualityProofError::NonCanonical)?;
+        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(EqualityProofError::NonCanonical)?;
+        let z_r = Scalar::from_canonical_bytes(*z_r).ok_or(EqualityProofError::NonCanonical)?;
 
         Ok(EqualityProof {
             Y_0,
@@ -224,10 +293,300 @@ impl EqualityProof {
     }
 }
 
+/// Ciphertext-ciphertext equality proof.
+///
+/// Contains all the elliptic curve and scalar components that make up the sigma protocol.
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct CtxtCtxtEqualityProof {
+    Y_0: CompressedRistretto,
+    Y_1: CompressedRistretto,
+    Y_2: CompressedRistretto,
+    Y_3: CompressedRistretto,
+    z_s: Scalar,
+    z_x: Scalar,
+    z_r: Scalar,
+}
+
+#[allow(non_snake_case)]
+#[cfg(not(target_arch = "bpf"))]
+impl CtxtCtxtEqualityProof {
+    /// Ciphertext-ciphertext equality proof constructor.
+    ///
+    /// The function does *not* hash the public keys or the ciphertexts into the transcript. For
+    /// security, the caller (the main protocol) should hash these public components prior to
+    /// invoking this constructor.
+    ///
+    /// This function is randomized. It uses `OsRng` internally to generate random scalars.
+    ///
+    /// * `source_keypair` - The ElGamal keypair associated with the first ciphertext to be proved
+    /// * `dest_pubkey` - The ElGamal pubkey associated with the second ciphertext to be proved
+    /// * `source_ciphertext` - The first ElGamal ciphertext to be proved
+    /// * `amount` - The message associated with the ElGamal ciphertexts
+    /// * `dest_opening` - The opening associated with the second ElGamal ciphertext to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
+    pub fn new(
+        source_keypair: &ElGamalKeypair,
+        dest_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        dest_opening: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(
+            source_keypair,
+            dest_pubkey,
+            source_ciphertext,
+            amount,
+            dest_opening,
+            transcript,
+            &mut OsRng,
+        )
+    }
+
+    /// Like `CtxtCtxtEqualityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        source_keypair: &ElGamalKeypair,
+        dest_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        dest_opening: &PedersenOpening,
+        transcript: &mut Transcript,
+        rng: &mut R,
+    ) -> Self {
+        transcript.ctxt_ctxt_equality_proof_domain_sep();
+
+        // extract the relevant scalar and Ristretto points from the inputs
+        let P_source = source_keypair.public.get_point();
+        let D_source = source_ciphertext.handle.get_point();
+        let P_dest = dest_pubkey.get_point();
+
+        let s = source_keypair.secret.get_scalar();
+        let x = Scalar::from(amount);
+        let r = dest_opening.get_scalar();
+
+        // generate random masking factors that also serves as nonces
+        let mut y_s = Scalar::random(rng);
+        let mut y_x = Scalar::random(rng);
+        let mut y_r = Scalar::random(rng);
+
+        let Y_0 = (&y_s * P_source).compress();
+        let Y_1 =
+            RistrettoPoint::multiscalar_mul(vec![&y_x, &y_s], vec![&(*G), D_source]).compress();
+        let Y_2 = RistrettoPoint::multiscalar_mul(vec![&y_x, &y_r], vec![&(*G), &(*H)]).compress();
+        let Y_3 = (&y_r * P_dest).compress();
+
+        // record masking factors in the transcript
+        transcript.append_point(b"Y_0", &Y_0);
+        transcript.append_point(b"Y_1", &Y_1);
+        transcript.append_point(b"Y_2", &Y_2);
+        transcript.append_point(b"Y_3", &Y_3);
+
+        let c = transcript.challenge_scalar(b"c");
+        transcript.challenge_scalar(b"w");
+
+        // compute the masked values
+        let z_s = &(&c * s) + &y_s;
+        let z_x = &(&c * &x) + &y_x;
+        let z_r = &(&c * r) + &y_r;
+
+        // zeroize random scalars
+        y_s.zeroize();
+        y_x.zeroize();
+        y_r.zeroize();
+
+        CtxtCtxtEqualityProof {
+            Y_0,
+            Y_1,
+            Y_2,
+            Y_3,
+            z_s,
+            z_x,
+            z_r,
+        }
+    }
+
+    /// Ciphertext-ciphertext equality proof verifier.
+    ///
+    /// * `source_pubkey` - The ElGamal pubkey associated with the first ciphertext to be proved
+    /// * `dest_pubkey` - The ElGamal pubkey associated with the second ciphertext to be proved
+    /// * `source_ciphertext` - The first ElGamal ciphertext to be proved
+    /// * `dest_ciphertext` - The second ElGamal ciphertext to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    pub fn verify(
+        self,
+        source_pubkey: &ElGamalPubkey,
+        dest_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        dest_ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+    ) -> Result<(), EqualityProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(
+            source_pubkey,
+            dest_pubkey,
+            source_ciphertext,
+            dest_ciphertext,
+            transcript,
+            &mut batch,
+        )?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(EqualityProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Ciphertext-ciphertext equality proof verifier that defers the check of the algebraic
+    /// relation to `batch`.
+    ///
+    /// * `source_pubkey` - The ElGamal pubkey associated with the first ciphertext to be proved
+    /// * `dest_pubkey` - The ElGamal pubkey associated with the second ciphertext to be proved
+    /// * `source_ciphertext` - The first ElGamal ciphertext to be proved
+    /// * `dest_ciphertext` - The second ElGamal ciphertext to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        source_pubkey: &ElGamalPubkey,
+        dest_pubkey: &ElGamalPubkey,
+        source_ciphertext: &ElGamalCiphertext,
+        dest_ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), EqualityProofError> {
+        transcript.ctxt_ctxt_equality_proof_domain_sep();
+
+        // extract the relevant scalar and Ristretto points from the inputs
+        let P_source = source_pubkey.get_point();
+        let C_source = source_ciphertext.commitment.get_point();
+        let D_source = source_ciphertext.handle.get_point();
+
+        let P_dest = dest_pubkey.get_point();
+        let C_dest = dest_ciphertext.commitment.get_point();
+        let D_dest = dest_ciphertext.handle.get_point();
+
+        // include Y_0, Y_1, Y_2, Y_3 to transcript and extract challenges
+        transcript.validate_and_append_point(b"Y_0", &self.Y_0)?;
+        transcript.validate_and_append_point(b"Y_1", &self.Y_1)?;
+        transcript.validate_and_append_point(b"Y_2", &self.Y_2)?;
+        transcript.validate_and_append_point(b"Y_3", &self.Y_3)?;
+
+        let c = transcript.challenge_scalar(b"c");
+        let w = transcript.challenge_scalar(b"w"); // w used for batch verification
+        let ww = &w * &w;
+        let www = &w * &ww;
+
+        let w_negated = -&w;
+        let ww_negated = -&ww;
+        let www_negated = -&www;
+
+        // add the required algebraic condition to the batch
+        let Y_0 = self
+            .Y_0
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+        let Y_1 = self
+            .Y_1
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+        let Y_2 = self
+            .Y_2
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+        let Y_3 = self
+            .Y_3
+            .decompress()
+            .ok_or(EqualityProofError::NonCanonical)?;
+
+        batch.append(
+            ProofError::EqualityProofFailed,
+            vec![
+                self.z_s,          // z_s
+                -&c,               // -c
+                -&Scalar::one(),   // -identity
+                &w * &self.z_x,    // w * z_x
+                &w * &self.z_s,    // w * z_s
+                &w_negated * &c,   // -w * c
+                w_negated,         // -w
+                &ww * &self.z_x,   // ww * z_x
+                &ww * &self.z_r,   // ww * z_r
+                &ww_negated * &c,  // -ww * c
+                ww_negated,        // -ww
+                &www * &self.z_r,  // www * z_r
+                &www_negated * &c, // -www * c
+                www_negated,       // -www
+            ],
+            vec![
+                *P_source, // P_source
+                *H,        // H
+                Y_0,       // Y_0
+                *G,        // G
+                *D_source, // D_source
+                *C_source, // C_source
+                Y_1,       // Y_1
+                *G,        // G
+                *H,        // H
+                *C_dest,   // C_dest
+                Y_2,       // Y_2
+                *P_dest,   // P_dest
+                *D_dest,   // D_dest
+                Y_3,       // Y_3
+            ],
+        );
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 224] {
+        let mut buf = [0_u8; 224];
+        buf[..32].copy_from_slice(self.Y_0.as_bytes());
+        buf[32..64].copy_from_slice(self.Y_1.as_bytes());
+        buf[64..96].copy_from_slice(self.Y_2.as_bytes());
+        buf[96..128].copy_from_slice(self.Y_3.as_bytes());
+        buf[128..160].copy_from_slice(self.z_s.as_bytes());
+        buf[160..192].copy_from_slice(self.z_x.as_bytes());
+        buf[192..224].copy_from_slice(self.z_r.as_bytes());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EqualityProofError> {
+        if bytes.len() != 224 {
+            return Err(EqualityProofError::Format);
+        }
+
+        let bytes = array_ref![bytes, 0, 224];
+        let (Y_0, Y_1, Y_2, Y_3, z_s, z_x, z_r) = array_refs![bytes, 32, 32, 32, 32, 32, 32, 32];
+
+        let Y_0 = CompressedRistretto::from_slice(Y_0);
+        let Y_1 = CompressedRistretto::from_slice(Y_1);
+        let Y_2 = CompressedRistretto::from_slice(Y_2);
+        let Y_3 = CompressedRistretto::from_slice(Y_3);
+
+        let z_s = Scalar::from_canonical_bytes(*z_s).ok_or(EqualityProofError::NonCanonical)?;
+        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(EqualityProofError::NonCanonical)?;
+        let z_r = Scalar::from_canonical_bytes(*z_r).ok_or(EqualityProofError::NonCanonical)?;
+
+        Ok(CtxtCtxtEqualityProof {
+            Y_0,
+            Y_1,
+            Y_2,
+            Y_3,
+            z_s,
+            z_x,
+            z_r,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::encryption::{elgamal::ElGamalSecretKey, pedersen::Pedersen};
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn test_equality_proof_correctness() {
@@ -287,6 +646,41 @@ mod test {
             .is_err());
     }
 
+    #[test]
+    fn test_equality_proof_with_rng() {
+        // a seeded rng yields the same keypair, ciphertext, and proof on every run
+        let prove = || {
+            let mut rng = StdRng::seed_from_u64(0);
+
+            let elgamal_keypair = ElGamalKeypair::new_rand_with_rng(&mut rng);
+            let message: u64 = 55;
+
+            let ciphertext = elgamal_keypair.public.encrypt_with_rng(message, &mut rng);
+            let (commitment, opening) = Pedersen::new_with_rng(message, &mut rng);
+
+            let mut transcript_prover = Transcript::new(b"Test");
+            let proof = EqualityProof::new_with_rng(
+                &elgamal_keypair,
+                &ciphertext,
+                message,
+                &opening,
+                &mut transcript_prover,
+                &mut rng,
+            );
+
+            (elgamal_keypair.public, ciphertext, commitment, proof)
+        };
+
+        let (pubkey, ciphertext, commitment, proof) = prove();
+        let (_, _, _, same_proof) = prove();
+        assert_eq!(proof.to_bytes(), same_proof.to_bytes());
+
+        let mut transcript_verifier = Transcript::new(b"Test");
+        assert!(proof
+            .verify(&pubkey, &ciphertext, &commitment, &mut transcript_verifier)
+            .is_ok());
+    }
+
     #[test]
     fn test_equality_proof_edge_cases() {
         // if ElGamal public key zero (public key is invalid), then the proof should always reject
@@ -405,4 +799,72 @@ mod test {
             )
             .is_ok());
     }
+
+    #[test]
+    fn test_ctxt_ctxt_equality_proof_correctness() {
+        // success case
+        let source_keypair = ElGamalKeypair::new_rand();
+        let dest_keypair = ElGamalKeypair::new_rand();
+        let message: u64 = 55;
+
+        let source_ciphertext = source_keypair.public.encrypt(message);
+
+        let dest_opening = PedersenOpening::new_rand();
+        let dest_ciphertext = dest_keypair.public.encrypt_with(message, &dest_opening);
+
+        let mut transcript_prover = Transcript::new(b"Test");
+        let mut transcript_verifier = Transcript::new(b"Test");
+
+        let proof = CtxtCtxtEqualityProof::new(
+            &source_keypair,
+            &dest_keypair.public,
+            &source_ciphertext,
+            message,
+            &dest_opening,
+            &mut transcript_prover,
+        );
+
+        assert!(proof
+            .verify(
+                &source_keypair.public,
+                &dest_keypair.public,
+                &source_ciphertext,
+                &dest_ciphertext,
+                &mut transcript_verifier
+            )
+            .is_ok());
+
+        // fail case: encrypted messages are different
+        let source_message: u64 = 55;
+        let dest_message: u64 = 77;
+
+        let source_ciphertext = source_keypair.public.encrypt(source_message);
+
+        let dest_opening = PedersenOpening::new_rand();
+        let dest_ciphertext = dest_keypair
+            .public
+            .encrypt_with(dest_message, &dest_opening);
+
+        let mut transcript_prover = Transcript::new(b"Test");
+        let mut transcript_verifier = Transcript::new(b"Test");
+
+        let proof = CtxtCtxtEqualityProof::new(
+            &source_keypair,
+            &dest_keypair.public,
+            &source_ciphertext,
+            source_message,
+            &dest_opening,
+            &mut transcript_prover,
+        );
+
+        assert!(proof
+            .verify(
+                &source_keypair.public,
+                &dest_keypair.public,
+                &source_ciphertext,
+                &dest_ciphertext,
+                &mut transcript_verifier
+            )
+            .is_err());
+    }
 }
@@ -8,6 +8,8 @@ pub enum EqualityProofError {
     AlgebraicRelation,
     #[error("malformed proof")]
     Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
     #[error("multiscalar multiplication failed")]
     MultiscalarMul,
     #[error("transcript failed to produce a challenge")]
@@ -26,6 +28,8 @@ pub enum ValidityProofError {
     AlgebraicRelation,
     #[error("malformed proof")]
     Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
     #[error("multiscalar multiplication failed")]
     MultiscalarMul,
     #[error("transcript failed to produce a challenge")]
@@ -44,6 +48,8 @@ pub enum ZeroBalanceProofError {
     AlgebraicRelation,
     #[error("malformed proof")]
     Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
     #[error("multiscalar multiplication failed")]
     MultiscalarMul,
     #[error("transcript failed to produce a challenge")]
@@ -56,12 +62,34 @@ impl From<TranscriptError> for ZeroBalanceProofError {
     }
 }
 
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum PubkeyValidityProofError {
+    #[error("the required algebraic relation does not hold")]
+    AlgebraicRelation,
+    #[error("malformed proof")]
+    Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
+    #[error("multiscalar multiplication failed")]
+    MultiscalarMul,
+    #[error("transcript failed to produce a challenge")]
+    Transcript,
+}
+
+impl From<TranscriptError> for PubkeyValidityProofError {
+    fn from(_err: TranscriptError) -> Self {
+        Self::Transcript
+    }
+}
+
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
 pub enum FeeSigmaProofError {
     #[error("the required algebraic relation does not hold")]
     AlgebraicRelation,
     #[error("malformed proof")]
     Format,
+    #[error("proof contains a non-canonical point or scalar encoding")]
+    NonCanonical,
     #[error("multiscalar multiplication failed")]
     MultiscalarMul,
     #[error("transcript failed to produce a challenge")]
@@ -3,20 +3,24 @@
 //! TODO: Add detail on how the fee is calculated.
 
 #[cfg(not(target_arch = "bpf"))]
+use crate::{
+    batch::MultiscalarMulBatch,
+    encryption::pedersen::{PedersenCommitment, G, H},
+    errors::ProofError,
+};
+#[cfg(feature = "prover")]
 use {
-    crate::encryption::pedersen::{PedersenCommitment, PedersenOpening, G, H},
-    rand::rngs::OsRng,
+    crate::encryption::pedersen::PedersenOpening,
+    curve25519_dalek::{ristretto::RistrettoPoint, traits::MultiscalarMul},
+    rand::{rngs::OsRng, CryptoRng, RngCore},
+    subtle::ConstantTimeGreater,
 };
 use {
     crate::{sigma_proofs::errors::FeeSigmaProofError, transcript::TranscriptProtocol},
     arrayref::{array_ref, array_refs},
-    curve25519_dalek::{
-        ristretto::{CompressedRistretto, RistrettoPoint},
-        scalar::Scalar,
-        traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul},
-    },
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
     merlin::Transcript,
-    subtle::{Choice, ConditionallySelectable, ConstantTimeGreater},
+    subtle::{Choice, ConditionallySelectable},
 };
 
 /// Fee sigma proof.
@@ -48,12 +52,34 @@ impl FeeSigmaProof {
     /// "claimed" delta amount
     /// * `max_fee` - The maximum fee bound
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
     pub fn new(
         (fee_amount, commitment_fee, opening_fee): (u64, &PedersenCommitment, &PedersenOpening),
         (delta_fee, commitment_delta, opening_delta): (u64, &PedersenCommitment, &PedersenOpening),
         (commitment_claimed, opening_claimed): (&PedersenCommitment, &PedersenOpening),
         max_fee: u64,
         transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(
+            (fee_amount, commitment_fee, opening_fee),
+            (delta_fee, commitment_delta, opening_delta),
+            (commitment_claimed, opening_claimed),
+            max_fee,
+            transcript,
+            &mut OsRng,
+        )
+    }
+
+    /// Like `FeeSigmaProof::new`, but samples the random scalars of both the real and the
+    /// simulated proof from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        (fee_amount, commitment_fee, opening_fee): (u64, &PedersenCommitment, &PedersenOpening),
+        (delta_fee, commitment_delta, opening_delta): (u64, &PedersenCommitment, &PedersenOpening),
+        (commitment_claimed, opening_claimed): (&PedersenCommitment, &PedersenOpening),
+        max_fee: u64,
+        transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         let mut transcript_fee_above_max = transcript.clone();
         let mut transcript_fee_below_max = transcript.clone();
@@ -64,6 +90,7 @@ impl FeeSigmaProof {
             commitment_delta,
             commitment_claimed,
             &mut transcript_fee_above_max,
+            rng,
         );
 
         let proof_fee_below_max = Self::create_proof_fee_below_max(
@@ -72,6 +99,7 @@ impl FeeSigmaProof {
             opening_claimed,
             max_fee,
             &mut transcript_fee_below_max,
+            rng,
         );
 
         let below_max = u64::ct_gt(&max_fee, &fee_amount);
@@ -104,20 +132,22 @@ impl FeeSigmaProof {
     /// * `commitment_delta` - The Pedersen commitment of the "real" delta value
     /// * `commitment_claimed` - The Pedersen commitment of the "claimed" delta value
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
-    fn create_proof_fee_above_max(
+    #[cfg(feature = "prover")]
+    fn create_proof_fee_above_max<R: RngCore + CryptoRng>(
         opening_fee: &PedersenOpening,
         commitment_delta: &PedersenCommitment,
         commitment_claimed: &PedersenCommitment,
         transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         // simulate equality proof
         let C_delta = commitment_delta.get_point();
         let C_claimed = commitment_claimed.get_point();
 
-        let z_x = Scalar::random(&mut OsRng);
-        let z_delta = Scalar::random(&mut OsRng);
-        let z_claimed = Scalar::random(&mut OsRng);
-        let c_equality = Scalar::random(&mut OsRng);
+        let z_x = Scalar::random(rng);
+        let z_delta = Scalar::random(rng);
+        let z_claimed = Scalar::random(rng);
+        let c_equality = Scalar::random(rng);
 
         let Y_delta = RistrettoPoint::multiscalar_mul(
             vec![z_x, z_delta, -c_equality],
@@ -142,7 +172,7 @@ impl FeeSigmaProof {
         // generate max proof
         let r_fee = opening_fee.get_scalar();
 
-        let y_max_proof = Scalar::random(&mut OsRng);
+        let y_max_proof = Scalar::random(rng);
         let Y_max_proof = (y_max_proof * &(*H)).compress();
 
         transcript.append_point(b"Y_max_proof", &Y_max_proof);
@@ -177,19 +207,21 @@ impl FeeSigmaProof {
     /// * `opening_claimed` - The opening of the Pedersen commitment of the "claimed" delta value
     /// * `max_fee` - The maximum fee bound
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
-    fn create_proof_fee_below_max(
+    #[cfg(feature = "prover")]
+    fn create_proof_fee_below_max<R: RngCore + CryptoRng>(
         commitment_fee: &PedersenCommitment,
         (delta_fee, opening_delta): (u64, &PedersenOpening),
         opening_claimed: &PedersenOpening,
         max_fee: u64,
         transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         // simulate max proof
         let m = Scalar::from(max_fee);
         let C_fee = commitment_fee.get_point();
 
-        let z_max_proof = Scalar::random(&mut OsRng);
-        let c_max_proof = Scalar::random(&mut OsRng); // random challenge
+        let z_max_proof = Scalar::random(rng);
+        let c_max_proof = Scalar::random(rng); // random challenge
 
         // solve for Y_max in the verification algebraic relation
         let Y_max_proof = RistrettoPoint::multiscalar_mul(
@@ -210,9 +242,9 @@ impl FeeSigmaProof {
         let r_delta = opening_delta.get_scalar();
         let r_claimed = opening_claimed.get_scalar();
 
-        let y_x = Scalar::random(&mut OsRng);
-        let y_delta = Scalar::random(&mut OsRng);
-        let y_claimed = Scalar::random(&mut OsRng);
+        let y_x = Scalar::random(rng);
+        let y_delta = Scalar::random(rng);
+        let y_claimed = Scalar::random(rng);
 
         let Y_delta =
             RistrettoPoint::multiscalar_mul(vec![y_x, y_delta], vec![&(*G), &(*H)]).compress();
@@ -260,6 +292,40 @@ impl FeeSigmaProof {
         commitment_claimed: &PedersenCommitment,
         max_fee: u64,
         transcript: &mut Transcript,
+    ) -> Result<(), FeeSigmaProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(
+            commitment_fee,
+            commitment_delta,
+            commitment_claimed,
+            max_fee,
+            transcript,
+            &mut batch,
+        )?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(FeeSigmaProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Fee sigma proof verifier that defers the check of the algebraic relation to `batch`.
+    ///
+    /// * `commitment_fee` - The Pedersen commitment of the transfer fee
+    /// * `commitment_delta` - The Pedersen commitment of the "real" delta value
+    /// * `commitment_claimed` - The Pedersen commitment of the "claimed" delta value
+    /// * `max_fee` - The maximum fee bound
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        commitment_fee: &PedersenCommitment,
+        commitment_delta: &PedersenCommitment,
+        commitment_claimed: &PedersenCommitment,
+        max_fee: u64,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), FeeSigmaProofError> {
         // extract the relevant scalar and Ristretto points from the input
         let m = Scalar::from(max_fee);
@@ -276,19 +342,19 @@ impl FeeSigmaProof {
             .fee_max_proof
             .Y_max_proof
             .decompress()
-            .ok_or(FeeSigmaProofError::Format)?;
+            .ok_or(FeeSigmaProofError::NonCanonical)?;
         let z_max = self.fee_max_proof.z_max_proof;
 
         let Y_delta_real = self
             .fee_equality_proof
             .Y_delta
             .decompress()
-            .ok_or(FeeSigmaProofError::Format)?;
+            .ok_or(FeeSigmaProofError::NonCanonical)?;
         let Y_claimed = self
             .fee_equality_proof
             .Y_claimed
             .decompress()
-            .ok_or(FeeSigmaProofError::Format)?;
+            .ok_or(FeeSigmaProofError::NonCanonical)?;
         let z_x = self.fee_equality_proof.z_x;
         let z_delta_real = self.fee_equality_proof.z_delta;
         let z_claimed = self.fee_equality_proof.z_claimed;
@@ -300,7 +366,8 @@ impl FeeSigmaProof {
         let w = transcript.challenge_scalar(b"w");
         let ww = w * w;
 
-        let check = RistrettoPoint::vartime_multiscalar_mul(
+        batch.append(
+            ProofError::FeeSigmaProofFailed,
             vec![
                 c_max_proof,
                 -c_max_proof * m,
@@ -316,26 +383,22 @@ impl FeeSigmaProof {
                 -ww,
             ],
             vec![
-                C_max,
-                &(*G),
-                &(*H),
-                &Y_max,
-                &(*G),
-                &(*H),
-                C_delta,
-                &Y_delta_real,
-                &(*G),
-                &(*H),
-                C_claimed,
-                &Y_claimed,
+                *C_max,
+                *G,
+                *H,
+                Y_max,
+                *G,
+                *H,
+                *C_delta,
+                Y_delta_real,
+                *G,
+                *H,
+                *C_claimed,
+                Y_claimed,
             ],
         );
 
-        if check.is_identity() {
-            Ok(())
-        } else {
-            Err(FeeSigmaProofError::AlgebraicRelation)
-        }
+        Ok(())
     }
 
     pub fn to_bytes(&self) -> [u8; 256] {
@@ -352,22 +415,27 @@ impl FeeSigmaProof {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, FeeSigmaProofError> {
+        if bytes.len() != 256 {
+            return Err(FeeSigmaProofError::Format);
+        }
+
         let bytes = array_ref![bytes, 0, 256];
         let (Y_max_proof, z_max_proof, c_max_proof, Y_delta, Y_claimed, z_x, z_delta, z_claimed) =
             array_refs![bytes, 32, 32, 32, 32, 32, 32, 32, 32];
 
         let Y_max_proof = CompressedRistretto::from_slice(Y_max_proof);
         let z_max_proof =
-            Scalar::from_canonical_bytes(*z_max_proof).ok_or(FeeSigmaProofError::Format)?;
+            Scalar::from_canonical_bytes(*z_max_proof).ok_or(FeeSigmaProofError::NonCanonical)?;
         let c_max_proof =
-            Scalar::from_canonical_bytes(*c_max_proof).ok_or(FeeSigmaProofError::Format)?;
+            Scalar::from_canonical_bytes(*c_max_proof).ok_or(FeeSigmaProofError::NonCanonical)?;
 
         let Y_delta = CompressedRistretto::from_slice(Y_delta);
         let Y_claimed = CompressedRistretto::from_slice(Y_claimed);
-        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(FeeSigmaProofError::Format)?;
-        let z_delta = Scalar::from_canonical_bytes(*z_delta).ok_or(FeeSigmaProofError::Format)?;
+        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(FeeSigmaProofError::NonCanonical)?;
+        let z_delta =
+            Scalar::from_canonical_bytes(*z_delta).ok_or(FeeSigmaProofError::NonCanonical)?;
         let z_claimed =
-            Scalar::from_canonical_bytes(*z_claimed).ok_or(FeeSigmaProofError::Format)?;
+            Scalar::from_canonical_bytes(*z_claimed).ok_or(FeeSigmaProofError::NonCanonical)?;
 
         Ok(Self {
             fee_max_proof: FeeMaxProof {
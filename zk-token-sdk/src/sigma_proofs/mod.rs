@@ -5,10 +5,14 @@
 //! commitments and twisted ElGamal ciphertexts:
 //! - Equality proof: can be used to certify that a twisted ElGamal ciphertext and a Pedersen
 //! commitment encrypt/encode the same message.
+//! - Ciphertext-ciphertext equality proof: can be used to certify that two twisted ElGamal
+//! ciphertexts under different public keys encrypt the same message.
 //! - Validity proof: can be used to certify that a twisted ElGamal ciphertext is a properly-formed
 //! ciphertext with respect to a pair of ElGamal public keys.
 //! - Zero-balance proof: can be used to certify that a twisted ElGamal ciphertext encrypts the
 //! message 0.
+//! - Public-key validity proof: can be used to certify that an ElGamal public key is well-formed
+//! and that the prover knows the corresponding secret key.
 //! - Fee proof: can be used to certify that an ElGamal ciphertext properly encrypts a transfer
 //! fee.
 //!
@@ -18,5 +22,6 @@
 pub mod equality_proof;
 pub mod errors;
 pub mod fee_proof;
+pub mod pubkey_proof;
 pub mod validity_proof;
 pub mod zero_balance_proof;
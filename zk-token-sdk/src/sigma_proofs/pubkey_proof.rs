@@ -0,0 +1,232 @@
+//! The public-key validity sigma proof system.
+//!
+//! A public-key validity proof is defined with respect to an ElGamal public key. The proof
+//! certifies that the public key is well-formed and that the prover knows the corresponding secret
+//! key. To generate the proof, a prover must provide the secret key for the public key.
+//!
+//! The protocol guarantees computational soundness (by the hardness of discrete log) and perfect
+//! zero-knowledge in the random oracle model.
+
+#[cfg(not(target_arch = "bpf"))]
+use crate::{
+    batch::MultiscalarMulBatch,
+    encryption::{elgamal::ElGamalPubkey, pedersen::H},
+    errors::ProofError,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::elgamal::ElGamalKeypair,
+    rand::{rngs::OsRng, CryptoRng, RngCore},
+    zeroize::Zeroize,
+};
+use {
+    crate::{sigma_proofs::errors::PubkeyValidityProofError, transcript::TranscriptProtocol},
+    arrayref::{array_ref, array_refs},
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
+    merlin::Transcript,
+};
+
+/// Public-key validity proof.
+///
+/// Contains all the elliptic curve and scalar components that make up the sigma protocol.
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct PubkeyValidityProof {
+    Y: CompressedRistretto,
+    z: Scalar,
+}
+
+#[allow(non_snake_case)]
+#[cfg(not(target_arch = "bpf"))]
+impl PubkeyValidityProof {
+    /// Public-key validity proof constructor.
+    ///
+    /// The function does *not* hash the public key into the transcript. For security, the caller
+    /// (the main protocol) should hash the public key prior to invoking this constructor.
+    ///
+    /// This function is randomized. It uses `OsRng` internally to generate random scalars.
+    ///
+    /// * `elgamal_keypair` - The ElGamal keypair whose public key is to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
+    pub fn new(elgamal_keypair: &ElGamalKeypair, transcript: &mut Transcript) -> Self {
+        Self::new_with_rng(elgamal_keypair, transcript, &mut OsRng)
+    }
+
+    /// Like `PubkeyValidityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        elgamal_keypair: &ElGamalKeypair,
+        transcript: &mut Transcript,
+        rng: &mut R,
+    ) -> Self {
+        transcript.pubkey_proof_domain_sep();
+
+        // extract the relevant scalar and Ristretto points from the input
+        let P = elgamal_keypair.public.get_point();
+        let s = elgamal_keypair.secret.get_scalar();
+
+        // generate a random masking factor that also serves as a nonce
+        let mut y = Scalar::random(rng);
+        let Y = (&y * P).compress();
+
+        // record Y in the transcript and receive a challenge scalar
+        transcript.append_point(b"Y", &Y);
+        let c = transcript.challenge_scalar(b"c");
+
+        // compute the masked secret key
+        let z = &(&c * s) + &y;
+
+        // zeroize random scalar
+        y.zeroize();
+
+        Self { Y, z }
+    }
+
+    /// Public-key validity proof verifier.
+    ///
+    /// * `elgamal_pubkey` - The ElGamal public key to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    pub fn verify(
+        self,
+        elgamal_pubkey: &ElGamalPubkey,
+        transcript: &mut Transcript,
+    ) -> Result<(), PubkeyValidityProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(elgamal_pubkey, transcript, &mut batch)?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(PubkeyValidityProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Public-key validity proof verifier that defers the check of the algebraic relation to
+    /// `batch`.
+    ///
+    /// * `elgamal_pubkey` - The ElGamal public key to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        elgamal_pubkey: &ElGamalPubkey,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), PubkeyValidityProofError> {
+        transcript.pubkey_proof_domain_sep();
+
+        // extract the relevant scalar and Ristretto points from the input
+        let P = elgamal_pubkey.get_point();
+
+        // record Y in transcript and receive a challenge scalar
+        transcript.validate_and_append_point(b"Y", &self.Y)?;
+        let c = transcript.challenge_scalar(b"c");
+
+        // decompress Y or return verification error
+        let Y = self
+            .Y
+            .decompress()
+            .ok_or(PubkeyValidityProofError::NonCanonical)?;
+
+        // add the required algebraic relation to the batch
+        batch.append(
+            ProofError::PubkeyValidityProofFailed,
+            vec![
+                self.z,          // z
+                -&c,             // -c
+                -&Scalar::one(), // -identity
+            ],
+            vec![
+                *P, // P
+                *H, // H
+                Y,  // Y
+            ],
+        );
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut buf = [0_u8; 64];
+        buf[..32].copy_from_slice(self.Y.as_bytes());
+        buf[32..64].copy_from_slice(self.z.as_bytes());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PubkeyValidityProofError> {
+        if bytes.len() != 64 {
+            return Err(PubkeyValidityProofError::Format);
+        }
+
+        let bytes = array_ref![bytes, 0, 64];
+        let (Y, z) = array_refs![bytes, 32, 32];
+
+        let Y = CompressedRistretto::from_slice(Y);
+        let z = Scalar::from_canonical_bytes(*z).ok_or(PubkeyValidityProofError::NonCanonical)?;
+
+        Ok(PubkeyValidityProof { Y, z })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::encryption::elgamal::ElGamalSecretKey,
+        curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint},
+    };
+
+    #[test]
+    fn test_pubkey_proof_correctness() {
+        let keypair = ElGamalKeypair::new_rand();
+
+        let mut transcript_prover = Transcript::new(b"test");
+        let mut transcript_verifier = Transcript::new(b"test");
+
+        let proof = PubkeyValidityProof::new(&keypair, &mut transcript_prover);
+        assert!(proof
+            .verify(&keypair.public, &mut transcript_verifier)
+            .is_ok());
+
+        // the proof does not verify for a different public key
+        let other_keypair = ElGamalKeypair::new_rand();
+
+        let mut transcript_prover = Transcript::new(b"test");
+        let mut transcript_verifier = Transcript::new(b"test");
+
+        let proof = PubkeyValidityProof::new(&keypair, &mut transcript_prover);
+        assert!(proof
+            .verify(&other_keypair.public, &mut transcript_verifier)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pubkey_proof_edge_cases() {
+        // a public key that is not of the form `s^-1 * H` for the secret key cannot be proved
+        let public = ElGamalPubkey(RISTRETTO_BASEPOINT_POINT);
+        let secret = ElGamalSecretKey::new_rand();
+        let keypair = ElGamalKeypair { public, secret };
+
+        let mut transcript_prover = Transcript::new(b"test");
+        let mut transcript_verifier = Transcript::new(b"test");
+
+        let proof = PubkeyValidityProof::new(&keypair, &mut transcript_prover);
+        assert!(proof
+            .verify(&keypair.public, &mut transcript_verifier)
+            .is_err());
+
+        // the zeroed public key cannot be proved
+        let public = ElGamalPubkey(RistrettoPoint::default());
+        let secret = ElGamalSecretKey::new_rand();
+        let keypair = ElGamalKeypair { public, secret };
+
+        let mut transcript_prover = Transcript::new(b"test");
+        let mut transcript_verifier = Transcript::new(b"test");
+
+        let proof = PubkeyValidityProof::new(&keypair, &mut transcript_prover);
+        assert!(proof
+            .verify(&keypair.public, &mut transcript_verifier)
+            .is_err());
+    }
+}
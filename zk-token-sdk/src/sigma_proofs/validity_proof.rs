@@ -1,31 +1,33 @@
 //! The ciphertext validity sigma proof system.
 //!
-//! The ciphertext validity proof is defined with respect to a Pedersen commitment and two
+//! The ciphertext validity proof is defined with respect to a Pedersen commitment and a list of
 //! decryption handles. The proof certifies that a given Pedersen commitment can be decrypted using
-//! ElGamal private keys that are associated with each of the two decryption handles. To generate
-//! the proof, a prover must provide the Pedersen opening associated with the commitment.
+//! ElGamal private keys that are associated with each of the decryption handles. To generate the
+//! proof, a prover must provide the Pedersen opening associated with the commitment.
 //!
 //! The protocol guarantees computational soundness (by the hardness of discrete log) and perfect
 //! zero-knowledge in the random oracle model.
 
 #[cfg(not(target_arch = "bpf"))]
-use {
-    crate::encryption::{
+use crate::{
+    batch::MultiscalarMulBatch,
+    encryption::{
         elgamal::{DecryptHandle, ElGamalPubkey},
-        pedersen::{PedersenCommitment, PedersenOpening, G, H},
+        pedersen::{PedersenCommitment, G, H},
     },
-    curve25519_dalek::traits::MultiscalarMul,
-    rand::rngs::OsRng,
+    errors::ProofError,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::pedersen::PedersenOpening,
+    curve25519_dalek::{ristretto::RistrettoPoint, traits::MultiscalarMul},
+    rand::{rngs::OsRng, CryptoRng, RngCore},
     zeroize::Zeroize,
 };
 use {
     crate::{sigma_proofs::errors::ValidityProofError, transcript::TranscriptProtocol},
     arrayref::{array_ref, array_refs},
-    curve25519_dalek::{
-        ristretto::{CompressedRistretto, RistrettoPoint},
-        scalar::Scalar,
-        traits::{IsIdentity, VartimeMultiscalarMul},
-    },
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
     merlin::Transcript,
 };
 
@@ -36,8 +38,8 @@ use {
 #[derive(Clone)]
 pub struct ValidityProof {
     Y_0: CompressedRistretto,
-    Y_1: CompressedRistretto,
-    Y_2: CompressedRistretto,
+    /// The masking factor associated with each decryption handle
+    Y_handles: Vec<CompressedRistretto>,
     z_r: Scalar,
     z_x: Scalar,
 }
@@ -56,38 +58,50 @@ impl ValidityProof {
     /// Note that the proof constructor does not take the actual Pedersen commitment or decryption
     /// handles as input; it only takes the associated Pedersen opening instead.
     ///
-    /// * `(pubkey_dest, pubkey_auditor)` - The ElGamal public keys associated with the decryption
-    /// handles
+    /// * `pubkeys` - The ElGamal public keys associated with the decryption handles
     /// * `amount` - The committed message in the commitment
     /// * `opening` - The opening associated with the Pedersen commitment
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
     pub fn new<T: Into<Scalar>>(
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey), // TODO: rename pubkey_auditor
+        pubkeys: &[&ElGamalPubkey],
+        amount: T,
+        opening: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(pubkeys, amount, opening, transcript, &mut OsRng)
+    }
+
+    /// Like `ValidityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        pubkeys: &[&ElGamalPubkey],
         amount: T,
         opening: &PedersenOpening,
         transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         transcript.validity_proof_domain_sep();
 
         // extract the relevant scalar and Ristretto points from the inputs
-        let P_dest = pubkey_dest.get_point();
-        let P_auditor = pubkey_auditor.get_point();
-
         let x = amount.into();
         let r = opening.get_scalar();
 
         // generate random masking factors that also serves as nonces
-        let mut y_r = Scalar::random(&mut OsRng);
-        let mut y_x = Scalar::random(&mut OsRng);
+        let mut y_r = Scalar::random(rng);
+        let mut y_x = Scalar::random(rng);
 
         let Y_0 = RistrettoPoint::multiscalar_mul(vec![&y_r, &y_x], vec![&(*H), &(*G)]).compress();
-        let Y_1 = (&y_r * P_dest).compress();
-        let Y_2 = (&y_r * P_auditor).compress();
+        let Y_handles: Vec<CompressedRistretto> = pubkeys
+            .iter()
+            .map(|pubkey| (&y_r * pubkey.get_point()).compress())
+            .collect();
 
         // record masking factors in transcript and get challenges
         transcript.append_point(b"Y_0", &Y_0);
-        transcript.append_point(b"Y_1", &Y_1);
-        transcript.append_point(b"Y_2", &Y_2);
+        for Y_handle in &Y_handles {
+            transcript.append_point(b"Y_handle", Y_handle);
+        }
 
         let c = transcript.challenge_scalar(b"c");
         transcript.challenge_scalar(b"w");
@@ -101,8 +115,7 @@ impl ValidityProof {
 
         Self {
             Y_0,
-            Y_1,
-            Y_2,
+            Y_handles,
             z_r,
             z_x,
         }
@@ -111,117 +124,239 @@ impl ValidityProof {
     /// The ciphertext validity proof verifier.
     ///
     /// * `commitment` - The Pedersen commitment
-    /// * `(pubkey_dest, pubkey_auditor)` - The ElGamal pubkeys associated with the decryption
-    /// handles
-    /// * `(handle_dest, handle_audtior)` - The decryption handles
+    /// * `pubkeys` - The ElGamal pubkeys associated with the decryption handles
+    /// * `handles` - The decryption handles
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
     pub fn verify(
         self,
         commitment: &PedersenCommitment,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
-        (handle_dest, handle_auditor): (&DecryptHandle, &DecryptHandle),
+        pubkeys: &[&ElGamalPubkey],
+        handles: &[&DecryptHandle],
         transcript: &mut Transcript,
     ) -> Result<(), ValidityProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(commitment, pubkeys, handles, transcript, &mut batch)?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(ValidityProofError::AlgebraicRelation)
+        }
+    }
+
+    /// The ciphertext validity proof verifier that defers the check of the algebraic relation to
+    /// `batch`.
+    ///
+    /// * `commitment` - The Pedersen commitment
+    /// * `pubkeys` - The ElGamal pubkeys associated with the decryption handles
+    /// * `handles` - The decryption handles
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        commitment: &PedersenCommitment,
+        pubkeys: &[&ElGamalPubkey],
+        handles: &[&DecryptHandle],
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ValidityProofError> {
+        if pubkeys.len() != self.Y_handles.len() || handles.len() != self.Y_handles.len() {
+            return Err(ValidityProofError::Format);
+        }
+
         transcript.validity_proof_domain_sep();
 
-        // include Y_0, Y_1, Y_2 to transcript and extract challenges
+        // include Y_0 and the masking factors of the handles to transcript and extract challenges
         transcript.validate_and_append_point(b"Y_0", &self.Y_0)?;
-        transcript.validate_and_append_point(b"Y_1", &self.Y_1)?;
-        transcript.validate_and_append_point(b"Y_2", &self.Y_2)?;
+        for Y_handle in &self.Y_handles {
+            transcript.validate_and_append_point(b"Y_handle", Y_handle)?;
+        }
 
         let c = transcript.challenge_scalar(b"c");
         let w = transcript.challenge_scalar(b"w");
-        let ww = &w * &w;
 
-        let w_negated = -&w;
-        let ww_negated = -&ww;
+        // add the required algebraic conditions to the batch
+        let Y_0 = self
+            .Y_0
+            .decompress()
+            .ok_or(ValidityProofError::NonCanonical)?;
+        let C = commitment.get_point();
 
-        // check the required algebraic conditions
-        let Y_0 = self.Y_0.decompress().ok_or(ValidityProofError::Format)?;
-        let Y_1 = self.Y_1.decompress().ok_or(ValidityProofError::Format)?;
-        let Y_2 = self.Y_2.decompress().ok_or(ValidityProofError::Format)?;
+        let mut scalars = vec![
+            self.z_r,        // z_r
+            self.z_x,        // z_x
+            -&c,             // -c
+            -&Scalar::one(), // -identity
+        ];
+        let mut points = vec![
+            *H,  // H
+            *G,  // G
+            *C,  // C
+            Y_0, // Y_0
+        ];
+
+        // the condition of each handle is weighted by the next power of w
+        let mut w_power = Scalar::one();
+        for ((Y_handle, pubkey), handle) in self.Y_handles.iter().zip(pubkeys).zip(handles) {
+            w_power *= w;
+            let Y_handle = Y_handle
+                .decompress()
+                .ok_or(ValidityProofError::NonCanonical)?;
+
+            scalars.extend([
+                &w_power * &self.z_r, // w^i * z_r
+                -&w_power * &c,       // -w^i * c
+                -w_power,             // -w^i
+            ]);
+            points.extend([
+                *pubkey.get_point(), // P_i
+                *handle.get_point(), // D_i
+                Y_handle,            // Y_i
+            ]);
+        }
 
-        let P_dest = pubkey_dest.get_point();
-        let P_auditor = pubkey_auditor.get_point();
+        batch.append(ProofError::ValidityProofFailed, scalars, points);
 
-        let C = commitment.get_point();
-        let D_dest = handle_dest.get_point();
-        let D_auditor = handle_auditor.get_point();
-
-        let check = RistrettoPoint::vartime_multiscalar_mul(
-            vec![
-                &self.z_r,           // z_r
-                &self.z_x,           // z_x
-                &(-&c),              // -c
-                &-(&Scalar::one()),  // -identity
-                &(&w * &self.z_r),   // w * z_r
-                &(&w_negated * &c),  // -w * c
-                &w_negated,          // -w
-                &(&ww * &self.z_r),  // ww * z_r
-                &(&ww_negated * &c), // -ww * c
-                &ww_negated,         // -ww
-            ],
-            vec![
-                &(*H),     // H
-                &(*G),     // G
-                C,         // C
-                &Y_0,      // Y_0
-                P_dest,    // P_dest
-                D_dest,    // D_dest
-                &Y_1,      // Y_1
-                P_auditor, // P_auditor
-                D_auditor, // D_auditor
-                &Y_2,      // Y_2
-            ],
-        );
+        Ok(())
+    }
 
-        if check.is_identity() {
-            Ok(())
-        } else {
-            Err(ValidityProofError::AlgebraicRelation)
-        }
+    /// The length of a serialized proof for `num_handles` decryption handles.
+    pub const fn serialized_size(num_handles: usize) -> usize {
+        32 * (num_handles + 3)
     }
 
-    pub fn to_bytes(&self) -> [u8; 160] {
-        let mut buf = [0_u8; 160];
-        buf[..32].copy_from_slice(self.Y_0.as_bytes());
-        buf[32..64].copy_from_slice(self.Y_1.as_bytes());
-        buf[64..96].copy_from_slice(self.Y_2.as_bytes());
-        buf[96..128].copy_from_slice(self.z_r.as_bytes());
-        buf[128..160].copy_from_slice(self.z_x.as_bytes());
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::serialized_size(self.Y_handles.len()));
+        buf.extend_from_slice(self.Y_0.as_bytes());
+        for Y_handle in &self.Y_handles {
+            buf.extend_from_slice(Y_handle.as_bytes());
+        }
+        buf.extend_from_slice(self.z_r.as_bytes());
+        buf.extend_from_slice(self.z_x.as_bytes());
         buf
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ValidityProofError> {
-        let bytes = array_ref![bytes, 0, 160];
-        let (Y_0, Y_1, Y_2, z_r, z_x) = array_refs![bytes, 32, 32, 32, 32, 32];
+        if bytes.len() % 32 != 0 || bytes.len() < Self::serialized_size(1) {
+            return Err(ValidityProofError::Format);
+        }
+
+        let (Y_0, bytes) = bytes.split_at(32);
+        let (Y_handles, z) = bytes.split_at(bytes.len() - 64);
+        let (z_r, z_x) = array_refs![array_ref![z, 0, 64], 32, 32];
 
         let Y_0 = CompressedRistretto::from_slice(Y_0);
-        let Y_1 = CompressedRistretto::from_slice(Y_1);
-        let Y_2 = CompressedRistretto::from_slice(Y_2);
+        let Y_handles = Y_handles
+            .chunks(32)
+            .map(CompressedRistretto::from_slice)
+            .collect();
 
-        let z_r = Scalar::from_canonical_bytes(*z_r).ok_or(ValidityProofError::Format)?;
-        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(ValidityProofError::Format)?;
+        let z_r = Scalar::from_canonical_bytes(*z_r).ok_or(ValidityProofError::NonCanonical)?;
+        let z_x = Scalar::from_canonical_bytes(*z_x).ok_or(ValidityProofError::NonCanonical)?;
 
         Ok(ValidityProof {
             Y_0,
-            Y_1,
-            Y_2,
+            Y_handles,
             z_r,
             z_x,
         })
     }
 }
 
+/// Grouped ciphertext validity proof for a fixed number `N` of decryption handles.
+///
+/// A grouped ciphertext consists of a single Pedersen commitment and one decryption handle for
+/// each of `N` ElGamal public keys, e.g. the source, destination, and auditor of a transfer. The
+/// proof is a standard ciphertext validity proof whose number of handles is fixed by its type, so
+/// that it can be serialized to a fixed-size pod type.
+#[derive(Clone)]
+pub struct GroupedCiphertextValidityProof<const N: usize>(ValidityProof);
+
+/// Grouped ciphertext validity proof for two decryption handles
+pub type GroupedCiphertext2HandlesValidityProof = GroupedCiphertextValidityProof<2>;
+
+/// Grouped ciphertext validity proof for three decryption handles
+pub type GroupedCiphertext3HandlesValidityProof = GroupedCiphertextValidityProof<3>;
+
+#[cfg(not(target_arch = "bpf"))]
+impl<const N: usize> GroupedCiphertextValidityProof<N> {
+    /// The length of a serialized proof.
+    pub const SERIALIZED_SIZE: usize = ValidityProof::serialized_size(N);
+
+    /// Grouped ciphertext validity proof constructor.
+    ///
+    /// The arguments are the same as for `ValidityProof::new`, except that there must be exactly
+    /// `N` public keys.
+    #[cfg(feature = "prover")]
+    pub fn new<T: Into<Scalar>>(
+        pubkeys: [&ElGamalPubkey; N],
+        amount: T,
+        opening: &PedersenOpening,
+        transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(pubkeys, amount, opening, transcript, &mut OsRng)
+    }
+
+    /// Like `GroupedCiphertextValidityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        pubkeys: [&ElGamalPubkey; N],
+        amount: T,
+        opening: &PedersenOpening,
+        transcript: &mut Transcript,
+        rng: &mut R,
+    ) -> Self {
+        Self(ValidityProof::new_with_rng(
+            &pubkeys, amount, opening, transcript, rng,
+        ))
+    }
+
+    /// Grouped ciphertext validity proof verifier.
+    pub fn verify(
+        self,
+        commitment: &PedersenCommitment,
+        pubkeys: [&ElGamalPubkey; N],
+        handles: [&DecryptHandle; N],
+        transcript: &mut Transcript,
+    ) -> Result<(), ValidityProofError> {
+        self.0.verify(commitment, &pubkeys, &handles, transcript)
+    }
+
+    /// Grouped ciphertext validity proof verifier that defers the check of the algebraic relation
+    /// to `batch`.
+    pub fn verify_batched(
+        self,
+        commitment: &PedersenCommitment,
+        pubkeys: [&ElGamalPubkey; N],
+        handles: [&DecryptHandle; N],
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ValidityProofError> {
+        self.0
+            .verify_batched(commitment, &pubkeys, &handles, transcript, batch)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ValidityProofError> {
+        if bytes.len() != Self::SERIALIZED_SIZE {
+            return Err(ValidityProofError::Format);
+        }
+
+        ValidityProof::from_bytes(bytes).map(Self)
+    }
+}
+
 /// Aggregated ciphertext validity proof.
 ///
 /// An aggregated ciphertext validity proof certifies the validity of two instances of a standard
 /// ciphertext validity proof. An instance of a standard validity proof consist of one ciphertext
-/// and two decryption handles `(commitment, handle_dest, handle_auditor)`. An instance of an
-/// aggregated ciphertext validity proof is a pair `(commitment_0, handle_dest_0,
-/// handle_auditor_0)` and `(commitment_1, handle_dest_1, handle_auditor_1)`. The proof certifies
-/// the analogous decryptable properties for each one of these pair of commitment and decryption
-/// handles.
+/// and a list of decryption handles `(commitment, handles)`. An instance of an aggregated
+/// ciphertext validity proof is a pair `(commitment_lo, handles_lo)` and `(commitment_hi,
+/// handles_hi)`. The proof certifies the analogous decryptable properties for each one of these
+/// pair of commitment and decryption handles.
 #[allow(non_snake_case)]
 #[derive(Clone)]
 pub struct AggregatedValidityProof(ValidityProof);
@@ -233,11 +368,30 @@ impl AggregatedValidityProof {
     ///
     /// The function simples aggregates the input openings and invokes the standard ciphertext
     /// validity proof constructor.
+    #[cfg(feature = "prover")]
     pub fn new<T: Into<Scalar>>(
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        pubkeys: &[&ElGamalPubkey],
         (amount_lo, amount_hi): (T, T),
         (opening_lo, opening_hi): (&PedersenOpening, &PedersenOpening),
         transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(
+            pubkeys,
+            (amount_lo, amount_hi),
+            (opening_lo, opening_hi),
+            transcript,
+            &mut OsRng,
+        )
+    }
+
+    /// Like `AggregatedValidityProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<T: Into<Scalar>, R: RngCore + CryptoRng>(
+        pubkeys: &[&ElGamalPubkey],
+        (amount_lo, amount_hi): (T, T),
+        (opening_lo, opening_hi): (&PedersenOpening, &PedersenOpening),
+        transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         transcript.aggregated_validity_proof_domain_sep();
 
@@ -246,11 +400,12 @@ impl AggregatedValidityProof {
         let aggregated_message = amount_lo.into() + amount_hi.into() * t;
         let aggregated_opening = opening_lo + &(opening_hi * &t);
 
-        AggregatedValidityProof(ValidityProof::new(
-            (pubkey_dest, pubkey_auditor),
+        AggregatedValidityProof(ValidityProof::new_with_rng(
+            pubkeys,
             aggregated_message,
             &aggregated_opening,
             transcript,
+            rng,
         ))
     }
 
@@ -263,31 +418,76 @@ impl AggregatedValidityProof {
     /// This function is randomized. It uses `OsRng` internally to generate random scalars.
     pub fn verify(
         self,
-        (pubkey_dest, pubkey_auditor): (&ElGamalPubkey, &ElGamalPubkey),
+        pubkeys: &[&ElGamalPubkey],
         (commitment_lo, commitment_hi): (&PedersenCommitment, &PedersenCommitment),
-        (handle_lo_dest, handle_hi_dest): (&DecryptHandle, &DecryptHandle),
-        (handle_lo_auditor, handle_hi_auditor): (&DecryptHandle, &DecryptHandle),
+        (handles_lo, handles_hi): (&[&DecryptHandle], &[&DecryptHandle]),
         transcript: &mut Transcript,
     ) -> Result<(), ValidityProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(
+            pubkeys,
+            (commitment_lo, commitment_hi),
+            (handles_lo, handles_hi),
+            transcript,
+            &mut batch,
+        )?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(ValidityProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Aggregated ciphertext validity proof verifier that defers the check of the algebraic
+    /// relation to `batch`.
+    ///
+    /// The function does *not* hash the public keys, commitment, or decryption handles into the
+    /// transcript. For security, the caller (the main protocol) should hash these public
+    /// components prior to invoking this constructor.
+    ///
+    /// This function is randomized. It uses `OsRng` internally to generate random scalars.
+    pub fn verify_batched(
+        self,
+        pubkeys: &[&ElGamalPubkey],
+        (commitment_lo, commitment_hi): (&PedersenCommitment, &PedersenCommitment),
+        (handles_lo, handles_hi): (&[&DecryptHandle], &[&DecryptHandle]),
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
+    ) -> Result<(), ValidityProofError> {
+        if handles_lo.len() != handles_hi.len() {
+            return Err(ValidityProofError::Format);
+        }
+
         transcript.aggregated_validity_proof_domain_sep();
 
         let t = transcript.challenge_scalar(b"t");
 
         let aggregated_commitment = commitment_lo + commitment_hi * t;
-        let aggregated_handle_dest = handle_lo_dest + handle_hi_dest * t;
-        let aggregated_handle_auditor = handle_lo_auditor + handle_hi_auditor * t;
+        let aggregated_handles: Vec<DecryptHandle> = handles_lo
+            .iter()
+            .zip(handles_hi)
+            .map(|(handle_lo, handle_hi)| *handle_lo + *handle_hi * t)
+            .collect();
+        let aggregated_handles: Vec<&DecryptHandle> = aggregated_handles.iter().collect();
 
         let AggregatedValidityProof(validity_proof) = self;
 
-        validity_proof.verify(
+        validity_proof.verify_batched(
             &aggregated_commitment,
-            (pubkey_dest, pubkey_auditor),
-            (&aggregated_handle_dest, &aggregated_handle_auditor),
+            pubkeys,
+            &aggregated_handles,
             transcript,
+            batch,
         )
     }
 
-    pub fn to_bytes(&self) -> [u8; 160] {
+    /// The length of a serialized proof for `num_handles` pairs of decryption handles.
+    pub const fn serialized_size(num_handles: usize) -> usize {
+        ValidityProof::serialized_size(num_handles)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
         self.0.to_bytes()
     }
 
@@ -316,7 +516,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = ValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             amount,
             &opening,
             &mut transcript_prover,
@@ -325,8 +525,8 @@ mod test {
         assert!(proof
             .verify(
                 &commitment,
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
-                (&handle_dest, &handle_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
+                &[&handle_dest, &handle_auditor],
                 &mut transcript_verifier,
             )
             .is_ok());
@@ -348,7 +548,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = ValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             amount,
             &opening,
             &mut transcript_prover,
@@ -357,8 +557,8 @@ mod test {
         assert!(proof
             .verify(
                 &commitment,
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
-                (&handle_dest, &handle_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
+                &[&handle_dest, &handle_auditor],
                 &mut transcript_verifier,
             )
             .is_err());
@@ -377,7 +577,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = ValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             amount,
             &opening,
             &mut transcript_prover,
@@ -386,8 +586,8 @@ mod test {
         assert!(proof
             .verify(
                 &commitment,
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
-                (&handle_dest, &handle_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
+                &[&handle_dest, &handle_auditor],
                 &mut transcript_verifier,
             )
             .is_err());
@@ -407,7 +607,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = ValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             amount,
             &opening,
             &mut transcript_prover,
@@ -416,8 +616,8 @@ mod test {
         assert!(proof
             .verify(
                 &commitment,
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
-                (&handle_dest, &handle_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
+                &[&handle_dest, &handle_auditor],
                 &mut transcript_verifier,
             )
             .is_ok());
@@ -436,7 +636,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = ValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             amount,
             &opening,
             &mut transcript_prover,
@@ -445,8 +645,8 @@ mod test {
         assert!(proof
             .verify(
                 &commitment,
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
-                (&handle_dest, &handle_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
+                &[&handle_dest, &handle_auditor],
                 &mut transcript_verifier,
             )
             .is_ok());
@@ -473,7 +673,7 @@ mod test {
         let mut transcript_verifier = Transcript::new(b"Test");
 
         let proof = AggregatedValidityProof::new(
-            (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+            &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
             (amount_lo, amount_hi),
             (&open_lo, &open_hi),
             &mut transcript_prover,
@@ -481,12 +681,106 @@ mod test {
 
         assert!(proof
             .verify(
-                (&elgamal_pubkey_dest, &elgamal_pubkey_auditor),
+                &[&elgamal_pubkey_dest, &elgamal_pubkey_auditor],
                 (&commitment_lo, &commitment_hi),
-                (&handle_lo_dest, &handle_hi_dest),
-                (&handle_lo_auditor, &handle_hi_auditor),
+                (
+                    &[&handle_lo_dest, &handle_lo_auditor],
+                    &[&handle_hi_dest, &handle_hi_auditor],
+                ),
                 &mut transcript_verifier,
             )
             .is_ok());
     }
+
+    #[test]
+    fn test_grouped_ciphertext_validity_proof() {
+        let elgamal_pubkey_source = ElGamalKeypair::new_rand().public;
+        let elgamal_pubkey_dest = ElGamalKeypair::new_rand().public;
+        let elgamal_pubkey_auditor = ElGamalKeypair::new_rand().public;
+
+        let amount: u64 = 55;
+        let (commitment, opening) = Pedersen::new(amount);
+
+        let handle_source = elgamal_pubkey_source.decrypt_handle(&opening);
+        let handle_dest = elgamal_pubkey_dest.decrypt_handle(&opening);
+        let handle_auditor = elgamal_pubkey_auditor.decrypt_handle(&opening);
+
+        let mut transcript_prover = Transcript::new(b"Test");
+        let mut transcript_verifier = Transcript::new(b"Test");
+
+        let proof = GroupedCiphertext3HandlesValidityProof::new(
+            [
+                &elgamal_pubkey_source,
+                &elgamal_pubkey_dest,
+                &elgamal_pubkey_auditor,
+            ],
+            amount,
+            &opening,
+            &mut transcript_prover,
+        );
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            GroupedCiphertext3HandlesValidityProof::SERIALIZED_SIZE
+        );
+        assert!(GroupedCiphertext2HandlesValidityProof::from_bytes(&bytes).is_err());
+        let proof = GroupedCiphertext3HandlesValidityProof::from_bytes(&bytes).unwrap();
+
+        assert!(proof
+            .verify(
+                &commitment,
+                [
+                    &elgamal_pubkey_source,
+                    &elgamal_pubkey_dest,
+                    &elgamal_pubkey_auditor,
+                ],
+                [&handle_source, &handle_dest, &handle_auditor],
+                &mut transcript_verifier,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validity_proof_handle_counts() {
+        let pubkeys: Vec<ElGamalPubkey> =
+            (0..3).map(|_| ElGamalKeypair::new_rand().public).collect();
+
+        let amount: u64 = 55;
+        let (commitment, opening) = Pedersen::new(amount);
+
+        for num_handles in 1..=pubkeys.len() {
+            let pubkeys: Vec<&ElGamalPubkey> = pubkeys[..num_handles].iter().collect();
+            let handles: Vec<DecryptHandle> = pubkeys
+                .iter()
+                .map(|pubkey| pubkey.decrypt_handle(&opening))
+                .collect();
+            let handles: Vec<&DecryptHandle> = handles.iter().collect();
+
+            let mut transcript_prover = Transcript::new(b"Test");
+            let proof = ValidityProof::new(&pubkeys, amount, &opening, &mut transcript_prover);
+
+            let bytes = proof.to_bytes();
+            assert_eq!(bytes.len(), ValidityProof::serialized_size(num_handles));
+            let proof = ValidityProof::from_bytes(&bytes).unwrap();
+
+            let mut transcript_verifier = Transcript::new(b"Test");
+            assert!(proof
+                .clone()
+                .verify(&commitment, &pubkeys, &handles, &mut transcript_verifier)
+                .is_ok());
+
+            // the proof is bound to the number of handles
+            let mut transcript_verifier = Transcript::new(b"Test");
+            assert_eq!(
+                proof.verify(
+                    &commitment,
+                    &pubkeys[1..],
+                    &handles[1..],
+                    &mut transcript_verifier
+                ),
+                Err(ValidityProofError::Format)
+            );
+        }
+    }
 }
@@ -8,23 +8,24 @@
 //! zero-knowledge in the random oracle model.
 
 #[cfg(not(target_arch = "bpf"))]
-use {
-    crate::encryption::{
-        elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+use crate::{
+    batch::MultiscalarMulBatch,
+    encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalPubkey},
         pedersen::H,
     },
-    curve25519_dalek::traits::MultiscalarMul,
-    rand::rngs::OsRng,
+    errors::ProofError,
+};
+#[cfg(feature = "prover")]
+use {
+    crate::encryption::elgamal::ElGamalKeypair,
+    rand::{rngs::OsRng, CryptoRng, RngCore},
     zeroize::Zeroize,
 };
 use {
     crate::{sigma_proofs::errors::ZeroBalanceProofError, transcript::TranscriptProtocol},
     arrayref::{array_ref, array_refs},
-    curve25519_dalek::{
-        ristretto::{CompressedRistretto, RistrettoPoint},
-        scalar::Scalar,
-        traits::IsIdentity,
-    },
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar},
     merlin::Transcript,
 };
 
@@ -56,10 +57,22 @@ impl ZeroBalanceProof {
     /// * `elgamal_keypair` - The ElGamal keypair associated with the ciphertext to be proved
     /// * `ciphertext` - The main ElGamal ciphertext to be proved
     /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    #[cfg(feature = "prover")]
     pub fn new(
         elgamal_keypair: &ElGamalKeypair,
         ciphertext: &ElGamalCiphertext,
         transcript: &mut Transcript,
+    ) -> Self {
+        Self::new_with_rng(elgamal_keypair, ciphertext, transcript, &mut OsRng)
+    }
+
+    /// Like `ZeroBalanceProof::new`, but samples the random scalars from `rng`.
+    #[cfg(feature = "prover")]
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        elgamal_keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        rng: &mut R,
     ) -> Self {
         // extract the relevant scalar and Ristretto points from the input
         let P = elgamal_keypair.public.get_point();
@@ -67,7 +80,7 @@ impl ZeroBalanceProof {
         let D = ciphertext.handle.get_point();
 
         // generate a random masking factor that also serves as a nonce
-        let mut y = Scalar::random(&mut OsRng);
+        let mut y = Scalar::random(rng);
         let Y_P = (&y * P).compress();
         let Y_D = (&y * D).compress();
 
@@ -97,6 +110,29 @@ impl ZeroBalanceProof {
         elgamal_pubkey: &ElGamalPubkey,
         ciphertext: &ElGamalCiphertext,
         transcript: &mut Transcript,
+    ) -> Result<(), ZeroBalanceProofError> {
+        let mut batch = MultiscalarMulBatch::new();
+        self.verify_batched(elgamal_pubkey, ciphertext, transcript, &mut batch)?;
+
+        if batch.verify() {
+            Ok(())
+        } else {
+            Err(ZeroBalanceProofError::AlgebraicRelation)
+        }
+    }
+
+    /// Zero-balance proof verifier that defers the check of the algebraic relation to `batch`.
+    ///
+    /// * `elgamal_pubkey` - The ElGamal pubkey associated with the ciphertext to be proved
+    /// * `ciphertext` - The main ElGamal ciphertext to be proved
+    /// * `transcript` - The transcript that does the bookkeeping for the Fiat-Shamir heuristic
+    /// * `batch` - The batch that the algebraic relation is added to
+    pub fn verify_batched(
+        self,
+        elgamal_pubkey: &ElGamalPubkey,
+        ciphertext: &ElGamalCiphertext,
+        transcript: &mut Transcript,
+        batch: &mut MultiscalarMulBatch,
     ) -> Result<(), ZeroBalanceProofError> {
         // extract the relevant scalar and Ristretto points from the input
         let P = elgamal_pubkey.get_point();
@@ -113,34 +149,37 @@ impl ZeroBalanceProof {
         let w_negated = -&w;
 
         // decompress R or return verification error
-        let Y_P = self.Y_P.decompress().ok_or(ZeroBalanceProofError::Format)?;
-        let Y_D = self.Y_D.decompress().ok_or(ZeroBalanceProofError::Format)?;
-
-        // check the required algebraic relation
-        let check = RistrettoPoint::multiscalar_mul(
+        let Y_P = self
+            .Y_P
+            .decompress()
+            .ok_or(ZeroBalanceProofError::NonCanonical)?;
+        let Y_D = self
+            .Y_D
+            .decompress()
+            .ok_or(ZeroBalanceProofError::NonCanonical)?;
+
+        // add the required algebraic relation to the batch
+        batch.append(
+            ProofError::ZeroBalanceProofFailed,
             vec![
-                &self.z,            // z
-                &(-&c),             // -c
-                &(-&Scalar::one()), // -identity
-                &(&w * &self.z),    // w * z
-                &(&w_negated * &c), // -w * c
-                &w_negated,         // -w
+                self.z,          // z
+                -&c,             // -c
+                -&Scalar::one(), // -identity
+                &w * &self.z,    // w * z
+                &w_negated * &c, // -w * c
+                w_negated,       // -w
             ],
             vec![
-                P,     // P
-                &(*H), // H
-                &Y_P,  // Y_P
-                D,     // D
-                C,     // C
-                &Y_D,  // Y_D
+                *P,  // P
+                *H,  // H
+                Y_P, // Y_P
+                *D,  // D
+                *C,  // C
+                Y_D, // Y_D
             ],
         );
 
-        if check.is_identity() {
-            Ok(())
-        } else {
-            Err(ZeroBalanceProofError::AlgebraicRelation)
-        }
+        Ok(())
     }
 
     pub fn to_bytes(&self) -> [u8; 96] {
@@ -152,13 +191,17 @@ impl ZeroBalanceProof {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZeroBalanceProofError> {
+        if bytes.len() != 96 {
+            return Err(ZeroBalanceProofError::Format);
+        }
+
         let bytes = array_ref![bytes, 0, 96];
         let (Y_P, Y_D, z) = array_refs![bytes, 32, 32, 32];
 
         let Y_P = CompressedRistretto::from_slice(Y_P);
         let Y_D = CompressedRistretto::from_slice(Y_D);
 
-        let z = Scalar::from_canonical_bytes(*z).ok_or(ZeroBalanceProofError::Format)?;
+        let z = Scalar::from_canonical_bytes(*z).ok_or(ZeroBalanceProofError::NonCanonical)?;
 
         Ok(ZeroBalanceProof { Y_P, Y_D, z })
     }
@@ -1,3 +1,10 @@
+//! Fiat-Shamir transcripts of the zk-token proofs
+//!
+//! The `TranscriptProtocol` extension trait on `merlin::Transcript` provides the typed append
+//! helpers and domain separators that the sigma proofs use, so that a program can compose the
+//! sigma proofs into its own protocols: it appends its public inputs to a transcript, for
+//! instance one created by the `transcript_new` of a proof data type, and generates or verifies
+//! each sub-proof on that transcript in a fixed order.
 use {
     crate::{errors::TranscriptError, zk_token_elgamal::pod},
     curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar, traits::IsIdentity},
@@ -16,6 +23,12 @@ pub trait TranscriptProtocol {
     /// Append a domain separator for a length-`n` inner product proof.
     fn innerproduct_domain_sep(&mut self, n: u64);
 
+    /// Append a domain separator for an `n`-bit Bulletproofs+ rangeproof.
+    fn rangeproof_plus_domain_sep(&mut self, n: u64);
+
+    /// Append a domain separator for a length-`n` weighted inner product proof.
+    fn weighted_innerproduct_domain_sep(&mut self, n: u64);
+
     /// Append a domain separator for close account proof.
     fn close_account_proof_domain_sep(&mut self);
 
@@ -25,6 +38,9 @@ pub trait TranscriptProtocol {
     /// Append a domain separator for transfer proof.
     fn transfer_proof_domain_sep(&mut self);
 
+    /// Append a `u64` with the given `label`.
+    fn append_u64(&mut self, label: &'static [u8], value: u64);
+
     /// Append a `scalar` with the given `label`.
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
 
@@ -46,6 +62,9 @@ pub trait TranscriptProtocol {
     /// Append a domain separator for equality proof.
     fn equality_proof_domain_sep(&mut self);
 
+    /// Append a domain separator for ciphertext-ciphertext equality proof.
+    fn ctxt_ctxt_equality_proof_domain_sep(&mut self);
+
     /// Append a domain separator for zero-balance proof.
     fn zero_balance_proof_domain_sep(&mut self);
 
@@ -58,6 +77,9 @@ pub trait TranscriptProtocol {
     /// Append a domain separator for fee sigma proof.
     fn fee_sigma_proof_domain_sep(&mut self);
 
+    /// Append a domain separator for public-key validity proof.
+    fn pubkey_proof_domain_sep(&mut self);
+
     /// Check that a point is not the identity, then append it to the
     /// transcript.  Otherwise, return an error.
     fn validate_and_append_point(
@@ -68,6 +90,9 @@ pub trait TranscriptProtocol {
 
     /// Compute a `label`ed challenge variable.
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+
+    /// Return a copy of the transcript that is separated from its other forks by `label`.
+    fn fork(&self, label: &'static [u8]) -> Transcript;
 }
 
 impl TranscriptProtocol for Transcript {
@@ -86,6 +111,16 @@ impl TranscriptProtocol for Transcript {
         self.append_u64(b"n", n);
     }
 
+    fn rangeproof_plus_domain_sep(&mut self, n: u64) {
+        self.append_message(b"dom-sep", b"rangeproof plus v1");
+        self.append_u64(b"n", n);
+    }
+
+    fn weighted_innerproduct_domain_sep(&mut self, n: u64) {
+        self.append_message(b"dom-sep", b"wipp v1");
+        self.append_u64(b"n", n);
+    }
+
     fn close_account_proof_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"CloseAccountProof");
     }
@@ -98,6 +133,10 @@ impl TranscriptProtocol for Transcript {
         self.append_message(b"dom-sep", b"TransferProof");
     }
 
+    fn append_u64(&mut self, label: &'static [u8], value: u64) {
+        Transcript::append_u64(self, label, value);
+    }
+
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
         self.append_message(label, scalar.as_bytes());
     }
@@ -126,6 +165,12 @@ impl TranscriptProtocol for Transcript {
         Scalar::from_bytes_mod_order_wide(&buf)
     }
 
+    fn fork(&self, label: &'static [u8]) -> Transcript {
+        let mut fork = self.clone();
+        fork.append_message(b"fork", label);
+        fork
+    }
+
     fn append_pubkey(&mut self, label: &'static [u8], pubkey: &pod::ElGamalPubkey) {
         self.append_message(label, &pubkey.0);
     }
@@ -146,6 +191,10 @@ impl TranscriptProtocol for Transcript {
         self.append_message(b"dom-sep", b"equality-proof")
     }
 
+    fn ctxt_ctxt_equality_proof_domain_sep(&mut self) {
+        self.append_message(b"dom-sep", b"ctxt-ctxt-equality-proof")
+    }
+
     fn zero_balance_proof_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"zero-balance-proof")
     }
@@ -161,4 +210,8 @@ impl TranscriptProtocol for Transcript {
     fn fee_sigma_proof_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"fee-sigma-proof")
     }
+
+    fn pubkey_proof_domain_sep(&mut self) {
+        self.append_message(b"dom-sep", b"pubkey-proof")
+    }
 }
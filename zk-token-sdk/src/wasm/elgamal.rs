@@ -0,0 +1,80 @@
+//! `ElGamalKeypair` and `ElGamalPubkey` Javascript interface
+#![cfg(target_arch = "wasm32")]
+#![allow(non_snake_case)]
+use {
+    crate::encryption::elgamal::{ElGamalKeypair, ElGamalPubkey},
+    solana_program::{pubkey::Pubkey, wasm::display_to_jsvalue},
+    solana_sdk::signer::keypair::Keypair,
+    std::convert::TryInto,
+    wasm_bindgen::prelude::*,
+};
+
+#[wasm_bindgen(js_name = ElGamalKeypair)]
+pub struct WasmElGamalKeypair(pub(crate) ElGamalKeypair);
+
+#[wasm_bindgen(js_class = ElGamalKeypair)]
+impl WasmElGamalKeypair {
+    /// Create a new random `ElGamalKeypair`
+    #[wasm_bindgen(constructor)]
+    pub fn constructor() -> WasmElGamalKeypair {
+        Self(ElGamalKeypair::new_rand())
+    }
+
+    /// Derive the `ElGamalKeypair` of a token account from the signing `Keypair` of its owner
+    pub fn fromSigner(signer: &Keypair, address: &Pubkey) -> Result<WasmElGamalKeypair, JsValue> {
+        ElGamalKeypair::new(signer, address)
+            .map(Self)
+            .map_err(display_to_jsvalue)
+    }
+
+    /// Derive an `ElGamalKeypair` from a signing `Keypair` and an arbitrary seed
+    pub fn fromSignerAndSeed(signer: &Keypair, seed: &[u8]) -> Result<WasmElGamalKeypair, JsValue> {
+        ElGamalKeypair::new_from_signer(signer, seed)
+            .map(Self)
+            .map_err(display_to_jsvalue)
+    }
+
+    /// Derive the child `ElGamalKeypair` associated with a token account address
+    pub fn deriveChild(&self, address: &Pubkey) -> WasmElGamalKeypair {
+        Self(self.0.derive_child(address))
+    }
+
+    /// Return the `ElGamalPubkey` of this keypair
+    pub fn pubkey(&self) -> WasmElGamalPubkey {
+        WasmElGamalPubkey(self.0.public)
+    }
+
+    /// Convert an `ElGamalKeypair` to a `Uint8Array`
+    pub fn toBytes(&self) -> Box<[u8]> {
+        self.0.to_bytes().into()
+    }
+
+    /// Recover an `ElGamalKeypair` from a `Uint8Array`
+    pub fn fromBytes(bytes: &[u8]) -> Result<WasmElGamalKeypair, JsValue> {
+        ElGamalKeypair::from_bytes(bytes)
+            .map(Self)
+            .ok_or_else(|| display_to_jsvalue("Invalid ElGamalKeypair"))
+    }
+}
+
+#[wasm_bindgen(js_name = ElGamalPubkey)]
+#[derive(Clone, Copy)]
+pub struct WasmElGamalPubkey(pub(crate) ElGamalPubkey);
+
+#[wasm_bindgen(js_class = ElGamalPubkey)]
+impl WasmElGamalPubkey {
+    /// Convert an `ElGamalPubkey` to a `Uint8Array`
+    pub fn toBytes(&self) -> Box<[u8]> {
+        self.0.to_bytes().into()
+    }
+
+    /// Recover an `ElGamalPubkey` from a `Uint8Array`
+    pub fn fromBytes(bytes: &[u8]) -> Result<WasmElGamalPubkey, JsValue> {
+        bytes
+            .try_into()
+            .ok()
+            .and_then(ElGamalPubkey::from_bytes)
+            .map(Self)
+            .ok_or_else(|| display_to_jsvalue("Invalid ElGamalPubkey"))
+    }
+}
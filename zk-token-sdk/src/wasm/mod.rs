@@ -0,0 +1,5 @@
+//! solana-zk-token-sdk Javascript interface
+#![cfg(target_arch = "wasm32")]
+
+pub mod elgamal;
+pub mod transfer_with_fee;
@@ -0,0 +1,133 @@
+//! `TransferWithFeeData` Javascript interface
+#![cfg(target_arch = "wasm32")]
+#![allow(non_snake_case)]
+use {
+    crate::{
+        encryption::elgamal::{ElGamalCiphertext, ElGamalPubkey},
+        instruction::{transfer_with_fee::FeeParameters, Role, TransferWithFeeData},
+        wasm::elgamal::{WasmElGamalKeypair, WasmElGamalPubkey},
+        zk_token_proof_instruction::verify_transfer_with_fee,
+    },
+    bytemuck::{bytes_of, try_from_bytes},
+    solana_program::{instruction::Instruction, wasm::display_to_jsvalue},
+    std::convert::TryInto,
+    wasm_bindgen::prelude::*,
+};
+
+/// The party of a transfer that decrypts the transfer amount or fee
+#[wasm_bindgen(js_name = TransferRole)]
+#[derive(Clone, Copy)]
+pub enum WasmTransferRole {
+    Source,
+    Dest,
+    Auditor,
+    FeeCollector,
+}
+
+impl WasmTransferRole {
+    fn to_role(self, auditor_index: usize) -> Role {
+        match self {
+            Self::Source => Role::Source,
+            Self::Dest => Role::Dest,
+            Self::Auditor => Role::Auditor(auditor_index),
+            Self::FeeCollector => Role::FeeCollector,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = TransferWithFeeData)]
+pub struct WasmTransferWithFeeData(TransferWithFeeData);
+
+#[wasm_bindgen(js_class = TransferWithFeeData)]
+impl WasmTransferWithFeeData {
+    /// Create the proof data of a confidential transfer with fee
+    ///
+    /// `ciphertextOldSource` is the encrypted spendable balance of the source account, which
+    /// decrypts to `spendableBalance`, and `pubkeysAuditor` is the concatenation of the
+    /// 32-byte auditor pubkeys
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn constructor(
+        transferAmount: u64,
+        spendableBalance: u64,
+        ciphertextOldSource: &[u8],
+        keypairSource: &WasmElGamalKeypair,
+        pubkeyDest: &WasmElGamalPubkey,
+        pubkeysAuditor: &[u8],
+        feeRate: u64,
+        maximumFee: u64,
+        feeDenominator: u64,
+        pubkeyFeeCollector: &WasmElGamalPubkey,
+    ) -> Result<WasmTransferWithFeeData, JsValue> {
+        let ciphertext_old_source = ElGamalCiphertext::from_bytes(ciphertextOldSource)
+            .ok_or_else(|| display_to_jsvalue("Invalid ElGamalCiphertext"))?;
+
+        if pubkeysAuditor.len() % 32 != 0 {
+            return Err(display_to_jsvalue("Invalid auditor ElGamalPubkeys"));
+        }
+        let pubkeys_auditor = pubkeysAuditor
+            .chunks(32)
+            .map(|bytes| ElGamalPubkey::from_bytes(bytes.try_into().ok()?))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| display_to_jsvalue("Invalid auditor ElGamalPubkeys"))?;
+
+        let fee_parameters = FeeParameters {
+            fee_rate: feeRate,
+            maximum_fee: maximumFee,
+            fee_denominator: feeDenominator,
+        };
+
+        TransferWithFeeData::new(
+            transferAmount,
+            (spendableBalance, &ciphertext_old_source),
+            &keypairSource.0,
+            (&pubkeyDest.0, &pubkeys_auditor),
+            fee_parameters,
+            &pubkeyFeeCollector.0,
+        )
+        .map(Self)
+        .map_err(display_to_jsvalue)
+    }
+
+    /// Decrypt the transfer amount as the given party of the transfer
+    ///
+    /// `auditorIndex` is only used for `TransferRole.Auditor`
+    pub fn decryptAmount(
+        &self,
+        role: WasmTransferRole,
+        auditorIndex: usize,
+        keypair: &WasmElGamalKeypair,
+    ) -> Result<u64, JsValue> {
+        self.0
+            .decrypt_amount(role.to_role(auditorIndex), &keypair.0.secret)
+            .map_err(display_to_jsvalue)
+    }
+
+    /// Decrypt the transfer fee as the destination or the fee collector of the transfer
+    pub fn decryptFee(
+        &self,
+        role: WasmTransferRole,
+        keypair: &WasmElGamalKeypair,
+    ) -> Result<u64, JsValue> {
+        self.0
+            .decrypt_fee(role.to_role(0), &keypair.0.secret)
+            .map_err(display_to_jsvalue)
+    }
+
+    /// Create the ZkToken Proof program instruction that verifies the proof data
+    pub fn verifyInstruction(&self) -> Instruction {
+        verify_transfer_with_fee(&self.0)
+    }
+
+    /// Convert the proof data to a `Uint8Array`
+    pub fn toBytes(&self) -> Box<[u8]> {
+        bytes_of(&self.0).into()
+    }
+
+    /// Recover the proof data from a `Uint8Array`
+    pub fn fromBytes(bytes: &[u8]) -> Result<WasmTransferWithFeeData, JsValue> {
+        try_from_bytes::<TransferWithFeeData>(bytes)
+            .map(|proof_data| Self(*proof_data))
+            .map_err(|_| display_to_jsvalue("Invalid TransferWithFeeData"))
+    }
+}
@@ -22,15 +22,22 @@ mod target_arch {
             },
             errors::ProofError,
             instruction::{
+                burn::{BurnAmountEncryption, BurnPubkeys},
+                mint::{MintAmountEncryption, MintPubkeys},
                 transfer::{TransferAmountEncryption, TransferPubkeys},
                 transfer_with_fee::{FeeEncryption, FeeParameters, TransferWithFeePubkeys},
+                AmountSplit, MAX_AUDITORS,
             },
-            range_proof::{errors::RangeProofError, RangeProof},
+            range_proof::{errors::RangeProofError, plus::RangeProofPlus, RangeProof},
             sigma_proofs::{
-                equality_proof::EqualityProof,
+                equality_proof::{CtxtCtxtEqualityProof, EqualityProof},
                 errors::*,
                 fee_proof::FeeSigmaProof,
-                validity_proof::{AggregatedValidityProof, ValidityProof},
+                pubkey_proof::PubkeyValidityProof,
+                validity_proof::{
+                    AggregatedValidityProof, GroupedCiphertext2HandlesValidityProof,
+                    GroupedCiphertext3HandlesValidityProof, ValidityProof,
+                },
                 zero_balance_proof::ZeroBalanceProof,
             },
         },
@@ -44,9 +51,11 @@ mod target_arch {
         }
     }
 
-    impl From<pod::Scalar> for Scalar {
-        fn from(pod: pod::Scalar) -> Self {
-            Scalar::from_bits(pod.0)
+    impl TryFrom<pod::Scalar> for Scalar {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::Scalar) -> Result<Self, Self::Error> {
+            Scalar::from_canonical_bytes(pod.0).ok_or(ProofError::NonCanonical)
         }
     }
 
@@ -60,7 +69,7 @@ mod target_arch {
         type Error = ProofError;
 
         fn try_from(ct: pod::ElGamalCiphertext) -> Result<Self, Self::Error> {
-            Self::from_bytes(&ct.0).ok_or(ProofError::InconsistentCTData)
+            Self::from_bytes(&ct.0).ok_or(ProofError::NonCanonical)
         }
     }
 
@@ -74,7 +83,7 @@ mod target_arch {
         type Error = ProofError;
 
         fn try_from(pk: pod::ElGamalPubkey) -> Result<Self, Self::Error> {
-            Self::from_bytes(&pk.0).ok_or(ProofError::InconsistentCTData)
+            Self::from_bytes(&pk.0).ok_or(ProofError::NonCanonical)
         }
     }
 
@@ -109,7 +118,7 @@ mod target_arch {
         type Error = ProofError;
 
         fn try_from(pod: pod::PedersenCommitment) -> Result<Self, Self::Error> {
-            Self::from_bytes(&pod.0).ok_or(ProofError::InconsistentCTData)
+            Self::from_bytes(&pod.0).ok_or(ProofError::NonCanonical)
         }
     }
 
@@ -133,7 +142,7 @@ mod target_arch {
         type Error = ProofError;
 
         fn try_from(pod: pod::DecryptHandle) -> Result<Self, Self::Error> {
-            Self::from_bytes(&pod.0).ok_or(ProofError::InconsistentCTData)
+            Self::from_bytes(&pod.0).ok_or(ProofError::NonCanonical)
         }
     }
 
@@ -165,12 +174,35 @@ mod target_arch {
         }
     }
 
-    impl From<ValidityProof> for pod::ValidityProof {
-        fn from(proof: ValidityProof) -> Self {
+    impl From<CtxtCtxtEqualityProof> for pod::CtxtCtxtEqualityProof {
+        fn from(proof: CtxtCtxtEqualityProof) -> Self {
             Self(proof.to_bytes())
         }
     }
 
+    impl TryFrom<pod::CtxtCtxtEqualityProof> for CtxtCtxtEqualityProof {
+        type Error = EqualityProofError;
+
+        fn try_from(pod: pod::CtxtCtxtEqualityProof) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl TryFrom<ValidityProof> for pod::ValidityProof {
+        type Error = ValidityProofError;
+
+        fn try_from(proof: ValidityProof) -> Result<Self, Self::Error> {
+            let bytes = proof.to_bytes();
+            if bytes.len() != ValidityProof::serialized_size(2) {
+                return Err(ValidityProofError::Format);
+            }
+
+            let mut buf = [0_u8; ValidityProof::serialized_size(2)];
+            buf.copy_from_slice(&bytes);
+            Ok(Self(buf))
+        }
+    }
+
     impl TryFrom<pod::ValidityProof> for ValidityProof {
         type Error = ValidityProofError;
 
@@ -179,20 +211,74 @@ mod target_arch {
         }
     }
 
-    impl From<AggregatedValidityProof> for pod::AggregatedValidityProof {
-        fn from(proof: AggregatedValidityProof) -> Self {
-            Self(proof.to_bytes())
+    impl From<GroupedCiphertext2HandlesValidityProof> for pod::GroupedCiphertext2HandlesValidityProof {
+        fn from(proof: GroupedCiphertext2HandlesValidityProof) -> Self {
+            let mut buf = [0_u8; GroupedCiphertext2HandlesValidityProof::SERIALIZED_SIZE];
+            buf.copy_from_slice(&proof.to_bytes());
+            Self(buf)
+        }
+    }
+
+    impl TryFrom<pod::GroupedCiphertext2HandlesValidityProof>
+        for GroupedCiphertext2HandlesValidityProof
+    {
+        type Error = ValidityProofError;
+
+        fn try_from(pod: pod::GroupedCiphertext2HandlesValidityProof) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
         }
     }
 
-    impl TryFrom<pod::AggregatedValidityProof> for AggregatedValidityProof {
+    impl From<GroupedCiphertext3HandlesValidityProof> for pod::GroupedCiphertext3HandlesValidityProof {
+        fn from(proof: GroupedCiphertext3HandlesValidityProof) -> Self {
+            let mut buf = [0_u8; GroupedCiphertext3HandlesValidityProof::SERIALIZED_SIZE];
+            buf.copy_from_slice(&proof.to_bytes());
+            Self(buf)
+        }
+    }
+
+    impl TryFrom<pod::GroupedCiphertext3HandlesValidityProof>
+        for GroupedCiphertext3HandlesValidityProof
+    {
         type Error = ValidityProofError;
 
-        fn try_from(pod: pod::AggregatedValidityProof) -> Result<Self, Self::Error> {
+        fn try_from(pod: pod::GroupedCiphertext3HandlesValidityProof) -> Result<Self, Self::Error> {
             Self::from_bytes(&pod.0)
         }
     }
 
+    impl TryFrom<AggregatedValidityProof> for pod::AggregatedValidityProof {
+        type Error = ValidityProofError;
+
+        fn try_from(proof: AggregatedValidityProof) -> Result<Self, Self::Error> {
+            // proofs for fewer handles than the maximum are padded with zeroes
+            let bytes = proof.to_bytes();
+            let mut buf = [0_u8; AggregatedValidityProof::serialized_size(MAX_AUDITORS + 1)];
+            if bytes.len() > buf.len() {
+                return Err(ValidityProofError::Format);
+            }
+
+            buf[..bytes.len()].copy_from_slice(&bytes);
+            Ok(Self(buf))
+        }
+    }
+
+    impl pod::AggregatedValidityProof {
+        /// Deserializes the proof for `num_handles` pairs of decryption handles, rejecting any
+        /// non-zero padding
+        pub fn to_proof(
+            &self,
+            num_handles: usize,
+        ) -> Result<AggregatedValidityProof, ValidityProofError> {
+            let len = AggregatedValidityProof::serialized_size(num_handles);
+            if len > self.0.len() || self.0[len..].iter().any(|byte| *byte != 0) {
+                return Err(ValidityProofError::Format);
+            }
+
+            AggregatedValidityProof::from_bytes(&self.0[..len])
+        }
+    }
+
     impl From<ZeroBalanceProof> for pod::ZeroBalanceProof {
         fn from(proof: ZeroBalanceProof) -> Self {
             Self(proof.to_bytes())
@@ -207,6 +293,20 @@ mod target_arch {
         }
     }
 
+    impl From<PubkeyValidityProof> for pod::PubkeyValidityProof {
+        fn from(proof: PubkeyValidityProof) -> Self {
+            Self(proof.to_bytes())
+        }
+    }
+
+    impl TryFrom<pod::PubkeyValidityProof> for PubkeyValidityProof {
+        type Error = PubkeyValidityProofError;
+
+        fn try_from(pod: pod::PubkeyValidityProof) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
     impl From<FeeSigmaProof> for pod::FeeSigmaProof {
         fn from(proof: FeeSigmaProof) -> Self {
             Self(proof.to_bytes())
@@ -310,6 +410,102 @@ mod target_arch {
         }
     }
 
+    #[cfg(not(target_arch = "bpf"))]
+    impl TryFrom<RangeProof> for pod::RangeProof512 {
+        type Error = RangeProofError;
+
+        fn try_from(proof: RangeProof) -> Result<Self, Self::Error> {
+            if proof.ipp_proof.serialized_size() != 640 {
+                return Err(RangeProofError::Format);
+            }
+
+            let mut buf = [0_u8; 864];
+            buf[..32].copy_from_slice(proof.A.as_bytes());
+            buf[32..64].copy_from_slice(proof.S.as_bytes());
+            buf[64..96].copy_from_slice(proof.T_1.as_bytes());
+            buf[96..128].copy_from_slice(proof.T_2.as_bytes());
+            buf[128..160].copy_from_slice(proof.t_x.as_bytes());
+            buf[160..192].copy_from_slice(proof.t_x_blinding.as_bytes());
+            buf[192..224].copy_from_slice(proof.e_blinding.as_bytes());
+            buf[224..864].copy_from_slice(&proof.ipp_proof.to_bytes());
+            Ok(pod::RangeProof512(buf))
+        }
+    }
+
+    impl TryFrom<pod::RangeProof512> for RangeProof {
+        type Error = RangeProofError;
+
+        fn try_from(pod: pod::RangeProof512) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl TryFrom<RangeProofPlus> for pod::RangeProofPlus64 {
+        type Error = RangeProofError;
+
+        fn try_from(proof: RangeProofPlus) -> Result<Self, Self::Error> {
+            if proof.wip_proof.serialized_size() != 544 {
+                return Err(RangeProofError::Format);
+            }
+
+            let mut buf = [0_u8; 576];
+            buf.copy_from_slice(&proof.to_bytes());
+            Ok(pod::RangeProofPlus64(buf))
+        }
+    }
+
+    impl TryFrom<pod::RangeProofPlus64> for RangeProofPlus {
+        type Error = RangeProofError;
+
+        fn try_from(pod: pod::RangeProofPlus64) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl TryFrom<RangeProofPlus> for pod::RangeProofPlus128 {
+        type Error = RangeProofError;
+
+        fn try_from(proof: RangeProofPlus) -> Result<Self, Self::Error> {
+            if proof.wip_proof.serialized_size() != 608 {
+                return Err(RangeProofError::Format);
+            }
+
+            let mut buf = [0_u8; 640];
+            buf.copy_from_slice(&proof.to_bytes());
+            Ok(pod::RangeProofPlus128(buf))
+        }
+    }
+
+    impl TryFrom<pod::RangeProofPlus128> for RangeProofPlus {
+        type Error = RangeProofError;
+
+        fn try_from(pod: pod::RangeProofPlus128) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl TryFrom<RangeProofPlus> for pod::RangeProofPlus256 {
+        type Error = RangeProofError;
+
+        fn try_from(proof: RangeProofPlus) -> Result<Self, Self::Error> {
+            if proof.wip_proof.serialized_size() != 672 {
+                return Err(RangeProofError::Format);
+            }
+
+            let mut buf = [0_u8; 704];
+            buf.copy_from_slice(&proof.to_bytes());
+            Ok(pod::RangeProofPlus256(buf))
+        }
+    }
+
+    impl TryFrom<pod::RangeProofPlus256> for RangeProofPlus {
+        type Error = RangeProofError;
+
+        fn try_from(pod: pod::RangeProofPlus256) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
     impl From<TransferPubkeys> for pod::TransferPubkeys {
         fn from(keys: TransferPubkeys) -> Self {
             Self(keys.to_bytes())
@@ -352,6 +548,83 @@ mod target_arch {
         }
     }
 
+    impl From<MintPubkeys> for pod::MintPubkeys {
+        fn from(keys: MintPubkeys) -> Self {
+            Self(keys.to_bytes())
+        }
+    }
+
+    impl TryFrom<pod::MintPubkeys> for MintPubkeys {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::MintPubkeys) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl From<MintAmountEncryption> for pod::MintAmountEncryption {
+        fn from(ciphertext: MintAmountEncryption) -> Self {
+            Self(ciphertext.to_bytes())
+        }
+    }
+
+    impl TryFrom<pod::MintAmountEncryption> for MintAmountEncryption {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::MintAmountEncryption) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl From<BurnPubkeys> for pod::BurnPubkeys {
+        fn from(keys: BurnPubkeys) -> Self {
+            Self(keys.to_bytes())
+        }
+    }
+
+    impl TryFrom<pod::BurnPubkeys> for BurnPubkeys {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::BurnPubkeys) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl From<BurnAmountEncryption> for pod::BurnAmountEncryption {
+        fn from(ciphertext: BurnAmountEncryption) -> Self {
+            Self(ciphertext.to_bytes())
+        }
+    }
+
+    impl TryFrom<pod::BurnAmountEncryption> for BurnAmountEncryption {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::BurnAmountEncryption) -> Result<Self, Self::Error> {
+            Self::from_bytes(&pod.0)
+        }
+    }
+
+    impl From<AmountSplit> for pod::AmountSplit {
+        fn from(amount_split: AmountSplit) -> Self {
+            match amount_split {
+                AmountSplit::Lo32Hi32 => Self([0]),
+                AmountSplit::Lo48Hi16 => Self([1]),
+            }
+        }
+    }
+
+    impl TryFrom<pod::AmountSplit> for AmountSplit {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::AmountSplit) -> Result<Self, Self::Error> {
+            match pod.0 {
+                [0] => Ok(AmountSplit::Lo32Hi32),
+                [1] => Ok(AmountSplit::Lo48Hi16),
+                _ => Err(ProofError::Verification),
+            }
+        }
+    }
+
     impl From<FeeEncryption> for pod::FeeEncryption {
         fn from(ciphertext: FeeEncryption) -> Self {
             Self(ciphertext.to_bytes())
@@ -372,8 +645,10 @@ mod target_arch {
         }
     }
 
-    impl From<pod::FeeParameters> for FeeParameters {
-        fn from(pod: pod::FeeParameters) -> Self {
+    impl TryFrom<pod::FeeParameters> for FeeParameters {
+        type Error = ProofError;
+
+        fn try_from(pod: pod::FeeParameters) -> Result<Self, Self::Error> {
             Self::from_bytes(&pod.0)
         }
     }
@@ -387,11 +662,69 @@ mod target_arch {}
 mod tests {
     use {
         super::*,
-        crate::{encryption::pedersen::Pedersen, range_proof::RangeProof},
+        crate::{
+            encryption::{
+                elgamal::{ElGamalKeypair, ElGamalPubkey},
+                pedersen::Pedersen,
+            },
+            errors::ProofError,
+            range_proof::RangeProof,
+            sigma_proofs::equality_proof::EqualityProof,
+        },
+        curve25519_dalek::{constants::BASEPOINT_ORDER, scalar::Scalar},
         merlin::Transcript,
         std::convert::TryInto,
     };
 
+    #[test]
+    fn test_pod_non_canonical() {
+        // the group order itself is an unreduced scalar
+        let unreduced = pod::Scalar(BASEPOINT_ORDER.to_bytes());
+        assert_eq!(
+            TryInto::<Scalar>::try_into(unreduced).err(),
+            Some(ProofError::NonCanonical)
+        );
+
+        // the field modulus is a non-canonical encoding of the identity
+        let mut modulus = [0xff; 32];
+        modulus[0] = 0xed;
+        modulus[31] = 0x7f;
+        assert!(TryInto::<ElGamalPubkey>::try_into(pod::ElGamalPubkey([0; 32])).is_ok());
+        assert_eq!(
+            TryInto::<ElGamalPubkey>::try_into(pod::ElGamalPubkey(modulus)).err(),
+            Some(ProofError::NonCanonical)
+        );
+
+        // a sigma proof response plus the group order is the same scalar, but must be rejected
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = keypair.public.encrypt(55_u64);
+        let (_, opening) = Pedersen::new(55_u64);
+        let proof = EqualityProof::new(
+            &keypair,
+            &ciphertext,
+            55_u64,
+            &opening,
+            &mut Transcript::new(b"Test"),
+        );
+
+        let mut pod_proof: pod::EqualityProof = proof.into();
+        let mut carry = 0_u16;
+        for (byte, order_byte) in pod_proof.0[96..128]
+            .iter_mut()
+            .zip(BASEPOINT_ORDER.to_bytes())
+        {
+            let sum = *byte as u16 + order_byte as u16 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(
+            TryInto::<EqualityProof>::try_into(pod_proof)
+                .map_err(ProofError::from)
+                .err(),
+            Some(ProofError::NonCanonical)
+        );
+    }
+
     #[test]
     fn test_pod_range_proof_64() {
         let (comm, open) = Pedersen::new(55_u64);
@@ -399,7 +732,8 @@ mod tests {
         let mut transcript_create = Transcript::new(b"Test");
         let mut transcript_verify = Transcript::new(b"Test");
 
-        let proof = RangeProof::new(vec![55], vec![64], vec![&open], &mut transcript_create);
+        let proof =
+            RangeProof::new(vec![55], vec![64], vec![&open], &mut transcript_create).unwrap();
 
         let proof_serialized: pod::RangeProof64 = proof.try_into().unwrap();
         let proof_deserialized: RangeProof = proof_serialized.try_into().unwrap();
@@ -409,7 +743,8 @@ mod tests {
             .is_ok());
 
         // should fail to serialize to pod::RangeProof128
-        let proof = RangeProof::new(vec![55], vec![64], vec![&open], &mut transcript_create);
+        let proof =
+            RangeProof::new(vec![55], vec![64], vec![&open], &mut transcript_create).unwrap();
 
         assert!(TryInto::<pod::RangeProof128>::try_into(proof).is_err());
     }
@@ -428,7 +763,8 @@ mod tests {
             vec![64, 32, 32],
             vec![&open_1, &open_2, &open_3],
             &mut transcript_create,
-        );
+        )
+        .unwrap();
 
         let proof_serialized: pod::RangeProof128 = proof.try_into().unwrap();
         let proof_deserialized: RangeProof = proof_serialized.try_into().unwrap();
@@ -447,7 +783,8 @@ mod tests {
             vec![64, 32, 32],
             vec![&open_1, &open_2, &open_3],
             &mut transcript_create,
-        );
+        )
+        .unwrap();
 
         assert!(TryInto::<pod::RangeProof64>::try_into(proof).is_err());
     }
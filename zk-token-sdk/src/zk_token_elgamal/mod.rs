@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
 pub mod convert;
+#[cfg(feature = "std")]
 pub mod ops;
 pub mod pod;
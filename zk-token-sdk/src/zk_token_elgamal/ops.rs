@@ -1,3 +1,11 @@
+//! Homomorphic arithmetic on ElGamal ciphertexts in their pod encoding.
+//!
+//! The operations compile for `target_arch = "bpf"`, where they are carried out by the
+//! `sol_zk_token_elgamal_op*` syscalls, so that a token program can update an encrypted balance
+//! on-chain once the proof that accompanies the update has been verified. Every operation returns
+//! `None` if one of the input ciphertexts is not a valid encoding. Multiplication by a public
+//! amount is carried out by the curve25519 syscalls instead.
+
 pub use target_arch::*;
 
 #[cfg(not(target_arch = "bpf"))]
@@ -87,12 +95,21 @@ mod target_arch {
             &pod::ElGamalCiphertext(amount_as_ct),
         )
     }
+
+    pub fn multiply(ct: &pod::ElGamalCiphertext, amount: u64) -> Option<pod::ElGamalCiphertext> {
+        let ct: ElGamalCiphertext = (*ct).try_into().ok()?;
+        Some(pod::ElGamalCiphertext::from(ct * Scalar::from(amount)))
+    }
 }
 
 #[cfg(target_arch = "bpf")]
 #[allow(unused_variables)]
 mod target_arch {
-    use {super::*, crate::zk_token_elgamal::pod, bytemuck::Zeroable};
+    use {
+        super::*,
+        crate::{curve25519::multiply_ristretto, zk_token_elgamal::pod},
+        bytemuck::Zeroable,
+    };
 
     fn op(
         op: u64,
@@ -202,6 +219,27 @@ mod target_arch {
     ) -> Option<pod::ElGamalCiphertext> {
         op_with_scalar(OP_SUB, ct, amount)
     }
+
+    /// Multiplies the ciphertext by `amount` with the curve25519 syscalls, which must be enabled
+    /// on the cluster
+    pub fn multiply(ct: &pod::ElGamalCiphertext, amount: u64) -> Option<pod::ElGamalCiphertext> {
+        // a `u64` in little-endian encoding is a canonical scalar
+        let mut scalar = pod::Scalar::zeroed();
+        scalar.0[..8].copy_from_slice(&amount.to_le_bytes());
+
+        let mut commitment = pod::CompressedRistretto::zeroed();
+        let mut handle = pod::CompressedRistretto::zeroed();
+        commitment.0.copy_from_slice(&ct.0[..32]);
+        handle.0.copy_from_slice(&ct.0[32..]);
+
+        let commitment = multiply_ristretto(&scalar, &commitment)?;
+        let handle = multiply_ristretto(&scalar, &handle)?;
+
+        let mut ct_result = pod::ElGamalCiphertext::zeroed();
+        ct_result.0[..32].copy_from_slice(&commitment.0);
+        ct_result.0[32..].copy_from_slice(&handle.0);
+        Some(ct_result)
+    }
 }
 
 pub const OP_ADD: u64 = 0;
@@ -283,6 +321,21 @@ mod tests {
         assert_eq!(expected, added_ct);
     }
 
+    #[test]
+    fn test_multiply() {
+        let public = ElGamalKeypair::new_rand().public;
+        let open = PedersenOpening::new_rand();
+        let encrypted_amount: pod::ElGamalCiphertext = public.encrypt_with(55_u64, &open).into();
+
+        let multiplied_ct = ops::multiply(&encrypted_amount, 3).unwrap();
+
+        let expected: pod::ElGamalCiphertext = public
+            .encrypt_with(165_u64, &(open * Scalar::from(3_u64)))
+            .into();
+
+        assert_eq!(expected, multiplied_ct);
+    }
+
     #[test]
     fn test_subtract_from() {
         let amount = 77_u64;
@@ -1,60 +1,43 @@
 pub use bytemuck::{Pod, Zeroable};
-use std::fmt;
+use {
+    core::{array::TryFromSliceError, fmt},
+    static_assertions::const_assert_eq,
+    subtle::{Choice, ConstantTimeEq},
+};
 
-#[derive(Clone, Copy, Pod, Zeroable, PartialEq)]
+/// Maximum number of auditors that can decrypt the amount of a transfer
+pub const MAX_AUDITORS: usize = 2;
+
+#[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct Scalar(pub [u8; 32]);
 
-#[derive(Clone, Copy, Pod, Zeroable, PartialEq)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct CompressedRistretto(pub [u8; 32]);
 
-#[derive(Clone, Copy, Pod, Zeroable, PartialEq)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct ElGamalCiphertext(pub [u8; 64]);
 
-impl fmt::Debug for ElGamalCiphertext {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-
 impl Default for ElGamalCiphertext {
     fn default() -> Self {
         Self::zeroed()
     }
 }
 
-#[derive(Clone, Copy, Default, Pod, Zeroable, PartialEq)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct ElGamalPubkey(pub [u8; 32]);
 
-impl fmt::Debug for ElGamalPubkey {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-
-#[derive(Clone, Copy, Default, Pod, Zeroable, PartialEq)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct PedersenCommitment(pub [u8; 32]);
 
-impl fmt::Debug for PedersenCommitment {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-
-#[derive(Clone, Copy, Default, Pod, Zeroable, PartialEq)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct DecryptHandle(pub [u8; 32]);
 
-impl fmt::Debug for DecryptHandle {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-
 /// Serialization of equality proofs
 #[derive(Clone, Copy)]
 #[repr(transparent)]
@@ -65,6 +48,16 @@ pub struct EqualityProof(pub [u8; 192]);
 unsafe impl Zeroable for EqualityProof {}
 unsafe impl Pod for EqualityProof {}
 
+/// Serialization of ciphertext-ciphertext equality proofs
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct CtxtCtxtEqualityProof(pub [u8; 224]);
+
+// `CtxtCtxtEqualityProof` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for CtxtCtxtEqualityProof {}
+unsafe impl Pod for CtxtCtxtEqualityProof {}
+
 /// Serialization of validity proofs
 #[derive(Clone, Copy)]
 #[repr(transparent)]
@@ -75,10 +68,31 @@ pub struct ValidityProof(pub [u8; 160]);
 unsafe impl Zeroable for ValidityProof {}
 unsafe impl Pod for ValidityProof {}
 
-/// Serialization of aggregated validity proofs
+/// Serialization of grouped ciphertext validity proofs for two decryption handles
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct GroupedCiphertext2HandlesValidityProof(pub [u8; 160]);
+
+// `GroupedCiphertext2HandlesValidityProof` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for GroupedCiphertext2HandlesValidityProof {}
+unsafe impl Pod for GroupedCiphertext2HandlesValidityProof {}
+
+/// Serialization of grouped ciphertext validity proofs for three decryption handles
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct GroupedCiphertext3HandlesValidityProof(pub [u8; 192]);
+
+// `GroupedCiphertext3HandlesValidityProof` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for GroupedCiphertext3HandlesValidityProof {}
+unsafe impl Pod for GroupedCiphertext3HandlesValidityProof {}
+
+/// Serialization of aggregated validity proofs for the destination and up to `MAX_AUDITORS`
+/// auditor decryption handles, padded with zeroes
 #[derive(Clone, Copy)]
 #[repr(transparent)]
-pub struct AggregatedValidityProof(pub [u8; 160]);
+pub struct AggregatedValidityProof(pub [u8; 32 * (MAX_AUDITORS + 4)]);
 
 // `AggregatedValidityProof` is a Pod and Zeroable.
 // Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
@@ -95,6 +109,11 @@ pub struct ZeroBalanceProof(pub [u8; 96]);
 unsafe impl Zeroable for ZeroBalanceProof {}
 unsafe impl Pod for ZeroBalanceProof {}
 
+/// Serialization of public-key validity proofs
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct PubkeyValidityProof(pub [u8; 64]);
+
 /// Serialization of fee sigma proof
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
@@ -130,8 +149,48 @@ pub struct RangeProof256(pub [u8; 800]);
 unsafe impl Zeroable for RangeProof256 {}
 unsafe impl Pod for RangeProof256 {}
 
+/// Serialization of range proofs for 512-bit numbers (for `TransferBatch` instruction)
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct RangeProof512(pub [u8; 864]);
+
+// `PodRangeProof512` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for RangeProof512 {}
+unsafe impl Pod for RangeProof512 {}
+
+/// Serialization of Bulletproofs+ range proofs for 64-bit numbers
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct RangeProofPlus64(pub [u8; 576]);
+
+// `RangeProofPlus64` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for RangeProofPlus64 {}
+unsafe impl Pod for RangeProofPlus64 {}
+
+/// Serialization of Bulletproofs+ range proofs for 128-bit numbers
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct RangeProofPlus128(pub [u8; 640]);
+
+// `RangeProofPlus128` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for RangeProofPlus128 {}
+unsafe impl Pod for RangeProofPlus128 {}
+
+/// Serialization of Bulletproofs+ range proofs for 256-bit numbers
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct RangeProofPlus256(pub [u8; 704]);
+
+// `RangeProofPlus256` is a Pod and Zeroable.
+// Add the marker traits manually because `bytemuck` only adds them for some `u8` arrays
+unsafe impl Zeroable for RangeProofPlus256 {}
+unsafe impl Pod for RangeProofPlus256 {}
+
 /// Serialization for AeCiphertext
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy)]
 #[repr(transparent)]
 pub struct AeCiphertext(pub [u8; 36]);
 
@@ -140,12 +199,6 @@ pub struct AeCiphertext(pub [u8; 36]);
 unsafe impl Zeroable for AeCiphertext {}
 unsafe impl Pod for AeCiphertext {}
 
-impl fmt::Debug for AeCiphertext {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self.0)
-    }
-}
-
 impl Default for AeCiphertext {
     fn default() -> Self {
         Self::zeroed()
@@ -155,18 +208,57 @@ impl Default for AeCiphertext {
 // TODO: refactor this code into the instruction module
 #[derive(Clone, Copy)]
 #[repr(transparent)]
-pub struct TransferPubkeys(pub [u8; 96]);
+pub struct TransferPubkeys(pub [u8; 32 * (MAX_AUDITORS + 2)]);
 
 unsafe impl Zeroable for TransferPubkeys {}
 unsafe impl Pod for TransferPubkeys {}
 
-#[derive(Clone, Copy, Pod, Zeroable)]
+#[derive(Clone, Copy)]
 #[repr(transparent)]
-pub struct TransferWithFeePubkeys(pub [u8; 128]);
+pub struct TransferWithFeePubkeys(pub [u8; 32 * (MAX_AUDITORS + 3)]);
 
-#[derive(Clone, Copy, Pod, Zeroable)]
+unsafe impl Zeroable for TransferWithFeePubkeys {}
+unsafe impl Pod for TransferWithFeePubkeys {}
+
+#[derive(Clone, Copy)]
 #[repr(transparent)]
-pub struct TransferAmountEncryption(pub [u8; 128]);
+pub struct TransferAmountEncryption(pub [u8; 32 * (MAX_AUDITORS + 3)]);
+
+unsafe impl Zeroable for TransferAmountEncryption {}
+unsafe impl Pod for TransferAmountEncryption {}
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct MintPubkeys(pub [u8; 32 * (MAX_AUDITORS + 1)]);
+
+unsafe impl Zeroable for MintPubkeys {}
+unsafe impl Pod for MintPubkeys {}
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct MintAmountEncryption(pub [u8; 32 * (MAX_AUDITORS + 2)]);
+
+unsafe impl Zeroable for MintAmountEncryption {}
+unsafe impl Pod for MintAmountEncryption {}
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct BurnPubkeys(pub [u8; 32 * (MAX_AUDITORS + 1)]);
+
+unsafe impl Zeroable for BurnPubkeys {}
+unsafe impl Pod for BurnPubkeys {}
+
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct BurnAmountEncryption(pub [u8; 32 * (MAX_AUDITORS + 2)]);
+
+unsafe impl Zeroable for BurnAmountEncryption {}
+unsafe impl Pod for BurnAmountEncryption {}
+
+/// Serialization of `AmountSplit`: 0 for a 32/32-bit split and 1 for a 48/16-bit split
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct AmountSplit(pub [u8; 1]);
 
 #[derive(Clone, Copy)]
 #[repr(transparent)]
@@ -177,7 +269,409 @@ unsafe impl Pod for FeeEncryption {}
 
 #[derive(Clone, Copy)]
 #[repr(transparent)]
-pub struct FeeParameters(pub [u8; 10]);
+pub struct FeeParameters(pub [u8; 24]);
 
 unsafe impl Zeroable for FeeParameters {}
 unsafe impl Pod for FeeParameters {}
+
+// The pod types are formatted as hex for `Debug` and as base64 for `Display`, and they are compared
+// in constant time since some of them hold secret-dependent data such as decryption handles.
+macro_rules! impl_fmt_and_eq {
+    ($($pod:ident),* $(,)?) => {$(
+        impl fmt::Debug for $pod {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(concat!(stringify!($pod), "("))?;
+                for byte in self.0.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                f.write_str(")")
+            }
+        }
+
+        impl fmt::Display for $pod {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", base64::encode(&self.0[..]))
+            }
+        }
+
+        impl ConstantTimeEq for $pod {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.0[..].ct_eq(&other.0[..])
+            }
+        }
+
+        impl PartialEq for $pod {
+            fn eq(&self, other: &Self) -> bool {
+                self.ct_eq(other).into()
+            }
+        }
+
+        impl Eq for $pod {}
+    )*};
+}
+
+impl_fmt_and_eq!(
+    Scalar,
+    CompressedRistretto,
+    ElGamalCiphertext,
+    ElGamalPubkey,
+    PedersenCommitment,
+    DecryptHandle,
+    EqualityProof,
+    CtxtCtxtEqualityProof,
+    ValidityProof,
+    GroupedCiphertext2HandlesValidityProof,
+    GroupedCiphertext3HandlesValidityProof,
+    AggregatedValidityProof,
+    ZeroBalanceProof,
+    PubkeyValidityProof,
+    FeeSigmaProof,
+    RangeProof64,
+    RangeProof128,
+    RangeProof256,
+    RangeProof512,
+    RangeProofPlus64,
+    RangeProofPlus128,
+    RangeProofPlus256,
+    AeCiphertext,
+    TransferPubkeys,
+    TransferWithFeePubkeys,
+    TransferAmountEncryption,
+    MintPubkeys,
+    MintAmountEncryption,
+    BurnPubkeys,
+    BurnAmountEncryption,
+    AmountSplit,
+    FeeEncryption,
+    FeeParameters,
+);
+
+// Every pod type wraps a byte array of `LEN` bytes. The slice conversions copy the bytes out of the
+// slice, so parsing instruction data with them does not depend on the alignment of the buffer.
+macro_rules! impl_len_and_try_from_slice {
+    ($($pod:ident: $len:expr),* $(,)?) => {$(
+        impl $pod {
+            pub const LEN: usize = $len;
+        }
+
+        const_assert_eq!(core::mem::size_of::<$pod>(), $pod::LEN);
+
+        impl TryFrom<&[u8]> for $pod {
+            type Error = TryFromSliceError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                bytes.try_into().map(Self)
+            }
+        }
+    )*};
+}
+
+impl_len_and_try_from_slice!(
+    Scalar: 32,
+    CompressedRistretto: 32,
+    ElGamalCiphertext: 64,
+    ElGamalPubkey: 32,
+    PedersenCommitment: 32,
+    DecryptHandle: 32,
+    EqualityProof: 192,
+    CtxtCtxtEqualityProof: 224,
+    ValidityProof: 160,
+    GroupedCiphertext2HandlesValidityProof: 160,
+    GroupedCiphertext3HandlesValidityProof: 192,
+    AggregatedValidityProof: 32 * (MAX_AUDITORS + 4),
+    ZeroBalanceProof: 96,
+    PubkeyValidityProof: 64,
+    FeeSigmaProof: 256,
+    RangeProof64: 672,
+    RangeProof128: 736,
+    RangeProof256: 800,
+    RangeProof512: 864,
+    RangeProofPlus64: 576,
+    RangeProofPlus128: 640,
+    RangeProofPlus256: 704,
+    AeCiphertext: 36,
+    TransferPubkeys: 32 * (MAX_AUDITORS + 2),
+    TransferWithFeePubkeys: 32 * (MAX_AUDITORS + 3),
+    TransferAmountEncryption: 32 * (MAX_AUDITORS + 3),
+    MintPubkeys: 32 * (MAX_AUDITORS + 1),
+    MintAmountEncryption: 32 * (MAX_AUDITORS + 2),
+    BurnPubkeys: 32 * (MAX_AUDITORS + 1),
+    BurnAmountEncryption: 32 * (MAX_AUDITORS + 2),
+    AmountSplit: 1,
+    FeeEncryption: 96,
+    FeeParameters: 24,
+);
+
+#[cfg(all(feature = "serde-traits", not(target_arch = "bpf")))]
+mod serde_traits {
+    use {
+        super::*,
+        serde::{
+            de::{self, SeqAccess, Visitor},
+            Deserialize, Deserializer, Serialize, Serializer,
+        },
+        std::convert::TryInto,
+    };
+
+    /// Visitor for a fixed-size byte array that is encoded either as a base64 string, as bytes,
+    /// or as a sequence of bytes
+    struct BytesVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for BytesVisitor<N> {
+        type Value = [u8; N];
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} bytes or a base64 string encoding them", N)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let bytes = base64::decode(v).map_err(E::custom)?;
+            self.visit_bytes(&bytes)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = [0u8; N];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+            Ok(bytes)
+        }
+    }
+
+    fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&base64::encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    fn deserialize_bytes<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor::<N>)
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor::<N>)
+        }
+    }
+
+    macro_rules! impl_serde {
+        ($($pod:ident),* $(,)?) => {$(
+            impl Serialize for $pod {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_bytes(&self.0, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $pod {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_bytes(deserializer).map(Self)
+                }
+            }
+        )*};
+    }
+
+    impl_serde!(
+        Scalar,
+        CompressedRistretto,
+        ElGamalCiphertext,
+        ElGamalPubkey,
+        PedersenCommitment,
+        DecryptHandle,
+        EqualityProof,
+        CtxtCtxtEqualityProof,
+        ValidityProof,
+        GroupedCiphertext2HandlesValidityProof,
+        GroupedCiphertext3HandlesValidityProof,
+        AggregatedValidityProof,
+        ZeroBalanceProof,
+        PubkeyValidityProof,
+        FeeSigmaProof,
+        RangeProof64,
+        RangeProof128,
+        RangeProof256,
+        RangeProof512,
+        RangeProofPlus64,
+        RangeProofPlus128,
+        RangeProofPlus256,
+        AeCiphertext,
+        TransferPubkeys,
+        TransferWithFeePubkeys,
+        TransferAmountEncryption,
+        MintPubkeys,
+        MintAmountEncryption,
+        BurnPubkeys,
+        BurnAmountEncryption,
+        AmountSplit,
+        FeeEncryption,
+        FeeParameters,
+    );
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_pod_serde() {
+            let ciphertext = ElGamalCiphertext([7u8; 64]);
+
+            let json = serde_json::to_string(&ciphertext).unwrap();
+            assert_eq!(json, format!("\"{}\"", base64::encode([7u8; 64])));
+            assert_eq!(
+                serde_json::from_str::<ElGamalCiphertext>(&json).unwrap(),
+                ciphertext
+            );
+
+            let bytes = bincode::serialize(&ciphertext).unwrap();
+            assert_eq!(
+                bincode::deserialize::<ElGamalCiphertext>(&bytes).unwrap(),
+                ciphertext
+            );
+
+            // the encoded length must match the pod type
+            let json = format!("\"{}\"", base64::encode([7u8; 32]));
+            assert!(serde_json::from_str::<ElGamalCiphertext>(&json).is_err());
+        }
+    }
+}
+
+#[cfg(feature = "borsh-traits")]
+mod borsh_traits {
+    use {
+        super::*,
+        borsh::{BorshDeserialize, BorshSerialize},
+        std::io,
+    };
+
+    // Every pod type is a fixed-size byte array, so the borsh encoding is just the raw bytes with
+    // no length prefix. This keeps the borsh layout of the proof data identical to its pod layout.
+    macro_rules! impl_borsh {
+        ($($pod:ident),* $(,)?) => {$(
+            impl BorshSerialize for $pod {
+                fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+                    writer.write_all(&self.0)
+                }
+            }
+
+            impl BorshDeserialize for $pod {
+                fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+                    let mut pod = Self::zeroed();
+                    if buf.len() < pod.0.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "Unexpected length of input",
+                        ));
+                    }
+                    let (bytes, rest) = buf.split_at(pod.0.len());
+                    pod.0.copy_from_slice(bytes);
+                    *buf = rest;
+                    Ok(pod)
+                }
+            }
+        )*};
+    }
+
+    impl_borsh!(
+        Scalar,
+        CompressedRistretto,
+        ElGamalCiphertext,
+        ElGamalPubkey,
+        PedersenCommitment,
+        DecryptHandle,
+        EqualityProof,
+        CtxtCtxtEqualityProof,
+        ValidityProof,
+        GroupedCiphertext2HandlesValidityProof,
+        GroupedCiphertext3HandlesValidityProof,
+        AggregatedValidityProof,
+        ZeroBalanceProof,
+        PubkeyValidityProof,
+        FeeSigmaProof,
+        RangeProof64,
+        RangeProof128,
+        RangeProof256,
+        RangeProof512,
+        RangeProofPlus64,
+        RangeProofPlus128,
+        RangeProofPlus256,
+        AeCiphertext,
+        TransferPubkeys,
+        TransferWithFeePubkeys,
+        TransferAmountEncryption,
+        MintPubkeys,
+        MintAmountEncryption,
+        BurnPubkeys,
+        BurnAmountEncryption,
+        AmountSplit,
+        FeeEncryption,
+        FeeParameters,
+    );
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_pod_borsh() {
+            let ciphertext = ElGamalCiphertext([7u8; 64]);
+
+            let bytes = ciphertext.try_to_vec().unwrap();
+            assert_eq!(bytes, bytemuck::bytes_of(&ciphertext));
+            assert_eq!(
+                ElGamalCiphertext::try_from_slice(&bytes).unwrap(),
+                ciphertext
+            );
+
+            // truncated and trailing input are both rejected
+            assert!(ElGamalCiphertext::try_from_slice(&bytes[..63]).is_err());
+            assert!(ElGamalCiphertext::try_from_slice(&[bytes, vec![0]].concat()).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pod_fmt_and_eq() {
+        let commitment = PedersenCommitment([0xab; 32]);
+
+        assert_eq!(
+            format!("{:?}", commitment),
+            format!("PedersenCommitment({})", "ab".repeat(32))
+        );
+        assert_eq!(format!("{}", commitment), base64::encode([0xab; 32]));
+
+        assert_eq!(commitment, PedersenCommitment([0xab; 32]));
+        assert_ne!(commitment, PedersenCommitment::default());
+        assert!(bool::from(
+            commitment.ct_eq(&PedersenCommitment([0xab; 32]))
+        ));
+    }
+
+    #[test]
+    fn test_pod_try_from_slice() {
+        // an odd offset into the buffer, as with unaligned instruction data
+        let buffer = [0xcd; PedersenCommitment::LEN + 1];
+        let commitment = PedersenCommitment::try_from(&buffer[1..]).unwrap();
+        assert_eq!(commitment, PedersenCommitment([0xcd; 32]));
+
+        assert!(PedersenCommitment::try_from(&buffer[..]).is_err());
+        assert!(FeeParameters::try_from(&[][..]).is_err());
+        assert_eq!(
+            TransferPubkeys::try_from(&[0; TransferPubkeys::LEN][..]).unwrap(),
+            TransferPubkeys::zeroed()
+        );
+    }
+}
@@ -1,10 +1,20 @@
+#[cfg(not(target_arch = "bpf"))]
+use crate::errors::ProofError;
 ///! Instructions provided by the ZkToken Proof program
 pub use crate::instruction::*;
 use {
-    bytemuck::{bytes_of, Pod},
+    bytemuck::{bytes_of, bytes_of_mut, Pod, Zeroable},
     num_derive::{FromPrimitive, ToPrimitive},
     num_traits::{FromPrimitive, ToPrimitive},
-    solana_program::instruction::Instruction,
+    solana_program::{
+        account_info::AccountInfo,
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::instructions::{get_instruction_relative, load_instruction_at_checked},
+    },
+    static_assertions::const_assert_eq,
+    std::mem::{align_of, size_of},
 };
 
 #[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
@@ -49,9 +59,340 @@ pub enum ProofInstruction {
     ///   `TransferWithFeeData`
     ///
     VerifyTransferWithFee,
+
+    /// Verify a `WithdrawWithheldTokensData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `WithdrawWithheldTokensData`
+    ///
+    VerifyWithdrawWithheldTokens,
+
+    /// Verify a `MintData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `MintData`
+    ///
+    VerifyMint,
+
+    /// Verify a `BurnData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `BurnData`
+    ///
+    VerifyBurn,
+
+    /// Verify a `RotateAuditorData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `RotateAuditorData`
+    ///
+    VerifyRotateAuditor,
+
+    /// Verify a `TransferBatchData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `TransferBatchData`
+    ///
+    VerifyTransferBatch,
+
+    /// Write a chunk of proof data to a proof context-state account
+    ///
+    /// Proof data that is too large to fit in a single transaction is split into chunks with
+    /// `ProofData::split` and written to a context-state account that is owned by the program and
+    /// allocated with `proof_context_state_len` bytes. The first chunk that is written to an
+    /// uninitialized account sets its authority and proof type. Writing a chunk clears the
+    /// verified flag of the account.
+    ///
+    /// Accounts expected by this instruction:
+    ///   0. `[writable]` The proof context-state account
+    ///   1. `[signer]` The context-state authority
+    ///
+    /// Data expected by this instruction:
+    ///   `ProofChunkHeader` followed by the bytes of the chunk
+    ///
+    WriteProofChunk,
+
+    /// Verify the proof data in a proof context-state account and mark it as verified
+    ///
+    /// Accounts expected by this instruction:
+    ///   0. `[writable]` The proof context-state account
+    ///
+    /// Data expected by this instruction:
+    ///   None
+    ///
+    VerifyProofContext,
+
+    /// Close a proof context-state account and reclaim its lamports
+    ///
+    /// Accounts expected by this instruction:
+    ///   0. `[writable]` The proof context-state account
+    ///   1. `[writable]` The destination account for the lamports
+    ///   2. `[signer]` The context-state authority
+    ///
+    /// Data expected by this instruction:
+    ///   None
+    ///
+    CloseProofContext,
+
+    /// Verify a `PubkeyValidityData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `PubkeyValidityData`
+    ///
+    VerifyPubkeyValidity,
+
+    /// Verify a `CiphertextCiphertextEqualityData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `CiphertextCiphertextEqualityData`
+    ///
+    VerifyCiphertextCiphertextEquality,
+
+    /// Verify a `CiphertextCommitmentEqualityData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `CiphertextCommitmentEqualityData`
+    ///
+    VerifyCiphertextCommitmentEquality,
+
+    /// Verify a `FeeParametersUpdateData` struct
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `FeeParametersUpdateData`
+    ///
+    VerifyFeeParametersUpdate,
+}
+
+/// Header of the instruction data of `ProofInstruction::WriteProofChunk`
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProofChunkHeader {
+    /// The `ProofInstruction` that verifies the proof data
+    pub proof_type: u8,
+
+    /// The offset of the chunk in the proof data, as a little-endian `u32`
+    pub offset: [u8; 4],
+}
+
+const_assert_eq!(align_of::<ProofChunkHeader>(), 1);
+
+/// A chunk of proof data at the given offset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProofDataChunk<'a> {
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+/// Proof data that is verified by one of the `ProofInstruction`s
+pub trait ProofData: Pod {
+    /// The instruction that verifies the proof data
+    const PROOF_TYPE: ProofInstruction;
+
+    /// Splits the proof data into chunks of at most `max_chunk_len` bytes so that it can be
+    /// written to a proof context-state account over several instructions
+    ///
+    /// Returns no chunks if `max_chunk_len` is zero.
+    fn split(&self, max_chunk_len: usize) -> Vec<ProofDataChunk<'_>> {
+        if max_chunk_len == 0 {
+            return vec![];
+        }
+        bytes_of(self)
+            .chunks(max_chunk_len)
+            .enumerate()
+            .map(|(i, data)| ProofDataChunk {
+                offset: i * max_chunk_len,
+                data,
+            })
+            .collect()
+    }
+
+    /// Copies the proof data out of `bytes`, which need not be aligned
+    ///
+    /// Returns `None` if `bytes` is not exactly the size of the proof data.
+    fn from_bytes_unaligned(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != size_of::<Self>() {
+            return None;
+        }
+        let mut proof_data = Self::zeroed();
+        bytes_of_mut(&mut proof_data).copy_from_slice(bytes);
+        Some(proof_data)
+    }
+}
+
+macro_rules! impl_proof_data {
+    ($($data:ty => $proof_type:ident),* $(,)?) => {
+        $(
+            impl ProofData for $data {
+                const PROOF_TYPE: ProofInstruction = ProofInstruction::$proof_type;
+            }
+
+            // instruction data is not aligned on-chain, so the proof data must be castable from
+            // a byte slice at any address
+            const_assert_eq!(align_of::<$data>(), 1);
+        )*
+
+        impl ProofInstruction {
+            /// Returns the size of the proof data that is verified by the instruction, or `None`
+            /// if the instruction does not verify proof data
+            pub fn proof_data_len(&self) -> Option<usize> {
+                match self {
+                    $(Self::$proof_type => Some(size_of::<$data>()),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_proof_data!(
+    CloseAccountData => VerifyCloseAccount,
+    WithdrawData => VerifyWithdraw,
+    TransferData => VerifyTransfer,
+    TransferWithFeeData => VerifyTransferWithFee,
+    WithdrawWithheldTokensData => VerifyWithdrawWithheldTokens,
+    MintData => VerifyMint,
+    BurnData => VerifyBurn,
+    RotateAuditorData => VerifyRotateAuditor,
+    TransferBatchData => VerifyTransferBatch,
+    PubkeyValidityData => VerifyPubkeyValidity,
+    CiphertextCiphertextEqualityData => VerifyCiphertextCiphertextEquality,
+    CiphertextCommitmentEqualityData => VerifyCiphertextCommitmentEquality,
+    FeeParametersUpdateData => VerifyFeeParametersUpdate,
+);
+
+/// Proof data that consists of the public inputs of a proof, its context, and the proof itself
+///
+/// Programs that consume a verified proof only need to read its context, which carries the
+/// ciphertexts, pubkeys, and other public inputs that the proof certifies.
+pub trait ZkProofData: ProofData {
+    /// The public inputs of the proof
+    type Context: Pod;
+
+    /// Returns the public inputs of the proof
+    fn context(&self) -> &Self::Context;
+}
+
+macro_rules! impl_zk_proof_data {
+    ($($data:ty => $context:ty),* $(,)?) => {
+        $(
+            impl ZkProofData for $data {
+                type Context = $context;
+
+                fn context(&self) -> &Self::Context {
+                    &self.context
+                }
+            }
+        )*
+    };
+}
+
+impl_zk_proof_data!(
+    CloseAccountData => CloseAccountProofContext,
+    WithdrawData => WithdrawProofContext,
+    TransferData => TransferProofContext,
+    TransferWithFeeData => TransferWithFeeProofContext,
+    WithdrawWithheldTokensData => WithdrawWithheldTokensProofContext,
+    MintData => MintProofContext,
+    BurnData => BurnProofContext,
+    RotateAuditorData => RotateAuditorProofContext,
+    PubkeyValidityData => PubkeyValidityProofContext,
+    CiphertextCiphertextEqualityData => CiphertextCiphertextEqualityProofContext,
+    CiphertextCommitmentEqualityData => CiphertextCommitmentEqualityProofContext,
+    FeeParametersUpdateData => FeeParametersUpdateProofContext,
+);
+
+/// Reassembles proof data from the chunks produced by `ProofData::split`
+///
+/// The chunks must be pushed in order.
+#[cfg(not(target_arch = "bpf"))]
+pub struct ProofDataAccumulator<T: ProofData> {
+    proof_data: T,
+    len: usize,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl<T: ProofData> Default for ProofDataAccumulator<T> {
+    fn default() -> Self {
+        Self {
+            proof_data: T::zeroed(),
+            len: 0,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl<T: ProofData> ProofDataAccumulator<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the next chunk of the proof data
+    pub fn push(&mut self, chunk: &ProofDataChunk) -> Result<(), ProofError> {
+        let end = self.len + chunk.data.len();
+        if chunk.offset != self.len || end > size_of::<T>() {
+            return Err(ProofError::InvalidProofDataChunk);
+        }
+
+        bytes_of_mut(&mut self.proof_data)[self.len..end].copy_from_slice(chunk.data);
+        self.len = end;
+        Ok(())
+    }
+
+    /// Returns whether all of the proof data has been pushed
+    pub fn is_complete(&self) -> bool {
+        self.len == size_of::<T>()
+    }
+
+    /// Returns the reassembled proof data, or `None` if some of its chunks are missing
+    pub fn finish(self) -> Option<T> {
+        if self.is_complete() {
+            Some(self.proof_data)
+        } else {
+            None
+        }
+    }
 }
 
+/// Number of compute units that the ZkToken Proof program consumes to verify proof data
+pub const VERIFY_PROOF_COMPUTE_UNITS: u64 = 100_000;
+
 impl ProofInstruction {
+    /// Returns the number of compute units that the program consumes to process the instruction,
+    /// which should be accounted for when requesting a compute budget for the transaction
+    pub fn compute_units(&self) -> u64 {
+        match self {
+            Self::WriteProofChunk | Self::CloseProofContext => 0,
+            _ => VERIFY_PROOF_COMPUTE_UNITS,
+        }
+    }
+
     pub fn encode<T: Pod>(&self, proof: &T) -> Instruction {
         let mut data = vec![ToPrimitive::to_u8(self).unwrap()];
         data.extend_from_slice(bytes_of(proof));
@@ -73,6 +414,20 @@ impl ProofInstruction {
             bytemuck::try_from_bytes(&input[1..]).ok()
         }
     }
+
+    pub fn decode_chunk(input: &[u8]) -> Option<(&ProofChunkHeader, &[u8])> {
+        if input.len() < 1 + size_of::<ProofChunkHeader>() {
+            None
+        } else {
+            let (header, chunk) = input[1..].split_at(size_of::<ProofChunkHeader>());
+            Some((bytemuck::try_from_bytes(header).ok()?, chunk))
+        }
+    }
+}
+
+/// Creates the instruction that verifies `proof_data`, whatever its proof type
+pub fn verify_proof<T: ProofData>(proof_data: &T) -> Instruction {
+    T::PROOF_TYPE.encode(proof_data)
 }
 
 pub fn verify_close_account(proof_data: &CloseAccountData) -> Instruction {
@@ -90,3 +445,247 @@ pub fn verify_transfer(proof_data: &TransferData) -> Instruction {
 pub fn verify_transfer_with_fee(proof_data: &TransferWithFeeData) -> Instruction {
     ProofInstruction::VerifyTransferWithFee.encode(proof_data)
 }
+
+pub fn verify_withdraw_withheld_tokens(proof_data: &WithdrawWithheldTokensData) -> Instruction {
+    ProofInstruction::VerifyWithdrawWithheldTokens.encode(proof_data)
+}
+
+pub fn verify_mint(proof_data: &MintData) -> Instruction {
+    ProofInstruction::VerifyMint.encode(proof_data)
+}
+
+pub fn verify_burn(proof_data: &BurnData) -> Instruction {
+    ProofInstruction::VerifyBurn.encode(proof_data)
+}
+
+pub fn verify_rotate_auditor(proof_data: &RotateAuditorData) -> Instruction {
+    ProofInstruction::VerifyRotateAuditor.encode(proof_data)
+}
+
+pub fn verify_transfer_batch(proof_data: &TransferBatchData) -> Instruction {
+    ProofInstruction::VerifyTransferBatch.encode(proof_data)
+}
+
+pub fn verify_pubkey_validity(proof_data: &PubkeyValidityData) -> Instruction {
+    ProofInstruction::VerifyPubkeyValidity.encode(proof_data)
+}
+
+pub fn verify_ciphertext_ciphertext_equality(
+    proof_data: &CiphertextCiphertextEqualityData,
+) -> Instruction {
+    ProofInstruction::VerifyCiphertextCiphertextEquality.encode(proof_data)
+}
+
+pub fn verify_ciphertext_commitment_equality(
+    proof_data: &CiphertextCommitmentEqualityData,
+) -> Instruction {
+    ProofInstruction::VerifyCiphertextCommitmentEquality.encode(proof_data)
+}
+
+pub fn verify_fee_parameters_update(proof_data: &FeeParametersUpdateData) -> Instruction {
+    ProofInstruction::VerifyFeeParametersUpdate.encode(proof_data)
+}
+
+pub fn write_proof_chunk(
+    context_state_account: &Pubkey,
+    context_state_authority: &Pubkey,
+    proof_type: ProofInstruction,
+    chunk: &ProofDataChunk,
+) -> Instruction {
+    let header = ProofChunkHeader {
+        proof_type: ToPrimitive::to_u8(&proof_type).unwrap(),
+        offset: (chunk.offset as u32).to_le_bytes(),
+    };
+
+    let mut data = vec![ToPrimitive::to_u8(&ProofInstruction::WriteProofChunk).unwrap()];
+    data.extend_from_slice(bytes_of(&header));
+    data.extend_from_slice(chunk.data);
+    Instruction {
+        program_id: crate::zk_token_proof_program::id(),
+        accounts: vec![
+            AccountMeta::new(*context_state_account, false),
+            AccountMeta::new_readonly(*context_state_authority, true),
+        ],
+        data,
+    }
+}
+
+/// Creates the `WriteProofChunk` instructions that write `proof_data` to a proof context-state
+/// account in chunks of at most `max_chunk_len` bytes
+pub fn write_proof_data<T: ProofData>(
+    context_state_account: &Pubkey,
+    context_state_authority: &Pubkey,
+    proof_data: &T,
+    max_chunk_len: usize,
+) -> Vec<Instruction> {
+    proof_data
+        .split(max_chunk_len)
+        .iter()
+        .map(|chunk| {
+            write_proof_chunk(
+                context_state_account,
+                context_state_authority,
+                T::PROOF_TYPE,
+                chunk,
+            )
+        })
+        .collect()
+}
+
+pub fn verify_proof_context(context_state_account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::zk_token_proof_program::id(),
+        accounts: vec![AccountMeta::new(*context_state_account, false)],
+        data: vec![ToPrimitive::to_u8(&ProofInstruction::VerifyProofContext).unwrap()],
+    }
+}
+
+pub fn close_proof_context(
+    context_state_account: &Pubkey,
+    destination_account: &Pubkey,
+    context_state_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::zk_token_proof_program::id(),
+        accounts: vec![
+            AccountMeta::new(*context_state_account, false),
+            AccountMeta::new(*destination_account, false),
+            AccountMeta::new_readonly(*context_state_authority, true),
+        ],
+        data: vec![ToPrimitive::to_u8(&ProofInstruction::CloseProofContext).unwrap()],
+    }
+}
+
+/// Returns the proof data of `instruction` if it is the ZkToken Proof program instruction that
+/// verifies proof data of type `T`
+pub fn decode_proof_instruction<T: ProofData>(instruction: &Instruction) -> Option<T> {
+    if instruction.program_id != crate::zk_token_proof_program::id()
+        || instruction.data.is_empty()
+        || ProofInstruction::decode_type(&instruction.data) != Some(T::PROOF_TYPE)
+    {
+        return None;
+    }
+
+    T::from_bytes_unaligned(&instruction.data[1..])
+}
+
+/// Loads the instruction at `index` of the currently executing transaction from the instructions
+/// sysvar and returns its proof data, if it is the ZkToken Proof program instruction that verifies
+/// proof data of type `T`
+///
+/// The ZkToken Proof program fails the whole transaction if the proof does not verify, so a program
+/// that finds the proof instruction in its own transaction can rely on the returned proof data.
+pub fn get_zk_proof_from_instruction<T: ProofData>(
+    sysvar_instructions: &AccountInfo,
+    index: usize,
+) -> Result<T, ProgramError> {
+    let instruction = load_instruction_at_checked(index, sysvar_instructions)?;
+    if instruction.program_id != crate::zk_token_proof_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    decode_proof_instruction(&instruction).ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Same as `get_zk_proof_from_instruction`, but the instruction is located relative to the
+/// currently executing instruction
+pub fn get_zk_proof_from_relative_instruction<T: ProofData>(
+    sysvar_instructions: &AccountInfo,
+    index_relative_to_current: i64,
+) -> Result<T, ProgramError> {
+    let instruction = get_instruction_relative(index_relative_to_current, sysvar_instructions)?;
+    if instruction.program_id != crate::zk_token_proof_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    decode_proof_instruction(&instruction).ok_or(ProgramError::InvalidInstructionData)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::encryption::elgamal::ElGamalKeypair};
+
+    #[test]
+    fn test_split_and_accumulate_proof_data() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = keypair.public.encrypt(0_u64);
+        let proof_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
+
+        let chunks = proof_data.split(100);
+        assert_eq!(chunks.len(), (size_of::<CloseAccountData>() + 99) / 100);
+        assert!(chunks.iter().all(|chunk| chunk.data.len() <= 100));
+
+        // chunks must be pushed in order
+        let mut accumulator = ProofDataAccumulator::<CloseAccountData>::new();
+        assert!(accumulator.push(&chunks[1]).is_err());
+
+        for chunk in &chunks {
+            assert!(!accumulator.is_complete());
+            accumulator.push(chunk).unwrap();
+        }
+        assert!(accumulator.push(&chunks[0]).is_err());
+
+        let reassembled = accumulator.finish().unwrap();
+        assert_eq!(bytes_of(&reassembled), bytes_of(&proof_data));
+        assert!(reassembled.verify().is_ok());
+
+        // the write instructions carry the chunks in order
+        let context_state = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let instructions = write_proof_data(&context_state, &authority, &proof_data, 100);
+        assert_eq!(instructions.len(), chunks.len());
+
+        for (instruction, chunk) in instructions.iter().zip(chunks.iter()) {
+            let (header, data) = ProofInstruction::decode_chunk(&instruction.data).unwrap();
+            assert_eq!(
+                header.proof_type,
+                ToPrimitive::to_u8(&ProofInstruction::VerifyCloseAccount).unwrap()
+            );
+            assert_eq!(u32::from_le_bytes(header.offset) as usize, chunk.offset);
+            assert_eq!(data, chunk.data);
+        }
+
+        // the proof data cannot be split into empty chunks
+        assert!(proof_data.split(0).is_empty());
+        assert!(write_proof_data(&context_state, &authority, &proof_data, 0).is_empty());
+    }
+
+    #[test]
+    fn test_decode_proof_instruction() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = keypair.public.encrypt(0_u64);
+        let proof_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
+
+        let instruction = verify_close_account(&proof_data);
+        assert_eq!(instruction, verify_proof(&proof_data));
+        let decoded = decode_proof_instruction::<CloseAccountData>(&instruction).unwrap();
+        assert_eq!(bytes_of(&decoded), bytes_of(&proof_data));
+
+        // the instruction must verify proof data of the requested type
+        assert!(decode_proof_instruction::<WithdrawData>(&instruction).is_none());
+
+        // the instruction must be addressed to the ZkToken Proof program
+        let mut instruction = instruction;
+        instruction.program_id = Pubkey::new_unique();
+        assert!(decode_proof_instruction::<CloseAccountData>(&instruction).is_none());
+    }
+
+    #[test]
+    fn test_proof_data_from_bytes_unaligned() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = keypair.public.encrypt(0_u64);
+        let proof_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
+
+        // place the proof data at an odd offset, as instruction data may be on-chain
+        let mut buffer = vec![0_u8; size_of::<CloseAccountData>() + 1];
+        buffer[1..].copy_from_slice(bytes_of(&proof_data));
+
+        let decoded = CloseAccountData::from_bytes_unaligned(&buffer[1..]).unwrap();
+        assert_eq!(bytes_of(&decoded), bytes_of(&proof_data));
+        assert!(decoded.verify().is_ok());
+
+        let decoded = ProofInstruction::decode_data::<CloseAccountData>(&buffer).unwrap();
+        assert_eq!(bytes_of(decoded), bytes_of(&proof_data));
+
+        assert!(CloseAccountData::from_bytes_unaligned(&buffer).is_none());
+        assert!(CloseAccountData::from_bytes_unaligned(&buffer[2..]).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+///! State of the accounts owned by the ZkToken Proof program
+use {
+    crate::zk_token_proof_instruction::{ProofData, ZkProofData},
+    bytemuck::{Pod, Zeroable},
+    num_traits::ToPrimitive,
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+    static_assertions::const_assert_eq,
+    std::{
+        cell::Ref,
+        mem::{align_of, size_of},
+    },
+};
+
+/// Header of a proof context-state account
+///
+/// Proof data that does not fit in a single transaction is written to a context-state account in
+/// chunks with `ProofInstruction::WriteProofChunk` and then verified in place with
+/// `ProofInstruction::VerifyProofContext`. The header is followed by the proof data itself, so
+/// the account must be allocated with `proof_context_state_len::<T>()` bytes for proof data of
+/// type `T`.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ProofContextStateHeader {
+    /// The authority that writes the proof data and closes the account; the default pubkey marks
+    /// an uninitialized account
+    pub context_state_authority: Pubkey,
+
+    /// The `ProofInstruction` that verifies the proof data in the account
+    pub proof_type: u8,
+
+    /// Whether the proof data in the account has been verified since it was last written to
+    pub verified: u8,
+}
+
+const_assert_eq!(align_of::<ProofContextStateHeader>(), 1);
+
+impl ProofContextStateHeader {
+    pub const LEN: usize = size_of::<Self>();
+}
+
+/// Returns the size of a proof context-state account that holds proof data of type `T`
+pub fn proof_context_state_len<T: ProofData>() -> usize {
+    ProofContextStateHeader::LEN + size_of::<T>()
+}
+
+/// Returns the proof data of type `T` in a proof context-state account, if the account is owned
+/// by the ZkToken Proof program and its proof data has been verified by
+/// `ProofInstruction::VerifyProofContext`
+///
+/// Anyone can create an account whose data looks like a verified context-state account, so the
+/// proof data is only trusted if the ZkToken Proof program owns the account.
+pub fn verified_proof_data<'a, T: ProofData>(
+    account_info: &'a AccountInfo,
+) -> Result<Ref<'a, T>, ProgramError> {
+    if account_info.owner != &crate::zk_token_proof_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let data = account_info.try_borrow_data()?;
+    if data.len() != proof_context_state_len::<T>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (header, proof_data) = data.split_at(ProofContextStateHeader::LEN);
+    let header: &ProofContextStateHeader =
+        bytemuck::try_from_bytes(header).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if header.context_state_authority == Pubkey::default()
+        || header.verified == 0
+        || Some(header.proof_type) != T::PROOF_TYPE.to_u8()
+        || bytemuck::try_from_bytes::<T>(proof_data).is_err()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Ref::map(data, |data| {
+        bytemuck::from_bytes(&data[ProofContextStateHeader::LEN..])
+    }))
+}
+
+/// Returns the context of the proof data of type `T` in a proof context-state account, under the
+/// same conditions as `verified_proof_data`
+pub fn verified_proof_context<'a, T: ZkProofData>(
+    account_info: &'a AccountInfo,
+) -> Result<Ref<'a, T::Context>, ProgramError> {
+    verified_proof_data::<T>(account_info)
+        .map(|proof_data| Ref::map(proof_data, |proof_data| proof_data.context()))
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            encryption::elgamal::ElGamalKeypair, instruction::CloseAccountData,
+            zk_token_proof_instruction::ProofInstruction,
+        },
+        bytemuck::bytes_of,
+    };
+
+    #[test]
+    fn test_verified_proof_data() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = keypair.public.encrypt(0_u64);
+        let proof_data = CloseAccountData::new(&keypair, &ciphertext).unwrap();
+
+        let header = ProofContextStateHeader {
+            context_state_authority: Pubkey::new_unique(),
+            proof_type: ProofInstruction::VerifyCloseAccount.to_u8().unwrap(),
+            verified: 1,
+        };
+        let mut data = bytes_of(&header).to_vec();
+        data.extend_from_slice(bytes_of(&proof_data));
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let owner = crate::zk_token_proof_program::id();
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let verified = verified_proof_data::<CloseAccountData>(&account_info).unwrap();
+        assert_eq!(bytes_of(&*verified), bytes_of(&proof_data));
+        drop(verified);
+
+        // a forged account with the same data is rejected unless the proof program owns it
+        let forged_owner = Pubkey::new_unique();
+        let mut forged_lamports = 0;
+        let mut forged_data = account_info.data.borrow().to_vec();
+        let forged_account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut forged_lamports,
+            &mut forged_data,
+            &forged_owner,
+            false,
+            0,
+        );
+        assert_eq!(
+            verified_proof_data::<CloseAccountData>(&forged_account_info).err(),
+            Some(ProgramError::IncorrectProgramId)
+        );
+
+        // proof data that has not been verified is rejected
+        account_info.data.borrow_mut()[size_of::<Pubkey>() + 1] = 0;
+        assert_eq!(
+            verified_proof_data::<CloseAccountData>(&account_info).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+}